@@ -23,7 +23,17 @@ export default class NewTokenController extends Controller {
   @tracked scopesInvalid;
   @tracked crateScopes;
 
-  ENDPOINT_SCOPES = ['change-owners', 'publish-new', 'publish-update', 'trusted-publishing', 'yank'];
+  ENDPOINT_SCOPES = [
+    'change-owners',
+    'delete',
+    'manage-trustpub',
+    'publish-new',
+    'publish-release',
+    'publish-update',
+    'read-private',
+    'update-metadata',
+    'yank',
+  ];
 
   scopeDescription = scopeDescription;
 
@@ -1,8 +1,13 @@
 const DESCRIPTIONS = {
   'change-owners': 'Invite new crate owners or remove existing ones',
+  delete: 'Delete crates',
+  'manage-trustpub': 'Manage trusted publishing configurations',
   'publish-new': 'Publish new crates',
+  'publish-release': 'Release and download staged crate versions',
   'publish-update': 'Publish new versions of existing crates',
+  'read-private': 'Read private information like owner invitations and audit logs',
   'trusted-publishing': 'Manage trusted publishing configurations',
+  'update-metadata': 'Update crate metadata like deprecations and webhooks',
   yank: 'Yank and unyank crate versions',
 };
 
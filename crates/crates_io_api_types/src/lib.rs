@@ -8,8 +8,9 @@ pub use self::krate_publish::{EncodableCrateDependency, PublishMetadata};
 
 use chrono::{DateTime, Utc};
 use crates_io_database::models::{
-    ApiToken, Category, Crate, Dependency, DependencyKind, Keyword, Owner, ReverseDependency, Team,
-    TopVersions, TrustpubData, User, Version, VersionDownload, VersionOwnerAction,
+    ApiToken, Category, Crate, CrateAuditAction, CrateWebhook, CrateWebhookDelivery, Dependency,
+    DependencyKind, IndexChange, Keyword, Owner, ReverseDependency, Team, TopVersions,
+    TrustpubData, User, Version, VersionDownload, VersionOwnerAction, WebhookEvent,
 };
 use serde::{Deserialize, Serialize};
 
@@ -364,6 +365,26 @@ pub struct EncodableCrate {
 
     /// Whether this crate can only be published via Trusted Publishing.
     pub trustpub_only: bool,
+
+    /// The deprecation status of this crate, or `null` if the crate is not
+    /// deprecated.
+    pub deprecation: Option<EncodableCrateDeprecation>,
+}
+
+#[derive(Serialize, Deserialize, Debug, utoipa::ToSchema)]
+#[schema(as = CrateDeprecation)]
+pub struct EncodableCrateDeprecation {
+    /// The reason why the owners deprecated this crate.
+    #[schema(example = "This crate is no longer maintained.")]
+    pub reason: String,
+
+    /// The name of the crate that should be used instead, if any.
+    #[schema(example = "serde_json")]
+    pub successor: Option<String>,
+
+    /// The date and time this crate was deprecated.
+    #[schema(example = "2019-12-13T13:46:41Z")]
+    pub deprecated_at: DateTime<Utc>,
 }
 
 impl EncodableCrate {
@@ -390,6 +411,9 @@ impl EncodableCrate {
             documentation,
             repository,
             trustpub_only,
+            deprecated_at,
+            deprecation_reason,
+            deprecation_successor,
             ..
         } = krate;
         let versions_link = match versions {
@@ -409,6 +433,12 @@ impl EncodableCrate {
         }
         let yanked = yanked.unwrap_or_default();
 
+        let deprecation = deprecated_at.map(|deprecated_at| EncodableCrateDeprecation {
+            reason: deprecation_reason.unwrap_or_default(),
+            successor: deprecation_successor,
+            deprecated_at,
+        });
+
         let max_version = top_versions
             .and_then(|v| v.highest.as_ref())
             .map(|v| v.to_string())
@@ -456,6 +486,7 @@ impl EncodableCrate {
             description,
             repository,
             trustpub_only,
+            deprecation,
             links: EncodableCrateLinks {
                 version_downloads: format!("/api/v1/crates/{name}/downloads"),
                 versions: versions_link,
@@ -811,6 +842,198 @@ pub struct EncodableAuditAction {
     pub time: DateTime<Utc>,
 }
 
+#[derive(Serialize, Deserialize, Debug, utoipa::ToSchema)]
+#[schema(as = CrateAuditAction)]
+pub struct EncodableCrateAuditAction {
+    /// An opaque identifier for the audit log entry.
+    #[schema(example = 42)]
+    pub id: i32,
+
+    /// The action that was performed.
+    #[schema(example = "owner_invite")]
+    pub action: String,
+
+    /// The user who performed the action, or `null` if the action was
+    /// performed via Trusted Publishing or the user account has been deleted.
+    pub user: Option<EncodablePublicUser>,
+
+    /// The ID of the API token that was used to perform the action, or
+    /// `null` if the action was performed via the web interface.
+    #[schema(example = 1)]
+    pub api_token_id: Option<i32>,
+
+    /// Information about the trusted publisher that performed the action,
+    /// if any.
+    ///
+    /// The exact structure of this field depends on the `provider` field
+    /// inside it.
+    #[schema(value_type = Option<Object>)]
+    pub trustpub_data: Option<TrustpubData>,
+
+    /// Action-specific details, e.g. the login of the invited owner.
+    #[schema(value_type = Object, example = json!({ "owner": "octocat" }))]
+    pub details: serde_json::Value,
+
+    /// The date and time the action was performed.
+    #[schema(example = "2019-12-13T13:46:41Z")]
+    pub time: DateTime<Utc>,
+}
+
+impl EncodableCrateAuditAction {
+    pub fn from(action: CrateAuditAction, user: Option<User>) -> Self {
+        Self {
+            id: action.id,
+            action: action.action.into(),
+            user: user.map(User::into),
+            api_token_id: action.api_token_id,
+            trustpub_data: action.trustpub_data,
+            details: action.details,
+            time: action.time,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, utoipa::ToSchema)]
+#[schema(as = CrateWebhook)]
+pub struct EncodableCrateWebhook {
+    /// An opaque identifier for the webhook.
+    #[schema(example = 42)]
+    pub id: i32,
+
+    /// The URL that event payloads are sent to.
+    #[schema(example = "https://example.com/crates-io-webhook")]
+    pub url: String,
+
+    /// The events that the webhook is subscribed to.
+    pub events: Vec<WebhookEvent>,
+
+    /// Whether events are currently delivered to the webhook.
+    ///
+    /// Webhooks are disabled automatically after repeated delivery failures.
+    #[schema(example = true)]
+    pub enabled: bool,
+
+    /// The number of failed delivery attempts since the last successful
+    /// delivery.
+    #[schema(example = 0)]
+    pub consecutive_failures: i32,
+
+    /// The date and time the webhook was created.
+    #[schema(example = "2019-12-13T13:46:41Z")]
+    pub created_at: DateTime<Utc>,
+
+    /// The date and time the webhook was disabled, if it is disabled.
+    #[schema(example = "2019-12-13T13:46:41Z")]
+    pub disabled_at: Option<DateTime<Utc>>,
+}
+
+impl From<CrateWebhook> for EncodableCrateWebhook {
+    fn from(webhook: CrateWebhook) -> Self {
+        Self {
+            id: webhook.id,
+            url: webhook.url,
+            events: webhook.events,
+            enabled: webhook.disabled_at.is_none(),
+            consecutive_failures: webhook.consecutive_failures,
+            created_at: webhook.created_at,
+            disabled_at: webhook.disabled_at,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, utoipa::ToSchema)]
+#[schema(as = CrateWebhookDelivery)]
+pub struct EncodableCrateWebhookDelivery {
+    /// An opaque identifier for the delivery.
+    ///
+    /// This is also sent in the `X-Crates-Io-Delivery` header.
+    #[schema(example = 42)]
+    pub id: i64,
+
+    /// The event that was delivered.
+    pub event: WebhookEvent,
+
+    /// The JSON payload that was sent to the webhook.
+    #[schema(value_type = Object)]
+    pub payload: serde_json::Value,
+
+    /// The number of delivery attempts so far.
+    #[schema(example = 1)]
+    pub attempts: i32,
+
+    /// The HTTP status code of the response to the last delivery attempt,
+    /// if a response was received.
+    #[schema(example = 200)]
+    pub response_status: Option<i32>,
+
+    /// The error of the last failed delivery attempt, if any.
+    #[schema(example = "unexpected response status: 500 Internal Server Error")]
+    pub error: Option<String>,
+
+    /// The date and time the event occurred.
+    #[schema(example = "2019-12-13T13:46:41Z")]
+    pub created_at: DateTime<Utc>,
+
+    /// The date and time of the last delivery attempt, if any.
+    #[schema(example = "2019-12-13T13:46:41Z")]
+    pub last_attempt_at: Option<DateTime<Utc>>,
+
+    /// The date and time the event was successfully delivered, if it was.
+    #[schema(example = "2019-12-13T13:46:41Z")]
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+impl From<CrateWebhookDelivery> for EncodableCrateWebhookDelivery {
+    fn from(delivery: CrateWebhookDelivery) -> Self {
+        Self {
+            id: delivery.id,
+            event: delivery.event,
+            payload: delivery.payload,
+            attempts: delivery.attempts,
+            response_status: delivery.response_status,
+            error: delivery.error,
+            created_at: delivery.created_at,
+            last_attempt_at: delivery.last_attempt_at,
+            delivered_at: delivery.delivered_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, utoipa::ToSchema)]
+#[schema(as = IndexChange)]
+pub struct EncodableIndexChange {
+    /// The sequence number of the change.
+    ///
+    /// Sequence numbers are strictly increasing, but not necessarily
+    /// contiguous.
+    #[schema(example = 42)]
+    pub seq: i64,
+
+    /// The name of the crate whose index file was modified.
+    #[schema(example = "serde")]
+    pub name: String,
+
+    /// The type of the modification: `update` if the index file was
+    /// created or updated, or `delete` if it was removed.
+    #[schema(example = "update")]
+    pub reason: String,
+
+    /// The date and time the index file was modified.
+    #[schema(example = "2019-12-13T13:46:41Z")]
+    pub time: DateTime<Utc>,
+}
+
+impl From<IndexChange> for EncodableIndexChange {
+    fn from(change: IndexChange) -> Self {
+        Self {
+            seq: change.seq,
+            name: change.crate_name,
+            reason: change.reason.into(),
+            time: change.changed_at,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, utoipa::ToSchema)]
 #[schema(as = Version)]
 pub struct EncodableVersion {
@@ -1207,6 +1430,7 @@ mod tests {
             },
             exact_match: false,
             trustpub_only: false,
+            deprecation: None,
         };
         let json = serde_json::to_string(&crt).unwrap();
         assert_some!(json.as_str().find(r#""updated_at":"2017-01-06T14:23:11Z""#));
@@ -19,6 +19,10 @@ pub struct GitHubConfig {
     pub workflow_filename: String,
     #[schema(example = json!(null))]
     pub environment: Option<String>,
+    #[schema(example = "refs/tags/v*")]
+    pub ref_pattern: Option<String>,
+    #[schema(example = json!(null))]
+    pub expires_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -36,6 +40,10 @@ pub struct NewGitHubConfig {
     pub workflow_filename: String,
     #[schema(example = json!(null))]
     pub environment: Option<String>,
+    #[schema(example = "refs/tags/v*")]
+    pub ref_pattern: Option<String>,
+    #[schema(example = json!(null))]
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize, utoipa::ToSchema)]
@@ -56,6 +64,12 @@ pub struct GitLabConfig {
     pub workflow_filepath: String,
     #[schema(example = json!(null))]
     pub environment: Option<String>,
+    #[schema(example = "https://gitlab.com")]
+    pub issuer: String,
+    #[schema(example = "refs/tags/v*")]
+    pub ref_pattern: Option<String>,
+    #[schema(example = json!(null))]
+    pub expires_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -73,4 +87,43 @@ pub struct NewGitLabConfig {
     pub workflow_filepath: String,
     #[schema(example = json!(null))]
     pub environment: Option<String>,
+    #[schema(example = json!(null))]
+    pub issuer: Option<String>,
+    #[schema(example = "refs/tags/v*")]
+    pub ref_pattern: Option<String>,
+    #[schema(example = json!(null))]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[schema(as = ForgejoConfig)]
+pub struct ForgejoConfig {
+    #[schema(example = 42)]
+    pub id: i32,
+    #[schema(example = "regex")]
+    #[serde(rename = "crate")]
+    pub krate: String,
+    #[schema(example = "rust-lang")]
+    pub repository_owner: String,
+    #[schema(example = json!(null))]
+    pub repository_owner_id: Option<String>,
+    #[schema(example = "regex")]
+    pub repository_name: String,
+    #[schema(example = "release.yml")]
+    pub workflow_filename: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[schema(as = NewForgejoConfig)]
+pub struct NewForgejoConfig {
+    #[schema(example = "regex")]
+    #[serde(rename = "crate")]
+    pub krate: String,
+    #[schema(example = "rust-lang")]
+    pub repository_owner: String,
+    #[schema(example = "regex")]
+    pub repository_name: String,
+    #[schema(example = "release.yml")]
+    pub workflow_filename: String,
 }
@@ -1,4 +1,4 @@
-use crates_io_cdn_logs::{cloudfront, fastly};
+use crates_io_cdn_logs::{caddy, cloudfront, fastly, nginx};
 use criterion::{Criterion, criterion_group, criterion_main};
 use std::hint::black_box;
 use std::io::Cursor;
@@ -24,6 +24,24 @@ fn criterion_benchmark(c: &mut Criterion) {
         b.to_async(&rt)
             .iter(|| fastly::count_downloads(black_box(Cursor::new(bytes))));
     });
+
+    let bytes = include_bytes!("../test_data/nginx/combined.log");
+    c.bench_function("nginx", |b| {
+        b.to_async(&rt)
+            .iter(|| nginx::count_downloads(black_box(Cursor::new(bytes))));
+    });
+
+    let bytes = include_bytes!("../test_data/nginx/json.log");
+    c.bench_function("nginx_json", |b| {
+        b.to_async(&rt)
+            .iter(|| nginx::count_json_downloads(black_box(Cursor::new(bytes))));
+    });
+
+    let bytes = include_bytes!("../test_data/caddy/basic.log");
+    c.bench_function("caddy", |b| {
+        b.to_async(&rt)
+            .iter(|| caddy::count_downloads(black_box(Cursor::new(bytes))));
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -0,0 +1,154 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::borrow::Cow;
+
+/// This struct corresponds to a log line of the `http.log.access` logger of
+/// Caddy, using the default `json` encoder.
+///
+/// Only the fields that are needed for counting downloads are included. The
+/// string fields are using `Cow` to avoid unnecessary allocations.
+#[derive(Debug, Deserialize)]
+pub struct LogLine<'a> {
+    #[serde(borrow)]
+    pub ts: Timestamp<'a>,
+    #[serde(borrow)]
+    pub request: Request<'a>,
+    pub status: u16,
+}
+
+impl LogLine<'_> {
+    pub fn date_time(&self) -> Option<DateTime<Utc>> {
+        self.ts.date_time()
+    }
+
+    pub fn method(&self) -> &str {
+        &self.request.method
+    }
+
+    pub fn uri(&self) -> &str {
+        &self.request.uri
+    }
+
+    /// Returns the `client_ip` of the request, which takes trusted proxies
+    /// into account, or the `remote_ip` for older Caddy versions.
+    pub fn client_ip(&self) -> Option<&str> {
+        let request = &self.request;
+        request
+            .client_ip
+            .as_deref()
+            .or(request.remote_ip.as_deref())
+    }
+
+    pub fn user_agent(&self) -> Option<&str> {
+        self.request
+            .headers
+            .user_agent
+            .first()
+            .map(|ua| ua.as_ref())
+    }
+}
+
+/// The `ts` field is a floating point Unix timestamp by default, but the
+/// `time_format` option of the encoder can change it to a formatted string.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Timestamp<'a> {
+    Unix(f64),
+    #[serde(borrow)]
+    Formatted(Cow<'a, str>),
+}
+
+impl Timestamp<'_> {
+    fn date_time(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Timestamp::Unix(ts) => {
+                let secs = ts.floor();
+                let nanos = ((ts - secs) * 1e9) as u32;
+                DateTime::from_timestamp(secs as i64, nanos)
+            }
+            // Caddy's `iso8601` time format uses offsets without a colon,
+            // which is not valid RFC 3339.
+            Timestamp::Formatted(ts) => DateTime::parse_from_rfc3339(ts)
+                .or_else(|_| DateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S%.f%z"))
+                .ok()
+                .map(|date_time| date_time.to_utc()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Request<'a> {
+    #[serde(borrow, default)]
+    pub remote_ip: Option<Cow<'a, str>>,
+    #[serde(borrow, default)]
+    pub client_ip: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    pub method: Cow<'a, str>,
+    #[serde(borrow)]
+    pub uri: Cow<'a, str>,
+    #[serde(borrow, default)]
+    pub headers: Headers<'a>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Headers<'a> {
+    #[serde(borrow, default, rename = "User-Agent")]
+    pub user_agent: Vec<Cow<'a, str>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_none, assert_ok};
+    use insta::assert_debug_snapshot;
+
+    #[test]
+    fn test_parse() {
+        let input = r#"{"level":"info","ts":1705449200.4605572,"logger":"http.log.access.log0","msg":"handled request","request":{"remote_ip":"1.2.3.4","method":"GET","host":"static.example.com","uri":"/crates/foo/foo-1.0.0.crate","headers":{"User-Agent":["cargo/1.74.0 (ecb9851af 2023-10-18)"]}},"size":11355,"status":200}"#;
+        let output = assert_ok!(serde_json::from_str::<LogLine<'_>>(input));
+        assert_debug_snapshot!(output, @r#"
+        LogLine {
+            ts: Unix(
+                1705449200.4605572,
+            ),
+            request: Request {
+                remote_ip: Some(
+                    "1.2.3.4",
+                ),
+                client_ip: None,
+                method: "GET",
+                uri: "/crates/foo/foo-1.0.0.crate",
+                headers: Headers {
+                    user_agent: [
+                        "cargo/1.74.0 (ecb9851af 2023-10-18)",
+                    ],
+                },
+            },
+            status: 200,
+        }
+        "#);
+
+        assert_debug_snapshot!(output.date_time(), @"
+        Some(
+            2024-01-16T23:53:20.460557222Z,
+        )
+        ");
+        assert_eq!(output.client_ip(), Some("1.2.3.4"));
+        assert_eq!(output.method(), "GET");
+        assert_eq!(output.uri(), "/crates/foo/foo-1.0.0.crate");
+        assert_eq!(
+            output.user_agent(),
+            Some("cargo/1.74.0 (ecb9851af 2023-10-18)")
+        );
+
+        let input = r#"{"ts":"2024-01-17T08:01:44.123+0100","request":{"method":"GET","uri":"/"},"status":404}"#;
+        let output = assert_ok!(serde_json::from_str::<LogLine<'_>>(input));
+        assert_debug_snapshot!(output.date_time(), @"
+        Some(
+            2024-01-17T07:01:44.123Z,
+        )
+        ");
+        assert_none!(output.client_ip());
+        assert_none!(output.user_agent());
+    }
+}
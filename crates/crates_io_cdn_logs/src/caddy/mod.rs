@@ -0,0 +1,132 @@
+//! # Caddy access log parsing
+//!
+//! see <https://caddyserver.com/docs/caddyfile/directives/log>.
+
+mod json;
+
+use crate::paths::parse_path;
+use crate::user_agent::should_count_user_agent;
+use crate::{DownloadsMap, RequestInfo};
+use std::borrow::Cow;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+use tracing::{debug_span, instrument, warn};
+
+/// The prefix of the logger names that Caddy uses for access logs, e.g.
+/// `"logger":"http.log.access.log0"`.
+const ACCESS_LOGGER: &str = r#""logger":"http.log.access"#;
+
+/// Determines if the given log line was written by the Caddy access logger.
+pub(crate) fn is_access_log_line(line: &str) -> bool {
+    line.contains(ACCESS_LOGGER)
+}
+
+#[instrument(level = "debug", skip(reader))]
+pub async fn count_downloads(reader: impl AsyncBufRead + Unpin) -> anyhow::Result<DownloadsMap> {
+    let mut downloads = DownloadsMap::new();
+
+    let mut lines = reader.lines();
+    while let Some(line) = lines.next_line().await? {
+        let span = debug_span!("process_line");
+        let _guard = span.enter();
+
+        let json = match serde_json::from_str::<json::LogLine<'_>>(&line) {
+            Ok(json) => json,
+            Err(error) => {
+                warn!("Failed to parse JSON: {error}");
+                continue;
+            }
+        };
+
+        if json.method() != "GET" {
+            // Ignore non-GET requests.
+            continue;
+        }
+
+        if json.status != 200 {
+            // Ignore non-200 responses.
+            continue;
+        }
+
+        if json
+            .user_agent()
+            .is_some_and(|ua| !should_count_user_agent(ua))
+        {
+            // Ignore requests from user agents that should not be counted.
+            continue;
+        }
+
+        let uri = decode_uri(json.uri());
+
+        let Some((name, version)) = parse_path(&uri) else {
+            continue;
+        };
+
+        let Some(date_time) = json.date_time() else {
+            warn!(ts = ?json.ts, "Failed to parse timestamp");
+            continue;
+        };
+
+        let date = date_time.date_naive();
+        let request = RequestInfo {
+            user_agent: json.user_agent(),
+            client_ip: json.client_ip(),
+            pop: None,
+        };
+
+        downloads.add(name, version, date, request);
+    }
+
+    Ok(downloads)
+}
+
+/// Deal with paths like `/crates/tikv-jemalloc-sys/tikv-jemalloc-sys-0.5.4%2B5.3.0-patched.crate`.
+///
+/// Caddy logs the request URI as it was sent by the client, so a single
+/// round of percent-decoding is sufficient.
+#[instrument(level = "debug", skip(uri))]
+fn decode_uri(uri: &str) -> Cow<'_, str> {
+    percent_encoding::percent_decode_str(uri).decode_utf8_lossy()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use claims::assert_ok;
+    use insta::assert_debug_snapshot;
+    use std::io::Cursor;
+
+    #[tokio::test]
+    async fn test_basic() {
+        let _guard = enable_tracing_output();
+
+        let mut cursor = Cursor::new(include_bytes!("../../test_data/caddy/basic.log"));
+        let downloads = assert_ok!(count_downloads(&mut cursor).await);
+
+        assert_debug_snapshot!(downloads, @r"
+        DownloadsMap {
+            2024-01-16  strsim@0.10.0 .. 1
+            2024-01-16  tikv-jemalloc-sys@0.5.2+5.3.0-patched .. 1
+            2024-01-17  anstyle@1.0.1 .. 1
+            2024-01-17  cc@1.0.73 .. 1
+            2024-01-17  winnow@0.5.4 .. 1
+        }
+        ");
+    }
+
+    #[test]
+    fn test_is_access_log_line() {
+        assert!(is_access_log_line(
+            r#"{"level":"info","ts":1705449200.46,"logger":"http.log.access.log0","msg":"handled request"}"#
+        ));
+        assert!(is_access_log_line(
+            r#"{"level":"info","ts":1705449200.46,"logger":"http.log.access","msg":"handled request"}"#
+        ));
+        assert!(!is_access_log_line(
+            r#"{"level":"info","ts":1705449200.46,"logger":"tls","msg":"cleaning storage unit"}"#
+        ));
+        assert!(!is_access_log_line(
+            r#"{"time":"2024-01-16T23:53:20+00:00","request_method":"GET"}"#
+        ));
+    }
+}
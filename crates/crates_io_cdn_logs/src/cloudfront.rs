@@ -3,9 +3,9 @@
 //! see <https://docs.aws.amazon.com/AmazonCloudFront/latest/DeveloperGuide/AccessLogs.html#LogFileFormat>
 //! and <https://www.w3.org/TR/WD-logfile.html>.
 
-use crate::DownloadsMap;
 use crate::paths::parse_path;
 use crate::user_agent::should_count_user_agent;
+use crate::{DownloadsMap, RequestInfo};
 use chrono::NaiveDate;
 use std::borrow::Cow;
 use tokio::io::{AsyncBufRead, AsyncBufReadExt};
@@ -16,6 +16,8 @@ const HEADER_VERSION: &str = "#Version:";
 const HEADER_FIELDS: &str = "#Fields:";
 
 const FIELD_DATE: &str = "date";
+const FIELD_EDGE_LOCATION: &str = "x-edge-location";
+const FIELD_CLIENT_IP: &str = "c-ip";
 const FIELD_METHOD: &str = "cs-method";
 const FIELD_PATH: &str = "cs-uri-stem";
 const FIELD_STATUS: &str = "sc-status";
@@ -25,6 +27,8 @@ const FIELD_USER_AGENT: &str = "cs(User-Agent)";
 pub async fn count_downloads(reader: impl AsyncBufRead + Unpin) -> anyhow::Result<DownloadsMap> {
     let mut num_fields = 0;
     let mut date_index = None;
+    let mut edge_location_index = None;
+    let mut client_ip_index = None;
     let mut method_index = None;
     let mut path_index = None;
     let mut status_index = None;
@@ -47,6 +51,8 @@ pub async fn count_downloads(reader: impl AsyncBufRead + Unpin) -> anyhow::Resul
 
             num_fields = fields.len();
             date_index = fields.iter().position(|f| f == &FIELD_DATE);
+            edge_location_index = fields.iter().position(|f| f == &FIELD_EDGE_LOCATION);
+            client_ip_index = fields.iter().position(|f| f == &FIELD_CLIENT_IP);
             method_index = fields.iter().position(|f| f == &FIELD_METHOD);
             path_index = fields.iter().position(|f| f == &FIELD_PATH);
             status_index = fields.iter().position(|f| f == &FIELD_STATUS);
@@ -109,7 +115,16 @@ pub async fn count_downloads(reader: impl AsyncBufRead + Unpin) -> anyhow::Resul
             }
         };
 
-        downloads.add(name, version, date);
+        // CloudFront percent-encodes the user agent when logging it.
+        let user_agent = user_agent.map(decode_path);
+
+        let request = RequestInfo {
+            user_agent: user_agent.as_deref(),
+            client_ip: get_optional_value(&values, client_ip_index),
+            pop: get_optional_value(&values, edge_location_index),
+        };
+
+        downloads.add(name, version, date, request);
     }
 
     Ok(downloads)
@@ -137,6 +152,7 @@ fn get_optional_value<'a>(values: &'a [&'a str], index: Option<usize>) -> Option
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Platform;
     use crate::test_utils::*;
     use claims::{assert_err, assert_ok};
     use insta::{assert_debug_snapshot, assert_snapshot};
@@ -173,6 +189,108 @@ mod tests {
         ");
     }
 
+    #[tokio::test]
+    async fn test_source_downloads() {
+        let _guard = enable_tracing_output();
+
+        let mut cursor = Cursor::new(include_bytes!("../test_data/cloudfront/basic.log"));
+        let downloads = assert_ok!(count_downloads(&mut cursor).await);
+
+        let source_downloads = downloads
+            .source_downloads(2)
+            .into_iter()
+            .map(|(krate, version, date, source, downloads)| {
+                format!("{date}  {krate}@{version}  {source} .. {downloads}")
+            })
+            .collect::<Vec<_>>();
+
+        assert_debug_snapshot!(source_downloads, @r#"
+        [
+            "2024-01-16  quick-error@1.2.3  ip_prefix=1.2.3.0/24 .. 2",
+            "2024-01-16  quick-error@1.2.3  user_agent=cargo 1.74.0 (ecb9851af 2023-10-18) .. 2",
+        ]
+        "#);
+    }
+
+    #[tokio::test]
+    async fn test_platform_downloads() {
+        let _guard = enable_tracing_output();
+
+        let mut cursor = Cursor::new(include_bytes!("../test_data/cloudfront/basic.log"));
+        let downloads = assert_ok!(count_downloads(&mut cursor).await);
+
+        let platform_downloads = downloads
+            .platform_downloads()
+            .into_iter()
+            .map(|(krate, date, platform, downloads)| {
+                let Platform { os, arch, region } = platform;
+                format!("{date}  {krate}  {os}/{arch}/{region} .. {downloads}")
+            })
+            .collect::<Vec<_>>();
+
+        assert_debug_snapshot!(platform_downloads, @r#"
+        [
+            "2024-01-16  bindgen  unknown/unknown/north_america .. 1",
+            "2024-01-16  cumulus-primitives-core  unknown/unknown/north_america .. 1",
+            "2024-01-16  derive_more  unknown/unknown/north_america .. 1",
+            "2024-01-16  hash-db  unknown/unknown/north_america .. 1",
+            "2024-01-16  hyper-rustls  unknown/unknown/north_america .. 1",
+            "2024-01-16  jsonrpsee-server  unknown/unknown/north_america .. 1",
+            "2024-01-16  peeking_take_while  unknown/unknown/north_america .. 1",
+            "2024-01-16  quick-error  unknown/unknown/north_america .. 2",
+            "2024-01-16  tracing-core  unknown/unknown/north_america .. 1",
+            "2024-01-17  flatbuffers  unknown/unknown/north_america .. 1",
+            "2024-01-17  jemallocator  unknown/unknown/north_america .. 1",
+            "2024-01-17  leveldb-sys  unknown/unknown/north_america .. 1",
+            "2024-01-17  paste  unknown/unknown/north_america .. 1",
+            "2024-01-17  quick-error  unknown/unknown/north_america .. 1",
+            "2024-01-17  rand  unknown/unknown/north_america .. 1",
+            "2024-01-17  serde_derive  unknown/unknown/north_america .. 1",
+            "2024-01-17  smallvec  unknown/unknown/north_america .. 1",
+            "2024-01-17  tar  unknown/unknown/north_america .. 1",
+        ]
+        "#);
+    }
+
+    #[tokio::test]
+    async fn test_client_downloads() {
+        let _guard = enable_tracing_output();
+
+        let mut cursor = Cursor::new(include_bytes!("../test_data/cloudfront/basic.log"));
+        let downloads = assert_ok!(count_downloads(&mut cursor).await);
+
+        let client_downloads = downloads
+            .client_downloads()
+            .into_iter()
+            .map(|(krate, date, client, downloads)| {
+                format!("{date}  {krate}  {client} .. {downloads}")
+            })
+            .collect::<Vec<_>>();
+
+        assert_debug_snapshot!(client_downloads, @r#"
+        [
+            "2024-01-16  bindgen  1.74 .. 1",
+            "2024-01-16  cumulus-primitives-core  1.74 .. 1",
+            "2024-01-16  derive_more  1.74 .. 1",
+            "2024-01-16  hash-db  1.74 .. 1",
+            "2024-01-16  hyper-rustls  1.74 .. 1",
+            "2024-01-16  jsonrpsee-server  1.74 .. 1",
+            "2024-01-16  peeking_take_while  1.74 .. 1",
+            "2024-01-16  quick-error  1.74 .. 2",
+            "2024-01-16  tracing-core  1.74 .. 1",
+            "2024-01-17  flatbuffers  1.71 .. 1",
+            "2024-01-17  jemallocator  1.71 .. 1",
+            "2024-01-17  leveldb-sys  1.71 .. 1",
+            "2024-01-17  paste  1.71 .. 1",
+            "2024-01-17  quick-error  1.74 .. 1",
+            "2024-01-17  rand  1.71 .. 1",
+            "2024-01-17  serde_derive  1.71 .. 1",
+            "2024-01-17  smallvec  1.71 .. 1",
+            "2024-01-17  tar  1.71 .. 1",
+        ]
+        "#);
+    }
+
     #[tokio::test]
     async fn test_percent_encoding() {
         let _guard = enable_tracing_output();
@@ -1,3 +1,6 @@
+use crate::platform::Platform;
+use crate::source::{Source, ip_prefix};
+use crate::user_agent::Client;
 use chrono::NaiveDate;
 use derive_more::Deref;
 use semver::Version;
@@ -5,31 +8,132 @@ use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 
 #[derive(Clone, Default, Deref)]
-pub struct DownloadsMap(HashMap<(String, Version, NaiveDate), u64>);
+pub struct DownloadsMap {
+    #[deref]
+    downloads: HashMap<(String, Version, NaiveDate), u64>,
+    clients: HashMap<(String, NaiveDate, Client), u64>,
+    sources: HashMap<(String, Version, NaiveDate, Source), u64>,
+    platforms: HashMap<(String, NaiveDate, Platform), u64>,
+}
+
+/// The attributes of a download request, besides the crate version and
+/// date, that are used for the per-client, per-source and per-platform
+/// download counts.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RequestInfo<'a> {
+    /// The decoded user agent of the request, if any.
+    pub user_agent: Option<&'a str>,
+    /// The IP address of the client, if it was logged.
+    pub client_ip: Option<&'a str>,
+    /// The code of the CDN point of presence that served the request (e.g.
+    /// `CMH68-P2`), if it was logged.
+    pub pop: Option<&'a str>,
+}
 
 impl DownloadsMap {
     pub fn new() -> Self {
-        Self(HashMap::new())
+        Self::default()
     }
 
-    /// Increments the download count for the given crate version on the given date.
-    pub fn add(&mut self, name: String, version: Version, date: NaiveDate) {
-        *self.0.entry((name, version, date)).or_default() += 1;
+    /// Increments the download count for the given crate version on the given
+    /// date, the per-client and per-platform download counts for the crate
+    /// on that date, and the per-source download counts for the crate version
+    /// on that date.
+    pub fn add(
+        &mut self,
+        name: String,
+        version: Version,
+        date: NaiveDate,
+        request: RequestInfo<'_>,
+    ) {
+        let client = Client::from_user_agent(request.user_agent);
+        *self
+            .clients
+            .entry((name.clone(), date, client))
+            .or_default() += 1;
+
+        let platform = Platform::from_request(request.user_agent, request.pop);
+        *self
+            .platforms
+            .entry((name.clone(), date, platform))
+            .or_default() += 1;
+
+        let ip_prefix = request.client_ip.and_then(ip_prefix).map(Source::IpPrefix);
+        let user_agent = request
+            .user_agent
+            .map(|ua| Source::UserAgent(ua.to_string()));
+        for source in ip_prefix.into_iter().chain(user_agent) {
+            let key = (name.clone(), version.clone(), date, source);
+            *self.sources.entry(key).or_default() += 1;
+        }
+
+        *self.downloads.entry((name, version, date)).or_default() += 1;
     }
 
     /// Returns a [HashSet] of all crate names in the map.
     pub fn unique_crates(&self) -> HashSet<&str> {
-        self.0.keys().map(|(krate, _, _)| krate.as_str()).collect()
+        self.downloads
+            .keys()
+            .map(|(krate, _, _)| krate.as_str())
+            .collect()
     }
 
     /// Returns the total number of downloads across all crates and versions.
     pub fn sum_downloads(&self) -> u64 {
-        self.0.values().sum()
+        self.downloads.values().sum()
+    }
+
+    /// Returns the `(crate, date, client, downloads)` tuples of the per-client
+    /// download counts, sorted by date, crate and client.
+    pub fn client_downloads(&self) -> Vec<(&str, NaiveDate, Client, u64)> {
+        let mut downloads = self
+            .clients
+            .iter()
+            .map(|((krate, date, client), downloads)| (krate.as_str(), *date, *client, *downloads))
+            .collect::<Vec<_>>();
+
+        downloads.sort_by(|a, b| (a.1, a.0, a.2).cmp(&(b.1, b.0, b.2)));
+        downloads
+    }
+
+    /// Returns the `(crate, date, platform, downloads)` tuples of the
+    /// per-platform download counts, sorted by date, crate and platform.
+    pub fn platform_downloads(&self) -> Vec<(&str, NaiveDate, Platform, u64)> {
+        let mut downloads = self
+            .platforms
+            .iter()
+            .map(|((krate, date, platform), downloads)| {
+                (krate.as_str(), *date, *platform, *downloads)
+            })
+            .collect::<Vec<_>>();
+
+        downloads.sort_by(|a, b| (a.1, a.0, a.2).cmp(&(b.1, b.0, b.2)));
+        downloads
+    }
+
+    /// Returns the `(crate, version, date, source, downloads)` tuples of the
+    /// per-source download counts with at least `min_downloads` downloads,
+    /// sorted by date, crate, version and source.
+    pub fn source_downloads(
+        &self,
+        min_downloads: u64,
+    ) -> Vec<(&str, &Version, NaiveDate, &Source, u64)> {
+        let mut downloads = self
+            .sources
+            .iter()
+            .filter(|(_, downloads)| **downloads >= min_downloads)
+            .map(|((krate, version, date, source), downloads)| {
+                (krate.as_str(), version, *date, source, *downloads)
+            })
+            .collect::<Vec<_>>();
+
+        downloads.sort_by(|a, b| (a.2, a.0, a.1, a.3).cmp(&(b.2, b.0, b.1, b.3)));
+        downloads
     }
 
     /// Converts the map into a vector of `(crate, version, date, downloads)` tuples.
     pub fn into_vec(self) -> Vec<(String, Version, NaiveDate, u64)> {
-        self.0
+        self.downloads
             .into_iter()
             .map(|((name, version, date), downloads)| (name, version, date, downloads))
             .collect()
@@ -39,7 +143,7 @@ impl DownloadsMap {
 impl Debug for DownloadsMap {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut downloads = self
-            .0
+            .downloads
             .iter()
             .map(|((krate, version, date), downloads)| (date, krate, version, downloads))
             .collect::<Vec<_>>();
@@ -66,10 +170,21 @@ mod tests {
     use semver::Version;
 
     fn add(downloads: &mut DownloadsMap, name: &str, version: &str, date: &str) {
+        add_with_request(downloads, name, version, date, RequestInfo::default());
+    }
+
+    fn add_with_request(
+        downloads: &mut DownloadsMap,
+        name: &str,
+        version: &str,
+        date: &str,
+        request: RequestInfo<'_>,
+    ) {
         downloads.add(
             name.to_string(),
             version.parse::<Version>().unwrap(),
             date.parse::<NaiveDate>().unwrap(),
+            request,
         );
     }
 
@@ -106,4 +221,126 @@ mod tests {
         }
         ");
     }
+
+    #[test]
+    fn test_client_downloads() {
+        let cargo_1_74 = RequestInfo {
+            user_agent: Some("cargo/1.74.0 (ecb9851af 2023-10-18)"),
+            ..Default::default()
+        };
+        let other = RequestInfo::default();
+
+        let mut downloads = DownloadsMap::new();
+        add_with_request(&mut downloads, "xmas", "2.0.0", "2023-12-25", cargo_1_74);
+        add_with_request(&mut downloads, "xmas", "1.0.0", "2023-12-25", cargo_1_74);
+        add_with_request(&mut downloads, "xmas", "2.0.0", "2023-12-25", other);
+        add_with_request(&mut downloads, "foo", "2.0.0", "2023-12-26", cargo_1_74);
+
+        let client_downloads = downloads
+            .client_downloads()
+            .into_iter()
+            .map(|(krate, date, client, downloads)| {
+                format!("{date}  {krate}  {client} .. {downloads}")
+            })
+            .collect::<Vec<_>>();
+
+        assert_debug_snapshot!(client_downloads, @r#"
+        [
+            "2023-12-25  xmas  1.74 .. 2",
+            "2023-12-25  xmas  other .. 1",
+            "2023-12-26  foo  1.74 .. 1",
+        ]
+        "#);
+    }
+
+    #[test]
+    fn test_source_downloads() {
+        let request = RequestInfo {
+            user_agent: Some("cargo/1.74.0 (ecb9851af 2023-10-18)"),
+            client_ip: Some("1.2.3.4"),
+            ..Default::default()
+        };
+        let other_ip = RequestInfo {
+            client_ip: Some("1.2.3.5"),
+            ..Default::default()
+        };
+        let other_network = RequestInfo {
+            client_ip: Some("2001:db8::1"),
+            ..Default::default()
+        };
+
+        let mut downloads = DownloadsMap::new();
+        add_with_request(&mut downloads, "xmas", "2.0.0", "2023-12-25", request);
+        add_with_request(&mut downloads, "xmas", "2.0.0", "2023-12-25", request);
+        add_with_request(&mut downloads, "xmas", "2.0.0", "2023-12-25", other_ip);
+        add_with_request(&mut downloads, "xmas", "2.0.0", "2023-12-25", other_network);
+        add_with_request(&mut downloads, "xmas", "1.0.0", "2023-12-25", request);
+
+        let format = |min_downloads| {
+            downloads
+                .source_downloads(min_downloads)
+                .into_iter()
+                .map(|(krate, version, date, source, downloads)| {
+                    format!("{date}  {krate}@{version}  {source} .. {downloads}")
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_debug_snapshot!(format(1), @r#"
+        [
+            "2023-12-25  xmas@1.0.0  ip_prefix=1.2.3.0/24 .. 1",
+            "2023-12-25  xmas@1.0.0  user_agent=cargo/1.74.0 (ecb9851af 2023-10-18) .. 1",
+            "2023-12-25  xmas@2.0.0  ip_prefix=1.2.3.0/24 .. 3",
+            "2023-12-25  xmas@2.0.0  ip_prefix=2001:db8::/48 .. 1",
+            "2023-12-25  xmas@2.0.0  user_agent=cargo/1.74.0 (ecb9851af 2023-10-18) .. 2",
+        ]
+        "#);
+
+        assert_debug_snapshot!(format(2), @r#"
+        [
+            "2023-12-25  xmas@2.0.0  ip_prefix=1.2.3.0/24 .. 3",
+            "2023-12-25  xmas@2.0.0  user_agent=cargo/1.74.0 (ecb9851af 2023-10-18) .. 2",
+        ]
+        "#);
+    }
+
+    #[test]
+    fn test_platform_downloads() {
+        let linux = RequestInfo {
+            user_agent: Some("cargo/1.74.0 x86_64-unknown-linux-gnu"),
+            pop: Some("FRA56-P1"),
+            ..Default::default()
+        };
+        let windows = RequestInfo {
+            user_agent: Some("cargo/1.74.0 x86_64-pc-windows-msvc"),
+            pop: Some("IAD89-C1"),
+            ..Default::default()
+        };
+        let other = RequestInfo::default();
+
+        let mut downloads = DownloadsMap::new();
+        add_with_request(&mut downloads, "xmas", "2.0.0", "2023-12-25", linux);
+        add_with_request(&mut downloads, "xmas", "1.0.0", "2023-12-25", linux);
+        add_with_request(&mut downloads, "xmas", "2.0.0", "2023-12-25", windows);
+        add_with_request(&mut downloads, "xmas", "2.0.0", "2023-12-25", other);
+        add_with_request(&mut downloads, "foo", "2.0.0", "2023-12-26", linux);
+
+        let platform_downloads = downloads
+            .platform_downloads()
+            .into_iter()
+            .map(|(krate, date, platform, downloads)| {
+                let Platform { os, arch, region } = platform;
+                format!("{date}  {krate}  {os}/{arch}/{region} .. {downloads}")
+            })
+            .collect::<Vec<_>>();
+
+        assert_debug_snapshot!(platform_downloads, @r#"
+        [
+            "2023-12-25  xmas  linux/x86_64/europe .. 2",
+            "2023-12-25  xmas  windows/x86_64/north_america .. 1",
+            "2023-12-25  xmas  unknown/unknown/unknown .. 1",
+            "2023-12-26  foo  linux/x86_64/europe .. 1",
+        ]
+        "#);
+    }
 }
@@ -38,6 +38,12 @@ impl LogLine<'_> {
         }
     }
 
+    pub fn ip(&self) -> Option<&str> {
+        match self {
+            LogLine::V1(line) => line.ip.as_deref(),
+        }
+    }
+
     pub fn user_agent(&self) -> Option<&str> {
         match self {
             LogLine::V1(line) => line
@@ -54,7 +60,6 @@ impl LogLine<'_> {
 /// repository, there are a couple of differences:
 ///
 /// - The `bytes` field is not included, because we don't need it.
-/// - The `ip` field is not included, because we don't need it.
 /// - The `method` and `status` fields are not optional, because we handle
 ///   parsing errors gracefully.
 /// - The `date_time` field is using `chrono` like the rest of the
@@ -65,6 +70,8 @@ impl LogLine<'_> {
 pub struct LogLineV1<'a> {
     pub date_time: DateTime<Utc>,
     #[serde(borrow)]
+    pub ip: Option<Cow<'a, str>>,
+    #[serde(borrow)]
     pub method: Cow<'a, str>,
     #[serde(borrow)]
     pub url: Cow<'a, str>,
@@ -93,6 +100,9 @@ mod tests {
         V1(
             LogLineV1 {
                 date_time: 2024-01-16T16:03:04.440073230Z,
+                ip: Some(
+                    "45.79.107.220",
+                ),
                 method: "GET",
                 url: "https://static.staging.crates.io/?1705420437",
                 status: 403,
@@ -108,6 +118,7 @@ mod tests {
         assert_eq!(output.method(), "GET");
         assert_eq!(output.url(), "https://static.staging.crates.io/?1705420437");
         assert_eq!(output.status(), 403);
+        assert_eq!(output.ip(), Some("45.79.107.220"));
         assert_eq!(output.user_agent(), None);
 
         match output {
@@ -126,6 +137,9 @@ mod tests {
         V1(
             LogLineV1 {
                 date_time: 2025-10-26T23:57:34.867635728Z,
+                ip: Some(
+                    "192.0.2.1",
+                ),
                 method: "GET",
                 url: "https://static.crates.io/crates/scale-info/2.11.3/download",
                 status: 200,
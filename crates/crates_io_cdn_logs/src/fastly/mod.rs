@@ -4,9 +4,9 @@
 
 mod json;
 
-use crate::DownloadsMap;
 use crate::paths::parse_path;
 use crate::user_agent::should_count_user_agent;
+use crate::{DownloadsMap, RequestInfo};
 use std::borrow::Cow;
 use tokio::io::{AsyncBufRead, AsyncBufReadExt};
 use tracing::{debug_span, instrument, warn};
@@ -62,8 +62,13 @@ pub async fn count_downloads(reader: impl AsyncBufRead + Unpin) -> anyhow::Resul
         };
 
         let date = json.date_time().date_naive();
+        let request = RequestInfo {
+            user_agent: json.user_agent(),
+            client_ip: json.ip(),
+            pop: parse_pop(&line),
+        };
 
-        downloads.add(name, version, date);
+        downloads.add(name, version, date, request);
     }
 
     Ok(downloads)
@@ -76,6 +81,14 @@ fn parse_line(line: &str) -> Option<&str> {
     line.find(r#"]: {"#).map(|pos| &line[pos + 3..])
 }
 
+/// Extracts the point of presence from the syslog hostname of a log line
+/// like `<134>2024-01-16T23:53:20Z cache-iad-kiad7000128 s3-request-logs[...]: {...}`.
+fn parse_pop(line: &str) -> Option<&str> {
+    let hostname = line.split(' ').nth(1)?;
+    let pop = hostname.strip_prefix("cache-")?.split('-').next()?;
+    Some(pop).filter(|pop| !pop.is_empty())
+}
+
 #[instrument(level = "debug", skip(json))]
 fn parse_json(json: &str) -> Result<json::LogLine<'_>, serde_json::Error> {
     serde_json::from_str(json)
@@ -94,7 +107,7 @@ fn decode_url(url: &str) -> Cow<'_, str> {
 mod tests {
     use super::*;
     use crate::test_utils::*;
-    use claims::assert_ok;
+    use claims::{assert_none, assert_ok, assert_some_eq};
     use insta::assert_debug_snapshot;
     use std::io::Cursor;
 
@@ -210,4 +223,15 @@ mod tests {
         }
         ");
     }
+
+    #[test]
+    fn test_parse_pop() {
+        let line = r#"<134>2024-01-16T23:53:20Z cache-iad-kiad7000128 s3-request-logs[322614]: {}"#;
+        assert_some_eq!(parse_pop(line), "iad");
+
+        let line = r#"<134>2024-01-16T23:53:20Z localhost s3-request-logs[322614]: {}"#;
+        assert_none!(parse_pop(line));
+
+        assert_none!(parse_pop(""));
+    }
 }
@@ -1,18 +1,24 @@
 #![doc = include_str!("../README.md")]
 
+pub mod caddy;
 pub mod cloudfront;
 mod compression;
 mod download_map;
 pub mod fastly;
+pub mod nginx;
 mod paths;
+mod platform;
+mod source;
 #[cfg(test)]
 mod test_utils;
 pub mod user_agent;
 
 pub use crate::compression::Decompressor;
-pub use crate::download_map::DownloadsMap;
+pub use crate::download_map::{DownloadsMap, RequestInfo};
+pub use crate::platform::{HostArch, HostOs, Platform, Region};
+pub use crate::source::Source;
 use std::io::Cursor;
-use tokio::io::{AsyncBufRead, AsyncReadExt};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt};
 use tracing::instrument;
 
 #[instrument(skip_all)]
@@ -38,6 +44,28 @@ where
             let reader = Cursor::new(b"<").chain(reader);
             fastly::count_downloads(reader).await
         }
+        // JSON log lines are written by Caddy and by nginx with a custom
+        // `log_format`. The first line is used to tell them apart.
+        b'{' => {
+            let mut first_line = vec![b'{'];
+            reader.read_until(b'\n', &mut first_line).await?;
+
+            let is_caddy = std::str::from_utf8(&first_line).is_ok_and(caddy::is_access_log_line);
+
+            let reader = Cursor::new(first_line).chain(reader);
+            if is_caddy {
+                caddy::count_downloads(reader).await
+            } else {
+                nginx::count_json_downloads(reader).await
+            }
+        }
+        // nginx `combined` log lines start with the IPv4 or IPv6 address of
+        // the client. IPv6 addresses can start with a hex letter (e.g.
+        // `fd00::1`) or with `::`.
+        byte @ (b'0'..=b'9' | b'a'..=b'f' | b'A'..=b'F' | b':') => {
+            let reader = Cursor::new([byte]).chain(reader);
+            nginx::count_downloads(reader).await
+        }
         // Anything else is rejected.
         byte => {
             anyhow::bail!("Failed to determine log file format. Unrecognized first byte: {byte:?}.")
@@ -195,12 +223,106 @@ mod tests {
         ");
     }
 
+    #[tokio::test]
+    async fn test_nginx() {
+        let _guard = enable_tracing_output();
+
+        let mut cursor = Cursor::new(include_bytes!("../test_data/nginx/combined.log"));
+        let downloads = assert_ok!(count_downloads(&mut cursor).await);
+
+        assert_debug_snapshot!(downloads, @r"
+        DownloadsMap {
+            2024-01-16  strsim@0.10.0 .. 1
+            2024-01-16  tikv-jemalloc-sys@0.5.2+5.3.0-patched .. 1
+            2024-01-16  tinyvec@1.6.0 .. 1
+            2024-01-16  winnow@0.5.4 .. 1
+            2024-01-17  anstyle@1.0.1 .. 1
+            2024-01-17  cc@1.0.73 .. 1
+        }
+        ");
+    }
+
+    #[tokio::test]
+    async fn test_compressed_nginx() {
+        let _guard = enable_tracing_output();
+
+        let cursor = Cursor::new(include_bytes!("../test_data/nginx/combined.log.gz"));
+
+        let decompressor = assert_ok!(Decompressor::from_extension(cursor, Some("gz")));
+        let reader = tokio::io::BufReader::new(decompressor);
+
+        let downloads = assert_ok!(count_downloads(reader).await);
+
+        assert_debug_snapshot!(downloads, @r"
+        DownloadsMap {
+            2024-01-16  strsim@0.10.0 .. 1
+            2024-01-16  tikv-jemalloc-sys@0.5.2+5.3.0-patched .. 1
+            2024-01-16  tinyvec@1.6.0 .. 1
+            2024-01-16  winnow@0.5.4 .. 1
+            2024-01-17  anstyle@1.0.1 .. 1
+            2024-01-17  cc@1.0.73 .. 1
+        }
+        ");
+    }
+
+    #[tokio::test]
+    async fn test_nginx_ipv6() {
+        let _guard = enable_tracing_output();
+
+        let mut cursor = Cursor::new(include_bytes!("../test_data/nginx/combined-ipv6.log"));
+        let downloads = assert_ok!(count_downloads(&mut cursor).await);
+
+        assert_debug_snapshot!(downloads, @r"
+        DownloadsMap {
+            2024-01-16  strsim@0.10.0 .. 1
+            2024-01-16  tinyvec@1.6.0 .. 1
+            2024-01-17  anstyle@1.0.1 .. 1
+        }
+        ");
+    }
+
+    #[tokio::test]
+    async fn test_nginx_json() {
+        let _guard = enable_tracing_output();
+
+        let mut cursor = Cursor::new(include_bytes!("../test_data/nginx/json.log"));
+        let downloads = assert_ok!(count_downloads(&mut cursor).await);
+
+        assert_debug_snapshot!(downloads, @r"
+        DownloadsMap {
+            2024-01-16  strsim@0.10.0 .. 1
+            2024-01-16  tikv-jemalloc-sys@0.5.2+5.3.0-patched .. 1
+            2024-01-16  winnow@0.5.4 .. 1
+            2024-01-17  anstyle@1.0.1 .. 1
+            2024-01-17  cc@1.0.73 .. 1
+        }
+        ");
+    }
+
+    #[tokio::test]
+    async fn test_caddy() {
+        let _guard = enable_tracing_output();
+
+        let mut cursor = Cursor::new(include_bytes!("../test_data/caddy/basic.log"));
+        let downloads = assert_ok!(count_downloads(&mut cursor).await);
+
+        assert_debug_snapshot!(downloads, @r"
+        DownloadsMap {
+            2024-01-16  strsim@0.10.0 .. 1
+            2024-01-16  tikv-jemalloc-sys@0.5.2+5.3.0-patched .. 1
+            2024-01-17  anstyle@1.0.1 .. 1
+            2024-01-17  cc@1.0.73 .. 1
+            2024-01-17  winnow@0.5.4 .. 1
+        }
+        ");
+    }
+
     #[tokio::test]
     async fn test_unknown() {
         let _guard = enable_tracing_output();
 
-        let mut cursor = Cursor::new(b"foo");
+        let mut cursor = Cursor::new(b"xyz");
         let error = assert_err!(count_downloads(&mut cursor).await);
-        assert_snapshot!(error, @"Failed to determine log file format. Unrecognized first byte: 102.");
+        assert_snapshot!(error, @"Failed to determine log file format. Unrecognized first byte: 120.");
     }
 }
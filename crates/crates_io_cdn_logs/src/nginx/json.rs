@@ -0,0 +1,102 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::borrow::Cow;
+
+/// This struct corresponds to a log line in the JSON format that is described
+/// in the [module documentation](super).
+///
+/// The string fields are using `Cow` to avoid unnecessary allocations.
+#[derive(Debug, Deserialize)]
+pub struct LogLine<'a> {
+    /// The local time in ISO 8601 format (`$time_iso8601`).
+    pub time: DateTime<Utc>,
+    /// The client address (`$remote_addr`), which is optional because it is
+    /// not part of the documented format.
+    #[serde(borrow, default)]
+    pub remote_addr: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    pub request_method: Cow<'a, str>,
+    #[serde(borrow)]
+    pub request_uri: Cow<'a, str>,
+    #[serde(borrow)]
+    pub status: Status<'a>,
+    /// nginx writes an empty string if the request had no user agent.
+    #[serde(borrow, default)]
+    pub http_user_agent: Option<Cow<'a, str>>,
+}
+
+/// The `$status` variable is commonly written both with and without quotes.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Status<'a> {
+    Number(u16),
+    #[serde(borrow)]
+    String(Cow<'a, str>),
+}
+
+impl Status<'_> {
+    pub fn as_u16(&self) -> Option<u16> {
+        match self {
+            Status::Number(status) => Some(*status),
+            Status::String(status) => status.parse().ok(),
+        }
+    }
+}
+
+impl<'a> From<&'a LogLine<'a>> for super::LogLine<'a> {
+    fn from(line: &'a LogLine<'a>) -> Self {
+        let user_agent = line.http_user_agent.as_deref();
+
+        Self {
+            client_ip: line.remote_addr.as_deref(),
+            date_time: line.time,
+            method: &line.request_method,
+            path: &line.request_uri,
+            // Unparsable status codes are treated like any other non-200
+            // response and ignored.
+            status: line.status.as_u16().unwrap_or_default(),
+            user_agent: user_agent.filter(|ua| !ua.is_empty()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::assert_ok;
+    use insta::assert_debug_snapshot;
+
+    #[test]
+    fn test_parse() {
+        let input = r#"{"time":"2024-01-16T23:53:20+01:00","remote_addr":"1.2.3.4","request_method":"GET","request_uri":"/crates/foo/foo-1.0.0.crate","status":"200","http_user_agent":""}"#;
+        let output = assert_ok!(serde_json::from_str::<LogLine<'_>>(input));
+        assert_debug_snapshot!(output, @r#"
+        LogLine {
+            time: 2024-01-16T22:53:20Z,
+            remote_addr: Some(
+                "1.2.3.4",
+            ),
+            request_method: "GET",
+            request_uri: "/crates/foo/foo-1.0.0.crate",
+            status: String(
+                "200",
+            ),
+            http_user_agent: Some(
+                "",
+            ),
+        }
+        "#);
+
+        let line = crate::nginx::LogLine::from(&output);
+        assert_eq!(line.client_ip, Some("1.2.3.4"));
+        assert_eq!(line.status, 200);
+        assert_eq!(line.user_agent, None);
+
+        let input = r#"{"time":"2024-01-16T23:53:20+00:00","request_method":"GET","request_uri":"/","status":404}"#;
+        let output = assert_ok!(serde_json::from_str::<LogLine<'_>>(input));
+        let line = crate::nginx::LogLine::from(&output);
+        assert_eq!(line.client_ip, None);
+        assert_eq!(line.status, 404);
+        assert_eq!(line.user_agent, None);
+    }
+}
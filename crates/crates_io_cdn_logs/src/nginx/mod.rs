@@ -0,0 +1,261 @@
+//! # nginx access log parsing
+//!
+//! Two formats are supported: the predefined `combined` format
+//! (see <https://nginx.org/en/docs/http/ngx_http_log_module.html#log_format>)
+//! and a JSON format with the following definition:
+//!
+//! ```nginx
+//! log_format crates_io_json escape=json
+//!     '{"time":"$time_iso8601","remote_addr":"$remote_addr",'
+//!     '"request_method":"$request_method","request_uri":"$request_uri",'
+//!     '"status":"$status","http_user_agent":"$http_user_agent"}';
+//! ```
+//!
+//! Additional fields in the JSON format are ignored, `remote_addr` is
+//! optional, and `status` may also be written as a number.
+
+mod json;
+
+use crate::paths::parse_path;
+use crate::user_agent::should_count_user_agent;
+use crate::{DownloadsMap, RequestInfo};
+use chrono::{DateTime, Utc};
+use std::borrow::Cow;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+use tracing::{debug_span, instrument, warn};
+
+/// Count the downloads in an nginx access log file in the `combined` format.
+#[instrument(level = "debug", skip(reader))]
+pub async fn count_downloads(reader: impl AsyncBufRead + Unpin) -> anyhow::Result<DownloadsMap> {
+    let mut downloads = DownloadsMap::new();
+
+    let mut lines = reader.lines();
+    while let Some(line) = lines.next_line().await? {
+        let span = debug_span!("process_line");
+        let _guard = span.enter();
+
+        let Some(line) = parse_combined_line(&line) else {
+            warn!("Failed to parse log line");
+            continue;
+        };
+
+        add_download(&mut downloads, &line);
+    }
+
+    Ok(downloads)
+}
+
+/// Count the downloads in an nginx access log file in the JSON format that is
+/// described in the [module documentation](self).
+#[instrument(level = "debug", skip(reader))]
+pub async fn count_json_downloads(
+    reader: impl AsyncBufRead + Unpin,
+) -> anyhow::Result<DownloadsMap> {
+    let mut downloads = DownloadsMap::new();
+
+    let mut lines = reader.lines();
+    while let Some(line) = lines.next_line().await? {
+        let span = debug_span!("process_line");
+        let _guard = span.enter();
+
+        let json = match serde_json::from_str::<json::LogLine<'_>>(&line) {
+            Ok(json) => json,
+            Err(error) => {
+                warn!("Failed to parse JSON: {error}");
+                continue;
+            }
+        };
+
+        add_download(&mut downloads, &LogLine::from(&json));
+    }
+
+    Ok(downloads)
+}
+
+/// The parts of an access log line that are relevant for counting downloads,
+/// independent of the log format.
+#[derive(Debug)]
+struct LogLine<'a> {
+    client_ip: Option<&'a str>,
+    date_time: DateTime<Utc>,
+    method: &'a str,
+    path: &'a str,
+    status: u16,
+    user_agent: Option<&'a str>,
+}
+
+fn add_download(downloads: &mut DownloadsMap, line: &LogLine<'_>) {
+    if line.method != "GET" {
+        // Ignore non-GET requests.
+        return;
+    }
+
+    if line.status != 200 {
+        // Ignore non-200 responses.
+        return;
+    }
+
+    if line
+        .user_agent
+        .is_some_and(|ua| !should_count_user_agent(ua))
+    {
+        // Ignore requests from user agents that should not be counted.
+        return;
+    }
+
+    let path = decode_path(line.path);
+    let Some((name, version)) = parse_path(&path) else {
+        return;
+    };
+
+    let date = line.date_time.date_naive();
+    let request = RequestInfo {
+        user_agent: line.user_agent,
+        client_ip: line.client_ip,
+        pop: None,
+    };
+
+    downloads.add(name, version, date, request);
+}
+
+/// Parse a log line in the `combined` format:
+///
+/// ```text
+/// $remote_addr - $remote_user [$time_local] "$request" $status $body_bytes_sent "$http_referer" "$http_user_agent"
+/// ```
+///
+/// nginx escapes double quotes within the quoted fields as `\x22`, so the
+/// line can be split at the double quotes without having to deal with
+/// escaping.
+#[instrument(level = "debug", skip(line))]
+fn parse_combined_line(line: &str) -> Option<LogLine<'_>> {
+    let mut parts = line.split('"');
+    let prefix = parts.next()?;
+    let request = parts.next()?;
+    let status_and_size = parts.next()?;
+    let _referer = parts.next()?;
+    let _separator = parts.next()?;
+    let user_agent = parts.next()?;
+
+    let (client_ip, time) = prefix.split_once('[')?;
+    let client_ip = client_ip.split(' ').next().filter(|ip| !ip.is_empty());
+
+    let (time, _) = time.split_once(']')?;
+    let date_time = DateTime::parse_from_str(time, "%d/%b/%Y:%H:%M:%S %z").ok()?;
+
+    let mut request = request.split(' ');
+    let method = request.next()?;
+    let path = request.next()?;
+
+    let status = status_and_size.split_whitespace().next()?;
+    let status = status.parse().ok()?;
+
+    let user_agent = (user_agent != "-").then_some(user_agent);
+
+    Some(LogLine {
+        client_ip,
+        date_time: date_time.to_utc(),
+        method,
+        path,
+        status,
+        user_agent,
+    })
+}
+
+/// Deal with paths like `/crates/tikv-jemalloc-sys/tikv-jemalloc-sys-0.5.4%2B5.3.0-patched.crate`.
+///
+/// nginx logs the request URI as it was sent by the client, so a single
+/// round of percent-decoding is sufficient.
+#[instrument(level = "debug", skip(path))]
+fn decode_path(path: &str) -> Cow<'_, str> {
+    percent_encoding::percent_decode_str(path).decode_utf8_lossy()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use claims::{assert_none, assert_ok, assert_some};
+    use insta::assert_debug_snapshot;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_parse_combined_line() {
+        let line = r#"1.2.3.4 - - [16/Jan/2024:23:53:20 +0100] "GET /crates/foo/foo-1.0.0.crate HTTP/1.1" 200 11355 "-" "cargo/1.74.0 (ecb9851af 2023-10-18)""#;
+        let line = assert_some!(parse_combined_line(line));
+        assert_debug_snapshot!(line, @r#"
+        LogLine {
+            client_ip: Some(
+                "1.2.3.4",
+            ),
+            date_time: 2024-01-16T22:53:20Z,
+            method: "GET",
+            path: "/crates/foo/foo-1.0.0.crate",
+            status: 200,
+            user_agent: Some(
+                "cargo/1.74.0 (ecb9851af 2023-10-18)",
+            ),
+        }
+        "#);
+
+        let line = r#"1.2.3.4 - - [16/Jan/2024:23:53:20 +0000] "GET /crates/foo/foo-1.0.0.crate HTTP/1.1" 200 11355 "-" "-""#;
+        let line = assert_some!(parse_combined_line(line));
+        assert_none!(line.user_agent);
+
+        let line = r#"1.2.3.4 - - [16/Jan/2024:23:53:20 +0000] "GET /crates/foo/foo-1.0.0.crate HTTP/1.1" 200 11355"#;
+        assert_none!(parse_combined_line(line));
+    }
+
+    #[tokio::test]
+    async fn test_combined() {
+        let _guard = enable_tracing_output();
+
+        let mut cursor = Cursor::new(include_bytes!("../../test_data/nginx/combined.log"));
+        let downloads = assert_ok!(count_downloads(&mut cursor).await);
+
+        assert_debug_snapshot!(downloads, @r"
+        DownloadsMap {
+            2024-01-16  strsim@0.10.0 .. 1
+            2024-01-16  tikv-jemalloc-sys@0.5.2+5.3.0-patched .. 1
+            2024-01-16  tinyvec@1.6.0 .. 1
+            2024-01-16  winnow@0.5.4 .. 1
+            2024-01-17  anstyle@1.0.1 .. 1
+            2024-01-17  cc@1.0.73 .. 1
+        }
+        ");
+    }
+
+    #[tokio::test]
+    async fn test_combined_recoverable_errors() {
+        let _guard = enable_tracing_output();
+
+        let mut cursor = Cursor::new(include_bytes!(
+            "../../test_data/nginx/recoverable-errors.log"
+        ));
+        let downloads = assert_ok!(count_downloads(&mut cursor).await);
+
+        assert_debug_snapshot!(downloads, @r"
+        DownloadsMap {
+            2024-01-16  strsim@0.10.0 .. 2
+        }
+        ");
+    }
+
+    #[tokio::test]
+    async fn test_json() {
+        let _guard = enable_tracing_output();
+
+        let mut cursor = Cursor::new(include_bytes!("../../test_data/nginx/json.log"));
+        let downloads = assert_ok!(count_json_downloads(&mut cursor).await);
+
+        assert_debug_snapshot!(downloads, @r"
+        DownloadsMap {
+            2024-01-16  strsim@0.10.0 .. 1
+            2024-01-16  tikv-jemalloc-sys@0.5.2+5.3.0-patched .. 1
+            2024-01-16  winnow@0.5.4 .. 1
+            2024-01-17  anstyle@1.0.1 .. 1
+            2024-01-17  cc@1.0.73 .. 1
+        }
+        ");
+    }
+}
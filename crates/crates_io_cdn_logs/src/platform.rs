@@ -0,0 +1,289 @@
+use std::fmt;
+
+/// The coarse host platform and CDN region of a download request.
+///
+/// The dimensions are deliberately coarse, so that the aggregated download
+/// counts can't be used to identify individual clients.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Platform {
+    pub os: HostOs,
+    pub arch: HostArch,
+    pub region: Region,
+}
+
+impl Platform {
+    /// Determines the [`Platform`] from the decoded user agent of a request
+    /// and the code of the CDN point of presence that served it.
+    pub fn from_request(user_agent: Option<&str>, pop: Option<&str>) -> Self {
+        let (os, arch) = user_agent
+            .and_then(parse_host_triple)
+            .unwrap_or((HostOs::Unknown, HostArch::Unknown));
+
+        let region = pop.map(Region::from_pop).unwrap_or(Region::Unknown);
+
+        Self { os, arch, region }
+    }
+}
+
+/// The operating system of the host that downloaded a crate file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HostOs {
+    Linux,
+    MacOs,
+    Windows,
+    /// A known target triple for any other operating system (e.g. FreeBSD
+    /// or Android).
+    Other,
+    /// A user agent without a target triple.
+    Unknown,
+}
+
+impl HostOs {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Linux => "linux",
+            Self::MacOs => "macos",
+            Self::Windows => "windows",
+            Self::Other => "other",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    fn from_triple_parts<'a>(mut parts: impl Iterator<Item = &'a str> + Clone) -> Option<Self> {
+        // Mobile targets like `aarch64-linux-android` also contain the kernel
+        // name, so they have to be checked first.
+        let is_mobile = parts.clone().any(|part| {
+            matches!(
+                part,
+                "android" | "androideabi" | "ios" | "tvos" | "watchos" | "visionos"
+            )
+        });
+        if is_mobile {
+            return Some(Self::Other);
+        }
+
+        parts.find_map(|part| match part {
+            "linux" => Some(Self::Linux),
+            "darwin" => Some(Self::MacOs),
+            "windows" => Some(Self::Windows),
+            "freebsd" | "netbsd" | "openbsd" | "dragonfly" | "illumos" | "solaris" | "haiku" => {
+                Some(Self::Other)
+            }
+            _ => None,
+        })
+    }
+}
+
+impl fmt::Display for HostOs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The CPU architecture of the host that downloaded a crate file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HostArch {
+    X86_64,
+    Aarch64,
+    X86,
+    Arm,
+    /// A known target triple for any other architecture (e.g. RISC-V or
+    /// PowerPC).
+    Other,
+    /// A user agent without a target triple.
+    Unknown,
+}
+
+impl HostArch {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::X86_64 => "x86_64",
+            Self::Aarch64 => "aarch64",
+            Self::X86 => "x86",
+            Self::Arm => "arm",
+            Self::Other => "other",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    fn from_triple_part(part: &str) -> Option<Self> {
+        match part {
+            "x86_64" | "amd64" => Some(Self::X86_64),
+            "aarch64" | "arm64" => Some(Self::Aarch64),
+            "i386" | "i586" | "i686" => Some(Self::X86),
+            _ if part.starts_with("armv") || part.starts_with("thumbv") || part == "arm" => {
+                Some(Self::Arm)
+            }
+            _ if ["riscv", "powerpc", "s390x", "loongarch", "mips", "sparc"]
+                .iter()
+                .any(|prefix| part.starts_with(prefix)) =>
+            {
+                Some(Self::Other)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for HostArch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Searches the user agent for a target triple like
+/// `x86_64-unknown-linux-gnu` and returns the host OS and architecture
+/// encoded in it.
+///
+/// Official cargo releases don't include the host in their user agent, but
+/// some distribution packages and wrappers append the target triple.
+fn parse_host_triple(user_agent: &str) -> Option<(HostOs, HostArch)> {
+    user_agent
+        .split(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | ';' | ',' | '/'))
+        .find_map(|token| {
+            let mut parts = token.split('-');
+            let arch = HostArch::from_triple_part(parts.next()?)?;
+            let os = HostOs::from_triple_parts(parts)?;
+            Some((os, arch))
+        })
+}
+
+/// The continent-level region of the CDN point of presence that served a
+/// download request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Region {
+    NorthAmerica,
+    SouthAmerica,
+    Europe,
+    MiddleEast,
+    Africa,
+    Asia,
+    Oceania,
+    /// A request without a point of presence, or with an unknown code.
+    Unknown,
+}
+
+impl Region {
+    /// Determines the [`Region`] from the IATA airport code that CloudFront
+    /// and Fastly use to name their points of presence (e.g. `IAD` or
+    /// `CMH68-P2`). Only the first three characters are considered, and the
+    /// comparison is case-insensitive.
+    pub fn from_pop(pop: &str) -> Self {
+        let Some(code) = pop.get(..3) else {
+            return Self::Unknown;
+        };
+
+        match code.to_ascii_uppercase().as_str() {
+            "ANC" | "ATL" | "AUS" | "BNA" | "BOS" | "BUR" | "CHI" | "CLT" | "CMH" | "DAL"
+            | "DEN" | "DFW" | "DTW" | "EWR" | "HIO" | "HNL" | "HOU" | "IAD" | "IAH" | "JAX"
+            | "JFK" | "LAS" | "LAX" | "LGA" | "MCI" | "MCO" | "MEX" | "MIA" | "MSP" | "NYC"
+            | "OAK" | "ORD" | "PAO" | "PDX" | "PHL" | "PHX" | "PIT" | "QRO" | "RDU" | "SAN"
+            | "SEA" | "SFO" | "SJC" | "SLC" | "STL" | "TPA" | "YUL" | "YVR" | "YYC" | "YYZ" => {
+                Self::NorthAmerica
+            }
+            "BOG" | "CNF" | "EZE" | "FOR" | "GIG" | "GRU" | "LIM" | "POA" | "SCL" => {
+                Self::SouthAmerica
+            }
+            "AMS" | "ARN" | "ATH" | "BCN" | "BER" | "BMA" | "BRU" | "BUD" | "CDG" | "CPH"
+            | "DUB" | "DUS" | "FCO" | "FRA" | "HAM" | "HEL" | "LCY" | "LHR" | "LIS" | "MAD"
+            | "MAN" | "MRS" | "MUC" | "MXP" | "OSL" | "OTP" | "PMO" | "PRG" | "SOF" | "TXL"
+            | "VIE" | "WAW" | "ZAG" | "ZRH" => Self::Europe,
+            "AMM" | "BAH" | "DOH" | "DXB" | "FJR" | "JED" | "MCT" | "RUH" | "TLV" => {
+                Self::MiddleEast
+            }
+            "ACC" | "CAI" | "CPT" | "JNB" | "LOS" | "NBO" => Self::Africa,
+            "BKK" | "BLR" | "BOM" | "CCU" | "CGK" | "CMB" | "DEL" | "HAN" | "HKG" | "HND"
+            | "HYD" | "ICN" | "ITM" | "KHH" | "KIX" | "KUL" | "MAA" | "MNL" | "NRT" | "PNQ"
+            | "SGN" | "SIN" | "TPE" | "TYO" => Self::Asia,
+            "ADL" | "AKL" | "BNE" | "CHC" | "MEL" | "PER" | "SYD" => Self::Oceania,
+            _ => Self::Unknown,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NorthAmerica => "north_america",
+            Self::SouthAmerica => "south_america",
+            Self::Europe => "europe",
+            Self::MiddleEast => "middle_east",
+            Self::Africa => "africa",
+            Self::Asia => "asia",
+            Self::Oceania => "oceania",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_from_user_agent() {
+        let host = |ua| {
+            let platform = Platform::from_request(ua, None);
+            format!("{}/{}", platform.os, platform.arch)
+        };
+
+        assert_eq!(host(None), "unknown/unknown");
+        assert_eq!(
+            host(Some("cargo/1.88.0 (873a06493 2025-05-10)")),
+            "unknown/unknown"
+        );
+        assert_eq!(
+            host(Some(
+                "cargo/1.88.0 (873a06493 2025-05-10) x86_64-unknown-linux-gnu"
+            )),
+            "linux/x86_64"
+        );
+        assert_eq!(
+            host(Some("cargo/1.88.0 (aarch64-apple-darwin)")),
+            "macos/aarch64"
+        );
+        assert_eq!(
+            host(Some("cargo/1.88.0 (i686-pc-windows-msvc; 2025-05-10)")),
+            "windows/x86"
+        );
+        assert_eq!(
+            host(Some("cargo/1.88.0 armv7-unknown-linux-gnueabihf")),
+            "linux/arm"
+        );
+        assert_eq!(
+            host(Some("cargo/1.88.0 riscv64gc-unknown-linux-gnu")),
+            "linux/other"
+        );
+        assert_eq!(
+            host(Some("cargo/1.88.0 aarch64-linux-android")),
+            "other/aarch64"
+        );
+        assert_eq!(
+            host(Some("cargo/1.88.0 x86_64-unknown-freebsd")),
+            "other/x86_64"
+        );
+        assert_eq!(
+            host(Some("cargo/1.88.0 foo-unknown-linux")),
+            "unknown/unknown"
+        );
+    }
+
+    #[test]
+    fn test_region_from_pop() {
+        assert_eq!(Region::from_pop("CMH68-P2"), Region::NorthAmerica);
+        assert_eq!(Region::from_pop("iad"), Region::NorthAmerica);
+        assert_eq!(Region::from_pop("GRU3-C1"), Region::SouthAmerica);
+        assert_eq!(Region::from_pop("FRA56-P1"), Region::Europe);
+        assert_eq!(Region::from_pop("DXB2-C1"), Region::MiddleEast);
+        assert_eq!(Region::from_pop("JNB1-C1"), Region::Africa);
+        assert_eq!(Region::from_pop("NRT12-C3"), Region::Asia);
+        assert_eq!(Region::from_pop("SYD1-C1"), Region::Oceania);
+        assert_eq!(Region::from_pop("XYZ1"), Region::Unknown);
+        assert_eq!(Region::from_pop("-"), Region::Unknown);
+        assert_eq!(Region::from_pop(""), Region::Unknown);
+    }
+}
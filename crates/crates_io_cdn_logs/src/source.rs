@@ -0,0 +1,81 @@
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// The source of a download, used to detect downloads that are concentrated
+/// on a small number of clients (e.g. CI loops or bots).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Source {
+    /// The network prefix of the client IP address, see [`ip_prefix`].
+    IpPrefix(String),
+    /// The full user agent of the request.
+    UserAgent(String),
+}
+
+impl Source {
+    /// Returns the name of the source type, as it is stored in the
+    /// database.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::IpPrefix(_) => "ip_prefix",
+            Self::UserAgent(_) => "user_agent",
+        }
+    }
+
+    /// Returns the IP prefix or user agent of the source.
+    pub fn value(&self) -> &str {
+        match self {
+            Self::IpPrefix(value) | Self::UserAgent(value) => value,
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.kind(), self.value())
+    }
+}
+
+/// Returns the `/24` network prefix of an IPv4 address, or the `/48` network
+/// prefix of an IPv6 address, in CIDR notation.
+///
+/// Only the prefix is used instead of the full address to avoid storing
+/// personal data, and because a single client will often use multiple
+/// addresses from the same network.
+pub fn ip_prefix(ip: &str) -> Option<String> {
+    match ip.parse::<IpAddr>().ok()? {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            Some(format!("{}/24", Ipv4Addr::new(a, b, c, 0)))
+        }
+        IpAddr::V6(ip) => {
+            let [a, b, c, ..] = ip.segments();
+            Some(format!("{}/48", Ipv6Addr::new(a, b, c, 0, 0, 0, 0, 0)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_none, assert_some_eq};
+
+    #[test]
+    fn test_ip_prefix() {
+        assert_some_eq!(ip_prefix("1.2.3.4"), "1.2.3.0/24");
+        assert_some_eq!(ip_prefix("192.168.255.255"), "192.168.255.0/24");
+        assert_some_eq!(ip_prefix("2001:db8:1234:5678::1"), "2001:db8:1234::/48");
+        assert_some_eq!(ip_prefix("::1"), "::/48");
+        assert_none!(ip_prefix(""));
+        assert_none!(ip_prefix("-"));
+        assert_none!(ip_prefix("1.2.3"));
+    }
+
+    #[test]
+    fn test_display() {
+        let source = Source::IpPrefix("1.2.3.0/24".into());
+        assert_eq!(source.to_string(), "ip_prefix=1.2.3.0/24");
+
+        let source = Source::UserAgent("cargo/1.74.0".into());
+        assert_eq!(source.to_string(), "user_agent=cargo/1.74.0");
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+
 /// Determines if downloads from the given user agent should be counted.
 ///
 /// Returns `true` if the download should be counted, `false` otherwise.
@@ -13,6 +15,58 @@ pub fn should_count_user_agent(user_agent: &str) -> bool {
         || suffix.starts_with("%20")
 }
 
+/// The client that downloaded a crate file, as far as it can be determined
+/// from the user agent of the request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Client {
+    /// A cargo release with the given `major.minor` version. Patch versions
+    /// and pre-release suffixes like `-nightly` are ignored.
+    Cargo { major: u64, minor: u64 },
+    /// A request without a user agent, or from a cargo version that could
+    /// not be parsed.
+    Other,
+}
+
+impl Client {
+    /// Determines the [`Client`] from the user agent of a request that was
+    /// already accepted by [`should_count_user_agent`].
+    pub fn from_user_agent(user_agent: Option<&str>) -> Self {
+        user_agent
+            .and_then(parse_cargo_version)
+            .map(|(major, minor)| Self::Cargo { major, minor })
+            .unwrap_or(Self::Other)
+    }
+}
+
+impl fmt::Display for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cargo { major, minor } => write!(f, "{major}.{minor}"),
+            Self::Other => f.write_str("other"),
+        }
+    }
+}
+
+/// Extracts the `major.minor` version from cargo user agents like
+/// `cargo/1.88.0 (873a06493 2025-05-10)` or `cargo%201.74.0%20(...)`.
+fn parse_cargo_version(user_agent: &str) -> Option<(u64, u64)> {
+    let suffix = user_agent.strip_prefix("cargo")?;
+    let version = ["/", " ", "%2f", "%2F", "%20"]
+        .iter()
+        .find_map(|separator| suffix.strip_prefix(separator))?;
+
+    let mut parts = version.splitn(3, '.');
+    let major = parts.next()?.parse().ok()?;
+
+    let minor = parts.next()?;
+    let minor_end = minor
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(minor.len());
+    let minor = minor[..minor_end].parse().ok()?;
+
+    Some((major, minor))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -52,4 +106,30 @@ mod tests {
         assert!(!should_count_user_agent("cargo"));
         assert!(!should_count_user_agent("cargo-"));
     }
+
+    #[test]
+    fn test_client_from_user_agent() {
+        let client = |ua| Client::from_user_agent(ua).to_string();
+
+        assert_eq!(
+            client(Some("cargo/1.92.0-nightly (344c4567c 2025-10-21)")),
+            "1.92"
+        );
+        assert_eq!(client(Some("cargo/1.88.0 (873a06493 2025-05-10)")), "1.88");
+        assert_eq!(client(Some("cargo%2f1.74.0")), "1.74");
+        assert_eq!(client(Some("cargo%2F1.74.0")), "1.74");
+        assert_eq!(client(Some("cargo 1.74.0")), "1.74");
+        assert_eq!(
+            client(Some("cargo%201.71.0%20(cfd3bbd8f%202023-06-08)")),
+            "1.71"
+        );
+        assert_eq!(client(Some("cargo/1.9")), "1.9");
+
+        assert_eq!(client(Some("cargo/")), "other");
+        assert_eq!(client(Some("cargo%20")), "other");
+        assert_eq!(client(Some("cargo/1")), "other");
+        assert_eq!(client(Some("cargo/x.y.z")), "other");
+        assert_eq!(client(Some("curl/7.64.1")), "other");
+        assert_eq!(client(None), "other");
+    }
 }
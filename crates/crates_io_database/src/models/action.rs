@@ -1,4 +1,4 @@
-use crate::models::{ApiToken, User, Version};
+use crate::models::{ApiToken, TrustpubData, User, Version};
 use crate::schema::*;
 use bon::Builder;
 use chrono::{DateTime, Utc};
@@ -49,6 +49,8 @@ pub struct VersionOwnerAction {
     pub api_token_id: Option<i32>,
     pub action: VersionAction,
     pub time: DateTime<Utc>,
+    /// The team that performed the action via a team token, if any.
+    pub team_id: Option<i32>,
 }
 
 impl VersionOwnerAction {
@@ -91,6 +93,7 @@ pub struct NewVersionOwnerAction {
     version_id: i32,
     user_id: i32,
     api_token_id: Option<i32>,
+    team_id: Option<i32>,
     #[builder(into)]
     action: VersionAction,
 }
@@ -104,3 +107,89 @@ impl NewVersionOwnerAction {
             .await
     }
 }
+
+pg_enum! {
+    pub enum CrateAction {
+        OwnerInvite = 0,
+        OwnerAdd = 1,
+        OwnerRemove = 2,
+        TrustpubOnlyChange = 3,
+        TrustpubConfigCreate = 4,
+        TrustpubConfigDelete = 5,
+        Deprecate = 6,
+        Undeprecate = 7,
+        Delete = 8,
+        WebhookCreate = 9,
+        WebhookUpdate = 10,
+        WebhookDelete = 11,
+    }
+}
+
+impl From<CrateAction> for &'static str {
+    fn from(action: CrateAction) -> Self {
+        match action {
+            CrateAction::OwnerInvite => "owner_invite",
+            CrateAction::OwnerAdd => "owner_add",
+            CrateAction::OwnerRemove => "owner_remove",
+            CrateAction::TrustpubOnlyChange => "trustpub_only_change",
+            CrateAction::TrustpubConfigCreate => "trustpub_config_create",
+            CrateAction::TrustpubConfigDelete => "trustpub_config_delete",
+            CrateAction::Deprecate => "deprecate",
+            CrateAction::Undeprecate => "undeprecate",
+            CrateAction::Delete => "delete",
+            CrateAction::WebhookCreate => "webhook_create",
+            CrateAction::WebhookUpdate => "webhook_update",
+            CrateAction::WebhookDelete => "webhook_delete",
+        }
+    }
+}
+
+impl From<CrateAction> for String {
+    fn from(action: CrateAction) -> Self {
+        let string: &'static str = action.into();
+
+        string.into()
+    }
+}
+
+/// An entry in the audit log of a crate.
+///
+/// In contrast to [`VersionOwnerAction`], these entries are not tied to a
+/// specific version and are kept (with `crate_id` set to `NULL`) when the
+/// crate itself is deleted.
+#[derive(Debug, Clone, HasQuery, Identifiable)]
+#[diesel(table_name = crate_audit_actions)]
+pub struct CrateAuditAction {
+    pub id: i32,
+    pub crate_id: Option<i32>,
+    pub crate_name: String,
+    pub user_id: Option<i32>,
+    pub api_token_id: Option<i32>,
+    pub trustpub_data: Option<TrustpubData>,
+    pub action: CrateAction,
+    pub details: serde_json::Value,
+    pub time: DateTime<Utc>,
+}
+
+#[derive(Insertable, Debug, Builder)]
+#[diesel(table_name = crate_audit_actions, check_for_backend(diesel::pg::Pg))]
+pub struct NewCrateAuditAction<'a> {
+    crate_id: i32,
+    crate_name: &'a str,
+    user_id: Option<i32>,
+    api_token_id: Option<i32>,
+    trustpub_data: Option<&'a TrustpubData>,
+    action: CrateAction,
+    #[builder(default = serde_json::Value::Object(Default::default()))]
+    details: serde_json::Value,
+}
+
+impl NewCrateAuditAction<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<CrateAuditAction> {
+        diesel::insert_into(crate_audit_actions::table)
+            .values(self)
+            .returning(CrateAuditAction::as_select())
+            .get_result(conn)
+            .await
+    }
+}
@@ -4,7 +4,7 @@ use diesel_async::scoped_futures::ScopedFutureExt;
 use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 use secrecy::SecretString;
 
-use crate::models::{CrateOwner, User};
+use crate::models::{CrateAction, CrateOwner, NewCrateAuditAction, User};
 use crate::schema::{crate_owner_invitations, crates};
 
 #[derive(Debug)]
@@ -116,6 +116,22 @@ impl CrateOwnerInvitation {
             async move {
                 CrateOwner::from_invite(&self).insert(conn).await?;
 
+                let crate_name: String = crates::table
+                    .find(self.crate_id)
+                    .select(crates::name)
+                    .first(conn)
+                    .await?;
+
+                NewCrateAuditAction::builder()
+                    .crate_id(self.crate_id)
+                    .crate_name(&crate_name)
+                    .user_id(user.id)
+                    .action(CrateAction::OwnerAdd)
+                    .details(serde_json::json!({ "owner": user.gh_login }))
+                    .build()
+                    .insert(conn)
+                    .await?;
+
                 diesel::delete(&self).execute(conn).await?;
 
                 Ok(())
@@ -0,0 +1,103 @@
+use crate::schema::download_anomalies;
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// An anomalous daily download count of a version, as detected by the
+/// `detect_download_anomalies` background job.
+#[derive(Debug, Clone, HasQuery, Identifiable)]
+#[diesel(table_name = download_anomalies)]
+pub struct DownloadAnomaly {
+    pub id: i64,
+    pub version_id: i32,
+    pub date: NaiveDate,
+    pub reason: String,
+    pub downloads: i64,
+    pub expected_downloads: i64,
+    pub top_ip_prefix: Option<String>,
+    pub top_ip_prefix_downloads: Option<i64>,
+    pub top_user_agent: Option<String>,
+    pub top_user_agent_downloads: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub excluded_downloads: Option<i64>,
+}
+
+impl DownloadAnomaly {
+    pub async fn find(conn: &mut AsyncPgConnection, id: i64) -> QueryResult<Self> {
+        Self::query().find(id).first(conn).await
+    }
+
+    /// Loads all anomalies that have not been reviewed by an admin yet,
+    /// oldest first.
+    pub async fn unreviewed(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Self>> {
+        Self::query()
+            .filter(download_anomalies::reviewed_at.is_null())
+            .order((download_anomalies::date, download_anomalies::id))
+            .load(conn)
+            .await
+    }
+
+    /// Returns the number of downloads above the expected number of
+    /// downloads, which are the downloads that would be excluded by
+    /// [`Self::exclude()`].
+    ///
+    /// For `concentrated_source` anomalies, only the downloads of the top IP
+    /// prefix are suspicious, so at most the downloads of that prefix above
+    /// its expected share are returned.
+    pub fn excess_downloads(&self) -> i64 {
+        let excess = (self.downloads - self.expected_downloads).max(0);
+
+        match self.top_ip_prefix_downloads {
+            Some(top) if self.reason == "concentrated_source" && self.downloads > 0 => {
+                let expected_share = top * self.expected_downloads / self.downloads;
+                excess.min((top - expected_share).max(0))
+            }
+            _ => excess,
+        }
+    }
+
+    /// Marks the anomaly as reviewed by an admin.
+    pub async fn mark_reviewed(&self, conn: &mut AsyncPgConnection) -> QueryResult<()> {
+        diesel::update(self)
+            .set(download_anomalies::reviewed_at.eq(diesel::dsl::now))
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Subtracts the [excess downloads](Self::excess_downloads) from the
+    /// `version_downloads` row of the anomaly.
+    ///
+    /// The row is marked as unprocessed, so that the `update_downloads`
+    /// background job propagates the difference to the total download counts
+    /// of the version and crate.
+    ///
+    /// Returns `false` if the downloads were already excluded before.
+    pub async fn exclude(&self, conn: &mut AsyncPgConnection) -> QueryResult<bool> {
+        let updated = diesel::sql_query(
+            r#"
+                WITH excluded AS (
+                    UPDATE download_anomalies
+                    SET excluded_downloads = $2
+                    WHERE id = $1 AND excluded_downloads IS NULL
+                    RETURNING version_id, date, excluded_downloads
+                )
+                UPDATE version_downloads
+                SET downloads = GREATEST(version_downloads.downloads - excluded.excluded_downloads, 0),
+                    processed = false
+                FROM excluded
+                WHERE version_downloads.version_id = excluded.version_id
+                    AND version_downloads.date = excluded.date
+            "#,
+        )
+        .bind::<BigInt, _>(self.id)
+        .bind::<BigInt, _>(self.excess_downloads())
+        .execute(conn)
+        .await?;
+
+        Ok(updated > 0)
+    }
+}
@@ -0,0 +1,123 @@
+use crate::schema::index_changes;
+use chrono::{DateTime, Utc};
+use crates_io_diesel_helpers::pg_enum;
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+
+pg_enum! {
+    pub enum IndexChangeReason {
+        Update = 0,
+        Delete = 1,
+    }
+}
+
+impl From<IndexChangeReason> for &'static str {
+    fn from(reason: IndexChangeReason) -> Self {
+        match reason {
+            IndexChangeReason::Update => "update",
+            IndexChangeReason::Delete => "delete",
+        }
+    }
+}
+
+impl From<IndexChangeReason> for String {
+    fn from(reason: IndexChangeReason) -> Self {
+        let string: &'static str = reason.into();
+
+        string.into()
+    }
+}
+
+/// A modification of a sparse index file.
+///
+/// Changes are recorded without a sequence number, which is assigned later by
+/// [`IndexChange::assign_sequence_numbers`]. Only changes with a sequence
+/// number are part of the changes feed.
+#[derive(Debug, Clone, HasQuery, Identifiable)]
+#[diesel(table_name = index_changes)]
+pub struct IndexChange {
+    pub id: i64,
+    /// The sequence number of the change, which mirrors use as a cursor to
+    /// sync the index incrementally.
+    #[diesel(select_expression = index_changes::seq.assume_not_null())]
+    pub seq: i64,
+    pub crate_name: String,
+    pub reason: IndexChangeReason,
+    pub changed_at: DateTime<Utc>,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+/// The key of the advisory lock that serializes the assignment of sequence
+/// numbers.
+const SEQUENCE_LOCK_KEY: i64 = 0x696e_6465_785f_7365;
+
+define_sql_function!(fn pg_advisory_xact_lock(key: BigInt));
+
+impl IndexChange {
+    /// Loads up to `limit` changes with a sequence number greater than
+    /// `since`, in order.
+    pub async fn after(
+        conn: &mut AsyncPgConnection,
+        since: i64,
+        limit: i64,
+    ) -> QueryResult<Vec<Self>> {
+        Self::query()
+            .filter(index_changes::seq.gt(since))
+            .order(index_changes::seq)
+            .limit(limit)
+            .load(conn)
+            .await
+    }
+
+    /// Assigns sequence numbers to all committed changes that don't have one
+    /// yet, and returns the number of updated changes.
+    ///
+    /// The `id` can't be used as the sequence number, because concurrent
+    /// transactions might commit in a different order than they allocated
+    /// their ids, which would cause mirrors that already synced past a higher
+    /// id to miss the change. Instead, the sequence numbers are assigned by a
+    /// single writer, so that a change committed later always receives a
+    /// higher sequence number.
+    pub async fn assign_sequence_numbers(conn: &mut AsyncPgConnection) -> QueryResult<usize> {
+        conn.transaction(|conn| {
+            async move {
+                diesel::select(pg_advisory_xact_lock(SEQUENCE_LOCK_KEY))
+                    .execute(conn)
+                    .await?;
+
+                diesel::sql_query(
+                    "WITH last AS (
+                        SELECT COALESCE(MAX(seq), 0) AS seq FROM index_changes
+                    ), unsequenced AS (
+                        SELECT id, ROW_NUMBER() OVER (ORDER BY id) AS n
+                        FROM index_changes
+                        WHERE seq IS NULL
+                    )
+                    UPDATE index_changes
+                    SET seq = last.seq + unsequenced.n
+                    FROM last, unsequenced
+                    WHERE index_changes.id = unsequenced.id",
+                )
+                .execute(conn)
+                .await
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = index_changes, check_for_backend(diesel::pg::Pg))]
+pub struct NewIndexChange<'a> {
+    pub crate_name: &'a str,
+    pub reason: IndexChangeReason,
+}
+
+impl NewIndexChange<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<usize> {
+        self.insert_into(index_changes::table).execute(conn).await
+    }
+}
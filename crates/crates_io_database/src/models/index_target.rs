@@ -0,0 +1,121 @@
+use crate::schema::{index_target_bins, index_targets};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// The length and hash of a sparse index file, as listed in the signed
+/// `targets` metadata of the index.
+#[derive(Debug, Clone, HasQuery)]
+#[diesel(table_name = index_targets)]
+pub struct IndexTarget {
+    pub path: String,
+    pub bin: i16,
+    pub length: i64,
+    pub sha256: Vec<u8>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl IndexTarget {
+    /// Removes the index file with the given path from the targets.
+    pub async fn delete(conn: &mut AsyncPgConnection, path: &str) -> QueryResult<()> {
+        diesel::delete(index_targets::table.find(path))
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// A hash bin of the signed `targets` metadata of the index.
+///
+/// The `bin` of an [`IndexTarget`] is calculated by a database trigger,
+/// which also increments the `generation` of the bin whenever one of its
+/// targets changes.
+#[derive(Debug, Clone, HasQuery)]
+#[diesel(table_name = index_target_bins)]
+pub struct IndexTargetBin {
+    pub bin: i16,
+    pub version: i64,
+    pub generation: i64,
+}
+
+impl IndexTargetBin {
+    /// Loads the bins whose targets have changed since their metadata was
+    /// last signed, or whose metadata was last signed before the given
+    /// date and time.
+    pub async fn needs_signing(
+        conn: &mut AsyncPgConnection,
+        signed_before: DateTime<Utc>,
+    ) -> QueryResult<Vec<Self>> {
+        Self::query()
+            .filter(
+                index_target_bins::generation
+                    .gt(index_target_bins::signed_generation)
+                    .or(index_target_bins::signed_at.is_null())
+                    .or(index_target_bins::signed_at.lt(signed_before)),
+            )
+            .order(index_target_bins::bin)
+            .load(conn)
+            .await
+    }
+
+    /// Loads the targets of this bin.
+    pub async fn targets(&self, conn: &mut AsyncPgConnection) -> QueryResult<Vec<IndexTarget>> {
+        IndexTarget::query()
+            .filter(index_targets::bin.eq(self.bin))
+            .load(conn)
+            .await
+    }
+
+    /// Records that the metadata of this bin was signed with the given
+    /// version, covering all changes up to the `generation` that this bin
+    /// was loaded with.
+    pub async fn mark_signed(&self, conn: &mut AsyncPgConnection, version: i64) -> QueryResult<()> {
+        diesel::update(index_target_bins::table.find(self.bin))
+            .set((
+                index_target_bins::version.eq(version),
+                index_target_bins::signed_generation.eq(self.generation),
+                index_target_bins::signed_at.eq(diesel::dsl::now),
+            ))
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Insertable, AsChangeset)]
+#[diesel(table_name = index_targets, check_for_backend(diesel::pg::Pg))]
+pub struct NewIndexTarget<'a> {
+    pub path: &'a str,
+    pub length: i64,
+    pub sha256: &'a [u8],
+    pub updated_at: DateTime<Utc>,
+}
+
+impl NewIndexTarget<'_> {
+    pub async fn create_or_update(&self, conn: &mut AsyncPgConnection) -> QueryResult<()> {
+        diesel::insert_into(index_targets::table)
+            .values(self)
+            .on_conflict(index_targets::path)
+            .do_update()
+            .set(self)
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Records the target unless the index file already has one, which was
+    /// recorded when the file was last uploaded. Returns `true` if the
+    /// target was recorded.
+    pub async fn create_if_missing(&self, conn: &mut AsyncPgConnection) -> QueryResult<bool> {
+        let inserted = diesel::insert_into(index_targets::table)
+            .values(self)
+            .on_conflict_do_nothing()
+            .execute(conn)
+            .await?;
+
+        Ok(inserted > 0)
+    }
+}
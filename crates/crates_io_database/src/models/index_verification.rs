@@ -0,0 +1,47 @@
+use crate::schema::index_verifications;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// The result of a comparison of the database with the git and sparse
+/// indexes.
+#[derive(Debug, Clone, HasQuery, Identifiable)]
+#[diesel(table_name = index_verifications)]
+pub struct IndexVerification {
+    pub id: i64,
+    pub created_at: DateTime<Utc>,
+    pub crates_checked: i32,
+    pub mismatches: serde_json::Value,
+}
+
+impl IndexVerification {
+    /// Loads the most recent verification result, if any.
+    pub async fn latest(conn: &mut AsyncPgConnection) -> QueryResult<Option<Self>> {
+        Self::query()
+            .order(index_verifications::id.desc())
+            .first(conn)
+            .await
+            .optional()
+    }
+
+    /// Returns the number of index files that did not match the database.
+    pub fn num_mismatches(&self) -> usize {
+        self.mismatches.as_array().map_or(0, Vec::len)
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = index_verifications, check_for_backend(diesel::pg::Pg))]
+pub struct NewIndexVerification {
+    pub crates_checked: i32,
+    pub mismatches: serde_json::Value,
+}
+
+impl NewIndexVerification {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<IndexVerification> {
+        self.insert_into(index_verifications::table)
+            .returning(IndexVerification::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
@@ -38,6 +38,9 @@ pub struct Crate {
     pub max_upload_size: Option<i32>,
     pub max_features: Option<i16>,
     pub trustpub_only: bool,
+    pub deprecated_at: Option<DateTime<Utc>>,
+    pub deprecation_reason: Option<String>,
+    pub deprecation_successor: Option<String>,
 }
 
 /// We literally never want to select `textsearchable_index_col`
@@ -54,6 +57,9 @@ type AllColumns = (
     crates::max_upload_size,
     crates::max_features,
     crates::trustpub_only,
+    crates::deprecated_at,
+    crates::deprecation_reason,
+    crates::deprecation_successor,
 );
 
 pub const ALL_COLUMNS: AllColumns = (
@@ -68,6 +74,9 @@ pub const ALL_COLUMNS: AllColumns = (
     crates::max_upload_size,
     crates::max_features,
     crates::trustpub_only,
+    crates::deprecated_at,
+    crates::deprecation_reason,
+    crates::deprecation_successor,
 );
 
 type All = diesel::dsl::Select<crates::table, diesel::dsl::AsSelect<Crate, diesel::pg::Pg>>;
@@ -1,4 +1,7 @@
-pub use self::action::{NewVersionOwnerAction, VersionAction, VersionOwnerAction};
+pub use self::action::{
+    CrateAction, CrateAuditAction, NewCrateAuditAction, NewVersionOwnerAction, VersionAction,
+    VersionOwnerAction,
+};
 pub use self::category::{Category, CrateCategory, NewCategory};
 pub use self::cloudfront_invalidation_queue::{
     CloudFrontDistribution, CloudFrontInvalidationQueueItem,
@@ -10,16 +13,24 @@ pub use self::default_versions::{update_default_version, verify_default_version}
 pub use self::deleted_crate::NewDeletedCrate;
 pub use self::dependency::{Dependency, DependencyKind, ReverseDependency};
 pub use self::download::VersionDownload;
+pub use self::download_anomaly::DownloadAnomaly;
 pub use self::email::{Email, NewEmail};
 pub use self::follow::Follow;
+pub use self::index_change::{IndexChange, IndexChangeReason, NewIndexChange};
+pub use self::index_target::{IndexTarget, IndexTargetBin, NewIndexTarget};
+pub use self::index_verification::{IndexVerification, NewIndexVerification};
 pub use self::keyword::{CrateKeyword, Keyword};
 pub use self::krate::{Crate, CrateName, NewCrate};
 pub use self::owner::{CrateOwner, Owner, OwnerKind};
+pub use self::staged_version::{NewStagedVersion, StagedVersion};
 pub use self::team::{NewTeam, Team};
 pub use self::token::ApiToken;
 pub use self::trustpub::TrustpubData;
 pub use self::user::{NewOauthGithub, NewUser, OauthGithub, User};
 pub use self::version::{NewVersion, TopVersions, Version};
+pub use self::webhook::{
+    CrateWebhook, CrateWebhookDelivery, NewCrateWebhook, NewCrateWebhookDelivery, WebhookEvent,
+};
 
 pub mod helpers;
 
@@ -31,14 +42,20 @@ pub mod default_versions;
 mod deleted_crate;
 pub mod dependency;
 pub mod download;
+mod download_anomaly;
 mod email;
 mod follow;
+mod index_change;
+mod index_target;
+mod index_verification;
 mod keyword;
 pub mod krate;
 mod owner;
+mod staged_version;
 pub mod team;
 pub mod token;
 pub mod trustpub;
 pub mod user;
 pub mod version;
 pub mod versions_published_by;
+mod webhook;
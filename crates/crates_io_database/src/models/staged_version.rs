@@ -0,0 +1,131 @@
+use crate::models::TrustpubData;
+use crate::schema::staged_versions;
+use chrono::{DateTime, Utc};
+use crates_io_diesel_helpers::canon_crate_name;
+use diesel::dsl::{exists, select};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// A version that has been uploaded in "staged" mode, but has not been
+/// released yet.
+///
+/// Staged versions are not part of the `versions` table, which means that
+/// they are not visible in the index, the search or any of the version
+/// listings until they are released.
+///
+/// Versions of crates that have not been published yet are stored with a
+/// `crate_name` instead of a `crate_id`. The `crates` row is only created
+/// once the version is released, but the name is reserved for the user that
+/// staged the version in the meantime.
+#[derive(Debug, Identifiable, HasQuery)]
+pub struct StagedVersion {
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub crate_id: Option<i32>,
+    pub crate_name: Option<String>,
+    pub num: String,
+    pub checksum: String,
+    pub readme: Option<String>,
+    pub readme_file: Option<String>,
+    pub published_by: Option<i32>,
+    pub publisher_email: Option<String>,
+    pub trustpub_data: Option<TrustpubData>,
+    pub team_id: Option<i32>,
+}
+
+impl StagedVersion {
+    /// Finds a staged version of an existing crate.
+    pub async fn find(
+        conn: &mut AsyncPgConnection,
+        crate_id: i32,
+        num: &str,
+    ) -> QueryResult<Option<Self>> {
+        Self::query()
+            .filter(staged_versions::crate_id.eq(crate_id))
+            .filter(staged_versions::num.eq(num))
+            .first(conn)
+            .await
+            .optional()
+    }
+
+    /// Finds a staged version of a crate that has not been published yet.
+    pub async fn find_by_crate_name(
+        conn: &mut AsyncPgConnection,
+        name: &str,
+        num: &str,
+    ) -> QueryResult<Option<Self>> {
+        Self::query()
+            .filter(
+                canon_crate_name(staged_versions::crate_name.assume_not_null())
+                    .eq(canon_crate_name(name)),
+            )
+            .filter(staged_versions::num.eq(num))
+            .first(conn)
+            .await
+            .optional()
+    }
+
+    /// Returns `true` if a user other than the given one has staged a
+    /// version of a crate with the given name that has not been published
+    /// yet, which reserves the name for that user.
+    pub async fn is_name_reserved_for_others(
+        conn: &mut AsyncPgConnection,
+        name: &str,
+        user_id: i32,
+    ) -> QueryResult<bool> {
+        select(exists(
+            staged_versions::table
+                .filter(
+                    canon_crate_name(staged_versions::crate_name.assume_not_null())
+                        .eq(canon_crate_name(name)),
+                )
+                .filter(staged_versions::published_by.is_distinct_from(user_id)),
+        ))
+        .get_result(conn)
+        .await
+    }
+
+    /// Moves the staged versions of a crate that has not been published yet
+    /// to the newly created `crates` row.
+    pub async fn assign_to_crate(
+        conn: &mut AsyncPgConnection,
+        name: &str,
+        crate_id: i32,
+    ) -> QueryResult<usize> {
+        diesel::update(staged_versions::table)
+            .filter(
+                canon_crate_name(staged_versions::crate_name.assume_not_null())
+                    .eq(canon_crate_name(name)),
+            )
+            .set((
+                staged_versions::crate_id.eq(crate_id),
+                staged_versions::crate_name.eq(None::<String>),
+            ))
+            .execute(conn)
+            .await
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = staged_versions, check_for_backend(diesel::pg::Pg))]
+pub struct NewStagedVersion<'a> {
+    pub crate_id: Option<i32>,
+    pub crate_name: Option<&'a str>,
+    pub num: &'a str,
+    pub checksum: &'a str,
+    pub readme: Option<&'a str>,
+    pub readme_file: Option<&'a str>,
+    pub published_by: Option<i32>,
+    pub publisher_email: Option<&'a str>,
+    pub trustpub_data: Option<&'a TrustpubData>,
+    pub team_id: Option<i32>,
+}
+
+impl NewStagedVersion<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<StagedVersion> {
+        self.insert_into(staged_versions::table)
+            .returning(StagedVersion::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
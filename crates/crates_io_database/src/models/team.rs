@@ -1,9 +1,10 @@
 use bon::Builder;
+use diesel::dsl::exists;
 use diesel::prelude::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 
 use crate::models::{Crate, CrateOwner, Owner, OwnerKind};
-use crate::schema::{crate_owners, teams};
+use crate::schema::{crate_owners, crates, teams};
 
 /// For now, just a GitHub Team. Can be upgraded to other teams
 /// later if desirable.
@@ -66,6 +67,23 @@ impl Team {
         Ok(teams.collect())
     }
 
+    /// Returns `true` if the team with the given ID is an owner of the crate
+    /// with the given name.
+    pub async fn is_crate_owner(
+        conn: &mut AsyncPgConnection,
+        team_id: i32,
+        crate_name: &str,
+    ) -> QueryResult<bool> {
+        let query = crate_owners::table
+            .inner_join(crates::table)
+            .filter(Crate::with_name(crate_name))
+            .filter(crate_owners::owner_id.eq(team_id))
+            .filter(crate_owners::owner_kind.eq(OwnerKind::Team))
+            .filter(crate_owners::deleted.eq(false));
+
+        diesel::select(exists(query)).get_result(conn).await
+    }
+
     /// Splits the login into provider, organization, and team name.
     ///
     /// Returns `None` if the login format is invalid.
@@ -8,9 +8,9 @@ use diesel::sql_types::Timestamptz;
 use diesel_async::scoped_futures::ScopedFutureExt;
 use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 
-pub use self::scopes::{CrateScope, EndpointScope};
+pub use self::scopes::{CrateScope, EndpointScope, IpRange};
 use crate::models::User;
-use crate::schema::api_tokens;
+use crate::schema::{api_token_usages, api_tokens};
 use crate::utils::token::{HashedToken, PlainToken};
 
 #[derive(Debug, Insertable, Builder)]
@@ -26,6 +26,10 @@ pub struct NewApiToken {
     /// A list of endpoint scopes or `None` for the `legacy` endpoint scope (see RFC #2947)
     pub endpoint_scopes: Option<Vec<EndpointScope>>,
     pub expired_at: Option<DateTime<Utc>>,
+    /// `None` or a list of IP ranges that the token may be used from
+    pub allowed_ips: Option<Vec<IpRange>>,
+    /// `None` for personal tokens, or the team that owns the token
+    pub team_id: Option<i32>,
 }
 
 impl NewApiToken {
@@ -75,6 +79,17 @@ pub struct ApiToken {
     /// The date and time when the token will expire, or `null`.
     #[schema(example = "2030-10-26T11:32:12Z")]
     pub expired_at: Option<DateTime<Utc>>,
+
+    /// `None` or a list of CIDR ranges that the token may be used from.
+    #[schema(value_type = Option<Vec<String>>, example = json!(["192.0.2.0/24"]))]
+    pub allowed_ips: Option<Vec<IpRange>>,
+
+    /// The ID of the team that owns the token, or `null` for personal tokens.
+    #[schema(example = 42)]
+    pub team_id: Option<i32>,
+
+    #[serde(skip)]
+    pub team_membership_verified_at: Option<DateTime<Utc>>,
 }
 
 impl ApiToken {
@@ -112,6 +127,91 @@ impl ApiToken {
     }
 }
 
+/// The model representing a row in the `api_token_usages` database table.
+#[derive(Debug, Identifiable, HasQuery, serde::Serialize, utoipa::ToSchema)]
+pub struct ApiTokenUsage {
+    #[serde(skip)]
+    pub id: i64,
+
+    #[serde(skip)]
+    pub api_token_id: i32,
+
+    /// The date and time when the token was last used from this IP
+    /// address, for this endpoint and with this user agent.
+    #[schema(example = "2021-10-26T11:32:12Z")]
+    pub last_used_at: DateTime<Utc>,
+
+    /// The number of requests from this IP address, for this endpoint and
+    /// with this user agent.
+    #[schema(example = 42)]
+    pub request_count: i32,
+
+    /// The IP address of the client that used the token.
+    #[schema(example = "192.0.2.42")]
+    pub ip_address: String,
+
+    /// The HTTP method and path of the request that used the token.
+    #[schema(example = "PUT /api/v1/crates/new")]
+    pub endpoint: String,
+
+    /// The user agent of the client that used the token, if available.
+    #[schema(example = "cargo/1.90.0 (840b83a10 2025-07-30)")]
+    pub user_agent: Option<String>,
+}
+
+impl ApiTokenUsage {
+    /// Returns the most recently used combinations of IP address, endpoint
+    /// and user agent of the given API token, newest first.
+    pub async fn recent(
+        conn: &mut AsyncPgConnection,
+        api_token_id: i32,
+        limit: i64,
+    ) -> QueryResult<Vec<ApiTokenUsage>> {
+        ApiTokenUsage::query()
+            .filter(api_token_usages::api_token_id.eq(api_token_id))
+            .order((
+                api_token_usages::last_used_at.desc(),
+                api_token_usages::id.desc(),
+            ))
+            .limit(limit)
+            .load(conn)
+            .await
+    }
+}
+
+#[derive(Debug, Insertable, Builder)]
+#[diesel(table_name = api_token_usages, check_for_backend(diesel::pg::Pg))]
+pub struct NewApiTokenUsage<'a> {
+    pub api_token_id: i32,
+    pub ip_address: &'a str,
+    pub endpoint: &'a str,
+    pub user_agent: Option<&'a str>,
+}
+
+impl NewApiTokenUsage<'_> {
+    /// Inserts the usage, or increments the request count of an existing
+    /// entry with the same IP address, endpoint and user agent.
+    pub async fn upsert(&self, conn: &mut AsyncPgConnection) -> QueryResult<usize> {
+        use diesel::dsl::now;
+
+        diesel::insert_into(api_token_usages::table)
+            .values(self)
+            .on_conflict((
+                api_token_usages::api_token_id,
+                api_token_usages::ip_address,
+                api_token_usages::endpoint,
+                api_token_usages::user_agent,
+            ))
+            .do_update()
+            .set((
+                api_token_usages::last_used_at.eq(now),
+                api_token_usages::request_count.eq(api_token_usages::request_count + 1),
+            ))
+            .execute(conn)
+            .await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -140,6 +240,9 @@ mod tests {
             crate_scopes: None,
             endpoint_scopes: None,
             expired_at: None,
+            allowed_ips: None,
+            team_id: None,
+            team_membership_verified_at: None,
         };
         let json = serde_json::to_string(&tok).unwrap();
         assert_some!(json.as_str().find(r#""created_at":"2017-01-06T14:23:11Z""#));
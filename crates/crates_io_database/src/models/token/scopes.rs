@@ -3,7 +3,10 @@ use diesel::deserialize::{self, FromSql};
 use diesel::pg::Pg;
 use diesel::serialize::{self, IsNull, Output, ToSql};
 use diesel::sql_types::Text;
+use ipnetwork::IpNetwork;
 use std::io::Write;
+use std::net::IpAddr;
+use std::str::FromStr;
 
 #[derive(
     Clone, Copy, Debug, PartialEq, Eq, diesel::AsExpression, serde::Serialize, utoipa::ToSchema,
@@ -11,11 +14,41 @@ use std::io::Write;
 #[diesel(sql_type = Text)]
 #[serde(rename_all = "kebab-case")]
 pub enum EndpointScope {
+    /// Publish new crates.
     PublishNew,
+    /// Publish new versions of existing crates.
     PublishUpdate,
+    /// Release and download staged crate versions.
+    PublishRelease,
+    /// Deprecated alias of [`EndpointScope::ManageTrustpub`], kept for
+    /// tokens that were created before the scope was renamed.
     TrustedPublishing,
+    /// Yank and unyank crate versions.
     Yank,
+    /// Invite new crate owners, remove existing ones, and accept or decline
+    /// crate owner invitations.
     ChangeOwners,
+    /// Read private information like crate owner invitations, audit logs or
+    /// webhook deliveries.
+    ReadPrivate,
+    /// Update crate and version metadata like deprecations, yank messages
+    /// and webhooks.
+    UpdateMetadata,
+    /// Manage Trusted Publishing configurations and settings.
+    ManageTrustpub,
+    /// Delete crates.
+    Delete,
+}
+
+impl EndpointScope {
+    /// Returns `true` if a token with this scope may access endpoints that
+    /// require the `required` scope.
+    pub fn includes(self, required: EndpointScope) -> bool {
+        match (self, required) {
+            (EndpointScope::TrustedPublishing, EndpointScope::ManageTrustpub) => true,
+            (scope, required) => scope == required,
+        }
+    }
 }
 
 impl From<&EndpointScope> for &[u8] {
@@ -23,9 +56,14 @@ impl From<&EndpointScope> for &[u8] {
         match scope {
             EndpointScope::PublishNew => b"publish-new",
             EndpointScope::PublishUpdate => b"publish-update",
+            EndpointScope::PublishRelease => b"publish-release",
             EndpointScope::TrustedPublishing => b"trusted-publishing",
             EndpointScope::Yank => b"yank",
             EndpointScope::ChangeOwners => b"change-owners",
+            EndpointScope::ReadPrivate => b"read-private",
+            EndpointScope::UpdateMetadata => b"update-metadata",
+            EndpointScope::ManageTrustpub => b"manage-trustpub",
+            EndpointScope::Delete => b"delete",
         }
     }
 }
@@ -44,9 +82,14 @@ impl TryFrom<&[u8]> for EndpointScope {
         match bytes {
             b"publish-new" => Ok(EndpointScope::PublishNew),
             b"publish-update" => Ok(EndpointScope::PublishUpdate),
+            b"publish-release" => Ok(EndpointScope::PublishRelease),
             b"trusted-publishing" => Ok(EndpointScope::TrustedPublishing),
             b"yank" => Ok(EndpointScope::Yank),
             b"change-owners" => Ok(EndpointScope::ChangeOwners),
+            b"read-private" => Ok(EndpointScope::ReadPrivate),
+            b"update-metadata" => Ok(EndpointScope::UpdateMetadata),
+            b"manage-trustpub" => Ok(EndpointScope::ManageTrustpub),
+            b"delete" => Ok(EndpointScope::Delete),
             _ => Err("Unrecognized enum variant".to_string()),
         }
     }
@@ -128,6 +171,57 @@ impl CrateScope {
     }
 }
 
+/// A CIDR range (e.g. `192.0.2.0/24` or `2001:db8::/32`) that an API token
+/// may be used from. Single IP addresses are treated as ranges containing only
+/// that address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpRange(IpNetwork);
+
+impl TryFrom<&str> for IpRange {
+    type Error = String;
+
+    fn try_from(range: &str) -> Result<Self, Self::Error> {
+        IpNetwork::from_str(range)
+            .map(IpRange)
+            .map_err(|_| "Invalid IP range".to_string())
+    }
+}
+
+impl TryFrom<String> for IpRange {
+    type Error = String;
+
+    fn try_from(range: String) -> Result<Self, Self::Error> {
+        IpRange::try_from(range.as_str())
+    }
+}
+
+impl FromSql<Text, Pg> for IpRange {
+    fn from_sql(bytes: diesel::pg::PgValue<'_>) -> deserialize::Result<Self> {
+        let value = <String as FromSql<Text, Pg>>::from_sql(bytes)?;
+        Ok(IpRange::try_from(value)?)
+    }
+}
+
+impl ToSql<Text, Pg> for IpRange {
+    fn to_sql(&self, out: &mut Output<'_, '_, Pg>) -> serialize::Result {
+        write!(out, "{}", self.0)?;
+        Ok(IsNull::No)
+    }
+}
+
+impl serde::Serialize for IpRange {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+impl IpRange {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        // IPv4 clients connecting via IPv6 show up as IPv4-mapped addresses
+        self.0.contains(ip.to_canonical())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -143,8 +237,27 @@ mod tests {
         assert(EndpointScope::ChangeOwners, "\"change-owners\"");
         assert(EndpointScope::PublishNew, "\"publish-new\"");
         assert(EndpointScope::PublishUpdate, "\"publish-update\"");
+        assert(EndpointScope::PublishRelease, "\"publish-release\"");
         assert(EndpointScope::TrustedPublishing, "\"trusted-publishing\"");
         assert(EndpointScope::Yank, "\"yank\"");
+        assert(EndpointScope::ReadPrivate, "\"read-private\"");
+        assert(EndpointScope::UpdateMetadata, "\"update-metadata\"");
+        assert(EndpointScope::ManageTrustpub, "\"manage-trustpub\"");
+        assert(EndpointScope::Delete, "\"delete\"");
+    }
+
+    #[googletest::test]
+    fn endpoint_scope_includes() {
+        use EndpointScope::*;
+
+        expect_that!(Yank.includes(Yank), eq(true));
+        expect_that!(Yank.includes(UpdateMetadata), eq(false));
+        expect_that!(ManageTrustpub.includes(ManageTrustpub), eq(true));
+        expect_that!(ManageTrustpub.includes(TrustedPublishing), eq(false));
+
+        // `trusted-publishing` is a deprecated alias of `manage-trustpub`
+        expect_that!(TrustedPublishing.includes(ManageTrustpub), eq(true));
+        expect_that!(TrustedPublishing.includes(TrustedPublishing), eq(true));
     }
 
     #[googletest::test]
@@ -213,4 +326,46 @@ mod tests {
         expect_that!(scope("foo_*").matches("foo-bar"), eq(false));
         expect_that!(scope("foo_*").matches("foo_bar"), eq(true));
     }
+
+    #[googletest::test]
+    fn ip_range_serialization() {
+        fn assert(range: &str, expected: &str) {
+            let range = assert_ok!(IpRange::try_from(range));
+            expect_that!(serde_json::to_string(&range), ok(eq(expected)));
+        }
+
+        assert("192.0.2.0/24", "\"192.0.2.0/24\"");
+        assert("192.0.2.1", "\"192.0.2.1/32\"");
+        assert("2001:db8::/32", "\"2001:db8::/32\"");
+    }
+
+    #[googletest::test]
+    fn ip_range_validation() {
+        expect_that!(IpRange::try_from("192.0.2.0/24"), ok(anything()));
+        expect_that!(IpRange::try_from("192.0.2.1"), ok(anything()));
+        expect_that!(IpRange::try_from("2001:db8::/32"), ok(anything()));
+
+        expect_that!(IpRange::try_from(""), err(anything()));
+        expect_that!(IpRange::try_from("192.0.2.0/33"), err(anything()));
+        expect_that!(IpRange::try_from("example.com"), err(anything()));
+    }
+
+    #[googletest::test]
+    fn ip_range_matching() {
+        let range = |range: &str| IpRange::try_from(range).unwrap();
+        let ip = |ip: &str| IpAddr::from_str(ip).unwrap();
+
+        expect_that!(range("192.0.2.0/24").contains(ip("192.0.2.42")), eq(true));
+        expect_that!(range("192.0.2.0/24").contains(ip("192.0.3.1")), eq(false));
+        expect_that!(range("192.0.2.1").contains(ip("192.0.2.1")), eq(true));
+        expect_that!(range("192.0.2.1").contains(ip("192.0.2.2")), eq(false));
+        expect_that!(range("2001:db8::/32").contains(ip("2001:db8::1")), eq(true));
+        expect_that!(range("2001:db8::/32").contains(ip("192.0.2.1")), eq(false));
+
+        // IPv4-mapped IPv6 addresses
+        expect_that!(
+            range("192.0.2.0/24").contains(ip("::ffff:192.0.2.1")),
+            eq(true)
+        );
+    }
 }
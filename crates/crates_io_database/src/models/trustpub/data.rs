@@ -27,6 +27,18 @@ pub enum TrustpubData {
         job_id: String,
         /// SHA of the commit
         sha: String,
+        /// OIDC issuer URL of the self-hosted GitLab instance (`None` for gitlab.com)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        issuer: Option<String>,
+    },
+    #[serde(rename = "forgejo")]
+    Forgejo {
+        /// Repository (e.g. "octo-org/octo-repo")
+        repository: String,
+        /// Workflow run ID
+        run_id: String,
+        /// SHA of the commit
+        sha: String,
     },
 }
 
@@ -73,6 +85,26 @@ mod tests {
             project_path: "rust-lang/cargo".to_string(),
             job_id: "example-job-id".to_string(),
             sha: "example-sha".to_string(),
+            issuer: None,
+        };
+
+        assert_json_snapshot!(data, @r#"
+        {
+          "provider": "gitlab",
+          "project_path": "rust-lang/cargo",
+          "job_id": "example-job-id",
+          "sha": "example-sha"
+        }
+        "#);
+    }
+
+    #[test]
+    fn test_gitlab_self_hosted_serialization() {
+        let data = TrustpubData::GitLab {
+            project_path: "rust-lang/cargo".to_string(),
+            job_id: "example-job-id".to_string(),
+            sha: "example-sha".to_string(),
+            issuer: Some("https://gitlab.example.com".to_string()),
         };
 
         assert_json_snapshot!(data, @r#"
@@ -80,6 +112,25 @@ mod tests {
           "provider": "gitlab",
           "project_path": "rust-lang/cargo",
           "job_id": "example-job-id",
+          "sha": "example-sha",
+          "issuer": "https://gitlab.example.com"
+        }
+        "#);
+    }
+
+    #[test]
+    fn test_forgejo_serialization() {
+        let data = TrustpubData::Forgejo {
+            repository: "octo-org/octo-repo".to_string(),
+            run_id: "example-run-id".to_string(),
+            sha: "example-sha".to_string(),
+        };
+
+        assert_json_snapshot!(data, @r#"
+        {
+          "provider": "forgejo",
+          "repository": "octo-org/octo-repo",
+          "run_id": "example-run-id",
           "sha": "example-sha"
         }
         "#);
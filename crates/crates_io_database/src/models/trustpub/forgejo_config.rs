@@ -0,0 +1,97 @@
+use crate::schema::trustpub_configs_forgejo;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::Serialize;
+
+#[derive(Debug, Identifiable, HasQuery, Serialize)]
+#[diesel(table_name = trustpub_configs_forgejo)]
+pub struct ForgejoConfig {
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub crate_id: i32,
+    pub repository_owner: String,
+    pub repository_owner_id: Option<String>,
+    pub repository_name: String,
+    pub workflow_filename: String,
+}
+
+impl ForgejoConfig {
+    pub async fn count_for_crate(conn: &mut AsyncPgConnection, crate_id: i32) -> QueryResult<i64> {
+        trustpub_configs_forgejo::table
+            .filter(trustpub_configs_forgejo::crate_id.eq(crate_id))
+            .count()
+            .get_result(conn)
+            .await
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = trustpub_configs_forgejo, check_for_backend(diesel::pg::Pg))]
+pub struct NewForgejoConfig<'a> {
+    pub crate_id: i32,
+    pub repository_owner: &'a str,
+    pub repository_name: &'a str,
+    pub workflow_filename: &'a str,
+}
+
+impl NewForgejoConfig<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<ForgejoConfig> {
+        self.insert_into(trustpub_configs_forgejo::table)
+            .returning(ForgejoConfig::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::krate::*;
+    use crate::schema::crates;
+    use crates_io_test_db::TestDatabase;
+    use diesel_async::RunQueryDsl;
+    use insta::assert_debug_snapshot;
+
+    #[tokio::test]
+    async fn test_forgejo_config_insert_and_retrieve() {
+        let test_db = TestDatabase::new();
+        let mut conn = test_db.async_connect().await;
+
+        let test_crate = diesel::insert_into(crates::table)
+            .values((crates::name.eq("test-crate"),))
+            .returning(Crate::as_returning())
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+
+        let new_config = NewForgejoConfig {
+            crate_id: test_crate.id,
+            repository_owner: "rust-lang",
+            repository_name: "cargo",
+            workflow_filename: "release.yml",
+        };
+
+        let inserted_config = new_config.insert(&mut conn).await.unwrap();
+
+        let retrieved_config = ForgejoConfig::query()
+            .filter(trustpub_configs_forgejo::id.eq(inserted_config.id))
+            .first(&mut conn)
+            .await
+            .unwrap();
+
+        insta::with_settings!({ filters => vec![(r"\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d+)?Z", "[datetime]")] }, {
+            assert_debug_snapshot!(retrieved_config, @r#"
+            ForgejoConfig {
+                id: 1,
+                created_at: [datetime],
+                crate_id: 1,
+                repository_owner: "rust-lang",
+                repository_owner_id: None,
+                repository_name: "cargo",
+                workflow_filename: "release.yml",
+            }
+            "#);
+        });
+    }
+}
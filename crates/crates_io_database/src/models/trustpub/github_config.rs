@@ -15,6 +15,8 @@ pub struct GitHubConfig {
     pub repository_name: String,
     pub workflow_filename: String,
     pub environment: Option<String>,
+    pub ref_pattern: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 impl GitHubConfig {
@@ -36,6 +38,8 @@ pub struct NewGitHubConfig<'a> {
     pub repository_name: &'a str,
     pub workflow_filename: &'a str,
     pub environment: Option<&'a str>,
+    pub ref_pattern: Option<&'a str>,
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 impl NewGitHubConfig<'_> {
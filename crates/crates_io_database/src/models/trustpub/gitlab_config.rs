@@ -15,6 +15,9 @@ pub struct GitLabConfig {
     pub project: String,
     pub workflow_filepath: String,
     pub environment: Option<String>,
+    pub issuer: String,
+    pub ref_pattern: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 impl GitLabConfig {
@@ -35,6 +38,9 @@ pub struct NewGitLabConfig<'a> {
     pub project: &'a str,
     pub workflow_filepath: &'a str,
     pub environment: Option<&'a str>,
+    pub issuer: &'a str,
+    pub ref_pattern: Option<&'a str>,
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 impl NewGitLabConfig<'_> {
@@ -75,6 +81,9 @@ mod tests {
             project: "cargo",
             workflow_filepath: ".gitlab-ci.yml",
             environment: Some("production"),
+            issuer: "https://gitlab.com",
+            ref_pattern: None,
+            expires_at: None,
         };
 
         // Insert the config
@@ -101,6 +110,9 @@ mod tests {
                 environment: Some(
                     "production",
                 ),
+                issuer: "https://gitlab.com",
+                ref_pattern: None,
+                expires_at: None,
             }
             "#);
         });
@@ -1,10 +1,12 @@
 mod data;
+mod forgejo_config;
 mod github_config;
 mod gitlab_config;
 mod token;
 mod used_jti;
 
 pub use self::data::TrustpubData;
+pub use self::forgejo_config::{ForgejoConfig, NewForgejoConfig};
 pub use self::github_config::{GitHubConfig, NewGitHubConfig};
 pub use self::gitlab_config::{GitLabConfig, NewGitLabConfig};
 pub use self::token::NewToken;
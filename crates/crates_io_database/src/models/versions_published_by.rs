@@ -5,12 +5,14 @@ use diesel_async::{AsyncPgConnection, RunQueryDsl};
 pub async fn insert(
     version_id: i32,
     email: &str,
+    team_id: Option<i32>,
     conn: &mut AsyncPgConnection,
 ) -> QueryResult<usize> {
     diesel::insert_into(versions_published_by::table)
         .values((
             versions_published_by::version_id.eq(version_id),
             versions_published_by::email.eq(email),
+            versions_published_by::team_id.eq(team_id),
         ))
         .execute(conn)
         .await
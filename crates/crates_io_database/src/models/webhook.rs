@@ -0,0 +1,151 @@
+use crate::schema::{crate_webhook_deliveries, crate_webhooks};
+use chrono::{DateTime, Utc};
+use diesel::deserialize::{self, FromSql};
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::sql_types::Text;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::io::Write;
+
+/// A crate event that webhooks can subscribe to.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    diesel::AsExpression,
+    diesel::FromSqlRow,
+    serde::Serialize,
+    serde::Deserialize,
+    utoipa::ToSchema,
+)]
+#[diesel(sql_type = Text)]
+#[serde(rename_all = "kebab-case")]
+pub enum WebhookEvent {
+    Publish,
+    Yank,
+    Unyank,
+    OwnerChange,
+    Delete,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::Publish => "publish",
+            WebhookEvent::Yank => "yank",
+            WebhookEvent::Unyank => "unyank",
+            WebhookEvent::OwnerChange => "owner-change",
+            WebhookEvent::Delete => "delete",
+        }
+    }
+}
+
+impl ToSql<Text, Pg> for WebhookEvent {
+    fn to_sql(&self, out: &mut Output<'_, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl TryFrom<&str> for WebhookEvent {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "publish" => Ok(WebhookEvent::Publish),
+            "yank" => Ok(WebhookEvent::Yank),
+            "unyank" => Ok(WebhookEvent::Unyank),
+            "owner-change" => Ok(WebhookEvent::OwnerChange),
+            "delete" => Ok(WebhookEvent::Delete),
+            _ => Err("Unrecognized enum variant".to_string()),
+        }
+    }
+}
+
+impl FromSql<Text, Pg> for WebhookEvent {
+    fn from_sql(bytes: diesel::pg::PgValue<'_>) -> deserialize::Result<Self> {
+        let value = <String as FromSql<Text, Pg>>::from_sql(bytes)?;
+        Ok(WebhookEvent::try_from(value.as_str())?)
+    }
+}
+
+/// An outbound webhook endpoint that is notified about events of a crate.
+#[derive(Debug, Identifiable, HasQuery)]
+pub struct CrateWebhook {
+    pub id: i32,
+    /// `None` if the crate has been deleted, but the deletion event has not
+    /// been delivered yet.
+    pub crate_id: Option<i32>,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<WebhookEvent>,
+    pub created_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub consecutive_failures: i32,
+    pub disabled_at: Option<DateTime<Utc>>,
+}
+
+impl CrateWebhook {
+    pub async fn count_for_crate(conn: &mut AsyncPgConnection, crate_id: i32) -> QueryResult<i64> {
+        crate_webhooks::table
+            .filter(crate_webhooks::crate_id.eq(crate_id))
+            .count()
+            .get_result(conn)
+            .await
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate_webhooks, check_for_backend(diesel::pg::Pg))]
+pub struct NewCrateWebhook<'a> {
+    pub crate_id: i32,
+    pub url: &'a str,
+    pub secret: &'a str,
+    pub events: Vec<WebhookEvent>,
+    pub created_by: i32,
+}
+
+impl NewCrateWebhook<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<CrateWebhook> {
+        self.insert_into(crate_webhooks::table)
+            .returning(CrateWebhook::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
+
+/// A single event that is (or was) delivered to a [`CrateWebhook`].
+#[derive(Debug, Identifiable, HasQuery)]
+#[diesel(table_name = crate_webhook_deliveries)]
+pub struct CrateWebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i32,
+    pub event: WebhookEvent,
+    pub payload: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub attempts: i32,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate_webhook_deliveries, check_for_backend(diesel::pg::Pg))]
+pub struct NewCrateWebhookDelivery<'a> {
+    pub webhook_id: i32,
+    pub event: WebhookEvent,
+    pub payload: &'a serde_json::Value,
+}
+
+impl NewCrateWebhookDelivery<'_> {
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> QueryResult<CrateWebhookDelivery> {
+        self.insert_into(crate_webhook_deliveries::table)
+            .returning(CrateWebhookDelivery::as_returning())
+            .get_result(conn)
+            .await
+    }
+}
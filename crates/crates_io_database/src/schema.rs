@@ -12,6 +12,29 @@ pub mod sql_types {
     pub struct Ltree;
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Rolling log of API token usages, with one row per token, IP address, endpoint and user agent, pruned after a retention period
+    api_token_usages (id) {
+        /// Unique identifier of the API token that was used
+        api_token_id -> Int4,
+        /// HTTP method and path of the request that used the token (e.g. `PUT /api/v1/crates/new`)
+        endpoint -> Varchar,
+        /// Unique identifier of the `api_token_usages` row
+        id -> Int8,
+        /// IP address of the client that used the token
+        ip_address -> Varchar,
+        /// Date and time when the token was last used with this combination of IP address, endpoint and user agent
+        last_used_at -> Timestamptz,
+        /// Number of requests with this combination of IP address, endpoint and user agent
+        request_count -> Int4,
+        /// User agent of the client that used the token, if available
+        user_agent -> Nullable<Varchar>,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -20,6 +43,8 @@ diesel::table! {
     ///
     /// (Automatically generated by Diesel.)
     api_tokens (id) {
+        /// NULL or an array of CIDR ranges that the token may be used from
+        allowed_ips -> Nullable<Array<Text>>,
         /// NULL or an array of crate scope patterns (see RFC #2947)
         crate_scopes -> Nullable<Array<Text>>,
         /// The `created_at` column of the `api_tokens` table.
@@ -62,6 +87,10 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         revoked -> Bool,
+        /// Unique identifier of the team that owns the token, or NULL for personal tokens. For team tokens, `user_id` refers to the team maintainer that created the token.
+        team_id -> Nullable<Int4>,
+        /// Date and time when the team membership of the token creator was last verified (only used for team tokens)
+        team_membership_verified_at -> Nullable<Timestamptz>,
         /// The `token` column of the `api_tokens` table.
         ///
         /// Its SQL type is `Bytea`.
@@ -130,6 +159,23 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Daily download counts of a crate, broken down by the cargo version that downloaded it. Filled by the `process_cdn_log` background job.
+    cargo_version_downloads (crate_id, date, cargo_version) {
+        /// Minor version of cargo (e.g. `1.75`) that downloaded the crate, or `other` if the client could not be determined
+        cargo_version -> Varchar,
+        /// Reference to the crate that was downloaded
+        crate_id -> Int4,
+        /// Date on which the downloads happened
+        date -> Date,
+        /// Number of downloads of the crate by this cargo version on this date
+        downloads -> Int8,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -195,6 +241,50 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Audit log of owner, settings and Trusted Publishing changes made to crates
+    crate_audit_actions (id) {
+        /// Type of the action (see `CrateAction` enum)
+        action -> Int4,
+        /// Unique identifier of the API token that was used to perform the action, if any
+        api_token_id -> Nullable<Int4>,
+        /// Unique identifier of the crate that the action was performed on (`NULL` if the crate has been deleted)
+        crate_id -> Nullable<Int4>,
+        /// Name of the crate that the action was performed on
+        crate_name -> Varchar,
+        /// JSONB data with action-specific details
+        details -> Jsonb,
+        /// Unique identifier of the `crate_audit_actions` row
+        id -> Int4,
+        /// Date and time when the action was performed
+        time -> Timestamptz,
+        /// JSONB data containing JWT claims from the trusted publisher that performed the action, if any
+        trustpub_data -> Nullable<Jsonb>,
+        /// Unique identifier of the user that performed the action (`NULL` for Trusted Publishing)
+        user_id -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Weekly and monthly download counts of a crate, rolled up from `version_downloads` by the `roll_up_version_downloads` background job before the rows are archived.
+    crate_download_rollups (crate_id, granularity, period_start) {
+        /// Reference to the crate that was downloaded
+        crate_id -> Int4,
+        /// Number of downloads of the crate within the period
+        downloads -> Int8,
+        /// Length of the period, either `week` or `month`
+        granularity -> Varchar,
+        /// First day of the period (Monday for weeks, the first of the month for months)
+        period_start -> Date,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -302,6 +392,100 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Daily download counts of a crate, broken down by the coarse host platform of the client and the region of the CDN point of presence that served the download. Filled by the `process_cdn_log` background job.
+    crate_platform_downloads (crate_id, date, host_os, host_arch, cdn_region) {
+        /// Continent-level region of the CDN point of presence (e.g. `europe`), or `unknown` if it was not logged
+        cdn_region -> Varchar,
+        /// Reference to the crate that was downloaded
+        crate_id -> Int4,
+        /// Date on which the downloads happened
+        date -> Date,
+        /// Number of downloads of the crate from this platform and region on this date
+        downloads -> Int8,
+        /// CPU architecture of the host (e.g. `x86_64`), or `unknown` if the user agent did not contain a target triple
+        host_arch -> Varchar,
+        /// Operating system of the host (e.g. `linux`), or `unknown` if the user agent did not contain a target triple
+        host_os -> Varchar,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Precomputed popularity scores that are used to weight the text rank of the search results. Refreshed by the `update_search_scores` background job.
+    crate_search_scores (crate_id) {
+        /// Reference to the crate that this row belongs to
+        crate_id -> Int4,
+        /// Date and time of the latest non-yanked release of the crate, or NULL if all versions are yanked
+        last_release_at -> Nullable<Timestamptz>,
+        /// Number of crates whose default version depends on this crate
+        reverse_dependencies -> Int8,
+        /// Factor that the text rank of the crate is multiplied with when sorting by relevance
+        score -> Float4,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Delivery log of the events that were sent to `crate_webhooks`
+    crate_webhook_deliveries (id) {
+        /// Number of delivery attempts so far
+        attempts -> Int4,
+        /// Date and time when the event occurred
+        created_at -> Timestamptz,
+        /// Date and time when the event was successfully delivered, if it was
+        delivered_at -> Nullable<Timestamptz>,
+        /// Error message of the last failed delivery attempt, if any
+        error -> Nullable<Varchar>,
+        /// Type of the event (see `WebhookEvent` enum)
+        event -> Text,
+        /// Unique identifier of the `crate_webhook_deliveries` row
+        id -> Int8,
+        /// Date and time of the last delivery attempt, if any
+        last_attempt_at -> Nullable<Timestamptz>,
+        /// JSONB payload that is sent to the webhook
+        payload -> Jsonb,
+        /// HTTP status code of the response to the last delivery attempt, if any
+        response_status -> Nullable<Int4>,
+        /// Unique identifier of the webhook that the event is delivered to
+        webhook_id -> Int4,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Outbound webhook endpoints that are notified about crate events
+    crate_webhooks (id) {
+        /// Number of failed delivery attempts since the last successful delivery
+        consecutive_failures -> Int4,
+        /// Unique identifier of the crate that the webhook belongs to (`NULL` if the crate has been deleted and the deletion event is still being delivered)
+        crate_id -> Nullable<Int4>,
+        /// Date and time when the webhook was created
+        created_at -> Timestamptz,
+        /// Unique identifier of the user that created the webhook
+        created_by -> Nullable<Int4>,
+        /// Date and time when the webhook was disabled, either manually or due to repeated delivery failures
+        disabled_at -> Nullable<Timestamptz>,
+        /// Events that the webhook is subscribed to (see `WebhookEvent` enum)
+        events -> Array<Text>,
+        /// Unique identifier of the `crate_webhooks` row
+        id -> Int4,
+        /// Shared secret that is used to sign the event payloads with HMAC-SHA256
+        secret -> Varchar,
+        /// URL that the event payloads are sent to
+        url -> Varchar,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -316,6 +500,12 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         created_at -> Timestamptz,
+        /// Date and time when this crate was marked as deprecated by its owners, or NULL if the crate is not deprecated
+        deprecated_at -> Nullable<Timestamptz>,
+        /// Explanation given by the owners for why this crate is deprecated
+        deprecation_reason -> Nullable<Varchar>,
+        /// Name of the crate that should be used instead of this deprecated crate, if any
+        deprecation_successor -> Nullable<Varchar>,
         /// The `description` column of the `crates` table.
         ///
         /// Its SQL type is `Nullable<Varchar>`.
@@ -542,6 +732,41 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Anomalous daily download counts of a version that were flagged by the `detect_download_anomalies` background job for review by an admin
+    download_anomalies (id) {
+        /// Date and time when the anomaly was detected
+        created_at -> Timestamptz,
+        /// Date of the anomalous download count
+        date -> Date,
+        /// Number of downloads of the version on this date at the time of detection
+        downloads -> Int8,
+        /// Number of downloads that were subtracted from the download counts, or NULL if the downloads were not excluded
+        excluded_downloads -> Nullable<Int8>,
+        /// Mean number of daily downloads of the version in the weeks before this date
+        expected_downloads -> Int8,
+        /// Unique identifier of the anomaly
+        id -> Int8,
+        /// Why the download count was flagged: `spike` for a statistically significant increase, `concentrated_source` if most downloads came from a single IP network prefix
+        reason -> Varchar,
+        /// Date and time when the anomaly was reviewed by an admin, or NULL if it has not been reviewed yet
+        reviewed_at -> Nullable<Timestamptz>,
+        /// IP network prefix with the most downloads of the version on this date
+        top_ip_prefix -> Nullable<Varchar>,
+        /// Number of downloads from `top_ip_prefix`
+        top_ip_prefix_downloads -> Nullable<Int8>,
+        /// User agent with the most downloads of the version on this date
+        top_user_agent -> Nullable<Varchar>,
+        /// Number of downloads from `top_user_agent`
+        top_user_agent_downloads -> Nullable<Int8>,
+        /// Reference to the version with the anomalous download count
+        version_id -> Int4,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -612,6 +837,82 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Append-only log of sparse index file modifications, used by mirrors to sync the index incrementally
+    index_changes (id) {
+        /// Date and time when the index file was modified
+        changed_at -> Timestamptz,
+        /// Name of the crate whose index file was modified
+        crate_name -> Varchar,
+        /// Unique identifier of the change, in the order in which the changes were recorded
+        id -> Int8,
+        /// Date and time when the change was published to the changes feed in the index storage (`NULL` if not published yet)
+        published_at -> Nullable<Timestamptz>,
+        /// Type of the modification (see `IndexChangeReason` enum)
+        reason -> Int4,
+        /// Sequence number of the change in the changes feed, assigned once the change has been committed (`NULL` if not assigned yet)
+        seq -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Hash bins of the signed `targets` metadata of the index, each of which lists the index files whose path hash starts with a given byte
+    index_target_bins (bin) {
+        /// First byte of the SHA256 hash of the paths of the index files in this bin
+        bin -> Int2,
+        /// Counter that is incremented whenever an index file in this bin changes
+        generation -> Int8,
+        /// Value of `generation` when the metadata of this bin was last signed
+        signed_generation -> Int8,
+        /// Date and time when the metadata of this bin was last signed
+        signed_at -> Nullable<Timestamptz>,
+        /// Version of the last signed metadata of this bin (`0` if it has not been signed yet)
+        version -> Int8,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Length and hash of every sparse index file, used to generate the signed `targets` metadata of the index
+    index_targets (path) {
+        /// Hash bin of the index file, i.e. the first byte of the SHA256 hash of its path
+        bin -> Int2,
+        /// Length of the index file in bytes
+        length -> Int8,
+        /// Path of the index file, relative to the index root
+        path -> Varchar,
+        /// SHA256 hash of the index file
+        sha256 -> Bytea,
+        /// Date and time when the index file was last uploaded
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Results of the periodic comparison of the database with the git and sparse indexes
+    index_verifications (id) {
+        /// Number of crates that were checked
+        crates_checked -> Int4,
+        /// Date and time when the verification run finished
+        created_at -> Timestamptz,
+        /// Unique identifier of the verification run
+        id -> Int8,
+        /// JSON array of the index files that do not match the database
+        mismatches -> Jsonb,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -824,6 +1125,53 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Dates whose `version_downloads` rows have been added to `crate_download_rollups`, to ensure that every date is only rolled up once.
+    rolled_up_download_dates (date) {
+        /// Date whose downloads have been rolled up
+        date -> Date,
+        /// Date and time when the downloads were rolled up
+        rolled_up_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Versions that have been uploaded in "staged" mode, but not released yet
+    staged_versions (id) {
+        /// SHA256 checksum of the uploaded `.crate` file
+        #[max_length = 64]
+        checksum -> Bpchar,
+        /// Unique identifier of the crate that this version belongs to, or `NULL` if the crate has not been published yet
+        crate_id -> Nullable<Int4>,
+        /// Name of the crate that this version belongs to, if the crate has not been published yet. The name is reserved for the user that staged the version until it is released or deleted.
+        crate_name -> Nullable<Varchar>,
+        /// Date and time when the version was staged
+        created_at -> Timestamptz,
+        /// Unique identifier of the `staged_versions` row
+        id -> Int4,
+        /// Version number of the staged version
+        num -> Varchar,
+        /// Unique identifier of the user that staged the version (`NULL` for Trusted Publishing)
+        published_by -> Nullable<Int4>,
+        /// Verified email address of the user that staged the version
+        publisher_email -> Nullable<Varchar>,
+        /// README content that was submitted with the publish request
+        readme -> Nullable<Text>,
+        /// Path of the README file that was submitted with the publish request
+        readme_file -> Nullable<Varchar>,
+        /// Unique identifier of the team that staged the version via a team token, if any
+        team_id -> Nullable<Int4>,
+        /// JSONB data containing JWT claims from the trusted publisher that staged the version
+        trustpub_data -> Nullable<Jsonb>,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -859,6 +1207,29 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Trusted Publisher configuration for Forgejo Actions
+    trustpub_configs_forgejo (id) {
+        /// Unique identifier of the crate that this configuration is for
+        crate_id -> Int4,
+        /// Date and time when the configuration was created
+        created_at -> Timestamptz,
+        /// Unique identifier of the `trustpub_configs_forgejo` row
+        id -> Int4,
+        /// Name of the Forgejo repository that this configuration is for
+        repository_name -> Varchar,
+        /// Forgejo name of the user or organization that owns the repository
+        repository_owner -> Varchar,
+        /// Forgejo ID of the user or organization that owns the repository, populated on first token exchange for resurrection attack protection
+        repository_owner_id -> Nullable<Varchar>,
+        /// Name of the Forgejo Actions workflow file that will be used to publish the crate
+        workflow_filename -> Varchar,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -871,8 +1242,12 @@ diesel::table! {
         created_at -> Timestamptz,
         /// GitHub Actions environment that will be used to publish the crate (if `NULL` the environment is unrestricted)
         environment -> Nullable<Varchar>,
+        /// Date and time after which the configuration can no longer be used (if `NULL` the configuration does not expire)
+        expires_at -> Nullable<Timestamptz>,
         /// Unique identifier of the `trustpub_configs_github` row
         id -> Int4,
+        /// Pattern that the git ref of the workflow run has to match, with `*` as wildcard (if `NULL` the ref is unrestricted)
+        ref_pattern -> Nullable<Varchar>,
         /// Name of the repository that this configuration is for
         repository_name -> Varchar,
         /// GitHub name of the user or organization that owns the repository
@@ -896,14 +1271,20 @@ diesel::table! {
         created_at -> Timestamptz,
         /// GitLab environment that will be used to publish the crate (if `NULL` the environment is unrestricted)
         environment -> Nullable<Varchar>,
+        /// Date and time after which the configuration can no longer be used (if `NULL` the configuration does not expire)
+        expires_at -> Nullable<Timestamptz>,
         /// Unique identifier of the `trustpub_configs_gitlab` row
         id -> Int4,
+        /// OIDC issuer URL of the GitLab instance that hosts the project (e.g. `https://gitlab.com`)
+        issuer -> Varchar,
         /// GitLab namespace (user or group) that owns the project
         namespace -> Varchar,
         /// GitLab namespace ID, populated on first token exchange for resurrection attack protection
         namespace_id -> Nullable<Varchar>,
         /// Name of the GitLab project that this configuration is for
         project -> Varchar,
+        /// Pattern that the git ref of the pipeline has to match, with `*` as wildcard (if `NULL` the ref is unrestricted)
+        ref_pattern -> Nullable<Varchar>,
         /// Path to the CI/CD configuration file that will be used to publish the crate
         workflow_filepath -> Varchar,
     }
@@ -1010,6 +1391,25 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::Tsvector;
+
+    /// Daily download counts of a version from a single IP network prefix or user agent, used for the detection of download anomalies. Filled by the `process_cdn_log` background job and pruned by the `detect_download_anomalies` background job.
+    version_download_sources (version_id, date, source_type, source) {
+        /// Date on which the downloads happened
+        date -> Date,
+        /// Number of downloads of the version from this source on this date
+        downloads -> Int8,
+        /// IP network prefix (`/24` for IPv4, `/48` for IPv6) or user agent of the downloads
+        source -> Varchar,
+        /// Type of the source: `ip_prefix` or `user_agent`
+        source_type -> Varchar,
+        /// Reference to the version that was downloaded
+        version_id -> Int4,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::Tsvector;
@@ -1077,6 +1477,8 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         id -> Int4,
+        /// Unique identifier of the team that performed the action via a team token, if any
+        team_id -> Nullable<Int4>,
         /// The `time` column of the `version_owner_actions` table.
         ///
         /// Its SQL type is `Timestamptz`.
@@ -1236,6 +1638,8 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         email -> Varchar,
+        /// Unique identifier of the team that published the version via a team token, if any
+        team_id -> Nullable<Int4>,
         /// The `version_id` column of the `versions_published_by` table.
         ///
         /// Its SQL type is `Int4`.
@@ -1245,12 +1649,24 @@ diesel::table! {
     }
 }
 
+diesel::joinable!(api_token_usages -> api_tokens (api_token_id));
+diesel::joinable!(api_tokens -> teams (team_id));
 diesel::joinable!(api_tokens -> users (user_id));
+diesel::joinable!(cargo_version_downloads -> crates (crate_id));
+diesel::joinable!(crate_audit_actions -> api_tokens (api_token_id));
+diesel::joinable!(crate_audit_actions -> crates (crate_id));
+diesel::joinable!(crate_audit_actions -> users (user_id));
+diesel::joinable!(crate_download_rollups -> crates (crate_id));
 diesel::joinable!(crate_downloads -> crates (crate_id));
 diesel::joinable!(crate_owner_invitations -> crates (crate_id));
 diesel::joinable!(crate_owners -> crates (crate_id));
 diesel::joinable!(crate_owners -> teams (owner_id));
 diesel::joinable!(crate_owners -> users (owner_id));
+diesel::joinable!(crate_platform_downloads -> crates (crate_id));
+diesel::joinable!(crate_search_scores -> crates (crate_id));
+diesel::joinable!(crate_webhook_deliveries -> crate_webhooks (webhook_id));
+diesel::joinable!(crate_webhooks -> crates (crate_id));
+diesel::joinable!(crate_webhooks -> users (created_by));
 diesel::joinable!(crates_categories -> categories (category_id));
 diesel::joinable!(crates_categories -> crates (crate_id));
 diesel::joinable!(crates_keywords -> crates (crate_id));
@@ -1260,6 +1676,7 @@ diesel::joinable!(default_versions -> versions (version_id));
 diesel::joinable!(deleted_crates -> users (deleted_by));
 diesel::joinable!(dependencies -> crates (crate_id));
 diesel::joinable!(dependencies -> versions (version_id));
+diesel::joinable!(download_anomalies -> versions (version_id));
 diesel::joinable!(emails -> users (user_id));
 diesel::joinable!(follows -> crates (crate_id));
 diesel::joinable!(follows -> users (user_id));
@@ -1268,32 +1685,52 @@ diesel::joinable!(publish_limit_buckets -> users (user_id));
 diesel::joinable!(publish_rate_overrides -> users (user_id));
 diesel::joinable!(readme_renderings -> versions (version_id));
 diesel::joinable!(recent_crate_downloads -> crates (crate_id));
+diesel::joinable!(staged_versions -> crates (crate_id));
+diesel::joinable!(staged_versions -> teams (team_id));
+diesel::joinable!(staged_versions -> users (published_by));
+diesel::joinable!(trustpub_configs_forgejo -> crates (crate_id));
 diesel::joinable!(trustpub_configs_github -> crates (crate_id));
 diesel::joinable!(trustpub_configs_gitlab -> crates (crate_id));
+diesel::joinable!(version_download_sources -> versions (version_id));
 diesel::joinable!(version_downloads -> versions (version_id));
 diesel::joinable!(version_owner_actions -> api_tokens (api_token_id));
+diesel::joinable!(version_owner_actions -> teams (team_id));
 diesel::joinable!(version_owner_actions -> users (user_id));
 diesel::joinable!(version_owner_actions -> versions (version_id));
 diesel::joinable!(versions -> crates (crate_id));
 diesel::joinable!(versions -> users (published_by));
+diesel::joinable!(versions_published_by -> teams (team_id));
 diesel::joinable!(versions_published_by -> versions (version_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    api_token_usages,
     api_tokens,
     background_jobs,
+    cargo_version_downloads,
     categories,
     cloudfront_invalidation_queue,
+    crate_audit_actions,
+    crate_download_rollups,
     crate_downloads,
     crate_owner_invitations,
     crate_owners,
+    crate_platform_downloads,
+    crate_search_scores,
+    crate_webhook_deliveries,
+    crate_webhooks,
     crates,
     crates_categories,
     crates_keywords,
     default_versions,
     deleted_crates,
     dependencies,
+    download_anomalies,
     emails,
     follows,
+    index_changes,
+    index_target_bins,
+    index_targets,
+    index_verifications,
     keywords,
     metadata,
     oauth_github,
@@ -1303,12 +1740,16 @@ diesel::allow_tables_to_appear_in_same_query!(
     readme_renderings,
     recent_crate_downloads,
     reserved_crate_names,
+    rolled_up_download_dates,
+    staged_versions,
     teams,
+    trustpub_configs_forgejo,
     trustpub_configs_github,
     trustpub_configs_gitlab,
     trustpub_tokens,
     trustpub_used_jtis,
     users,
+    version_download_sources,
     version_downloads,
     version_owner_actions,
     versions,
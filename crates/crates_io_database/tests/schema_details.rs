@@ -13,6 +13,17 @@ async fn all_columns_called_crate_id_have_a_cascading_foreign_key() {
                 row.table_name
             ),
         };
+
+        if row.table_name == "crate_audit_actions" {
+            // The audit log is explicitly supposed to outlive deleted crates.
+            continue;
+        }
+
+        if row.table_name == "crate_webhooks" {
+            // Webhooks need to outlive deleted crates to deliver the deletion event.
+            continue;
+        }
+
         if !constraint.definition.contains("ON DELETE CASCADE") {
             panic!(
                 "Foreign key {} on table {} should have `ON DELETE CASCADE` \
@@ -1,5 +1,7 @@
 use diesel::define_sql_function;
-use diesel::sql_types::{Date, Double, Integer, Interval, SingleValue, Text, Timestamptz};
+use diesel::sql_types::{
+    Date, Double, Integer, Interval, Nullable, SingleValue, Text, Timestamptz,
+};
 
 define_sql_function!(#[aggregate] fn array_agg<T: SingleValue>(x: T) -> Array<T>);
 define_sql_function!(fn canon_crate_name(x: Text) -> Text);
@@ -15,3 +17,5 @@ define_sql_function!(fn greatest<T: SingleValue>(x: T, y: T) -> T);
 define_sql_function!(fn least<T: SingleValue>(x: T, y: T) -> T);
 define_sql_function!(fn split_part(string: Text, delimiter: Text, n: Integer) -> Text);
 define_sql_function!(fn semver_ord(num: Text) -> Nullable<Jsonb>);
+define_sql_function!(fn regexp_split_to_array(string: Nullable<Text>, pattern: Text) -> Nullable<Array<Text>>);
+define_sql_function!(fn coalesce<T: SingleValue>(x: Nullable<T>, y: T) -> T);
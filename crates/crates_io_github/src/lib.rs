@@ -213,12 +213,17 @@ pub struct GitHubTeam {
 #[derive(Debug, Deserialize)]
 pub struct GitHubTeamMembership {
     pub state: String,
+    pub role: String,
 }
 
 impl GitHubTeamMembership {
     pub fn is_active(&self) -> bool {
         self.state == "active"
     }
+
+    pub fn is_active_maintainer(&self) -> bool {
+        self.is_active() && self.role == "maintainer"
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -12,6 +12,7 @@ mod repo;
 mod ser;
 #[cfg(feature = "testing")]
 pub mod testing;
+pub mod tuf;
 
 pub use crate::credentials::Credentials;
 pub use crate::data::{Crate, Dependency, DependencyKind};
@@ -1,7 +1,7 @@
 use crate::credentials::Credentials;
 use anyhow::{Context, anyhow};
 use base64::{Engine, engine::general_purpose};
-use crates_io_env_vars::{required_var, required_var_parsed, var};
+use crates_io_env_vars::{required_var, required_var_parsed, var, var_parsed};
 use secrecy::{ExposeSecret, SecretString};
 use std::path::{Path, PathBuf};
 use std::process::Command;
@@ -15,6 +15,15 @@ pub struct RepositoryConfig {
 }
 
 impl RepositoryConfig {
+    /// Returns whether the git index is enabled.
+    ///
+    /// Installations that only use the sparse index can disable the git
+    /// index with `GIT_INDEX_ENABLED=false`, in which case none of the other
+    /// `GIT_*` environment variables are required.
+    pub fn is_enabled() -> anyhow::Result<bool> {
+        Ok(var_parsed("GIT_INDEX_ENABLED")?.unwrap_or(true))
+    }
+
     pub fn from_environment() -> anyhow::Result<Self> {
         let repo_url: Url = required_var_parsed("GIT_REPO_URL")?;
         let is_ssh = repo_url.scheme() == "ssh";
@@ -135,6 +144,11 @@ impl Repository {
             .join(Self::relative_index_file(name))
     }
 
+    /// Returns the absolute path to the local checkout of the crate index.
+    pub fn checkout_path(&self) -> &Path {
+        self.checkout_path.path()
+    }
+
     /// Returns the relative path to the crate index file.
     /// Does not perform conversion to lowercase.
     fn relative_index_file_helper(name: &str) -> Vec<&str> {
@@ -0,0 +1,900 @@
+//! Signed metadata for the sparse index, following the
+//! [TUF specification](https://theupdateframework.github.io/specification/latest/).
+//!
+//! The metadata consists of four roles:
+//!
+//! - `root` lists the keys that are trusted for each role. It is signed by
+//!   the offline root key(s) and only changes when keys are rotated.
+//! - `targets` delegates the index files to [`NUM_BINS`] hash bins, based
+//!   on the SHA256 hash of their path. Each bin is a delegated `targets`
+//!   role that lists the length and SHA256 hash of its index files, so that
+//!   a change to a single index file only requires re-signing a single bin.
+//! - `snapshot` pins the current versions of the `targets` metadata and of
+//!   all bins.
+//! - `timestamp` pins the current version and hash of the `snapshot`
+//!   metadata, and is re-signed frequently to prove freshness.
+//!
+//! The `targets`, `snapshot` and `timestamp` roles are signed by an online
+//! key, which allows the metadata to be regenerated whenever the index
+//! changes.
+//!
+//! All keys are ECDSA keys on the NIST P-256 curve, and all signatures are
+//! calculated over the canonical JSON representation of the `signed` object.
+
+use anyhow::{Context, anyhow, bail};
+use chrono::{DateTime, SubsecRound, Utc};
+use p256::ecdsa::signature::{Signer, Verifier};
+use p256::ecdsa::{DerSignature, VerifyingKey};
+use p256::pkcs8::{
+    DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey, LineEnding,
+};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+/// The version of the TUF specification that the metadata conforms to.
+pub const SPEC_VERSION: &str = "1.0.31";
+
+/// The number of hash bins that the top-level `targets` role delegates to.
+/// Each bin is responsible for the index files whose path hashes to a
+/// SHA256 digest starting with a given byte.
+pub const NUM_BINS: usize = 256;
+
+const KEY_TYPE: &str = "ecdsa";
+const SIGNATURE_SCHEME: &str = "ecdsa-sha2-nistp256";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum RoleType {
+    Root,
+    Targets,
+    Snapshot,
+    Timestamp,
+}
+
+impl RoleType {
+    /// Returns the file name of the metadata of this role.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            RoleType::Root => "root.json",
+            RoleType::Targets => "targets.json",
+            RoleType::Snapshot => "snapshot.json",
+            RoleType::Timestamp => "timestamp.json",
+        }
+    }
+}
+
+/// The `signed` part of the metadata of a role.
+pub trait Metadata: Serialize + DeserializeOwned {
+    const ROLE: RoleType;
+
+    fn role(&self) -> RoleType;
+    fn version(&self) -> u64;
+    fn expires(&self) -> DateTime<Utc>;
+}
+
+/// Metadata together with the signatures over its canonical JSON
+/// representation.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Signed<T> {
+    pub signed: T,
+    pub signatures: Vec<Signature>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub keyid: String,
+    /// The hex-encoded DER representation of the ECDSA signature.
+    pub sig: String,
+}
+
+impl<T: Metadata> Signed<T> {
+    pub fn new(signed: T) -> Self {
+        Self {
+            signed,
+            signatures: vec![],
+        }
+    }
+
+    /// Adds a signature by the given key, replacing any previous signature
+    /// by the same key.
+    pub fn sign(&mut self, key: &SigningKey) -> anyhow::Result<()> {
+        let message = canonical_json(&self.signed)?;
+        let signature: DerSignature = key.inner.sign(&message);
+
+        self.signatures
+            .retain(|signature| signature.keyid != key.key_id);
+        self.signatures.push(Signature {
+            keyid: key.key_id.clone(),
+            sig: hex::encode(signature.as_bytes()),
+        });
+
+        Ok(())
+    }
+
+    /// Returns the canonical JSON representation of the signed metadata, as
+    /// it is uploaded to the index storage.
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        let bytes = canonical_json(self)?;
+        String::from_utf8(bytes).context("Failed to decode metadata as utf8")
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> anyhow::Result<Self> {
+        let metadata: Self = serde_json::from_slice(bytes)?;
+        if metadata.signed.role() != T::ROLE {
+            bail!("Expected `{:?}` metadata", T::ROLE);
+        }
+
+        Ok(metadata)
+    }
+
+    /// Returns the number of distinct trusted keys that produced a valid
+    /// signature of this metadata.
+    fn count_valid_signatures(
+        &self,
+        keys: &BTreeMap<String, PublicKey>,
+        role: &RoleKeys,
+    ) -> anyhow::Result<u32> {
+        let message = canonical_json(&self.signed)?;
+
+        let mut valid_key_ids = HashSet::new();
+        for signature in &self.signatures {
+            if !role.keyids.contains(&signature.keyid) {
+                continue;
+            }
+
+            let Some(key) = keys.get(&signature.keyid) else {
+                continue;
+            };
+
+            if key.verify(&message, &signature.sig) {
+                valid_key_ids.insert(&signature.keyid);
+            }
+        }
+
+        Ok(valid_key_ids.len() as u32)
+    }
+}
+
+/// Returns the canonical JSON representation of the given value, with all
+/// object keys sorted and without any insignificant whitespace.
+pub fn canonical_json<T: Serialize + ?Sized>(value: &T) -> anyhow::Result<Vec<u8>> {
+    // `serde_json::Value` uses a `BTreeMap` for objects, which sorts the keys.
+    let value = serde_json::to_value(value)?;
+    Ok(serde_json::to_vec(&value)?)
+}
+
+/// Truncates the given date and time to whole seconds, as required by the
+/// `expires` field of the metadata.
+fn expiry(expires: DateTime<Utc>) -> DateTime<Utc> {
+    expires.trunc_subsecs(0)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PublicKey {
+    pub keytype: String,
+    pub scheme: String,
+    pub keyval: KeyValue,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct KeyValue {
+    /// The PEM-encoded SubjectPublicKeyInfo of the key.
+    pub public: String,
+}
+
+impl PublicKey {
+    pub fn from_pem(pem: &str) -> anyhow::Result<Self> {
+        let key = VerifyingKey::from_public_key_pem(pem.trim())
+            .map_err(|error| anyhow!("Failed to parse public key: {error}"))?;
+
+        Self::from_verifying_key(&key)
+    }
+
+    fn from_verifying_key(key: &VerifyingKey) -> anyhow::Result<Self> {
+        let public = key
+            .to_public_key_pem(LineEnding::LF)
+            .map_err(|error| anyhow!("Failed to encode public key: {error}"))?;
+
+        Ok(Self {
+            keytype: KEY_TYPE.to_string(),
+            scheme: SIGNATURE_SCHEME.to_string(),
+            keyval: KeyValue { public },
+        })
+    }
+
+    /// Returns the key ID, which is the hex-encoded SHA256 hash of the
+    /// canonical JSON representation of the key.
+    pub fn key_id(&self) -> anyhow::Result<String> {
+        let bytes = canonical_json(self)?;
+        Ok(hex::encode(Sha256::digest(bytes)))
+    }
+
+    fn verify(&self, message: &[u8], signature: &str) -> bool {
+        if self.keytype != KEY_TYPE || self.scheme != SIGNATURE_SCHEME {
+            return false;
+        }
+
+        let Ok(key) = VerifyingKey::from_public_key_pem(&self.keyval.public) else {
+            return false;
+        };
+
+        let Ok(signature) = hex::decode(signature) else {
+            return false;
+        };
+
+        let Ok(signature) = DerSignature::from_bytes(&signature) else {
+            return false;
+        };
+
+        key.verify(message, &signature).is_ok()
+    }
+}
+
+/// A private key that is used to sign the metadata.
+#[derive(Clone)]
+pub struct SigningKey {
+    inner: p256::ecdsa::SigningKey,
+    public_key: PublicKey,
+    key_id: String,
+}
+
+impl SigningKey {
+    /// Creates a signing key from the 32 bytes of its secret scalar.
+    pub fn from_secret_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let key = p256::ecdsa::SigningKey::from_slice(bytes)
+            .map_err(|_| anyhow!("Invalid secret key"))?;
+
+        Self::from_inner(key)
+    }
+
+    /// Parses a signing key from its PEM-encoded PKCS#8 representation.
+    pub fn from_pkcs8_pem(pem: &str) -> anyhow::Result<Self> {
+        let key = p256::ecdsa::SigningKey::from_pkcs8_pem(pem.trim())
+            .map_err(|error| anyhow!("Failed to parse signing key: {error}"))?;
+
+        Self::from_inner(key)
+    }
+
+    fn from_inner(inner: p256::ecdsa::SigningKey) -> anyhow::Result<Self> {
+        let public_key = PublicKey::from_verifying_key(inner.verifying_key())?;
+        let key_id = public_key.key_id()?;
+
+        Ok(Self {
+            inner,
+            public_key,
+            key_id,
+        })
+    }
+
+    /// Returns the PEM-encoded PKCS#8 representation of the signing key.
+    pub fn to_pkcs8_pem(&self) -> anyhow::Result<String> {
+        let pem = self
+            .inner
+            .to_pkcs8_pem(LineEnding::LF)
+            .map_err(|error| anyhow!("Failed to encode signing key: {error}"))?;
+
+        Ok(pem.to_string())
+    }
+
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+}
+
+impl fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("key_id", &self.key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RoleKeys {
+    pub keyids: Vec<String>,
+    pub threshold: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Root {
+    #[serde(rename = "_type")]
+    pub typ: RoleType,
+    pub spec_version: String,
+    pub consistent_snapshot: bool,
+    pub version: u64,
+    pub expires: DateTime<Utc>,
+    pub keys: BTreeMap<String, PublicKey>,
+    pub roles: BTreeMap<RoleType, RoleKeys>,
+}
+
+impl Root {
+    pub fn new(version: u64, expires: DateTime<Utc>) -> Self {
+        let roles = [
+            RoleType::Root,
+            RoleType::Targets,
+            RoleType::Snapshot,
+            RoleType::Timestamp,
+        ];
+
+        let roles = roles
+            .into_iter()
+            .map(|role| {
+                let keyids = vec![];
+                (
+                    role,
+                    RoleKeys {
+                        keyids,
+                        threshold: 1,
+                    },
+                )
+            })
+            .collect();
+
+        Self {
+            typ: RoleType::Root,
+            spec_version: SPEC_VERSION.to_string(),
+            consistent_snapshot: false,
+            version,
+            expires: expiry(expires),
+            keys: BTreeMap::new(),
+            roles,
+        }
+    }
+
+    /// Adds the given key to the list of trusted keys of the given role.
+    pub fn add_key(&mut self, role: RoleType, key: &PublicKey) -> anyhow::Result<()> {
+        let key_id = key.key_id()?;
+
+        let role = self.roles.entry(role).or_insert_with(|| RoleKeys {
+            keyids: vec![],
+            threshold: 1,
+        });
+
+        if !role.keyids.contains(&key_id) {
+            role.keyids.push(key_id.clone());
+        }
+
+        self.keys.insert(key_id, key.clone());
+
+        Ok(())
+    }
+
+    /// Sets the number of valid signatures that are required for metadata
+    /// of the given role.
+    pub fn set_threshold(&mut self, role: RoleType, threshold: u32) -> anyhow::Result<()> {
+        let role_keys = self
+            .roles
+            .get_mut(&role)
+            .ok_or_else(|| anyhow!("Unknown role `{role:?}`"))?;
+
+        if threshold == 0 || threshold as usize > role_keys.keyids.len() {
+            bail!(
+                "Threshold for role `{role:?}` must be between 1 and {}",
+                role_keys.keyids.len()
+            );
+        }
+
+        role_keys.threshold = threshold;
+        Ok(())
+    }
+
+    /// Verifies that the given metadata is signed by at least `threshold`
+    /// of the keys that this root trusts for the corresponding role.
+    ///
+    /// This does not check whether the metadata is expired.
+    pub fn verify<T: Metadata>(&self, metadata: &Signed<T>) -> anyhow::Result<()> {
+        let role = metadata.signed.role();
+        if role != T::ROLE {
+            bail!("Expected `{:?}` metadata, found `{role:?}`", T::ROLE);
+        }
+
+        let role_keys = self
+            .roles
+            .get(&role)
+            .ok_or_else(|| anyhow!("Root metadata has no keys for role `{role:?}`"))?;
+
+        let valid_signatures = metadata.count_valid_signatures(&self.keys, role_keys)?;
+        if valid_signatures < role_keys.threshold {
+            bail!(
+                "`{role:?}` metadata has {valid_signatures} valid signature(s), but {} are required",
+                role_keys.threshold
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl Metadata for Root {
+    const ROLE: RoleType = RoleType::Root;
+
+    fn role(&self) -> RoleType {
+        self.typ
+    }
+
+    fn version(&self) -> u64 {
+        self.version
+    }
+
+    fn expires(&self) -> DateTime<Utc> {
+        self.expires
+    }
+}
+
+/// The length and hashes of a target file, i.e. an index file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TargetFile {
+    pub length: u64,
+    pub hashes: BTreeMap<String, String>,
+}
+
+impl TargetFile {
+    pub fn new(length: u64, sha256: &[u8]) -> Self {
+        let hashes = BTreeMap::from([("sha256".to_string(), hex::encode(sha256))]);
+        Self { length, hashes }
+    }
+
+    pub fn from_content(content: &[u8]) -> Self {
+        Self::new(content.len() as u64, &Sha256::digest(content))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Targets {
+    #[serde(rename = "_type")]
+    pub typ: RoleType,
+    pub spec_version: String,
+    pub version: u64,
+    pub expires: DateTime<Utc>,
+    /// The target files, keyed by their path relative to the index root.
+    pub targets: BTreeMap<String, TargetFile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delegations: Option<Delegations>,
+}
+
+impl Targets {
+    pub fn new(
+        version: u64,
+        expires: DateTime<Utc>,
+        targets: BTreeMap<String, TargetFile>,
+    ) -> Self {
+        Self {
+            typ: RoleType::Targets,
+            spec_version: SPEC_VERSION.to_string(),
+            version,
+            expires: expiry(expires),
+            targets,
+            delegations: None,
+        }
+    }
+
+    /// Creates top-level `targets` metadata without any target files, which
+    /// delegates all targets to the given roles instead.
+    pub fn with_delegations(
+        version: u64,
+        expires: DateTime<Utc>,
+        delegations: Delegations,
+    ) -> Self {
+        Self {
+            delegations: Some(delegations),
+            ..Self::new(version, expires, BTreeMap::new())
+        }
+    }
+}
+
+impl Metadata for Targets {
+    const ROLE: RoleType = RoleType::Targets;
+
+    fn role(&self) -> RoleType {
+        self.typ
+    }
+
+    fn version(&self) -> u64 {
+        self.version
+    }
+
+    fn expires(&self) -> DateTime<Utc> {
+        self.expires
+    }
+}
+
+/// Returns the hash bin that is responsible for the index file with the
+/// given path.
+pub fn bin_for_path(path: &str) -> u8 {
+    Sha256::digest(path.as_bytes())[0]
+}
+
+/// Returns the name of the delegated role of the given hash bin, which is
+/// also the file name of its metadata without the `.json` extension.
+pub fn bin_role_name(bin: u8) -> String {
+    format!("bins-{bin:02x}")
+}
+
+/// The roles that the `targets` role delegates its target files to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Delegations {
+    pub keys: BTreeMap<String, PublicKey>,
+    pub roles: Vec<DelegatedRole>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DelegatedRole {
+    pub name: String,
+    pub keyids: Vec<String>,
+    pub threshold: u32,
+    pub terminating: bool,
+    /// The hex-encoded prefixes of the SHA256 hashes of the target paths
+    /// that this role is responsible for.
+    pub path_hash_prefixes: Vec<String>,
+}
+
+impl Delegations {
+    /// Delegates all target files to [`NUM_BINS`] hash bins, which are
+    /// signed by the given key.
+    pub fn hash_bins(key: &PublicKey) -> anyhow::Result<Self> {
+        let key_id = key.key_id()?;
+
+        let roles = (0..NUM_BINS)
+            .map(|bin| {
+                let bin = bin as u8;
+                DelegatedRole {
+                    name: bin_role_name(bin),
+                    keyids: vec![key_id.clone()],
+                    threshold: 1,
+                    terminating: true,
+                    path_hash_prefixes: vec![format!("{bin:02x}")],
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            keys: BTreeMap::from([(key_id, key.clone())]),
+            roles,
+        })
+    }
+
+    /// Verifies that the given metadata of the delegated role with the given
+    /// name is signed by at least `threshold` of the keys that are trusted
+    /// for the role, and that it only lists target files that the role is
+    /// responsible for.
+    ///
+    /// This does not check whether the metadata is expired.
+    pub fn verify(&self, name: &str, metadata: &Signed<Targets>) -> anyhow::Result<()> {
+        let role = self
+            .roles
+            .iter()
+            .find(|role| role.name == name)
+            .ok_or_else(|| anyhow!("Unknown delegated role `{name}`"))?;
+
+        let role_keys = RoleKeys {
+            keyids: role.keyids.clone(),
+            threshold: role.threshold,
+        };
+
+        let valid_signatures = metadata.count_valid_signatures(&self.keys, &role_keys)?;
+        if valid_signatures < role.threshold {
+            bail!(
+                "`{name}` metadata has {valid_signatures} valid signature(s), but {} are required",
+                role.threshold
+            );
+        }
+
+        for path in metadata.signed.targets.keys() {
+            let hash = hex::encode(Sha256::digest(path.as_bytes()));
+            let is_delegated = role
+                .path_hash_prefixes
+                .iter()
+                .any(|prefix| hash.starts_with(prefix));
+
+            if !is_delegated {
+                bail!("`{name}` metadata lists `{path}`, which is not delegated to it");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A reference to the metadata file of another role.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MetaFile {
+    pub version: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hashes: Option<BTreeMap<String, String>>,
+}
+
+impl MetaFile {
+    pub fn new(version: u64) -> Self {
+        Self {
+            version,
+            length: None,
+            hashes: None,
+        }
+    }
+
+    /// Creates a reference that also pins the length and SHA256 hash of the
+    /// given metadata file content.
+    pub fn with_content(version: u64, content: &[u8]) -> Self {
+        let sha256 = hex::encode(Sha256::digest(content));
+
+        Self {
+            version,
+            length: Some(content.len() as u64),
+            hashes: Some(BTreeMap::from([("sha256".to_string(), sha256)])),
+        }
+    }
+
+    /// Checks that the given metadata file content matches the pinned length
+    /// and hashes, if any.
+    pub fn matches(&self, content: &[u8]) -> bool {
+        let expected = Self::with_content(self.version, content);
+
+        let length_matches = self
+            .length
+            .is_none_or(|length| expected.length == Some(length));
+        let hashes_match = self.hashes.is_none() || self.hashes == expected.hashes;
+
+        length_matches && hashes_match
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Snapshot {
+    #[serde(rename = "_type")]
+    pub typ: RoleType,
+    pub spec_version: String,
+    pub version: u64,
+    pub expires: DateTime<Utc>,
+    pub meta: BTreeMap<String, MetaFile>,
+}
+
+impl Snapshot {
+    /// Creates `snapshot` metadata that pins the given `targets` metadata
+    /// files, keyed by their file name.
+    pub fn new(version: u64, expires: DateTime<Utc>, meta: BTreeMap<String, MetaFile>) -> Self {
+        Self {
+            typ: RoleType::Snapshot,
+            spec_version: SPEC_VERSION.to_string(),
+            version,
+            expires: expiry(expires),
+            meta,
+        }
+    }
+}
+
+impl Metadata for Snapshot {
+    const ROLE: RoleType = RoleType::Snapshot;
+
+    fn role(&self) -> RoleType {
+        self.typ
+    }
+
+    fn version(&self) -> u64 {
+        self.version
+    }
+
+    fn expires(&self) -> DateTime<Utc> {
+        self.expires
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Timestamp {
+    #[serde(rename = "_type")]
+    pub typ: RoleType,
+    pub spec_version: String,
+    pub version: u64,
+    pub expires: DateTime<Utc>,
+    pub meta: BTreeMap<String, MetaFile>,
+}
+
+impl Timestamp {
+    pub fn new(version: u64, expires: DateTime<Utc>, snapshot: MetaFile) -> Self {
+        let file_name = RoleType::Snapshot.file_name().to_string();
+
+        Self {
+            typ: RoleType::Timestamp,
+            spec_version: SPEC_VERSION.to_string(),
+            version,
+            expires: expiry(expires),
+            meta: BTreeMap::from([(file_name, snapshot)]),
+        }
+    }
+}
+
+impl Metadata for Timestamp {
+    const ROLE: RoleType = RoleType::Timestamp;
+
+    fn role(&self) -> RoleType {
+        self.typ
+    }
+
+    fn version(&self) -> u64 {
+        self.version
+    }
+
+    fn expires(&self) -> DateTime<Utc> {
+        self.expires
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::*;
+
+    fn root_key() -> SigningKey {
+        SigningKey::from_secret_bytes(&[1; 32]).unwrap()
+    }
+
+    fn online_key() -> SigningKey {
+        SigningKey::from_secret_bytes(&[2; 32]).unwrap()
+    }
+
+    fn expires() -> DateTime<Utc> {
+        "2030-01-01T00:00:00.123Z".parse().unwrap()
+    }
+
+    fn root(root_key: &SigningKey, online_key: &SigningKey) -> Root {
+        let mut root = Root::new(1, expires());
+        root.add_key(RoleType::Root, root_key.public_key()).unwrap();
+        for role in [RoleType::Targets, RoleType::Snapshot, RoleType::Timestamp] {
+            root.add_key(role, online_key.public_key()).unwrap();
+        }
+        root
+    }
+
+    fn targets() -> Targets {
+        let targets = BTreeMap::from([(
+            "3/f/foo".to_string(),
+            TargetFile::from_content(b"{\"name\":\"foo\"}\n"),
+        )]);
+        Targets::new(1, expires(), targets)
+    }
+
+    #[test]
+    fn test_canonical_json() {
+        let targets = targets();
+        let json = String::from_utf8(canonical_json(&targets).unwrap()).unwrap();
+        insta::assert_snapshot!(json, @r#"{"_type":"targets","expires":"2030-01-01T00:00:00Z","spec_version":"1.0.31","targets":{"3/f/foo":{"hashes":{"sha256":"e3ef61f583bd08f585b5a727764f2265b4ffae39ec37be29842ada7644ef27d7"},"length":15}},"version":1}"#);
+    }
+
+    #[test]
+    fn test_key_roundtrip() {
+        let key = root_key();
+
+        let pem = key.to_pkcs8_pem().unwrap();
+        let parsed = SigningKey::from_pkcs8_pem(&pem).unwrap();
+        assert_eq!(parsed.key_id(), key.key_id());
+
+        let public_key = PublicKey::from_pem(&key.public_key().keyval.public).unwrap();
+        assert_eq!(&public_key, key.public_key());
+        assert_eq!(public_key.key_id().unwrap(), key.key_id());
+
+        assert_err!(SigningKey::from_secret_bytes(&[0; 32]));
+        assert_err!(SigningKey::from_pkcs8_pem("foo"));
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let (root_key, online_key) = (root_key(), online_key());
+        let root = root(&root_key, &online_key);
+
+        let mut signed_root = Signed::new(root.clone());
+        signed_root.sign(&root_key).unwrap();
+        assert_ok!(root.verify(&signed_root));
+
+        let mut targets = Signed::new(targets());
+        assert_err!(root.verify(&targets));
+
+        targets.sign(&online_key).unwrap();
+        assert_ok!(root.verify(&targets));
+
+        // Signing again with the same key replaces the previous signature
+        targets.sign(&online_key).unwrap();
+        assert_eq!(targets.signatures.len(), 1);
+
+        // The published JSON can be parsed and verified again
+        let json = targets.to_json().unwrap();
+        let parsed = Signed::<Targets>::from_slice(json.as_bytes()).unwrap();
+        assert_ok!(root.verify(&parsed));
+        assert_err!(Signed::<Snapshot>::from_slice(json.as_bytes()));
+    }
+
+    #[test]
+    fn test_verify_tampered_metadata() {
+        let (root_key, online_key) = (root_key(), online_key());
+        let root = root(&root_key, &online_key);
+
+        let mut targets = Signed::new(targets());
+        targets.sign(&online_key).unwrap();
+
+        let target = targets.signed.targets.get_mut("3/f/foo").unwrap();
+        target.length += 1;
+        assert_err!(root.verify(&targets));
+    }
+
+    #[test]
+    fn test_verify_untrusted_key() {
+        let (root_key, online_key) = (root_key(), online_key());
+        let root = root(&root_key, &online_key);
+
+        // The root key is not trusted for the `targets` role
+        let mut targets = Signed::new(targets());
+        targets.sign(&root_key).unwrap();
+        assert_err!(root.verify(&targets));
+
+        // The online key is not trusted for the `root` role
+        let mut signed_root = Signed::new(root.clone());
+        signed_root.sign(&online_key).unwrap();
+        assert_err!(root.verify(&signed_root));
+    }
+
+    #[test]
+    fn test_verify_threshold() {
+        let (root_key, online_key) = (root_key(), online_key());
+        let second_root_key = SigningKey::from_secret_bytes(&[3; 32]).unwrap();
+
+        let mut root = root(&root_key, &online_key);
+        root.add_key(RoleType::Root, second_root_key.public_key())
+            .unwrap();
+        assert_err!(root.set_threshold(RoleType::Root, 3));
+        root.set_threshold(RoleType::Root, 2).unwrap();
+
+        let mut signed_root = Signed::new(root.clone());
+        signed_root.sign(&root_key).unwrap();
+        assert_err!(root.verify(&signed_root));
+
+        // Duplicate signatures by the same key only count once
+        let signature = signed_root.signatures[0].clone();
+        signed_root.signatures.push(signature);
+        assert_err!(root.verify(&signed_root));
+
+        signed_root.sign(&second_root_key).unwrap();
+        assert_ok!(root.verify(&signed_root));
+    }
+
+    #[test]
+    fn test_hash_bins() {
+        let online_key = online_key();
+        let delegations = Delegations::hash_bins(online_key.public_key()).unwrap();
+        assert_eq!(delegations.roles.len(), NUM_BINS);
+        assert_eq!(delegations.roles[0].name, "bins-00");
+        assert_eq!(delegations.roles[255].path_hash_prefixes, vec!["ff"]);
+
+        // `sha256("3/f/foo")` starts with `0x05`
+        let bin = bin_for_path("3/f/foo");
+        assert_eq!(bin_role_name(bin), "bins-05");
+
+        let mut targets = Signed::new(targets());
+        targets.sign(&online_key).unwrap();
+        assert_ok!(delegations.verify("bins-05", &targets));
+
+        // The bin is not responsible for targets of other bins
+        assert_err!(delegations.verify("bins-00", &targets));
+        assert_err!(delegations.verify("bins-100", &targets));
+
+        // The bin must be signed by the delegated key
+        targets.signatures.clear();
+        targets.sign(&root_key()).unwrap();
+        assert_err!(delegations.verify("bins-05", &targets));
+    }
+
+    #[test]
+    fn test_meta_file() {
+        let content = b"{}";
+        let meta = MetaFile::with_content(1, content);
+        assert!(meta.matches(content));
+        assert!(!meta.matches(b"{ }"));
+        assert!(MetaFile::new(1).matches(b"{ }"));
+    }
+}
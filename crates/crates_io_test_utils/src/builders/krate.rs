@@ -11,6 +11,7 @@ use diesel_async::{AsyncPgConnection, RunQueryDsl};
 /// instead.
 pub struct CrateBuilder<'a> {
     categories: Vec<&'a str>,
+    deprecation: Option<(&'a str, Option<&'a str>)>,
     downloads: Option<i32>,
     keywords: Vec<&'a str>,
     krate: NewCrate<'a>,
@@ -27,6 +28,7 @@ impl<'a> CrateBuilder<'a> {
     pub fn new(name: &str, owner_id: i32) -> CrateBuilder<'_> {
         CrateBuilder {
             categories: Vec::new(),
+            deprecation: None,
             downloads: None,
             keywords: Vec::new(),
             krate: NewCrate {
@@ -121,6 +123,12 @@ impl<'a> CrateBuilder<'a> {
         self
     }
 
+    /// Marks the crate as deprecated, with an optional successor crate.
+    pub fn deprecated(mut self, reason: &'a str, successor: Option<&'a str>) -> Self {
+        self.deprecation = Some((reason, successor));
+        self
+    }
+
     pub async fn build(mut self, connection: &mut AsyncPgConnection) -> anyhow::Result<Crate> {
         use diesel::{insert_into, select, update};
 
@@ -171,6 +179,18 @@ impl<'a> CrateBuilder<'a> {
             Keyword::update_crate(connection, krate.id, &self.keywords).await?;
         }
 
+        if let Some((reason, successor)) = self.deprecation {
+            krate = update(&krate)
+                .set((
+                    crates::deprecated_at.eq(diesel::dsl::now),
+                    crates::deprecation_reason.eq(reason),
+                    crates::deprecation_successor.eq(successor),
+                ))
+                .returning(Crate::as_returning())
+                .get_result(connection)
+                .await?;
+        }
+
         if let Some(updated_at) = self.updated_at {
             krate = update(&krate)
                 .set(crates::updated_at.eq(updated_at))
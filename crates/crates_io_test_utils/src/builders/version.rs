@@ -19,6 +19,9 @@ pub struct VersionBuilder {
     checksum: String,
     links: Option<String>,
     rust_version: Option<String>,
+    bin_names: Option<Vec<&'static str>>,
+    edition: Option<&'static str>,
+    has_lib: Option<bool>,
 }
 
 #[allow(dead_code)]
@@ -45,6 +48,9 @@ impl VersionBuilder {
             checksum: String::new(),
             links: None,
             rust_version: None,
+            bin_names: None,
+            edition: None,
+            has_lib: None,
         }
     }
 
@@ -89,6 +95,37 @@ impl VersionBuilder {
         self
     }
 
+    /// Adds a feature to this version.
+    pub fn feature(mut self, name: &str, values: &[&str]) -> Self {
+        let values = values.iter().map(|v| v.to_string()).collect();
+        self.features.insert(name.to_string(), values);
+        self
+    }
+
+    /// Sets the version's `links` value.
+    pub fn links(mut self, links: &str) -> Self {
+        self.links = Some(links.to_owned());
+        self
+    }
+
+    /// Sets the version's `bin_names` value.
+    pub fn bin_names(mut self, bin_names: &[&'static str]) -> Self {
+        self.bin_names = Some(bin_names.to_vec());
+        self
+    }
+
+    /// Sets the version's `edition` value.
+    pub fn edition(mut self, edition: &'static str) -> Self {
+        self.edition = Some(edition);
+        self
+    }
+
+    /// Sets the version's `has_lib` value.
+    pub fn has_lib(mut self, has_lib: bool) -> Self {
+        self.has_lib = Some(has_lib);
+        self
+    }
+
     pub async fn build(
         self,
         crate_id: i32,
@@ -107,12 +144,15 @@ impl VersionBuilder {
             .checksum(&self.checksum)
             .maybe_links(self.links.as_deref())
             .maybe_rust_version(self.rust_version.as_deref())
+            .maybe_bin_names(self.bin_names.as_deref())
+            .maybe_edition(self.edition)
+            .maybe_has_lib(self.has_lib)
             .yanked(self.yanked)
             .maybe_created_at(self.created_at.as_ref())
             .build();
 
         let vers = new_version.save(connection).await?;
-        versions_published_by::insert(vers.id, "someone@example.com", connection).await?;
+        versions_published_by::insert(vers.id, "someone@example.com", None, connection).await?;
 
         let new_deps = self
             .dependencies
@@ -0,0 +1,312 @@
+use crate::github::workflows::extract_workflow_filename;
+use chrono::serde::ts_seconds;
+use chrono::{DateTime, Utc};
+use jsonwebtoken::errors::{Error, ErrorKind};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+
+/// Claims extracted from a Forgejo Actions OIDC token.
+///
+/// This struct is used to decode and validate the JWT token generated by
+/// Forgejo Actions. It contains the claims that are relevant for our "Trusted
+/// Publishing" implementation.
+///
+/// Forgejo mirrors the claim names used by GitHub Actions, but since every
+/// Forgejo instance is its own issuer, the expected issuer URL has to be
+/// passed in explicitly when decoding a token.
+///
+/// See <https://forgejo.org/docs/latest/user/actions/security-openid-connect/>.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ForgejoClaims {
+    pub aud: String,
+    #[serde(with = "ts_seconds")]
+    pub iat: DateTime<Utc>,
+    #[serde(with = "ts_seconds")]
+    pub exp: DateTime<Utc>,
+    pub jti: String,
+
+    pub repository_owner_id: String,
+    pub repository: String,
+    pub workflow_ref: String,
+    pub event_name: String,
+    pub run_id: String,
+    pub sha: String,
+}
+
+impl ForgejoClaims {
+    /// Decode and validate a JWT token, returning the relevant claims if valid.
+    pub fn decode(
+        token: &str,
+        issuer: &str,
+        audience: &str,
+        key: &DecodingKey,
+    ) -> Result<Self, Error> {
+        let validation = validation(issuer, audience);
+
+        let claims: Self = jsonwebtoken::decode(token, key, &validation)?.claims;
+
+        let leeway = chrono::TimeDelta::seconds(validation.leeway as i64);
+        if claims.iat > Utc::now() + leeway {
+            return Err(ErrorKind::ImmatureSignature.into());
+        }
+
+        Ok(claims)
+    }
+
+    /// Extract the workflow filename from the [`workflow_ref`](Self::workflow_ref)
+    /// field or return `None` if the filename cannot be extracted.
+    pub fn workflow_filename(&self) -> Option<&str> {
+        extract_workflow_filename(&self.workflow_ref)
+    }
+}
+
+fn validation(issuer: &str, audience: &str) -> Validation {
+    let mut validation = Validation::new(Algorithm::RS256);
+    validation.required_spec_claims.insert("iss".into());
+    validation.required_spec_claims.insert("exp".into());
+    validation.required_spec_claims.insert("aud".into());
+    validation.validate_exp = true;
+    validation.validate_aud = true;
+    validation.validate_nbf = true;
+    validation.set_issuer(&[issuer]);
+    validation.set_audience(&[audience]);
+    validation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forgejo::FORGEJO_ISSUER_URL;
+    use crate::test_keys::{DECODING_KEY, encode_for_testing};
+    use insta::{assert_compact_debug_snapshot, assert_json_snapshot};
+    use serde_json::json;
+    use std::time::SystemTime;
+
+    const AUDIENCE: &str = "crates.io";
+
+    #[test]
+    fn test_decode() -> anyhow::Result<()> {
+        let now = SystemTime::UNIX_EPOCH.elapsed()?.as_secs();
+
+        let jwt = encode_for_testing(&json!({
+          "jti": "example-id",
+          "sub": "repo:octo-org/octo-repo:ref:refs/heads/main",
+          "aud": AUDIENCE,
+          "ref": "refs/heads/main",
+          "sha": "example-sha",
+          "repository": "octo-org/octo-repo",
+          "repository_owner": "octo-org",
+          "repository_owner_id": "65",
+          "run_id": "example-run-id",
+          "run_number": "10",
+          "run_attempt": "1",
+          "actor": "octocat",
+          "workflow": "release.yml",
+          "head_ref": "",
+          "base_ref": "",
+          "event_name": "push",
+          "ref_type": "branch",
+          "workflow_ref": "octo-org/octo-repo/.forgejo/workflows/release.yml@refs/heads/main",
+          "iss": FORGEJO_ISSUER_URL,
+          "nbf": now,
+          "exp": now + 30,
+          "iat": now,
+        }))?;
+
+        let claims = ForgejoClaims::decode(&jwt, FORGEJO_ISSUER_URL, AUDIENCE, &DECODING_KEY)?;
+        assert_json_snapshot!(claims, { ".iat" => "[datetime]", ".exp" => "[datetime]" }, @r#"
+        {
+          "aud": "crates.io",
+          "iat": "[datetime]",
+          "exp": "[datetime]",
+          "jti": "example-id",
+          "repository_owner_id": "65",
+          "repository": "octo-org/octo-repo",
+          "workflow_ref": "octo-org/octo-repo/.forgejo/workflows/release.yml@refs/heads/main",
+          "event_name": "push",
+          "run_id": "example-run-id",
+          "sha": "example-sha"
+        }
+        "#);
+
+        assert_eq!(claims.workflow_filename(), Some("release.yml"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_custom_issuer() -> anyhow::Result<()> {
+        const ISSUER: &str = "https://forgejo.example.com/api/actions";
+
+        let now = SystemTime::UNIX_EPOCH.elapsed()?.as_secs();
+
+        let jwt = encode_for_testing(&json!({
+          "jti": "example-id",
+          "aud": AUDIENCE,
+          "repository": "octo-org/octo-repo",
+          "repository_owner_id": "65",
+          "event_name": "push",
+          "run_id": "example-run-id",
+          "sha": "example-sha",
+          "workflow_ref": "octo-org/octo-repo/.forgejo/workflows/release.yml@refs/heads/main",
+          "iss": ISSUER,
+          "exp": now + 30,
+          "iat": now,
+        }))?;
+
+        let claims = ForgejoClaims::decode(&jwt, ISSUER, AUDIENCE, &DECODING_KEY)?;
+        assert_eq!(claims.repository, "octo-org/octo-repo");
+
+        let error =
+            ForgejoClaims::decode(&jwt, FORGEJO_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
+        assert_compact_debug_snapshot!(error, @"Error(InvalidIssuer)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_missing_jti() -> anyhow::Result<()> {
+        let now = SystemTime::UNIX_EPOCH.elapsed()?.as_secs();
+
+        let jwt = encode_for_testing(&json!({
+          "aud": AUDIENCE,
+          "repository": "octo-org/octo-repo",
+          "repository_owner_id": "65",
+          "event_name": "push",
+          "run_id": "example-run-id",
+          "sha": "example-sha",
+          "workflow_ref": "octo-org/octo-repo/.forgejo/workflows/release.yml@refs/heads/main",
+          "iss": FORGEJO_ISSUER_URL,
+          "exp": now + 30,
+          "iat": now,
+        }))?;
+
+        let error =
+            ForgejoClaims::decode(&jwt, FORGEJO_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
+        assert_compact_debug_snapshot!(error, @r#"Error(Json(Error("missing field `jti`", line: 1, column: 304)))"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_wrong_audience() -> anyhow::Result<()> {
+        let now = SystemTime::UNIX_EPOCH.elapsed()?.as_secs();
+
+        let jwt = encode_for_testing(&json!({
+          "jti": "example-id",
+          "aud": "somebody-else",
+          "repository": "octo-org/octo-repo",
+          "repository_owner_id": "65",
+          "event_name": "push",
+          "run_id": "example-run-id",
+          "sha": "example-sha",
+          "workflow_ref": "octo-org/octo-repo/.forgejo/workflows/release.yml@refs/heads/main",
+          "iss": FORGEJO_ISSUER_URL,
+          "exp": now + 30,
+          "iat": now,
+        }))?;
+
+        let error =
+            ForgejoClaims::decode(&jwt, FORGEJO_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
+        assert_compact_debug_snapshot!(error, @"Error(InvalidAudience)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_missing_owner_id() -> anyhow::Result<()> {
+        let now = SystemTime::UNIX_EPOCH.elapsed()?.as_secs();
+
+        let jwt = encode_for_testing(&json!({
+          "jti": "example-id",
+          "aud": AUDIENCE,
+          "repository": "octo-org/octo-repo",
+          "event_name": "push",
+          "run_id": "example-run-id",
+          "sha": "example-sha",
+          "workflow_ref": "octo-org/octo-repo/.forgejo/workflows/release.yml@refs/heads/main",
+          "iss": FORGEJO_ISSUER_URL,
+          "exp": now + 30,
+          "iat": now,
+        }))?;
+
+        let error =
+            ForgejoClaims::decode(&jwt, FORGEJO_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
+        assert_compact_debug_snapshot!(error, @r#"Error(Json(Error("missing field `repository_owner_id`", line: 1, column: 296)))"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_missing_issuer() -> anyhow::Result<()> {
+        let now = SystemTime::UNIX_EPOCH.elapsed()?.as_secs();
+
+        let jwt = encode_for_testing(&json!({
+          "jti": "example-id",
+          "aud": AUDIENCE,
+          "repository": "octo-org/octo-repo",
+          "repository_owner_id": "65",
+          "event_name": "push",
+          "run_id": "example-run-id",
+          "sha": "example-sha",
+          "workflow_ref": "octo-org/octo-repo/.forgejo/workflows/release.yml@refs/heads/main",
+          "exp": now + 30,
+          "iat": now,
+        }))?;
+
+        let error =
+            ForgejoClaims::decode(&jwt, FORGEJO_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
+        assert_compact_debug_snapshot!(error, @r#"Error(MissingRequiredClaim("iss"))"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_expired() -> anyhow::Result<()> {
+        let now = SystemTime::UNIX_EPOCH.elapsed()?.as_secs();
+
+        let jwt = encode_for_testing(&json!({
+          "jti": "example-id",
+          "aud": AUDIENCE,
+          "repository": "octo-org/octo-repo",
+          "repository_owner_id": "65",
+          "event_name": "push",
+          "run_id": "example-run-id",
+          "sha": "example-sha",
+          "workflow_ref": "octo-org/octo-repo/.forgejo/workflows/release.yml@refs/heads/main",
+          "iss": FORGEJO_ISSUER_URL,
+          "exp": now - 3000,
+          "iat": now - 6000,
+        }))?;
+
+        let error =
+            ForgejoClaims::decode(&jwt, FORGEJO_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
+        assert_compact_debug_snapshot!(error, @"Error(ExpiredSignature)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_future_iat() -> anyhow::Result<()> {
+        let now = SystemTime::UNIX_EPOCH.elapsed()?.as_secs();
+
+        let jwt = encode_for_testing(&json!({
+          "jti": "example-id",
+          "aud": AUDIENCE,
+          "repository": "octo-org/octo-repo",
+          "repository_owner_id": "65",
+          "event_name": "push",
+          "run_id": "example-run-id",
+          "sha": "example-sha",
+          "workflow_ref": "octo-org/octo-repo/.forgejo/workflows/release.yml@refs/heads/main",
+          "iss": FORGEJO_ISSUER_URL,
+          "exp": now + 3000,
+          "iat": now + 600,
+        }))?;
+
+        let error =
+            ForgejoClaims::decode(&jwt, FORGEJO_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
+        assert_compact_debug_snapshot!(error, @"Error(ImmatureSignature)");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,11 @@
+mod claims;
+#[cfg(any(test, feature = "test-helpers"))]
+pub mod test_helpers;
+pub mod validation;
+
+pub use self::claims::ForgejoClaims;
+
+/// The default issuer URL for Forgejo Actions OIDC tokens, pointing at the
+/// Codeberg instance. Self-hosted Forgejo instances use their own base URL,
+/// which can be configured on the server.
+pub const FORGEJO_ISSUER_URL: &str = "https://codeberg.org/api/actions";
@@ -0,0 +1,117 @@
+use super::FORGEJO_ISSUER_URL;
+use crate::test_keys::encode_for_testing;
+use bon::bon;
+use serde_json::json;
+
+pub const AUDIENCE: &str = "crates.io";
+
+/// A struct representing all the claims in a Forgejo Actions OIDC token.
+///
+/// This struct is used to create a JWT for testing purposes.
+#[derive(Debug, serde::Serialize)]
+pub struct FullForgejoClaims {
+    pub iss: String,
+    pub nbf: i64,
+    pub exp: i64,
+    pub iat: i64,
+    pub jti: String,
+    pub sub: String,
+    pub aud: String,
+
+    #[serde(rename = "ref")]
+    pub r#ref: String,
+    pub sha: String,
+    pub repository: String,
+    pub repository_owner: String,
+    pub repository_owner_id: String,
+    pub run_id: String,
+    pub run_number: String,
+    pub run_attempt: String,
+    pub actor: String,
+    pub workflow: String,
+    pub head_ref: String,
+    pub base_ref: String,
+    pub event_name: String,
+    pub ref_type: String,
+    pub workflow_ref: String,
+}
+
+#[bon]
+impl FullForgejoClaims {
+    #[builder]
+    pub fn new(
+        owner_id: i32,
+        owner_name: &str,
+        repository_name: &str,
+        workflow_filename: &str,
+        issuer: Option<&str>,
+    ) -> Self {
+        let now = chrono::Utc::now().timestamp();
+
+        Self {
+            iss: issuer.unwrap_or(FORGEJO_ISSUER_URL).into(),
+            nbf: now,
+            iat: now,
+            exp: now + 30 * 60,
+            jti: "example-id".into(),
+            sub: format!("repo:{owner_name}/{repository_name}:ref:refs/heads/main"),
+            aud: AUDIENCE.into(),
+
+            r#ref: "refs/heads/main".into(),
+            sha: "example-sha".into(),
+            repository: format!("{owner_name}/{repository_name}"),
+            repository_owner: owner_name.into(),
+            repository_owner_id: owner_id.to_string(),
+            run_id: "example-run-id".into(),
+            run_number: "10".into(),
+            run_attempt: "1".into(),
+            actor: "octocat".into(),
+            workflow: workflow_filename.into(),
+            head_ref: "".into(),
+            base_ref: "".into(),
+            event_name: "push".into(),
+            ref_type: "branch".into(),
+            workflow_ref: format!(
+                "{owner_name}/{repository_name}/.forgejo/workflows/{workflow_filename}@refs/heads/main"
+            ),
+        }
+    }
+
+    pub fn encoded(&self) -> anyhow::Result<String> {
+        Ok(encode_for_testing(self)?)
+    }
+
+    pub fn as_exchange_body(&self) -> anyhow::Result<String> {
+        let jwt = self.encoded()?;
+        Ok(serde_json::to_string(&json!({ "jwt": jwt }))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::assert_ok;
+    use insta::assert_json_snapshot;
+
+    #[test]
+    fn test_forgejo_claims() {
+        let claims = FullForgejoClaims::builder()
+            .owner_id(123)
+            .owner_name("octocat")
+            .repository_name("hello-world")
+            .workflow_filename("release.yml")
+            .build();
+
+        assert_json_snapshot!(claims, {
+            ".nbf" => "[timestamp]",
+            ".iat" => "[timestamp]",
+            ".exp" => "[timestamp]",
+        });
+
+        let encoded = assert_ok!(claims.encoded());
+        assert!(!encoded.is_empty());
+
+        let exchange_body = assert_ok!(claims.as_exchange_body());
+        assert!(exchange_body.contains(&encoded));
+    }
+}
@@ -25,6 +25,9 @@ pub struct GitHubClaims {
     pub repository: String,
     pub workflow_ref: String,
     pub environment: Option<String>,
+    /// Fully qualified git ref that triggered the workflow run (e.g. `refs/heads/main`)
+    #[serde(rename = "ref")]
+    pub r#ref: Option<String>,
     pub event_name: String,
     pub run_id: String,
     pub sha: String,
@@ -120,6 +123,7 @@ mod tests {
           "repository": "octo-org/octo-repo",
           "workflow_ref": "octo-org/octo-automation/.github/workflows/oidc.yml@refs/heads/main",
           "environment": "prod",
+          "ref": "refs/heads/main",
           "event_name": "workflow_dispatch",
           "run_id": "example-run-id",
           "sha": "example-sha"
@@ -158,6 +162,7 @@ mod tests {
           "repository": "octo-org/octo-repo",
           "workflow_ref": "octo-org/octo-automation/.github/workflows/oidc.yml@refs/heads/main",
           "environment": null,
+          "ref": null,
           "event_name": "workflow_dispatch",
           "run_id": "example-run-id",
           "sha": "example-sha"
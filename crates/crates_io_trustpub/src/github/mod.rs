@@ -2,7 +2,7 @@ mod claims;
 #[cfg(any(test, feature = "test-helpers"))]
 pub mod test_helpers;
 pub mod validation;
-mod workflows;
+pub(crate) mod workflows;
 
 pub use claims::GitHubClaims;
 
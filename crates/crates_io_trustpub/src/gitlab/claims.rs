@@ -1,4 +1,3 @@
-use crate::gitlab::GITLAB_ISSUER_URL;
 use crate::gitlab::workflows::extract_workflow_filepath;
 use chrono::serde::ts_seconds;
 use chrono::{DateTime, Utc};
@@ -11,6 +10,9 @@ use jsonwebtoken::{Algorithm, DecodingKey, Validation};
 /// GitLab CI. It contains the claims that are relevant for our "Trusted
 /// Publishing" implementation.
 ///
+/// Since GitLab can be self-hosted, the expected issuer URL has to be passed
+/// in explicitly when decoding a token.
+///
 /// See <https://docs.gitlab.com/ci/secrets/id_token_authentication/#token-payload>.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct GitLabClaims {
@@ -25,14 +27,21 @@ pub struct GitLabClaims {
     pub project_path: String,
     pub ci_config_ref_uri: String,
     pub environment: Option<String>,
+    /// Fully qualified git ref of the pipeline (e.g. `refs/heads/main`)
+    pub ref_path: Option<String>,
     pub job_id: String,
     pub sha: String,
 }
 
 impl GitLabClaims {
     /// Decode and validate a JWT token, returning the relevant claims if valid.
-    pub fn decode(token: &str, audience: &str, key: &DecodingKey) -> Result<Self, Error> {
-        let validation = validation(audience);
+    pub fn decode(
+        token: &str,
+        issuer: &str,
+        audience: &str,
+        key: &DecodingKey,
+    ) -> Result<Self, Error> {
+        let validation = validation(issuer, audience);
 
         let claims: Self = jsonwebtoken::decode(token, key, &validation)?.claims;
 
@@ -51,7 +60,7 @@ impl GitLabClaims {
     }
 }
 
-fn validation(audience: &str) -> Validation {
+fn validation(issuer: &str, audience: &str) -> Validation {
     let mut validation = Validation::new(Algorithm::RS256);
     validation.required_spec_claims.insert("iss".into());
     validation.required_spec_claims.insert("exp".into());
@@ -59,7 +68,7 @@ fn validation(audience: &str) -> Validation {
     validation.validate_exp = true;
     validation.validate_aud = true;
     validation.validate_nbf = true;
-    validation.set_issuer(&[GITLAB_ISSUER_URL]);
+    validation.set_issuer(&[issuer]);
     validation.set_audience(&[audience]);
     validation
 }
@@ -67,6 +76,7 @@ fn validation(audience: &str) -> Validation {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::gitlab::GITLAB_ISSUER_URL;
     use crate::test_keys::{DECODING_KEY, encode_for_testing};
     use insta::{assert_compact_debug_snapshot, assert_json_snapshot};
     use serde_json::json;
@@ -117,7 +127,7 @@ mod tests {
             "aud": AUDIENCE
         }))?;
 
-        let claims = GitLabClaims::decode(&jwt, AUDIENCE, &DECODING_KEY)?;
+        let claims = GitLabClaims::decode(&jwt, GITLAB_ISSUER_URL, AUDIENCE, &DECODING_KEY)?;
         assert_json_snapshot!(claims, { ".iat" => "[datetime]", ".exp" => "[datetime]" }, @r#"
         {
           "aud": "crates.io",
@@ -128,6 +138,7 @@ mod tests {
           "project_path": "my-group/my-project",
           "ci_config_ref_uri": "gitlab.example.com/my-group/my-project//.gitlab-ci.yml@refs/heads/main",
           "environment": "test-environment2",
+          "ref_path": "refs/heads/feature-branch-1",
           "job_id": "302",
           "sha": "714a629c0b401fdce83e847fc9589983fc6f46bc"
         }
@@ -154,7 +165,7 @@ mod tests {
             "aud": AUDIENCE
         }))?;
 
-        let claims = GitLabClaims::decode(&jwt, AUDIENCE, &DECODING_KEY)?;
+        let claims = GitLabClaims::decode(&jwt, GITLAB_ISSUER_URL, AUDIENCE, &DECODING_KEY)?;
         assert_json_snapshot!(claims, { ".iat" => "[datetime]", ".exp" => "[datetime]" }, @r#"
         {
           "aud": "crates.io",
@@ -165,6 +176,7 @@ mod tests {
           "project_path": "my-group/my-project",
           "ci_config_ref_uri": "gitlab.example.com/my-group/my-project//.gitlab-ci.yml@refs/heads/main",
           "environment": "test-environment2",
+          "ref_path": null,
           "job_id": "302",
           "sha": "714a629c0b401fdce83e847fc9589983fc6f46bc"
         }
@@ -190,7 +202,8 @@ mod tests {
             "aud": AUDIENCE
         }))?;
 
-        let error = GitLabClaims::decode(&jwt, AUDIENCE, &DECODING_KEY).unwrap_err();
+        let error =
+            GitLabClaims::decode(&jwt, GITLAB_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
         assert_compact_debug_snapshot!(error, @r#"Error(Json(Error("missing field `jti`", line: 1, column: 328)))"#);
 
         Ok(())
@@ -214,7 +227,8 @@ mod tests {
             "aud": "somebody-else"
         }))?;
 
-        let error = GitLabClaims::decode(&jwt, AUDIENCE, &DECODING_KEY).unwrap_err();
+        let error =
+            GitLabClaims::decode(&jwt, GITLAB_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
         assert_compact_debug_snapshot!(error, @"Error(InvalidAudience)");
 
         Ok(())
@@ -238,7 +252,8 @@ mod tests {
             "aud": [AUDIENCE, "somebody-else"]
         }))?;
 
-        let error = GitLabClaims::decode(&jwt, AUDIENCE, &DECODING_KEY).unwrap_err();
+        let error =
+            GitLabClaims::decode(&jwt, GITLAB_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
         assert_compact_debug_snapshot!(error, @r#"Error(Json(Error("invalid type: sequence, expected a string", line: 1, column: 7)))"#);
 
         Ok(())
@@ -260,7 +275,8 @@ mod tests {
             "aud": AUDIENCE
         }))?;
 
-        let error = GitLabClaims::decode(&jwt, AUDIENCE, &DECODING_KEY).unwrap_err();
+        let error =
+            GitLabClaims::decode(&jwt, GITLAB_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
         assert_compact_debug_snapshot!(error, @r#"Error(Json(Error("missing field `project_path`", line: 1, column: 336)))"#);
 
         Ok(())
@@ -282,7 +298,8 @@ mod tests {
             "aud": AUDIENCE
         }))?;
 
-        let error = GitLabClaims::decode(&jwt, AUDIENCE, &DECODING_KEY).unwrap_err();
+        let error =
+            GitLabClaims::decode(&jwt, GITLAB_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
         assert_compact_debug_snapshot!(error, @r#"Error(Json(Error("missing field `namespace_id`", line: 1, column: 353)))"#);
 
         Ok(())
@@ -304,7 +321,8 @@ mod tests {
             "aud": AUDIENCE
         }))?;
 
-        let error = GitLabClaims::decode(&jwt, AUDIENCE, &DECODING_KEY).unwrap_err();
+        let error =
+            GitLabClaims::decode(&jwt, GITLAB_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
         assert_compact_debug_snapshot!(error, @r#"Error(Json(Error("missing field `ci_config_ref_uri`", line: 1, column: 280)))"#);
 
         Ok(())
@@ -327,7 +345,8 @@ mod tests {
             "aud": AUDIENCE
         }))?;
 
-        let error = GitLabClaims::decode(&jwt, AUDIENCE, &DECODING_KEY).unwrap_err();
+        let error =
+            GitLabClaims::decode(&jwt, GITLAB_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
         assert_compact_debug_snapshot!(error, @r#"Error(MissingRequiredClaim("iss"))"#);
 
         Ok(())
@@ -351,7 +370,37 @@ mod tests {
             "aud": AUDIENCE
         }))?;
 
-        let error = GitLabClaims::decode(&jwt, AUDIENCE, &DECODING_KEY).unwrap_err();
+        let error =
+            GitLabClaims::decode(&jwt, GITLAB_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
+        assert_compact_debug_snapshot!(error, @"Error(InvalidIssuer)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_self_hosted_issuer() -> anyhow::Result<()> {
+        const ISSUER: &str = "https://gitlab.example.com";
+
+        let now = SystemTime::UNIX_EPOCH.elapsed()?.as_secs();
+
+        let jwt = encode_for_testing(&json!({
+            "namespace_id": "72",
+            "project_path": "my-group/my-project",
+            "job_id": "302",
+            "sha": "714a629c0b401fdce83e847fc9589983fc6f46bc",
+            "ci_config_ref_uri": "gitlab.example.com/my-group/my-project//.gitlab-ci.yml@refs/heads/main",
+            "jti": "235b3a54-b797-45c7-ae9a-f72d7bc6ef5b",
+            "iss": ISSUER,
+            "iat": now,
+            "exp": now + 60 * 60,
+            "aud": AUDIENCE
+        }))?;
+
+        let claims = GitLabClaims::decode(&jwt, ISSUER, AUDIENCE, &DECODING_KEY)?;
+        assert_eq!(claims.project_path, "my-group/my-project");
+
+        let error =
+            GitLabClaims::decode(&jwt, GITLAB_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
         assert_compact_debug_snapshot!(error, @"Error(InvalidIssuer)");
 
         Ok(())
@@ -374,7 +423,8 @@ mod tests {
             "aud": AUDIENCE
         }))?;
 
-        let error = GitLabClaims::decode(&jwt, AUDIENCE, &DECODING_KEY).unwrap_err();
+        let error =
+            GitLabClaims::decode(&jwt, GITLAB_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
         assert_compact_debug_snapshot!(error, @r#"Error(Json(Error("missing field `exp`", line: 1, column: 356)))"#);
 
         Ok(())
@@ -398,7 +448,8 @@ mod tests {
             "aud": AUDIENCE
         }))?;
 
-        let error = GitLabClaims::decode(&jwt, AUDIENCE, &DECODING_KEY).unwrap_err();
+        let error =
+            GitLabClaims::decode(&jwt, GITLAB_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
         assert_compact_debug_snapshot!(error, @"Error(ExpiredSignature)");
 
         Ok(())
@@ -421,7 +472,8 @@ mod tests {
             "aud": AUDIENCE
         }))?;
 
-        let error = GitLabClaims::decode(&jwt, AUDIENCE, &DECODING_KEY).unwrap_err();
+        let error =
+            GitLabClaims::decode(&jwt, GITLAB_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
         assert_compact_debug_snapshot!(error, @r#"Error(Json(Error("missing field `iat`", line: 1, column: 356)))"#);
 
         Ok(())
@@ -445,7 +497,8 @@ mod tests {
             "aud": AUDIENCE
         }))?;
 
-        let error = GitLabClaims::decode(&jwt, AUDIENCE, &DECODING_KEY).unwrap_err();
+        let error =
+            GitLabClaims::decode(&jwt, GITLAB_ISSUER_URL, AUDIENCE, &DECODING_KEY).unwrap_err();
         assert_compact_debug_snapshot!(error, @"Error(ImmatureSignature)");
 
         Ok(())
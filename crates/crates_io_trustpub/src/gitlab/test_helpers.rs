@@ -63,11 +63,15 @@ impl FullGitLabClaims {
         project: &str,
         workflow_filepath: &str,
         environment: Option<&str>,
+        issuer: Option<&str>,
     ) -> Self {
         let now = chrono::Utc::now().timestamp();
 
+        let issuer = issuer.unwrap_or(GITLAB_ISSUER_URL);
+        let host = issuer.trim_start_matches("https://");
+
         Self {
-            iss: GITLAB_ISSUER_URL.into(),
+            iss: issuer.into(),
             nbf: now,
             iat: now,
             exp: now + 60 * 60,
@@ -103,7 +107,7 @@ impl FullGitLabClaims {
             sha: "76719c2658b5c4423810d655a4624af1b38b7091".into(),
             project_visibility: "public".into(),
             ci_config_ref_uri: format!(
-                "gitlab.com/{namespace}/{project}//{workflow_filepath}@refs/heads/main"
+                "{host}/{namespace}/{project}//{workflow_filepath}@refs/heads/main"
             ),
             ci_config_sha: "76719c2658b5c4423810d655a4624af1b38b7091".into(),
         }
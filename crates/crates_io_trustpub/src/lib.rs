@@ -1,9 +1,11 @@
 #![doc = include_str!("../README.md")]
 
 pub mod access_token;
+pub mod forgejo;
 pub mod github;
 pub mod gitlab;
 pub mod keystore;
+pub mod ref_pattern;
 #[cfg(any(test, feature = "test-helpers"))]
 pub mod test_keys;
 pub mod unverified;
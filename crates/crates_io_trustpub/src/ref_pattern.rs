@@ -0,0 +1,135 @@
+//! Validation and matching of the optional git ref patterns (e.g.
+//! `refs/tags/v*`) that restrict which refs a Trusted Publishing config
+//! accepts.
+//!
+//! The only supported wildcard is `*`, which matches any (possibly empty)
+//! sequence of characters, including `/`. This is intentionally implemented
+//! without regular expressions to avoid potential ReDoS attack vectors.
+
+const MAX_PATTERN_LENGTH: usize = 255;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ValidationError {
+    #[error("Ref pattern may not be empty (use `null` to omit)")]
+    Empty,
+    #[error("Ref pattern is too long (maximum is {MAX_PATTERN_LENGTH} characters)")]
+    TooLong,
+    #[error("Ref pattern must start with `refs/`")]
+    MissingPrefix,
+    #[error("Ref pattern may not contain whitespace or control characters")]
+    InvalidCharacters,
+}
+
+pub fn validate_ref_pattern(pattern: &str) -> Result<(), ValidationError> {
+    if pattern.is_empty() {
+        Err(ValidationError::Empty)
+    } else if pattern.len() > MAX_PATTERN_LENGTH {
+        Err(ValidationError::TooLong)
+    } else if !pattern.starts_with("refs/") {
+        Err(ValidationError::MissingPrefix)
+    } else if pattern.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        Err(ValidationError::InvalidCharacters)
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns `true` if the fully qualified git ref (e.g. `refs/tags/v1.0.0`)
+/// matches the given pattern.
+pub fn matches_ref_pattern(pattern: &str, git_ref: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let git_ref = git_ref.as_bytes();
+
+    let mut pattern_index = 0;
+    let mut ref_index = 0;
+
+    // Position after the last seen `*` in the pattern, and the position in
+    // the ref that this `*` is currently matched up to.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while ref_index < git_ref.len() {
+        match pattern.get(pattern_index) {
+            Some(b'*') => {
+                pattern_index += 1;
+                backtrack = Some((pattern_index, ref_index));
+            }
+            Some(&c) if c == git_ref[ref_index] => {
+                pattern_index += 1;
+                ref_index += 1;
+            }
+            _ => {
+                // Let the last `*` consume one more character and try again
+                let Some((star_pattern_index, star_ref_index)) = backtrack else {
+                    return false;
+                };
+
+                pattern_index = star_pattern_index;
+                ref_index = star_ref_index + 1;
+                backtrack = Some((star_pattern_index, ref_index));
+            }
+        }
+    }
+
+    pattern[pattern_index..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok};
+    use insta::assert_snapshot;
+
+    #[test]
+    fn test_validate_ref_pattern() {
+        assert_snapshot!(assert_err!(validate_ref_pattern("")), @"Ref pattern may not be empty (use `null` to omit)");
+        assert_snapshot!(assert_err!(validate_ref_pattern(&format!("refs/{}", "x".repeat(251)))), @"Ref pattern is too long (maximum is 255 characters)");
+        assert_snapshot!(assert_err!(validate_ref_pattern("main")), @"Ref pattern must start with `refs/`");
+        assert_snapshot!(assert_err!(validate_ref_pattern("refs/heads/foo bar")), @"Ref pattern may not contain whitespace or control characters");
+
+        assert_ok!(validate_ref_pattern("refs/heads/main"));
+        assert_ok!(validate_ref_pattern("refs/tags/v*"));
+        assert_ok!(validate_ref_pattern("refs/*"));
+    }
+
+    #[test]
+    fn test_matches_ref_pattern() {
+        let test_cases = [
+            ("refs/heads/main", "refs/heads/main", true),
+            ("refs/heads/main", "refs/heads/main2", false),
+            ("refs/heads/main", "refs/heads/mai", false),
+            ("refs/heads/main", "refs/tags/main", false),
+            ("refs/tags/v*", "refs/tags/v1.0.0", true),
+            ("refs/tags/v*", "refs/tags/v", true),
+            ("refs/tags/v*", "refs/tags/1.0.0", false),
+            ("refs/tags/v*", "refs/heads/v1.0.0", false),
+            ("refs/tags/*", "refs/tags/foo/bar", true),
+            ("refs/tags/v*.*.*", "refs/tags/v1.2.3", true),
+            ("refs/tags/v*.*.*", "refs/tags/v1.2", false),
+            ("refs/tags/*-release", "refs/tags/1.0-release", true),
+            (
+                "refs/tags/*-release",
+                "refs/tags/1.0-release-candidate",
+                false,
+            ),
+            ("refs/heads/release/**", "refs/heads/release/1.x", true),
+            ("refs/*/main", "refs/heads/main", true),
+            ("refs/*/main", "refs/heads/not-main", false),
+            ("refs/tags/v*", "", false),
+        ];
+
+        for (pattern, git_ref, expected) in test_cases {
+            assert_eq!(
+                matches_ref_pattern(pattern, git_ref),
+                expected,
+                "pattern: {pattern}, ref: {git_ref}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_matches_ref_pattern_pathological() {
+        let pattern = format!("refs/{}b", "*a".repeat(100));
+        let git_ref = format!("refs/{}", "a".repeat(10_000));
+        assert!(!matches_ref_pattern(&pattern, &git_ref));
+    }
+}
@@ -32,6 +32,11 @@ impl<Context: Clone + Send + Sync + 'static> Runner<Context> {
         }
     }
 
+    /// Returns the context that is passed to the jobs of this runner.
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
     /// Register a new job type for this job runner.
     pub fn register_job_type<J: BackgroundJob<Context = Context>>(mut self) -> Self {
         let queue = self.queues.entry(J::QUEUE.into()).or_default();
@@ -0,0 +1,18 @@
+CREATE TABLE trustpub_configs_forgejo (
+    id SERIAL PRIMARY KEY,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    crate_id INTEGER NOT NULL REFERENCES crates ON DELETE CASCADE,
+    repository_owner VARCHAR NOT NULL,
+    repository_owner_id VARCHAR,
+    repository_name VARCHAR NOT NULL,
+    workflow_filename VARCHAR NOT NULL
+);
+
+comment on table trustpub_configs_forgejo is 'Trusted Publisher configuration for Forgejo Actions';
+comment on column trustpub_configs_forgejo.id is 'Unique identifier of the `trustpub_configs_forgejo` row';
+comment on column trustpub_configs_forgejo.created_at is 'Date and time when the configuration was created';
+comment on column trustpub_configs_forgejo.crate_id is 'Unique identifier of the crate that this configuration is for';
+comment on column trustpub_configs_forgejo.repository_owner is 'Forgejo name of the user or organization that owns the repository';
+comment on column trustpub_configs_forgejo.repository_owner_id is 'Forgejo ID of the user or organization that owns the repository, populated on first token exchange for resurrection attack protection';
+comment on column trustpub_configs_forgejo.repository_name is 'Name of the Forgejo repository that this configuration is for';
+comment on column trustpub_configs_forgejo.workflow_filename is 'Name of the Forgejo Actions workflow file that will be used to publish the crate';
@@ -0,0 +1 @@
+ALTER TABLE trustpub_configs_gitlab DROP COLUMN issuer;
@@ -0,0 +1,2 @@
+ALTER TABLE trustpub_configs_gitlab ADD COLUMN issuer VARCHAR NOT NULL DEFAULT 'https://gitlab.com';
+COMMENT ON COLUMN trustpub_configs_gitlab.issuer IS 'OIDC issuer URL of the GitLab instance that hosts the project (e.g. `https://gitlab.com`)';
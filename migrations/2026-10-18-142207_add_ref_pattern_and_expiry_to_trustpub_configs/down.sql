@@ -0,0 +1,7 @@
+ALTER TABLE trustpub_configs_gitlab
+    DROP COLUMN ref_pattern,
+    DROP COLUMN expires_at;
+
+ALTER TABLE trustpub_configs_github
+    DROP COLUMN ref_pattern,
+    DROP COLUMN expires_at;
@@ -0,0 +1,13 @@
+ALTER TABLE trustpub_configs_github
+    ADD COLUMN ref_pattern VARCHAR,
+    ADD COLUMN expires_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN trustpub_configs_github.ref_pattern IS 'Pattern that the git ref of the workflow run has to match, with `*` as wildcard (if `NULL` the ref is unrestricted)';
+COMMENT ON COLUMN trustpub_configs_github.expires_at IS 'Date and time after which the configuration can no longer be used (if `NULL` the configuration does not expire)';
+
+ALTER TABLE trustpub_configs_gitlab
+    ADD COLUMN ref_pattern VARCHAR,
+    ADD COLUMN expires_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN trustpub_configs_gitlab.ref_pattern IS 'Pattern that the git ref of the pipeline has to match, with `*` as wildcard (if `NULL` the ref is unrestricted)';
+COMMENT ON COLUMN trustpub_configs_gitlab.expires_at IS 'Date and time after which the configuration can no longer be used (if `NULL` the configuration does not expire)';
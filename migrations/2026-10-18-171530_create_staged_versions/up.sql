@@ -0,0 +1,25 @@
+CREATE TABLE staged_versions (
+    id SERIAL PRIMARY KEY,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    crate_id INTEGER NOT NULL REFERENCES crates ON DELETE CASCADE,
+    num VARCHAR NOT NULL,
+    checksum CHAR(64) NOT NULL,
+    readme TEXT,
+    readme_file VARCHAR,
+    published_by INTEGER REFERENCES users ON DELETE SET NULL,
+    publisher_email VARCHAR,
+    trustpub_data JSONB,
+    UNIQUE (crate_id, num)
+);
+
+COMMENT ON TABLE staged_versions IS 'Versions that have been uploaded in "staged" mode, but not released yet';
+COMMENT ON COLUMN staged_versions.id IS 'Unique identifier of the `staged_versions` row';
+COMMENT ON COLUMN staged_versions.created_at IS 'Date and time when the version was staged';
+COMMENT ON COLUMN staged_versions.crate_id IS 'Unique identifier of the crate that this version belongs to';
+COMMENT ON COLUMN staged_versions.num IS 'Version number of the staged version';
+COMMENT ON COLUMN staged_versions.checksum IS 'SHA256 checksum of the uploaded `.crate` file';
+COMMENT ON COLUMN staged_versions.readme IS 'README content that was submitted with the publish request';
+COMMENT ON COLUMN staged_versions.readme_file IS 'Path of the README file that was submitted with the publish request';
+COMMENT ON COLUMN staged_versions.published_by IS 'Unique identifier of the user that staged the version (`NULL` for Trusted Publishing)';
+COMMENT ON COLUMN staged_versions.publisher_email IS 'Verified email address of the user that staged the version';
+COMMENT ON COLUMN staged_versions.trustpub_data IS 'JSONB data containing JWT claims from the trusted publisher that staged the version';
@@ -0,0 +1,8 @@
+ALTER TABLE crates
+    ADD COLUMN deprecated_at TIMESTAMPTZ,
+    ADD COLUMN deprecation_reason VARCHAR,
+    ADD COLUMN deprecation_successor VARCHAR;
+
+COMMENT ON COLUMN crates.deprecated_at IS 'Date and time when this crate was marked as deprecated by its owners, or NULL if the crate is not deprecated';
+COMMENT ON COLUMN crates.deprecation_reason IS 'Explanation given by the owners for why this crate is deprecated';
+COMMENT ON COLUMN crates.deprecation_successor IS 'Name of the crate that should be used instead of this deprecated crate, if any';
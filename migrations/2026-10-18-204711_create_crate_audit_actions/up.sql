@@ -0,0 +1,27 @@
+CREATE TABLE crate_audit_actions (
+    id SERIAL PRIMARY KEY,
+    crate_id INTEGER REFERENCES crates ON DELETE SET NULL,
+    crate_name VARCHAR NOT NULL,
+    user_id INTEGER REFERENCES users ON DELETE SET NULL,
+    api_token_id INTEGER REFERENCES api_tokens ON DELETE SET NULL,
+    trustpub_data JSONB,
+    action INTEGER NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}',
+    time TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- safety-assured:start
+-- This table doesn't exist yet, so creating this index concurrently isn't necessary.
+CREATE INDEX index_crate_audit_actions_crate_id ON crate_audit_actions (crate_id, id);
+-- safety-assured:end
+
+COMMENT ON TABLE crate_audit_actions IS 'Audit log of owner, settings and Trusted Publishing changes made to crates';
+COMMENT ON COLUMN crate_audit_actions.id IS 'Unique identifier of the `crate_audit_actions` row';
+COMMENT ON COLUMN crate_audit_actions.crate_id IS 'Unique identifier of the crate that the action was performed on (`NULL` if the crate has been deleted)';
+COMMENT ON COLUMN crate_audit_actions.crate_name IS 'Name of the crate that the action was performed on';
+COMMENT ON COLUMN crate_audit_actions.user_id IS 'Unique identifier of the user that performed the action (`NULL` for Trusted Publishing)';
+COMMENT ON COLUMN crate_audit_actions.api_token_id IS 'Unique identifier of the API token that was used to perform the action, if any';
+COMMENT ON COLUMN crate_audit_actions.trustpub_data IS 'JSONB data containing JWT claims from the trusted publisher that performed the action, if any';
+COMMENT ON COLUMN crate_audit_actions.action IS 'Type of the action (see `CrateAction` enum)';
+COMMENT ON COLUMN crate_audit_actions.details IS 'JSONB data with action-specific details';
+COMMENT ON COLUMN crate_audit_actions.time IS 'Date and time when the action was performed';
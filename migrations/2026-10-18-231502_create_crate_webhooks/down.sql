@@ -0,0 +1,2 @@
+DROP TABLE crate_webhook_deliveries;
+DROP TABLE crate_webhooks;
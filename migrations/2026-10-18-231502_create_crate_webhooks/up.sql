@@ -0,0 +1,53 @@
+CREATE TABLE crate_webhooks (
+    id SERIAL PRIMARY KEY,
+    crate_id INTEGER REFERENCES crates ON DELETE SET NULL,
+    url VARCHAR NOT NULL,
+    secret VARCHAR NOT NULL,
+    events TEXT[] NOT NULL,
+    created_by INTEGER REFERENCES users ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    consecutive_failures INTEGER NOT NULL DEFAULT 0,
+    disabled_at TIMESTAMPTZ
+);
+
+CREATE TABLE crate_webhook_deliveries (
+    id BIGSERIAL PRIMARY KEY,
+    webhook_id INTEGER NOT NULL REFERENCES crate_webhooks ON DELETE CASCADE,
+    event TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_attempt_at TIMESTAMPTZ,
+    response_status INTEGER,
+    error VARCHAR,
+    delivered_at TIMESTAMPTZ
+);
+
+-- safety-assured:start
+-- These tables don't exist yet, so creating these indexes concurrently isn't necessary.
+CREATE INDEX index_crate_webhooks_crate_id ON crate_webhooks (crate_id);
+CREATE INDEX index_crate_webhook_deliveries_webhook_id ON crate_webhook_deliveries (webhook_id, id);
+-- safety-assured:end
+
+COMMENT ON TABLE crate_webhooks IS 'Outbound webhook endpoints that are notified about crate events';
+COMMENT ON COLUMN crate_webhooks.id IS 'Unique identifier of the `crate_webhooks` row';
+COMMENT ON COLUMN crate_webhooks.crate_id IS 'Unique identifier of the crate that the webhook belongs to (`NULL` if the crate has been deleted and the deletion event is still being delivered)';
+COMMENT ON COLUMN crate_webhooks.url IS 'URL that the event payloads are sent to';
+COMMENT ON COLUMN crate_webhooks.secret IS 'Shared secret that is used to sign the event payloads with HMAC-SHA256';
+COMMENT ON COLUMN crate_webhooks.events IS 'Events that the webhook is subscribed to (see `WebhookEvent` enum)';
+COMMENT ON COLUMN crate_webhooks.created_by IS 'Unique identifier of the user that created the webhook';
+COMMENT ON COLUMN crate_webhooks.created_at IS 'Date and time when the webhook was created';
+COMMENT ON COLUMN crate_webhooks.consecutive_failures IS 'Number of failed delivery attempts since the last successful delivery';
+COMMENT ON COLUMN crate_webhooks.disabled_at IS 'Date and time when the webhook was disabled, either manually or due to repeated delivery failures';
+
+COMMENT ON TABLE crate_webhook_deliveries IS 'Delivery log of the events that were sent to `crate_webhooks`';
+COMMENT ON COLUMN crate_webhook_deliveries.id IS 'Unique identifier of the `crate_webhook_deliveries` row';
+COMMENT ON COLUMN crate_webhook_deliveries.webhook_id IS 'Unique identifier of the webhook that the event is delivered to';
+COMMENT ON COLUMN crate_webhook_deliveries.event IS 'Type of the event (see `WebhookEvent` enum)';
+COMMENT ON COLUMN crate_webhook_deliveries.payload IS 'JSONB payload that is sent to the webhook';
+COMMENT ON COLUMN crate_webhook_deliveries.created_at IS 'Date and time when the event occurred';
+COMMENT ON COLUMN crate_webhook_deliveries.attempts IS 'Number of delivery attempts so far';
+COMMENT ON COLUMN crate_webhook_deliveries.last_attempt_at IS 'Date and time of the last delivery attempt, if any';
+COMMENT ON COLUMN crate_webhook_deliveries.response_status IS 'HTTP status code of the response to the last delivery attempt, if any';
+COMMENT ON COLUMN crate_webhook_deliveries.error IS 'Error message of the last failed delivery attempt, if any';
+COMMENT ON COLUMN crate_webhook_deliveries.delivered_at IS 'Date and time when the event was successfully delivered, if it was';
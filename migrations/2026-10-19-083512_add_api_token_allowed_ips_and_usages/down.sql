@@ -0,0 +1,3 @@
+DROP TABLE api_token_usages;
+
+ALTER TABLE api_tokens DROP COLUMN allowed_ips;
@@ -0,0 +1,26 @@
+ALTER TABLE api_tokens ADD COLUMN allowed_ips TEXT[];
+
+COMMENT ON COLUMN api_tokens.allowed_ips IS 'NULL or an array of CIDR ranges that the token may be used from';
+
+CREATE TABLE api_token_usages (
+    id BIGSERIAL PRIMARY KEY,
+    api_token_id INTEGER NOT NULL REFERENCES api_tokens ON DELETE CASCADE,
+    used_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ip_address VARCHAR NOT NULL,
+    endpoint VARCHAR NOT NULL,
+    user_agent VARCHAR
+);
+
+-- safety-assured:start
+-- This table doesn't exist yet, so creating these indexes concurrently isn't necessary.
+CREATE INDEX index_api_token_usages_api_token_id ON api_token_usages (api_token_id, id);
+CREATE INDEX index_api_token_usages_used_at ON api_token_usages (used_at);
+-- safety-assured:end
+
+COMMENT ON TABLE api_token_usages IS 'Rolling log of API token usages, pruned after a retention period';
+COMMENT ON COLUMN api_token_usages.id IS 'Unique identifier of the `api_token_usages` row';
+COMMENT ON COLUMN api_token_usages.api_token_id IS 'Unique identifier of the API token that was used';
+COMMENT ON COLUMN api_token_usages.used_at IS 'Date and time when the token was used';
+COMMENT ON COLUMN api_token_usages.ip_address IS 'IP address of the client that used the token';
+COMMENT ON COLUMN api_token_usages.endpoint IS 'HTTP method and path of the request that used the token (e.g. `PUT /api/v1/crates/new`)';
+COMMENT ON COLUMN api_token_usages.user_agent IS 'User agent of the client that used the token, if available';
@@ -0,0 +1,5 @@
+ALTER TABLE staged_versions DROP COLUMN team_id;
+ALTER TABLE versions_published_by DROP COLUMN team_id;
+ALTER TABLE version_owner_actions DROP COLUMN team_id;
+ALTER TABLE api_tokens DROP COLUMN team_membership_verified_at;
+ALTER TABLE api_tokens DROP COLUMN team_id;
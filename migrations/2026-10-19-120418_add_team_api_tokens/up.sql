@@ -0,0 +1,17 @@
+ALTER TABLE api_tokens ADD COLUMN team_id INTEGER REFERENCES teams ON DELETE CASCADE;
+ALTER TABLE api_tokens ADD COLUMN team_membership_verified_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN api_tokens.team_id IS 'Unique identifier of the team that owns the token, or NULL for personal tokens. For team tokens, `user_id` refers to the team maintainer that created the token.';
+COMMENT ON COLUMN api_tokens.team_membership_verified_at IS 'Date and time when the team membership of the token creator was last verified (only used for team tokens)';
+
+ALTER TABLE version_owner_actions ADD COLUMN team_id INTEGER REFERENCES teams ON DELETE SET NULL;
+
+COMMENT ON COLUMN version_owner_actions.team_id IS 'Unique identifier of the team that performed the action via a team token, if any';
+
+ALTER TABLE versions_published_by ADD COLUMN team_id INTEGER REFERENCES teams ON DELETE SET NULL;
+
+COMMENT ON COLUMN versions_published_by.team_id IS 'Unique identifier of the team that published the version via a team token, if any';
+
+ALTER TABLE staged_versions ADD COLUMN team_id INTEGER REFERENCES teams ON DELETE SET NULL;
+
+COMMENT ON COLUMN staged_versions.team_id IS 'Unique identifier of the team that staged the version via a team token, if any';
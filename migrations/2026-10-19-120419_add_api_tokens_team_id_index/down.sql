@@ -0,0 +1 @@
+DROP INDEX index_api_tokens_team_id;
@@ -0,0 +1,2 @@
+CREATE INDEX CONCURRENTLY IF NOT EXISTS index_api_tokens_team_id
+ON api_tokens (team_id) WHERE team_id IS NOT NULL;
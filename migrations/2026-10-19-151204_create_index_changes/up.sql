@@ -0,0 +1,19 @@
+CREATE TABLE index_changes (
+    id BIGSERIAL PRIMARY KEY,
+    crate_name VARCHAR NOT NULL,
+    reason INTEGER NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    published_at TIMESTAMPTZ
+);
+
+-- safety-assured:start
+-- This table doesn't exist yet, so creating this index concurrently isn't necessary.
+CREATE INDEX index_index_changes_unpublished ON index_changes (id) WHERE published_at IS NULL;
+-- safety-assured:end
+
+COMMENT ON TABLE index_changes IS 'Append-only log of sparse index file modifications, used by mirrors to sync the index incrementally';
+COMMENT ON COLUMN index_changes.id IS 'Sequence number of the change';
+COMMENT ON COLUMN index_changes.crate_name IS 'Name of the crate whose index file was modified';
+COMMENT ON COLUMN index_changes.reason IS 'Type of the modification (see `IndexChangeReason` enum)';
+COMMENT ON COLUMN index_changes.changed_at IS 'Date and time when the index file was modified';
+COMMENT ON COLUMN index_changes.published_at IS 'Date and time when the change was published to the changes feed in the index storage (`NULL` if not published yet)';
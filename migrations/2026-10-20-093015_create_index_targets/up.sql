@@ -0,0 +1,12 @@
+CREATE TABLE index_targets (
+    path VARCHAR PRIMARY KEY,
+    length BIGINT NOT NULL,
+    sha256 BYTEA NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE index_targets IS 'Length and hash of every sparse index file, used to generate the signed `targets` metadata of the index';
+COMMENT ON COLUMN index_targets.path IS 'Path of the index file, relative to the index root';
+COMMENT ON COLUMN index_targets.length IS 'Length of the index file in bytes';
+COMMENT ON COLUMN index_targets.sha256 IS 'SHA256 hash of the index file';
+COMMENT ON COLUMN index_targets.updated_at IS 'Date and time when the index file was last uploaded';
@@ -0,0 +1,12 @@
+CREATE TABLE index_verifications (
+    id BIGSERIAL PRIMARY KEY,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    crates_checked INTEGER NOT NULL,
+    mismatches JSONB NOT NULL
+);
+
+COMMENT ON TABLE index_verifications IS 'Results of the periodic comparison of the database with the git and sparse indexes';
+COMMENT ON COLUMN index_verifications.id IS 'Unique identifier of the verification run';
+COMMENT ON COLUMN index_verifications.created_at IS 'Date and time when the verification run finished';
+COMMENT ON COLUMN index_verifications.crates_checked IS 'Number of crates that were checked';
+COMMENT ON COLUMN index_verifications.mismatches IS 'JSON array of the index files that do not match the database';
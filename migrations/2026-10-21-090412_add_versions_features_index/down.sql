@@ -0,0 +1 @@
+DROP INDEX CONCURRENTLY IF EXISTS versions_features_idx;
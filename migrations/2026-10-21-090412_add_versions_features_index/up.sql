@@ -0,0 +1,2 @@
+CREATE INDEX CONCURRENTLY IF NOT EXISTS versions_features_idx
+    ON versions USING gin (features);
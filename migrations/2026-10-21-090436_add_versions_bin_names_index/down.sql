@@ -0,0 +1 @@
+DROP INDEX CONCURRENTLY IF EXISTS versions_bin_names_idx;
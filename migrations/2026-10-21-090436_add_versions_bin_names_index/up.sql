@@ -0,0 +1,2 @@
+CREATE INDEX CONCURRENTLY IF NOT EXISTS versions_bin_names_idx
+    ON versions USING gin (bin_names);
@@ -0,0 +1,2 @@
+CREATE INDEX CONCURRENTLY IF NOT EXISTS versions_links_idx
+    ON versions (links) WHERE links IS NOT NULL;
@@ -0,0 +1,12 @@
+CREATE TABLE crate_search_scores (
+    crate_id INTEGER PRIMARY KEY REFERENCES crates (id) ON DELETE CASCADE,
+    reverse_dependencies BIGINT NOT NULL,
+    last_release_at TIMESTAMPTZ,
+    score REAL NOT NULL
+);
+
+COMMENT ON TABLE crate_search_scores IS 'Precomputed popularity scores that are used to weight the text rank of the search results. Refreshed by the `update_search_scores` background job.';
+COMMENT ON COLUMN crate_search_scores.crate_id IS 'Reference to the crate that this row belongs to';
+COMMENT ON COLUMN crate_search_scores.reverse_dependencies IS 'Number of crates whose default version depends on this crate';
+COMMENT ON COLUMN crate_search_scores.last_release_at IS 'Date and time of the latest non-yanked release of the crate, or NULL if all versions are yanked';
+COMMENT ON COLUMN crate_search_scores.score IS 'Factor that the text rank of the crate is multiplied with when sorting by relevance';
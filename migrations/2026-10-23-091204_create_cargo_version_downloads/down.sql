@@ -0,0 +1 @@
+DROP TABLE cargo_version_downloads;
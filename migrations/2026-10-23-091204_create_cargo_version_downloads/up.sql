@@ -0,0 +1,13 @@
+CREATE TABLE cargo_version_downloads (
+    crate_id INTEGER NOT NULL REFERENCES crates (id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    cargo_version VARCHAR NOT NULL,
+    downloads BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (crate_id, date, cargo_version)
+);
+
+COMMENT ON TABLE cargo_version_downloads IS 'Daily download counts of a crate, broken down by the cargo version that downloaded it. Filled by the `process_cdn_log` background job.';
+COMMENT ON COLUMN cargo_version_downloads.crate_id IS 'Reference to the crate that was downloaded';
+COMMENT ON COLUMN cargo_version_downloads.date IS 'Date on which the downloads happened';
+COMMENT ON COLUMN cargo_version_downloads.cargo_version IS 'Minor version of cargo (e.g. `1.75`) that downloaded the crate, or `other` if the client could not be determined';
+COMMENT ON COLUMN cargo_version_downloads.downloads IS 'Number of downloads of the crate by this cargo version on this date';
@@ -0,0 +1,2 @@
+DROP TABLE rolled_up_download_dates;
+DROP TABLE crate_download_rollups;
@@ -0,0 +1,22 @@
+CREATE TABLE crate_download_rollups (
+    crate_id INTEGER NOT NULL REFERENCES crates (id) ON DELETE CASCADE,
+    granularity VARCHAR NOT NULL CHECK (granularity IN ('week', 'month')),
+    period_start DATE NOT NULL,
+    downloads BIGINT NOT NULL,
+    PRIMARY KEY (crate_id, granularity, period_start)
+);
+
+COMMENT ON TABLE crate_download_rollups IS 'Weekly and monthly download counts of a crate, rolled up from `version_downloads` by the `roll_up_version_downloads` background job before the rows are archived.';
+COMMENT ON COLUMN crate_download_rollups.crate_id IS 'Reference to the crate that was downloaded';
+COMMENT ON COLUMN crate_download_rollups.granularity IS 'Length of the period, either `week` or `month`';
+COMMENT ON COLUMN crate_download_rollups.period_start IS 'First day of the period (Monday for weeks, the first of the month for months)';
+COMMENT ON COLUMN crate_download_rollups.downloads IS 'Number of downloads of the crate within the period';
+
+CREATE TABLE rolled_up_download_dates (
+    date DATE PRIMARY KEY,
+    rolled_up_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+COMMENT ON TABLE rolled_up_download_dates IS 'Dates whose `version_downloads` rows have been added to `crate_download_rollups`, to ensure that every date is only rolled up once.';
+COMMENT ON COLUMN rolled_up_download_dates.date IS 'Date whose downloads have been rolled up';
+COMMENT ON COLUMN rolled_up_download_dates.rolled_up_at IS 'Date and time when the downloads were rolled up';
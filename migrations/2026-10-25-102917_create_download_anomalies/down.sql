@@ -0,0 +1,2 @@
+DROP TABLE download_anomalies;
+DROP TABLE version_download_sources;
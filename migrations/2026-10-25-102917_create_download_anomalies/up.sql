@@ -0,0 +1,49 @@
+CREATE TABLE version_download_sources (
+    version_id INTEGER NOT NULL REFERENCES versions (id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    source_type VARCHAR NOT NULL CHECK (source_type IN ('ip_prefix', 'user_agent')),
+    source VARCHAR NOT NULL,
+    downloads BIGINT NOT NULL,
+    PRIMARY KEY (version_id, date, source_type, source)
+);
+
+CREATE INDEX version_download_sources_date_idx ON version_download_sources (date);
+
+COMMENT ON TABLE version_download_sources IS 'Daily download counts of a version from a single IP network prefix or user agent, used for the detection of download anomalies. Filled by the `process_cdn_log` background job and pruned by the `detect_download_anomalies` background job.';
+COMMENT ON COLUMN version_download_sources.version_id IS 'Reference to the version that was downloaded';
+COMMENT ON COLUMN version_download_sources.date IS 'Date on which the downloads happened';
+COMMENT ON COLUMN version_download_sources.source_type IS 'Type of the source: `ip_prefix` or `user_agent`';
+COMMENT ON COLUMN version_download_sources.source IS 'IP network prefix (`/24` for IPv4, `/48` for IPv6) or user agent of the downloads';
+COMMENT ON COLUMN version_download_sources.downloads IS 'Number of downloads of the version from this source on this date';
+
+CREATE TABLE download_anomalies (
+    id BIGSERIAL PRIMARY KEY,
+    version_id INTEGER NOT NULL REFERENCES versions (id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    reason VARCHAR NOT NULL CHECK (reason IN ('spike', 'concentrated_source')),
+    downloads BIGINT NOT NULL,
+    expected_downloads BIGINT NOT NULL,
+    top_ip_prefix VARCHAR,
+    top_ip_prefix_downloads BIGINT,
+    top_user_agent VARCHAR,
+    top_user_agent_downloads BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    reviewed_at TIMESTAMPTZ,
+    excluded_downloads BIGINT,
+    UNIQUE (version_id, date)
+);
+
+COMMENT ON TABLE download_anomalies IS 'Anomalous daily download counts of a version that were flagged by the `detect_download_anomalies` background job for review by an admin';
+COMMENT ON COLUMN download_anomalies.id IS 'Unique identifier of the anomaly';
+COMMENT ON COLUMN download_anomalies.version_id IS 'Reference to the version with the anomalous download count';
+COMMENT ON COLUMN download_anomalies.date IS 'Date of the anomalous download count';
+COMMENT ON COLUMN download_anomalies.reason IS 'Why the download count was flagged: `spike` for a statistically significant increase, `concentrated_source` if most downloads came from a single IP network prefix';
+COMMENT ON COLUMN download_anomalies.downloads IS 'Number of downloads of the version on this date at the time of detection';
+COMMENT ON COLUMN download_anomalies.expected_downloads IS 'Mean number of daily downloads of the version in the weeks before this date';
+COMMENT ON COLUMN download_anomalies.top_ip_prefix IS 'IP network prefix with the most downloads of the version on this date';
+COMMENT ON COLUMN download_anomalies.top_ip_prefix_downloads IS 'Number of downloads from `top_ip_prefix`';
+COMMENT ON COLUMN download_anomalies.top_user_agent IS 'User agent with the most downloads of the version on this date';
+COMMENT ON COLUMN download_anomalies.top_user_agent_downloads IS 'Number of downloads from `top_user_agent`';
+COMMENT ON COLUMN download_anomalies.created_at IS 'Date and time when the anomaly was detected';
+COMMENT ON COLUMN download_anomalies.reviewed_at IS 'Date and time when the anomaly was reviewed by an admin, or NULL if it has not been reviewed yet';
+COMMENT ON COLUMN download_anomalies.excluded_downloads IS 'Number of downloads that were subtracted from the download counts, or NULL if the downloads were not excluded';
@@ -0,0 +1,17 @@
+CREATE TABLE crate_platform_downloads (
+    crate_id INTEGER NOT NULL REFERENCES crates (id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    host_os VARCHAR NOT NULL,
+    host_arch VARCHAR NOT NULL,
+    cdn_region VARCHAR NOT NULL,
+    downloads BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (crate_id, date, host_os, host_arch, cdn_region)
+);
+
+COMMENT ON TABLE crate_platform_downloads IS 'Daily download counts of a crate, broken down by the coarse host platform of the client and the region of the CDN point of presence that served the download. Filled by the `process_cdn_log` background job.';
+COMMENT ON COLUMN crate_platform_downloads.crate_id IS 'Reference to the crate that was downloaded';
+COMMENT ON COLUMN crate_platform_downloads.date IS 'Date on which the downloads happened';
+COMMENT ON COLUMN crate_platform_downloads.host_os IS 'Operating system of the host (e.g. `linux`), or `unknown` if the user agent did not contain a target triple';
+COMMENT ON COLUMN crate_platform_downloads.host_arch IS 'CPU architecture of the host (e.g. `x86_64`), or `unknown` if the user agent did not contain a target triple';
+COMMENT ON COLUMN crate_platform_downloads.cdn_region IS 'Continent-level region of the CDN point of presence (e.g. `europe`), or `unknown` if it was not logged';
+COMMENT ON COLUMN crate_platform_downloads.downloads IS 'Number of downloads of the crate from this platform and region on this date';
@@ -0,0 +1,12 @@
+DROP INDEX index_api_token_usages_unique;
+DROP INDEX index_api_token_usages_api_token_id;
+DROP INDEX index_api_token_usages_last_used_at;
+
+ALTER TABLE api_token_usages DROP COLUMN request_count;
+ALTER TABLE api_token_usages RENAME COLUMN last_used_at TO used_at;
+
+CREATE INDEX index_api_token_usages_api_token_id ON api_token_usages (api_token_id, id);
+CREATE INDEX index_api_token_usages_used_at ON api_token_usages (used_at);
+
+COMMENT ON TABLE api_token_usages IS 'Rolling log of API token usages, pruned after a retention period';
+COMMENT ON COLUMN api_token_usages.used_at IS 'Date and time when the token was used';
@@ -0,0 +1,29 @@
+ALTER TABLE api_token_usages RENAME COLUMN used_at TO last_used_at;
+ALTER TABLE api_token_usages ADD COLUMN request_count INTEGER NOT NULL DEFAULT 1;
+
+-- Merge the existing entries, so that the unique index can be created.
+WITH deleted AS (
+    DELETE FROM api_token_usages
+    RETURNING api_token_id, ip_address, endpoint, user_agent, last_used_at
+)
+INSERT INTO api_token_usages (api_token_id, ip_address, endpoint, user_agent, last_used_at, request_count)
+SELECT api_token_id, ip_address, endpoint, user_agent, MAX(last_used_at), COUNT(*)
+FROM deleted
+GROUP BY api_token_id, ip_address, endpoint, user_agent;
+
+-- safety-assured:start
+-- This table is small and only written to by the API server, so creating
+-- these indexes concurrently isn't necessary.
+DROP INDEX index_api_token_usages_api_token_id;
+DROP INDEX index_api_token_usages_used_at;
+
+CREATE UNIQUE INDEX index_api_token_usages_unique
+    ON api_token_usages (api_token_id, ip_address, endpoint, user_agent)
+    NULLS NOT DISTINCT;
+CREATE INDEX index_api_token_usages_api_token_id ON api_token_usages (api_token_id, last_used_at);
+CREATE INDEX index_api_token_usages_last_used_at ON api_token_usages (last_used_at);
+-- safety-assured:end
+
+COMMENT ON TABLE api_token_usages IS 'Rolling log of API token usages, with one row per token, IP address, endpoint and user agent, pruned after a retention period';
+COMMENT ON COLUMN api_token_usages.last_used_at IS 'Date and time when the token was last used with this combination of IP address, endpoint and user agent';
+COMMENT ON COLUMN api_token_usages.request_count IS 'Number of requests with this combination of IP address, endpoint and user agent';
@@ -0,0 +1,9 @@
+DROP INDEX index_index_changes_seq;
+DROP INDEX index_index_changes_unpublished;
+DROP INDEX index_index_changes_unsequenced;
+
+ALTER TABLE index_changes DROP COLUMN seq;
+
+CREATE INDEX index_index_changes_unpublished ON index_changes (id) WHERE published_at IS NULL;
+
+COMMENT ON COLUMN index_changes.id IS 'Sequence number of the change';
@@ -0,0 +1,18 @@
+ALTER TABLE index_changes ADD COLUMN seq BIGINT;
+
+-- The `id` was used as the sequence number so far, so the existing changes
+-- keep their position in the feed.
+UPDATE index_changes SET seq = id;
+
+-- safety-assured:start
+-- This table is only written to by the background worker and is still small,
+-- so creating these indexes concurrently isn't necessary.
+DROP INDEX index_index_changes_unpublished;
+
+CREATE UNIQUE INDEX index_index_changes_seq ON index_changes (seq);
+CREATE INDEX index_index_changes_unpublished ON index_changes (seq) WHERE published_at IS NULL;
+CREATE INDEX index_index_changes_unsequenced ON index_changes (id) WHERE seq IS NULL;
+-- safety-assured:end
+
+COMMENT ON COLUMN index_changes.id IS 'Unique identifier of the change, in the order in which the changes were recorded';
+COMMENT ON COLUMN index_changes.seq IS 'Sequence number of the change in the changes feed, assigned once the change has been committed (`NULL` if not assigned yet)';
@@ -0,0 +1,10 @@
+DROP TRIGGER trigger_touch_index_target_bin ON index_targets;
+DROP FUNCTION touch_index_target_bin();
+
+DROP TRIGGER trigger_set_index_target_bin ON index_targets;
+DROP FUNCTION set_index_target_bin();
+
+DROP INDEX index_index_targets_bin;
+ALTER TABLE index_targets DROP COLUMN bin;
+
+DROP TABLE index_target_bins;
@@ -0,0 +1,58 @@
+CREATE TABLE index_target_bins (
+    bin SMALLINT PRIMARY KEY,
+    version BIGINT NOT NULL DEFAULT 0,
+    generation BIGINT NOT NULL DEFAULT 1,
+    signed_generation BIGINT NOT NULL DEFAULT 0,
+    signed_at TIMESTAMPTZ
+);
+
+COMMENT ON TABLE index_target_bins IS 'Hash bins of the signed `targets` metadata of the index, each of which lists the index files whose path hash starts with a given byte';
+COMMENT ON COLUMN index_target_bins.bin IS 'First byte of the SHA256 hash of the paths of the index files in this bin';
+COMMENT ON COLUMN index_target_bins.version IS 'Version of the last signed metadata of this bin (`0` if it has not been signed yet)';
+COMMENT ON COLUMN index_target_bins.generation IS 'Counter that is incremented whenever an index file in this bin changes';
+COMMENT ON COLUMN index_target_bins.signed_generation IS 'Value of `generation` when the metadata of this bin was last signed';
+COMMENT ON COLUMN index_target_bins.signed_at IS 'Date and time when the metadata of this bin was last signed';
+
+INSERT INTO index_target_bins (bin) SELECT generate_series(0, 255);
+
+ALTER TABLE index_targets ADD COLUMN bin SMALLINT;
+
+COMMENT ON COLUMN index_targets.bin IS 'Hash bin of the index file, i.e. the first byte of the SHA256 hash of its path';
+
+CREATE FUNCTION set_index_target_bin() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.bin := get_byte(sha256(convert_to(NEW.path, 'UTF8')), 0);
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_set_index_target_bin
+    BEFORE INSERT OR UPDATE ON index_targets
+    FOR EACH ROW
+    EXECUTE PROCEDURE set_index_target_bin();
+
+CREATE FUNCTION touch_index_target_bin() RETURNS TRIGGER AS $$
+BEGIN
+    IF (TG_OP = 'DELETE') THEN
+        UPDATE index_target_bins SET generation = generation + 1 WHERE bin = OLD.bin;
+    ELSE
+        UPDATE index_target_bins SET generation = generation + 1 WHERE bin = NEW.bin;
+    END IF;
+    RETURN NULL;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_touch_index_target_bin
+    AFTER INSERT OR UPDATE OR DELETE ON index_targets
+    FOR EACH ROW
+    EXECUTE PROCEDURE touch_index_target_bin();
+
+UPDATE index_targets SET bin = get_byte(sha256(convert_to(path, 'UTF8')), 0);
+
+-- safety-assured:start
+-- This table is only written to by the background worker and the `bin`
+-- column was filled above, so the lock taken by these statements is short.
+ALTER TABLE index_targets ALTER COLUMN bin SET NOT NULL;
+
+CREATE INDEX index_index_targets_bin ON index_targets (bin);
+-- safety-assured:end
@@ -0,0 +1 @@
+DELETE FROM reserved_crate_names WHERE name = 'suggest';
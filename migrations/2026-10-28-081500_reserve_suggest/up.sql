@@ -0,0 +1,3 @@
+-- `/api/v1/crates/suggest` would be shadowed by the crate name suggestions
+-- endpoint, so no crate may use this name.
+INSERT INTO reserved_crate_names (name) VALUES ('suggest') ON CONFLICT DO NOTHING;
@@ -0,0 +1 @@
+DROP INDEX CONCURRENTLY IF EXISTS cargo_version_downloads_date_idx;
@@ -0,0 +1,2 @@
+CREATE INDEX CONCURRENTLY IF NOT EXISTS cargo_version_downloads_date_idx
+    ON cargo_version_downloads (date);
@@ -0,0 +1 @@
+DROP INDEX CONCURRENTLY IF EXISTS crate_platform_downloads_date_idx;
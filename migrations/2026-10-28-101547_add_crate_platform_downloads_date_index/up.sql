@@ -0,0 +1,2 @@
+CREATE INDEX CONCURRENTLY IF NOT EXISTS crate_platform_downloads_date_idx
+    ON crate_platform_downloads (date);
@@ -0,0 +1,11 @@
+DELETE FROM staged_versions WHERE crate_id IS NULL;
+
+DROP INDEX staged_versions_crate_name_num_idx;
+
+ALTER TABLE staged_versions DROP CONSTRAINT staged_versions_crate_id_or_name_check;
+
+ALTER TABLE staged_versions DROP COLUMN crate_name;
+
+ALTER TABLE staged_versions ALTER COLUMN crate_id SET NOT NULL;
+
+COMMENT ON COLUMN staged_versions.crate_id IS 'Unique identifier of the crate that this version belongs to';
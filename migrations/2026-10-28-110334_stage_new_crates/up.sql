@@ -0,0 +1,17 @@
+-- safety-assured:start
+-- `staged_versions` only holds the versions of the last seven days, so the
+-- locks taken by these statements are short.
+ALTER TABLE staged_versions ALTER COLUMN crate_id DROP NOT NULL;
+
+ALTER TABLE staged_versions ADD COLUMN crate_name VARCHAR;
+
+ALTER TABLE staged_versions ADD CONSTRAINT staged_versions_crate_id_or_name_check
+    CHECK ((crate_id IS NULL) <> (crate_name IS NULL));
+
+CREATE UNIQUE INDEX staged_versions_crate_name_num_idx
+    ON staged_versions (canon_crate_name(crate_name), num)
+    WHERE crate_name IS NOT NULL;
+-- safety-assured:end
+
+COMMENT ON COLUMN staged_versions.crate_id IS 'Unique identifier of the crate that this version belongs to, or `NULL` if the crate has not been published yet';
+COMMENT ON COLUMN staged_versions.crate_name IS 'Name of the crate that this version belongs to, if the crate has not been published yet. The name is reserved for the user that staged the version until it is released or deleted.';
@@ -1,19 +1,21 @@
 //! Application-wide components in a struct accessible from each request
 
 use crate::config;
+use crate::controllers::krate::suggest::SuggestCache;
 use crate::db::{ConnectionConfig, connection_url, make_manager_config};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::email::Emails;
+use crate::index::SparseIndexCache;
 use crate::metrics::{InstanceMetrics, ServiceMetrics};
-use crate::rate_limiter::{LimitedAction, RateLimiter, RateLimiterConfig};
+use crate::rate_limiter::{IpRateLimiter, LimitedAction, RateLimiter, RateLimiterConfig};
 use crate::storage::{Storage, StorageConfig};
 use axum::extract::{FromRef, FromRequestParts, State};
 use bon::Builder;
 use crates_io_github::GitHubClient;
 use crates_io_trustpub::github::GITHUB_ISSUER_URL;
-use crates_io_trustpub::gitlab::GITLAB_ISSUER_URL;
 use crates_io_trustpub::keystore::{OidcKeyStore, RealOidcKeyStore};
 use deadpool_diesel::Runtime;
 use derive_more::Deref;
@@ -72,6 +74,15 @@ pub struct App {
 
     /// Rate limit select actions.
     pub rate_limiter: RateLimiter,
+
+    /// Cache for the sparse index files served by the API server.
+    pub sparse_index_cache: SparseIndexCache,
+
+    /// Cache for the crate name suggestions.
+    pub suggest_cache: SuggestCache,
+
+    /// Rate limit the crate name suggestions per IP address.
+    pub suggest_rate_limiter: IpRateLimiter,
 }
 
 impl<S: app_builder::State> AppBuilder<S> {
@@ -102,10 +113,15 @@ impl<S: app_builder::State> AppBuilder<S> {
     /// This method configures the OIDC key stores for the specified providers
     /// and expects a list of provider names as input.
     ///
-    /// Currently, only "github" is supported as a provider.
+    /// Currently, "github", "gitlab" and "forgejo" are supported as providers.
+    /// Since GitLab and Forgejo can be self-hosted, a separate key store is
+    /// created for each of the provided `gitlab_issuer_urls` and for the
+    /// provided `forgejo_issuer_url`.
     pub fn trustpub_providers(
         self,
         providers: &[String],
+        gitlab_issuer_urls: &[String],
+        forgejo_issuer_url: &str,
     ) -> AppBuilder<app_builder::SetOidcKeyStores<S>>
     where
         S::OidcKeyStores: app_builder::IsUnset,
@@ -119,8 +135,14 @@ impl<S: app_builder::State> AppBuilder<S> {
                     key_stores.insert(GITHUB_ISSUER_URL.into(), Box::new(key_store));
                 }
                 "gitlab" => {
-                    let key_store = RealOidcKeyStore::new(GITLAB_ISSUER_URL.into());
-                    key_stores.insert(GITLAB_ISSUER_URL.into(), Box::new(key_store));
+                    for issuer_url in gitlab_issuer_urls {
+                        let key_store = RealOidcKeyStore::new(issuer_url.into());
+                        key_stores.insert(issuer_url.into(), Box::new(key_store));
+                    }
+                }
+                "forgejo" => {
+                    let key_store = RealOidcKeyStore::new(forgejo_issuer_url.into());
+                    key_stores.insert(forgejo_issuer_url.into(), Box::new(key_store));
                 }
                 provider => {
                     warn!("Unknown Trusted Publishing provider: {provider}");
@@ -165,6 +187,41 @@ impl<S: app_builder::State> AppBuilder<S> {
     {
         self.rate_limiter(RateLimiter::new(config))
     }
+
+    pub fn sparse_index_cache_from_config(
+        self,
+        config: &config::Server,
+    ) -> AppBuilder<app_builder::SetSparseIndexCache<S>>
+    where
+        S::SparseIndexCache: app_builder::IsUnset,
+    {
+        let max_capacity = config.sparse_index_cache_size;
+        let ttl = config.sparse_index_cache_ttl;
+        self.sparse_index_cache(SparseIndexCache::new(max_capacity, ttl))
+    }
+
+    pub fn suggest_cache_from_config(
+        self,
+        config: &config::Server,
+    ) -> AppBuilder<app_builder::SetSuggestCache<S>>
+    where
+        S::SuggestCache: app_builder::IsUnset,
+    {
+        let max_capacity = config.suggest_cache_size;
+        let ttl = config.suggest_cache_ttl;
+        self.suggest_cache(SuggestCache::new(max_capacity, ttl))
+    }
+
+    pub fn suggest_rate_limiter_from_config(
+        self,
+        config: &config::Server,
+    ) -> AppBuilder<app_builder::SetSuggestRateLimiter<S>>
+    where
+        S::SuggestRateLimiter: app_builder::IsUnset,
+    {
+        let window = Duration::from_secs(60);
+        self.suggest_rate_limiter(IpRateLimiter::new(config.suggest_rate_limit, window))
+    }
 }
 
 pub fn create_database_pool(config: &config::DbPoolConfig) -> DeadpoolPool<AsyncPgConnection> {
@@ -1,8 +1,9 @@
 use crate::controllers;
 use crate::controllers::util::RequestPartsExt;
 use crate::middleware::log_request::RequestLogExt;
-use crate::models::token::{CrateScope, EndpointScope};
-use crate::models::{ApiToken, User};
+use crate::middleware::real_ip::RealIp;
+use crate::models::token::{CrateScope, EndpointScope, NewApiTokenUsage};
+use crate::models::{ApiToken, Team, User};
 use crate::util::errors::{
     AppResult, BoxedAppError, InsecurelyGeneratedTokenRevoked, account_locked, custom, forbidden,
     internal,
@@ -11,11 +12,13 @@ use crate::util::token::HashedToken;
 use axum::extract::FromRequestParts;
 use chrono::Utc;
 use crates_io_session::SessionExtension;
-use diesel_async::AsyncPgConnection;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection};
 use http::request::Parts;
 use http::{StatusCode, header};
 use secrecy::{ExposeSecret, SecretString};
-use tracing::instrument;
+use std::net::IpAddr;
+use tracing::{instrument, warn};
 
 pub struct AuthHeader(SecretString);
 
@@ -66,7 +69,7 @@ impl<S: Send + Sync> FromRequestParts<S> for AuthHeader {
 #[derive(Debug, Clone)]
 pub struct AuthCheck {
     allow_token: bool,
-    endpoint_scope: Option<EndpointScope>,
+    endpoint_scopes: Vec<EndpointScope>,
     crate_name: Option<String>,
     allow_any_crate_scope: bool,
 }
@@ -78,7 +81,7 @@ impl AuthCheck {
     pub fn default() -> Self {
         Self {
             allow_token: true,
-            endpoint_scope: None,
+            endpoint_scopes: Vec::new(),
             crate_name: None,
             allow_any_crate_scope: false,
         }
@@ -88,16 +91,25 @@ impl AuthCheck {
     pub fn only_cookie() -> Self {
         Self {
             allow_token: false,
-            endpoint_scope: None,
+            endpoint_scopes: Vec::new(),
             crate_name: None,
             allow_any_crate_scope: false,
         }
     }
 
+    /// Require the given endpoint scope for scoped API tokens.
+    ///
+    /// This can be called multiple times, in which case the token needs to
+    /// have all of the required endpoint scopes.
     pub fn with_endpoint_scope(&self, endpoint_scope: EndpointScope) -> Self {
+        let mut endpoint_scopes = self.endpoint_scopes.clone();
+        if !endpoint_scopes.contains(&endpoint_scope) {
+            endpoint_scopes.push(endpoint_scope);
+        }
+
         Self {
             allow_token: self.allow_token,
-            endpoint_scope: Some(endpoint_scope),
+            endpoint_scopes,
             crate_name: self.crate_name.clone(),
             allow_any_crate_scope: self.allow_any_crate_scope,
         }
@@ -106,7 +118,7 @@ impl AuthCheck {
     pub fn for_crate(&self, crate_name: &str) -> Self {
         Self {
             allow_token: self.allow_token,
-            endpoint_scope: self.endpoint_scope,
+            endpoint_scopes: self.endpoint_scopes.clone(),
             crate_name: Some(crate_name.to_string()),
             allow_any_crate_scope: self.allow_any_crate_scope,
         }
@@ -119,7 +131,7 @@ impl AuthCheck {
     pub fn allow_any_crate_scope(&self) -> Self {
         Self {
             allow_token: self.allow_token,
-            endpoint_scope: self.endpoint_scope,
+            endpoint_scopes: self.endpoint_scopes.clone(),
             crate_name: self.crate_name.clone(),
             allow_any_crate_scope: true,
         }
@@ -161,21 +173,45 @@ impl AuthCheck {
                     "this token does not have the required permissions to perform this action",
                 ));
             }
+
+            if let Some(team_id) = token.team_id
+                && !self.team_owns_crate(conn, team_id).await?
+            {
+                let error_message = "Team token used for a crate that is not owned by the team";
+                parts.request_log().add("cause", error_message);
+
+                return Err(forbidden(
+                    "team tokens can only be used for crates that are owned by the team",
+                ));
+            }
         }
 
         Ok(auth)
     }
 
+    async fn team_owns_crate(&self, conn: &mut AsyncPgConnection, team_id: i32) -> AppResult<bool> {
+        match &self.crate_name {
+            Some(crate_name) => Ok(Team::is_crate_owner(conn, team_id, crate_name).await?),
+            // Team tokens can't be used for endpoints that don't deal with
+            // a specific crate.
+            None => Ok(false),
+        }
+    }
+
     fn endpoint_scope_matches(&self, token_scopes: Option<&Vec<EndpointScope>>) -> bool {
-        match (&token_scopes, &self.endpoint_scope) {
+        match (&token_scopes, self.endpoint_scopes.as_slice()) {
             // The token is a legacy token.
             (None, _) => true,
 
             // The token is NOT a legacy token, and the endpoint only allows legacy tokens.
-            (Some(_), None) => false,
-
-            // The token is NOT a legacy token, and the endpoint allows a certain endpoint scope or a legacy token.
-            (Some(token_scopes), Some(endpoint_scope)) => token_scopes.contains(endpoint_scope),
+            (Some(_), []) => false,
+
+            // The token is NOT a legacy token, and the endpoint allows certain endpoint scopes or a legacy token.
+            (Some(token_scopes), endpoint_scopes) => endpoint_scopes.iter().all(|required| {
+                token_scopes
+                    .iter()
+                    .any(|token_scope| token_scope.includes(*required))
+            }),
         }
     }
 
@@ -225,6 +261,12 @@ impl Authentication {
         self.api_token().map(|token| token.id)
     }
 
+    /// Returns the ID of the team that owns the API token, if the request
+    /// was authenticated with a team token.
+    pub fn team_id(&self) -> Option<i32> {
+        self.api_token().and_then(|token| token.team_id)
+    }
+
     pub fn api_token(&self) -> Option<&ApiToken> {
         match self {
             Authentication::Token(token) => Some(&token.token),
@@ -303,6 +345,19 @@ async fn authenticate_via_token(
             forbidden("authentication failed")
         })?;
 
+    let real_ip = parts.extensions().get::<RealIp>().map(|ip| **ip);
+    if let Some(allowed_ips) = &token.allowed_ips {
+        let is_allowed =
+            real_ip.is_some_and(|ip| allowed_ips.iter().any(|range| range.contains(ip)));
+
+        if !is_allowed {
+            let error_message = "IP address not in the allowed IP ranges of the token";
+            parts.request_log().add("cause", error_message);
+
+            return Err(forbidden("this token can not be used from your IP address"));
+        }
+    }
+
     let user = User::find(conn, token.user_id).await.map_err(|err| {
         parts.request_log().add("cause", err);
         internal("user_id from token not found in database")
@@ -313,9 +368,42 @@ async fn authenticate_via_token(
     parts.request_log().add("uid", token.user_id);
     parts.request_log().add("tokenid", token.id);
 
+    record_token_usage(parts, conn, token.id, real_ip).await;
+
     Ok(Some(TokenAuthentication { user, token }))
 }
 
+/// Adds an entry to the usage log of the API token, or updates the existing
+/// entry for the same IP address, endpoint and user agent.
+///
+/// Failing to record the usage does not fail the request, since the
+/// database might be in read only mode.
+async fn record_token_usage(
+    parts: &Parts,
+    conn: &mut AsyncPgConnection,
+    api_token_id: i32,
+    real_ip: Option<IpAddr>,
+) {
+    let ip_address = real_ip.map(|ip| ip.to_string());
+    let endpoint = format!("{} {}", parts.method, parts.uri.path());
+    let user_agent = parts.headers.get(header::USER_AGENT);
+
+    let usage = NewApiTokenUsage::builder()
+        .api_token_id(api_token_id)
+        .ip_address(ip_address.as_deref().unwrap_or("unknown"))
+        .endpoint(&endpoint)
+        .maybe_user_agent(user_agent.and_then(|value| value.to_str().ok()))
+        .build();
+
+    let result = conn
+        .transaction(|conn| async move { usage.upsert(conn).await }.scope_boxed())
+        .await;
+
+    if let Err(error) = result {
+        warn!("Failed to record API token usage: {error}");
+    }
+}
+
 #[instrument(skip_all)]
 async fn authenticate(parts: &Parts, conn: &mut AsyncPgConnection) -> AppResult<Authentication> {
     controllers::util::verify_origin(parts)?;
@@ -458,4 +546,31 @@ mod tests {
         assert!(!auth_check.crate_scope_matches(Some(&vec![cs("anyhow")])));
         assert!(!auth_check.crate_scope_matches(Some(&vec![cs("actix-*")])));
     }
+
+    #[test]
+    fn manage_trustpub_endpoint() {
+        let auth_check = AuthCheck::default()
+            .with_endpoint_scope(EndpointScope::ManageTrustpub)
+            .for_crate("tokio-console");
+
+        assert!(auth_check.endpoint_scope_matches(None));
+        assert!(auth_check.endpoint_scope_matches(Some(&vec![EndpointScope::ManageTrustpub])));
+        assert!(auth_check.endpoint_scope_matches(Some(&vec![EndpointScope::TrustedPublishing])));
+        assert!(!auth_check.endpoint_scope_matches(Some(&vec![EndpointScope::UpdateMetadata])));
+    }
+
+    #[test]
+    fn multiple_endpoint_scopes() {
+        let auth_check = AuthCheck::default()
+            .with_endpoint_scope(EndpointScope::ManageTrustpub)
+            .with_endpoint_scope(EndpointScope::UpdateMetadata)
+            .for_crate("tokio-console");
+
+        let both = vec![EndpointScope::UpdateMetadata, EndpointScope::ManageTrustpub];
+
+        assert!(auth_check.endpoint_scope_matches(None));
+        assert!(auth_check.endpoint_scope_matches(Some(&both)));
+        assert!(!auth_check.endpoint_scope_matches(Some(&vec![EndpointScope::ManageTrustpub])));
+        assert!(!auth_check.endpoint_scope_matches(Some(&vec![EndpointScope::UpdateMetadata])));
+    }
 }
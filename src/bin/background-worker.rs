@@ -23,6 +23,7 @@ use crates_io::{Emails, config};
 use crates_io_docs_rs::RealDocsRsClient;
 use crates_io_env_vars::var;
 use crates_io_fastly::Fastly;
+use crates_io_github::RealGitHubClient;
 use crates_io_index::RepositoryConfig;
 use crates_io_og_image::OgImageGenerator;
 use crates_io_team_repo::TeamRepoImpl;
@@ -66,11 +67,16 @@ fn main() -> anyhow::Result<()> {
         }
     }
 
-    if var("HEROKU")?.is_some() {
-        ssh::write_known_hosts_file()?;
-    }
+    let repository_config = if config.git_index_enabled {
+        if var("HEROKU")?.is_some() {
+            ssh::write_known_hosts_file()?;
+        }
 
-    let repository_config = RepositoryConfig::from_environment()?;
+        Some(RepositoryConfig::from_environment()?)
+    } else {
+        info!("The git index is disabled, only the sparse index will be updated");
+        None
+    };
 
     let cloudfront = CloudFront::from_environment();
     let storage = Arc::new(Storage::from_config(&config.storage));
@@ -85,19 +91,24 @@ fn main() -> anyhow::Result<()> {
 
     let team_repo = TeamRepoImpl::default();
 
+    let user_agent = crates_io_version::user_agent();
+    let client = reqwest::Client::builder().user_agent(user_agent).build()?;
+    let github = RealGitHubClient::new(client);
+
     let docs_rs = RealDocsRsClient::from_environment().map(|cl| Box::new(cl) as _);
 
     let deadpool = create_database_pool(&config.db.primary);
 
     let environment = Environment::builder()
         .config(Arc::new(config))
-        .repository_config(repository_config)
+        .maybe_repository_config(repository_config)
         .maybe_cloudfront(cloudfront)
         .maybe_fastly(fastly)
         .storage(storage)
         .downloads_archive_store(downloads_archive_store)
         .deadpool(deadpool.clone())
         .emails(emails)
+        .github(Box::new(github))
         .maybe_docs_rs(docs_rs)
         .team_repo(Box::new(team_repo))
         .og_image_generator(OgImageGenerator::from_environment()?)
@@ -105,20 +116,24 @@ fn main() -> anyhow::Result<()> {
 
     let environment = Arc::new(environment);
 
-    std::thread::spawn({
-        let environment = environment.clone();
-        move || {
-            if let Err(err) = environment.lock_index() {
-                warn!(%err, "Failed to clone index");
-            };
-        }
-    });
+    if environment.config.git_index_enabled {
+        std::thread::spawn({
+            let environment = environment.clone();
+            move || {
+                if let Err(err) = environment.lock_index() {
+                    warn!(%err, "Failed to clone index");
+                };
+            }
+        });
+    }
 
     let runner = Runner::new(deadpool, environment.clone())
         .configure_default_queue(|queue| queue.num_workers(5))
         .configure_queue("downloads", |queue| queue.num_workers(1))
         .configure_queue("repository", |queue| queue.num_workers(1))
         .configure_queue("cloudfront", |queue| queue.num_workers(1))
+        .configure_queue("index_changes", |queue| queue.num_workers(1))
+        .configure_queue("index_metadata", |queue| queue.num_workers(1))
         .register_crates_io_job_types();
 
     runtime.block_on(async {
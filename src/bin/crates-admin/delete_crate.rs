@@ -8,6 +8,7 @@ use crates_io::schema::{crate_downloads, deleted_crates};
 use crates_io::worker::jobs;
 use crates_io::{db, schema::crates};
 use crates_io_database::schema::dependencies;
+use crates_io_index::RepositoryConfig;
 use crates_io_worker::BackgroundJob;
 use diesel::dsl::{count_star, sql};
 use diesel::expression::SqlLiteral;
@@ -47,6 +48,8 @@ pub struct Opts {
 }
 
 pub async fn run(opts: Opts) -> anyhow::Result<()> {
+    let git_index_enabled = RepositoryConfig::is_enabled()?;
+
     let mut conn = db::oneoff_connection()
         .await
         .context("Failed to establish database connection")?;
@@ -106,15 +109,13 @@ pub async fn run(opts: Opts) -> anyhow::Result<()> {
         };
 
         info!("{name}: Enqueuing background jobs…");
-        let git_index_job = jobs::SyncToGitIndex::new(name);
-        let sparse_index_job = jobs::SyncToSparseIndex::new(name);
-        let delete_from_storage_job = jobs::DeleteCrateFromStorage::new(name.into());
+        let result = jobs::enqueue_sync_to_index(name, git_index_enabled, &mut conn).await;
+        if let Err(error) = result {
+            warn!("{name}: Failed to enqueue background job: {error}");
+        }
 
-        if let Err(error) = tokio::try_join!(
-            git_index_job.enqueue(&mut conn),
-            sparse_index_job.enqueue(&mut conn),
-            delete_from_storage_job.enqueue(&mut conn),
-        ) {
+        let delete_from_storage_job = jobs::DeleteCrateFromStorage::new(name.into());
+        if let Err(error) = delete_from_storage_job.enqueue(&mut conn).await {
             warn!("{name}: Failed to enqueue background job: {error}");
         }
     }
@@ -5,6 +5,7 @@ use crates_io::schema::crates;
 use crates_io::storage::Storage;
 use crates_io::worker::jobs;
 use crates_io::{db, schema::versions};
+use crates_io_index::RepositoryConfig;
 use crates_io_worker::BackgroundJob;
 use diesel::prelude::*;
 use diesel_async::scoped_futures::ScopedFutureExt;
@@ -30,6 +31,8 @@ pub struct Opts {
 }
 
 pub async fn run(opts: Opts) -> anyhow::Result<()> {
+    let git_index_enabled = RepositoryConfig::is_enabled()?;
+
     let mut conn = db::oneoff_connection()
         .await
         .context("Failed to establish database connection")?;
@@ -95,13 +98,8 @@ pub async fn run(opts: Opts) -> anyhow::Result<()> {
     let crate_name = &opts.crate_name;
 
     info!(%crate_name, "Enqueuing index sync jobs");
-    let git_index_job = jobs::SyncToGitIndex::new(crate_name);
-    let sparse_index_job = jobs::SyncToSparseIndex::new(crate_name);
-
-    if let Err(error) = tokio::try_join!(
-        git_index_job.enqueue(&mut conn),
-        sparse_index_job.enqueue(&mut conn),
-    ) {
+    let result = jobs::enqueue_sync_to_index(crate_name, git_index_enabled, &mut conn).await;
+    if let Err(error) = result {
         warn!(%crate_name, "Failed to enqueue background job: {error}");
     }
 
@@ -0,0 +1,130 @@
+use crate::dialoguer;
+use crates_io::db;
+use crates_io::models::DownloadAnomaly;
+use crates_io::schema::{crates, versions};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::collections::HashMap;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "download-anomalies",
+    about = "Review the download anomalies flagged by the `detect_download_anomalies` background job"
+)]
+pub enum Command {
+    /// List all download anomalies that have not been reviewed yet.
+    List,
+    /// Mark a download anomaly as reviewed.
+    Review {
+        /// The ID of the download anomaly.
+        id: i64,
+        /// Subtract the excess downloads of the anomaly from the download
+        /// counts.
+        #[arg(long)]
+        exclude: bool,
+        /// Don't ask for confirmation: yes, we are sure. Best for scripting.
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+pub async fn run(command: Command) -> anyhow::Result<()> {
+    let mut conn = db::oneoff_connection().await?;
+
+    match command {
+        Command::List => list(&mut conn).await,
+        Command::Review { id, exclude, yes } => review(&mut conn, id, exclude, yes).await,
+    }
+}
+
+async fn list(conn: &mut AsyncPgConnection) -> anyhow::Result<()> {
+    let anomalies = DownloadAnomaly::unreviewed(conn).await?;
+    if anomalies.is_empty() {
+        println!("No unreviewed download anomalies");
+        return Ok(());
+    }
+
+    let version_ids = anomalies.iter().map(|anomaly| anomaly.version_id);
+    let names = load_version_names(conn, version_ids).await?;
+
+    for anomaly in anomalies {
+        let name = names
+            .get(&anomaly.version_id)
+            .map_or("<unknown>", String::as_str);
+
+        println!(
+            "#{} {name} on {}: {} downloads, {} expected ({})",
+            anomaly.id, anomaly.date, anomaly.downloads, anomaly.expected_downloads, anomaly.reason
+        );
+
+        if let (Some(ip_prefix), Some(downloads)) =
+            (&anomaly.top_ip_prefix, anomaly.top_ip_prefix_downloads)
+        {
+            println!("    top IP prefix: {ip_prefix} ({downloads} downloads)");
+        }
+
+        if let (Some(user_agent), Some(downloads)) =
+            (&anomaly.top_user_agent, anomaly.top_user_agent_downloads)
+        {
+            println!("    top user agent: {user_agent} ({downloads} downloads)");
+        }
+    }
+
+    Ok(())
+}
+
+async fn review(
+    conn: &mut AsyncPgConnection,
+    id: i64,
+    exclude: bool,
+    yes: bool,
+) -> anyhow::Result<()> {
+    let anomaly = DownloadAnomaly::find(conn, id).await?;
+
+    if exclude {
+        let names = load_version_names(conn, [anomaly.version_id]).await?;
+        let name = names
+            .get(&anomaly.version_id)
+            .map_or("<unknown>", String::as_str);
+
+        let excess_downloads = anomaly.excess_downloads();
+        let prompt = format!(
+            "Exclude {excess_downloads} downloads of {name} on {} from the download counts?",
+            anomaly.date
+        );
+        if !yes && !dialoguer::confirm(prompt).await? {
+            return Ok(());
+        }
+
+        if anomaly.exclude(conn).await? {
+            println!("Excluded {excess_downloads} downloads");
+        } else {
+            println!("The downloads of this anomaly were already excluded");
+        }
+    }
+
+    anomaly.mark_reviewed(conn).await?;
+    println!("Marked download anomaly #{id} as reviewed");
+
+    Ok(())
+}
+
+/// Loads the `name@version` strings of the given versions.
+async fn load_version_names(
+    conn: &mut AsyncPgConnection,
+    version_ids: impl IntoIterator<Item = i32>,
+) -> QueryResult<HashMap<i32, String>> {
+    let version_ids = version_ids.into_iter().collect::<Vec<_>>();
+
+    let names: Vec<(i32, String, String)> = versions::table
+        .inner_join(crates::table)
+        .filter(versions::id.eq_any(version_ids))
+        .select((versions::id, crates::name, versions::num))
+        .load(conn)
+        .await?;
+
+    Ok(names
+        .into_iter()
+        .map(|(id, name, num)| (id, format!("{name}@{num}")))
+        .collect())
+}
@@ -3,6 +3,7 @@ use chrono::NaiveDate;
 use crates_io::db;
 use crates_io::schema::{background_jobs, crates};
 use crates_io::worker::jobs;
+use crates_io_index::RepositoryConfig;
 use crates_io_worker::BackgroundJob;
 use diesel::dsl::exists;
 use diesel::prelude::*;
@@ -20,12 +21,23 @@ pub enum Command {
         /// The date before which to archive version downloads (default: 90 days ago)
         before: Option<NaiveDate>,
     },
+    BackfillDownloadRollups,
+    BackfillIndexTargets,
     CheckTyposquat {
         #[arg()]
         name: String,
     },
     CleanProcessedLogFiles,
     DailyDbMaintenance,
+    DeleteExpiredStagedVersions,
+    DetectDownloadAnomalies {
+        #[arg(long)]
+        /// The date to check for download anomalies (default: yesterday)
+        date: Option<NaiveDate>,
+        /// Exclude the excess downloads of detected anomalies from the download counts
+        #[arg(long)]
+        exclude: bool,
+    },
     DumpDb,
     /// Generate OpenGraph images for the specified crates
     GenerateOgImage {
@@ -39,7 +51,15 @@ pub enum Command {
         dry_run: bool,
     },
     ProcessCdnLogQueue(jobs::ProcessCdnLogQueue),
+    PruneApiTokenUsages,
+    PruneDownloadBreakdowns,
+    RollUpVersionDownloads {
+        #[arg(long)]
+        /// The date before which to roll up version downloads (default: 90 days ago)
+        before: Option<NaiveDate>,
+    },
     SendTokenExpiryNotifications,
+    SignIndexMetadata,
     SquashIndex,
     SyncAdmins {
         /// Force a sync even if one is already in progress
@@ -50,6 +70,13 @@ pub enum Command {
     SyncUpdatesFeed,
     TrustpubCleanup,
     UpdateDownloads,
+    UpdateSearchScores,
+    VerifyIndex {
+        /// Enqueue sync jobs for all mismatched index files
+        #[arg(long)]
+        repair: bool,
+    },
+    VerifyTeamTokens,
 }
 
 pub async fn run(command: Command) -> Result<()> {
@@ -64,6 +91,12 @@ pub async fn run(command: Command) -> Result<()> {
                 .enqueue(&mut conn)
                 .await?;
         }
+        Command::BackfillDownloadRollups => {
+            jobs::BackfillDownloadRollups.enqueue(&mut conn).await?;
+        }
+        Command::BackfillIndexTargets => {
+            jobs::BackfillIndexTargets.enqueue(&mut conn).await?;
+        }
         Command::CheckTyposquat { name } => {
             // The job will fail if the crate doesn't actually exist, so let's check that up front.
             if crates::table
@@ -86,6 +119,15 @@ pub async fn run(command: Command) -> Result<()> {
         Command::DailyDbMaintenance => {
             jobs::DailyDbMaintenance.enqueue(&mut conn).await?;
         }
+        Command::DeleteExpiredStagedVersions => {
+            jobs::DeleteExpiredStagedVersions.enqueue(&mut conn).await?;
+        }
+        Command::DetectDownloadAnomalies { date, exclude } => {
+            date.map(|date| jobs::DetectDownloadAnomalies::new(date, exclude))
+                .unwrap_or_else(|| jobs::DetectDownloadAnomalies::yesterday(exclude))
+                .enqueue(&mut conn)
+                .await?;
+        }
         Command::DumpDb => {
             jobs::DumpDb.enqueue(&mut conn).await?;
         }
@@ -100,6 +142,7 @@ pub async fn run(command: Command) -> Result<()> {
                 .await?;
         }
         Command::NormalizeIndex { dry_run } => {
+            ensure_git_index_enabled()?;
             jobs::NormalizeIndex::new(dry_run)
                 .enqueue(&mut conn)
                 .await?;
@@ -107,12 +150,29 @@ pub async fn run(command: Command) -> Result<()> {
         Command::ProcessCdnLogQueue(job) => {
             job.enqueue(&mut conn).await?;
         }
+        Command::PruneApiTokenUsages => {
+            jobs::PruneApiTokenUsages.enqueue(&mut conn).await?;
+        }
+        Command::PruneDownloadBreakdowns => {
+            jobs::PruneDownloadBreakdowns.enqueue(&mut conn).await?;
+        }
+        Command::RollUpVersionDownloads { before } => {
+            before
+                .map(jobs::RollUpVersionDownloads::before)
+                .unwrap_or_default()
+                .enqueue(&mut conn)
+                .await?;
+        }
         Command::SendTokenExpiryNotifications => {
             jobs::SendTokenExpiryNotifications
                 .enqueue(&mut conn)
                 .await?;
         }
+        Command::SignIndexMetadata => {
+            jobs::SignIndexMetadata.enqueue(&mut conn).await?;
+        }
         Command::SquashIndex => {
+            ensure_git_index_enabled()?;
             jobs::SquashIndex.enqueue(&mut conn).await?;
         }
         Command::SyncAdmins { force } => {
@@ -166,7 +226,26 @@ pub async fn run(command: Command) -> Result<()> {
                 jobs::UpdateDownloads.enqueue(&mut conn).await?;
             }
         }
+        Command::UpdateSearchScores => {
+            jobs::UpdateSearchScores.enqueue(&mut conn).await?;
+        }
+        Command::VerifyIndex { repair } => {
+            jobs::VerifyIndex::new(repair).enqueue(&mut conn).await?;
+        }
+        Command::VerifyTeamTokens => {
+            jobs::VerifyTeamTokens.enqueue(&mut conn).await?;
+        }
     };
 
     Ok(())
 }
+
+/// The git index jobs are not registered by the background worker if the git
+/// index is disabled, so they would stay in the queue forever.
+fn ensure_git_index_enabled() -> Result<()> {
+    if !RepositoryConfig::is_enabled()? {
+        anyhow::bail!("The git index is disabled");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,196 @@
+use crate::dialoguer;
+use anyhow::{Context, bail};
+use chrono::Utc;
+use crates_io::index::{tuf_metadata_path, tuf_root_path};
+use crates_io::storage::Storage;
+use crates_io_index::tuf::{PublicKey, RoleType, Root, Signed, SigningKey};
+use rand::Rng;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "index-keys",
+    about = "Manage the signing keys of the TUF metadata of the sparse index"
+)]
+pub enum Command {
+    /// Generate a new P-256 signing key.
+    GenerateKey {
+        /// The file that the PEM-encoded private key is written to.
+        output: PathBuf,
+    },
+    /// Sign and upload a new version of the `root` metadata, e.g. to rotate
+    /// the root or online keys.
+    SignRoot {
+        /// The private root key(s) that are trusted by the new `root`
+        /// metadata. All of them sign the new version.
+        #[arg(long = "root-key", required = true)]
+        root_keys: Vec<PathBuf>,
+        /// The number of root key signatures that are required for the
+        /// `root` metadata.
+        #[arg(long, default_value_t = 1)]
+        root_threshold: u32,
+        /// The private root key(s) of the current `root` metadata, if they
+        /// are being rotated. The new version needs to be signed by these
+        /// too, so that clients can verify the rotation.
+        #[arg(long = "previous-root-key")]
+        previous_root_keys: Vec<PathBuf>,
+        /// The public key of the online key (i.e. `INDEX_SIGNING_KEY`) that
+        /// signs the `targets`, `snapshot` and `timestamp` metadata.
+        #[arg(long)]
+        online_key: PathBuf,
+        /// The number of days until the new `root` metadata expires.
+        #[arg(long, default_value_t = 365)]
+        expires_in_days: i64,
+    },
+}
+
+pub async fn run(command: Command) -> anyhow::Result<()> {
+    match command {
+        Command::GenerateKey { output } => generate_key(&output),
+        Command::SignRoot {
+            root_keys,
+            root_threshold,
+            previous_root_keys,
+            online_key,
+            expires_in_days,
+        } => {
+            let root_keys = read_signing_keys(&root_keys)?;
+            let previous_root_keys = read_signing_keys(&previous_root_keys)?;
+
+            let online_key = std::fs::read_to_string(&online_key)
+                .with_context(|| format!("Failed to read {}", online_key.display()))?;
+            let online_key = PublicKey::from_pem(&online_key)?;
+
+            let expires = Utc::now() + chrono::Duration::days(expires_in_days);
+
+            sign_root(
+                &root_keys,
+                root_threshold,
+                &previous_root_keys,
+                &online_key,
+                expires,
+            )
+            .await
+        }
+    }
+}
+
+fn generate_key(output: &Path) -> anyhow::Result<()> {
+    let key = loop {
+        let mut bytes = [0; 32];
+        rand::rng().fill(&mut bytes);
+
+        // A small fraction of the possible byte sequences are not valid
+        // secret scalars, in which case we just try again.
+        if let Ok(key) = SigningKey::from_secret_bytes(&bytes) {
+            break key;
+        }
+    };
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = options
+        .open(output)
+        .with_context(|| format!("Failed to create {}", output.display()))?;
+
+    file.write_all(key.to_pkcs8_pem()?.as_bytes())?;
+
+    println!("Private key written to {}", output.display());
+    println!("Key ID: {}", key.key_id());
+    println!();
+    print!("{}", key.public_key().keyval.public);
+
+    Ok(())
+}
+
+fn read_signing_keys(paths: &[PathBuf]) -> anyhow::Result<Vec<SigningKey>> {
+    paths
+        .iter()
+        .map(|path| {
+            let pem = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+
+            SigningKey::from_pkcs8_pem(&pem)
+                .with_context(|| format!("Failed to parse {}", path.display()))
+        })
+        .collect()
+}
+
+async fn sign_root(
+    root_keys: &[SigningKey],
+    root_threshold: u32,
+    previous_root_keys: &[SigningKey],
+    online_key: &PublicKey,
+    expires: chrono::DateTime<Utc>,
+) -> anyhow::Result<()> {
+    let storage = Storage::from_environment();
+
+    let path = tuf_metadata_path(RoleType::Root);
+    let previous = storage.download_index_file(&path).await?;
+    let previous = previous
+        .map(|bytes| Signed::<Root>::from_slice(&bytes))
+        .transpose()
+        .context("Failed to parse the current root metadata")?;
+
+    let version = previous.as_ref().map_or(1, |root| root.signed.version + 1);
+
+    let mut root = Root::new(version, expires);
+    for key in root_keys {
+        root.add_key(RoleType::Root, key.public_key())?;
+    }
+    root.set_threshold(RoleType::Root, root_threshold)?;
+
+    for role in [RoleType::Targets, RoleType::Snapshot, RoleType::Timestamp] {
+        root.add_key(role, online_key)?;
+    }
+
+    let mut signed = Signed::new(root.clone());
+    for key in root_keys.iter().chain(previous_root_keys) {
+        signed.sign(key)?;
+    }
+
+    root.verify(&signed)
+        .context("The new root metadata is not signed by enough of its own root keys")?;
+
+    if let Some(previous) = &previous {
+        let result = previous.signed.verify(&signed);
+        if let Err(error) = result {
+            bail!(
+                "The new root metadata is not signed by enough of the current root keys, \
+                use `--previous-root-key` to sign it with them: {error}"
+            );
+        }
+    }
+
+    println!("root metadata version: {version}");
+    println!("expires: {}", root.expires);
+    for (role, keys) in &root.roles {
+        println!(
+            "{role:?} keys: {:?} (threshold: {})",
+            keys.keyids, keys.threshold
+        );
+    }
+
+    if !dialoguer::confirm("upload the new root metadata?").await? {
+        return Ok(());
+    }
+
+    let content = signed.to_json()?;
+    storage
+        .upload_index_json(&tuf_root_path(version), content.clone())
+        .await?;
+    storage.upload_index_json(&path, content).await?;
+
+    println!(
+        "root metadata uploaded; if the online key has changed, update `INDEX_SIGNING_KEY` \
+        and run `crates-admin enqueue-job sign_index_metadata`"
+    );
+
+    Ok(())
+}
@@ -6,7 +6,9 @@ mod default_versions;
 mod delete_crate;
 mod delete_version;
 mod dialoguer;
+mod download_anomalies;
 mod enqueue_job;
+mod index_keys;
 mod migrate;
 mod populate;
 mod render_og_images;
@@ -15,6 +17,7 @@ mod sync_index;
 mod test_email;
 mod transfer_crates;
 mod upload_index;
+mod verify_index;
 mod verify_token;
 mod yank_version;
 
@@ -30,6 +33,7 @@ enum Command {
     SyncIndex(sync_index::Opts),
     TestEmail(test_email::Opts),
     TransferCrates(transfer_crates::Opts),
+    VerifyIndex(verify_index::Opts),
     VerifyToken(verify_token::Opts),
     Migrate(migrate::Opts),
     UploadIndex(upload_index::Opts),
@@ -38,6 +42,10 @@ enum Command {
     EnqueueJob(enqueue_job::Command),
     #[clap(subcommand)]
     DefaultVersions(default_versions::Command),
+    #[clap(subcommand)]
+    DownloadAnomalies(download_anomalies::Command),
+    #[clap(subcommand)]
+    IndexKeys(index_keys::Command),
 }
 
 #[tokio::main]
@@ -63,12 +71,15 @@ async fn main() -> anyhow::Result<()> {
         Command::SyncIndex(opts) => sync_index::run(opts).await,
         Command::TestEmail(opts) => test_email::run(opts).await,
         Command::TransferCrates(opts) => transfer_crates::run(opts).await,
+        Command::VerifyIndex(opts) => verify_index::run(opts).await,
         Command::VerifyToken(opts) => verify_token::run(opts).await,
         Command::Migrate(opts) => migrate::run(opts).await,
         Command::UploadIndex(opts) => upload_index::run(opts).await,
         Command::YankVersion(opts) => yank_version::run(opts).await,
         Command::EnqueueJob(command) => enqueue_job::run(command).await,
         Command::DefaultVersions(opts) => default_versions::run(opts).await,
+        Command::DownloadAnomalies(command) => download_anomalies::run(command).await,
+        Command::IndexKeys(command) => index_keys::run(command).await,
     }
 }
 
@@ -5,6 +5,7 @@ use clap::builder::ArgAction;
 use crates_io::db;
 use crates_io::schema::crates;
 use crates_io::worker::jobs;
+use crates_io_index::RepositoryConfig;
 use crates_io_worker::BackgroundJob;
 use crates_io_worker::schema::background_jobs;
 use diesel::prelude::*;
@@ -79,6 +80,11 @@ pub struct Opts {
 }
 
 pub async fn run(opts: Opts) -> Result<()> {
+    let git = opts.git && RepositoryConfig::is_enabled()?;
+    if opts.git && !git {
+        println!("The git index is disabled, only syncing to the sparse index");
+    }
+
     let mut conn = db::oneoff_connection().await?;
 
     // Determine which crates to sync
@@ -126,7 +132,7 @@ pub async fn run(opts: Opts) -> Result<()> {
     if let Some(batch_size) = opts.batch_size {
         let mut prompt_parts = Vec::new();
 
-        if opts.git {
+        if git {
             let num_batches = num_crates.div_ceil(batch_size);
             prompt_parts.push(format!(
                 "This will sync {num_crates} crate{} to the git index in {num_batches} batch{}.",
@@ -153,7 +159,7 @@ pub async fn run(opts: Opts) -> Result<()> {
     conn.transaction(|conn| {
         Box::pin(async move {
             // Handle git index sync
-            if opts.git {
+            if git {
                 if let Some(batch_size) = opts.batch_size
                     && let Some(commit_message) = opts.commit_message.as_ref()
                 {
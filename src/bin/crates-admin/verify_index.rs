@@ -0,0 +1,75 @@
+use crate::dialoguer;
+use anyhow::Result;
+use clap::builder::ArgAction;
+use crates_io::db;
+use crates_io::index::{IndexFiles, verify_index};
+use crates_io::storage::Storage;
+use crates_io::tasks::spawn_blocking;
+use crates_io_env_vars::var_parsed;
+use crates_io_index::{Repository, RepositoryConfig};
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "verify-index",
+    about = "Compare the git and sparse indexes with the index data generated from the database"
+)]
+pub struct Opts {
+    /// Names of the crates to verify. If not specified, all crates are
+    /// verified, and index files of unknown crates are reported too.
+    names: Vec<String>,
+
+    /// Skip verifying the git index
+    #[arg(long = "no-git", action = ArgAction::SetFalse)]
+    git: bool,
+
+    /// Skip verifying the sparse index
+    #[arg(long = "no-sparse", action = ArgAction::SetFalse)]
+    sparse: bool,
+
+    /// Enqueue sync jobs for all mismatched index files
+    #[arg(long)]
+    repair: bool,
+}
+
+pub async fn run(opts: Opts) -> Result<()> {
+    let mut conn = db::oneoff_connection().await?;
+
+    let include_pubtime = var_parsed("INDEX_INCLUDE_PUBTIME")?.unwrap_or(false);
+
+    let repo = if opts.git && RepositoryConfig::is_enabled()? {
+        let config = RepositoryConfig::from_environment()?;
+        Some(spawn_blocking(move || Repository::open(&config)).await??)
+    } else {
+        None
+    };
+
+    let storage = opts.sparse.then(Storage::from_environment);
+
+    let mut backends = Vec::new();
+    if let Some(repo) = &repo {
+        backends.push(IndexFiles::Git(repo.checkout_path()));
+    }
+    if let Some(storage) = &storage {
+        backends.push(IndexFiles::Sparse(storage));
+    }
+
+    let crate_names = (!opts.names.is_empty()).then_some(opts.names);
+    let report = verify_index(&mut conn, crate_names, include_pubtime, &backends).await?;
+
+    println!("{}", serde_json::to_string_pretty(&report)?);
+
+    let num_mismatches = report.mismatches.len();
+    if num_mismatches == 0 || !opts.repair {
+        return Ok(());
+    }
+
+    let prompt = format!("Enqueue sync jobs for {num_mismatches} mismatched index files?");
+    if !dialoguer::confirm(prompt).await? {
+        return Ok(());
+    }
+
+    let num_jobs = report.enqueue_repairs(&mut conn).await?;
+    eprintln!("Enqueued {num_jobs} index sync jobs");
+
+    Ok(())
+}
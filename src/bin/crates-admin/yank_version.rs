@@ -2,7 +2,8 @@ use crate::dialoguer;
 use crates_io::db;
 use crates_io::models::{Crate, Version};
 use crates_io::schema::versions;
-use crates_io::worker::jobs::{SyncToGitIndex, SyncToSparseIndex, UpdateDefaultVersion};
+use crates_io::worker::jobs::{UpdateDefaultVersion, enqueue_sync_to_index};
+use crates_io_index::RepositoryConfig;
 use crates_io_worker::BackgroundJob;
 use diesel::prelude::*;
 use diesel_async::scoped_futures::ScopedFutureExt;
@@ -67,15 +68,11 @@ async fn yank(opts: Opts, conn: &mut AsyncPgConnection) -> anyhow::Result<()> {
         .execute(conn)
         .await?;
 
-    let git_index_job = SyncToGitIndex::new(&krate.name);
-    let sparse_index_job = SyncToSparseIndex::new(&krate.name);
-    let update_default_version_job = UpdateDefaultVersion::new(krate.id);
+    let git_index_enabled = RepositoryConfig::is_enabled()?;
+    enqueue_sync_to_index(&krate.name, git_index_enabled, conn).await?;
 
-    tokio::try_join!(
-        git_index_job.enqueue(conn),
-        sparse_index_job.enqueue(conn),
-        update_default_version_job.enqueue(conn),
-    )?;
+    let update_default_version_job = UpdateDefaultVersion::new(krate.id);
+    update_default_version_job.enqueue(conn).await?;
 
     Ok(())
 }
@@ -7,6 +7,7 @@
 use anyhow::Result;
 use crates_io::worker::jobs;
 use crates_io::{db, schema::*};
+use crates_io_database::models::{DownloadAnomaly, IndexVerification};
 use crates_io_diesel_helpers::canon_crate_name;
 use crates_io_env_vars::{required_var, var, var_parsed};
 use crates_io_pagerduty as pagerduty;
@@ -27,6 +28,8 @@ async fn main() -> Result<()> {
     check_failing_background_jobs(conn, &client).await?;
     check_stalled_update_downloads(conn, &client).await?;
     check_spam_attack(conn, &client).await?;
+    check_index_drift(conn, &client).await?;
+    check_download_anomalies(conn, &client).await?;
     Ok(())
 }
 
@@ -173,6 +176,77 @@ async fn check_spam_attack(
     Ok(())
 }
 
+/// Check whether the latest `verify_index` background job found index files
+/// that do not match the database
+async fn check_index_drift(
+    conn: &mut AsyncPgConnection,
+    pagerduty: &PagerdutyClient,
+) -> Result<()> {
+    const EVENT_KEY: &str = "index_drift";
+
+    println!("Checking for index files that do not match the database");
+
+    let verification = IndexVerification::latest(conn).await?;
+    let num_mismatches = verification.as_ref().map_or(0, |v| v.num_mismatches());
+
+    let event = if let Some(verification) = verification.filter(|_| num_mismatches > 0) {
+        pagerduty::Event::Trigger {
+            incident_key: Some(EVENT_KEY.into()),
+            description: format!(
+                "{num_mismatches} index files did not match the database at {}, \
+                run `crates-admin verify-index --repair` to fix them",
+                verification.created_at
+            ),
+        }
+    } else {
+        pagerduty::Event::Resolve {
+            incident_key: EVENT_KEY.into(),
+            description: Some("No index drift detected".into()),
+        }
+    };
+
+    log_and_trigger_event(pagerduty, event).await?;
+    Ok(())
+}
+
+/// Check for unreviewed download anomalies with more excess downloads than
+/// the configured threshold
+async fn check_download_anomalies(
+    conn: &mut AsyncPgConnection,
+    pagerduty: &PagerdutyClient,
+) -> Result<()> {
+    const EVENT_KEY: &str = "download_anomalies";
+
+    println!("Checking for unreviewed download anomalies");
+
+    // Minimum number of excess downloads of an anomaly to page on-call
+    let threshold = var_parsed("DOWNLOAD_ANOMALY_PAGE_THRESHOLD")?.unwrap_or(1_000_000);
+
+    let num_anomalies = DownloadAnomaly::unreviewed(conn)
+        .await?
+        .iter()
+        .filter(|anomaly| anomaly.excess_downloads() >= threshold)
+        .count();
+
+    let event = if num_anomalies > 0 {
+        pagerduty::Event::Trigger {
+            incident_key: Some(EVENT_KEY.into()),
+            description: format!(
+                "{num_anomalies} unreviewed download anomalies with at least {threshold} \
+                excess downloads, run `crates-admin download-anomalies list` to review them"
+            ),
+        }
+    } else {
+        pagerduty::Event::Resolve {
+            incident_key: EVENT_KEY.into(),
+            description: Some("No download anomalies above the threshold".into()),
+        }
+    };
+
+    log_and_trigger_event(pagerduty, event).await?;
+    Ok(())
+}
+
 async fn log_and_trigger_event(pagerduty: &PagerdutyClient, event: pagerduty::Event) -> Result<()> {
     match event {
         pagerduty::Event::Trigger {
@@ -40,10 +40,17 @@ fn main() -> anyhow::Result<()> {
         .databases_from_config(&config.db)
         .github(github)
         .github_oauth_from_config(&config)
-        .trustpub_providers(&list("TRUSTPUB_PROVIDERS")?)
+        .trustpub_providers(
+            &list("TRUSTPUB_PROVIDERS")?,
+            &config.trustpub_gitlab_issuer_urls,
+            &config.trustpub_forgejo_issuer_url,
+        )
         .emails(emails)
         .storage_from_config(&config.storage)
         .rate_limiter_from_config(config.rate_limiter.clone())
+        .sparse_index_cache_from_config(&config)
+        .suggest_cache_from_config(&config)
+        .suggest_rate_limiter_from_config(&config)
         .config(Arc::new(config))
         .build();
 
@@ -2,6 +2,7 @@ mod base;
 mod cdn_log_queue;
 mod cdn_log_storage;
 mod database_pools;
+mod index_signing;
 mod sentry;
 mod server;
 
@@ -9,5 +10,6 @@ pub use self::base::Base;
 pub use self::cdn_log_queue::CdnLogQueueConfig;
 pub use self::cdn_log_storage::CdnLogStorageConfig;
 pub use self::database_pools::{DatabasePools, DbPoolConfig};
+pub use self::index_signing::IndexSigningConfig;
 pub use self::sentry::SentryConfig;
 pub use self::server::Server;
@@ -0,0 +1,52 @@
+use anyhow::Context;
+use crates_io_env_vars::{var, var_parsed};
+use crates_io_index::tuf::SigningKey;
+
+const DEFAULT_TARGETS_EXPIRY_DAYS: i64 = 7;
+const DEFAULT_TIMESTAMP_EXPIRY_HOURS: i64 = 24;
+
+/// Configuration for the signed TUF metadata of the sparse index.
+///
+/// The `root` metadata is signed offline (see the `crates-admin index-keys`
+/// command), so only the online key that signs the `targets`, `snapshot`
+/// and `timestamp` metadata is part of the server configuration.
+#[derive(Debug, Clone)]
+pub struct IndexSigningConfig {
+    pub online_key: SigningKey,
+    /// How long the `targets` and `snapshot` metadata stay valid.
+    pub targets_expiry: chrono::Duration,
+    /// How long the `timestamp` metadata stays valid. The metadata needs
+    /// to be re-signed more frequently than this, even if the index has
+    /// not changed.
+    pub timestamp_expiry: chrono::Duration,
+}
+
+impl IndexSigningConfig {
+    /// Reads the configuration from the environment, returning `None` if
+    /// the `INDEX_SIGNING_KEY` environment variable is not set.
+    ///
+    /// - `INDEX_SIGNING_KEY`: The PEM-encoded PKCS#8 P-256 private key that is
+    ///   used as the online key.
+    /// - `INDEX_SIGNING_TARGETS_EXPIRY_DAYS`: Defaults to 7 days.
+    /// - `INDEX_SIGNING_TIMESTAMP_EXPIRY_HOURS`: Defaults to 24 hours.
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let Some(pem) = var("INDEX_SIGNING_KEY")?.filter(|s| !s.is_empty()) else {
+            return Ok(None);
+        };
+
+        let online_key =
+            SigningKey::from_pkcs8_pem(&pem).context("Failed to parse INDEX_SIGNING_KEY")?;
+
+        let targets_expiry =
+            var_parsed("INDEX_SIGNING_TARGETS_EXPIRY_DAYS")?.unwrap_or(DEFAULT_TARGETS_EXPIRY_DAYS);
+
+        let timestamp_expiry = var_parsed("INDEX_SIGNING_TIMESTAMP_EXPIRY_HOURS")?
+            .unwrap_or(DEFAULT_TIMESTAMP_EXPIRY_HOURS);
+
+        Ok(Some(Self {
+            online_key,
+            targets_expiry: chrono::Duration::days(targets_expiry),
+            timestamp_expiry: chrono::Duration::hours(timestamp_expiry),
+        }))
+    }
+}
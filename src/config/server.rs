@@ -8,10 +8,14 @@ use crate::util::gh_token_encryption::GitHubTokenEncryption;
 use super::base::Base;
 use super::database_pools::DatabasePools;
 use crate::config::CdnLogQueueConfig;
+use crate::config::IndexSigningConfig;
 use crate::config::cdn_log_storage::CdnLogStorageConfig;
 use crate::middleware::cargo_compat::StatusCodeConfig;
 use crate::storage::StorageConfig;
 use crates_io_env_vars::{list, list_parsed, required_var, var, var_parsed};
+use crates_io_index::RepositoryConfig;
+use crates_io_trustpub::forgejo::FORGEJO_ISSUER_URL;
+use crates_io_trustpub::gitlab::GITLAB_ISSUER_URL;
 use http::HeaderValue;
 use std::collections::{HashMap, HashSet};
 use std::convert::Infallible;
@@ -21,6 +25,11 @@ use std::time::Duration;
 
 const DEFAULT_VERSION_ID_CACHE_SIZE: u64 = 10_000;
 const DEFAULT_VERSION_ID_CACHE_TTL: u64 = 5 * 60; // 5 minutes
+const DEFAULT_SPARSE_INDEX_CACHE_SIZE: u64 = 10_000;
+const DEFAULT_SPARSE_INDEX_CACHE_TTL: u64 = 60; // 1 minute
+const DEFAULT_SUGGEST_CACHE_SIZE: u64 = 10_000;
+const DEFAULT_SUGGEST_CACHE_TTL: u64 = 60; // 1 minute
+const DEFAULT_SUGGEST_RATE_LIMIT: u32 = 120;
 
 /// Maximum number of features a crate can have or that a feature itself can
 /// enable. This value can be overridden in the database on a per-crate basis.
@@ -90,6 +99,14 @@ pub struct Server {
     /// token exchange.
     pub trustpub_audience: String,
 
+    /// The issuer URLs (`iss` claim) of the GitLab instances whose CI/CD
+    /// OIDC tokens are accepted for Trusted Publishing.
+    pub trustpub_gitlab_issuer_urls: Vec<String>,
+
+    /// The issuer URL (`iss` claim) of the Forgejo instance whose Actions
+    /// OIDC tokens are accepted for Trusted Publishing.
+    pub trustpub_forgejo_issuer_url: String,
+
     /// Disables API token creation when set to any non-empty value.
     /// The value is used as the error message returned to users.
     pub disable_token_creation: Option<String>,
@@ -100,8 +117,38 @@ pub struct Server {
     /// Include publication timestamp in index entries (ISO8601 format).
     pub index_include_pubtime: bool,
 
+    /// Sync the index files to the git index repository. If disabled, only
+    /// the sparse index is updated.
+    pub git_index_enabled: bool,
+
     /// Enable Fastly CDN invalidation for sparse index files.
     pub sparse_index_fastly_enabled: bool,
+
+    /// Serve the sparse index directly from the API server at `/index/`,
+    /// instead of only uploading the index files to the index storage.
+    pub serve_sparse_index: bool,
+
+    /// The base URL of the API, as advertised in the `config.json` file of
+    /// the sparse index that is served by the API server.
+    pub sparse_index_api_url: Url,
+
+    pub sparse_index_cache_size: u64,
+    pub sparse_index_cache_ttl: Duration,
+
+    pub suggest_cache_size: u64,
+    pub suggest_cache_ttl: Duration,
+
+    /// The maximum number of crate name suggestions requests per minute and
+    /// IP address.
+    pub suggest_rate_limit: u32,
+
+    /// Configuration for signing the TUF metadata of the sparse index, or
+    /// `None` if the metadata should not be generated.
+    pub index_signing: Option<IndexSigningConfig>,
+
+    /// Allow webhook deliveries to loopback, private and other internal
+    /// network addresses. This should only be enabled for local testing.
+    pub webhooks_allow_private_addresses: bool,
 }
 
 impl Server {
@@ -133,6 +180,18 @@ impl Server {
     ///   by an operator (e.g. `/crates/{crate_id}/{version}/download`).
     /// - `DISABLE_TOKEN_CREATION`: If set to any non-empty value, disables API token creation
     ///   and uses the value as the error message returned to users.
+    /// - `TRUSTPUB_GITLAB_ISSUER_URLS`: A comma separated list of OIDC issuer URLs of the
+    ///   GitLab instances that are allowed for Trusted Publishing. Defaults to `gitlab.com`.
+    /// - `TRUSTPUB_FORGEJO_ISSUER_URL`: The OIDC issuer URL of the Forgejo instance used for
+    ///   Trusted Publishing. Defaults to the Codeberg instance.
+    /// - `SERVE_SPARSE_INDEX`: Whether the API server should serve the sparse index at `/index/`.
+    ///   Defaults to `true` in development and `false` otherwise.
+    /// - `SPARSE_INDEX_API_URL`: The API base URL advertised in the `config.json` file of the
+    ///   sparse index served by the API server. Defaults to `https://{DOMAIN_NAME}`.
+    /// - `INDEX_SIGNING_KEY`: The online key used to sign the TUF metadata of the sparse index.
+    ///   If missing, no signed metadata is generated. See [`IndexSigningConfig`].
+    /// - `WEBHOOKS_ALLOW_PRIVATE_ADDRESSES`: Whether webhooks may be delivered to internal network
+    ///   addresses. Defaults to `true` in development and `false` otherwise.
     ///
     /// # Panics
     ///
@@ -199,9 +258,23 @@ impl Server {
 
         let domain_name = dotenvy::var("DOMAIN_NAME").unwrap_or_else(|_| "crates.io".into());
         let trustpub_audience = var("TRUSTPUB_AUDIENCE")?.unwrap_or_else(|| domain_name.clone());
+        let mut trustpub_gitlab_issuer_urls = list("TRUSTPUB_GITLAB_ISSUER_URLS")?;
+        if trustpub_gitlab_issuer_urls.is_empty() {
+            trustpub_gitlab_issuer_urls.push(GITLAB_ISSUER_URL.into());
+        }
+        let trustpub_forgejo_issuer_url =
+            var("TRUSTPUB_FORGEJO_ISSUER_URL")?.unwrap_or_else(|| FORGEJO_ISSUER_URL.into());
         let disable_token_creation = var("DISABLE_TOKEN_CREATION")?.filter(|s| !s.is_empty());
         let banner_message = var("BANNER_MESSAGE")?.filter(|s| !s.is_empty());
         let index_include_pubtime = var_parsed("INDEX_INCLUDE_PUBTIME")?.unwrap_or(false);
+        let serve_sparse_index =
+            var_parsed("SERVE_SPARSE_INDEX")?.unwrap_or(base.env == Env::Development);
+        let webhooks_allow_private_addresses =
+            var_parsed("WEBHOOKS_ALLOW_PRIVATE_ADDRESSES")?.unwrap_or(base.env == Env::Development);
+        let sparse_index_api_url = match var_parsed("SPARSE_INDEX_API_URL")? {
+            Some(url) => url,
+            None => format!("https://{domain_name}").parse()?,
+        };
 
         Ok(Server {
             db: DatabasePools::full_from_environment(&base)?,
@@ -250,11 +323,30 @@ impl Server {
             html_render_cache_max_capacity: var_parsed("HTML_RENDER_CACHE_CAP")?.unwrap_or(1024),
             content_security_policy: Some(content_security_policy.parse()?),
             trustpub_audience,
+            trustpub_gitlab_issuer_urls,
+            trustpub_forgejo_issuer_url,
             disable_token_creation,
             banner_message,
             index_include_pubtime,
+            git_index_enabled: RepositoryConfig::is_enabled()?,
             sparse_index_fastly_enabled: var_parsed("SPARSE_INDEX_FASTLY_ENABLED")?
                 .unwrap_or(false),
+            serve_sparse_index,
+            sparse_index_api_url,
+            sparse_index_cache_size: var_parsed("SPARSE_INDEX_CACHE_SIZE")?
+                .unwrap_or(DEFAULT_SPARSE_INDEX_CACHE_SIZE),
+            sparse_index_cache_ttl: Duration::from_secs(
+                var_parsed("SPARSE_INDEX_CACHE_TTL")?.unwrap_or(DEFAULT_SPARSE_INDEX_CACHE_TTL),
+            ),
+            suggest_cache_size: var_parsed("SUGGEST_CACHE_SIZE")?
+                .unwrap_or(DEFAULT_SUGGEST_CACHE_SIZE),
+            suggest_cache_ttl: Duration::from_secs(
+                var_parsed("SUGGEST_CACHE_TTL")?.unwrap_or(DEFAULT_SUGGEST_CACHE_TTL),
+            ),
+            suggest_rate_limit: var_parsed("SUGGEST_RATE_LIMIT")?
+                .unwrap_or(DEFAULT_SUGGEST_RATE_LIMIT),
+            index_signing: IndexSigningConfig::from_env()?,
+            webhooks_allow_private_addresses,
         })
     }
 }
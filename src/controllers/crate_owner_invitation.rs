@@ -4,7 +4,8 @@ use crate::auth::Authentication;
 use crate::controllers::helpers::authorization::Rights;
 use crate::controllers::helpers::pagination::{Page, PaginationOptions, PaginationQueryParams};
 use crate::models::crate_owner_invitation::AcceptError;
-use crate::models::{Crate, CrateOwnerInvitation, User};
+use crate::models::token::EndpointScope;
+use crate::models::{Crate, CrateOwnerInvitation, User, WebhookEvent};
 use crate::schema::{crate_owner_invitations, crates, users};
 use crate::util::RequestUtils;
 use crate::util::errors::{AppResult, BoxedAppError, bad_request, custom, forbidden, internal};
@@ -12,6 +13,7 @@ use crate::views::{
     EncodableCrateOwnerInvitation, EncodableCrateOwnerInvitationV1, EncodablePublicUser,
     InvitationResponse,
 };
+use crate::worker::jobs::webhooks;
 use axum::Json;
 use axum::extract::{FromRequestParts, Path, Query};
 use chrono::Utc;
@@ -23,6 +25,7 @@ use http::StatusCode;
 use http::request::Parts;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::collections::{HashMap, HashSet};
 
 #[derive(Serialize, utoipa::ToSchema)]
@@ -38,7 +41,10 @@ pub struct LegacyListResponse {
 #[utoipa::path(
     get,
     path = "/api/v1/me/crate_owner_invitations",
-    security(("cookie" = [])),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
     tag = "owners",
     responses((status = 200, description = "Successful Response", body = inline(LegacyListResponse))),
 )]
@@ -47,7 +53,12 @@ pub async fn list_crate_owner_invitations_for_user(
     req: Parts,
 ) -> AppResult<Json<LegacyListResponse>> {
     let mut conn = app.db_read().await?;
-    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::ReadPrivate)
+        .check(&req, &mut conn)
+        .await?;
+
+    auth.reject_legacy_tokens()?;
 
     let user_id = auth.user_id();
 
@@ -102,7 +113,10 @@ pub struct ListQueryParams {
     get,
     path = "/api/private/crate_owner_invitations",
     params(ListQueryParams, PaginationQueryParams),
-    security(("cookie" = [])),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
     tag = "owners",
     responses((status = 200, description = "Successful Response", body = inline(PrivateListResponse))),
 )]
@@ -112,7 +126,13 @@ pub async fn list_crate_owner_invitations(
     req: Parts,
 ) -> AppResult<Json<PrivateListResponse>> {
     let mut conn = app.db_read().await?;
-    let auth = AuthCheck::only_cookie().check(&req, &mut conn).await?;
+
+    let mut auth_check = AuthCheck::default().with_endpoint_scope(EndpointScope::ReadPrivate);
+    if let Some(crate_name) = &params.crate_name {
+        auth_check = auth_check.for_crate(crate_name);
+    }
+    let auth = auth_check.check(&req, &mut conn).await?;
+    auth.reject_legacy_tokens()?;
 
     let filter = params.try_into()?;
     let list = prepare_list(&app, &req, auth, filter, &mut conn).await?;
@@ -358,6 +378,7 @@ pub async fn handle_crate_owner_invitation(
 
     let mut conn = state.db_write().await?;
     let user_id = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::ChangeOwners)
         .check(&parts, &mut conn)
         .await?
         .user_id();
@@ -366,6 +387,7 @@ pub async fn handle_crate_owner_invitation(
 
     if crate_invite.accepted {
         invitation.accept(&mut conn).await?;
+        enqueue_owner_added_webhooks(&mut conn, crate_invite.crate_id, user_id).await?;
     } else {
         invitation.decline(&mut conn).await?;
     }
@@ -393,7 +415,9 @@ pub async fn accept_crate_owner_invitation_with_token(
     let invitation = CrateOwnerInvitation::find_by_token(&token, &mut conn).await?;
 
     let crate_id = invitation.crate_id;
+    let user_id = invitation.invited_user_id;
     invitation.accept(&mut conn).await?;
+    enqueue_owner_added_webhooks(&mut conn, crate_id, user_id).await?;
 
     let crate_owner_invitation = InvitationResponse {
         crate_id,
@@ -428,3 +452,28 @@ impl From<AcceptError> for BoxedAppError {
         }
     }
 }
+
+/// Notifies the webhooks of the crate that the user has become an owner.
+async fn enqueue_owner_added_webhooks(
+    conn: &mut AsyncPgConnection,
+    crate_id: i32,
+    user_id: i32,
+) -> AppResult<()> {
+    let crate_name: String = crates::table
+        .find(crate_id)
+        .select(crates::name)
+        .first(conn)
+        .await?;
+
+    let login: String = users::table
+        .find(user_id)
+        .select(users::gh_login)
+        .first(conn)
+        .await?;
+
+    let data = json!({ "action": "add", "owner": login });
+    webhooks::enqueue_deliveries(conn, crate_id, &crate_name, WebhookEvent::OwnerChange, data)
+        .await?;
+
+    Ok(())
+}
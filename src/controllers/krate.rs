@@ -7,7 +7,9 @@ use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use serde::Deserialize;
 use utoipa::IntoParams;
 
+pub mod audit;
 pub mod delete;
+pub mod download_history;
 pub mod downloads;
 pub mod follow;
 pub mod metadata;
@@ -15,8 +17,10 @@ pub mod owners;
 pub mod publish;
 pub mod rev_deps;
 pub mod search;
+pub mod suggest;
 pub mod update;
 pub mod versions;
+pub mod webhooks;
 
 #[derive(Deserialize, FromRequestParts, IntoParams)]
 #[into_params(parameter_in = Path)]
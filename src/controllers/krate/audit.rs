@@ -0,0 +1,181 @@
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::helpers::authorization::Rights;
+use crate::controllers::helpers::pagination::{
+    Page, PaginationOptions, PaginationQueryParams, encode_seek,
+};
+use crate::controllers::krate::CratePath;
+use crate::models::token::EndpointScope;
+use crate::models::{CrateAuditAction, User};
+use crate::schema::{crate_audit_actions, users};
+use crate::util::RequestUtils;
+use crate::util::errors::{AppResult, custom};
+use crate::views::EncodableCrateAuditAction;
+use axum::Json;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use http::StatusCode;
+use http::request::Parts;
+use indexmap::IndexMap;
+use serde::Serialize;
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct AuditResponse {
+    /// The audit log entries of the crate, newest first.
+    pub audit_actions: Vec<EncodableCrateAuditAction>,
+
+    #[schema(inline)]
+    pub meta: AuditResponseMeta,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct AuditResponseMeta {
+    /// The total number of audit log entries of the crate.
+    #[schema(example = 42)]
+    pub total: i64,
+
+    /// Query string to the next page of results, if any.
+    #[schema(example = "?seek=abc123")]
+    pub next_page: Option<String>,
+}
+
+/// List the audit log of a crate.
+///
+/// The audit log contains owner changes, settings changes, Trusted Publishing
+/// configuration changes and other administrative actions performed on the
+/// crate. It is only visible to owners of the crate.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/audit",
+    params(CratePath, PaginationQueryParams),
+    security(("api_token" = []), ("cookie" = [])),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response", body = inline(AuditResponse))),
+)]
+pub async fn list_audit_actions(
+    app: AppState,
+    path: CratePath,
+    parts: Parts,
+) -> AppResult<Json<AuditResponse>> {
+    let mut conn = app.db_read_prefer_primary().await?;
+
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::ReadPrivate)
+        .for_crate(&path.name)
+        .check(&parts, &mut conn)
+        .await?;
+
+    let krate = path.load_crate(&mut conn).await?;
+
+    let user = auth.user();
+    let owners = krate.owners(&mut conn).await?;
+    if Rights::get(user, &*app.github, &owners, &app.config.gh_token_encryption).await?
+        == Rights::None
+    {
+        let msg = "only owners of this crate are allowed to view its audit log";
+        return Err(custom(StatusCode::FORBIDDEN, msg));
+    }
+
+    let pagination = PaginationOptions::builder()
+        .enable_seek(true)
+        .enable_pages(false)
+        .gather(&parts)?;
+
+    let (actions, total, next_page) =
+        list_actions(&mut conn, krate.id, &pagination, &parts).await?;
+
+    let audit_actions = actions
+        .into_iter()
+        .map(|(action, user)| EncodableCrateAuditAction::from(action, user))
+        .collect();
+
+    Ok(Json(AuditResponse {
+        audit_actions,
+        meta: AuditResponseMeta { total, next_page },
+    }))
+}
+
+async fn list_actions(
+    conn: &mut AsyncPgConnection,
+    crate_id: i32,
+    options: &PaginationOptions,
+    req: &Parts,
+) -> AppResult<(Vec<(CrateAuditAction, Option<User>)>, i64, Option<String>)> {
+    use seek::*;
+
+    let seek = Seek::Id;
+
+    assert!(
+        !matches!(&options.page, Page::Numeric(_)),
+        "?page= is not supported"
+    );
+
+    let mut query = crate_audit_actions::table
+        .left_join(users::table)
+        .filter(crate_audit_actions::crate_id.eq(crate_id))
+        .select((CrateAuditAction::as_select(), Option::<User>::as_select()))
+        .order(crate_audit_actions::id.desc())
+        .limit(options.per_page)
+        .into_boxed();
+
+    if let Some(SeekPayload::Id(Id { id })) = seek.after(&options.page)? {
+        query = query.filter(crate_audit_actions::id.lt(id));
+    }
+
+    let data: Vec<(CrateAuditAction, Option<User>)> = query.load(conn).await?;
+
+    let next_page = next_seek_params(&data, options, |last| seek.to_payload(&last.0))?
+        .map(|p| req.query_with_params(p));
+
+    // Avoid the count query if we're on the first page and got fewer results than requested
+    let total =
+        if matches!(options.page, Page::Unspecified) && data.len() < options.per_page as usize {
+            data.len() as i64
+        } else {
+            crate_audit_actions::table
+                .filter(crate_audit_actions::crate_id.eq(crate_id))
+                .count()
+                .get_result(conn)
+                .await?
+        };
+
+    Ok((data, total, next_page))
+}
+
+fn next_seek_params<T, S, F>(
+    records: &[T],
+    options: &PaginationOptions,
+    f: F,
+) -> AppResult<Option<IndexMap<String, String>>>
+where
+    F: Fn(&T) -> S,
+    S: serde::Serialize,
+{
+    if records.len() < options.per_page as usize {
+        return Ok(None);
+    }
+
+    let seek = f(records.last().unwrap());
+    let mut opts = IndexMap::new();
+    opts.insert("seek".into(), encode_seek(seek)?);
+    Ok(Some(opts))
+}
+
+mod seek {
+    use crate::controllers::helpers::pagination::seek;
+    use crate::models::CrateAuditAction;
+
+    seek!(
+        pub enum Seek {
+            Id { id: i32 },
+        }
+    );
+
+    impl Seek {
+        pub(crate) fn to_payload(&self, record: &CrateAuditAction) -> SeekPayload {
+            match *self {
+                Seek::Id => SeekPayload::Id(Id { id: record.id }),
+            }
+        }
+    }
+}
@@ -3,10 +3,12 @@ use crate::auth::AuthCheck;
 use crate::controllers::helpers::authorization::Rights;
 use crate::controllers::krate::CratePath;
 use crate::email::EmailMessage;
-use crate::models::NewDeletedCrate;
+use crate::models::token::EndpointScope;
+use crate::models::{CrateAction, NewCrateAuditAction, NewDeletedCrate};
 use crate::schema::{crate_downloads, crates, dependencies};
 use crate::util::errors::{AppResult, BoxedAppError, custom};
 use crate::worker::jobs;
+use crate::worker::jobs::webhooks;
 use axum::extract::rejection::QueryRejection;
 use axum::extract::{FromRequestParts, Query};
 use bigdecimal::ToPrimitive;
@@ -20,6 +22,7 @@ use http::StatusCode;
 use http::request::Parts;
 use minijinja::context;
 use serde::Deserialize;
+use serde_json::json;
 use tracing::error;
 
 pub const DOWNLOADS_PER_MONTH_LIMIT: u64 = 1000;
@@ -47,11 +50,17 @@ impl DeleteQueryParams {
 /// crate has been published for less than 72 hours, or if the crate has a
 /// single owner, has been downloaded less than 1000 times for each month it has
 /// been published, and is not depended upon by any other crate on crates.io.
+///
+/// API tokens need the `delete` scope to use this endpoint. Legacy API tokens
+/// without any scopes are not allowed to delete crates.
 #[utoipa::path(
     delete,
     path = "/api/v1/crates/{name}",
     params(CratePath, DeleteQueryParams),
-    security(("cookie" = [])),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
     tag = "crates",
     responses((status = 204, description = "Successful Response")),
 )]
@@ -63,14 +72,22 @@ pub async fn delete_crate(
 ) -> AppResult<StatusCode> {
     let mut conn = app.db_write().await?;
 
-    // Check that the user is authenticated
-    let auth = AuthCheck::only_cookie().check(&parts, &mut conn).await?;
+    // Check that the user is authenticated, either via cookie or via an API
+    // token with the `delete` scope
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::Delete)
+        .for_crate(&path.name)
+        .check(&parts, &mut conn)
+        .await?;
+
+    auth.reject_legacy_tokens()?;
 
     // Check that the crate exists
     let krate = path.load_crate(&mut conn).await?;
 
     // Check that the user is an owner of the crate (team owners are not allowed to delete crates)
     let user = auth.user();
+    let api_token_id = auth.api_token_id();
     let owners = krate.owners(&mut conn).await?;
     match Rights::get(user, &*app.github, &owners, &app.config.gh_token_encryption).await? {
         Rights::Full => {}
@@ -110,8 +127,23 @@ pub async fn delete_crate(
     }
 
     let crate_name = krate.name.clone();
+    let git_index_enabled = app.config.git_index_enabled;
     conn.transaction(|conn| {
         async move {
+            NewCrateAuditAction::builder()
+                .crate_id(krate.id)
+                .crate_name(&krate.name)
+                .user_id(user.id)
+                .maybe_api_token_id(api_token_id)
+                .action(CrateAction::Delete)
+                .details(json!({ "message": params.message() }))
+                .build()
+                .insert(conn)
+                .await?;
+
+            let data = json!({ "message": params.message() });
+            webhooks::enqueue_deletion_deliveries(conn, krate.id, &krate.name, data).await?;
+
             diesel::delete(crates::table.find(krate.id))
                 .execute(conn)
                 .await?;
@@ -132,15 +164,10 @@ pub async fn delete_crate(
                 .execute(conn)
                 .await?;
 
-            let git_index_job = jobs::SyncToGitIndex::new(&krate.name);
-            let sparse_index_job = jobs::SyncToSparseIndex::new(&krate.name);
-            let delete_from_storage_job = jobs::DeleteCrateFromStorage::new(path.name);
+            jobs::enqueue_sync_to_index(&krate.name, git_index_enabled, conn).await?;
 
-            tokio::try_join!(
-                git_index_job.enqueue(conn),
-                sparse_index_job.enqueue(conn),
-                delete_from_storage_job.enqueue(conn),
-            )?;
+            let delete_from_storage_job = jobs::DeleteCrateFromStorage::new(path.name);
+            delete_from_storage_job.enqueue(conn).await?;
 
             Ok::<_, BoxedAppError>(())
         }
@@ -148,6 +175,8 @@ pub async fn delete_crate(
     })
     .await?;
 
+    app.sparse_index_cache.invalidate(&crate_name).await;
+
     let email_future = async {
         if let Some(recipient) = user.email(&mut conn).await? {
             let email = EmailMessage::from_template(
@@ -0,0 +1,190 @@
+//! Endpoint for exposing the long-range download history of a crate
+//!
+//! Daily download counts older than 90 days are archived to object storage
+//! by the `ArchiveVersionDownloads` background job. Before that happens, they
+//! are rolled up into weekly and monthly counts per crate, which are served
+//! by this endpoint together with the not yet rolled up daily counts.
+
+use crate::app::AppState;
+use crate::controllers::krate::CratePath;
+use crate::schema::{
+    crate_download_rollups, rolled_up_download_dates, version_downloads, versions,
+};
+use crate::util::errors::{AppResult, bad_request};
+use axum::Json;
+use axum::extract::FromRequestParts;
+use axum_extra::extract::Query;
+use chrono::{Datelike, Days, NaiveDate};
+use diesel::dsl::{not, sum};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
+#[from_request(via(Query))]
+#[into_params(parameter_in = Query)]
+pub struct DownloadHistoryQueryParams {
+    /// Only return periods that end on or after this date (`YYYY-MM-DD`).
+    ///
+    /// Defaults to the start of the download history.
+    #[param(example = "2023-01-01")]
+    from: Option<String>,
+
+    /// Only return periods that start on or before this date (`YYYY-MM-DD`).
+    ///
+    /// Defaults to the current period.
+    #[param(example = "2023-12-31")]
+    to: Option<String>,
+
+    /// The length of the periods that the downloads are aggregated into.
+    ///
+    /// Valid values: `week`, `month`.
+    ///
+    /// Defaults to `month`. Weeks start on Mondays.
+    #[param(example = "week")]
+    granularity: Option<String>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct DownloadHistoryResponse {
+    /// The download counts of the crate per period, ordered by date.
+    #[schema(inline)]
+    pub downloads: Vec<PeriodDownloads>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct PeriodDownloads {
+    /// The first day of the period.
+    #[schema(example = "2019-12-01")]
+    date: String,
+
+    /// The number of downloads of all versions of the crate in the period.
+    #[schema(example = 123)]
+    downloads: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Granularity {
+    Week,
+    Month,
+}
+
+impl Granularity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Week => "week",
+            Self::Month => "month",
+        }
+    }
+
+    /// Returns the first day of the period that contains the given date.
+    fn period_start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Week => date - Days::new(date.weekday().num_days_from_monday().into()),
+            Self::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+}
+
+/// Get the long-range download history of a crate.
+///
+/// This returns the downloads of all versions of the crate aggregated into
+/// weekly or monthly periods, including periods for which the daily download
+/// counts have already been archived.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/downloads/history",
+    params(CratePath, DownloadHistoryQueryParams),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response", body = inline(DownloadHistoryResponse))),
+)]
+pub async fn get_crate_download_history(
+    state: AppState,
+    path: CratePath,
+    params: DownloadHistoryQueryParams,
+) -> AppResult<Json<DownloadHistoryResponse>> {
+    let granularity = match params.granularity.as_deref() {
+        None | Some("month") => Granularity::Month,
+        Some("week") => Granularity::Week,
+        Some(_) => return Err(bad_request("granularity must be `week` or `month`")),
+    };
+
+    let from = params.from.as_deref().map(|from| parse_date("from", from));
+    let from = from.transpose()?;
+    let to = params.to.as_deref().map(|to| parse_date("to", to));
+    let to = to.transpose()?;
+
+    if let (Some(from), Some(to)) = (from, to)
+        && from > to
+    {
+        return Err(bad_request("`from` must not be after `to`"));
+    }
+
+    let from = from.map(|from| granularity.period_start(from));
+
+    let mut conn = state.db_read().await?;
+    let crate_id = path.load_crate_id(&mut conn).await?;
+
+    let mut query = crate_download_rollups::table
+        .filter(crate_download_rollups::crate_id.eq(crate_id))
+        .filter(crate_download_rollups::granularity.eq(granularity.as_str()))
+        .select((
+            crate_download_rollups::period_start,
+            crate_download_rollups::downloads,
+        ))
+        .into_boxed();
+
+    if let Some(from) = from {
+        query = query.filter(crate_download_rollups::period_start.ge(from));
+    }
+    if let Some(to) = to {
+        query = query.filter(crate_download_rollups::period_start.le(to));
+    }
+
+    let rollups: Vec<(NaiveDate, i64)> = query.load(&mut conn).await?;
+
+    // The daily downloads that have not been rolled up yet are aggregated on
+    // the fly. These are at most a few months worth of rows.
+    let rolled_up_dates = rolled_up_download_dates::table.select(rolled_up_download_dates::date);
+
+    let mut query = version_downloads::table
+        .inner_join(versions::table)
+        .filter(versions::crate_id.eq(crate_id))
+        .filter(not(version_downloads::date.eq_any(rolled_up_dates)))
+        .group_by(version_downloads::date)
+        .select((version_downloads::date, sum(version_downloads::downloads)))
+        .into_boxed();
+
+    if let Some(from) = from {
+        query = query.filter(version_downloads::date.ge(from));
+    }
+
+    let daily: Vec<(NaiveDate, Option<i64>)> = query.load(&mut conn).await?;
+
+    let mut downloads = BTreeMap::<NaiveDate, i64>::new();
+    for (period_start, count) in rollups {
+        *downloads.entry(period_start).or_default() += count;
+    }
+    for (date, count) in daily {
+        let period_start = granularity.period_start(date);
+        if to.is_none_or(|to| period_start <= to) {
+            *downloads.entry(period_start).or_default() += count.unwrap_or_default();
+        }
+    }
+
+    let downloads = downloads
+        .into_iter()
+        .map(|(date, downloads)| PeriodDownloads {
+            date: date.to_string(),
+            downloads,
+        })
+        .collect();
+
+    Ok(Json(DownloadHistoryResponse { downloads }))
+}
+
+fn parse_date(param: &str, value: &str) -> AppResult<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| bad_request(format!("`{param}` must be a date in the format YYYY-MM-DD")))
+}
@@ -7,7 +7,10 @@ use crate::app::AppState;
 use crate::controllers::krate::CratePath;
 use crate::models::download::Version;
 use crate::models::{User, Version as FullVersion, VersionDownload, VersionOwnerAction};
-use crate::schema::{version_downloads, version_owner_actions, versions};
+use crate::schema::{
+    cargo_version_downloads, crate_platform_downloads, version_downloads, version_owner_actions,
+    versions,
+};
 use crate::util::errors::{AppResult, BoxedAppError, bad_request};
 use crate::views::{EncodableVersion, EncodableVersionDownload};
 use axum::Json;
@@ -21,6 +24,7 @@ use futures_util::FutureExt;
 use futures_util::future::BoxFuture;
 use serde::{Deserialize, Serialize};
 use std::cmp;
+use std::collections::HashMap;
 use std::str::FromStr;
 
 #[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
@@ -29,7 +33,7 @@ use std::str::FromStr;
 pub struct DownloadsQueryParams {
     /// Additional data to include in the response.
     ///
-    /// Valid values: `versions`.
+    /// Valid values: `versions`, `cargo_versions`, `platforms`.
     ///
     /// Defaults to no additional data.
     ///
@@ -47,6 +51,20 @@ pub struct DownloadsResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub versions: Option<Vec<EncodableVersion>>,
 
+    /// The per-day download counts of the crate for the last 90 days, broken
+    /// down by the minor version of cargo that downloaded it, if
+    /// `?include=cargo_versions` was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(inline)]
+    pub cargo_version_downloads: Option<Vec<CargoVersionDownload>>,
+
+    /// The download counts of the crate for the last 90 days, broken down
+    /// by host platform and CDN region, if `?include=platforms` was
+    /// requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(inline)]
+    pub platform_downloads: Option<PlatformDownloads>,
+
     #[schema(inline)]
     pub meta: DownloadsMeta,
 }
@@ -68,6 +86,132 @@ pub struct ExtraDownload {
     downloads: i64,
 }
 
+#[derive(Debug, Serialize, Queryable, utoipa::ToSchema)]
+pub struct CargoVersionDownload {
+    /// The date this download count is for.
+    #[schema(example = "2019-12-13")]
+    date: String,
+
+    /// The minor version of cargo that downloaded the crate, or `other` if
+    /// the client could not be determined.
+    #[schema(example = "1.75")]
+    cargo_version: String,
+
+    /// The number of downloads by this cargo version on the given date.
+    #[schema(example = 123)]
+    downloads: i64,
+}
+
+/// The minimum number of downloads that a host OS, host architecture or CDN
+/// region needs within the last 90 days to be listed separately in the
+/// `platform_downloads` breakdown. Smaller counts are folded into the
+/// [`OTHER_PLATFORM`] bucket, since they could be used to single out
+/// individual users of a crate.
+const MIN_PLATFORM_DOWNLOADS: i64 = 100;
+
+/// The name of the bucket that the values below [`MIN_PLATFORM_DOWNLOADS`]
+/// are folded into.
+const OTHER_PLATFORM: &str = "other";
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct PlatformDownloads {
+    /// The downloads by the operating system of the host, as far as it
+    /// could be determined from the user agent (`linux`, `macos`, `windows`,
+    /// `other` or `unknown`).
+    #[schema(inline)]
+    host_os: Vec<PlatformDownload>,
+
+    /// The downloads by the CPU architecture of the host, as far as it
+    /// could be determined from the user agent (`x86_64`, `aarch64`, `x86`,
+    /// `arm`, `other` or `unknown`).
+    #[schema(inline)]
+    host_arch: Vec<PlatformDownload>,
+
+    /// The downloads by the continent-level region of the CDN point of
+    /// presence that served them (e.g. `europe` or `north_america`), or
+    /// `other` for the regions with too few downloads.
+    #[schema(inline)]
+    cdn_region: Vec<PlatformDownload>,
+}
+
+impl PlatformDownloads {
+    /// Sums up the `(host_os, host_arch, cdn_region, downloads)` rows per
+    /// dimension and folds the values with less than
+    /// [`MIN_PLATFORM_DOWNLOADS`] downloads into the [`OTHER_PLATFORM`]
+    /// bucket.
+    fn from_rows(rows: Vec<PlatformDownloadRow>) -> Self {
+        let mut host_os = HashMap::new();
+        let mut host_arch = HashMap::new();
+        let mut cdn_region = HashMap::new();
+        for (os, arch, region, downloads) in rows {
+            *host_os.entry(os).or_default() += downloads;
+            *host_arch.entry(arch).or_default() += downloads;
+            *cdn_region.entry(region).or_default() += downloads;
+        }
+
+        Self {
+            host_os: PlatformDownload::from_map(host_os),
+            host_arch: PlatformDownload::from_map(host_arch),
+            cdn_region: PlatformDownload::from_map(cdn_region),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct PlatformDownload {
+    /// The host OS, host architecture or CDN region.
+    #[schema(example = "linux")]
+    name: String,
+
+    /// The number of downloads in the last 90 days.
+    #[schema(example = 123)]
+    downloads: i64,
+}
+
+impl PlatformDownload {
+    /// Converts the summed up downloads into a list sorted by the number of
+    /// downloads, with the values below [`MIN_PLATFORM_DOWNLOADS`] folded
+    /// into the [`OTHER_PLATFORM`] bucket.
+    ///
+    /// The bucket always combines at least two values, so that a single
+    /// small value can't be recovered by subtracting the listed values from
+    /// the total downloads.
+    fn from_map(downloads: HashMap<String, i64>) -> Vec<Self> {
+        let (mut folded, mut listed): (Vec<_>, Vec<_>) = downloads
+            .into_iter()
+            .partition(|(_, downloads)| *downloads < MIN_PLATFORM_DOWNLOADS);
+
+        if !folded.is_empty() {
+            if let Some(index) = listed.iter().position(|(name, _)| name == OTHER_PLATFORM) {
+                folded.push(listed.swap_remove(index));
+            }
+
+            if folded.len() == 1 {
+                let smallest = listed
+                    .iter()
+                    .enumerate()
+                    .min_by(|(_, a), (_, b)| a.1.cmp(&b.1).then(a.0.cmp(&b.0)))
+                    .map(|(index, _)| index);
+
+                if let Some(index) = smallest {
+                    folded.push(listed.swap_remove(index));
+                }
+            }
+
+            let other = folded.iter().map(|(_, downloads)| downloads).sum();
+            listed.push((OTHER_PLATFORM.to_string(), other));
+        }
+
+        let mut downloads = listed
+            .into_iter()
+            .map(|(name, downloads)| Self { name, downloads })
+            .collect::<Vec<_>>();
+
+        downloads.sort_by(|a, b| b.downloads.cmp(&a.downloads).then(a.name.cmp(&b.name)));
+        downloads
+    }
+}
+
 /// Get the download counts for a crate.
 ///
 /// This includes the per-day downloads for the last 90 days and for the
@@ -107,29 +251,32 @@ pub async fn get_crate_downloads(
         .unwrap_or_default();
 
     let sum_downloads = sql::<BigInt>("SUM(version_downloads.downloads)");
-    let (downloads, extra_downloads, versions_and_publishers, actions) = tokio::try_join!(
-        VersionDownload::belonging_to(latest_five)
-            .filter(version_downloads::date.gt(date(now - 90.days())))
-            .select(VersionDownload::as_select())
-            .order((
-                version_downloads::date.asc(),
-                version_downloads::version_id.desc(),
-            ))
-            .load(&mut conn)
-            .boxed(),
-        VersionDownload::belonging_to(rest)
-            .select((
-                to_char(version_downloads::date, "YYYY-MM-DD"),
-                sum_downloads,
-            ))
-            .filter(version_downloads::date.gt(date(now - 90.days())))
-            .group_by(version_downloads::date)
-            .order(version_downloads::date.asc())
-            .load::<ExtraDownload>(&mut conn)
-            .boxed(),
-        load_versions_and_publishers(&mut conn, latest_five, include.versions),
-        load_actions(&mut conn, latest_five, include.versions),
-    )?;
+    let (downloads, extra_downloads, versions_and_publishers, actions, cargo_versions, platforms) =
+        tokio::try_join!(
+            VersionDownload::belonging_to(latest_five)
+                .filter(version_downloads::date.gt(date(now - 90.days())))
+                .select(VersionDownload::as_select())
+                .order((
+                    version_downloads::date.asc(),
+                    version_downloads::version_id.desc(),
+                ))
+                .load(&mut conn)
+                .boxed(),
+            VersionDownload::belonging_to(rest)
+                .select((
+                    to_char(version_downloads::date, "YYYY-MM-DD"),
+                    sum_downloads,
+                ))
+                .filter(version_downloads::date.gt(date(now - 90.days())))
+                .group_by(version_downloads::date)
+                .order(version_downloads::date.asc())
+                .load::<ExtraDownload>(&mut conn)
+                .boxed(),
+            load_versions_and_publishers(&mut conn, latest_five, include.versions),
+            load_actions(&mut conn, latest_five, include.versions),
+            load_cargo_version_downloads(&mut conn, crate_id, include.cargo_versions),
+            load_platform_downloads(&mut conn, crate_id, include.platforms),
+        )?;
 
     let version_downloads = downloads
         .into_iter()
@@ -157,6 +304,10 @@ pub async fn get_crate_downloads(
     Ok(Json(DownloadsResponse {
         version_downloads,
         versions,
+        cargo_version_downloads: include.cargo_versions.then_some(cargo_versions),
+        platform_downloads: include
+            .platforms
+            .then(|| PlatformDownloads::from_rows(platforms)),
         meta: DownloadsMeta { extra_downloads },
     }))
 }
@@ -193,24 +344,86 @@ fn load_actions<'a>(
         .boxed()
 }
 
+fn load_cargo_version_downloads<'a>(
+    conn: &mut AsyncPgConnection,
+    crate_id: i32,
+    includes: bool,
+) -> BoxFuture<'a, QueryResult<Vec<CargoVersionDownload>>> {
+    use diesel::dsl::*;
+
+    if !includes {
+        return futures_util::future::always_ready(|| Ok(vec![])).boxed();
+    }
+    cargo_version_downloads::table
+        .filter(cargo_version_downloads::crate_id.eq(crate_id))
+        .filter(cargo_version_downloads::date.gt(date(now - 90.days())))
+        .select((
+            to_char(cargo_version_downloads::date, "YYYY-MM-DD"),
+            cargo_version_downloads::cargo_version,
+            cargo_version_downloads::downloads,
+        ))
+        .order((
+            cargo_version_downloads::date.asc(),
+            cargo_version_downloads::downloads.desc(),
+            cargo_version_downloads::cargo_version.asc(),
+        ))
+        .load(conn)
+        .boxed()
+}
+
+type PlatformDownloadRow = (String, String, String, i64);
+fn load_platform_downloads<'a>(
+    conn: &mut AsyncPgConnection,
+    crate_id: i32,
+    includes: bool,
+) -> BoxFuture<'a, QueryResult<Vec<PlatformDownloadRow>>> {
+    use diesel::dsl::*;
+    use diesel::sql_types::BigInt;
+
+    if !includes {
+        return futures_util::future::always_ready(|| Ok(vec![])).boxed();
+    }
+    crate_platform_downloads::table
+        .filter(crate_platform_downloads::crate_id.eq(crate_id))
+        .filter(crate_platform_downloads::date.gt(date(now - 90.days())))
+        .group_by((
+            crate_platform_downloads::host_os,
+            crate_platform_downloads::host_arch,
+            crate_platform_downloads::cdn_region,
+        ))
+        .select((
+            crate_platform_downloads::host_os,
+            crate_platform_downloads::host_arch,
+            crate_platform_downloads::cdn_region,
+            sql::<BigInt>("SUM(crate_platform_downloads.downloads)::bigint"),
+        ))
+        .load(conn)
+        .boxed()
+}
+
 #[derive(Debug, Default)]
 struct ShowIncludeMode {
     versions: bool,
+    cargo_versions: bool,
+    platforms: bool,
 }
 
 impl ShowIncludeMode {
-    const INVALID_COMPONENT: &'static str = "invalid component for ?include= (expected 'versions')";
+    const INVALID_COMPONENT: &'static str =
+        "invalid component for ?include= (expected 'versions', 'cargo_versions' or 'platforms')";
 }
 
 impl FromStr for ShowIncludeMode {
     type Err = BoxedAppError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut mode = Self { versions: false };
+        let mut mode = Self::default();
         for component in s.split(',') {
             match component {
                 "" => {}
                 "versions" => mode.versions = true,
+                "cargo_versions" => mode.cargo_versions = true,
+                "platforms" => mode.platforms = true,
                 _ => return Err(bad_request(Self::INVALID_COMPONENT)),
             }
         }
@@ -5,12 +5,14 @@ use crate::controllers::krate::CratePath;
 use crate::models::krate::OwnerRemoveError;
 use crate::models::{Crate, Owner, Team, User};
 use crate::models::{
-    CrateOwner, NewCrateOwnerInvitation, NewCrateOwnerInvitationOutcome, NewTeam,
-    krate::NewOwnerInvite, token::EndpointScope,
+    CrateAction, CrateOwner, NewCrateAuditAction, NewCrateOwnerInvitation,
+    NewCrateOwnerInvitationOutcome, NewTeam, WebhookEvent, krate::NewOwnerInvite,
+    token::EndpointScope,
 };
 use crate::util::errors::{AppResult, BoxedAppError, bad_request, crate_not_found, custom};
 use crate::util::gh_token_encryption::GitHubTokenEncryption;
 use crate::views::EncodableOwner;
+use crate::worker::jobs::webhooks;
 use crate::{App, app::AppState};
 use crate::{auth::AuthCheck, email::EmailMessage};
 use axum::Json;
@@ -25,6 +27,7 @@ use minijinja::context;
 use oauth2::AccessToken;
 use secrecy::ExposeSecret;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use thiserror::Error;
 use tracing::warn;
 
@@ -194,6 +197,7 @@ async fn modify_owners(
         .await?;
 
     let user = auth.user();
+    let api_token_id = auth.api_token_id();
 
     let (msg, emails) = conn
         .transaction(|conn| {
@@ -243,6 +247,22 @@ async fn modify_owners(
                             // to email them the invite token for one-click
                             // acceptance.
                             Ok(NewOwnerInvite::User(invitee, token)) => {
+                                NewCrateAuditAction::builder()
+                                    .crate_id(krate.id)
+                                    .crate_name(&krate.name)
+                                    .user_id(user.id)
+                                    .maybe_api_token_id(api_token_id)
+                                    .action(CrateAction::OwnerInvite)
+                                    .details(json!({ "owner": invitee.gh_login }))
+                                    .build()
+                                    .insert(conn)
+                                    .await?;
+
+                                let data = json!({ "action": "invite", "owner": invitee.gh_login });
+                                let event = WebhookEvent::OwnerChange;
+                                webhooks::enqueue_deliveries(conn, krate.id, &krate.name, event, data)
+                                    .await?;
+
                                 msgs.push(format!(
                                     "user {} has been invited to be an owner of crate {}",
                                     invitee.gh_login, krate.name,
@@ -270,10 +290,28 @@ async fn modify_owners(
 
                             // A team was successfully invited. They are immediately
                             // added, and do not have an invite token.
-                            Ok(NewOwnerInvite::Team(team)) => msgs.push(format!(
-                                "team {} has been added as an owner of crate {}",
-                                team.login, krate.name
-                            )),
+                            Ok(NewOwnerInvite::Team(team)) => {
+                                NewCrateAuditAction::builder()
+                                    .crate_id(krate.id)
+                                    .crate_name(&krate.name)
+                                    .user_id(user.id)
+                                    .maybe_api_token_id(api_token_id)
+                                    .action(CrateAction::OwnerAdd)
+                                    .details(json!({ "owner": team.login }))
+                                    .build()
+                                    .insert(conn)
+                                    .await?;
+
+                                let data = json!({ "action": "add", "owner": team.login });
+                                let event = WebhookEvent::OwnerChange;
+                                webhooks::enqueue_deliveries(conn, krate.id, &krate.name, event, data)
+                                    .await?;
+
+                                msgs.push(format!(
+                                    "team {} has been added as an owner of crate {}",
+                                    team.login, krate.name
+                                ))
+                            }
 
                             // This user has a pending invite.
                             Err(OwnerAddError::AlreadyInvited(user)) => msgs.push(format!(
@@ -290,6 +328,22 @@ async fn modify_owners(
                 } else {
                     for login in &logins {
                         krate.owner_remove(conn, login).await?;
+
+                        NewCrateAuditAction::builder()
+                            .crate_id(krate.id)
+                            .crate_name(&krate.name)
+                            .user_id(user.id)
+                            .maybe_api_token_id(api_token_id)
+                            .action(CrateAction::OwnerRemove)
+                            .details(json!({ "owner": login }))
+                            .build()
+                            .insert(conn)
+                            .await?;
+
+                        let data = json!({ "action": "remove", "owner": login });
+                        let event = WebhookEvent::OwnerChange;
+                        webhooks::enqueue_deliveries(conn, krate.id, &krate.name, event, data)
+                            .await?;
                     }
                     if User::owning(&krate, conn).await?.is_empty() {
                         return Err(bad_request(
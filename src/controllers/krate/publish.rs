@@ -2,13 +2,16 @@
 
 use crate::app::AppState;
 use crate::auth::{AuthCheck, AuthHeader, Authentication};
+use crate::controllers::version::CrateVersionPath;
 use crate::worker::jobs::{
     self, AnalyzeCrateFile, CheckTyposquat, GenerateOgImage, SendPublishNotificationsJob,
     UpdateDefaultVersion,
 };
 use axum::Json;
 use axum::body::{Body, Bytes};
-use cargo_manifest::{Dependency, DepsSet, TargetDepsSet};
+use axum::extract::{FromRequestParts, Query};
+use axum::response::{IntoResponse, Response};
+use cargo_manifest::{Dependency, DepsSet, FeatureSet, TargetDepsSet};
 use chrono::{DateTime, SecondsFormat, Utc};
 use crates_io_tarball::{TarballError, process_tarball};
 use crates_io_validation::{
@@ -23,19 +26,22 @@ use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 use futures_util::TryFutureExt;
 use futures_util::TryStreamExt;
 use hex::ToHex;
-use http::StatusCode;
 use http::request::Parts;
+use http::{StatusCode, header};
 use secrecy::ExposeSecret;
+use serde::Deserialize;
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio_util::io::StreamReader;
 use tracing::{error, instrument};
 use url::Url;
+use utoipa::IntoParams;
 
 use crate::models::{
-    Category, Crate, DependencyKind, Keyword, NewCrate, NewVersion, NewVersionOwnerAction,
-    VersionAction, default_versions::Version as DefaultVersion,
+    Category, Crate, DependencyKind, Keyword, NewCrate, NewStagedVersion, NewVersion,
+    NewVersionOwnerAction, StagedVersion, TopVersions, VersionAction, WebhookEvent,
+    default_versions::Version as DefaultVersion,
 };
 
 use crate::controllers::helpers::authorization::Rights;
@@ -44,6 +50,7 @@ use crate::middleware::log_request::RequestLogExt;
 use crate::models::token::EndpointScope;
 use crate::rate_limiter::LimitedAction;
 use crate::schema::*;
+use crate::storage::CACHE_CONTROL_PRIVATE;
 use crate::util::errors::{AppResult, BoxedAppError, bad_request, custom, forbidden, internal};
 use crate::views::{
     EncodableCrate, EncodableCrateDependency, GoodCrate, PublishMetadata, PublishWarnings,
@@ -57,6 +64,14 @@ const MISSING_RIGHTS_ERROR_MESSAGE: &str = "this crate exists but you don't seem
      to accept an invitation to be an owner before \
      publishing.";
 
+const STAGED_NAME_RESERVED_ERROR_MESSAGE: &str =
+    "this crate name is reserved by a version that has been staged by another user";
+
+const STAGED_BY_OTHER_USER_ERROR_MESSAGE: &str =
+    "this crate has not been published yet and the version was staged by another user";
+
+const TRUSTPUB_ONLY_ERROR_MESSAGE: &str = "New versions of this crate can only be published using Trusted Publishing (see https://crates.io/docs/trusted-publishing).";
+
 const MAX_DESCRIPTION_LENGTH: usize = 1000;
 
 enum AuthType {
@@ -76,6 +91,13 @@ impl AuthType {
         self.user().map(|u| u.id)
     }
 
+    fn team_id(&self) -> Option<i32> {
+        match self {
+            AuthType::Regular(auth) => auth.team_id(),
+            AuthType::TrustPub(_) => None,
+        }
+    }
+
     fn trustpub_data(&self) -> Option<&TrustpubData> {
         match self {
             AuthType::Regular(_) => None,
@@ -84,6 +106,25 @@ impl AuthType {
     }
 }
 
+#[derive(Debug, Deserialize, FromRequestParts, IntoParams)]
+#[from_request(via(Query))]
+#[into_params(parameter_in = Query)]
+pub struct PublishQueryParams {
+    /// Upload the version in "staged" mode.
+    ///
+    /// Staged versions are not visible in the index or on the website and
+    /// only become public once they are released via the
+    /// `PUT /api/v1/crates/{name}/{version}/release` endpoint. Staged
+    /// versions that are not released within seven days are deleted.
+    ///
+    /// For crates that have not been published yet, the crate is only created
+    /// once the version is released. Until then, the crate name is reserved
+    /// for the user that staged the version.
+    #[serde(default)]
+    #[param(default = false)]
+    staged: bool,
+}
+
 /// Publish a new crate/version.
 ///
 /// Used by `cargo publish` to publish a new crate or to publish a new version of an
@@ -91,6 +132,7 @@ impl AuthType {
 #[utoipa::path(
     put,
     path = "/api/v1/crates/new",
+    params(PublishQueryParams),
     security(
         ("api_token" = []),
         ("trustpub_token" = []),
@@ -99,7 +141,20 @@ impl AuthType {
     tag = "publish",
     responses((status = 200, description = "Successful Response", body = inline(GoodCrate))),
 )]
-pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<GoodCrate>> {
+pub async fn publish(
+    app: AppState,
+    params: PublishQueryParams,
+    req: Parts,
+    body: Body,
+) -> AppResult<Json<GoodCrate>> {
+    // Staged crate files are kept in a separate private store, which is
+    // optional for deployments that don't need staged publishing.
+    if params.staged && !app.storage.supports_staged_versions() {
+        return Err(bad_request(
+            "Staged publishing is not available, since no private file storage is configured",
+        ));
+    }
+
     let stream = body.into_data_stream();
     let stream = stream.map_err(std::io::Error::other);
     let mut reader = StreamReader::new(stream);
@@ -223,9 +278,7 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
         && existing_crate.trustpub_only
         && matches!(auth, AuthType::Regular(_))
     {
-        return Err(forbidden(
-            "New versions of this crate can only be published using Trusted Publishing (see https://crates.io/docs/trusted-publishing).",
-        ));
+        return Err(forbidden(TRUSTPUB_ONLY_ERROR_MESSAGE));
     }
 
     let verified_email_address = if let Some(user) = auth.user() {
@@ -247,15 +300,105 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
             .await?;
     }
 
-    let max_upload_size = existing_crate
-        .as_ref()
+    let max_upload_size = max_upload_size(&app, existing_crate.as_ref());
+    let tarball_bytes = read_tarball_bytes(&mut reader, max_upload_size).await?;
+
+    let version = validate_version(
+        &app,
+        metadata,
+        semver,
+        tarball_bytes,
+        existing_crate.as_ref(),
+    )
+    .await?;
+
+    let publisher = Publisher {
+        user_id: auth.user_id(),
+        team_id: auth.team_id(),
+        trustpub_data: auth.trustpub_data().cloned(),
+        email_address: verified_email_address,
+    };
+
+    if params.staged {
+        return conn
+            .transaction(|conn| {
+                async move {
+                    stage_version(&app, conn, &auth, publisher, existing_crate, version).await
+                }
+                .scope_boxed()
+            })
+            .await;
+    }
+
+    let is_new_crate = existing_crate.is_none();
+
+    // Create a transaction on the database, if there are no errors,
+    // commit the transactions to record a new or updated crate.
+    let crate_name = version.metadata.name.clone();
+    let response = conn
+        .transaction(|conn| {
+            async { persist_version(&app, conn, &auth, publisher, is_new_crate, version).await }
+                .scope_boxed()
+        })
+        .await?;
+
+    app.sparse_index_cache.invalidate(&crate_name).await;
+
+    Ok(response)
+}
+
+/// A crate version that has been read from a `.crate` file and passed all
+/// validations, but has not been persisted yet.
+struct ValidatedVersion {
+    metadata: PublishMetadata,
+    semver: semver::Version,
+    version_string: String,
+    tarball_bytes: Bytes,
+    description: Option<String>,
+    license: Option<String>,
+    homepage: Option<String>,
+    documentation: Option<String>,
+    repository: Option<String>,
+    rust_version: Option<String>,
+    edition: Option<&'static str>,
+    links: Option<String>,
+    has_lib: bool,
+    bin_names: Vec<String>,
+    keywords: Vec<String>,
+    categories: Vec<String>,
+    features: FeatureSet,
+    deps: Vec<EncodableCrateDependency>,
+    path_in_vcs: Option<String>,
+}
+
+/// The user, Trusted Publishing workflow and email address that a new
+/// version is attributed to.
+struct Publisher {
+    user_id: Option<i32>,
+    team_id: Option<i32>,
+    trustpub_data: Option<TrustpubData>,
+    email_address: Option<String>,
+}
+
+fn max_upload_size(app: &AppState, existing_crate: Option<&Crate>) -> u32 {
+    existing_crate
         .and_then(|c| c.max_upload_size())
-        .unwrap_or(app.config.max_upload_size);
+        .unwrap_or(app.config.max_upload_size)
+}
 
-    let tarball_bytes = read_tarball_bytes(&mut reader, max_upload_size).await?;
-    let content_length = tarball_bytes.len() as u64;
+/// Processes the `.crate` file and validates its manifest against the
+/// publish metadata and the crates.io limits.
+async fn validate_version(
+    app: &AppState,
+    metadata: PublishMetadata,
+    semver: semver::Version,
+    tarball_bytes: Bytes,
+    existing_crate: Option<&Crate>,
+) -> AppResult<ValidatedVersion> {
+    let version_string = semver.to_string();
 
     let pkg_name = format!("{}-{}", &*metadata.name, &version_string);
+    let max_upload_size = max_upload_size(app, existing_crate);
     let max_unpack_size = std::cmp::max(app.config.max_unpack_size, max_upload_size as u64);
     let tarball_info = process_tarball(&pkg_name, &*tarball_bytes, max_unpack_size).await?;
 
@@ -370,7 +513,6 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
     }
 
     let max_features = existing_crate
-        .as_ref()
         .and_then(|c| c.max_features.map(|mf| mf as usize))
         .unwrap_or(app.config.max_features);
 
@@ -434,279 +576,813 @@ pub async fn publish(app: AppState, req: Parts, body: Body) -> AppResult<Json<Go
         validate_dependency(dep)?;
     }
 
-    // Create a transaction on the database, if there are no errors,
-    // commit the transactions to record a new or updated crate.
-    conn.transaction(|conn| async move {
-        let name = metadata.name;
-        let keywords = keywords.iter().map(|s| s.as_str()).collect::<Vec<_>>();
-        let categories = categories.iter().map(|s| s.as_str()).collect::<Vec<_>>();
-
-        // Persist the new crate, if it doesn't already exist
-        let persist = NewCrate {
-            name: &name,
-            description: description.as_deref(),
-            homepage: homepage.as_deref(),
-            documentation: documentation.as_deref(),
-            readme: metadata.readme.as_deref(),
-            repository: repository.as_deref(),
-            max_upload_size: None,
-            max_features: None,
+    // https://doc.rust-lang.org/cargo/reference/cargo-targets.html#the-name-field says that
+    // the `name` field is required for `bin` targets, so we can ignore `None` values via
+    // `filter_map()` here.
+    let bin_names = tarball_info
+        .manifest
+        .bin
+        .iter()
+        .filter_map(|bin| bin.name.clone())
+        .collect::<Vec<_>>();
+
+    Ok(ValidatedVersion {
+        metadata,
+        semver,
+        version_string,
+        tarball_bytes,
+        description,
+        license,
+        homepage,
+        documentation,
+        repository,
+        rust_version,
+        edition: edition.map(|edition| edition.as_str()),
+        links: package.links,
+        has_lib: tarball_info.manifest.lib.is_some(),
+        bin_names,
+        keywords,
+        categories,
+        features,
+        deps,
+        path_in_vcs: tarball_info.vcs_info.map(|info| info.path_in_vcs),
+    })
+}
+
+/// Persists a validated version, uploads the crate file and enqueues the
+/// index, notification and feed jobs.
+///
+/// This is expected to be called inside of a database transaction.
+async fn persist_version(
+    app: &AppState,
+    conn: &mut AsyncPgConnection,
+    auth: &AuthType,
+    publisher: Publisher,
+    is_new_crate: bool,
+    version: ValidatedVersion,
+) -> AppResult<Json<GoodCrate>> {
+    let ValidatedVersion {
+        metadata,
+        semver,
+        version_string,
+        tarball_bytes,
+        description,
+        license,
+        homepage,
+        documentation,
+        repository,
+        rust_version,
+        edition,
+        links,
+        has_lib,
+        bin_names,
+        keywords,
+        categories,
+        features,
+        deps,
+        path_in_vcs,
+    } = version;
+
+    let name = metadata.name;
+    let keywords = keywords.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+    let categories = categories.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+    let bin_names = bin_names.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+
+    // Persist the new crate, if it doesn't already exist
+    let persist = NewCrate {
+        name: &name,
+        description: description.as_deref(),
+        homepage: homepage.as_deref(),
+        documentation: documentation.as_deref(),
+        readme: metadata.readme.as_deref(),
+        repository: repository.as_deref(),
+        max_upload_size: None,
+        max_features: None,
+    };
+
+    if is_reserved_name(persist.name, conn).await? {
+        return Err(bad_request("cannot upload a crate with a reserved name"));
+    }
+
+    if is_new_crate
+        && let Some(user) = auth.user()
+        && StagedVersion::is_name_reserved_for_others(conn, persist.name, user.id).await?
+    {
+        return Err(forbidden(STAGED_NAME_RESERVED_ERROR_MESSAGE));
+    }
+
+    let krate = if let Some(user) = auth.user() {
+        // To avoid race conditions, we try to insert
+        // first so we know whether to add an owner
+        let krate = match persist.create(conn, user.id).await.optional()? {
+            Some(krate) => krate,
+            None => persist.update(conn).await?,
         };
 
-        if is_reserved_name(persist.name, conn).await? {
-            return Err(bad_request("cannot upload a crate with a reserved name"));
+        let owners = krate.owners(conn).await?;
+        if Rights::get(user, &*app.github, &owners, &app.config.gh_token_encryption).await?
+            < Rights::Publish
+        {
+            return Err(custom(StatusCode::FORBIDDEN, MISSING_RIGHTS_ERROR_MESSAGE));
         }
 
-        let krate = if let Some(user) = auth.user() {
-            // To avoid race conditions, we try to insert
-            // first so we know whether to add an owner
-            let krate = match persist.create(conn, user.id).await.optional()? {
-                Some(krate) => krate,
-                None => persist.update(conn).await?,
-            };
+        krate
+    } else {
+        // Trusted Publishing does not support creating new crates
+        persist.update(conn).await?
+    };
 
-            let owners = krate.owners(conn).await?;
-            if Rights::get(user, &*app.github, &owners, &app.config.gh_token_encryption).await? < Rights::Publish {
-                return Err(custom(StatusCode::FORBIDDEN, MISSING_RIGHTS_ERROR_MESSAGE));
-            }
+    if krate.name != *name {
+        return Err(bad_request(format_args!(
+            "crate was previously named `{}`",
+            krate.name
+        )));
+    }
 
-            krate
-        } else {
-            // Trusted Publishing does not support creating new crates
-            persist.update(conn).await?
-        };
+    // Versions of this crate that were staged before the crate existed
+    // now belong to the new `crates` row.
+    if is_new_crate {
+        StagedVersion::assign_to_crate(conn, &krate.name, krate.id).await?;
+    }
 
-        if krate.name != *name {
-            return Err(bad_request(format_args!(
-                "crate was previously named `{}`",
-                krate.name
-            )));
+    if let Some(daily_version_limit) = app.config.new_version_rate_limit {
+        let published_today = count_versions_published_today(krate.id, conn).await?;
+        if published_today >= daily_version_limit as i64 {
+            return Err(custom(
+                StatusCode::TOO_MANY_REQUESTS,
+                "You have published too many versions of this crate in the last 24 hours",
+            ));
         }
+    }
 
-        if let Some(daily_version_limit) = app.config.new_version_rate_limit {
-            let published_today = count_versions_published_today(krate.id, conn).await?;
-            if published_today >= daily_version_limit as i64 {
-                return Err(custom(
-                    StatusCode::TOO_MANY_REQUESTS,
-                    "You have published too many versions of this crate in the last 24 hours",
-                ));
+    // Read tarball from request
+    let hex_cksum: String = Sha256::digest(&tarball_bytes).encode_hex();
+
+    // Persist the new version of this crate
+    let new_version = NewVersion::builder(krate.id, &version_string)
+        .features(serde_json::to_value(&features)?)
+        .maybe_license(license.as_deref())
+        // Downcast is okay because the file length must be less than the max upload size
+        // to get here, and max upload sizes are way less than i32 max
+        .size(tarball_bytes.len() as i32)
+        .maybe_published_by(publisher.user_id)
+        .checksum(&hex_cksum)
+        .maybe_links(links.as_deref())
+        .maybe_rust_version(rust_version.as_deref())
+        .has_lib(has_lib)
+        .bin_names(bin_names.as_slice())
+        .maybe_edition(edition)
+        .maybe_description(description.as_deref())
+        .maybe_homepage(homepage.as_deref())
+        .maybe_documentation(documentation.as_deref())
+        .maybe_repository(repository.as_deref())
+        .categories(&categories)
+        .keywords(&keywords)
+        .maybe_trustpub_data(publisher.trustpub_data.as_ref())
+        .build();
+
+    let version = new_version.save(conn).await.map_err(|error| {
+        use diesel::result::{DatabaseErrorKind, Error};
+        match error {
+            Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                duplicate_version_error(new_version.num_no_build)
             }
+            error => error.into(),
         }
+    })?;
 
-        // https://doc.rust-lang.org/cargo/reference/cargo-targets.html#the-name-field says that
-        // the `name` field is required for `bin` targets, so we can ignore `None` values via
-        // `filter_map()` here.
-        let bin_names = tarball_info.manifest.bin
-            .iter()
-            .filter_map(|bin| bin.name.as_deref())
-            .collect::<Vec<_>>();
-
-        let edition = edition.map(|edition| edition.as_str());
-
-        // Read tarball from request
-        let hex_cksum: String = Sha256::digest(&tarball_bytes).encode_hex();
-
-        // Persist the new version of this crate
-        let new_version = NewVersion::builder(krate.id, &version_string)
-            .features(serde_json::to_value(&features)?)
-            .maybe_license(license.as_deref())
-            // Downcast is okay because the file length must be less than the max upload size
-            // to get here, and max upload sizes are way less than i32 max
-            .size(content_length as i32)
-            .maybe_published_by(auth.user_id())
-            .checksum(&hex_cksum)
-            .maybe_links(package.links.as_deref())
-            .maybe_rust_version(rust_version.as_deref())
-            .has_lib(tarball_info.manifest.lib.is_some())
-            .bin_names(bin_names.as_slice())
-            .maybe_edition(edition)
-            .maybe_description(description.as_deref())
-            .maybe_homepage(homepage.as_deref())
-            .maybe_documentation(documentation.as_deref())
-            .maybe_repository(repository.as_deref())
-            .categories(&categories)
-            .keywords(&keywords)
-            .maybe_trustpub_data(auth.trustpub_data())
-            .build();
-
-        let version = new_version.save(conn).await.map_err(|error| {
-            use diesel::result::{Error, DatabaseErrorKind};
-            match error {
-                Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) =>
-                    duplicate_version_error(new_version.num_no_build),
-                error => error.into(),
-            }
-        })?;
+    if let Some(email_address) = publisher.email_address {
+        versions_published_by::insert(version.id, &email_address, publisher.team_id, conn).await?;
+    }
 
-        if let Some(email_address) = verified_email_address {
-            versions_published_by::insert(version.id, &email_address, conn).await?;
-        }
+    if let AuthType::Regular(auth) = auth {
+        NewVersionOwnerAction::builder()
+            .version_id(version.id)
+            .user_id(auth.user().id)
+            .maybe_api_token_id(auth.api_token_id())
+            .maybe_team_id(auth.team_id())
+            .action(VersionAction::Publish)
+            .build()
+            .insert(conn)
+            .await?;
+    }
+
+    // Link this new version to all dependencies
+    add_dependencies(conn, &deps, version.id).await?;
+
+    let existing_default_version = default_versions::table
+        .inner_join(versions::table)
+        .filter(default_versions::crate_id.eq(krate.id))
+        .select((DefaultVersion::as_select(), default_versions::num_versions))
+        .first::<(DefaultVersion, Option<i32>)>(conn)
+        .await
+        .optional()?;
+
+    let num_versions = existing_default_version
+        .as_ref()
+        .and_then(|t| t.1)
+        .unwrap_or_default();
+    let mut default_version = None;
+    // Upsert the `default_value` determined by the existing `default_value` and the
+    // published version. Note that this could potentially write an outdated version
+    // (although this should not happen regularly), as we might be comparing to an
+    // outdated value. The initial record will be handled by the trigger function.
+    //
+    // Compared to only using a background job, this prevents us from getting into a
+    // situation where a crate exists in the `crates` table but doesn't have a default
+    // version in the `default_versions` table.
+    if let Some((existing_default_version, _)) = &existing_default_version {
+        let published_default_version = DefaultVersion {
+            id: version.id,
+            num: semver,
+            yanked: false,
+        };
 
-        if let AuthType::Regular(auth) = &auth {
-            NewVersionOwnerAction::builder()
-                .version_id(version.id)
-                .user_id(auth.user().id)
-                .maybe_api_token_id(auth.api_token_id())
-                .action(VersionAction::Publish)
-                .build()
-                .insert(conn)
+        if existing_default_version < &published_default_version {
+            diesel::update(default_versions::table)
+                .filter(default_versions::crate_id.eq(krate.id))
+                .set(default_versions::version_id.eq(version.id))
+                .execute(conn)
                 .await?;
+        } else {
+            default_version = Some(existing_default_version.num.to_string());
         }
 
-        // Link this new version to all dependencies
-        add_dependencies(conn, &deps, version.id).await?;
-
-        let existing_default_version = default_versions::table
-            .inner_join(versions::table)
-            .filter(default_versions::crate_id.eq(krate.id))
-            .select((DefaultVersion::as_select(), default_versions::num_versions))
-            .first::<(DefaultVersion, Option<i32>)>(conn)
-            .await
-            .optional()?;
-
-        let num_versions = existing_default_version.as_ref().and_then(|t| t.1).unwrap_or_default();
-        let mut default_version = None;
-        // Upsert the `default_value` determined by the existing `default_value` and the
-        // published version. Note that this could potentially write an outdated version
-        // (although this should not happen regularly), as we might be comparing to an
-        // outdated value. The initial record will be handled by the trigger function.
-        //
-        // Compared to only using a background job, this prevents us from getting into a
-        // situation where a crate exists in the `crates` table but doesn't have a default
-        // version in the `default_versions` table.
-        if let Some((existing_default_version, _)) = &existing_default_version {
-            let published_default_version = DefaultVersion {
-                id: version.id,
-                num: semver,
-                yanked: false,
-            };
+        // Update the default version asynchronously in a background job
+        // to ensure correctness and eventual consistency.
+        UpdateDefaultVersion::new(krate.id).enqueue(conn).await?;
+    }
 
-            if existing_default_version < &published_default_version {
-                diesel::update(default_versions::table)
-                    .filter(default_versions::crate_id.eq(krate.id))
-                    .set(default_versions::version_id.eq(version.id))
-                    .execute(conn)
-                    .await?;
-            } else {
-                default_version = Some(existing_default_version.num.to_string());
-            }
+    // Update all keywords for this crate
+    Keyword::update_crate(conn, krate.id, &keywords).await?;
 
-            // Update the default version asynchronously in a background job
-            // to ensure correctness and eventual consistency.
-            UpdateDefaultVersion::new(krate.id).enqueue(conn).await?;
-        }
+    // Update all categories for this crate, collecting any invalid categories
+    // in order to be able to return an error to the user.
+    let unknown_categories = Category::update_crate(conn, krate.id, &categories).await?;
+    if !unknown_categories.is_empty() {
+        let unknown_categories = unknown_categories.join(", ");
+        let domain = &app.config.domain_name;
+        return Err(bad_request(format!(
+            "The following category slugs are not currently supported on crates.io: {unknown_categories}\n\nSee https://{domain}/category_slugs for a list of supported slugs."
+        )));
+    }
 
-        // Update all keywords for this crate
-        Keyword::update_crate(conn, krate.id, &keywords).await?;
+    let top_versions = krate.top_versions(conn).await?;
 
-        // Update all categories for this crate, collecting any invalid categories
-        // in order to be able to return an error to the user.
-        let unknown_categories = Category::update_crate(conn, krate.id, &categories).await?;
-        if !unknown_categories.is_empty() {
-            let unknown_categories = unknown_categories.join(", ");
-            let domain = &app.config.domain_name;
-            return Err(bad_request(format!("The following category slugs are not currently supported on crates.io: {unknown_categories}\n\nSee https://{domain}/category_slugs for a list of supported slugs.")));
-        }
+    let downloads: i64 = crate_downloads::table
+        .select(crate_downloads::downloads)
+        .filter(crate_downloads::crate_id.eq(krate.id))
+        .first(conn)
+        .await?;
 
-        let top_versions = krate.top_versions(conn).await?;
+    if let Some(readme) = metadata.readme
+        && !readme.is_empty()
+    {
+        jobs::RenderAndUploadReadme::new(
+            version.id,
+            readme,
+            metadata
+                .readme_file
+                .unwrap_or_else(|| String::from("README.md")),
+            repository,
+            path_in_vcs,
+        )
+        .enqueue(conn)
+        .await?;
+    }
 
-        let downloads: i64 = crate_downloads::table.select(crate_downloads::downloads)
-            .filter(crate_downloads::crate_id.eq(krate.id))
-            .first(conn)
-            .await?;
+    // Upload crate tarball
+    app.storage
+        .upload_crate_file(&krate.name, &version_string, tarball_bytes)
+        .await
+        .map_err(|e| internal(format!("failed to upload crate: {e}")))?;
+
+    let git_index_enabled = app.config.git_index_enabled;
+    jobs::enqueue_sync_to_index(&krate.name, git_index_enabled, conn).await?;
+
+    let publish_notifications_job = SendPublishNotificationsJob::new(version.id);
+    let crate_feed_job = jobs::rss::SyncCrateFeed::new(krate.name.clone());
+    let updates_feed_job = jobs::rss::SyncUpdatesFeed;
+    let analyze_crate_file_job = AnalyzeCrateFile::new(version.id);
+
+    tokio::try_join!(
+        publish_notifications_job.enqueue(conn),
+        crate_feed_job.enqueue(conn).or_else(async |error| {
+            error!("Failed to enqueue `rss::SyncCrateFeed` job: {error}");
+            Ok::<_, EnqueueError>(None)
+        }),
+        updates_feed_job.enqueue(conn).or_else(async |error| {
+            error!("Failed to enqueue `rss::SyncUpdatesFeed` job: {error}");
+            Ok::<_, EnqueueError>(None)
+        }),
+        analyze_crate_file_job.enqueue(conn).or_else(async |error| {
+            error!("Failed to enqueue `AnalyzeCrateFile` job: {error}");
+            Ok::<_, EnqueueError>(None)
+        }),
+    )?;
+
+    let data = serde_json::json!({ "version": version_string });
+    jobs::webhooks::enqueue_deliveries(conn, krate.id, &krate.name, WebhookEvent::Publish, data)
+        .await?;
 
-        let pkg_path_in_vcs = tarball_info.vcs_info.map(|info| info.path_in_vcs);
-
-        if let Some(readme) = metadata.readme && !readme.is_empty() {
-            jobs::RenderAndUploadReadme::new(
-                version.id,
-                readme,
-                metadata
-                    .readme_file
-                    .unwrap_or_else(|| String::from("README.md")),
-                repository,
-                pkg_path_in_vcs,
-            ).enqueue(conn).await?;
+    // Enqueue OG image generation job if not handled by UpdateDefaultVersion
+    if existing_default_version.is_none() {
+        let og_image_job = GenerateOgImage::new(krate.name.clone());
+        if let Err(error) = og_image_job.enqueue(conn).await {
+            error!("Failed to enqueue `GenerateOgImage` job: {error}");
         }
+    };
 
-        // Upload crate tarball
-        app.storage.upload_crate_file(&krate.name, &version_string, tarball_bytes)
-            .await
-            .map_err(|e| internal(format!("failed to upload crate: {e}")))?;
-
-        let git_index_job = jobs::SyncToGitIndex::new(&krate.name);
-        let sparse_index_job = jobs::SyncToSparseIndex::new(&krate.name);
-        let publish_notifications_job = SendPublishNotificationsJob::new(version.id);
-        let crate_feed_job = jobs::rss::SyncCrateFeed::new(krate.name.clone());
-        let updates_feed_job = jobs::rss::SyncUpdatesFeed;
-        let analyze_crate_file_job = AnalyzeCrateFile::new(version.id);
+    // Experiment: check new crates for potential typosquatting.
+    if is_new_crate {
+        let crates_feed_job = jobs::rss::SyncCratesFeed;
+        let typosquat_job = CheckTyposquat::new(&krate.name);
 
         tokio::try_join!(
-            git_index_job.enqueue(conn),
-            sparse_index_job.enqueue(conn),
-            publish_notifications_job.enqueue(conn),
-            crate_feed_job.enqueue(conn).or_else(async |error| {
-                error!("Failed to enqueue `rss::SyncCrateFeed` job: {error}");
-                Ok::<_, EnqueueError>(None)
-            }),
-            updates_feed_job.enqueue(conn).or_else(async |error| {
-                error!("Failed to enqueue `rss::SyncUpdatesFeed` job: {error}");
+            crates_feed_job.enqueue(conn).or_else(async |error| {
+                error!("Failed to enqueue `rss::SyncCratesFeed` job: {error}");
                 Ok::<_, EnqueueError>(None)
             }),
-            analyze_crate_file_job.enqueue(conn).or_else(async |error| {
-                error!("Failed to enqueue `AnalyzeCrateFile` job: {error}");
+            typosquat_job.enqueue(conn).or_else(async |error| {
+                error!("Failed to enqueue `CheckTyposquat` job: {error}");
                 Ok::<_, EnqueueError>(None)
             }),
         )?;
+    }
+
+    // The `other` field on `PublishWarnings` was introduced to handle a temporary warning
+    // that is no longer needed. As such, crates.io currently does not return any `other`
+    // warnings for regular publishes, but if we need to, the field is available.
+    let warnings = PublishWarnings {
+        invalid_categories: vec![],
+        invalid_badges: vec![],
+        other: vec![],
+    };
+
+    Ok(Json(GoodCrate {
+        krate: EncodableCrate::from_minimal(
+            krate,
+            default_version.or(Some(version_string)).as_deref(),
+            num_versions,
+            Some(false),
+            Some(&top_versions),
+            false,
+            downloads,
+            None,
+        ),
+        warnings,
+    }))
+}
 
-        // Enqueue OG image generation job if not handled by UpdateDefaultVersion
-        if existing_default_version.is_none() {
-            let og_image_job = GenerateOgImage::new(krate.name.clone());
-            if let Err(error) = og_image_job.enqueue(conn).await {
-                error!("Failed to enqueue `GenerateOgImage` job: {error}");
+/// Stores a validated version as a staged version, without making it
+/// visible in the index or on the website.
+///
+/// If the crate has not been published yet, no `crates` row is created.
+/// Instead, the name is reserved for the user that staged the version until
+/// it is released or deleted.
+///
+/// This is expected to be called inside of a database transaction.
+async fn stage_version(
+    app: &AppState,
+    conn: &mut AsyncPgConnection,
+    auth: &AuthType,
+    publisher: Publisher,
+    existing_crate: Option<Crate>,
+    version: ValidatedVersion,
+) -> AppResult<Json<GoodCrate>> {
+    let metadata = &version.metadata;
+
+    if let Some(krate) = &existing_crate {
+        if let Some(user) = auth.user() {
+            let owners = krate.owners(conn).await?;
+            if Rights::get(user, &*app.github, &owners, &app.config.gh_token_encryption).await?
+                < Rights::Publish
+            {
+                return Err(custom(StatusCode::FORBIDDEN, MISSING_RIGHTS_ERROR_MESSAGE));
             }
-        };
+        }
 
-        // Experiment: check new crates for potential typosquatting.
-        if existing_crate.is_none() {
-            let crates_feed_job = jobs::rss::SyncCratesFeed;
-            let typosquat_job = CheckTyposquat::new(&krate.name);
-
-            tokio::try_join!(
-                crates_feed_job.enqueue(conn).or_else(async |error| {
-                    error!("Failed to enqueue `rss::SyncCratesFeed` job: {error}");
-                    Ok::<_, EnqueueError>(None)
-                }),
-                typosquat_job.enqueue(conn).or_else(async |error| {
-                    error!("Failed to enqueue `CheckTyposquat` job: {error}");
-                    Ok::<_, EnqueueError>(None)
-                }),
-            )?;
+        if krate.name != metadata.name {
+            return Err(bad_request(format_args!(
+                "crate was previously named `{}`",
+                krate.name
+            )));
         }
 
-        // The `other` field on `PublishWarnings` was introduced to handle a temporary warning
-        // that is no longer needed. As such, crates.io currently does not return any `other`
-        // warnings at this time, but if we need to, the field is available.
-        let warnings = PublishWarnings {
-            invalid_categories: vec![],
-            invalid_badges: vec![],
-            other: vec![],
+        let num_no_build = version.version_string.split('+').next().unwrap_or_default();
+        let is_duplicate: bool = select(exists(
+            versions::table
+                .filter(versions::crate_id.eq(krate.id))
+                .filter(versions::num_no_build.eq(num_no_build)),
+        ))
+        .get_result(conn)
+        .await?;
+
+        if is_duplicate {
+            return Err(duplicate_version_error(num_no_build));
+        }
+    } else {
+        if is_reserved_name(&metadata.name, conn).await? {
+            return Err(bad_request("cannot upload a crate with a reserved name"));
+        }
+
+        // Trusted Publishing does not support creating new crates, so there
+        // is always a user here.
+        if let Some(user) = auth.user()
+            && StagedVersion::is_name_reserved_for_others(conn, &metadata.name, user.id).await?
+        {
+            return Err(forbidden(STAGED_NAME_RESERVED_ERROR_MESSAGE));
+        }
+    }
+
+    let crate_name = match &existing_crate {
+        Some(krate) => krate.name.as_str(),
+        None => metadata.name.as_str(),
+    };
+
+    let checksum: String = Sha256::digest(&version.tarball_bytes).encode_hex();
+
+    let new_staged_version = NewStagedVersion {
+        crate_id: existing_crate.as_ref().map(|krate| krate.id),
+        crate_name: existing_crate.is_none().then_some(crate_name),
+        num: &version.version_string,
+        checksum: &checksum,
+        readme: metadata.readme.as_deref(),
+        readme_file: metadata.readme_file.as_deref(),
+        published_by: publisher.user_id,
+        publisher_email: publisher.email_address.as_deref(),
+        trustpub_data: publisher.trustpub_data.as_ref(),
+        team_id: publisher.team_id,
+    };
+
+    new_staged_version.insert(conn).await.map_err(|error| {
+        use diesel::result::{DatabaseErrorKind, Error};
+        match error {
+            Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => bad_request(format!(
+                "crate version `{}` is already staged",
+                version.version_string
+            )),
+            error => error.into(),
+        }
+    })?;
+
+    app.storage
+        .upload_staged_crate_file(
+            crate_name,
+            &version.version_string,
+            &checksum,
+            version.tarball_bytes,
+        )
+        .await
+        .map_err(|e| internal(format!("failed to upload crate: {e}")))?;
+
+    let lifetime_days = jobs::DeleteExpiredStagedVersions::LIFETIME.num_days();
+    let message = format!(
+        "`{name}@{version}` has been staged and is not publicly available yet. \
+            Release it via `PUT /api/v1/crates/{name}/{version}/release` \
+            within the next {lifetime_days} days, otherwise it will be deleted.",
+        name = crate_name,
+        version = version.version_string,
+    );
+
+    let warnings = PublishWarnings {
+        invalid_categories: vec![],
+        invalid_badges: vec![],
+        other: vec![message],
+    };
+
+    let Some(krate) = existing_crate else {
+        // The crate is only created once the version is released, so the
+        // response describes the crate as it will be created then.
+        let created_at = Utc::now();
+        let krate = Crate {
+            id: 0,
+            name: metadata.name.clone(),
+            updated_at: created_at,
+            created_at,
+            description: version.description,
+            homepage: version.homepage,
+            documentation: version.documentation,
+            repository: version.repository,
+            max_upload_size: None,
+            max_features: None,
+            trustpub_only: false,
+            deprecated_at: None,
+            deprecation_reason: None,
+            deprecation_successor: None,
         };
 
-        Ok(Json(GoodCrate {
+        let top_versions = TopVersions::from_versions(vec![]);
+
+        return Ok(Json(GoodCrate {
             krate: EncodableCrate::from_minimal(
                 krate,
-                default_version.or(Some(version_string)).as_deref(),
-                num_versions,
+                None,
+                0,
                 Some(false),
                 Some(&top_versions),
                 false,
-                downloads,
+                0,
                 None,
             ),
             warnings,
-        }))
-    }.scope_boxed()).await
+        }));
+    };
+
+    let existing_default_version = default_versions::table
+        .inner_join(versions::table)
+        .filter(default_versions::crate_id.eq(krate.id))
+        .select((DefaultVersion::as_select(), default_versions::num_versions))
+        .first::<(DefaultVersion, Option<i32>)>(conn)
+        .await
+        .optional()?;
+
+    let (default_version, num_versions) = existing_default_version
+        .map(|(default_version, num_versions)| {
+            let default_version = default_version.num.to_string();
+            (Some(default_version), num_versions.unwrap_or_default())
+        })
+        .unwrap_or_default();
+
+    let top_versions = krate.top_versions(conn).await?;
+
+    let downloads: i64 = crate_downloads::table
+        .select(crate_downloads::downloads)
+        .filter(crate_downloads::crate_id.eq(krate.id))
+        .first(conn)
+        .await?;
+
+    Ok(Json(GoodCrate {
+        krate: EncodableCrate::from_minimal(
+            krate,
+            default_version.as_deref(),
+            num_versions,
+            Some(false),
+            Some(&top_versions),
+            false,
+            downloads,
+            None,
+        ),
+        warnings,
+    }))
+}
+
+/// A staged version, together with its crate if the crate has already been
+/// published.
+struct LoadedStagedVersion {
+    krate: Option<Crate>,
+    crate_name: String,
+    staged_version: StagedVersion,
+}
+
+/// Loads a staged version of an existing crate or of a crate that has not
+/// been published yet.
+async fn load_staged_version(
+    conn: &mut AsyncPgConnection,
+    name: &str,
+    version: &str,
+) -> AppResult<LoadedStagedVersion> {
+    let krate: Option<Crate> = Crate::by_name(name).first(conn).await.optional()?;
+
+    let staged_version = match &krate {
+        Some(krate) => StagedVersion::find(conn, krate.id, version).await?,
+        None => StagedVersion::find_by_crate_name(conn, name, version).await?,
+    };
+
+    let staged_version = staged_version.ok_or_else(|| staged_version_not_found(name, version))?;
+
+    let crate_name = match (&krate, &staged_version.crate_name) {
+        (Some(krate), _) => krate.name.clone(),
+        (None, Some(crate_name)) => crate_name.clone(),
+        (None, None) => return Err(staged_version_not_found(name, version)),
+    };
+
+    Ok(LoadedStagedVersion {
+        krate,
+        crate_name,
+        staged_version,
+    })
+}
+
+/// Returns an error if the user is not allowed to release or download the
+/// staged version.
+///
+/// For existing crates, this requires publish rights on the crate. Versions
+/// of crates that have not been published yet can only be accessed by the
+/// user that staged them, since the crate does not have any owners yet.
+async fn check_staged_version_rights(
+    app: &AppState,
+    conn: &mut AsyncPgConnection,
+    user: &User,
+    loaded: &LoadedStagedVersion,
+    error_message: &'static str,
+) -> AppResult<()> {
+    let is_allowed = match &loaded.krate {
+        Some(krate) => {
+            let owners = krate.owners(conn).await?;
+            let encryption = &app.config.gh_token_encryption;
+            Rights::get(user, &*app.github, &owners, encryption).await? >= Rights::Publish
+        }
+        None => loaded.staged_version.published_by == Some(user.id),
+    };
+
+    if !is_allowed {
+        return Err(custom(StatusCode::FORBIDDEN, error_message));
+    }
+
+    Ok(())
+}
+
+/// Release a staged crate version.
+///
+/// This makes a version that was previously uploaded in "staged" mode
+/// publicly available, by adding it to the index and sending the usual
+/// publish notifications. If the crate has not been published yet, it is
+/// created with the releasing user as its owner.
+#[utoipa::path(
+    put,
+    path = "/api/v1/crates/{name}/{version}/release",
+    params(CrateVersionPath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "publish",
+    responses((status = 200, description = "Successful Response", body = inline(GoodCrate))),
+)]
+pub async fn release_staged_version(
+    app: AppState,
+    path: CrateVersionPath,
+    req: Parts,
+) -> AppResult<Json<GoodCrate>> {
+    let request_log = req.request_log();
+    request_log.add("crate_name", &*path.name);
+    request_log.add("crate_version", &path.version);
+
+    let mut conn = app.db_write().await?;
+
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::PublishRelease)
+        .for_crate(&path.name)
+        .check(&req, &mut conn)
+        .await?;
+
+    let loaded = load_staged_version(&mut conn, &path.name, &path.version).await?;
+
+    let error_message = match loaded.krate {
+        Some(_) => MISSING_RIGHTS_ERROR_MESSAGE,
+        None => STAGED_BY_OTHER_USER_ERROR_MESSAGE,
+    };
+    check_staged_version_rights(&app, &mut conn, auth.user(), &loaded, error_message).await?;
+
+    let LoadedStagedVersion {
+        krate,
+        crate_name,
+        staged_version,
+    } = loaded;
+
+    // Staged versions that were not uploaded via Trusted Publishing can not
+    // be released if the crate has been switched to Trusted Publishing only
+    // in the meantime.
+    if let Some(krate) = &krate
+        && krate.trustpub_only
+        && staged_version.trustpub_data.is_none()
+    {
+        return Err(forbidden(TRUSTPUB_ONLY_ERROR_MESSAGE));
+    }
+
+    let tarball_bytes = app
+        .storage
+        .download_staged_crate_file(&crate_name, &staged_version.num, &staged_version.checksum)
+        .await
+        .map_err(|e| internal(format!("failed to download staged crate: {e}")))?;
+
+    let checksum: String = Sha256::digest(&tarball_bytes).encode_hex();
+    if checksum != staged_version.checksum {
+        return Err(internal("checksum mismatch for staged crate file"));
+    }
+
+    let metadata = PublishMetadata {
+        name: crate_name.clone(),
+        vers: staged_version.num.clone(),
+        readme: staged_version.readme.clone(),
+        readme_file: staged_version.readme_file.clone(),
+    };
+
+    let semver = semver::Version::parse(&staged_version.num)
+        .map_err(|e| internal(format!("invalid staged version number: {e}")))?;
+
+    // The crate limits or our validation rules might have changed since the
+    // version was staged, so we validate the `.crate` file again.
+    let version = validate_version(&app, metadata, semver, tarball_bytes, krate.as_ref()).await?;
+
+    let publisher = Publisher {
+        user_id: staged_version.published_by,
+        team_id: staged_version.team_id,
+        trustpub_data: staged_version.trustpub_data.clone(),
+        email_address: staged_version.publisher_email.clone(),
+    };
+
+    let auth = AuthType::Regular(Box::new(auth));
+    let is_new_crate = krate.is_none();
+    let staged_version_id = staged_version.id;
+    let response = conn
+        .transaction(|conn| {
+            async {
+                let deleted = diesel::delete(staged_versions::table.find(staged_version_id))
+                    .execute(conn)
+                    .await?;
+
+                // The staged version has been released or deleted concurrently
+                if deleted == 0 {
+                    return Err(staged_version_not_found(&crate_name, &path.version));
+                }
+
+                persist_version(&app, conn, &auth, publisher, is_new_crate, version).await
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    app.sparse_index_cache.invalidate(&crate_name).await;
+
+    let result = app
+        .storage
+        .delete_staged_crate_file(&crate_name, &staged_version.num, &staged_version.checksum)
+        .await;
+
+    if let Err(error) = result {
+        error!("Failed to delete staged crate file: {error}");
+    }
+
+    Ok(response)
+}
+
+/// Download a staged crate version.
+///
+/// This returns the staged crate file itself, since staged versions are not
+/// published to the CDN. Only owners of the crate are allowed to download
+/// staged versions. API tokens need the `read-private` endpoint scope.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/{version}/staged/download",
+    params(CrateVersionPath),
+    security(
+        ("api_token" = []),
+        ("cookie" = []),
+    ),
+    tag = "publish",
+    responses(
+        (status = 200, description = "Successful Response", content_type = "application/gzip"),
+    ),
+)]
+pub async fn download_staged_version(
+    app: AppState,
+    path: CrateVersionPath,
+    req: Parts,
+) -> AppResult<Response> {
+    let mut conn = app.db_read_prefer_primary().await?;
+
+    // Downloading is read-only, so it does not require a token that could
+    // also release the version. This allows CI jobs to verify the staged
+    // crate file before it is released with a separate token.
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::ReadPrivate)
+        .for_crate(&path.name)
+        .check(&req, &mut conn)
+        .await?;
+
+    let loaded = load_staged_version(&mut conn, &path.name, &path.version).await?;
+
+    let error_message = match loaded.krate {
+        Some(_) => "only owners of this crate are allowed to download staged versions",
+        None => STAGED_BY_OTHER_USER_ERROR_MESSAGE,
+    };
+    check_staged_version_rights(&app, &mut conn, auth.user(), &loaded, error_message).await?;
+
+    let LoadedStagedVersion {
+        crate_name,
+        staged_version,
+        ..
+    } = loaded;
+
+    let bytes = app
+        .storage
+        .download_staged_crate_file(&crate_name, &staged_version.num, &staged_version.checksum)
+        .await
+        .map_err(|e| internal(format!("failed to download staged crate: {e}")))?;
+
+    let filename = format!("{crate_name}-{}.crate", staged_version.num);
+    let headers = [
+        (header::CONTENT_TYPE, "application/gzip".to_string()),
+        (header::CACHE_CONTROL, CACHE_CONTROL_PRIVATE.to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{filename}\""),
+        ),
+    ];
+
+    Ok((headers, bytes).into_response())
 }
 
 /// Counts the number of versions for `crate_id` that were published within
@@ -829,6 +1505,11 @@ fn duplicate_version_error(version: &str) -> BoxedAppError {
     bad_request(format!("crate version `{version}` is already uploaded"))
 }
 
+fn staged_version_not_found(krate: &str, version: &str) -> BoxedAppError {
+    let detail = format!("crate `{krate}` does not have a staged version `{version}`");
+    custom(StatusCode::NOT_FOUND, detail)
+}
+
 fn validate_rust_version(value: &str) -> AppResult<()> {
     match semver::VersionReq::parse(value) {
         // Exclude semver operators like `^` and pre-release identifiers
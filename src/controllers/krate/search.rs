@@ -4,11 +4,12 @@ use crate::auth::AuthCheck;
 use axum::Json;
 use axum::extract::FromRequestParts;
 use axum_extra::extract::Query;
+use chrono::{NaiveDate, NaiveTime, TimeDelta};
 use derive_more::Deref;
-use diesel::alias;
-use diesel::dsl::{InnerJoinQuerySource, LeftJoinQuerySource, exists};
+use diesel::dsl::{InnerJoinQuerySource, LeftJoinQuerySource, exists, sql};
 use diesel::prelude::*;
-use diesel::sql_types::Bool;
+use diesel::sql_types::{Array, Bool, Float, Integer, Nullable, Text};
+use diesel::{alias, define_sql_function, dsl};
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use diesel_full_text_search::{configuration::TsConfigurationByName, *};
 use http::request::Parts;
@@ -18,6 +19,8 @@ use utoipa::IntoParams;
 
 use crate::app::AppState;
 use crate::controllers::helpers::Paginate;
+use crate::licenses::parse_license_expr;
+use crate::models::token::EndpointScope;
 use crate::models::{Crate, CrateOwner, OwnerKind, TopVersions, Version};
 use crate::schema::*;
 use crate::util::errors::{AppResult, bad_request};
@@ -27,7 +30,15 @@ use crate::controllers::helpers::pagination::{Page, PaginationOptions, Paginatio
 use crate::models::krate::ALL_COLUMNS;
 use crate::util::RequestUtils;
 use crate::util::string_excl_null::StringExclNull;
-use crates_io_diesel_helpers::{array_agg, canon_crate_name, lower};
+use crates_io_diesel_helpers::{
+    array_agg, canon_crate_name, coalesce, lower, regexp_split_to_array,
+};
+use spdx::Expression;
+use spdx::expression::{ExprNode, Operator};
+
+mod query;
+
+use self::query::{Comparison, SearchQuery};
 
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct ListResponse {
@@ -92,7 +103,6 @@ pub async fn list_crates(
 
     let mut conn = app.db_read().await?;
 
-    use diesel::sql_types::Float;
     use seek::*;
 
     let filter_params = FilterParams::from(params, &req, &mut conn).await?;
@@ -116,11 +126,10 @@ pub async fn list_crates(
         .left_join(recent_crate_downloads::table)
         .left_join(default_versions::table)
         .left_join(versions::table.on(default_versions::version_id.eq(versions::id)))
+        .left_join(crate_search_scores::table)
         .select(selection);
 
-    if let Some(q_string) = &filter_params.q_string
-        && !q_string.is_empty()
-    {
+    if let Some(q_string) = &filter_params.query.text {
         let q_string = q_string.as_str();
 
         let sort = sort.unwrap_or("relevance");
@@ -128,8 +137,7 @@ pub async fn list_crates(
         query = query.order(Crate::with_name(q_string).desc());
 
         if sort == "relevance" {
-            let q = plainto_tsquery_with_search_config(TsConfigurationByName("english"), q_string);
-            let rank = ts_rank_cd(crates::textsearchable_index_col, q);
+            let rank = relevance_rank(q_string);
             query = query.select((
                 ALL_COLUMNS,
                 Crate::with_name(q_string),
@@ -285,14 +293,24 @@ pub struct ListQueryParams {
     sort: Option<String>,
 
     /// A search query string.
+    ///
+    /// Besides free text, the query may contain quoted phrases
+    /// (`"async runtime"`), the qualifiers `keyword:`, `category:`, `owner:`,
+    /// `downloads:` and `updated:`, and `-yanked` to exclude fully yanked
+    /// crates. The `downloads:` and `updated:` qualifiers accept a number or
+    /// `YYYY-MM-DD` date, optionally prefixed by `>`, `>=`, `<` or `<=`.
     #[serde(rename = "q")]
-    #[param(inline)]
+    #[param(inline, example = "http client keyword:async downloads:>1000")]
     q_string: Option<StringExclNull>,
 
     /// Set to `yes` to include yanked crates.
     #[param(example = "yes")]
     include_yanked: Option<String>,
 
+    /// Set to `no` to exclude crates that have been deprecated by their owners.
+    #[param(example = "no")]
+    include_deprecated: Option<String>,
+
     /// If set, only return crates that belong to this category, or one
     /// of its subcategories.
     #[param(inline)]
@@ -336,6 +354,45 @@ pub struct ListQueryParams {
     #[serde(rename = "ids[]", default)]
     #[param(inline)]
     ids: Vec<StringExclNull>,
+
+    /// If set, only return crates whose default version declares a feature
+    /// with the given name.
+    #[param(inline)]
+    feature: Option<StringExclNull>,
+
+    /// If set, only return crates whose default version contains a binary
+    /// target with the given name.
+    #[param(inline)]
+    bin_name: Option<StringExclNull>,
+
+    /// If set, only return crates whose default version links to the given
+    /// native library (see the `links` field in the `Cargo.toml` manifest).
+    #[param(inline)]
+    links: Option<StringExclNull>,
+
+    /// If set, only return crates whose default version declares a
+    /// `rust-version` that is lower than or equal to the given Rust version,
+    /// or does not declare a `rust-version` at all.
+    #[param(inline, example = "1.70")]
+    rust_version_max: Option<StringExclNull>,
+
+    /// If set, only return crates whose default version uses the given
+    /// Rust edition.
+    #[param(inline, example = "2021")]
+    edition: Option<StringExclNull>,
+
+    /// If set, only return crates whose default version declares a license
+    /// matching the given SPDX license expression.
+    ///
+    /// `MIT` matches crates licensed under e.g. `MIT OR Apache-2.0`, while
+    /// `MIT AND Apache-2.0` only matches crates mentioning both licenses.
+    #[param(inline, example = "MIT")]
+    license: Option<StringExclNull>,
+
+    /// Set to `yes` to only return crates whose default version contains a
+    /// library target, or `no` to only return crates without one.
+    #[param(example = "yes")]
+    has_lib: Option<String>,
 }
 
 impl ListQueryParams {
@@ -343,14 +400,23 @@ impl ListQueryParams {
         let include_yanked = self.include_yanked.as_ref();
         include_yanked.map(|s| s == "yes").unwrap_or(true)
     }
+
+    pub fn include_deprecated(&self) -> bool {
+        let include_deprecated = self.include_deprecated.as_ref();
+        include_deprecated.map(|s| s != "no").unwrap_or(true)
+    }
 }
 
 #[derive(Deref)]
 struct FilterParams {
     #[deref]
     search_params: ListQueryParams,
+    query: SearchQuery,
     letter: Option<char>,
     auth_user_id: Option<i32>,
+    rust_version_max: Option<Vec<i32>>,
+    license: Option<Expression>,
+    has_lib: Option<bool>,
 }
 
 impl FilterParams {
@@ -359,21 +425,58 @@ impl FilterParams {
         parts: &Parts,
         conn: &mut AsyncPgConnection,
     ) -> AppResult<Self> {
+        let query = match &search_params.q_string {
+            Some(q) => SearchQuery::parse(q).map_err(bad_request)?,
+            None => SearchQuery::default(),
+        };
+
         const LETTER_ERROR: &str = "letter value must contain 1 character";
         let letter = match &search_params.letter {
             Some(s) => Some(s.chars().next().ok_or_else(|| bad_request(LETTER_ERROR))?),
             None => None,
         };
 
+        const RUST_VERSION_ERROR: &str =
+            "rust_version_max value must be a Rust version like `1.70` or `1.70.0`";
+        let rust_version_max = match &search_params.rust_version_max {
+            Some(s) => Some(parse_rust_version(s).ok_or_else(|| bad_request(RUST_VERSION_ERROR))?),
+            None => None,
+        };
+
+        const LICENSE_ERROR: &str = "license value must be a valid SPDX license expression";
+        let license = match &search_params.license {
+            Some(s) => Some(parse_license_expr(s).map_err(|_| bad_request(LICENSE_ERROR))?),
+            None => None,
+        };
+
+        const HAS_LIB_ERROR: &str = "has_lib value must be `yes` or `no`";
+        let has_lib = match search_params.has_lib.as_deref() {
+            Some("yes") => Some(true),
+            Some("no") => Some(false),
+            Some(_) => return Err(bad_request(HAS_LIB_ERROR)),
+            None => None,
+        };
+
         let auth_user_id = match search_params.following {
-            Some(_) => Some(AuthCheck::default().check(parts, conn).await?.user_id()),
+            Some(_) => {
+                let auth = AuthCheck::default()
+                    .with_endpoint_scope(EndpointScope::ReadPrivate)
+                    .check(parts, conn)
+                    .await?;
+
+                Some(auth.user_id())
+            }
             None => None,
         };
 
         Ok(Self {
             search_params,
+            query,
             letter,
             auth_user_id,
+            rust_version_max,
+            license,
+            has_lib,
         })
     }
 }
@@ -382,9 +485,7 @@ impl FilterParams {
     fn make_query(&self) -> crates::BoxedQuery<'_, diesel::pg::Pg> {
         let mut query = crates::table.into_boxed();
 
-        if let Some(q_string) = &self.q_string
-            && !q_string.is_empty()
-        {
+        if let Some(q_string) = &self.query.text {
             let q = plainto_tsquery_with_search_config(
                 TsConfigurationByName("english"),
                 q_string.as_str(),
@@ -395,6 +496,95 @@ impl FilterParams {
             );
         }
 
+        for phrase in &self.query.phrases {
+            let q = phraseto_tsquery_with_search_config(
+                TsConfigurationByName("english"),
+                phrase.as_str(),
+            );
+            query = query.filter(q.matches(crates::textsearchable_index_col));
+        }
+
+        for keyword in &self.query.keywords {
+            query = query.filter(
+                crates::id.eq_any(
+                    crates_keywords::table
+                        .select(crates_keywords::crate_id)
+                        .inner_join(keywords::table)
+                        .filter(lower(keywords::keyword).eq(keyword.as_str())),
+                ),
+            );
+        }
+
+        for category in &self.query.categories {
+            query = query.filter(
+                crates::id.eq_any(
+                    crates_categories::table
+                        .select(crates_categories::crate_id)
+                        .inner_join(categories::table)
+                        .filter(
+                            categories::slug
+                                .eq(category.as_str())
+                                .or(categories::slug.like(format!("{category}::%"))),
+                        ),
+                ),
+            );
+        }
+
+        for owner in &self.query.owners {
+            let owner = owner.to_lowercase();
+            let user_ids = users::table
+                .select(users::id)
+                .filter(lower(users::gh_login).eq(owner.clone()));
+            let team_ids = teams::table
+                .select(teams::id)
+                .filter(lower(teams::login).eq(owner));
+
+            query = query.filter(
+                crates::id
+                    .eq_any(
+                        CrateOwner::by_owner_kind(OwnerKind::User)
+                            .select(crate_owners::crate_id)
+                            .filter(crate_owners::owner_id.eq_any(user_ids)),
+                    )
+                    .or(crates::id.eq_any(
+                        CrateOwner::by_owner_kind(OwnerKind::Team)
+                            .select(crate_owners::crate_id)
+                            .filter(crate_owners::owner_id.eq_any(team_ids)),
+                    )),
+            );
+        }
+
+        for comparison in &self.query.downloads {
+            let downloads = crate_downloads::downloads;
+            let crate_ids = crate_downloads::table
+                .select(crate_downloads::crate_id)
+                .into_boxed();
+            let crate_ids = match *comparison {
+                Comparison::Lt(value) => crate_ids.filter(downloads.lt(value)),
+                Comparison::Le(value) => crate_ids.filter(downloads.le(value)),
+                Comparison::Eq(value) => crate_ids.filter(downloads.eq(value)),
+                Comparison::Ge(value) => crate_ids.filter(downloads.ge(value)),
+                Comparison::Gt(value) => crate_ids.filter(downloads.gt(value)),
+            };
+            query = query.filter(crates::id.eq_any(crate_ids));
+        }
+
+        for comparison in &self.query.updated {
+            // Dates cover the whole day in UTC
+            let start_of_day = |date: NaiveDate| date.and_time(NaiveTime::MIN).and_utc();
+            let end_of_day = |date| start_of_day(date) + TimeDelta::days(1);
+            let updated_at = crates::updated_at;
+            query = match *comparison {
+                Comparison::Lt(date) => query.filter(updated_at.lt(start_of_day(date))),
+                Comparison::Le(date) => query.filter(updated_at.lt(end_of_day(date))),
+                Comparison::Eq(date) => query
+                    .filter(updated_at.ge(start_of_day(date)))
+                    .filter(updated_at.lt(end_of_day(date))),
+                Comparison::Ge(date) => query.filter(updated_at.ge(start_of_day(date))),
+                Comparison::Gt(date) => query.filter(updated_at.ge(end_of_day(date))),
+            };
+        }
+
         if let Some(cat) = &self.category {
             query = query.filter(
                 crates::id.eq_any(
@@ -464,7 +654,21 @@ impl FilterParams {
             query = query.filter(crates::name.eq_any(self.ids.iter().map(|s| s.as_str())));
         }
 
-        if !self.include_yanked() {
+        let conditions = self.default_version_conditions();
+        if !conditions.is_empty() {
+            let mut default_versions_query = default_versions::table
+                .inner_join(versions::table)
+                .select(default_versions::crate_id)
+                .into_boxed();
+
+            for condition in conditions {
+                default_versions_query = default_versions_query.filter(condition);
+            }
+
+            query = query.filter(crates::id.eq_any(default_versions_query));
+        }
+
+        if !self.include_yanked() || self.query.exclude_yanked {
             query = query.filter(exists(
                 versions::table
                     .filter(versions::crate_id.eq(crates::id))
@@ -472,9 +676,61 @@ impl FilterParams {
             ));
         }
 
+        if !self.include_deprecated() {
+            query = query.filter(crates::deprecated_at.is_null());
+        }
+
         query
     }
 
+    /// Returns the conditions that the default version of a crate has to
+    /// satisfy for the crate to be included in the results.
+    fn default_version_conditions(&self) -> Vec<DefaultVersionCondition<'_>> {
+        let mut conditions: Vec<DefaultVersionCondition<'_>> = Vec::new();
+
+        if let Some(feature) = &self.feature {
+            let condition = versions::features.has_key(feature.as_str());
+            conditions.push(Box::new(condition.nullable()));
+        }
+
+        if let Some(bin_name) = &self.bin_name {
+            let condition = versions::bin_names.contains(vec![Some(bin_name.as_str())]);
+            conditions.push(Box::new(condition));
+        }
+
+        if let Some(links) = &self.links {
+            conditions.push(Box::new(versions::links.eq(links.as_str())));
+        }
+
+        if let Some(rust_version_max) = &self.rust_version_max {
+            // The regular expression guards against casting errors for
+            // `rust-version` values that were published before they were
+            // validated.
+            let rust_version = sql::<Nullable<Array<Integer>>>(
+                "CASE WHEN versions.rust_version ~ '^\\d{1,9}(\\.\\d{1,9}){0,2}$' \
+                THEN string_to_array(versions.rust_version, '.')::int[] END",
+            );
+            let condition = versions::rust_version
+                .is_null()
+                .or(rust_version.le(rust_version_max.clone()));
+            conditions.push(Box::new(condition));
+        }
+
+        if let Some(edition) = &self.edition {
+            conditions.push(Box::new(versions::edition.eq(edition.as_str())));
+        }
+
+        if let Some(condition) = self.license.as_ref().and_then(license_condition) {
+            conditions.push(condition);
+        }
+
+        if let Some(has_lib) = self.has_lib {
+            conditions.push(Box::new(versions::has_lib.eq(has_lib)));
+        }
+
+        conditions
+    }
+
     fn seek_after(&self, seek_payload: &seek::SeekPayload) -> BoxedCondition<'_> {
         use seek::*;
 
@@ -591,7 +847,11 @@ impl FilterParams {
                 // WHERE (exact_match = exact_match' AND name > name') OR exact_match < exact_match'
                 // ORDER BY exact_match DESC, NAME ASC
                 // ```
-                let q_string = self.q_string.as_ref().expect("q_string should not be None");
+                let q_string = self
+                    .query
+                    .text
+                    .as_ref()
+                    .expect("query text should not be None");
                 let name_exact_match = Crate::with_name(q_string);
                 vec![
                     Box::new(
@@ -615,12 +875,12 @@ impl FilterParams {
                 //      OR exact_match < exact_match'
                 // ORDER BY exact_match DESC, rank DESC, name ASC
                 // ```
-                let q_string = self.q_string.as_ref().expect("q_string should not be None");
-                let q = plainto_tsquery_with_search_config(
-                    TsConfigurationByName("english"),
-                    q_string.as_str(),
-                );
-                let rank = ts_rank_cd(crates::textsearchable_index_col, q);
+                let q_string = self
+                    .query
+                    .text
+                    .as_ref()
+                    .expect("query text should not be None");
+                let rank = relevance_rank(q_string.as_str());
                 let name_exact_match = Crate::with_name(q_string.as_str());
                 vec![
                     Box::new(
@@ -651,6 +911,80 @@ impl FilterParams {
     }
 }
 
+/// Parses a Rust version like `1.70` into its numeric components, padded to
+/// `major.minor.patch`.
+fn parse_rust_version(value: &str) -> Option<Vec<i32>> {
+    let mut parts = value
+        .split('.')
+        .map(|part| {
+            if part.chars().all(|c| c.is_ascii_digit()) {
+                part.parse().ok()
+            } else {
+                None
+            }
+        })
+        .collect::<Option<Vec<i32>>>()?;
+
+    if parts.len() > 3 {
+        return None;
+    }
+
+    parts.resize(3, 0);
+    Some(parts)
+}
+
+/// Converts an SPDX license expression into a condition that matches
+/// versions whose `license` mentions the licenses in the expression.
+///
+/// The `license` column is split into lowercase tokens, so that e.g. `MIT`
+/// matches both `MIT OR Apache-2.0` and the legacy `MIT/Apache-2.0` syntax.
+fn license_condition(expression: &Expression) -> Option<DefaultVersionCondition<'static>> {
+    let mut stack: Vec<DefaultVersionCondition<'static>> = Vec::new();
+
+    // The expression nodes are returned in postfix order.
+    for node in expression.iter() {
+        let condition: DefaultVersionCondition<'static> = match node {
+            ExprNode::Req(req) => {
+                let license = req.req.license.to_string();
+                let license = license.trim_end_matches('+').to_lowercase();
+                let tokens = regexp_split_to_array(lower(versions::license), r"[\s/()+]+");
+                Box::new(tokens.contains(vec![license]))
+            }
+            ExprNode::Op(op) => {
+                let rhs = stack.pop()?;
+                let lhs = stack.pop()?;
+                match op {
+                    Operator::And => Box::new(lhs.and(rhs)),
+                    Operator::Or => Box::new(lhs.or(rhs)),
+                }
+            }
+        };
+
+        stack.push(condition);
+    }
+
+    stack.pop()
+}
+
+/// The rank of a crate when sorting by relevance, which is the text rank of
+/// the search query weighted by the precomputed popularity score of the crate.
+///
+/// Crates without a score yet (e.g. new crates) keep their text rank.
+#[dsl::auto_type(no_type_alias)]
+fn relevance_rank<'a>(q_string: &'a str) -> _ {
+    let config: TsConfigurationByName = TsConfigurationByName("english");
+    let q: plainto_tsquery_with_search_config<TsConfigurationByName, &'a str> =
+        plainto_tsquery_with_search_config(config, q_string);
+    let score: coalesce<Float, dsl::Nullable<crate_search_scores::score>, f32> =
+        coalesce(crate_search_scores::score.nullable(), 1.0_f32);
+    ts_rank_cd(crates::textsearchable_index_col, q) * score
+}
+
+define_sql_function! {
+    #[sql_name = "phraseto_tsquery"]
+    fn phraseto_tsquery_with_search_config(config: RegConfig, querytext: Text) -> TsQuery;
+}
+
 mod seek {
     use super::Record;
     use crate::controllers::helpers::pagination::seek;
@@ -737,13 +1071,24 @@ struct Record {
 type QuerySource = LeftJoinQuerySource<
     LeftJoinQuerySource<
         LeftJoinQuerySource<
-            InnerJoinQuerySource<crates::table, crate_downloads::table>,
-            recent_crate_downloads::table,
+            LeftJoinQuerySource<
+                InnerJoinQuerySource<crates::table, crate_downloads::table>,
+                recent_crate_downloads::table,
+            >,
+            default_versions::table,
         >,
-        default_versions::table,
+        versions::table,
+        diesel::dsl::Eq<default_versions::version_id, versions::id>,
     >,
-    versions::table,
-    diesel::dsl::Eq<default_versions::version_id, versions::id>,
+    crate_search_scores::table,
+>;
+
+type DefaultVersionCondition<'a> = Box<
+    dyn BoxableExpression<
+            InnerJoinQuerySource<default_versions::table, versions::table>,
+            diesel::pg::Pg,
+            SqlType = diesel::sql_types::Nullable<Bool>,
+        > + 'a,
 >;
 
 type BoxedCondition<'a> = Box<
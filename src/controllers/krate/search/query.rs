@@ -0,0 +1,332 @@
+//! Parser for the search query language used by the `q` parameter of the
+//! crate list endpoint.
+//!
+//! Besides free text, the query may contain quoted phrases (`"async runtime"`),
+//! field qualifiers (`keyword:async`, `category:web-programming`,
+//! `owner:rust-lang`, `downloads:>10000`, `updated:>2025-01-01`) and the
+//! `-yanked` flag, e.g.:
+//!
+//! ```text
+//! http client keyword:async downloads:>=1000 -yanked
+//! ```
+//!
+//! Tokens that only look like qualifiers or negations, like `tokio::spawn`,
+//! `license:MIT` or `-sys`, are treated as free text.
+
+use chrono::NaiveDate;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    /// The free text terms and phrases of the query, joined by spaces.
+    pub text: Option<String>,
+    /// Quoted phrases that have to appear in the crate's name, description,
+    /// keywords or readme, in the given order.
+    pub phrases: Vec<String>,
+    pub keywords: Vec<String>,
+    pub categories: Vec<String>,
+    pub owners: Vec<String>,
+    pub downloads: Vec<Comparison<i64>>,
+    pub updated: Vec<Comparison<NaiveDate>>,
+    pub exclude_yanked: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison<T> {
+    Lt(T),
+    Le(T),
+    Eq(T),
+    Ge(T),
+    Gt(T),
+}
+
+impl<T> Comparison<T> {
+    fn parse(value: &str, parse: impl FnOnce(&str) -> Option<T>) -> Option<Self> {
+        let (constructor, value): (fn(T) -> Self, _) = if let Some(v) = value.strip_prefix(">=") {
+            (Self::Ge, v)
+        } else if let Some(v) = value.strip_prefix("<=") {
+            (Self::Le, v)
+        } else if let Some(v) = value.strip_prefix('>') {
+            (Self::Gt, v)
+        } else if let Some(v) = value.strip_prefix('<') {
+            (Self::Lt, v)
+        } else {
+            (Self::Eq, value.strip_prefix('=').unwrap_or(value))
+        };
+
+        parse(value).map(constructor)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ParseError {
+    #[error("unterminated quote in search query")]
+    UnterminatedQuote,
+    #[error("missing value for search qualifier `{0}`")]
+    MissingValue(String),
+    #[error("invalid value `{value}` for search qualifier `{qualifier}`, expected {expected}")]
+    InvalidValue {
+        qualifier: String,
+        value: String,
+        expected: &'static str,
+    },
+}
+
+impl SearchQuery {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut query = SearchQuery::default();
+        let mut text = Vec::new();
+
+        for token in tokenize(input)? {
+            if token.quoted {
+                if !token.value.trim().is_empty() {
+                    text.push(token.value.clone());
+                    query.phrases.push(token.value);
+                }
+            } else if token.value.eq_ignore_ascii_case("-yanked") {
+                query.exclude_yanked = true;
+            } else if let Some((qualifier, value)) = split_qualifier(&token.value) {
+                query.add_qualifier(&qualifier, value)?;
+            } else {
+                text.push(token.value);
+            }
+        }
+
+        if !text.is_empty() {
+            query.text = Some(text.join(" "));
+        }
+
+        Ok(query)
+    }
+
+    fn add_qualifier(&mut self, qualifier: &str, value: &str) -> Result<(), ParseError> {
+        if value.is_empty() {
+            return Err(ParseError::MissingValue(qualifier.to_string()));
+        }
+
+        let invalid_value = |expected| ParseError::InvalidValue {
+            qualifier: qualifier.to_string(),
+            value: value.to_string(),
+            expected,
+        };
+
+        match qualifier {
+            "keyword" => self.keywords.push(value.to_lowercase()),
+            "category" => self.categories.push(value.to_lowercase()),
+            "owner" => self.owners.push(value.to_string()),
+            "downloads" => {
+                let comparison = Comparison::parse(value, |v| v.parse().ok())
+                    .ok_or_else(|| invalid_value("a number like `>1000`"))?;
+                self.downloads.push(comparison);
+            }
+            "updated" => {
+                let comparison = Comparison::parse(value, parse_date)
+                    .ok_or_else(|| invalid_value("a date like `>2025-01-01`"))?;
+                self.updated.push(comparison);
+            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+}
+
+const KNOWN_QUALIFIERS: &[&str] = &["keyword", "category", "owner", "downloads", "updated"];
+
+/// Splits a `name:value` token into the lowercase qualifier name and its
+/// value, if the name is a known qualifier and is followed by a single `:`.
+///
+/// Paths like `tokio::spawn` are not split, even if the first segment is a
+/// known qualifier name.
+fn split_qualifier(token: &str) -> Option<(String, &str)> {
+    let (qualifier, value) = token.split_once(':')?;
+    if value.starts_with(':') {
+        return None;
+    }
+
+    let qualifier = qualifier.to_lowercase();
+    KNOWN_QUALIFIERS
+        .contains(&qualifier.as_str())
+        .then_some((qualifier, value))
+}
+
+/// Parses a `YYYY-MM-DD` date. Longer years are rejected to avoid overflows
+/// when converting the date into a timestamp range.
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    if value.len() != 10 {
+        return None;
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+#[derive(Debug)]
+struct Token {
+    value: String,
+    /// Whether the whole token was enclosed in quotes, which turns it into a
+    /// phrase instead of a qualifier or negation.
+    quoted: bool,
+}
+
+/// Splits the input at whitespace that is not enclosed in double quotes, and
+/// removes the quotes from the resulting tokens.
+fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let Some(&first) = chars.peek() else {
+            return Ok(tokens);
+        };
+
+        let mut value = String::new();
+        let mut in_quotes = false;
+        let mut quoted = first == '"';
+        while let Some(c) = chars.next_if(|c| in_quotes || !c.is_whitespace()) {
+            if c == '"' {
+                in_quotes = !in_quotes;
+
+                // Only tokens consisting of a single quoted section are phrases
+                if !in_quotes && chars.peek().is_some_and(|c| !c.is_whitespace()) {
+                    quoted = false;
+                }
+            } else {
+                value.push(c);
+            }
+        }
+
+        if in_quotes {
+            return Err(ParseError::UnterminatedQuote);
+        }
+
+        tokens.push(Token { value, quoted });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn plain_text() {
+        assert_ok_eq!(
+            SearchQuery::parse("  http  client "),
+            SearchQuery {
+                text: Some("http client".into()),
+                ..Default::default()
+            }
+        );
+
+        assert_ok_eq!(SearchQuery::parse(""), SearchQuery::default());
+        assert_ok_eq!(
+            SearchQuery::parse("-"),
+            SearchQuery {
+                text: Some("-".into()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_qualifiers_and_negations() {
+        assert_ok_eq!(
+            SearchQuery::parse("tokio::spawn keyword::foo license:MIT -sys"),
+            SearchQuery {
+                text: Some("tokio::spawn keyword::foo license:MIT -sys".into()),
+                ..Default::default()
+            }
+        );
+
+        assert_ok_eq!(
+            SearchQuery::parse("openssl -sys -Yanked"),
+            SearchQuery {
+                text: Some("openssl -sys".into()),
+                exclude_yanked: true,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn phrases() {
+        let query = assert_ok!(SearchQuery::parse(r#"fast "async runtime" """#));
+        assert_eq!(query.text.as_deref(), Some("fast async runtime"));
+        assert_eq!(query.phrases, vec!["async runtime"]);
+
+        let query = assert_ok!(SearchQuery::parse(r#"category:"web-programming""#));
+        assert_eq!(query.text, None);
+        assert_eq!(query.phrases, Vec::<String>::new());
+        assert_eq!(query.categories, vec!["web-programming"]);
+
+        assert_err_eq!(
+            SearchQuery::parse(r#"foo "bar"#),
+            ParseError::UnterminatedQuote
+        );
+    }
+
+    #[test]
+    fn qualifiers() {
+        let query = assert_ok!(SearchQuery::parse(
+            "serde Keyword:JSON keyword:no-std category:encoding owner:dtolnay \
+            downloads:>10000 downloads:<=5000000 updated:2025-01-01 updated:>=2024-06-30 -yanked"
+        ));
+
+        assert_eq!(
+            query,
+            SearchQuery {
+                text: Some("serde".into()),
+                phrases: vec![],
+                keywords: vec!["json".into(), "no-std".into()],
+                categories: vec!["encoding".into()],
+                owners: vec!["dtolnay".into()],
+                downloads: vec![Comparison::Gt(10000), Comparison::Le(5000000)],
+                updated: vec![
+                    Comparison::Eq(date("2025-01-01")),
+                    Comparison::Ge(date("2024-06-30"))
+                ],
+                exclude_yanked: true,
+            }
+        );
+
+        let query = assert_ok!(SearchQuery::parse("owner:github:rust-lang:core"));
+        assert_eq!(query.owners, vec!["github:rust-lang:core"]);
+    }
+
+    #[test]
+    fn errors() {
+        assert_err_eq!(
+            SearchQuery::parse("keyword:"),
+            ParseError::MissingValue("keyword".into())
+        );
+        assert_err_eq!(
+            SearchQuery::parse("downloads:>lots"),
+            ParseError::InvalidValue {
+                qualifier: "downloads".into(),
+                value: ">lots".into(),
+                expected: "a number like `>1000`",
+            }
+        );
+        assert_err_eq!(
+            SearchQuery::parse("updated:>yesterday"),
+            ParseError::InvalidValue {
+                qualifier: "updated".into(),
+                value: ">yesterday".into(),
+                expected: "a date like `>2025-01-01`",
+            }
+        );
+        assert_err_eq!(
+            SearchQuery::parse("updated:<+262142-12-31"),
+            ParseError::InvalidValue {
+                qualifier: "updated".into(),
+                value: "<+262142-12-31".into(),
+                expected: "a date like `>2025-01-01`",
+            }
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Endpoint for search-as-you-type crate name suggestions
+
+use crate::app::AppState;
+use crate::middleware::real_ip::RealIp;
+use crate::schema::{crates, recent_crate_downloads};
+use crate::util::errors::{AppResult, bad_request, custom};
+use axum::extract::FromRequestParts;
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use axum_extra::extract::Query;
+use crates_io_diesel_helpers::canon_crate_name;
+use diesel::prelude::*;
+use diesel::sql_types::Text;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use http::{StatusCode, header};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// The default number of suggestions returned by [`suggest_crates`].
+const DEFAULT_LIMIT: i64 = 10;
+
+/// The maximum number of suggestions returned by [`suggest_crates`].
+const MAX_LIMIT: i64 = 25;
+
+/// The maximum length of a crate name, and thus of a useful query.
+const MAX_QUERY_LENGTH: usize = 64;
+
+#[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
+#[from_request(via(Query))]
+#[into_params(parameter_in = Query)]
+pub struct SuggestQueryParams {
+    /// The beginning of a crate name, or a crate name with typos.
+    ///
+    /// Hyphens and underscores are treated as equivalent, and the query is
+    /// matched case-insensitively.
+    #[param(example = "ser")]
+    q: String,
+
+    /// The maximum number of suggestions to return.
+    ///
+    /// Defaults to `10`, and must not be greater than `25`.
+    limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct SuggestResponse {
+    /// The suggested crates, ordered by relevance.
+    crates: Vec<CrateSuggestion>,
+}
+
+#[derive(Debug, Clone, Serialize, Queryable, Selectable, utoipa::ToSchema)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct CrateSuggestion {
+    /// The name of the crate.
+    #[diesel(select_expression = crates::name)]
+    #[schema(example = "serde")]
+    name: String,
+
+    /// The number of downloads of the crate in the last 90 days.
+    #[diesel(select_expression = recent_crate_downloads::downloads.nullable())]
+    #[schema(example = 2_000_000)]
+    recent_downloads: Option<i64>,
+}
+
+/// An in-process cache for crate name suggestions, keyed by the canonical
+/// query and the requested number of suggestions.
+///
+/// Suggestions only depend on the crate names and the recent downloads, so
+/// slightly stale entries are fine and the cache is never invalidated.
+#[derive(Clone)]
+pub struct SuggestCache(moka::future::Cache<(String, i64), Vec<CrateSuggestion>>);
+
+impl SuggestCache {
+    pub fn new(max_capacity: u64, ttl: Duration) -> Self {
+        let cache = moka::future::CacheBuilder::new(max_capacity)
+            .name("crate_suggestions")
+            .time_to_live(ttl)
+            .build();
+
+        Self(cache)
+    }
+}
+
+/// Suggest crate names for a partial query.
+///
+/// This endpoint is meant for search-as-you-type completions. It matches the
+/// query against the beginning of the crate names first, and falls back to
+/// trigram similarity for queries with typos. Matches are ranked by their
+/// number of recent downloads.
+///
+/// Requests are rate limited per IP address.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/suggest",
+    params(SuggestQueryParams),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response", body = inline(SuggestResponse))),
+)]
+pub async fn suggest_crates(
+    state: AppState,
+    Extension(real_ip): Extension<RealIp>,
+    params: SuggestQueryParams,
+) -> AppResult<Response> {
+    if !state.suggest_rate_limiter.check(*real_ip).await {
+        let message = "You have requested too many crate suggestions in a short period of time";
+        return Err(custom(StatusCode::TOO_MANY_REQUESTS, message));
+    }
+
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        let message = format!("limit must be between 1 and {MAX_LIMIT}");
+        return Err(bad_request(message));
+    }
+
+    let query = params.q.trim();
+    if query.len() > MAX_QUERY_LENGTH {
+        let message = format!("q must not be longer than {MAX_QUERY_LENGTH} characters");
+        return Err(bad_request(message));
+    }
+
+    // Same normalization as the `canon_crate_name()` SQL function
+    let query = query.to_lowercase().replace('-', "_");
+
+    let crates = if query.is_empty() {
+        Vec::new()
+    } else {
+        let key = (query, limit);
+        match state.suggest_cache.0.get(&key).await {
+            Some(suggestions) => suggestions,
+            None => {
+                let mut conn = state.db_read().await?;
+                let suggestions = load_suggestions(&key.0, limit, &mut conn).await?;
+                state.suggest_cache.0.insert(key, suggestions.clone()).await;
+                suggestions
+            }
+        }
+    };
+
+    let max_age = state.config.suggest_cache_ttl.as_secs();
+    let cache_control = format!("public,max-age={max_age}");
+    let headers = [(header::CACHE_CONTROL, cache_control)];
+
+    Ok((headers, Json(SuggestResponse { crates })).into_response())
+}
+
+/// Loads the crates whose canonical name starts with or is similar to the
+/// given canonical query.
+///
+/// Both conditions are served by the trigram index on
+/// `canon_crate_name(name)`.
+async fn load_suggestions(
+    query: &str,
+    limit: i64,
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<Vec<CrateSuggestion>> {
+    diesel::infix_operator!(IsSimilar, " % ");
+
+    // `_` is a wildcard in `LIKE` patterns, and part of canonical crate names
+    let escaped = query
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    let prefix_pattern = format!("{escaped}%");
+
+    let name = canon_crate_name(crates::name);
+    let is_prefix_match = name.like(prefix_pattern.clone());
+    let is_similar = IsSimilar::new(name, query.into_sql::<Text>());
+
+    crates::table
+        .left_join(recent_crate_downloads::table)
+        .filter(is_prefix_match.or(is_similar))
+        .select(CrateSuggestion::as_select())
+        .order((
+            name.eq(query).desc(),
+            name.like(prefix_pattern).desc(),
+            recent_crate_downloads::downloads.desc().nulls_last(),
+            crates::name.asc(),
+        ))
+        .limit(limit)
+        .load(conn)
+        .await
+}
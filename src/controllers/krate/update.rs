@@ -4,18 +4,23 @@ use crate::controllers::krate::CratePath;
 use crate::email::EmailMessage;
 use crate::middleware::real_ip::RealIp;
 use crate::models::token::EndpointScope;
-use crate::models::{Crate, User};
+use crate::models::{Crate, CrateAction, NewCrateAuditAction, User};
 use crate::schema::*;
-use crate::util::errors::{AppResult, crate_not_found, custom};
+use crate::util::errors::{AppResult, bad_request, crate_not_found, custom};
 use crate::views::EncodableCrate;
+use crate::worker::jobs;
 use anyhow::Context;
 use axum::{Extension, Json};
+use chrono::Utc;
+use crates_io_worker::BackgroundJob;
 use diesel::prelude::*;
 use diesel_async::scoped_futures::ScopedFutureExt;
 use diesel_async::{AsyncConnection, RunQueryDsl};
 use http::{StatusCode, request::Parts};
-use serde::{Deserialize, Serialize};
-use tracing::{info, warn};
+use serde::{Deserialize, Deserializer, Serialize};
+use tracing::{error, info, warn};
+
+const MAX_DEPRECATION_REASON_LENGTH: usize = 1000;
 
 #[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct PatchRequest {
@@ -29,6 +34,34 @@ pub struct PatchRequestCrate {
     /// Whether this crate can only be published via Trusted Publishing.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub trustpub_only: Option<bool>,
+
+    /// Marks this crate as deprecated, or removes the deprecation if set to
+    /// `null`.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[schema(value_type = Option<PatchRequestDeprecation>)]
+    pub deprecation: Option<Option<PatchRequestDeprecation>>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct PatchRequestDeprecation {
+    /// The reason why this crate is deprecated.
+    #[schema(example = "This crate is no longer maintained.")]
+    pub reason: String,
+
+    /// The name of the crate that should be used instead, if any.
+    #[serde(default)]
+    #[schema(example = "serde_json")]
+    pub successor: Option<String>,
+}
+
+/// Distinguishes a missing field (`None`) from an explicit `null`
+/// (`Some(None)`).
+fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
 }
 
 #[derive(Debug, Serialize, utoipa::ToSchema)]
@@ -64,17 +97,30 @@ pub async fn update_crate(
     let krate = path.load_crate(&mut conn).await?;
 
     // Check that the user is authenticated with appropriate permissions
-    let auth = AuthCheck::default()
-        .with_endpoint_scope(EndpointScope::TrustedPublishing)
-        .for_crate(&krate.name)
-        .check(&req, &mut conn)
-        .await?;
+    let mut auth_check = AuthCheck::default().for_crate(&krate.name);
+    if body.krate.trustpub_only.is_some() {
+        auth_check = auth_check.with_endpoint_scope(EndpointScope::ManageTrustpub);
+    }
+    if body.krate.deprecation.is_some() || body.krate.trustpub_only.is_none() {
+        auth_check = auth_check.with_endpoint_scope(EndpointScope::UpdateMetadata);
+    }
+    let auth = auth_check.check(&req, &mut conn).await?;
 
     auth.reject_legacy_tokens()?;
 
     // Update crate settings in a transaction
     conn.transaction(|conn| {
-        update_inner(conn, &app, &krate, auth.user(), &real_ip, body).scope_boxed()
+        let api_token_id = auth.api_token_id();
+        update_inner(
+            conn,
+            &app,
+            &krate,
+            auth.user(),
+            api_token_id,
+            &real_ip,
+            body,
+        )
+        .scope_boxed()
     })
     .await
 }
@@ -84,6 +130,7 @@ async fn update_inner(
     app: &AppState,
     krate: &Crate,
     user: &User,
+    api_token_id: Option<i32>,
     real_ip: &RealIp,
     body: PatchRequest,
 ) -> AppResult<Json<PatchResponse>> {
@@ -114,6 +161,17 @@ async fn update_inner(
             .execute(conn)
             .await?;
 
+        NewCrateAuditAction::builder()
+            .crate_id(krate.id)
+            .crate_name(&krate.name)
+            .user_id(user.id)
+            .maybe_api_token_id(api_token_id)
+            .action(CrateAction::TrustpubOnlyChange)
+            .details(serde_json::json!({ "trustpub_only": trustpub_only }))
+            .build()
+            .insert(conn)
+            .await?;
+
         // Audit log the setting change
         info!(
             target: "audit",
@@ -144,6 +202,11 @@ async fn update_inner(
         }
     }
 
+    // Update the deprecation state if provided
+    if let Some(deprecation) = body.krate.deprecation {
+        update_deprecation(conn, krate, user, api_token_id, real_ip, deprecation).await?;
+    }
+
     // Reload the crate to get updated data
     let (krate, downloads, recent_downloads, default_version, yanked, num_versions): (
         Crate,
@@ -189,6 +252,143 @@ async fn update_inner(
     }))
 }
 
+async fn update_deprecation(
+    conn: &mut diesel_async::AsyncPgConnection,
+    krate: &Crate,
+    user: &User,
+    api_token_id: Option<i32>,
+    real_ip: &RealIp,
+    deprecation: Option<PatchRequestDeprecation>,
+) -> AppResult<()> {
+    let Some(deprecation) = deprecation else {
+        if krate.deprecated_at.is_none() {
+            return Ok(());
+        }
+
+        diesel::update(crates::table)
+            .filter(crates::id.eq(krate.id))
+            .set((
+                crates::deprecated_at.eq(None::<chrono::DateTime<Utc>>),
+                crates::deprecation_reason.eq(None::<String>),
+                crates::deprecation_successor.eq(None::<String>),
+            ))
+            .execute(conn)
+            .await?;
+
+        NewCrateAuditAction::builder()
+            .crate_id(krate.id)
+            .crate_name(&krate.name)
+            .user_id(user.id)
+            .maybe_api_token_id(api_token_id)
+            .action(CrateAction::Undeprecate)
+            .build()
+            .insert(conn)
+            .await?;
+
+        info!(
+            target: "audit",
+            action = "crate_undeprecate",
+            krate.name = %krate.name,
+            network.client.ip = %**real_ip,
+            usr.id = user.id,
+            usr.name = %user.gh_login,
+            "User {} removed the deprecation of crate {}",
+            user.gh_login,
+            krate.name
+        );
+
+        return enqueue_feed_sync(conn, krate).await;
+    };
+
+    let reason = deprecation.reason.trim();
+    if reason.is_empty() {
+        return Err(bad_request("The deprecation reason must not be empty"));
+    }
+    if reason.chars().count() > MAX_DEPRECATION_REASON_LENGTH {
+        let detail = format!(
+            "The deprecation reason is too long. A maximum of {MAX_DEPRECATION_REASON_LENGTH} characters are allowed."
+        );
+        return Err(bad_request(detail));
+    }
+
+    let successor = match deprecation.successor.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(successor) => {
+            let successor = Crate::by_name(successor)
+                .select(crates::name)
+                .first::<String>(conn)
+                .await
+                .optional()?
+                .ok_or_else(|| {
+                    bad_request(format!("The successor crate `{successor}` does not exist"))
+                })?;
+
+            if successor == krate.name {
+                return Err(bad_request("A crate can not be its own successor"));
+            }
+
+            Some(successor)
+        }
+    };
+
+    let unchanged = krate.deprecated_at.is_some()
+        && krate.deprecation_reason.as_deref() == Some(reason)
+        && krate.deprecation_successor == successor;
+    if unchanged {
+        return Ok(());
+    }
+
+    let deprecated_at = krate.deprecated_at.unwrap_or_else(Utc::now);
+
+    diesel::update(crates::table)
+        .filter(crates::id.eq(krate.id))
+        .set((
+            crates::deprecated_at.eq(deprecated_at),
+            crates::deprecation_reason.eq(reason),
+            crates::deprecation_successor.eq(&successor),
+        ))
+        .execute(conn)
+        .await?;
+
+    NewCrateAuditAction::builder()
+        .crate_id(krate.id)
+        .crate_name(&krate.name)
+        .user_id(user.id)
+        .maybe_api_token_id(api_token_id)
+        .action(CrateAction::Deprecate)
+        .details(serde_json::json!({ "reason": reason, "successor": successor }))
+        .build()
+        .insert(conn)
+        .await?;
+
+    info!(
+        target: "audit",
+        action = "crate_deprecate",
+        krate.name = %krate.name,
+        krate.successor = successor.as_deref(),
+        network.client.ip = %**real_ip,
+        usr.id = user.id,
+        usr.name = %user.gh_login,
+        "User {} deprecated crate {}",
+        user.gh_login,
+        krate.name
+    );
+
+    enqueue_feed_sync(conn, krate).await
+}
+
+async fn enqueue_feed_sync(
+    conn: &mut diesel_async::AsyncPgConnection,
+    krate: &Crate,
+) -> AppResult<()> {
+    let crate_feed_job = jobs::rss::SyncCrateFeed::new(krate.name.clone());
+    if let Err(error) = crate_feed_job.enqueue(conn).await {
+        error!("Failed to enqueue `rss::SyncCrateFeed` job: {error}");
+    }
+
+    Ok(())
+}
+
 #[derive(Serialize)]
 struct TrustpubOnlyChangedEmail<'a> {
     /// The GitHub login of the email recipient.
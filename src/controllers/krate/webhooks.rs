@@ -0,0 +1,581 @@
+//! Endpoints for managing the outbound webhooks of a crate.
+//!
+//! The events are delivered by the [`DeliverWebhook`](crate::worker::jobs::DeliverWebhook)
+//! background job.
+
+use crate::Env;
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::helpers::authorization::Rights;
+use crate::controllers::helpers::pagination::{
+    Page, PaginationOptions, PaginationQueryParams, encode_seek,
+};
+use crate::controllers::krate::{CratePath, load_crate};
+use crate::models::token::EndpointScope;
+use crate::models::{
+    Crate, CrateAction, CrateWebhook, CrateWebhookDelivery, NewCrateAuditAction, NewCrateWebhook,
+    User, WebhookEvent,
+};
+use crate::schema::{crate_webhook_deliveries, crate_webhooks};
+use crate::util::RequestUtils;
+use crate::util::errors::{AppResult, bad_request, custom, not_found};
+use crate::views::{EncodableCrateWebhook, EncodableCrateWebhookDelivery};
+use axum::Json;
+use axum::extract::{FromRequest, FromRequestParts, Path};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use http::StatusCode;
+use http::request::Parts;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use url::Url;
+use utoipa::IntoParams;
+
+const MAX_WEBHOOKS_PER_CRATE: i64 = 5;
+const MAX_URL_LENGTH: usize = 2000;
+const MIN_SECRET_LENGTH: usize = 16;
+const MAX_SECRET_LENGTH: usize = 256;
+
+#[derive(Deserialize, FromRequestParts, IntoParams)]
+#[into_params(parameter_in = Path)]
+#[from_request(via(Path))]
+pub struct WebhookPath {
+    /// Name of the crate
+    pub name: String,
+    /// ID of the webhook
+    pub id: i32,
+}
+
+#[derive(Debug, Deserialize, FromRequest, utoipa::ToSchema)]
+#[from_request(via(Json))]
+pub struct CreateRequest {
+    #[schema(inline)]
+    pub webhook: NewWebhook,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct NewWebhook {
+    /// The URL that event payloads are sent to.
+    #[schema(example = "https://example.com/crates-io-webhook")]
+    pub url: String,
+
+    /// The shared secret that is used to sign the event payloads.
+    ///
+    /// The signature is sent in the `X-Crates-Io-Signature-256` header as
+    /// `sha256=` followed by the hex-encoded HMAC-SHA256 of the request body.
+    #[schema(example = "correct horse battery staple")]
+    pub secret: String,
+
+    /// The events that the webhook is subscribed to.
+    pub events: Vec<WebhookEvent>,
+}
+
+#[derive(Debug, Deserialize, FromRequest, utoipa::ToSchema)]
+#[from_request(via(Json))]
+pub struct UpdateRequest {
+    #[schema(inline)]
+    pub webhook: WebhookUpdate,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct WebhookUpdate {
+    /// Whether events should be delivered to the webhook.
+    ///
+    /// Enabling a webhook that was disabled due to repeated delivery
+    /// failures also resets its failure counter.
+    #[schema(example = true)]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct WebhookResponse {
+    pub webhook: EncodableCrateWebhook,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ListResponse {
+    pub webhooks: Vec<EncodableCrateWebhook>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct DeliveriesResponse {
+    /// The deliveries of the webhook, newest first.
+    pub deliveries: Vec<EncodableCrateWebhookDelivery>,
+
+    #[schema(inline)]
+    pub meta: DeliveriesResponseMeta,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct DeliveriesResponseMeta {
+    /// The total number of deliveries of the webhook.
+    #[schema(example = 42)]
+    pub total: i64,
+
+    /// Query string to the next page of results, if any.
+    #[schema(example = "?seek=abc123")]
+    pub next_page: Option<String>,
+}
+
+/// List the webhooks of a crate.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/webhooks",
+    params(CratePath),
+    security(("api_token" = []), ("cookie" = [])),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response", body = inline(ListResponse))),
+)]
+pub async fn list_webhooks(
+    app: AppState,
+    path: CratePath,
+    parts: Parts,
+) -> AppResult<Json<ListResponse>> {
+    let mut conn = app.db_read_prefer_primary().await?;
+
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::ReadPrivate)
+        .for_crate(&path.name)
+        .check(&parts, &mut conn)
+        .await?;
+
+    let krate = path.load_crate(&mut conn).await?;
+    check_ownership(&app, &mut conn, auth.user(), &krate).await?;
+
+    let webhooks = CrateWebhook::query()
+        .filter(crate_webhooks::crate_id.eq(krate.id))
+        .order(crate_webhooks::id)
+        .load(&mut conn)
+        .await?;
+
+    let webhooks = webhooks.into_iter().map(Into::into).collect();
+    Ok(Json(ListResponse { webhooks }))
+}
+
+/// Register a new webhook for a crate.
+///
+/// The webhook receives signed JSON payloads via `POST` requests for all of
+/// the selected events.
+#[utoipa::path(
+    post,
+    path = "/api/v1/crates/{name}/webhooks",
+    params(CratePath),
+    request_body = inline(CreateRequest),
+    security(("api_token" = []), ("cookie" = [])),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response", body = inline(WebhookResponse))),
+)]
+pub async fn create_webhook(
+    app: AppState,
+    path: CratePath,
+    parts: Parts,
+    json: CreateRequest,
+) -> AppResult<Json<WebhookResponse>> {
+    let json_webhook = json.webhook;
+
+    validate_url(&json_webhook.url, app.config.env())?;
+
+    let secret_length = json_webhook.secret.chars().count();
+    if !(MIN_SECRET_LENGTH..=MAX_SECRET_LENGTH).contains(&secret_length) {
+        let message = format!(
+            "The webhook secret must be between {MIN_SECRET_LENGTH} and {MAX_SECRET_LENGTH} characters long"
+        );
+        return Err(bad_request(message));
+    }
+
+    let mut events = Vec::with_capacity(json_webhook.events.len());
+    for event in json_webhook.events {
+        if !events.contains(&event) {
+            events.push(event);
+        }
+    }
+    if events.is_empty() {
+        return Err(bad_request("At least one webhook event must be selected"));
+    }
+
+    let mut conn = app.db_write().await?;
+
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::UpdateMetadata)
+        .for_crate(&path.name)
+        .check(&parts, &mut conn)
+        .await?;
+    let auth_user = auth.user();
+
+    let krate = load_crate(&mut conn, &path.name).await?;
+    check_ownership(&app, &mut conn, auth_user, &krate).await?;
+
+    let webhook_count = CrateWebhook::count_for_crate(&mut conn, krate.id).await?;
+    if webhook_count >= MAX_WEBHOOKS_PER_CRATE {
+        let message = format!(
+            "This crate already has the maximum number of webhooks ({MAX_WEBHOOKS_PER_CRATE})"
+        );
+        return Err(custom(StatusCode::CONFLICT, message));
+    }
+
+    let details = json!({ "url": json_webhook.url, "events": events });
+
+    let new_webhook = NewCrateWebhook {
+        crate_id: krate.id,
+        url: &json_webhook.url,
+        secret: &json_webhook.secret,
+        events,
+        created_by: auth_user.id,
+    };
+
+    let (crate_id, crate_name) = (krate.id, krate.name.as_str());
+    let (user_id, api_token_id) = (auth_user.id, auth.api_token_id());
+
+    let webhook = conn
+        .transaction(|conn| {
+            async move {
+                let webhook = new_webhook.insert(conn).await?;
+
+                NewCrateAuditAction::builder()
+                    .crate_id(crate_id)
+                    .crate_name(crate_name)
+                    .user_id(user_id)
+                    .maybe_api_token_id(api_token_id)
+                    .action(CrateAction::WebhookCreate)
+                    .details(details)
+                    .build()
+                    .insert(conn)
+                    .await?;
+
+                Ok::<_, diesel::result::Error>(webhook)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    let webhook = webhook.into();
+    Ok(Json(WebhookResponse { webhook }))
+}
+
+/// Enable or disable a webhook of a crate.
+#[utoipa::path(
+    patch,
+    path = "/api/v1/crates/{name}/webhooks/{id}",
+    params(WebhookPath),
+    request_body = inline(UpdateRequest),
+    security(("api_token" = []), ("cookie" = [])),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response", body = inline(WebhookResponse))),
+)]
+pub async fn update_webhook(
+    app: AppState,
+    path: WebhookPath,
+    parts: Parts,
+    json: UpdateRequest,
+) -> AppResult<Json<WebhookResponse>> {
+    let enabled = json.webhook.enabled;
+
+    let mut conn = app.db_write().await?;
+
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::UpdateMetadata)
+        .for_crate(&path.name)
+        .check(&parts, &mut conn)
+        .await?;
+    let auth_user = auth.user();
+
+    let krate = load_crate(&mut conn, &path.name).await?;
+    check_ownership(&app, &mut conn, auth_user, &krate).await?;
+
+    let webhook = load_webhook(&mut conn, &krate, path.id).await?;
+    if webhook.disabled_at.is_none() == enabled {
+        return Ok(Json(WebhookResponse {
+            webhook: webhook.into(),
+        }));
+    }
+
+    let (crate_id, crate_name) = (krate.id, krate.name.as_str());
+    let (user_id, api_token_id) = (auth_user.id, auth.api_token_id());
+    let details = json!({ "url": webhook.url, "enabled": enabled });
+
+    let webhook = conn
+        .transaction(|conn| {
+            async move {
+                let query = diesel::update(crate_webhooks::table.find(webhook.id));
+                let webhook = if enabled {
+                    query
+                        .set((
+                            crate_webhooks::disabled_at.eq(None::<chrono::DateTime<Utc>>),
+                            crate_webhooks::consecutive_failures.eq(0),
+                        ))
+                        .returning(CrateWebhook::as_returning())
+                        .get_result(conn)
+                        .await?
+                } else {
+                    query
+                        .set(crate_webhooks::disabled_at.eq(Utc::now()))
+                        .returning(CrateWebhook::as_returning())
+                        .get_result(conn)
+                        .await?
+                };
+
+                NewCrateAuditAction::builder()
+                    .crate_id(crate_id)
+                    .crate_name(crate_name)
+                    .user_id(user_id)
+                    .maybe_api_token_id(api_token_id)
+                    .action(CrateAction::WebhookUpdate)
+                    .details(details)
+                    .build()
+                    .insert(conn)
+                    .await?;
+
+                Ok::<_, diesel::result::Error>(webhook)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    let webhook = webhook.into();
+    Ok(Json(WebhookResponse { webhook }))
+}
+
+/// Delete a webhook of a crate.
+///
+/// Pending deliveries of the webhook are discarded.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/crates/{name}/webhooks/{id}",
+    params(WebhookPath),
+    security(("api_token" = []), ("cookie" = [])),
+    tag = "crates",
+    responses((status = 204, description = "Successful Response")),
+)]
+pub async fn delete_webhook(
+    app: AppState,
+    path: WebhookPath,
+    parts: Parts,
+) -> AppResult<StatusCode> {
+    let mut conn = app.db_write().await?;
+
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::UpdateMetadata)
+        .for_crate(&path.name)
+        .check(&parts, &mut conn)
+        .await?;
+    let auth_user = auth.user();
+
+    let krate = load_crate(&mut conn, &path.name).await?;
+    check_ownership(&app, &mut conn, auth_user, &krate).await?;
+
+    let webhook = load_webhook(&mut conn, &krate, path.id).await?;
+
+    let (crate_id, crate_name) = (krate.id, krate.name.as_str());
+    let (user_id, api_token_id) = (auth_user.id, auth.api_token_id());
+    let details = json!({ "url": webhook.url });
+
+    conn.transaction(|conn| {
+        async move {
+            diesel::delete(crate_webhooks::table.find(webhook.id))
+                .execute(conn)
+                .await?;
+
+            NewCrateAuditAction::builder()
+                .crate_id(crate_id)
+                .crate_name(crate_name)
+                .user_id(user_id)
+                .maybe_api_token_id(api_token_id)
+                .action(CrateAction::WebhookDelete)
+                .details(details)
+                .build()
+                .insert(conn)
+                .await?;
+
+            Ok::<_, diesel::result::Error>(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List the deliveries of a webhook.
+///
+/// The delivery log contains the payload, the number of delivery attempts
+/// and the outcome of the last attempt for every event that was sent to the
+/// webhook.
+#[utoipa::path(
+    get,
+    path = "/api/v1/crates/{name}/webhooks/{id}/deliveries",
+    params(WebhookPath, PaginationQueryParams),
+    security(("api_token" = []), ("cookie" = [])),
+    tag = "crates",
+    responses((status = 200, description = "Successful Response", body = inline(DeliveriesResponse))),
+)]
+pub async fn list_webhook_deliveries(
+    app: AppState,
+    path: WebhookPath,
+    parts: Parts,
+) -> AppResult<Json<DeliveriesResponse>> {
+    let mut conn = app.db_read_prefer_primary().await?;
+
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::ReadPrivate)
+        .for_crate(&path.name)
+        .check(&parts, &mut conn)
+        .await?;
+
+    let krate = load_crate(&mut conn, &path.name).await?;
+    check_ownership(&app, &mut conn, auth.user(), &krate).await?;
+
+    let webhook = load_webhook(&mut conn, &krate, path.id).await?;
+
+    let pagination = PaginationOptions::builder()
+        .enable_seek(true)
+        .enable_pages(false)
+        .gather(&parts)?;
+
+    let (deliveries, total, next_page) =
+        list_deliveries(&mut conn, webhook.id, &pagination, &parts).await?;
+
+    let deliveries = deliveries.into_iter().map(Into::into).collect();
+
+    Ok(Json(DeliveriesResponse {
+        deliveries,
+        meta: DeliveriesResponseMeta { total, next_page },
+    }))
+}
+
+async fn check_ownership(
+    app: &AppState,
+    conn: &mut AsyncPgConnection,
+    user: &User,
+    krate: &Crate,
+) -> AppResult<()> {
+    let owners = krate.owners(conn).await?;
+    let encryption = &app.config.gh_token_encryption;
+    match Rights::get(user, &*app.github, &owners, encryption).await? {
+        Rights::Full => Ok(()),
+        Rights::Publish => Err(custom(
+            StatusCode::FORBIDDEN,
+            "team members don't have permission to manage webhooks",
+        )),
+        Rights::None => Err(custom(
+            StatusCode::FORBIDDEN,
+            "only owners have permission to manage webhooks",
+        )),
+    }
+}
+
+async fn load_webhook(
+    conn: &mut AsyncPgConnection,
+    krate: &Crate,
+    id: i32,
+) -> AppResult<CrateWebhook> {
+    CrateWebhook::query()
+        .filter(crate_webhooks::id.eq(id))
+        .filter(crate_webhooks::crate_id.eq(krate.id))
+        .first(conn)
+        .await
+        .optional()?
+        .ok_or_else(not_found)
+}
+
+fn validate_url(url: &str, env: Env) -> AppResult<()> {
+    if url.len() > MAX_URL_LENGTH {
+        let message = format!("The webhook URL must not exceed {MAX_URL_LENGTH} characters");
+        return Err(bad_request(message));
+    }
+
+    let url = Url::parse(url).map_err(|_| bad_request("Invalid webhook URL"))?;
+
+    // Plain HTTP is only allowed outside of production to support local
+    // testing setups.
+    match url.scheme() {
+        "https" => Ok(()),
+        "http" if env != Env::Production => Ok(()),
+        _ => Err(bad_request("Webhook URLs must use the `https` scheme")),
+    }
+}
+
+async fn list_deliveries(
+    conn: &mut AsyncPgConnection,
+    webhook_id: i32,
+    options: &PaginationOptions,
+    req: &Parts,
+) -> AppResult<(Vec<CrateWebhookDelivery>, i64, Option<String>)> {
+    use seek::*;
+
+    let seek = Seek::Id;
+
+    assert!(
+        !matches!(&options.page, Page::Numeric(_)),
+        "?page= is not supported"
+    );
+
+    let mut query = CrateWebhookDelivery::query()
+        .filter(crate_webhook_deliveries::webhook_id.eq(webhook_id))
+        .order(crate_webhook_deliveries::id.desc())
+        .limit(options.per_page)
+        .into_boxed();
+
+    if let Some(SeekPayload::Id(Id { id })) = seek.after(&options.page)? {
+        query = query.filter(crate_webhook_deliveries::id.lt(id));
+    }
+
+    let data: Vec<CrateWebhookDelivery> = query.load(conn).await?;
+
+    let next_page = next_seek_params(&data, options, |last| seek.to_payload(last))?
+        .map(|p| req.query_with_params(p));
+
+    // Avoid the count query if we're on the first page and got fewer results than requested
+    let total =
+        if matches!(options.page, Page::Unspecified) && data.len() < options.per_page as usize {
+            data.len() as i64
+        } else {
+            crate_webhook_deliveries::table
+                .filter(crate_webhook_deliveries::webhook_id.eq(webhook_id))
+                .count()
+                .get_result(conn)
+                .await?
+        };
+
+    Ok((data, total, next_page))
+}
+
+fn next_seek_params<T, S, F>(
+    records: &[T],
+    options: &PaginationOptions,
+    f: F,
+) -> AppResult<Option<IndexMap<String, String>>>
+where
+    F: Fn(&T) -> S,
+    S: serde::Serialize,
+{
+    if records.len() < options.per_page as usize {
+        return Ok(None);
+    }
+
+    let seek = f(records.last().unwrap());
+    let mut opts = IndexMap::new();
+    opts.insert("seek".into(), encode_seek(seek)?);
+    Ok(Some(opts))
+}
+
+mod seek {
+    use crate::controllers::helpers::pagination::seek;
+    use crate::models::CrateWebhookDelivery;
+
+    seek!(
+        pub enum Seek {
+            Id { id: i64 },
+        }
+    );
+
+    impl Seek {
+        pub(crate) fn to_payload(&self, record: &CrateWebhookDelivery) -> SeekPayload {
+            match *self {
+                Seek::Id => SeekPayload::Id(Id { id: record.id }),
+            }
+        }
+    }
+}
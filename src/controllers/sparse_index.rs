@@ -0,0 +1,221 @@
+//! Serves the sparse index directly from the API server.
+//!
+//! On crates.io the sparse index is uploaded to the index storage by the
+//! `SyncToSparseIndex` background job and served by a CDN. For self-hosted
+//! deployments and integration tests, the API server can instead generate the
+//! index files on demand from the database (see
+//! [`crate::config::Server::serve_sparse_index`]).
+
+use crate::app::AppState;
+use crate::index::{CHANGES_EPOCH, CHANGES_PAGE_SIZE, IndexChangesPage, SparseIndexFile};
+use crate::models::IndexChange;
+use crate::storage::{CACHE_CONTROL_INDEX, CONTENT_TYPE_INDEX};
+use crate::util::errors::{AppResult, bad_request, internal, not_found};
+use crate::views::EncodableIndexChange;
+use axum::Json;
+use axum::extract::{FromRequestParts, Path, Query};
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use crates_io_index::Repository;
+use http::{HeaderMap, HeaderValue, StatusCode, header};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::LazyLock;
+
+/// The default number of changes returned by [`list_index_changes`].
+const DEFAULT_CHANGES_LIMIT: i64 = 100;
+
+/// The `config.json` file only changes with the server configuration, so the
+/// server start time is used as its modification time.
+static STARTED_AT: LazyLock<DateTime<Utc>> = LazyLock::new(Utc::now);
+
+/// Returns the `config.json` file of the sparse index.
+pub async fn get_index_config(state: AppState, headers: HeaderMap) -> AppResult<Response> {
+    let api_url = state
+        .config
+        .sparse_index_api_url
+        .as_str()
+        .trim_end_matches('/');
+    let config = json!({
+        "dl": format!("{api_url}/api/v1/crates"),
+        "api": api_url,
+    });
+
+    let content = serde_json::to_string_pretty(&config)?;
+    let file = SparseIndexFile::new(content, *STARTED_AT);
+    Ok(file_response(&file, &headers, "application/json"))
+}
+
+/// Returns the sparse index file of a crate, e.g. `/index/se/rd/serde`.
+pub async fn get_index_file(
+    state: AppState,
+    Path(path): Path<String>,
+    headers: HeaderMap,
+) -> AppResult<Response> {
+    let Some((_, name)) = path.rsplit_once('/') else {
+        return Err(not_found());
+    };
+
+    // Only the canonical lowercase paths are served, like on the CDN.
+    if Repository::relative_index_file_for_url(name) != path {
+        return Err(not_found());
+    }
+
+    let mut conn = state.db_read().await?;
+    let include_pubtime = state.config.index_include_pubtime;
+    let file = state
+        .sparse_index_cache
+        .get_or_load(name, &mut conn, include_pubtime)
+        .await
+        .map_err(|error| internal(format!("Failed to load index file: {error:#}")))?
+        .ok_or_else(not_found)?;
+
+    Ok(file_response(&file, &headers, CONTENT_TYPE_INDEX))
+}
+
+/// Returns a page of the index changes feed, e.g. `/index/changes/1/0.json`.
+pub async fn get_index_changes_page(
+    state: AppState,
+    Path((epoch, file)): Path<(u32, String)>,
+    headers: HeaderMap,
+) -> AppResult<Response> {
+    let page = file
+        .strip_suffix(".json")
+        .and_then(|page| page.parse::<i64>().ok())
+        .filter(|page| *page >= 0);
+
+    let Some(page) = page.filter(|_| epoch == CHANGES_EPOCH) else {
+        return Err(not_found());
+    };
+
+    let mut conn = state.db_read().await?;
+    let content = IndexChangesPage::load(&mut conn, page).await?;
+    let last_modified = content.changes.last().map(|change| change.time);
+
+    let content = serde_json::to_string(&content)?;
+    let file = SparseIndexFile::new(content, last_modified.unwrap_or(*STARTED_AT));
+    Ok(file_response(&file, &headers, "application/json"))
+}
+
+#[derive(Debug, Deserialize, FromRequestParts, utoipa::IntoParams)]
+#[from_request(via(Query))]
+#[into_params(parameter_in = Query)]
+pub struct ListChangesQueryParams {
+    /// Only return changes with a sequence number greater than this value.
+    ///
+    /// Defaults to `0`, which returns the changes from the start of the feed.
+    since: Option<i64>,
+
+    /// The maximum number of changes to return.
+    ///
+    /// Defaults to `100`, and must not be greater than `1000`.
+    limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ListChangesResponse {
+    /// The epoch of the changes feed.
+    ///
+    /// The sequence numbers are only comparable within the same epoch. If the
+    /// epoch changes, mirrors need to do a full resync.
+    #[schema(example = 1)]
+    pub epoch: u32,
+
+    /// The list of index changes, ordered by sequence number.
+    pub changes: Vec<EncodableIndexChange>,
+
+    #[schema(inline)]
+    pub meta: ListChangesMeta,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ListChangesMeta {
+    /// The value of the `since` query parameter for the next request.
+    #[schema(example = 42)]
+    pub next_since: i64,
+}
+
+/// List the changes to the sparse index.
+///
+/// This endpoint allows mirrors of the sparse index to sync incrementally by
+/// only fetching the index files that were modified since their last sync.
+#[utoipa::path(
+    get,
+    path = "/api/v1/index/changes",
+    params(ListChangesQueryParams),
+    tag = "index",
+    responses((status = 200, description = "Successful Response", body = inline(ListChangesResponse))),
+)]
+pub async fn list_index_changes(
+    state: AppState,
+    params: ListChangesQueryParams,
+) -> AppResult<Json<ListChangesResponse>> {
+    let since = params.since.unwrap_or_default();
+    let limit = params.limit.unwrap_or(DEFAULT_CHANGES_LIMIT);
+    if !(1..=CHANGES_PAGE_SIZE).contains(&limit) {
+        let message = format!("limit must be between 1 and {CHANGES_PAGE_SIZE}");
+        return Err(bad_request(message));
+    }
+
+    let mut conn = state.db_read().await?;
+    let changes = IndexChange::after(&mut conn, since, limit).await?;
+
+    let next_since = changes.last().map(|change| change.seq).unwrap_or(since);
+    let changes = changes.into_iter().map(Into::into).collect();
+
+    Ok(Json(ListChangesResponse {
+        epoch: CHANGES_EPOCH,
+        changes,
+        meta: ListChangesMeta { next_since },
+    }))
+}
+
+fn file_response(file: &SparseIndexFile, headers: &HeaderMap, content_type: &str) -> Response {
+    let last_modified = format_http_date(file.last_modified);
+
+    let mut response = if is_not_modified(file, headers) {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        let content_type = HeaderValue::from_str(content_type).unwrap();
+        let response_headers = [(header::CONTENT_TYPE, content_type)];
+        (response_headers, file.content.clone()).into_response()
+    };
+
+    let response_headers = response.headers_mut();
+    response_headers.insert(header::ETAG, HeaderValue::from_str(&file.etag).unwrap());
+    response_headers.insert(
+        header::LAST_MODIFIED,
+        HeaderValue::from_str(&last_modified).unwrap(),
+    );
+    response_headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static(CACHE_CONTROL_INDEX),
+    );
+
+    response
+}
+
+/// Checks the `If-None-Match` and `If-Modified-Since` request headers
+/// against the file, following the precedence rules of RFC 9110.
+fn is_not_modified(file: &SparseIndexFile, headers: &HeaderMap) -> bool {
+    if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH) {
+        let Ok(if_none_match) = if_none_match.to_str() else {
+            return false;
+        };
+
+        return if_none_match
+            .split(',')
+            .map(|etag| etag.trim().trim_start_matches("W/"))
+            .any(|etag| etag == "*" || etag == file.etag);
+    }
+
+    headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+        .is_some_and(|since| file.last_modified.timestamp() <= since.timestamp())
+}
+
+fn format_http_date(date: DateTime<Utc>) -> String {
+    date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
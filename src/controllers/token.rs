@@ -1,14 +1,15 @@
 use crate::email::EmailMessage;
-use crate::models::ApiToken;
-use crate::schema::api_tokens;
+use crate::models::token::ApiTokenUsage;
+use crate::models::{ApiToken, Team, User};
+use crate::schema::{api_tokens, teams};
 use crate::views::EncodableApiTokenWithToken;
 use anyhow::Context;
 
 use crate::app::AppState;
 use crate::auth::AuthCheck;
 use crate::middleware::real_ip::RealIp;
-use crate::models::token::{CrateScope, EndpointScope};
-use crate::util::errors::{AppResult, bad_request, custom};
+use crate::models::token::{CrateScope, EndpointScope, IpRange};
+use crate::util::errors::{AppResult, bad_request, custom, forbidden};
 use crate::util::token::PlainToken;
 use axum::Json;
 use axum::extract::{Path, Query};
@@ -20,7 +21,7 @@ use diesel::data_types::PgInterval;
 use diesel::dsl::{IntervalDsl, now};
 use diesel::prelude::*;
 use diesel::sql_types::Timestamptz;
-use diesel_async::RunQueryDsl;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use http::request::Parts;
 use http::{StatusCode, header};
 use minijinja::context;
@@ -87,6 +88,8 @@ pub struct NewApiToken {
     crate_scopes: Option<Vec<String>>,
     endpoint_scopes: Option<Vec<String>>,
     expired_at: Option<DateTime<Utc>>,
+    allowed_ips: Option<Vec<String>>,
+    team: Option<String>,
 }
 
 /// The incoming serialization format for the `ApiToken` model.
@@ -101,6 +104,43 @@ pub struct CreateResponse {
 }
 
 /// Create a new API token.
+///
+/// Tokens can optionally be restricted to a set of crates via
+/// `crate_scopes` (e.g. `serde` or `serde-*`) and to a set of endpoints via
+/// `endpoint_scopes`. Tokens without endpoint scopes are legacy tokens, which
+/// are allowed to use most endpoints. The following endpoint scopes are
+/// available:
+///
+/// - `publish-new`: Publish new crates.
+/// - `publish-update`: Publish new versions of existing crates.
+/// - `publish-release`: Release staged crate versions.
+/// - `yank`: Yank and unyank crate versions.
+/// - `change-owners`: Invite new crate owners, remove existing ones, and
+///   accept or decline crate owner invitations.
+/// - `read-private`: Read private information like API tokens, crate owner
+///   invitations, audit logs, webhooks, and staged crate versions.
+/// - `update-metadata`: Update crate and version metadata like deprecations,
+///   yank messages, and webhooks.
+/// - `manage-trustpub`: Manage Trusted Publishing configurations and the
+///   `trustpub_only` setting of crates. `trusted-publishing` is a deprecated
+///   alias of this scope.
+/// - `delete`: Delete crates. Crate deletion is not available to legacy
+///   tokens.
+///
+/// An empty `endpoint_scopes` list creates a token without any endpoint
+/// scopes. Unlike legacy tokens, such a token cannot be used for any of the
+/// scoped endpoints.
+///
+/// Tokens can also be restricted to a set of client IP addresses via
+/// `allowed_ips`, a list of CIDR ranges (e.g. `192.0.2.0/24`) or single IP
+/// addresses. Requests from other IP addresses are rejected.
+///
+/// Maintainers of a GitHub team can create tokens that are owned by the team
+/// by passing the team login (e.g. `github:rust-lang:release`) as `team`.
+/// Team tokens can only be used for crates that are owned by the team, and
+/// only support the `publish-update`, `publish-release` and `yank` endpoint
+/// scopes. Publishes and yanks are recorded as performed by the team, and the
+/// tokens are revoked once the creator is no longer a maintainer of the team.
 #[utoipa::path(
     put,
     path = "/api/v1/me/tokens",
@@ -183,6 +223,52 @@ pub async fn create_api_token(
         .transpose()
         .map_err(|_err| bad_request("invalid endpoint scope"))?;
 
+    let endpoint_scopes = endpoint_scopes.map(|scopes| {
+        let mut unique_scopes = Vec::with_capacity(scopes.len());
+        for scope in scopes {
+            if !unique_scopes.contains(&scope) {
+                unique_scopes.push(scope);
+            }
+        }
+        unique_scopes
+    });
+
+    let allowed_ips = new
+        .api_token
+        .allowed_ips
+        .map(|ranges| {
+            ranges
+                .into_iter()
+                .map(IpRange::try_from)
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()
+        .map_err(|_err| bad_request("invalid allowed IP range"))?;
+
+    if allowed_ips.as_ref().is_some_and(|ranges| ranges.is_empty()) {
+        return Err(bad_request("at least one allowed IP range is required"));
+    }
+
+    let team_id = match new.api_token.team.as_deref() {
+        Some(login) => {
+            let is_supported = endpoint_scopes.as_ref().is_some_and(|scopes| {
+                scopes
+                    .iter()
+                    .all(|scope| TEAM_TOKEN_ENDPOINT_SCOPES.contains(scope))
+            });
+
+            if !is_supported {
+                return Err(bad_request(
+                    "team tokens require endpoint scopes and only support the `publish-update`, `publish-release` and `yank` scopes",
+                ));
+            }
+
+            let team = load_team_for_maintainer(&app, &mut conn, user, login).await?;
+            Some(team.id)
+        }
+        None => None,
+    };
+
     let recipient = user.email(&mut conn).await?;
 
     let plaintext = PlainToken::generate();
@@ -194,6 +280,8 @@ pub async fn create_api_token(
         .maybe_crate_scopes(crate_scopes)
         .maybe_endpoint_scopes(endpoint_scopes)
         .maybe_expired_at(new.api_token.expired_at)
+        .maybe_allowed_ips(allowed_ips)
+        .maybe_team_id(team_id)
         .build();
 
     if let Some(recipient) = recipient {
@@ -219,12 +307,22 @@ pub async fn create_api_token(
     Ok(Json(CreateResponse { api_token }))
 }
 
+/// The maximum number of usage log entries returned by [`find_api_token`].
+const MAX_USAGES: i64 = 100;
+
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct GetResponse {
     pub api_token: ApiToken,
+
+    /// The most recent usages of the token, newest first.
+    pub usages: Vec<ApiTokenUsage>,
 }
 
 /// Find API token by id.
+///
+/// The response includes the most recent usages of the token, including the
+/// IP address, endpoint and user agent of each request. Usages are kept for
+/// a limited time only.
 #[utoipa::path(
     get,
     path = "/api/v1/me/tokens/{id}",
@@ -244,7 +342,13 @@ pub async fn find_api_token(
     req: Parts,
 ) -> AppResult<Json<GetResponse>> {
     let mut conn = app.db_write().await?;
-    let auth = AuthCheck::default().check(&req, &mut conn).await?;
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::ReadPrivate)
+        .check(&req, &mut conn)
+        .await?;
+
+    auth.reject_legacy_tokens()?;
+
     let user = auth.user();
     let api_token = ApiToken::belonging_to(user)
         .find(id)
@@ -252,7 +356,9 @@ pub async fn find_api_token(
         .first(&mut conn)
         .await?;
 
-    Ok(Json(GetResponse { api_token }))
+    let usages = ApiTokenUsage::recent(&mut conn, api_token.id, MAX_USAGES).await?;
+
+    Ok(Json(GetResponse { api_token, usages }))
 }
 
 /// Revoke API token.
@@ -311,6 +417,58 @@ pub async fn revoke_current_api_token(app: AppState, req: Parts) -> AppResult<Re
     Ok(StatusCode::NO_CONTENT.into_response())
 }
 
+/// The endpoint scopes that team tokens can be created with.
+const TEAM_TOKEN_ENDPOINT_SCOPES: [EndpointScope; 3] = [
+    EndpointScope::PublishUpdate,
+    EndpointScope::PublishRelease,
+    EndpointScope::Yank,
+];
+
+/// Loads the team with the given login and checks that the user is one of
+/// its maintainers on GitHub.
+async fn load_team_for_maintainer(
+    app: &AppState,
+    conn: &mut AsyncPgConnection,
+    user: &User,
+    login: &str,
+) -> AppResult<Team> {
+    let team = Team::query()
+        .filter(teams::login.eq(login.to_lowercase()))
+        .first(conn)
+        .await
+        .optional()?
+        .ok_or_else(|| {
+            bad_request(format!(
+                "could not find the team `{login}`. Only teams that own crates can own API tokens."
+            ))
+        })?;
+
+    let token = app
+        .config
+        .gh_token_encryption
+        .decrypt(&user.gh_encrypted_token)
+        .map_err(|err| {
+            custom(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to decrypt GitHub token: {err}"),
+            )
+        })?;
+
+    let is_maintainer = app
+        .github
+        .team_membership(team.org_id, team.github_id, &user.gh_login, &token)
+        .await?
+        .is_some_and(|membership| membership.is_active_maintainer());
+
+    if !is_maintainer {
+        return Err(forbidden(
+            "only maintainers of a team can create API tokens for it",
+        ));
+    }
+
+    Ok(team)
+}
+
 async fn send_creation_email(
     emails: &crate::Emails,
     recipient: &str,
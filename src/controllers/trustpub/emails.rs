@@ -1,5 +1,5 @@
 use crate::email::EmailMessage;
-use crates_io_database::models::trustpub::{GitHubConfig, GitLabConfig};
+use crates_io_database::models::trustpub::{ForgejoConfig, GitHubConfig, GitLabConfig};
 use crates_io_database::models::{Crate, User};
 
 #[derive(Debug, Clone, Copy, serde::Serialize)]
@@ -7,6 +7,7 @@ use crates_io_database::models::{Crate, User};
 pub enum ConfigType<'a> {
     GitHub(&'a GitHubConfig),
     GitLab(&'a GitLabConfig),
+    Forgejo(&'a ForgejoConfig),
 }
 
 #[derive(serde::Serialize)]
@@ -80,6 +81,9 @@ mod tests {
             max_upload_size: None,
             max_features: None,
             trustpub_only: false,
+            deprecated_at: None,
+            deprecation_reason: None,
+            deprecation_successor: None,
         }
     }
 
@@ -93,6 +97,8 @@ mod tests {
             repository_name: "rust".into(),
             workflow_filename: "publish.yml".into(),
             environment: environment.map(String::from),
+            ref_pattern: None,
+            expires_at: None,
         }
     }
 
@@ -106,6 +112,21 @@ mod tests {
             project: "my-crate".into(),
             workflow_filepath: ".gitlab-ci.yml".into(),
             environment: environment.map(String::from),
+            issuer: "https://gitlab.com".into(),
+            ref_pattern: None,
+            expires_at: None,
+        }
+    }
+
+    fn test_forgejo_config() -> ForgejoConfig {
+        ForgejoConfig {
+            id: 1,
+            created_at: Utc::now(),
+            crate_id: 1,
+            repository_owner: "rust-lang".into(),
+            repository_owner_id: None,
+            repository_name: "my-crate".into(),
+            workflow_filename: "release.yml".into(),
         }
     }
 
@@ -137,6 +158,24 @@ mod tests {
         assert_snapshot!(rendered.body_text);
     }
 
+    #[test]
+    fn test_config_created_email_with_ref_pattern_and_expiry() {
+        let mut config = test_github_config(None);
+        config.ref_pattern = Some("refs/tags/v*".into());
+        config.expires_at = Some("2030-01-01T00:00:00Z".parse().unwrap());
+
+        let email = ConfigCreatedEmail {
+            recipient: "octocat",
+            auth_user: &test_user(),
+            krate: &test_crate(),
+            saved_config: ConfigType::GitHub(&config),
+        };
+
+        let rendered = assert_ok!(email.render());
+        assert_snapshot!(rendered.subject, @"crates.io: Trusted Publishing configuration added to my-crate");
+        assert_snapshot!(rendered.body_text);
+    }
+
     #[test]
     fn test_config_created_email_different_recipient() {
         let email = ConfigCreatedEmail {
@@ -179,6 +218,20 @@ mod tests {
         assert_snapshot!(rendered.body_text);
     }
 
+    #[test]
+    fn test_config_created_email_forgejo() {
+        let email = ConfigCreatedEmail {
+            recipient: "octocat",
+            auth_user: &test_user(),
+            krate: &test_crate(),
+            saved_config: ConfigType::Forgejo(&test_forgejo_config()),
+        };
+
+        let rendered = assert_ok!(email.render());
+        assert_snapshot!(rendered.subject, @"crates.io: Trusted Publishing configuration added to my-crate");
+        assert_snapshot!(rendered.body_text);
+    }
+
     #[test]
     fn test_config_deleted_email() {
         let email = ConfigDeletedEmail {
@@ -248,4 +301,18 @@ mod tests {
         assert_snapshot!(rendered.subject, @"crates.io: Trusted Publishing configuration removed from my-crate");
         assert_snapshot!(rendered.body_text);
     }
+
+    #[test]
+    fn test_config_deleted_email_forgejo() {
+        let email = ConfigDeletedEmail {
+            recipient: "octocat",
+            auth_user: &test_user(),
+            krate: &test_crate(),
+            config: ConfigType::Forgejo(&test_forgejo_config()),
+        };
+
+        let rendered = assert_ok!(email.render());
+        assert_snapshot!(rendered.subject, @"crates.io: Trusted Publishing configuration removed from my-crate");
+        assert_snapshot!(rendered.body_text);
+    }
 }
@@ -0,0 +1,169 @@
+use crate::app::AppState;
+use crate::auth::AuthCheck;
+use crate::controllers::krate::load_crate;
+use crate::controllers::trustpub::emails::{ConfigCreatedEmail, ConfigType};
+use crate::controllers::trustpub::forgejo_configs::json;
+use crate::util::errors::{AppResult, bad_request, custom, forbidden};
+use anyhow::Context;
+use axum::Json;
+use crates_io_database::models::token::EndpointScope;
+use crates_io_database::models::trustpub::{ForgejoConfig, NewForgejoConfig};
+use crates_io_database::models::{CrateAction, NewCrateAuditAction, OwnerKind};
+use crates_io_database::schema::{crate_owners, emails, users};
+use crates_io_trustpub::forgejo::validation::{
+    validate_owner, validate_repo, validate_workflow_filename,
+};
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use http::request::Parts;
+use serde_json::json;
+use tracing::warn;
+
+const MAX_CONFIGS_PER_CRATE: usize = 5;
+
+/// Create a new Trusted Publishing configuration for Forgejo Actions.
+#[utoipa::path(
+    post,
+    path = "/api/v1/trusted_publishing/forgejo_configs",
+    security(("cookie" = []), ("api_token" = [])),
+    request_body = inline(json::CreateRequest),
+    tag = "trusted_publishing",
+    responses((status = 200, description = "Successful Response", body = inline(json::CreateResponse))),
+)]
+pub async fn create_trustpub_forgejo_config(
+    state: AppState,
+    parts: Parts,
+    json: json::CreateRequest,
+) -> AppResult<Json<json::CreateResponse>> {
+    let json_config = json.forgejo_config;
+
+    validate_owner(&json_config.repository_owner)?;
+    validate_repo(&json_config.repository_name)?;
+    validate_workflow_filename(&json_config.workflow_filename)?;
+
+    let mut conn = state.db_write().await?;
+
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::ManageTrustpub)
+        .for_crate(&json_config.krate)
+        .check(&parts, &mut conn)
+        .await?;
+    let auth_user = auth.user();
+
+    let krate = load_crate(&mut conn, &json_config.krate).await?;
+
+    // Check if the crate has reached the maximum number of configs
+    let config_count = ForgejoConfig::count_for_crate(&mut conn, krate.id).await?;
+    if config_count >= MAX_CONFIGS_PER_CRATE as i64 {
+        let message = format!(
+            "This crate already has the maximum number of Forgejo Trusted Publishing configurations ({})",
+            MAX_CONFIGS_PER_CRATE
+        );
+        return Err(custom(http::StatusCode::CONFLICT, message));
+    }
+
+    let user_owners = crate_owners::table
+        .filter(crate_owners::crate_id.eq(krate.id))
+        .filter(crate_owners::deleted.eq(false))
+        .filter(crate_owners::owner_kind.eq(OwnerKind::User))
+        .inner_join(users::table)
+        .inner_join(emails::table.on(users::id.eq(emails::user_id)))
+        .select((users::id, users::gh_login, emails::email, emails::verified))
+        .load::<(i32, String, String, bool)>(&mut conn)
+        .await?;
+
+    let (_, _, _, email_verified) = user_owners
+        .iter()
+        .find(|(id, _, _, _)| *id == auth_user.id)
+        .ok_or_else(|| bad_request("You are not an owner of this crate"))?;
+
+    if !email_verified {
+        let message = "You must verify your email address to create a Trusted Publishing config";
+        return Err(forbidden(message));
+    }
+
+    // Save the new Forgejo OIDC config to the database
+
+    let new_config = NewForgejoConfig {
+        crate_id: krate.id,
+        repository_owner: &json_config.repository_owner,
+        repository_name: &json_config.repository_name,
+        workflow_filename: &json_config.workflow_filename,
+    };
+
+    let (crate_id, crate_name) = (krate.id, krate.name.as_str());
+    let (user_id, api_token_id) = (auth_user.id, auth.api_token_id());
+
+    let saved_config = conn
+        .transaction(|conn| {
+            async move {
+                let saved_config = new_config.insert(conn).await?;
+
+                NewCrateAuditAction::builder()
+                    .crate_id(crate_id)
+                    .crate_name(crate_name)
+                    .user_id(user_id)
+                    .maybe_api_token_id(api_token_id)
+                    .action(CrateAction::TrustpubConfigCreate)
+                    .details(json!({ "provider": "forgejo", "config": saved_config }))
+                    .build()
+                    .insert(conn)
+                    .await?;
+
+                Ok::<_, diesel::result::Error>(saved_config)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    // Send notification emails to crate owners
+
+    let recipients = user_owners
+        .into_iter()
+        .filter(|(_, _, _, verified)| *verified)
+        .map(|(_, login, email, _)| (login, email))
+        .collect::<Vec<_>>();
+
+    for (recipient, email_address) in &recipients {
+        let saved_config = ConfigType::Forgejo(&saved_config);
+
+        let context = ConfigCreatedEmail {
+            recipient,
+            auth_user,
+            krate: &krate,
+            saved_config,
+        };
+
+        if let Err(err) = send_notification_email(&state, email_address, context).await {
+            warn!("Failed to send trusted publishing notification to {email_address}: {err}");
+        }
+    }
+
+    let forgejo_config = json::ForgejoConfig {
+        id: saved_config.id,
+        krate: krate.name,
+        repository_owner: saved_config.repository_owner,
+        repository_owner_id: saved_config.repository_owner_id,
+        repository_name: saved_config.repository_name,
+        workflow_filename: saved_config.workflow_filename,
+        created_at: saved_config.created_at,
+    };
+
+    Ok(Json(json::CreateResponse { forgejo_config }))
+}
+
+async fn send_notification_email(
+    state: &AppState,
+    email_address: &str,
+    context: ConfigCreatedEmail<'_>,
+) -> anyhow::Result<()> {
+    let email = context.render();
+    let email = email.context("Failed to render email template")?;
+
+    state
+        .emails
+        .send(email_address, email)
+        .await
+        .context("Failed to send email")
+}
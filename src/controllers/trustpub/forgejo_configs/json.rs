@@ -0,0 +1,35 @@
+use axum::Json;
+use axum::extract::FromRequest;
+use serde::{Deserialize, Serialize};
+
+pub use crate::views::trustpub::{ForgejoConfig, NewForgejoConfig};
+
+#[derive(Debug, Deserialize, FromRequest, utoipa::ToSchema)]
+#[from_request(via(Json))]
+pub struct CreateRequest {
+    pub forgejo_config: NewForgejoConfig,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct CreateResponse {
+    pub forgejo_config: ForgejoConfig,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ListResponse {
+    pub forgejo_configs: Vec<ForgejoConfig>,
+
+    #[schema(inline)]
+    pub meta: ListResponseMeta,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ListResponseMeta {
+    /// The total number of Forgejo configs belonging to the crate.
+    #[schema(example = 42)]
+    pub total: i64,
+
+    /// Query string to the next page of results, if any.
+    #[schema(example = "?seek=abc123")]
+    pub next_page: Option<String>,
+}
@@ -6,17 +6,21 @@ use crate::controllers::trustpub::github_configs::json;
 use crate::util::errors::{AppResult, bad_request, custom, forbidden, server_error};
 use anyhow::Context;
 use axum::Json;
-use crates_io_database::models::OwnerKind;
+use chrono::Utc;
 use crates_io_database::models::token::EndpointScope;
 use crates_io_database::models::trustpub::{GitHubConfig, NewGitHubConfig};
+use crates_io_database::models::{CrateAction, NewCrateAuditAction, OwnerKind};
 use crates_io_database::schema::{crate_owners, emails, users};
 use crates_io_github::GitHubError;
 use crates_io_trustpub::github::validation::{
     validate_environment, validate_owner, validate_repo, validate_workflow_filename,
 };
+use crates_io_trustpub::ref_pattern::validate_ref_pattern;
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
 use http::request::Parts;
+use serde_json::json;
 use tracing::warn;
 
 const MAX_CONFIGS_PER_CRATE: usize = 5;
@@ -43,11 +47,19 @@ pub async fn create_trustpub_github_config(
     if let Some(env) = &json_config.environment {
         validate_environment(env)?;
     }
+    if let Some(ref_pattern) = &json_config.ref_pattern {
+        validate_ref_pattern(ref_pattern)?;
+    }
+    if let Some(expires_at) = json_config.expires_at
+        && expires_at <= Utc::now()
+    {
+        return Err(bad_request("Expiry date must be in the future"));
+    }
 
     let mut conn = state.db_write().await?;
 
     let auth = AuthCheck::default()
-        .with_endpoint_scope(EndpointScope::TrustedPublishing)
+        .with_endpoint_scope(EndpointScope::ManageTrustpub)
         .for_crate(&json_config.krate)
         .check(&parts, &mut conn)
         .await?;
@@ -113,9 +125,34 @@ pub async fn create_trustpub_github_config(
         repository_name: &json_config.repository_name,
         workflow_filename: &json_config.workflow_filename,
         environment: json_config.environment.as_deref(),
+        ref_pattern: json_config.ref_pattern.as_deref(),
+        expires_at: json_config.expires_at,
     };
 
-    let saved_config = new_config.insert(&mut conn).await?;
+    let (crate_id, crate_name) = (krate.id, krate.name.as_str());
+    let (user_id, api_token_id) = (auth_user.id, auth.api_token_id());
+
+    let saved_config = conn
+        .transaction(|conn| {
+            async move {
+                let saved_config = new_config.insert(conn).await?;
+
+                NewCrateAuditAction::builder()
+                    .crate_id(crate_id)
+                    .crate_name(crate_name)
+                    .user_id(user_id)
+                    .maybe_api_token_id(api_token_id)
+                    .action(CrateAction::TrustpubConfigCreate)
+                    .details(json!({ "provider": "github", "config": saved_config }))
+                    .build()
+                    .insert(conn)
+                    .await?;
+
+                Ok::<_, diesel::result::Error>(saved_config)
+            }
+            .scope_boxed()
+        })
+        .await?;
 
     // Send notification emails to crate owners
 
@@ -148,6 +185,8 @@ pub async fn create_trustpub_github_config(
         repository_name: saved_config.repository_name,
         workflow_filename: saved_config.workflow_filename,
         environment: saved_config.environment,
+        ref_pattern: saved_config.ref_pattern,
+        expires_at: saved_config.expires_at,
         created_at: saved_config.created_at,
     };
 
@@ -6,16 +6,21 @@ use crate::controllers::trustpub::gitlab_configs::json;
 use crate::util::errors::{AppResult, bad_request, custom, forbidden};
 use anyhow::Context;
 use axum::Json;
-use crates_io_database::models::OwnerKind;
+use chrono::Utc;
 use crates_io_database::models::token::EndpointScope;
 use crates_io_database::models::trustpub::{GitLabConfig, NewGitLabConfig};
+use crates_io_database::models::{CrateAction, NewCrateAuditAction, OwnerKind};
 use crates_io_database::schema::{crate_owners, emails, users};
+use crates_io_trustpub::gitlab::GITLAB_ISSUER_URL;
 use crates_io_trustpub::gitlab::validation::{
     validate_environment, validate_namespace, validate_project, validate_workflow_filepath,
 };
+use crates_io_trustpub::ref_pattern::validate_ref_pattern;
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
 use http::request::Parts;
+use serde_json::json;
 use tracing::warn;
 
 const MAX_CONFIGS_PER_CRATE: usize = 5;
@@ -41,11 +46,29 @@ pub async fn create_trustpub_gitlab_config(
     if let Some(env) = &json_config.environment {
         validate_environment(env)?;
     }
+    if let Some(ref_pattern) = &json_config.ref_pattern {
+        validate_ref_pattern(ref_pattern)?;
+    }
+    if let Some(expires_at) = json_config.expires_at
+        && expires_at <= Utc::now()
+    {
+        return Err(bad_request("Expiry date must be in the future"));
+    }
+
+    let issuer = json_config.issuer.as_deref().unwrap_or(GITLAB_ISSUER_URL);
+    if !state
+        .config
+        .trustpub_gitlab_issuer_urls
+        .iter()
+        .any(|url| url == issuer)
+    {
+        return Err(bad_request(format!("Unsupported GitLab issuer: {issuer}")));
+    }
 
     let mut conn = state.db_write().await?;
 
     let auth = AuthCheck::default()
-        .with_endpoint_scope(EndpointScope::TrustedPublishing)
+        .with_endpoint_scope(EndpointScope::ManageTrustpub)
         .for_crate(&json_config.krate)
         .check(&parts, &mut conn)
         .await?;
@@ -91,9 +114,35 @@ pub async fn create_trustpub_gitlab_config(
         project: &json_config.project,
         workflow_filepath: &json_config.workflow_filepath,
         environment: json_config.environment.as_deref(),
+        issuer,
+        ref_pattern: json_config.ref_pattern.as_deref(),
+        expires_at: json_config.expires_at,
     };
 
-    let saved_config = new_config.insert(&mut conn).await?;
+    let (crate_id, crate_name) = (krate.id, krate.name.as_str());
+    let (user_id, api_token_id) = (auth_user.id, auth.api_token_id());
+
+    let saved_config = conn
+        .transaction(|conn| {
+            async move {
+                let saved_config = new_config.insert(conn).await?;
+
+                NewCrateAuditAction::builder()
+                    .crate_id(crate_id)
+                    .crate_name(crate_name)
+                    .user_id(user_id)
+                    .maybe_api_token_id(api_token_id)
+                    .action(CrateAction::TrustpubConfigCreate)
+                    .details(json!({ "provider": "gitlab", "config": saved_config }))
+                    .build()
+                    .insert(conn)
+                    .await?;
+
+                Ok::<_, diesel::result::Error>(saved_config)
+            }
+            .scope_boxed()
+        })
+        .await?;
 
     // Send notification emails to crate owners
 
@@ -126,6 +175,9 @@ pub async fn create_trustpub_gitlab_config(
         project: saved_config.project,
         workflow_filepath: saved_config.workflow_filepath,
         environment: saved_config.environment,
+        issuer: saved_config.issuer,
+        ref_pattern: saved_config.ref_pattern,
+        expires_at: saved_config.expires_at,
         created_at: saved_config.created_at,
     };
 
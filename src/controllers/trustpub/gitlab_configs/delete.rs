@@ -6,12 +6,14 @@ use anyhow::Context;
 use axum::extract::Path;
 use crates_io_database::models::token::EndpointScope;
 use crates_io_database::models::trustpub::GitLabConfig;
-use crates_io_database::models::{Crate, OwnerKind};
+use crates_io_database::models::{Crate, CrateAction, NewCrateAuditAction, OwnerKind};
 use crates_io_database::schema::{crate_owners, crates, emails, trustpub_configs_gitlab, users};
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
 use http::StatusCode;
 use http::request::Parts;
+use serde_json::json;
 use tracing::warn;
 
 /// Delete Trusted Publishing configuration for GitLab CI/CD.
@@ -43,7 +45,7 @@ pub async fn delete_trustpub_gitlab_config(
         .ok_or_else(not_found)?;
 
     let auth = AuthCheck::default()
-        .with_endpoint_scope(EndpointScope::TrustedPublishing)
+        .with_endpoint_scope(EndpointScope::ManageTrustpub)
         .for_crate(&krate.name)
         .check(&parts, &mut conn)
         .await?;
@@ -65,10 +67,35 @@ pub async fn delete_trustpub_gitlab_config(
         return Err(bad_request("You are not an owner of this crate"));
     }
 
+    let (crate_id, crate_name) = (krate.id, krate.name.as_str());
+    let (user_id, api_token_id) = (auth_user.id, auth.api_token_id());
+    let details = json!({ "provider": "gitlab", "config": config });
+
     // Delete the configuration from the database
-    diesel::delete(trustpub_configs_gitlab::table.filter(trustpub_configs_gitlab::id.eq(id)))
-        .execute(&mut conn)
-        .await?;
+    conn.transaction(|conn| {
+        async move {
+            diesel::delete(
+                trustpub_configs_gitlab::table.filter(trustpub_configs_gitlab::id.eq(id)),
+            )
+            .execute(conn)
+            .await?;
+
+            NewCrateAuditAction::builder()
+                .crate_id(crate_id)
+                .crate_name(crate_name)
+                .user_id(user_id)
+                .maybe_api_token_id(api_token_id)
+                .action(CrateAction::TrustpubConfigDelete)
+                .details(details)
+                .build()
+                .insert(conn)
+                .await?;
+
+            Ok::<_, diesel::result::Error>(())
+        }
+        .scope_boxed()
+    })
+    .await?;
 
     // Send notification emails to crate owners
 
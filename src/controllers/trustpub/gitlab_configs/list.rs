@@ -66,7 +66,7 @@ async fn list_by_crate(
     let mut conn = state.db_read().await?;
 
     let auth = AuthCheck::default()
-        .with_endpoint_scope(EndpointScope::TrustedPublishing)
+        .with_endpoint_scope(EndpointScope::ManageTrustpub)
         .for_crate(krate_name)
         .check(&parts, &mut conn)
         .await?;
@@ -100,7 +100,7 @@ async fn list_by_user(
     let mut conn = state.db_read().await?;
 
     let auth = AuthCheck::default()
-        .with_endpoint_scope(EndpointScope::TrustedPublishing)
+        .with_endpoint_scope(EndpointScope::ManageTrustpub)
         .allow_any_crate_scope()
         .check(&parts, &mut conn)
         .await?;
@@ -174,6 +174,9 @@ fn to_json_config(config: ConfigWithCrateName) -> json::GitLabConfig {
         project: config.project,
         workflow_filepath: config.workflow_filepath,
         environment: config.environment,
+        issuer: config.issuer,
+        ref_pattern: config.ref_pattern,
+        expires_at: config.expires_at,
         created_at: config.created_at,
     }
 }
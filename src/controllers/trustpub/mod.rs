@@ -1,4 +1,5 @@
 pub mod emails;
+pub mod forgejo_configs;
 pub mod github_configs;
 pub mod gitlab_configs;
 pub mod tokens;
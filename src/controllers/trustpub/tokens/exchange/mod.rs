@@ -4,14 +4,18 @@ use crate::util::errors::{AppResult, BoxedAppError, bad_request, server_error};
 use axum::Json;
 use chrono::{DateTime, Utc};
 use crates_io_database::models::trustpub::{
-    GitHubConfig, GitLabConfig, NewToken, NewUsedJti, TrustpubData,
+    ForgejoConfig, GitHubConfig, GitLabConfig, NewToken, NewUsedJti, TrustpubData,
+};
+use crates_io_database::schema::{
+    trustpub_configs_forgejo, trustpub_configs_github, trustpub_configs_gitlab,
 };
-use crates_io_database::schema::{trustpub_configs_github, trustpub_configs_gitlab};
 use crates_io_diesel_helpers::lower;
 use crates_io_trustpub::access_token::AccessToken;
+use crates_io_trustpub::forgejo::ForgejoClaims;
 use crates_io_trustpub::github::{GITHUB_ISSUER_URL, GitHubClaims};
 use crates_io_trustpub::gitlab::{GITLAB_ISSUER_URL, GitLabClaims};
 use crates_io_trustpub::keystore::DecodingKey;
+use crates_io_trustpub::ref_pattern::matches_ref_pattern;
 use crates_io_trustpub::unverified::UnverifiedClaims;
 use diesel::prelude::*;
 use diesel::result::DatabaseErrorKind::UniqueViolation;
@@ -60,7 +64,18 @@ pub async fn exchange_trustpub_token(
 
     match unverified_issuer.as_str() {
         GITHUB_ISSUER_URL => handle_github_token(&state, &unverified_jwt, &key).await,
-        GITLAB_ISSUER_URL => handle_gitlab_token(&state, &unverified_jwt, &key).await,
+        issuer
+            if state
+                .config
+                .trustpub_gitlab_issuer_urls
+                .iter()
+                .any(|url| url == issuer) =>
+        {
+            handle_gitlab_token(&state, issuer, &unverified_jwt, &key).await
+        }
+        issuer if issuer == state.config.trustpub_forgejo_issuer_url => {
+            handle_forgejo_token(&state, &unverified_jwt, &key).await
+        }
         _ => Err(unsupported_issuer(&unverified_issuer)),
     }
 }
@@ -148,6 +163,17 @@ async fn handle_github_token_inner(
         return Err(bad_request(message));
     }
 
+    // Remove configs that have passed their expiry date
+    let now = Utc::now();
+    repo_configs.retain(|config| config.expires_at.is_none_or(|expires_at| expires_at > now));
+
+    if repo_configs.is_empty() {
+        let message = format!(
+            "The Trusted Publishing config for repository `{repo}` has expired. Please create a new Trusted Publishing config."
+        );
+        return Err(bad_request(message));
+    }
+
     let mismatched_owner_ids: Vec<String> = repo_configs
         .extract_if(.., |config| {
             config.repository_owner_id != repository_owner_id
@@ -207,6 +233,36 @@ async fn handle_github_token_inner(
         return Err(bad_request(message));
     }
 
+    // Filter by git ref pattern (if config specifies one)
+    let mismatched_ref_patterns: Vec<String> = repo_configs
+        .extract_if(.., |config| {
+            match (&config.ref_pattern, &signed_claims.r#ref) {
+                // Keep configs with no ref pattern
+                (None, _) => false,
+                // Remove configs requiring a ref when JWT has none
+                (Some(_), None) => true,
+                // Remove configs whose pattern does not match the ref
+                (Some(pattern), Some(git_ref)) => !matches_ref_pattern(pattern, git_ref),
+            }
+        })
+        .filter_map(|config| config.ref_pattern.map(|pattern| format!("`{pattern}`")))
+        .collect();
+
+    if repo_configs.is_empty() {
+        let message = if let Some(git_ref) = &signed_claims.r#ref {
+            format!(
+                "The Trusted Publishing config for repository `{repo}` does not match the ref `{git_ref}` in the JWT. Expected ref patterns: {}",
+                mismatched_ref_patterns.join(", ")
+            )
+        } else {
+            format!(
+                "The Trusted Publishing config for repository `{repo}` requires a ref, but the JWT does not specify one. Expected ref patterns: {}",
+                mismatched_ref_patterns.join(", ")
+            )
+        };
+        return Err(bad_request(message));
+    }
+
     let crate_ids = repo_configs
         .iter()
         .map(|config| config.crate_id)
@@ -235,23 +291,26 @@ async fn handle_github_token_inner(
 
 async fn handle_gitlab_token(
     state: &AppState,
+    issuer: &str,
     unverified_jwt: &str,
     key: &DecodingKey,
 ) -> AppResult<Json<json::ExchangeResponse>> {
     let audience = &state.config.trustpub_audience;
-    let signed_claims = GitLabClaims::decode(unverified_jwt, audience, key).map_err(|err| {
-        warn!("Failed to decode JWT: {err}");
-        bad_request("Failed to decode JWT")
-    })?;
+    let signed_claims =
+        GitLabClaims::decode(unverified_jwt, issuer, audience, key).map_err(|err| {
+            warn!("Failed to decode JWT: {err}");
+            bad_request("Failed to decode JWT")
+        })?;
 
     let mut conn = state.db_write().await?;
 
-    conn.transaction(|conn| Box::pin(handle_gitlab_token_inner(conn, signed_claims)))
+    conn.transaction(|conn| Box::pin(handle_gitlab_token_inner(conn, issuer, signed_claims)))
         .await
 }
 
 async fn handle_gitlab_token_inner(
     conn: &mut AsyncPgConnection,
+    issuer: &str,
     signed_claims: GitLabClaims,
 ) -> AppResult<Json<json::ExchangeResponse>> {
     insert_jti(conn, &signed_claims.jti, signed_claims.exp).await?;
@@ -276,6 +335,7 @@ async fn handle_gitlab_token_inner(
     };
 
     let mut repo_configs = GitLabConfig::query()
+        .filter(trustpub_configs_gitlab::issuer.eq(issuer))
         .filter(lower(trustpub_configs_gitlab::namespace).eq(lower(&namespace)))
         .filter(lower(trustpub_configs_gitlab::project).eq(lower(&project)))
         .load(conn)
@@ -287,6 +347,17 @@ async fn handle_gitlab_token_inner(
         return Err(bad_request(message));
     }
 
+    // Remove configs that have passed their expiry date
+    let now = Utc::now();
+    repo_configs.retain(|config| config.expires_at.is_none_or(|expires_at| expires_at > now));
+
+    if repo_configs.is_empty() {
+        let message = format!(
+            "The Trusted Publishing config for repository `{project_path}` has expired. Please create a new Trusted Publishing config."
+        );
+        return Err(bad_request(message));
+    }
+
     // First, handle resurrection protection by lazily storing namespace_id and
     // verifying it on subsequent exchanges, before checking workflow/environment.
     let configs_to_update: Vec<i32> = repo_configs
@@ -370,6 +441,36 @@ async fn handle_gitlab_token_inner(
         return Err(bad_request(message));
     }
 
+    // Filter by git ref pattern (if config specifies one)
+    let mismatched_ref_patterns: Vec<String> = repo_configs
+        .extract_if(.., |config| {
+            match (&config.ref_pattern, &signed_claims.ref_path) {
+                // Keep configs with no ref pattern
+                (None, _) => false,
+                // Remove configs requiring a ref when JWT has none
+                (Some(_), None) => true,
+                // Remove configs whose pattern does not match the ref
+                (Some(pattern), Some(git_ref)) => !matches_ref_pattern(pattern, git_ref),
+            }
+        })
+        .filter_map(|config| config.ref_pattern.map(|pattern| format!("`{pattern}`")))
+        .collect();
+
+    if repo_configs.is_empty() {
+        let message = if let Some(git_ref) = &signed_claims.ref_path {
+            format!(
+                "The Trusted Publishing config for repository `{project_path}` does not match the ref `{git_ref}` in the JWT. Expected ref patterns: {}",
+                mismatched_ref_patterns.join(", ")
+            )
+        } else {
+            format!(
+                "The Trusted Publishing config for repository `{project_path}` requires a ref, but the JWT does not specify one. Expected ref patterns: {}",
+                mismatched_ref_patterns.join(", ")
+            )
+        };
+        return Err(bad_request(message));
+    }
+
     let crate_ids = repo_configs
         .iter()
         .map(|config| config.crate_id)
@@ -381,6 +482,147 @@ async fn handle_gitlab_token_inner(
         project_path: signed_claims.project_path,
         job_id: signed_claims.job_id,
         sha: signed_claims.sha,
+        issuer: (issuer != GITLAB_ISSUER_URL).then(|| issuer.to_string()),
+    };
+
+    let new_token_model = NewToken {
+        expires_at: chrono::Utc::now() + chrono::Duration::minutes(30),
+        hashed_token: &new_token.sha256(),
+        crate_ids: &crate_ids,
+        trustpub_data: Some(&trustpub_data),
+    };
+
+    new_token_model.insert(conn).await?;
+
+    let token = new_token.finalize().expose_secret().into();
+    Ok(Json(json::ExchangeResponse { token }))
+}
+
+async fn handle_forgejo_token(
+    state: &AppState,
+    unverified_jwt: &str,
+    key: &DecodingKey,
+) -> AppResult<Json<json::ExchangeResponse>> {
+    let issuer = &state.config.trustpub_forgejo_issuer_url;
+    let audience = &state.config.trustpub_audience;
+    let signed_claims =
+        ForgejoClaims::decode(unverified_jwt, issuer, audience, key).map_err(|err| {
+            warn!("Failed to decode JWT: {err}");
+            bad_request("Failed to decode JWT")
+        })?;
+
+    let mut conn = state.db_write().await?;
+
+    conn.transaction(|conn| Box::pin(handle_forgejo_token_inner(conn, signed_claims)))
+        .await
+}
+
+async fn handle_forgejo_token_inner(
+    conn: &mut AsyncPgConnection,
+    signed_claims: ForgejoClaims,
+) -> AppResult<Json<json::ExchangeResponse>> {
+    insert_jti(conn, &signed_claims.jti, signed_claims.exp).await?;
+
+    if signed_claims.event_name == "pull_request_target"
+        || signed_claims.event_name == "workflow_run"
+    {
+        let message = format!(
+            "Trusted Publishing does not support the `{}` event trigger due to security concerns. \
+             Please use a different trigger such as `push`, `release`, or `workflow_dispatch`.",
+            signed_claims.event_name
+        );
+        return Err(bad_request(message));
+    }
+
+    let repo = &signed_claims.repository;
+    let Some((repository_owner, repository_name)) = repo.split_once('/') else {
+        warn!("Unexpected repository format in JWT: {repo}");
+        let message = "Unexpected `repository` value";
+        return Err(bad_request(message));
+    };
+
+    let Some(workflow_filename) = signed_claims.workflow_filename() else {
+        let workflow_ref = &signed_claims.workflow_ref;
+        warn!("Unexpected `workflow_ref` format in JWT: {workflow_ref}");
+        let message = "Unexpected `workflow_ref` value";
+        return Err(bad_request(message));
+    };
+
+    let mut repo_configs = ForgejoConfig::query()
+        .filter(lower(trustpub_configs_forgejo::repository_owner).eq(lower(&repository_owner)))
+        .filter(lower(trustpub_configs_forgejo::repository_name).eq(lower(&repository_name)))
+        .load(conn)
+        .await?;
+
+    if repo_configs.is_empty() {
+        let message = format!("No Trusted Publishing config found for repository `{repo}`.");
+        return Err(bad_request(message));
+    }
+
+    // Forgejo instances can't be queried for the owner ID when the config is
+    // created, so we lazily store it on the first exchange and verify it on
+    // subsequent exchanges to protect against resurrection attacks.
+    let configs_to_update: Vec<i32> = repo_configs
+        .iter()
+        .filter(|config| config.repository_owner_id.is_none())
+        .map(|config| config.id)
+        .collect();
+
+    if !configs_to_update.is_empty() {
+        diesel::update(trustpub_configs_forgejo::table)
+            .filter(trustpub_configs_forgejo::id.eq_any(&configs_to_update))
+            .filter(trustpub_configs_forgejo::repository_owner_id.is_null())
+            .set(
+                trustpub_configs_forgejo::repository_owner_id
+                    .eq(&signed_claims.repository_owner_id),
+            )
+            .execute(conn)
+            .await?;
+    }
+
+    let mismatched_owner_ids: Vec<String> = repo_configs
+        .extract_if(.., |config| {
+            config
+                .repository_owner_id
+                .as_ref()
+                .is_some_and(|stored| stored != &signed_claims.repository_owner_id)
+        })
+        .filter_map(|config| config.repository_owner_id)
+        .collect();
+
+    if repo_configs.is_empty() {
+        let message = format!(
+            "The Trusted Publishing config for repository `{repo}` does not match the repository owner ID ({}) in the JWT. Expected owner IDs: {}. Please recreate the Trusted Publishing config to update the repository owner ID.",
+            signed_claims.repository_owner_id,
+            mismatched_owner_ids.join(", ")
+        );
+        return Err(bad_request(message));
+    }
+
+    let mismatched_workflows: Vec<String> = repo_configs
+        .extract_if(.., |config| config.workflow_filename != workflow_filename)
+        .map(|config| format!("`{}`", config.workflow_filename))
+        .collect();
+
+    if repo_configs.is_empty() {
+        let message = format!(
+            "The Trusted Publishing config for repository `{repo}` does not match the workflow filename `{workflow_filename}` in the JWT. Expected workflow filenames: {}",
+            mismatched_workflows.join(", ")
+        );
+        return Err(bad_request(message));
+    }
+
+    let crate_ids = repo_configs
+        .iter()
+        .map(|config| config.crate_id)
+        .collect::<Vec<_>>();
+
+    let new_token = AccessToken::generate();
+
+    let trustpub_data = TrustpubData::Forgejo {
+        repository: signed_claims.repository,
+        run_id: signed_claims.run_id,
+        sha: signed_claims.sha,
     };
 
     let new_token_model = NewToken {
@@ -3,12 +3,15 @@ use crate::app::AppState;
 use crate::auth::{AuthCheck, Authentication};
 use crate::controllers::helpers::authorization::Rights;
 use crate::models::token::EndpointScope;
-use crate::models::{Crate, NewVersionOwnerAction, Version, VersionAction, VersionOwnerAction};
+use crate::models::{
+    Crate, NewVersionOwnerAction, Version, VersionAction, VersionOwnerAction, WebhookEvent,
+};
 use crate::rate_limiter::LimitedAction;
 use crate::schema::versions;
 use crate::util::errors::{AppResult, bad_request, custom};
 use crate::views::EncodableVersion;
-use crate::worker::jobs::{SyncToGitIndex, SyncToSparseIndex, UpdateDefaultVersion};
+use crate::worker::jobs::webhooks;
+use crate::worker::jobs::{UpdateDefaultVersion, enqueue_sync_to_index};
 use axum::Json;
 use crates_io_worker::BackgroundJob;
 use diesel::prelude::*;
@@ -16,6 +19,7 @@ use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use http::StatusCode;
 use http::request::Parts;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use tracing::warn;
 
 #[derive(Deserialize)]
@@ -36,6 +40,10 @@ pub struct UpdateResponse {
 /// Update a crate version.
 ///
 /// This endpoint allows updating the `yanked` state of a version, including a yank message.
+///
+/// Changing the `yanked` state requires the `yank` scope for scoped API
+/// tokens, while only updating the yank message requires the
+/// `update-metadata` scope.
 #[utoipa::path(
     patch,
     path = "/api/v1/crates/{name}/{version}",
@@ -56,7 +64,18 @@ pub async fn update_version(
     let mut conn = state.db_write().await?;
     let (mut version, krate) = path.load_version_and_crate(&mut conn).await?;
     validate_yank_update(&update_request.version, &version)?;
-    let auth = authenticate(&req, &mut conn, &krate.name).await?;
+
+    // Updating the message of an already yanked version does not change
+    // its yanked state, so it only requires the `update-metadata` scope.
+    let endpoint_scope = match update_request.version.yanked {
+        Some(_) => EndpointScope::Yank,
+        None => EndpointScope::UpdateMetadata,
+    };
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(endpoint_scope)
+        .for_crate(&krate.name)
+        .check(&req, &mut conn)
+        .await?;
 
     state
         .rate_limiter
@@ -123,7 +142,8 @@ pub async fn perform_version_yank_update(
     let user = auth.user();
     let owners = krate.owners(conn).await?;
 
-    let yanked = yanked.unwrap_or(version.yanked);
+    let was_yanked = version.yanked;
+    let yanked = yanked.unwrap_or(was_yanked);
 
     let encryption = &state.config.gh_token_encryption;
     if Rights::get(user, &*state.github, &owners, encryption).await? < Rights::Publish {
@@ -174,20 +194,30 @@ pub async fn perform_version_yank_update(
         .version_id(version.id)
         .user_id(user.id)
         .maybe_api_token_id(api_token_id)
+        .maybe_team_id(auth.team_id())
         .action(action)
         .build()
         .insert(conn)
         .await?;
 
-    let git_index_job = SyncToGitIndex::new(&krate.name);
-    let sparse_index_job = SyncToSparseIndex::new(&krate.name);
+    let git_index_enabled = state.config.git_index_enabled;
+    enqueue_sync_to_index(&krate.name, git_index_enabled, conn).await?;
+
     let update_default_version_job = UpdateDefaultVersion::new(krate.id);
+    update_default_version_job.enqueue(conn).await?;
 
-    tokio::try_join!(
-        git_index_job.enqueue(conn),
-        sparse_index_job.enqueue(conn),
-        update_default_version_job.enqueue(conn),
-    )?;
+    state.sparse_index_cache.invalidate(&krate.name).await;
+
+    // Changes of only the yank message are not relevant for webhooks
+    if yanked != was_yanked {
+        let event = if yanked {
+            WebhookEvent::Yank
+        } else {
+            WebhookEvent::Unyank
+        };
+        let data = json!({ "version": version.num, "yank_message": version.yank_message });
+        webhooks::enqueue_deliveries(conn, krate.id, &krate.name, event, data).await?;
+    }
 
     Ok(())
 }
@@ -2,15 +2,30 @@
 //! and is used by the corresponding background jobs to generate the
 //! index files.
 
-use crate::models::{Crate, Dependency, Version};
-use crate::schema::crates;
+use crate::models::{Crate, Dependency, IndexChange, Version};
+use crate::schema::{crates, index_changes, versions};
+use crate::views::EncodableIndexChange;
 use anyhow::Context;
+use chrono::{DateTime, Utc};
 use crates_io_index::features::split_features;
+use crates_io_index::tuf::{RoleType, bin_role_name};
+use diesel::dsl::{exists, max};
 use diesel::prelude::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use hex::ToHex;
 use sentry::Level;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Duration;
 use tracing::{debug, instrument};
 
+mod verification;
+
+pub use verification::{
+    IndexBackend, IndexFiles, Mismatch, MismatchKind, VerificationReport, verify_index,
+};
+
 #[instrument(skip_all, fields(krate.name = ?name))]
 pub async fn get_index_data(
     name: &str,
@@ -139,6 +154,180 @@ pub async fn index_metadata(
         .collect()
 }
 
+/// A sparse index file that was generated from the database.
+pub struct SparseIndexFile {
+    pub content: String,
+    /// A strong `ETag` header value derived from the content.
+    pub etag: String,
+    /// The last time any of the versions of the crate was modified.
+    pub last_modified: DateTime<Utc>,
+}
+
+impl SparseIndexFile {
+    pub fn new(content: String, last_modified: DateTime<Utc>) -> Self {
+        let hash: String = Sha256::digest(content.as_bytes()).encode_hex();
+        let etag = format!("\"{hash}\"");
+
+        Self {
+            content,
+            etag,
+            last_modified,
+        }
+    }
+}
+
+/// An in-process cache for the sparse index files that are served directly
+/// by the API server, keyed by lowercase crate name.
+///
+/// Entries are invalidated when a crate is published, yanked or deleted
+/// through this server instance. Changes made by other processes (e.g.
+/// `crates-admin`) are picked up once the entries expire.
+#[derive(Clone)]
+pub struct SparseIndexCache(moka::future::Cache<String, Arc<SparseIndexFile>>);
+
+impl SparseIndexCache {
+    pub fn new(max_capacity: u64, ttl: Duration) -> Self {
+        let cache = moka::future::CacheBuilder::new(max_capacity)
+            .name("sparse_index")
+            .time_to_live(ttl)
+            .build();
+
+        Self(cache)
+    }
+
+    /// Returns the sparse index file for the crate with the given name, or
+    /// `None` if the crate does not exist or has no versions.
+    ///
+    /// The crate name is matched case-insensitively.
+    pub async fn get_or_load(
+        &self,
+        name: &str,
+        conn: &mut AsyncPgConnection,
+        include_pubtime: bool,
+    ) -> anyhow::Result<Option<Arc<SparseIndexFile>>> {
+        let key = name.to_lowercase();
+        if let Some(file) = self.0.get(&key).await {
+            return Ok(Some(file));
+        }
+
+        let krate: Option<Crate> = Crate::by_name(name)
+            .first(conn)
+            .await
+            .optional()
+            .context("Failed to load crate")?;
+
+        // `Crate::by_name()` also treats `-` and `_` as equivalent, but the
+        // sparse index uses separate files for these.
+        let Some(krate) = krate.filter(|krate| krate.name.to_lowercase() == key) else {
+            return Ok(None);
+        };
+
+        let Some(content) = get_index_data(&krate.name, conn, include_pubtime).await? else {
+            return Ok(None);
+        };
+
+        let last_modified = versions::table
+            .filter(versions::crate_id.eq(krate.id))
+            .select(max(versions::updated_at))
+            .get_result::<Option<DateTime<Utc>>>(conn)
+            .await
+            .context("Failed to load last modification time")?
+            .unwrap_or(krate.updated_at);
+
+        let file = Arc::new(SparseIndexFile::new(content, last_modified));
+        self.0.insert(key, file.clone()).await;
+
+        Ok(Some(file))
+    }
+
+    /// Removes the cached sparse index file of the crate with the given name.
+    pub async fn invalidate(&self, name: &str) {
+        self.0.invalidate(&name.to_lowercase()).await;
+    }
+}
+
+/// The epoch of the sparse index changes feed.
+///
+/// This needs to be incremented whenever the sequence numbers of the feed are
+/// reset, which tells mirrors to do a full resync.
+pub const CHANGES_EPOCH: u32 = 1;
+
+/// The number of sequence numbers covered by each page of the changes feed.
+pub const CHANGES_PAGE_SIZE: i64 = 1000;
+
+/// A page of the sparse index changes feed, as published at
+/// `changes/{epoch}/{page}.json` in the index storage.
+///
+/// Page `n` contains the changes with sequence numbers from
+/// `n * CHANGES_PAGE_SIZE + 1` to `(n + 1) * CHANGES_PAGE_SIZE`.
+#[derive(Debug, Serialize)]
+pub struct IndexChangesPage {
+    pub epoch: u32,
+    pub page: i64,
+    /// Whether there are changes on later pages, which means that this page
+    /// will not be modified anymore.
+    pub complete: bool,
+    pub changes: Vec<EncodableIndexChange>,
+}
+
+impl IndexChangesPage {
+    /// Returns the page number that contains the given sequence number.
+    pub fn number_for(seq: i64) -> i64 {
+        (seq - 1).max(0) / CHANGES_PAGE_SIZE
+    }
+
+    /// Returns the path of the given page, relative to the index root.
+    pub fn path(page: i64) -> String {
+        format!("changes/{CHANGES_EPOCH}/{page}.json")
+    }
+
+    pub async fn load(conn: &mut AsyncPgConnection, page: i64) -> QueryResult<Self> {
+        let first_seq = page * CHANGES_PAGE_SIZE + 1;
+        let last_seq = (page + 1) * CHANGES_PAGE_SIZE;
+
+        let changes = IndexChange::query()
+            .filter(index_changes::seq.between(first_seq, last_seq))
+            .order(index_changes::seq)
+            .load(conn)
+            .await?;
+
+        let later_changes = index_changes::table.filter(index_changes::seq.gt(last_seq));
+        let complete = diesel::select(exists(later_changes))
+            .get_result(conn)
+            .await?;
+
+        let changes = changes.into_iter().map(Into::into).collect();
+
+        Ok(Self {
+            epoch: CHANGES_EPOCH,
+            page,
+            complete,
+            changes,
+        })
+    }
+}
+
+/// Returns the path of the signed TUF metadata of the given role, relative
+/// to the index root.
+pub fn tuf_metadata_path(role: RoleType) -> String {
+    format!("tuf/{}", role.file_name())
+}
+
+/// Returns the path of the signed TUF metadata of the given hash bin of the
+/// `targets` role, relative to the index root.
+pub fn tuf_bin_metadata_path(bin: u8) -> String {
+    format!("tuf/{}.json", bin_role_name(bin))
+}
+
+/// Returns the path of the given version of the signed TUF `root` metadata,
+/// relative to the index root.
+///
+/// All previous versions are kept, so that clients can walk the chain of
+/// trust from an older root to the current one after a key rotation.
+pub fn tuf_root_path(version: u64) -> String {
+    format!("tuf/{version}.root.json")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
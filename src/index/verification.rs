@@ -0,0 +1,503 @@
+//! Detection of drift between the database and the git and sparse indexes.
+//!
+//! The index files are regenerated from the database via [`get_index_data()`]
+//! and compared line by line with the files in each index backend.
+
+use crate::index::get_index_data;
+use crate::schema::{background_jobs, crates};
+use crate::storage::Storage;
+use crate::tasks::spawn_blocking;
+use crate::worker::jobs::{BulkSyncToGitIndex, SyncToGitIndex, SyncToSparseIndex};
+use anyhow::Context;
+use crates_io_index::Repository;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tracing::{info, instrument};
+
+/// Log the progress of the verification every `PROGRESS_INTERVAL` crates.
+const PROGRESS_INTERVAL: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexBackend {
+    Git,
+    Sparse,
+}
+
+/// The index files of one of the index backends.
+pub enum IndexFiles<'a> {
+    /// A local checkout of the git index repository.
+    Git(&'a Path),
+    /// The sparse index files in the index storage.
+    Sparse(&'a Storage),
+}
+
+impl IndexFiles<'_> {
+    pub fn backend(&self) -> IndexBackend {
+        match self {
+            Self::Git(_) => IndexBackend::Git,
+            Self::Sparse(_) => IndexBackend::Sparse,
+        }
+    }
+
+    /// Reads the index file of the crate with the given name, or returns
+    /// `None` if the file does not exist.
+    async fn read(&self, name: &str) -> anyhow::Result<Option<String>> {
+        match self {
+            Self::Git(checkout_path) => {
+                let path = checkout_path.join(Repository::relative_index_file(name));
+                match tokio::fs::read_to_string(&path).await {
+                    Ok(content) => Ok(Some(content)),
+                    Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+                    Err(error) => Err(error.into()),
+                }
+            }
+            Self::Sparse(storage) => {
+                let path = Repository::relative_index_file_for_url(name);
+                let Some(bytes) = storage.download_index_file(&path).await? else {
+                    return Ok(None);
+                };
+
+                let content = String::from_utf8(bytes.to_vec())
+                    .with_context(|| format!("Index file `{path}` is not valid UTF-8"))?;
+
+                Ok(Some(content))
+            }
+        }
+    }
+
+    /// Returns the names of all crates that have an index file in this
+    /// backend. Since the index file names are always lowercase, these are
+    /// lowercase too.
+    async fn list(&self) -> anyhow::Result<Vec<String>> {
+        let paths = match self {
+            Self::Git(checkout_path) => {
+                let checkout_path = checkout_path.to_path_buf();
+                spawn_blocking(move || list_git_index_files(&checkout_path)).await??
+            }
+            Self::Sparse(storage) => storage.list_index_files().await?,
+        };
+
+        let names = paths
+            .into_iter()
+            .filter_map(|path| path.rsplit('/').next().map(ToString::to_string))
+            .collect();
+
+        Ok(names)
+    }
+}
+
+/// Returns the paths of all crate index files in the git index checkout,
+/// relative to the root of the checkout.
+fn list_git_index_files(checkout_path: &Path) -> anyhow::Result<Vec<String>> {
+    let mut paths = Vec::new();
+    let mut dirs = vec![PathBuf::new()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(checkout_path.join(&dir))? {
+            let entry = entry?;
+            let Some(name) = entry.file_name().to_str().map(ToString::to_string) else {
+                continue;
+            };
+
+            // Skip the `.git` folder, and `config.json` and other files
+            // that can't be crate index files.
+            if name.contains('.') {
+                continue;
+            }
+
+            let path = dir.join(&name);
+            if entry.file_type()?.is_dir() {
+                dirs.push(path);
+            } else if !dir.as_os_str().is_empty() {
+                let components = path.iter().filter_map(|c| c.to_str()).collect::<Vec<_>>();
+                paths.push(components.join("/"));
+            }
+        }
+    }
+
+    Ok(paths)
+}
+
+/// An index file that does not match the content expected from the database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mismatch {
+    #[serde(rename = "crate")]
+    pub krate: String,
+    pub backend: IndexBackend,
+    #[serde(flatten)]
+    pub kind: MismatchKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MismatchKind {
+    /// The crate has versions in the database, but no index file.
+    MissingFile,
+    /// The index file exists, but the crate does not exist in the database
+    /// or has no versions left.
+    UnexpectedFile,
+    /// The index file exists, but its content is different from the
+    /// content generated from the database.
+    ContentMismatch {
+        /// Versions that are missing from the index file.
+        missing_versions: Vec<String>,
+        /// Versions that are in the index file, but not in the database.
+        unexpected_versions: Vec<String>,
+        /// Versions with index lines that differ from the database.
+        changed_versions: Vec<String>,
+    },
+}
+
+impl MismatchKind {
+    /// Compares the expected content of an index file with its actual
+    /// content, and returns `None` if they match.
+    pub fn compare(expected: Option<&str>, actual: Option<&str>) -> Option<Self> {
+        match (expected, actual) {
+            (None, None) => None,
+            (Some(_), None) => Some(Self::MissingFile),
+            (None, Some(_)) => Some(Self::UnexpectedFile),
+            (Some(expected), Some(actual)) if expected == actual => None,
+            (Some(expected), Some(actual)) => {
+                let expected = lines_by_version(expected);
+                let actual = lines_by_version(actual);
+
+                let missing_versions = expected
+                    .keys()
+                    .filter(|version| !actual.contains_key(*version))
+                    .cloned()
+                    .collect();
+
+                let unexpected_versions = actual
+                    .keys()
+                    .filter(|version| !expected.contains_key(*version))
+                    .cloned()
+                    .collect();
+
+                let changed_versions = expected
+                    .iter()
+                    .filter(|(version, line)| actual.get(*version).is_some_and(|a| a != *line))
+                    .map(|(version, _)| version.clone())
+                    .collect();
+
+                Some(Self::ContentMismatch {
+                    missing_versions,
+                    unexpected_versions,
+                    changed_versions,
+                })
+            }
+        }
+    }
+}
+
+/// Splits the content of an index file into lines, keyed by the `vers`
+/// field of each line.
+///
+/// Lines that can not be parsed are keyed by their full content, which means
+/// that they will be reported as unexpected versions.
+fn lines_by_version(content: &str) -> BTreeMap<String, &str> {
+    #[derive(Deserialize)]
+    struct Line {
+        vers: String,
+    }
+
+    content
+        .lines()
+        .map(|line| match serde_json::from_str::<Line>(line) {
+            Ok(parsed) => (parsed.vers, line),
+            Err(_) => (line.to_string(), line),
+        })
+        .collect()
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct VerificationReport {
+    pub crates_checked: usize,
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl VerificationReport {
+    /// Enqueues sync jobs for all mismatched index files, and returns the
+    /// number of enqueued jobs.
+    ///
+    /// The sync jobs regenerate the files from the database, or delete them
+    /// if the crate does not exist anymore.
+    pub async fn enqueue_repairs(&self, conn: &mut AsyncPgConnection) -> anyhow::Result<usize> {
+        for mismatch in &self.mismatches {
+            let krate = &mismatch.krate;
+            let result = match mismatch.backend {
+                IndexBackend::Git => SyncToGitIndex::new(krate).enqueue(conn).await,
+                IndexBackend::Sparse => SyncToSparseIndex::new(krate).enqueue(conn).await,
+            };
+            result.with_context(|| format!("Failed to enqueue repair job for `{krate}`"))?;
+        }
+
+        Ok(self.mismatches.len())
+    }
+}
+
+/// Compares the index files of the given crates in all given backends with
+/// the content generated from the database.
+///
+/// If `crate_names` is `None`, all crates are checked, and index files of
+/// crates that do not exist in the database are reported too.
+///
+/// Mismatched index files of crates with a queued sync job are not
+/// reported, and all other mismatches are checked a second time, to avoid
+/// reporting index files that are in the process of being updated.
+#[instrument(skip_all)]
+pub async fn verify_index(
+    conn: &mut AsyncPgConnection,
+    crate_names: Option<Vec<String>>,
+    include_pubtime: bool,
+    backends: &[IndexFiles<'_>],
+) -> anyhow::Result<VerificationReport> {
+    let check_orphans = crate_names.is_none();
+    let crate_names = match crate_names {
+        Some(crate_names) => crate_names,
+        None => crates::table
+            .select(crates::name)
+            .order(crates::name)
+            .load(conn)
+            .await
+            .context("Failed to load crate names")?,
+    };
+
+    info!("Verifying the index files of {} crates", crate_names.len());
+
+    let mut report = VerificationReport::default();
+
+    for (i, name) in crate_names.iter().enumerate() {
+        if i > 0 && i % PROGRESS_INTERVAL == 0 {
+            info!(
+                "Verified {i} crates, found {} mismatches",
+                report.mismatches.len()
+            );
+        }
+
+        let expected = get_index_data(name, conn, include_pubtime)
+            .await
+            .with_context(|| format!("Failed to get index data for `{name}`"))?;
+
+        for backend in backends {
+            let actual = backend.read(name).await.with_context(|| {
+                format!(
+                    "Failed to read {:?} index file for `{name}`",
+                    backend.backend()
+                )
+            })?;
+
+            if let Some(kind) = MismatchKind::compare(expected.as_deref(), actual.as_deref()) {
+                let krate = name.clone();
+                let backend = backend.backend();
+                report.mismatches.push(Mismatch {
+                    krate,
+                    backend,
+                    kind,
+                });
+            }
+        }
+
+        report.crates_checked += 1;
+    }
+
+    if check_orphans {
+        let known_names = crate_names
+            .iter()
+            .map(|name| name.to_lowercase())
+            .collect::<HashSet<_>>();
+
+        for backend in backends {
+            let names = backend
+                .list()
+                .await
+                .with_context(|| format!("Failed to list {:?} index files", backend.backend()))?;
+
+            for krate in names {
+                if !known_names.contains(&krate) {
+                    let backend = backend.backend();
+                    let kind = MismatchKind::UnexpectedFile;
+                    report.mismatches.push(Mismatch {
+                        krate,
+                        backend,
+                        kind,
+                    });
+                }
+            }
+        }
+    }
+
+    // Index files with a queued sync job are about to be regenerated, and
+    // files that were regenerated while the verification was running might
+    // have been read before the sync. Both are expected to differ
+    // temporarily, so they are skipped or checked again before reporting.
+    let pending = pending_sync_jobs(conn).await?;
+    let mismatches = std::mem::take(&mut report.mismatches);
+    let mut num_pending = 0;
+    for mismatch in mismatches {
+        if pending.contains(&(mismatch.backend, mismatch.krate.to_lowercase())) {
+            num_pending += 1;
+            continue;
+        }
+
+        let Some(backend) = backends.iter().find(|b| b.backend() == mismatch.backend) else {
+            continue;
+        };
+
+        let name = &mismatch.krate;
+        let expected = get_index_data(name, conn, include_pubtime)
+            .await
+            .with_context(|| format!("Failed to get index data for `{name}`"))?;
+
+        let actual = backend.read(name).await.with_context(|| {
+            format!(
+                "Failed to read {:?} index file for `{name}`",
+                mismatch.backend
+            )
+        })?;
+
+        if let Some(kind) = MismatchKind::compare(expected.as_deref(), actual.as_deref()) {
+            report.mismatches.push(Mismatch { kind, ..mismatch });
+        }
+    }
+
+    info!(
+        "Verified {} crates, found {} mismatches, skipped {num_pending} files with queued sync jobs",
+        report.crates_checked,
+        report.mismatches.len()
+    );
+
+    Ok(report)
+}
+
+/// Returns the backends and lowercase names of all crates that have a
+/// queued sync job.
+async fn pending_sync_jobs(
+    conn: &mut AsyncPgConnection,
+) -> anyhow::Result<HashSet<(IndexBackend, String)>> {
+    #[derive(Deserialize)]
+    struct SyncJob {
+        krate: String,
+    }
+
+    #[derive(Deserialize)]
+    struct BulkSyncJob {
+        crate_names: Vec<String>,
+    }
+
+    let job_types = [
+        SyncToSparseIndex::JOB_NAME,
+        SyncToGitIndex::JOB_NAME,
+        BulkSyncToGitIndex::JOB_NAME,
+    ];
+
+    let jobs: Vec<(String, serde_json::Value)> = background_jobs::table
+        .filter(background_jobs::job_type.eq_any(job_types))
+        .select((background_jobs::job_type, background_jobs::data))
+        .load(conn)
+        .await
+        .context("Failed to load queued sync jobs")?;
+
+    let mut pending = HashSet::new();
+    for (job_type, data) in jobs {
+        if job_type == BulkSyncToGitIndex::JOB_NAME {
+            let job: BulkSyncJob = serde_json::from_value(data)?;
+            for name in job.crate_names {
+                pending.insert((IndexBackend::Git, name.to_lowercase()));
+            }
+        } else {
+            let job: SyncJob = serde_json::from_value(data)?;
+            let backend = match job_type.as_str() {
+                SyncToGitIndex::JOB_NAME => IndexBackend::Git,
+                _ => IndexBackend::Sparse,
+            };
+            pending.insert((backend, job.krate.to_lowercase()));
+        }
+    }
+
+    Ok(pending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_json_snapshot;
+
+    const V1: &str =
+        r#"{"name":"foo","vers":"1.0.0","deps":[],"cksum":"aa","features":{},"yanked":false}"#;
+    const V2: &str =
+        r#"{"name":"foo","vers":"2.0.0","deps":[],"cksum":"bb","features":{},"yanked":false}"#;
+    const V2_YANKED: &str =
+        r#"{"name":"foo","vers":"2.0.0","deps":[],"cksum":"bb","features":{},"yanked":true}"#;
+    const V3: &str =
+        r#"{"name":"foo","vers":"3.0.0","deps":[],"cksum":"cc","features":{},"yanked":false}"#;
+
+    #[test]
+    fn test_compare_files() {
+        let content = format!("{V1}\n");
+        assert_eq!(MismatchKind::compare(None, None), None);
+        assert_eq!(MismatchKind::compare(Some(&content), Some(&content)), None);
+
+        let kind = MismatchKind::compare(Some(&content), None);
+        assert_eq!(kind, Some(MismatchKind::MissingFile));
+
+        let kind = MismatchKind::compare(None, Some(&content));
+        assert_eq!(kind, Some(MismatchKind::UnexpectedFile));
+    }
+
+    #[test]
+    fn test_compare_lines() {
+        let expected = format!("{V1}\n{V2_YANKED}\n{V3}\n");
+        let actual = format!("{V1}\n{V2}\nnot json\n");
+
+        let mismatch = Mismatch {
+            krate: "foo".into(),
+            backend: IndexBackend::Sparse,
+            kind: MismatchKind::compare(Some(&expected), Some(&actual)).unwrap(),
+        };
+
+        assert_json_snapshot!(mismatch, @r#"
+        {
+          "crate": "foo",
+          "backend": "sparse",
+          "kind": "content_mismatch",
+          "missing_versions": [
+            "3.0.0"
+          ],
+          "unexpected_versions": [
+            "not json"
+          ],
+          "changed_versions": [
+            "2.0.0"
+          ]
+        }
+        "#);
+    }
+
+    #[test]
+    fn test_list_git_index_files() {
+        let checkout = tempfile::tempdir().unwrap();
+        let root = checkout.path();
+
+        for path in [
+            ".git/config",
+            "config.json",
+            "1/a",
+            "3/f/foo",
+            "fo/ob/foobar",
+        ] {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+
+        let mut paths = list_git_index_files(root).unwrap();
+        paths.sort();
+        assert_eq!(paths, ["1/a", "3/f/foo", "fo/ob/foobar"]);
+    }
+}
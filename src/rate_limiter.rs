@@ -8,6 +8,9 @@ use diesel::sql_types::Interval;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::time::Duration;
 
 pg_enum! {
@@ -171,6 +174,42 @@ impl RateLimiter {
     }
 }
 
+/// An in-memory rate limiter for anonymous requests, which allows a fixed
+/// number of requests per IP address within each time window.
+///
+/// In contrast to [`RateLimiter`], the state is not shared between the
+/// server instances, so the effective limit is multiplied by the number of
+/// instances. This is good enough to protect cheap endpoints from single
+/// misbehaving clients without adding a database query to each request.
+#[derive(Clone)]
+pub struct IpRateLimiter {
+    max_requests: u32,
+    counters: moka::future::Cache<IpAddr, Arc<AtomicU32>>,
+}
+
+impl IpRateLimiter {
+    pub fn new(max_requests: u32, window: Duration) -> Self {
+        // The time to live is not reset by reads, so each counter is dropped
+        // at the end of the window that started with its first request.
+        let counters = moka::future::CacheBuilder::new(100_000)
+            .name("ip_rate_limiter")
+            .time_to_live(window)
+            .build();
+
+        Self {
+            max_requests,
+            counters,
+        }
+    }
+
+    /// Counts a request from the given IP address, and returns whether it is
+    /// within the limit.
+    pub async fn check(&self, ip: IpAddr) -> bool {
+        let counter = self.counters.get_with(ip, async { Arc::default() }).await;
+        counter.fetch_add(1, Ordering::Relaxed) < self.max_requests
+    }
+}
+
 #[derive(HasQuery, Insertable, Debug, PartialEq, Clone, Copy)]
 #[diesel(table_name = publish_limit_buckets)]
 #[allow(dead_code)] // Most fields only read in tests
@@ -731,6 +770,21 @@ mod tests {
             .await
     }
 
+    #[tokio::test]
+    async fn ip_rate_limiter() {
+        let rate_limiter = IpRateLimiter::new(2, Duration::from_millis(200));
+        let ip = IpAddr::from([127, 0, 0, 1]);
+        let other_ip = IpAddr::from([127, 0, 0, 2]);
+
+        assert!(rate_limiter.check(ip).await);
+        assert!(rate_limiter.check(ip).await);
+        assert!(!rate_limiter.check(ip).await);
+        assert!(rate_limiter.check(other_ip).await);
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(rate_limiter.check(ip).await);
+    }
+
     struct SampleRateLimiter {
         rate: Duration,
         burst: i32,
@@ -15,6 +15,7 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
     let (router, openapi) = BaseOpenApi::router()
         // Route used by both `cargo search` and the frontend
         .routes(routes!(krate::search::list_crates))
+        .routes(routes!(krate::suggest::suggest_crates))
         // Routes used by `cargo`
         .routes(routes!(
             krate::publish::publish,
@@ -28,6 +29,8 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
         .routes(routes!(version::yank::yank_version))
         .routes(routes!(version::yank::unyank_version))
         .routes(routes!(version::downloads::download_version))
+        .routes(routes!(krate::publish::release_staged_version))
+        .routes(routes!(krate::publish::download_staged_version))
         // Routes used by the frontend
         .routes(routes!(
             krate::metadata::find_crate,
@@ -44,6 +47,7 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
         .routes(routes!(version::docs::rebuild_version_docs))
         .routes(routes!(version::authors::get_version_authors))
         .routes(routes!(krate::downloads::get_crate_downloads))
+        .routes(routes!(krate::download_history::get_crate_download_history))
         .routes(routes!(krate::versions::list_versions))
         .routes(routes!(
             krate::follow::follow_crate,
@@ -53,6 +57,16 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
         .routes(routes!(krate::owners::get_team_owners))
         .routes(routes!(krate::owners::get_user_owners))
         .routes(routes!(krate::rev_deps::list_reverse_dependencies))
+        .routes(routes!(krate::audit::list_audit_actions))
+        .routes(routes!(
+            krate::webhooks::list_webhooks,
+            krate::webhooks::create_webhook
+        ))
+        .routes(routes!(
+            krate::webhooks::update_webhook,
+            krate::webhooks::delete_webhook
+        ))
+        .routes(routes!(krate::webhooks::list_webhook_deliveries))
         .routes(routes!(keyword::list_keywords))
         .routes(routes!(keyword::find_keyword))
         .routes(routes!(category::list_categories))
@@ -85,6 +99,7 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
         .routes(routes!(user::email_verification::confirm_user_email))
         .routes(routes!(user::email_verification::resend_email_verification))
         .routes(routes!(site_metadata::get_site_metadata))
+        .routes(routes!(sparse_index::list_index_changes))
         // Session management
         .routes(routes!(session::begin_session))
         .routes(routes!(session::authorize_session))
@@ -104,6 +119,11 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
             trustpub::gitlab_configs::delete::delete_trustpub_gitlab_config,
             trustpub::gitlab_configs::list::list_trustpub_gitlab_configs,
         ))
+        .routes(routes!(
+            trustpub::forgejo_configs::create::create_trustpub_forgejo_config,
+            trustpub::forgejo_configs::delete::delete_trustpub_forgejo_config,
+            trustpub::forgejo_configs::list::list_trustpub_forgejo_configs,
+        ))
         .split_for_parts();
 
     let mut router = router
@@ -128,6 +148,17 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
         );
     }
 
+    // Serve the sparse index for deployments without a separate index CDN.
+    if state.config.serve_sparse_index {
+        router = router
+            .route("/index/config.json", get(sparse_index::get_index_config))
+            .route(
+                "/index/changes/{epoch}/{file}",
+                get(sparse_index::get_index_changes_page),
+            )
+            .route("/index/{*path}", get(sparse_index::get_index_file));
+    }
+
     router
         .route("/api/openapi.json", get(async || Json(openapi)))
         .fallback(async |method: Method| match method {
@@ -21,19 +21,23 @@ use tokio::io::AsyncWriteExt;
 use tracing::{instrument, warn};
 
 const PREFIX_CRATES: &str = "crates";
+const PREFIX_STAGED_CRATES: &str = "staged-crates";
 const PREFIX_READMES: &str = "readmes";
 const PREFIX_OG_IMAGES: &str = "og-images";
+const PREFIX_PRIVATE: &str = "private";
 const DEFAULT_REGION: &str = "us-west-1";
 const CONTENT_TYPE_CRATE: &str = "application/gzip";
 const CONTENT_TYPE_GZIP: &str = "application/gzip";
 const CONTENT_TYPE_ZIP: &str = "application/zip";
-const CONTENT_TYPE_INDEX: &str = "text/plain";
+pub const CONTENT_TYPE_INDEX: &str = "text/plain";
+const CONTENT_TYPE_JSON: &str = "application/json";
 const CONTENT_TYPE_README: &str = "text/html";
 const CONTENT_TYPE_OG_IMAGE: &str = "image/png";
 const CACHE_CONTROL_IMMUTABLE: &str = "public,max-age=31536000,immutable";
-const CACHE_CONTROL_INDEX: &str = "public,max-age=600";
+pub const CACHE_CONTROL_INDEX: &str = "public,max-age=600";
 const CACHE_CONTROL_README: &str = "public,max-age=604800";
 const CACHE_CONTROL_OG_IMAGE: &str = "public,max-age=86400";
+pub const CACHE_CONTROL_PRIVATE: &str = "private,no-store";
 
 type StdPath = std::path::Path;
 
@@ -46,8 +50,15 @@ pub struct StorageConfig {
 #[derive(Debug)]
 #[allow(clippy::large_enum_variant)]
 pub enum StorageBackend {
-    S3 { default: S3Config, index: S3Config },
-    LocalFileSystem { path: PathBuf },
+    S3 {
+        default: S3Config,
+        index: S3Config,
+        private: Option<S3Config>,
+    },
+    LocalFileSystem {
+        path: PathBuf,
+        private_path: PathBuf,
+    },
     InMemory,
 }
 
@@ -75,6 +86,11 @@ impl StorageConfig {
             let index_bucket = required_var("S3_INDEX_BUCKET").unwrap();
             let index_region = dotenvy::var("S3_INDEX_REGION").ok();
 
+            // The private bucket is optional, since it is only needed for
+            // staged publishing.
+            let private_bucket = dotenvy::var("S3_PRIVATE_BUCKET").ok();
+            let private_region = dotenvy::var("S3_PRIVATE_REGION").ok();
+
             let access_key = required_var("AWS_ACCESS_KEY").unwrap();
             let secret_key: SecretString = required_var("AWS_SECRET_KEY").unwrap().into();
 
@@ -88,11 +104,22 @@ impl StorageConfig {
             let index = S3Config {
                 bucket: index_bucket,
                 region: index_region,
+                access_key: access_key.clone(),
+                secret_key: secret_key.clone(),
+            };
+
+            let private = private_bucket.map(|bucket| S3Config {
+                bucket,
+                region: private_region,
                 access_key,
                 secret_key,
-            };
+            });
 
-            let backend = StorageBackend::S3 { default, index };
+            let backend = StorageBackend::S3 {
+                default,
+                index,
+                private,
+            };
 
             return Self {
                 backend,
@@ -106,7 +133,11 @@ impl StorageConfig {
 
         let path = current_dir.join("local_uploads");
 
-        let backend = StorageBackend::LocalFileSystem { path };
+        // `local_uploads` is served by the development server, so private
+        // files are stored next to it instead.
+        let private_path = current_dir.join("local_private_uploads");
+
+        let backend = StorageBackend::LocalFileSystem { path, private_path };
 
         Self {
             backend,
@@ -119,6 +150,12 @@ pub struct Storage {
     cdn_prefix: Option<String>,
     store: Arc<dyn ObjectStore>,
     index_store: Arc<dyn ObjectStore>,
+    /// Store for files that must not be publicly accessible through the
+    /// CDN, like the archives of staged crate versions.
+    ///
+    /// This is `None` if no private S3 bucket is configured, in which case
+    /// staged publishing is not available.
+    private_store: Option<Arc<dyn ObjectStore>>,
     supports_attributes: bool,
 }
 
@@ -131,7 +168,11 @@ impl Storage {
         let cdn_prefix = config.cdn_prefix.clone();
 
         match &config.backend {
-            StorageBackend::S3 { default, index } => {
+            StorageBackend::S3 {
+                default,
+                index,
+                private,
+            } => {
                 let options = ClientOptions::default()
                     // Apply default content types for the version downloads archive
                     .with_content_type_for_suffix("html", "text/html")
@@ -147,6 +188,10 @@ impl Storage {
 
                 let index_store = build_s3(index, Default::default());
 
+                let private_store = private.as_ref().map(|private| {
+                    Arc::new(build_s3(private, Default::default())) as Arc<dyn ObjectStore>
+                });
+
                 if cdn_prefix.is_none() {
                     panic!("Missing S3_CDN environment variable");
                 }
@@ -155,11 +200,12 @@ impl Storage {
                     cdn_prefix,
                     store: Arc::new(store),
                     index_store: Arc::new(index_store),
+                    private_store,
                     supports_attributes: true,
                 }
             }
 
-            StorageBackend::LocalFileSystem { path } => {
+            StorageBackend::LocalFileSystem { path, private_path } => {
                 warn!(?path, "Using local file system for file storage");
 
                 let index_path = path.join("index");
@@ -168,6 +214,10 @@ impl Storage {
                     .context("Failed to create file storage directories")
                     .unwrap();
 
+                fs::create_dir_all(private_path)
+                    .context("Failed to create file storage directories")
+                    .unwrap();
+
                 let local = LocalFileSystem::new_with_prefix(path)
                     .context("Failed to initialize local file system storage")
                     .unwrap();
@@ -176,13 +226,19 @@ impl Storage {
                     .context("Failed to initialize local file system storage")
                     .unwrap();
 
+                let local_private = LocalFileSystem::new_with_prefix(private_path)
+                    .context("Failed to initialize local file system storage")
+                    .unwrap();
+
                 let store: Arc<dyn ObjectStore> = Arc::new(local);
                 let index_store: Arc<dyn ObjectStore> = Arc::new(local_index);
+                let private_store: Arc<dyn ObjectStore> = Arc::new(local_private);
 
                 Self {
                     cdn_prefix,
                     store,
                     index_store,
+                    private_store: Some(private_store),
                     supports_attributes: false,
                 }
             }
@@ -194,13 +250,28 @@ impl Storage {
                 Self {
                     cdn_prefix,
                     store: store.clone(),
-                    index_store: Arc::new(PrefixStore::new(store, "index")),
+                    index_store: Arc::new(PrefixStore::new(store.clone(), "index")),
+                    private_store: Some(Arc::new(PrefixStore::new(store, PREFIX_PRIVATE))),
                     supports_attributes: true,
                 }
             }
         }
     }
 
+    /// Returns `true` if a private store is configured, which is required
+    /// for staged publishing.
+    pub fn supports_staged_versions(&self) -> bool {
+        self.private_store.is_some()
+    }
+
+    fn private_store(&self) -> Result<&Arc<dyn ObjectStore>> {
+        self.private_store
+            .as_ref()
+            .ok_or_else(|| object_store::Error::NotSupported {
+                source: "no private store is configured (`S3_PRIVATE_BUCKET`)".into(),
+            })
+    }
+
     /// Returns the URL of an uploaded crate's version archive.
     ///
     /// The function doesn't check for the existence of the file.
@@ -231,14 +302,25 @@ impl Storage {
     #[instrument(skip(self))]
     pub async fn delete_all_crate_files(&self, name: &str) -> Result<Vec<Path>> {
         let prefix = format!("{PREFIX_CRATES}/{name}").into();
-        self.delete_all_with_prefix(&prefix).await
+        delete_all_with_prefix(&self.store, &prefix).await
+    }
+
+    /// Deletes all staged crate files for the given crate, returning the paths that were deleted.
+    #[instrument(skip(self))]
+    pub async fn delete_all_staged_crate_files(&self, name: &str) -> Result<Vec<Path>> {
+        let Some(private_store) = &self.private_store else {
+            return Ok(vec![]);
+        };
+
+        let prefix = format!("{PREFIX_STAGED_CRATES}/{name}").into();
+        delete_all_with_prefix(private_store, &prefix).await
     }
 
     /// Deletes all READMEs for the given crate, returning the paths that were deleted.
     #[instrument(skip(self))]
     pub async fn delete_all_readmes(&self, name: &str) -> Result<Vec<Path>> {
         let prefix = format!("{PREFIX_READMES}/{name}").into();
-        self.delete_all_with_prefix(&prefix).await
+        delete_all_with_prefix(&self.store, &prefix).await
     }
 
     #[instrument(skip(self))]
@@ -247,6 +329,17 @@ impl Storage {
         self.store.delete(&path).await
     }
 
+    #[instrument(skip(self))]
+    pub async fn delete_staged_crate_file(
+        &self,
+        name: &str,
+        version: &str,
+        checksum: &str,
+    ) -> Result<()> {
+        let path = staged_crate_file_path(name, version, checksum);
+        self.private_store()?.delete(&path).await
+    }
+
     #[instrument(skip(self))]
     pub async fn delete_readme(&self, name: &str, version: &str) -> Result<()> {
         let path = readme_path(name, version);
@@ -289,6 +382,35 @@ impl Storage {
         Ok(result.into_stream())
     }
 
+    #[instrument(skip(self, bytes))]
+    pub async fn upload_staged_crate_file(
+        &self,
+        name: &str,
+        version: &str,
+        checksum: &str,
+        bytes: Bytes,
+    ) -> Result<()> {
+        let path = staged_crate_file_path(name, version, checksum);
+        let attributes = self.attrs([(Attribute::ContentType, CONTENT_TYPE_CRATE)]);
+        let opts = attributes.into();
+        self.private_store()?
+            .put_opts(&path, bytes.into(), opts)
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn download_staged_crate_file(
+        &self,
+        name: &str,
+        version: &str,
+        checksum: &str,
+    ) -> Result<Bytes> {
+        let path = staged_crate_file_path(name, version, checksum);
+        let result = self.private_store()?.get(&path).await?;
+        result.bytes().await
+    }
+
     #[instrument(skip(self, bytes))]
     pub async fn upload_readme(&self, name: &str, version: &str, bytes: Bytes) -> Result<()> {
         let path = readme_path(name, version);
@@ -351,6 +473,49 @@ impl Storage {
         Ok(())
     }
 
+    /// Uploads a JSON file (e.g. a page of the sparse index changes feed) to
+    /// the given path, relative to the index root.
+    #[instrument(skip(self, content))]
+    pub async fn upload_index_json(&self, path: &str, content: String) -> Result<()> {
+        let attributes = self.attrs([
+            (Attribute::ContentType, CONTENT_TYPE_JSON),
+            (Attribute::CacheControl, CACHE_CONTROL_INDEX),
+        ]);
+        let payload = content.into();
+        let opts = attributes.into();
+        self.index_store
+            .put_opts(&path.into(), payload, opts)
+            .await?;
+        Ok(())
+    }
+
+    /// Downloads the file at the given path, relative to the index root, or
+    /// returns `None` if the file does not exist.
+    #[instrument(skip(self))]
+    pub async fn download_index_file(&self, path: &str) -> Result<Option<Bytes>> {
+        match self.index_store.get(&path.into()).await {
+            Ok(result) => Ok(Some(result.bytes().await?)),
+            Err(object_store::Error::NotFound { .. }) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Returns the paths of all crate index files in the index storage,
+    /// relative to the index root.
+    ///
+    /// JSON files, like the `config.json` file, the pages of the changes feed
+    /// and the TUF metadata, are skipped. Crate names can not contain dots, so
+    /// these can't be mistaken for crate index files.
+    #[instrument(skip(self))]
+    pub async fn list_index_files(&self) -> Result<Vec<String>> {
+        self.index_store
+            .list(None)
+            .map_ok(|meta| meta.location.to_string())
+            .try_filter(|path| std::future::ready(!path.ends_with(".json")))
+            .try_collect()
+            .await
+    }
+
     #[instrument(skip(self))]
     pub async fn upload_db_dump(&self, target: &str, local_path: &StdPath) -> anyhow::Result<()> {
         let store = self.store.clone();
@@ -380,19 +545,6 @@ impl Storage {
         self.store.clone()
     }
 
-    async fn delete_all_with_prefix(&self, prefix: &Path) -> Result<Vec<Path>> {
-        let objects = self.store.list(Some(prefix));
-        let locations = objects.map(|meta| meta.map(|m| m.location)).boxed();
-
-        let paths = self
-            .store
-            .delete_stream(locations)
-            .try_collect::<Vec<_>>()
-            .await?;
-
-        Ok(paths)
-    }
-
     fn attrs(&self, slice: impl IntoIterator<Item = (Attribute, &'static str)>) -> Attributes {
         if self.supports_attributes {
             Attributes::from_iter(slice)
@@ -414,10 +566,29 @@ fn build_s3(config: &S3Config, client_options: ClientOptions) -> AmazonS3 {
         .unwrap()
 }
 
+async fn delete_all_with_prefix(store: &Arc<dyn ObjectStore>, prefix: &Path) -> Result<Vec<Path>> {
+    let objects = store.list(Some(prefix));
+    let locations = objects.map(|meta| meta.map(|m| m.location)).boxed();
+
+    let paths = store
+        .delete_stream(locations)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    Ok(paths)
+}
+
 fn crate_file_path(name: &str, version: &str) -> Path {
     format!("{PREFIX_CRATES}/{name}/{name}-{version}.crate").into()
 }
 
+/// Staged crate files are stored in the private store, since they must only
+/// be accessible to the owners of the crate. The checksum in the path keeps
+/// the files of repeated staging attempts apart.
+fn staged_crate_file_path(name: &str, version: &str, checksum: &str) -> Path {
+    format!("{PREFIX_STAGED_CRATES}/{name}/{name}-{version}-{checksum}.crate").into()
+}
+
 fn readme_path(name: &str, version: &str) -> Path {
     format!("{PREFIX_READMES}/{name}/{name}-{version}.html").into()
 }
@@ -662,6 +833,60 @@ mod tests {
         assert_eq!(stored_files(&s.store).await, expected_files);
     }
 
+    #[tokio::test]
+    async fn staged_crate_file() {
+        let s = Storage::from_config(&StorageConfig::in_memory());
+
+        s.upload_staged_crate_file("foo", "1.2.3", "abc", Bytes::from_static(b"foo"))
+            .await
+            .unwrap();
+
+        let expected_files = vec!["private/staged-crates/foo/foo-1.2.3-abc.crate"];
+        assert_eq!(stored_files(&s.store).await, expected_files);
+
+        let bytes = s.download_staged_crate_file("foo", "1.2.3", "abc").await;
+        assert_eq!(bytes.unwrap(), Bytes::from_static(b"foo"));
+
+        s.delete_staged_crate_file("foo", "1.2.3", "abc")
+            .await
+            .unwrap();
+
+        assert!(stored_files(&s.store).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn staged_crate_file_without_private_store() {
+        let s3_config = || S3Config {
+            bucket: "crates-io".to_string(),
+            region: None,
+            access_key: "access-key".to_string(),
+            secret_key: "secret-key".to_string().into(),
+        };
+
+        let config = StorageConfig {
+            backend: StorageBackend::S3 {
+                default: s3_config(),
+                index: s3_config(),
+                private: None,
+            },
+            cdn_prefix: Some("static.crates.io".to_string()),
+        };
+
+        let s = Storage::from_config(&config);
+        assert!(!s.supports_staged_versions());
+
+        let result = s
+            .upload_staged_crate_file("foo", "1.2.3", "abc", Bytes::from_static(b"foo"))
+            .await;
+        assert!(matches!(
+            result,
+            Err(object_store::Error::NotSupported { .. })
+        ));
+
+        let deleted = s.delete_all_staged_crate_files("foo").await.unwrap();
+        assert!(deleted.is_empty());
+    }
+
     #[tokio::test]
     async fn upload_readme() {
         let s = Storage::from_config(&StorageConfig::in_memory());
@@ -5,6 +5,7 @@ use crate::builders::PublishBuilder;
 use crate::util::encode_session_header;
 use http::{Method, StatusCode, header};
 use insta::assert_snapshot;
+use serde_json::json;
 
 static URL: &str = "/api/v1/me/updates";
 
@@ -44,3 +45,38 @@ async fn cookie_auth_cannot_find_user() {
     let error = anon.run::<()>(request).await;
     assert_eq!(error.status(), StatusCode::INTERNAL_SERVER_ERROR);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn token_auth_with_allowed_ips() {
+    let (app, _, user) = TestApp::init().with_user().await;
+
+    let body = json!({
+        "api_token": {
+            "name": "bar",
+            "endpoint_scopes": ["read-private"],
+            "allowed_ips": ["192.0.2.0/24"],
+        }
+    });
+    let response = user.put::<()>("/api/v1/me/tokens", body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let json = response.json();
+    let plaintext = json["api_token"]["token"].as_str().unwrap().to_string();
+    let url = format!("/api/v1/me/tokens/{}", json["api_token"]["id"]);
+
+    let client = MockTokenUser::with_auth_header(plaintext, app.clone());
+
+    let mut request = client.get_request(&url);
+    request.header("X-Forwarded-For", "192.0.2.42");
+    let response = client.run::<()>(request).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let mut request = client.get_request(&url);
+    request.header("X-Forwarded-For", "198.51.100.1");
+    let response = client.run::<()>(request).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this token can not be used from your IP address"}]}"#);
+
+    // Requests without an `X-Forwarded-For` header originate from 127.0.0.1
+    let response = client.get::<()>(&url).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+}
@@ -57,6 +57,8 @@ async fn test_dump_db_job() -> anyhow::Result<()> {
         "YYYY-MM-DD-HHMMSS/data/reserved_crate_names.csv",
         "YYYY-MM-DD-HHMMSS/data/teams.csv",
         "YYYY-MM-DD-HHMMSS/data/users.csv",
+        "YYYY-MM-DD-HHMMSS/data/cargo_version_downloads.csv",
+        "YYYY-MM-DD-HHMMSS/data/crate_download_rollups.csv",
         "YYYY-MM-DD-HHMMSS/data/crates_categories.csv",
         "YYYY-MM-DD-HHMMSS/data/crates_keywords.csv",
         "YYYY-MM-DD-HHMMSS/data/crate_owners.csv",
@@ -90,6 +92,8 @@ async fn test_dump_db_job() -> anyhow::Result<()> {
         "data/reserved_crate_names.csv",
         "data/teams.csv",
         "data/users.csv",
+        "data/cargo_version_downloads.csv",
+        "data/crate_download_rollups.csv",
         "data/crates_categories.csv",
         "data/crates_keywords.csv",
         "data/crate_owners.csv",
@@ -95,5 +95,6 @@ fn team(name: &str) -> GitHubTeam {
 
 fn active_membership() -> GitHubTeamMembership {
     let state = "active".to_string();
-    GitHubTeamMembership { state }
+    let role = "member".to_string();
+    GitHubTeamMembership { state, role }
 }
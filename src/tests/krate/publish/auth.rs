@@ -72,6 +72,7 @@ async fn new_krate_with_bearer_token() {
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/foo_new/foo_new-1.0.0.crate
+    index/changes/1/0.json
     index/fo/o_/foo_new
     rss/crates.xml
     rss/crates/foo_new.xml
@@ -24,6 +24,7 @@ async fn new_krate() {
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/foo_new/foo_new-1.0.0.crate
+    index/changes/1/0.json
     index/fo/o_/foo_new
     rss/crates.xml
     rss/crates/foo_new.xml
@@ -54,6 +55,7 @@ async fn new_krate_with_token() {
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/foo_new/foo_new-1.0.0.crate
+    index/changes/1/0.json
     index/fo/o_/foo_new
     rss/crates.xml
     rss/crates/foo_new.xml
@@ -75,6 +77,7 @@ async fn new_krate_weird_version() {
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/foo_weird/foo_weird-0.0.0-pre.crate
+    index/changes/1/0.json
     index/fo/o_/foo_weird
     rss/crates.xml
     rss/crates/foo_weird.xml
@@ -104,6 +107,7 @@ async fn new_krate_twice() {
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/foo_twice/foo_twice-0.99.0.crate
     crates/foo_twice/foo_twice-2.0.0.crate
+    index/changes/1/0.json
     index/fo/o_/foo_twice
     rss/crates.xml
     rss/crates/foo_twice.xml
@@ -135,6 +139,7 @@ async fn new_krate_twice_alt() {
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/foo_twice/foo_twice-0.99.0.crate
     crates/foo_twice/foo_twice-2.0.0.crate
+    index/changes/1/0.json
     index/fo/o_/foo_twice
     rss/crates.xml
     rss/crates/foo_twice.xml
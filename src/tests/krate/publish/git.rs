@@ -13,6 +13,7 @@ async fn new_krate_git_upload_with_conflicts() {
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/foo_conflicts/foo_conflicts-1.0.0.crate
+    index/changes/1/0.json
     index/fo/o_/foo_conflicts
     rss/crates.xml
     rss/crates/foo_conflicts.xml
@@ -53,6 +53,7 @@ async fn tarball_between_default_axum_limit_and_max_upload_size() {
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/foo/foo-1.1.0.crate
     index/3/f/foo
+    index/changes/1/0.json
     rss/crates.xml
     rss/crates/foo.xml
     rss/updates.xml
@@ -150,6 +151,7 @@ async fn new_krate_too_big_but_whitelisted() {
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/foo_whitelist/foo_whitelist-1.1.0.crate
+    index/changes/1/0.json
     index/fo/o_/foo_whitelist
     rss/crates/foo_whitelist.xml
     rss/updates.xml
@@ -18,8 +18,10 @@ mod max_size;
 mod rate_limit;
 mod readme;
 mod similar_names;
+mod staged;
 mod tarball;
 mod timestamps;
+mod trustpub_forgejo;
 mod trustpub_github;
 mod trustpub_gitlab;
 mod validation;
@@ -73,6 +73,7 @@ async fn publish_new_crate_ratelimit_expires() {
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/rate_limited/rate_limited-1.0.0.crate
+    index/changes/1/0.json
     index/ra/te/rate_limited
     rss/crates.xml
     rss/crates/rate_limited.xml
@@ -114,6 +115,7 @@ async fn publish_new_crate_override_loosens_ratelimit() {
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/rate_limited1/rate_limited1-1.0.0.crate
+    index/changes/1/0.json
     index/ra/te/rate_limited1
     rss/crates.xml
     rss/crates/rate_limited1.xml
@@ -129,6 +131,7 @@ async fn publish_new_crate_override_loosens_ratelimit() {
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/rate_limited1/rate_limited1-1.0.0.crate
     crates/rate_limited2/rate_limited2-1.0.0.crate
+    index/changes/1/0.json
     index/ra/te/rate_limited1
     index/ra/te/rate_limited2
     rss/crates.xml
@@ -149,6 +152,7 @@ async fn publish_new_crate_override_loosens_ratelimit() {
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/rate_limited1/rate_limited1-1.0.0.crate
     crates/rate_limited2/rate_limited2-1.0.0.crate
+    index/changes/1/0.json
     index/ra/te/rate_limited1
     index/ra/te/rate_limited2
     rss/crates.xml
@@ -193,6 +197,7 @@ async fn publish_new_crate_expired_override_ignored() {
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/rate_limited1/rate_limited1-1.0.0.crate
+    index/changes/1/0.json
     index/ra/te/rate_limited1
     rss/crates.xml
     rss/crates/rate_limited1.xml
@@ -210,6 +215,7 @@ async fn publish_new_crate_expired_override_ignored() {
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/rate_limited1/rate_limited1-1.0.0.crate
+    index/changes/1/0.json
     index/ra/te/rate_limited1
     rss/crates.xml
     rss/crates/rate_limited1.xml
@@ -252,6 +258,7 @@ async fn publish_existing_crate_rate_limited() {
     assert_eq!(json.krate.max_version, "1.0.0");
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/rate_limited1/rate_limited1-1.0.0.crate
+    index/changes/1/0.json
     index/ra/te/rate_limited1
     rss/crates.xml
     rss/crates/rate_limited1.xml
@@ -267,6 +274,7 @@ async fn publish_existing_crate_rate_limited() {
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/rate_limited1/rate_limited1-1.0.0.crate
     crates/rate_limited1/rate_limited1-1.0.1.crate
+    index/changes/1/0.json
     index/ra/te/rate_limited1
     rss/crates.xml
     rss/crates/rate_limited1.xml
@@ -286,6 +294,7 @@ async fn publish_existing_crate_rate_limited() {
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/rate_limited1/rate_limited1-1.0.0.crate
     crates/rate_limited1/rate_limited1-1.0.1.crate
+    index/changes/1/0.json
     index/ra/te/rate_limited1
     rss/crates.xml
     rss/crates/rate_limited1.xml
@@ -313,6 +322,7 @@ async fn publish_existing_crate_rate_limited() {
     crates/rate_limited1/rate_limited1-1.0.0.crate
     crates/rate_limited1/rate_limited1-1.0.1.crate
     crates/rate_limited1/rate_limited1-1.0.2.crate
+    index/changes/1/0.json
     index/ra/te/rate_limited1
     rss/crates.xml
     rss/crates/rate_limited1.xml
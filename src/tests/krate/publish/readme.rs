@@ -16,6 +16,7 @@ async fn new_krate_with_readme() {
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/foo_readme/foo_readme-1.0.0.crate
+    index/changes/1/0.json
     index/fo/o_/foo_readme
     readmes/foo_readme/foo_readme-1.0.0.html
     rss/crates.xml
@@ -38,6 +39,7 @@ async fn new_krate_with_empty_readme() {
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/foo_readme/foo_readme-1.0.0.crate
+    index/changes/1/0.json
     index/fo/o_/foo_readme
     rss/crates.xml
     rss/crates/foo_readme.xml
@@ -59,6 +61,7 @@ async fn new_krate_with_readme_and_plus_version() {
 
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/foo_readme/foo_readme-1.0.0+foo.crate
+    index/changes/1/0.json
     index/fo/o_/foo_readme
     readmes/foo_readme/foo_readme-1.0.0+foo.html
     rss/crates.xml
@@ -0,0 +1,383 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, Response, TestApp};
+use crates_io::models::token::{CrateScope, EndpointScope};
+use crates_io::schema::staged_versions;
+use crates_io::views::GoodCrate;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use insta::{assert_json_snapshot, assert_snapshot};
+use object_store::ObjectStoreExt;
+use serde_json::Value;
+
+const STAGED_PUBLISH_URL: &str = "/api/v1/crates/new?staged=true";
+
+async fn stage_crate(client: &impl RequestHelper, builder: PublishBuilder) -> Response<GoodCrate> {
+    let response = client.put(STAGED_PUBLISH_URL, builder).await;
+    client.app().run_pending_background_jobs().await;
+    response
+}
+
+async fn version_numbers(client: &impl RequestHelper, name: &str) -> Vec<String> {
+    let url = format!("/api/v1/crates/{name}/versions");
+    let json = client.get::<()>(&url).await.json();
+    json["versions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|version| version["num"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stage_and_release() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+
+    let response = stage_crate(&token, PublishBuilder::new("foo", "1.1.0")).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json()["warnings"], @r#"
+    {
+      "invalid_badges": [],
+      "invalid_categories": [],
+      "other": [
+        "`foo@1.1.0` has been staged and is not publicly available yet. Release it via `PUT /api/v1/crates/foo/1.1.0/release` within the next 7 days, otherwise it will be deleted."
+      ]
+    }
+    "#);
+    assert_eq!(response.json()["crate"]["max_version"], "1.0.0");
+
+    // The staged version is not visible anywhere yet
+    assert_eq!(version_numbers(&token, "foo").await, ["1.0.0"]);
+    assert_eq!(app.crates_from_index_head("foo").len(), 1);
+    assert_eq!(app.emails().await.len(), 1);
+
+    let stored_files = app.stored_files().await;
+    assert!(!stored_files.contains(&"crates/foo/foo-1.1.0.crate".to_string()));
+    assert!(
+        stored_files
+            .iter()
+            .any(|path| path.starts_with("private/staged-crates/foo/foo-1.1.0-"))
+    );
+
+    let response = token
+        .put::<()>("/api/v1/crates/foo/1.1.0/release", "")
+        .await;
+    app.run_pending_background_jobs().await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(response.json()["crate"]["max_version"], "1.1.0");
+
+    assert_eq!(version_numbers(&token, "foo").await, ["1.1.0", "1.0.0"]);
+    assert_eq!(app.crates_from_index_head("foo").len(), 2);
+    assert_eq!(app.emails().await.len(), 2);
+
+    let stored_files = app.stored_files().await;
+    assert!(stored_files.contains(&"crates/foo/foo-1.1.0.crate".to_string()));
+    assert!(
+        !stored_files
+            .iter()
+            .any(|path| path.starts_with("private/staged-crates/"))
+    );
+
+    let mut conn = app.db_conn().await;
+    let count: i64 = staged_versions::table
+        .count()
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+
+    // A released version can not be released again
+    let response = token
+        .put::<()>("/api/v1/crates/foo/1.1.0/release", "")
+        .await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `foo` does not have a staged version `1.1.0`"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stage_new_crate() {
+    let (app, anon, _, token) = TestApp::full().with_token().await;
+
+    let response = stage_crate(&token, PublishBuilder::new("foo", "1.0.0")).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json()["warnings"]["other"], @r#"
+    [
+      "`foo@1.0.0` has been staged and is not publicly available yet. Release it via `PUT /api/v1/crates/foo/1.0.0/release` within the next 7 days, otherwise it will be deleted."
+    ]
+    "#);
+    assert_eq!(response.json()["crate"]["name"], "foo");
+    assert_eq!(response.json()["crate"]["max_version"], "0.0.0");
+
+    // The crate is only created once the version is released
+    let response = anon.get::<()>("/api/v1/crates/foo").await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+
+    // Another version can be staged before the first one is released
+    stage_crate(&token, PublishBuilder::new("foo", "1.1.0"))
+        .await
+        .good();
+
+    let response = token
+        .put::<()>("/api/v1/crates/foo/1.0.0/release", "")
+        .await;
+    app.run_pending_background_jobs().await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(response.json()["crate"]["max_version"], "1.0.0");
+
+    assert_eq!(version_numbers(&token, "foo").await, ["1.0.0"]);
+    assert_eq!(app.crates_from_index_head("foo").len(), 1);
+
+    let response = anon.get::<()>("/api/v1/crates/foo/owner_user").await;
+    assert_eq!(response.json()["users"][0]["login"], "foo");
+
+    // The other staged version now belongs to the new crate
+    let response = token
+        .put::<()>("/api/v1/crates/foo/1.1.0/release", "")
+        .await;
+    app.run_pending_background_jobs().await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(version_numbers(&token, "foo").await, ["1.1.0", "1.0.0"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn staged_new_crate_reserves_name() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+
+    stage_crate(&token, PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+
+    let other_user = app.db_new_user("bar").await;
+    let other_token = other_user.db_new_token("other").await;
+
+    let response = other_token
+        .publish_crate(PublishBuilder::new("foo", "2.0.0"))
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this crate name is reserved by a version that has been staged by another user"}]}"#);
+
+    let response = stage_crate(&other_token, PublishBuilder::new("foo", "2.0.0")).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this crate name is reserved by a version that has been staged by another user"}]}"#);
+
+    let response = other_token
+        .put::<()>("/api/v1/crates/foo/1.0.0/release", "")
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this crate has not been published yet and the version was staged by another user"}]}"#);
+
+    let response = other_token
+        .get::<()>("/api/v1/crates/foo/1.0.0/staged/download")
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    // The user that staged the version can still publish the crate directly
+    token
+        .publish_crate(PublishBuilder::new("foo", "0.1.0"))
+        .await
+        .good();
+
+    let response = token
+        .put::<()>("/api/v1/crates/foo/1.0.0/release", "")
+        .await;
+    app.run_pending_background_jobs().await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(version_numbers(&token, "foo").await, ["1.0.0", "0.1.0"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stage_twice() {
+    let (_, _, _, token) = TestApp::full().with_token().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+
+    stage_crate(&token, PublishBuilder::new("foo", "1.1.0"))
+        .await
+        .good();
+
+    let response = stage_crate(&token, PublishBuilder::new("foo", "1.1.0")).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate version `1.1.0` is already staged"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stage_existing_version() {
+    let (_, _, _, token) = TestApp::full().with_token().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+
+    let response = stage_crate(&token, PublishBuilder::new("foo", "1.0.0")).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate version `1.0.0` is already uploaded"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn release_requires_endpoint_scope() {
+    let (app, _, user, token) = TestApp::full().with_token().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+
+    let crate_scopes = Some(vec![CrateScope::try_from("foo").unwrap()]);
+    let endpoint_scopes = Some(vec![EndpointScope::PublishUpdate]);
+    let scoped_token = user
+        .db_new_scoped_token("publish-only", crate_scopes, endpoint_scopes, None)
+        .await;
+
+    stage_crate(&scoped_token, PublishBuilder::new("foo", "1.1.0"))
+        .await
+        .good();
+
+    let response = scoped_token
+        .put::<()>("/api/v1/crates/foo/1.1.0/release", "")
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this token does not have the required permissions to perform this action"}]}"#);
+
+    let crate_scopes = Some(vec![CrateScope::try_from("foo").unwrap()]);
+    let endpoint_scopes = Some(vec![EndpointScope::PublishRelease]);
+    let release_token = user
+        .db_new_scoped_token("release-only", crate_scopes, endpoint_scopes, None)
+        .await;
+
+    let response = release_token
+        .put::<()>("/api/v1/crates/foo/1.1.0/release", "")
+        .await;
+    app.run_pending_background_jobs().await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(version_numbers(&token, "foo").await, ["1.1.0", "1.0.0"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn release_by_non_owner() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+
+    stage_crate(&token, PublishBuilder::new("foo", "1.1.0"))
+        .await
+        .good();
+
+    let other_user = app.db_new_user("bar").await;
+    let response = other_user
+        .put::<()>("/api/v1/crates/foo/1.1.0/release", "")
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this crate exists but you don't seem to be an owner. If you believe this is a mistake, perhaps you need to accept an invitation to be an owner before publishing."}]}"#);
+    assert_eq!(version_numbers(&token, "foo").await, ["1.0.0"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn download_staged_version() {
+    let (app, anon, _, token) = TestApp::full().with_token().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+
+    stage_crate(&token, PublishBuilder::new("foo", "1.1.0"))
+        .await
+        .good();
+
+    let staged_file = app
+        .stored_files()
+        .await
+        .into_iter()
+        .find(|path| path.starts_with("private/staged-crates/"))
+        .unwrap();
+
+    let store = app.as_inner().storage.as_inner();
+    let path = object_store::path::Path::parse(&staged_file).unwrap();
+    let staged_bytes = store.get(&path).await.unwrap().bytes().await.unwrap();
+
+    // The file is served by the API instead of redirecting to the CDN
+    let response = token
+        .get::<()>("/api/v1/crates/foo/1.1.0/staged/download")
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(response.headers()["content-type"], "application/gzip");
+    assert_eq!(response.headers()["cache-control"], "private,no-store");
+    assert_eq!(
+        response.headers()["content-disposition"],
+        r#"attachment; filename="foo-1.1.0.crate""#
+    );
+    assert_eq!(response.body(), &staged_bytes);
+
+    let response = anon
+        .get::<()>("/api/v1/crates/foo/1.1.0/staged/download")
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    let other_user = app.db_new_user("bar").await;
+    let response = other_user
+        .get::<()>("/api/v1/crates/foo/1.1.0/staged/download")
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only owners of this crate are allowed to download staged versions"}]}"#);
+
+    let response = token
+        .get::<()>("/api/v1/crates/foo/1.0.0/staged/download")
+        .await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+    let json: Value = response.json();
+    assert_snapshot!(json["errors"][0]["detail"], @r#""crate `foo` does not have a staged version `1.0.0`""#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn download_requires_read_scope() {
+    let (_, _, user, token) = TestApp::full().with_token().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+
+    stage_crate(&token, PublishBuilder::new("foo", "1.1.0"))
+        .await
+        .good();
+
+    // A token that can only read is enough to verify the staged version...
+    let crate_scopes = Some(vec![CrateScope::try_from("foo").unwrap()]);
+    let endpoint_scopes = Some(vec![EndpointScope::ReadPrivate]);
+    let read_token = user
+        .db_new_scoped_token("read-only", crate_scopes, endpoint_scopes, None)
+        .await;
+
+    let response = read_token
+        .get::<()>("/api/v1/crates/foo/1.1.0/staged/download")
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    // ...but it can not release it
+    let response = read_token
+        .put::<()>("/api/v1/crates/foo/1.1.0/release", "")
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    let crate_scopes = Some(vec![CrateScope::try_from("foo").unwrap()]);
+    let endpoint_scopes = Some(vec![EndpointScope::PublishRelease]);
+    let release_token = user
+        .db_new_scoped_token("release-only", crate_scopes, endpoint_scopes, None)
+        .await;
+
+    let response = release_token
+        .get::<()>("/api/v1/crates/foo/1.1.0/staged/download")
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+}
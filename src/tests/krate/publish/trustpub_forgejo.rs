@@ -0,0 +1,123 @@
+use crate::builders::PublishBuilder;
+use crate::util::{MockTokenUser, RequestHelper, TestApp};
+use crates_io_trustpub::forgejo::FORGEJO_ISSUER_URL;
+use crates_io_trustpub::forgejo::test_helpers::FullForgejoClaims;
+use crates_io_trustpub::keystore::MockOidcKeyStore;
+use crates_io_trustpub::test_keys::encode_for_testing;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
+
+/// Test the full flow of publishing a crate with OIDC authentication
+/// (aka. "Trusted Publishing") from Forgejo Actions
+///
+/// This test will:
+///
+/// 1. Publish a new crate via API token.
+/// 2. Create a Trusted Publishing configuration.
+/// 3. Generate a new OIDC token and exchange it for a temporary access token.
+/// 4. Publish a new version of the crate using the temporary access token.
+/// 5. Revoke the temporary access token.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_full_flow() -> anyhow::Result<()> {
+    const CRATE_NAME: &str = "foo";
+
+    const OWNER_NAME: &str = "rust-lang";
+    const OWNER_ID: i32 = 42;
+    const REPOSITORY_NAME: &str = "foo-rs";
+    const WORKFLOW_FILENAME: &str = "publish.yml";
+
+    let (app, client, cookie_client, api_token_client) = TestApp::full()
+        .with_oidc_keystore(FORGEJO_ISSUER_URL, MockOidcKeyStore::with_test_key())
+        .with_token()
+        .await;
+
+    // Step 1: Publish a new crate via API token
+
+    let pb = PublishBuilder::new(CRATE_NAME, "1.0.0");
+    let response = api_token_client.publish_crate(pb).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    // Step 2: Create a Trusted Publishing configuration
+
+    let body = serde_json::to_vec(&json!({
+        "forgejo_config": {
+            "crate": CRATE_NAME,
+            "repository_owner": OWNER_NAME,
+            "repository_name": REPOSITORY_NAME,
+            "workflow_filename": WORKFLOW_FILENAME,
+        }
+    }))?;
+
+    let url = "/api/v1/trusted_publishing/forgejo_configs";
+    let response = cookie_client.post::<()>(url, body).await;
+
+    assert_json_snapshot!(response.json(), { ".forgejo_config.created_at" => "[datetime]" }, @r#"
+    {
+      "forgejo_config": {
+        "crate": "foo",
+        "created_at": "[datetime]",
+        "id": 1,
+        "repository_name": "foo-rs",
+        "repository_owner": "rust-lang",
+        "repository_owner_id": null,
+        "workflow_filename": "publish.yml"
+      }
+    }
+    "#);
+
+    assert_snapshot!(response.status(), @"200 OK");
+
+    // Step 3: Generate a new OIDC token and exchange it for a temporary access token
+
+    let claims = FullForgejoClaims::builder()
+        .owner_id(OWNER_ID)
+        .owner_name(OWNER_NAME)
+        .repository_name(REPOSITORY_NAME)
+        .workflow_filename(WORKFLOW_FILENAME)
+        .build();
+
+    let jwt = encode_for_testing(&claims)?;
+
+    let body = serde_json::to_vec(&json!({ "jwt": jwt }))?;
+    let response = client
+        .post::<()>("/api/v1/trusted_publishing/tokens", body)
+        .await;
+    let json = response.json();
+    assert_json_snapshot!(json, { ".token" => "[token]" }, @r#"
+    {
+      "token": "[token]"
+    }
+    "#);
+    assert_snapshot!(response.status(), @"200 OK");
+    let token = json["token"].as_str().unwrap_or_default();
+
+    // Step 4: Publish a new version of the crate using the temporary access token
+
+    let oidc_token_client = MockTokenUser::with_auth_header(token.to_string(), app.clone());
+
+    let pb = PublishBuilder::new(CRATE_NAME, "1.1.0");
+    let response = oidc_token_client.publish_crate(pb).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    // Step 4b: Verify the new version was published successfully
+
+    let url = format!("/api/v1/crates/{CRATE_NAME}/1.1.0");
+    let response = client.get::<()>(&url).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), {
+        ".version.created_at" => "[datetime]",
+        ".version.updated_at" => "[datetime]",
+        ".version.audit_actions[].time" => "[datetime]",
+    });
+
+    // Step 5: Revoke the temporary access token
+
+    let response = oidc_token_client
+        .delete::<()>("/api/v1/trusted_publishing/tokens")
+        .await;
+    assert_snapshot!(response.status(), @"204 No Content");
+
+    assert_snapshot!(app.emails_snapshot().await);
+
+    Ok(())
+}
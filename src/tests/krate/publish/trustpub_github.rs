@@ -87,7 +87,9 @@ async fn test_full_flow() -> anyhow::Result<()> {
         "crate": "foo",
         "created_at": "[datetime]",
         "environment": null,
+        "expires_at": null,
         "id": 1,
+        "ref_pattern": null,
         "repository_name": "foo-rs",
         "repository_owner": "rust-lang",
         "repository_owner_id": 42,
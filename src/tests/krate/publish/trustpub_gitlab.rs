@@ -68,10 +68,13 @@ async fn test_full_flow() -> anyhow::Result<()> {
         "crate": "foo",
         "created_at": "[datetime]",
         "environment": null,
+        "expires_at": null,
         "id": 1,
+        "issuer": "https://gitlab.com",
         "namespace": "rust-lang",
         "namespace_id": null,
         "project": "foo-rs",
+        "ref_pattern": null,
         "workflow_filepath": ".gitlab-ci.yml"
       }
     }
@@ -44,6 +44,18 @@ async fn invalid_names() {
     assert_that!(app.stored_files().await, is_empty());
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn reserved_route_names() {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+
+    // Would be shadowed by the `/api/v1/crates/suggest` endpoint
+    let crate_to_publish = PublishBuilder::new("suggest", "1.0.0");
+    let response = token.publish_crate(crate_to_publish).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"cannot upload a crate with a reserved name"}]}"#);
+    assert_that!(app.stored_files().await, is_empty());
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn invalid_version() {
     let (app, _, _, token) = TestApp::full().with_token().await;
@@ -2,6 +2,7 @@ use crate::builders::{CrateBuilder, PublishBuilder};
 use crate::util::{MockAnonymousUser, MockCookieUser, MockTokenUser, RequestHelper, Response};
 use crate::{TestApp, add_team_to_crate, new_team};
 use crates_io::models::Crate;
+use crates_io::models::token::EndpointScope;
 use crates_io::schema::emails;
 use crates_io::views::{
     EncodableCrateOwnerInvitationV1, EncodableOwner, EncodablePublicUser, InvitationResponse,
@@ -425,6 +426,39 @@ async fn api_token_cannot_list_invitations_v1() {
     assert_snapshot!(response.status(), @"403 Forbidden");
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn scoped_api_token_can_list_invitations_v1() {
+    let (app, _, owner, token) = TestApp::init().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("invited_crate", owner.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let user = app.db_new_user("invited_user").await;
+    token
+        .add_named_owner("invited_crate", "invited_user")
+        .await
+        .good();
+
+    let url = "/api/v1/me/crate_owner_invitations";
+
+    let scopes = Some(vec![EndpointScope::PublishUpdate]);
+    let publish_token = user
+        .db_new_scoped_token("publish", None, scopes, None)
+        .await;
+    let response = publish_token.get::<()>(url).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    let scopes = Some(vec![EndpointScope::ReadPrivate]);
+    let read_token = user.db_new_scoped_token("read", None, scopes, None).await;
+    let response = read_token.get::<InvitationListResponse>(url).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let invitations = response.good().crate_owner_invitations;
+    assert_eq!(invitations.len(), 1);
+    assert_eq!(invitations[0].crate_name, "invited_crate");
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn invitations_list_v1() {
     let (app, _, owner, token) = TestApp::init().with_token().await;
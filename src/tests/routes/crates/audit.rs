@@ -0,0 +1,174 @@
+use crate::builders::CrateBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::models::CrateOwner;
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::{Value, json};
+
+const URL: &str = "/api/v1/crates/foo/audit";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_audit_log() {
+    let (app, _, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    let krate = CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    // An empty audit log
+    let response = user.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), @r#"
+    {
+      "audit_actions": [],
+      "meta": {
+        "next_page": null,
+        "total": 0
+      }
+    }
+    "#);
+
+    let user2 = app.db_new_user("user2").await;
+    let user3 = app.db_new_user("user3").await;
+    CrateOwner::builder()
+        .crate_id(krate.id)
+        .user_id(user3.as_model().id)
+        .created_by(user.as_model().id)
+        .build()
+        .insert(&mut conn)
+        .await
+        .unwrap();
+
+    token.add_named_owner("foo", "user2").await.good();
+    user.remove_named_owner("foo", "user3").await.good();
+
+    let body = json!({ "crate": { "trustpub_only": true } });
+    let response = user
+        .patch::<()>("/api/v1/crates/foo", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = json!({ "crate": { "deprecation": { "reason": "Unmaintained" } } });
+    let response = user
+        .patch::<()>("/api/v1/crates/foo", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = user.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), {
+        ".audit_actions[].time" => "[datetime]",
+        ".audit_actions[].user.id" => "[id]",
+    });
+
+    // Invited owners can not see the audit log until they accept the invitation
+    let response = user2.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only owners of this crate are allowed to view its audit log"}]}"#);
+
+    // Accepting the invitation is recorded in the audit log
+    let body = json!({ "crate_owner_invite": { "crate_id": krate.id, "accepted": true } });
+    let url = format!("/api/v1/me/crate_owner_invitations/{}", krate.id);
+    let response = user2.put::<()>(&url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let response = user2.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json()["audit_actions"][0], {
+        ".time" => "[datetime]",
+        ".user.id" => "[id]",
+    }, @r#"
+    {
+      "action": "owner_add",
+      "api_token_id": null,
+      "details": {
+        "owner": "user2"
+      },
+      "id": 5,
+      "time": "[datetime]",
+      "trustpub_data": null,
+      "user": {
+        "avatar": null,
+        "id": "[id]",
+        "login": "user2",
+        "name": null,
+        "url": "https://github.com/user2"
+      }
+    }
+    "#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pagination() {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    for trustpub_only in [true, false, true] {
+        let body = json!({ "crate": { "trustpub_only": trustpub_only } });
+        let response = user
+            .patch::<()>("/api/v1/crates/foo", body.to_string())
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let mut url = format!("{URL}?per_page=2");
+    let mut values = Vec::new();
+    loop {
+        let response = user.get::<()>(&url).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let json = response.json();
+        assert_eq!(json["meta"]["total"], 3);
+
+        let actions = json["audit_actions"].as_array().unwrap();
+        values.extend(
+            actions
+                .iter()
+                .map(|a| a["details"]["trustpub_only"].clone()),
+        );
+
+        match json["meta"]["next_page"].as_str() {
+            Some(next_page) => url = format!("{URL}{next_page}"),
+            None => break,
+        }
+    }
+
+    // The newest entries are returned first
+    assert_eq!(
+        values,
+        [Value::Bool(true), Value::Bool(false), Value::Bool(true)]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_requires_ownership() {
+    let (app, anon, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let response = anon.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action requires authentication"}]}"#);
+
+    let other_user = app.db_new_user("other").await;
+    let response = other_user.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only owners of this crate are allowed to view its audit log"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unknown_crate() {
+    let (_, _, user) = TestApp::full().with_user().await;
+
+    let response = user.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `foo` does not exist"}]}"#);
+}
@@ -5,9 +5,10 @@ use bigdecimal::ToPrimitive;
 use chrono::{TimeDelta, Utc};
 use claims::{assert_none, assert_some};
 use crates_io::controllers::krate::delete::{DOWNLOADS_PER_MONTH_LIMIT, DeleteQueryParams};
-use crates_io::models::OwnerKind;
+use crates_io::models::token::{CrateScope, EndpointScope};
+use crates_io::models::{CrateAction, OwnerKind};
 use crates_io::schema::{crate_downloads, crates};
-use crates_io_database::schema::crate_owners;
+use crates_io_database::schema::{crate_audit_actions, crate_owners};
 use diesel::prelude::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use http::{Request, StatusCode};
@@ -54,6 +55,7 @@ async fn test_happy_path_new_crate() -> anyhow::Result<()> {
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/foo/foo-1.0.0.crate
     index/3/f/foo
+    index/changes/1/0.json
     rss/crates.xml
     rss/crates/foo.xml
     rss/updates.xml
@@ -69,10 +71,22 @@ async fn test_happy_path_new_crate() -> anyhow::Result<()> {
     assert_crate_exists(&anon, "foo", false).await;
     assert!(!upstream.crate_exists("foo")?);
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    index/changes/1/0.json
     rss/crates.xml
     rss/updates.xml
     ");
 
+    // Assert that the deletion was recorded in the audit log
+    let actions = crate_audit_actions::table
+        .select((
+            crate_audit_actions::crate_id,
+            crate_audit_actions::crate_name,
+            crate_audit_actions::action,
+        ))
+        .load::<(Option<i32>, String, CrateAction)>(&mut conn)
+        .await?;
+    assert_eq!(actions, [(None, "foo".to_string(), CrateAction::Delete)]);
+
     Ok(())
 }
 
@@ -91,6 +105,7 @@ async fn test_happy_path_old_crate() -> anyhow::Result<()> {
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/foo/foo-1.0.0.crate
     index/3/f/foo
+    index/changes/1/0.json
     rss/crates.xml
     rss/crates/foo.xml
     rss/updates.xml
@@ -106,6 +121,7 @@ async fn test_happy_path_old_crate() -> anyhow::Result<()> {
     assert_crate_exists(&anon, "foo", false).await;
     assert!(!upstream.crate_exists("foo")?);
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    index/changes/1/0.json
     rss/crates.xml
     rss/updates.xml
     ");
@@ -128,6 +144,7 @@ async fn test_happy_path_really_old_crate() -> anyhow::Result<()> {
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
     crates/foo/foo-1.0.0.crate
     index/3/f/foo
+    index/changes/1/0.json
     rss/crates.xml
     rss/crates/foo.xml
     rss/updates.xml
@@ -143,6 +160,7 @@ async fn test_happy_path_really_old_crate() -> anyhow::Result<()> {
     assert_crate_exists(&anon, "foo", false).await;
     assert!(!upstream.crate_exists("foo")?);
     assert_snapshot!(app.stored_files().await.join("\n"), @r"
+    index/changes/1/0.json
     rss/crates.xml
     rss/updates.xml
     ");
@@ -173,13 +191,54 @@ async fn test_token_auth() -> anyhow::Result<()> {
 
     let response = delete_crate(&token, "foo").await;
     assert_snapshot!(response.status(), @"403 Forbidden");
-    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action can only be performed on the crates.io website"}]}"#);
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"This endpoint cannot be used with legacy API tokens. Use a scoped API token instead."}]}"#);
 
     assert_crate_exists(&anon, "foo", true).await;
 
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_scoped_token_auth() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    publish_crate(&user, "foo").await;
+
+    let scopes = Some(vec![EndpointScope::PublishUpdate, EndpointScope::Yank]);
+    let token = user.db_new_scoped_token("yank", None, scopes, None).await;
+    let response = delete_crate(&token, "foo").await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this token does not have the required permissions to perform this action"}]}"#);
+
+    let crate_scopes = Some(vec![CrateScope::try_from("bar").unwrap()]);
+    let scopes = Some(vec![EndpointScope::Delete]);
+    let token = user
+        .db_new_scoped_token("delete-bar", crate_scopes, scopes, None)
+        .await;
+    let response = delete_crate(&token, "foo").await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    assert_crate_exists(&anon, "foo", true).await;
+
+    let scopes = Some(vec![EndpointScope::Delete]);
+    let token = user.db_new_scoped_token("delete", None, scopes, None).await;
+    let response = delete_crate(&token, "foo").await;
+    assert_snapshot!(response.status(), @"204 No Content");
+
+    assert_crate_exists(&anon, "foo", false).await;
+
+    // Assert that the token was recorded in the audit log
+    let api_token_id: Option<i32> = crate_audit_actions::table
+        .filter(crate_audit_actions::action.eq(CrateAction::Delete))
+        .select(crate_audit_actions::api_token_id)
+        .get_result(&mut conn)
+        .await?;
+    assert_eq!(api_token_id, Some(token.as_model().id));
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_missing_crate() -> anyhow::Result<()> {
     let (_app, _anon, user) = TestApp::full().with_user().await;
@@ -0,0 +1,123 @@
+use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::util::{RequestHelper, TestApp};
+use chrono::NaiveDate;
+use crates_io::schema::{crates, version_downloads, versions};
+use crates_io::worker::jobs;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use insta::{assert_json_snapshot, assert_snapshot};
+
+async fn save_version_downloads(
+    conn: &mut AsyncPgConnection,
+    version: &str,
+    date: &str,
+    downloads: i32,
+) {
+    let version_id = versions::table
+        .inner_join(crates::table)
+        .filter(crates::name.eq("foo"))
+        .filter(versions::num.eq(version))
+        .select(versions::id)
+        .first::<i32>(conn)
+        .await
+        .unwrap();
+
+    diesel::insert_into(version_downloads::table)
+        .values((
+            version_downloads::version_id.eq(version_id),
+            version_downloads::date.eq(date.parse::<NaiveDate>().unwrap()),
+            version_downloads::downloads.eq(downloads),
+        ))
+        .execute(conn)
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_download_history() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .version(VersionBuilder::new("1.0.0"))
+        .version(VersionBuilder::new("2.0.0"))
+        .expect_build(&mut conn)
+        .await;
+
+    save_version_downloads(&mut conn, "1.0.0", "2024-01-10", 10).await;
+    save_version_downloads(&mut conn, "1.0.0", "2024-01-29", 5).await;
+    save_version_downloads(&mut conn, "2.0.0", "2024-02-02", 7).await;
+    save_version_downloads(&mut conn, "2.0.0", "2024-02-20", 3).await;
+
+    // Roll up January and archive it, so that only the rollups are left
+    let before = "2024-02-01".parse()?;
+    jobs::RollUpVersionDownloads::before(before)
+        .enqueue(&mut conn)
+        .await?;
+    app.run_pending_background_jobs().await;
+
+    diesel::delete(version_downloads::table.filter(version_downloads::date.lt(before)))
+        .execute(&mut conn)
+        .await?;
+
+    let url = "/api/v1/crates/foo/downloads/history";
+
+    let response = anon.get::<()>(url).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), @r#"
+    {
+      "downloads": [
+        {
+          "date": "2024-01-01",
+          "downloads": 15
+        },
+        {
+          "date": "2024-02-01",
+          "downloads": 10
+        }
+      ]
+    }
+    "#);
+
+    // The week of 2024-01-29 consists of rolled up and live downloads
+    let response = anon
+        .get_with_query::<()>(url, "granularity=week&from=2024-01-31&to=2024-02-19")
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), @r#"
+    {
+      "downloads": [
+        {
+          "date": "2024-01-29",
+          "downloads": 12
+        },
+        {
+          "date": "2024-02-19",
+          "downloads": 3
+        }
+      ]
+    }
+    "#);
+
+    let response = anon.get_with_query::<()>(url, "granularity=day").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"granularity must be `week` or `month`"}]}"#);
+
+    let response = anon.get_with_query::<()>(url, "from=2024-13-01").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"`from` must be a date in the format YYYY-MM-DD"}]}"#);
+
+    let response = anon
+        .get_with_query::<()>(url, "from=2024-02-01&to=2024-01-01")
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"`from` must not be after `to`"}]}"#);
+
+    let response = anon
+        .get::<()>("/api/v1/crates/unknown/downloads/history")
+        .await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+
+    Ok(())
+}
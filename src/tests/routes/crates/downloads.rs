@@ -1,12 +1,14 @@
 use crate::builders::{CrateBuilder, VersionBuilder};
 use crate::util::{MockAnonymousUser, RequestHelper, TestApp};
 use chrono::{Duration, Utc};
-use crates_io::schema::{crates, version_downloads, versions};
+use crates_io::schema::{
+    cargo_version_downloads, crate_platform_downloads, crates, version_downloads, versions,
+};
 use crates_io::views::EncodableVersionDownload;
 use diesel::prelude::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use http::StatusCode;
-use insta::{assert_json_snapshot, assert_snapshot};
+use insta::{assert_debug_snapshot, assert_json_snapshot, assert_snapshot};
 use serde::Deserialize;
 
 #[derive(Deserialize)]
@@ -158,6 +160,171 @@ async fn test_crate_downloads() {
     assert_eq!(response.json(), json);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_crate_downloads_by_cargo_version() {
+    let (app, anon, cookie) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let user_id = cookie.as_model().id;
+    let krate = CrateBuilder::new("foo", user_id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let today = Utc::now().date_naive();
+    let yesterday = today - Duration::days(1);
+    let long_ago = today - Duration::days(100);
+
+    let rows = [
+        (yesterday, "1.74", 5),
+        (yesterday, "other", 1),
+        (today, "1.74", 2),
+        (today, "1.75", 7),
+        (long_ago, "1.40", 100),
+    ];
+    for (date, cargo_version, downloads) in rows {
+        diesel::insert_into(cargo_version_downloads::table)
+            .values((
+                cargo_version_downloads::crate_id.eq(krate.id),
+                cargo_version_downloads::date.eq(date),
+                cargo_version_downloads::cargo_version.eq(cargo_version),
+                cargo_version_downloads::downloads.eq(downloads),
+            ))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+    }
+
+    // The breakdown is only included on request
+    let response = anon.get::<()>("/api/v1/crates/foo/downloads").await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert!(response.json().get("cargo_version_downloads").is_none());
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates/foo/downloads", "include=cargo_versions")
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let json = response.json();
+    let breakdown = json["cargo_version_downloads"].as_array().unwrap();
+    let breakdown = breakdown
+        .iter()
+        .map(|download| {
+            let date = download["date"].as_str().unwrap();
+            let date = if date == today.to_string() {
+                "today"
+            } else {
+                "yesterday"
+            };
+            format!(
+                "{date} | {} | {}",
+                download["cargo_version"].as_str().unwrap(),
+                download["downloads"]
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_debug_snapshot!(breakdown, @r#"
+    [
+        "yesterday | 1.74 | 5",
+        "yesterday | other | 1",
+        "today | 1.75 | 7",
+        "today | 1.74 | 2",
+    ]
+    "#);
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates/foo/downloads", "include=cargo_version")
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid component for ?include= (expected 'versions', 'cargo_versions' or 'platforms')"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_crate_downloads_by_platform() {
+    let (app, anon, cookie) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let user_id = cookie.as_model().id;
+    let krate = CrateBuilder::new("foo", user_id)
+        .version("1.0.0")
+        .expect_build(&mut conn)
+        .await;
+
+    let today = Utc::now().date_naive();
+    let yesterday = today - Duration::days(1);
+    let long_ago = today - Duration::days(100);
+
+    let rows = [
+        (yesterday, "linux", "x86_64", "europe", 300),
+        (today, "linux", "x86_64", "north_america", 200),
+        (today, "linux", "aarch64", "europe", 60),
+        (yesterday, "linux", "aarch64", "europe", 50),
+        (today, "windows", "x86_64", "north_america", 150),
+        (today, "macos", "aarch64", "oceania", 99),
+        (long_ago, "other", "other", "africa", 1000),
+    ];
+    for (date, host_os, host_arch, cdn_region, downloads) in rows {
+        diesel::insert_into(crate_platform_downloads::table)
+            .values((
+                crate_platform_downloads::crate_id.eq(krate.id),
+                crate_platform_downloads::date.eq(date),
+                crate_platform_downloads::host_os.eq(host_os),
+                crate_platform_downloads::host_arch.eq(host_arch),
+                crate_platform_downloads::cdn_region.eq(cdn_region),
+                crate_platform_downloads::downloads.eq(downloads),
+            ))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+    }
+
+    // The breakdown is only included on request
+    let response = anon.get::<()>("/api/v1/crates/foo/downloads").await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert!(response.json().get("platform_downloads").is_none());
+
+    // Values with less than 100 downloads (`macos` and `oceania`) are folded
+    // into `other` together with the next smallest value, and downloads older
+    // than 90 days are not included
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates/foo/downloads", "include=platforms")
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json()["platform_downloads"], @r#"
+    {
+      "cdn_region": [
+        {
+          "downloads": 449,
+          "name": "other"
+        },
+        {
+          "downloads": 410,
+          "name": "europe"
+        }
+      ],
+      "host_arch": [
+        {
+          "downloads": 650,
+          "name": "x86_64"
+        },
+        {
+          "downloads": 209,
+          "name": "aarch64"
+        }
+      ],
+      "host_os": [
+        {
+          "downloads": 610,
+          "name": "linux"
+        },
+        {
+          "downloads": 249,
+          "name": "other"
+        }
+      ]
+    }
+    "#);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_version_downloads() {
     let (app, anon, cookie) = TestApp::init().with_user().await;
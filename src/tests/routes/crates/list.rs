@@ -1,9 +1,12 @@
 use crate::builders::{CrateBuilder, VersionBuilder};
 use crate::util::{RequestHelper, TestApp};
-use crate::{new_category, new_user};
+use crate::{add_team_to_crate, new_category, new_team, new_user};
+use chrono::{TimeZone, Utc};
 use crates_io::models::Category;
-use crates_io::schema::crates;
+use crates_io::schema::{crate_search_scores, crates};
+use crates_io::worker::jobs;
 use crates_io_database::schema::categories;
+use crates_io_worker::BackgroundJob;
 use diesel::sql_types::Timestamptz;
 use diesel::{dsl::*, prelude::*, update};
 use diesel_async::RunQueryDsl;
@@ -207,6 +210,116 @@ async fn index_queries() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn index_query_qualifiers() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    let other_user = new_user("other_user").insert(&mut conn).await?;
+    let team = new_team("github:rust-lang:core")
+        .create_or_update(&mut conn)
+        .await?;
+
+    let cats = vec![
+        new_category("Web programming", "web-programming", "Web crates"),
+        new_category("HTTP client", "web-programming::http-client", "HTTP crates"),
+    ];
+
+    insert_into(categories::table)
+        .values(cats)
+        .execute(&mut conn)
+        .await?;
+
+    let fast_client = CrateBuilder::new("fast_client", user.id)
+        .description("A fast HTTP client library")
+        .keyword("http")
+        .keyword("async")
+        .category("web-programming::http-client")
+        .downloads(50000)
+        .updated_at(Utc.with_ymd_and_hms(2025, 3, 1, 12, 0, 0).unwrap())
+        .expect_build(&mut conn)
+        .await;
+
+    add_team_to_crate(&team, &fast_client, user, &mut conn).await?;
+
+    CrateBuilder::new("slow_client", other_user.id)
+        .description("A client for slow HTTP servers")
+        .keyword("http")
+        .downloads(500)
+        .updated_at(Utc.with_ymd_and_hms(2024, 1, 15, 23, 59, 59).unwrap())
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("old_yanked", user.id)
+        .keyword("async")
+        .version(VersionBuilder::new("1.0.0").yanked(true))
+        .downloads(20000)
+        .updated_at(Utc.with_ymd_and_hms(2023, 6, 1, 0, 0, 0).unwrap())
+        .expect_build(&mut conn)
+        .await;
+
+    let names = |json: &crate::CrateList| {
+        json.crates
+            .iter()
+            .map(|c| c.name.clone())
+            .collect::<Vec<_>>()
+    };
+
+    let queries: &[(&str, &[&str])] = &[
+        ("keyword:http", &["fast_client", "slow_client"]),
+        ("keyword:HTTP%20keyword:async", &["fast_client"]),
+        ("category:web-programming", &["fast_client"]),
+        ("category:web-programming::http-client", &["fast_client"]),
+        ("owner:FOO", &["fast_client", "old_yanked"]),
+        ("owner:other_user", &["slow_client"]),
+        ("owner:github:rust-lang:core", &["fast_client"]),
+        ("downloads:%3E10000", &["fast_client", "old_yanked"]),
+        ("downloads:%3C%3D500", &["slow_client"]),
+        ("downloads:500", &["slow_client"]),
+        ("updated:%3E2024-01-15", &["fast_client"]),
+        ("updated:%3E%3D2024-01-15", &["fast_client", "slow_client"]),
+        ("updated:2024-01-15", &["slow_client"]),
+        ("updated:%3C2024-01-15", &["old_yanked"]),
+        ("keyword:async%20-yanked", &["fast_client"]),
+        ("http%20client", &["fast_client", "slow_client"]),
+        ("%22http%20client%22", &["fast_client"]),
+        ("http%20keyword:async%20downloads:%3E1000", &["fast_client"]),
+    ];
+
+    for (query, expected) in queries {
+        for json in search_both(&anon, &format!("q={query}&sort=alphabetical")).await {
+            assert_eq!(names(&json), *expected, "q={query}");
+            assert_eq!(json.meta.total, expected.len() as i32, "q={query}");
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn index_query_syntax_errors() {
+    let (_app, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/api/v1/crates?q=keyword:").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"missing value for search qualifier `keyword`"}]}"#);
+
+    let response = anon.get::<()>("/api/v1/crates?q=downloads:%3Elots").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid value `>lots` for search qualifier `downloads`, expected a number like `>1000`"}]}"#);
+
+    let response = anon.get::<()>("/api/v1/crates?q=%22http%20client").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"unterminated quote in search query"}]}"#);
+
+    // Paths, unknown qualifiers and negations are searched as free text
+    for query in ["tokio::spawn", "keyword::foo", "license:MIT", "-sys"] {
+        let response = anon.get::<()>(&format!("/api/v1/crates?q={query}")).await;
+        assert_eq!(response.status(), StatusCode::OK, "q={query}");
+    }
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn search_includes_crates_where_name_is_stopword() -> anyhow::Result<()> {
     let (app, anon, user) = TestApp::init().with_user().await;
@@ -795,6 +908,316 @@ async fn index_include_yanked() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn index_include_deprecated() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    CrateBuilder::new("maintained", user.id)
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("unmaintained", user.id)
+        .deprecated("No longer maintained", Some("maintained"))
+        .expect_build(&mut conn)
+        .await;
+
+    // Include deprecated crates by default
+    for json in search_both(&anon, "sort=alphabetical").await {
+        assert_eq!(json.meta.total, 2);
+        assert_eq!(json.crates[0].name, "maintained");
+        assert!(json.crates[0].deprecation.is_none());
+        assert_eq!(json.crates[1].name, "unmaintained");
+
+        let deprecation = json.crates[1].deprecation.as_ref().unwrap();
+        assert_eq!(deprecation.reason, "No longer maintained");
+        assert_eq!(deprecation.successor.as_deref(), Some("maintained"));
+    }
+
+    // Do not include deprecated crates
+    for json in search_both(&anon, "include_deprecated=no&sort=alphabetical").await {
+        assert_eq!(json.meta.total, 1);
+        assert_eq!(json.crates[0].name, "maintained");
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn index_by_feature_bin_name_and_links() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    CrateBuilder::new("with_serde", user.id)
+        .version(VersionBuilder::new("1.0.0").feature("serde", &["dep:serde"]))
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("dropped_serde", user.id)
+        .version(VersionBuilder::new("1.0.0").feature("serde", &["dep:serde"]))
+        .version(VersionBuilder::new("2.0.0").feature("std", &[]))
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("yanked_serde", user.id)
+        .version(VersionBuilder::new("1.0.0").feature("serde", &["dep:serde"]))
+        .version(
+            VersionBuilder::new("2.0.0")
+                .yanked(true)
+                .feature("std", &[]),
+        )
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("cli_tool", user.id)
+        .version(VersionBuilder::new("1.0.0").bin_names(&["tool", "tool-helper"]))
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("libz_sys", user.id)
+        .version(VersionBuilder::new("1.0.0").links("z"))
+        .expect_build(&mut conn)
+        .await;
+
+    // Only the default version is considered
+    for json in search_both(&anon, "feature=serde").await {
+        assert_eq!(json.meta.total, 2);
+        assert_eq!(json.crates[0].name, "with_serde");
+        assert_eq!(json.crates[1].name, "yanked_serde");
+    }
+
+    for json in search_both(&anon, "feature=std").await {
+        assert_eq!(json.meta.total, 1);
+        assert_eq!(json.crates[0].name, "dropped_serde");
+    }
+
+    for json in search_both(&anon, "bin_name=tool-helper").await {
+        assert_eq!(json.meta.total, 1);
+        assert_eq!(json.crates[0].name, "cli_tool");
+    }
+
+    for json in search_both(&anon, "bin_name=tool-").await {
+        assert_eq!(json.meta.total, 0);
+    }
+
+    for json in search_both(&anon, "links=z").await {
+        assert_eq!(json.meta.total, 1);
+        assert_eq!(json.crates[0].name, "libz_sys");
+    }
+
+    // Filters can be combined with each other and with the other parameters
+    for json in search_both(&anon, "feature=serde&q=yanked").await {
+        assert_eq!(json.meta.total, 1);
+        assert_eq!(json.crates[0].name, "yanked_serde");
+    }
+
+    for json in search_both(&anon, "feature=serde&links=z").await {
+        assert_eq!(json.meta.total, 0);
+    }
+
+    let (resp, calls) = page_with_seek(&anon, "feature=serde&sort=new").await;
+    assert_eq!(resp[0].crates[0].name, "yanked_serde");
+    assert_eq!(resp[1].crates[0].name, "with_serde");
+    assert_eq!(resp[1].meta.total, 2);
+    assert_eq!(calls, 3);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn seek_based_pagination_with_feature_and_bin_name() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    // Matching and non-matching crates are interleaved, and several of them
+    // share the same (recent) download counts, so that the seek conditions
+    // have to fall back to the crate ID to break ties.
+    for i in 0..10 {
+        let mut version = VersionBuilder::new("1.0.0");
+        if i % 3 != 1 {
+            version = version.feature("serde", &["dep:serde"]);
+        }
+        if i % 2 == 0 {
+            version = version.bin_names(&["tool"]);
+        }
+
+        let name = format!("seek_{i}");
+        let mut builder = CrateBuilder::new(&name, user.id)
+            .downloads(i % 3 * 10)
+            .version(version);
+        if i % 4 != 0 {
+            builder = builder.recent_downloads(i % 2 * 5);
+        }
+        builder.expect_build(&mut conn).await;
+    }
+
+    // Crates that only match in a version other than the default version
+    // must not show up on any page.
+    CrateBuilder::new("seek_dropped", user.id)
+        .version(
+            VersionBuilder::new("1.0.0")
+                .feature("serde", &["dep:serde"])
+                .bin_names(&["tool"]),
+        )
+        .version(VersionBuilder::new("2.0.0"))
+        .expect_build(&mut conn)
+        .await;
+
+    let filters = [
+        ("feature=serde", 7),
+        ("bin_name=tool", 5),
+        ("feature=serde&bin_name=tool", 4),
+    ];
+    let sorts = [
+        "sort=alpha",
+        "sort=new",
+        "sort=downloads",
+        "sort=recent-downloads",
+        "sort=recent-updates",
+    ];
+
+    for (filter, expected_total) in filters {
+        for sort in sorts {
+            let query = format!("{filter}&{sort}");
+
+            let json = anon.search(&format!("per_page=100&{query}")).await;
+            assert_eq!(json.meta.total, expected_total, "{query}");
+            let expected = json.crates.iter().map(|c| &c.name).collect::<Vec<_>>();
+
+            let (resp, calls) = page_with_seek(&anon, &query).await;
+            let names = resp
+                .iter()
+                .flat_map(|json| &json.crates)
+                .map(|c| &c.name)
+                .collect::<Vec<_>>();
+
+            assert_eq!(names, expected, "{query}");
+            assert_eq!(calls, expected_total + 1, "{query}");
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn index_by_rust_version_edition_license_and_has_lib() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    CrateBuilder::new("old_msrv", user.id)
+        .version(
+            VersionBuilder::new("1.0.0")
+                .rust_version("1.56")
+                .edition("2021")
+                .license("MIT OR Apache-2.0")
+                .has_lib(true),
+        )
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("new_msrv", user.id)
+        .version(
+            VersionBuilder::new("1.0.0")
+                .rust_version("1.80.1")
+                .edition("2024")
+                .license("Apache-2.0")
+                .has_lib(true),
+        )
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("exact_msrv", user.id)
+        .version(VersionBuilder::new("1.0.0").rust_version("1.85"))
+        .version(
+            VersionBuilder::new("2.0.0")
+                .rust_version("1.70")
+                .license("MIT/Apache-2.0")
+                .has_lib(false),
+        )
+        .expect_build(&mut conn)
+        .await;
+
+    CrateBuilder::new("no_msrv", user.id)
+        .version(VersionBuilder::new("1.0.0").license("GPL-3.0+"))
+        .expect_build(&mut conn)
+        .await;
+
+    let names = |json: &crate::CrateList| {
+        json.crates
+            .iter()
+            .map(|c| c.name.clone())
+            .collect::<Vec<_>>()
+    };
+
+    for json in search_both(&anon, "rust_version_max=1.70").await {
+        assert_eq!(json.meta.total, 3);
+        assert_eq!(names(&json), ["exact_msrv", "no_msrv", "old_msrv"]);
+    }
+
+    for json in search_both(&anon, "rust_version_max=1.69.9").await {
+        assert_eq!(names(&json), ["no_msrv", "old_msrv"]);
+    }
+
+    for json in search_both(&anon, "rust_version_max=1.81").await {
+        assert_eq!(json.meta.total, 4);
+    }
+
+    for json in search_both(&anon, "edition=2021").await {
+        assert_eq!(names(&json), ["old_msrv"]);
+    }
+
+    for json in search_both(&anon, "license=MIT").await {
+        assert_eq!(names(&json), ["exact_msrv", "old_msrv"]);
+    }
+
+    for json in search_both(&anon, "license=Apache-2.0").await {
+        assert_eq!(names(&json), ["exact_msrv", "new_msrv", "old_msrv"]);
+    }
+
+    for json in search_both(&anon, "license=MIT%20AND%20Apache-2.0").await {
+        assert_eq!(names(&json), ["exact_msrv", "old_msrv"]);
+    }
+
+    for json in search_both(&anon, "license=MIT%20OR%20GPL-3.0").await {
+        assert_eq!(names(&json), ["exact_msrv", "no_msrv", "old_msrv"]);
+    }
+
+    for json in search_both(&anon, "has_lib=yes").await {
+        assert_eq!(names(&json), ["new_msrv", "old_msrv"]);
+    }
+
+    for json in search_both(&anon, "has_lib=no&rust_version_max=1.70").await {
+        assert_eq!(names(&json), ["exact_msrv"]);
+    }
+
+    let (resp, calls) = page_with_seek(&anon, "license=MIT&sort=downloads").await;
+    assert_eq!(resp[0].meta.total, 2);
+    assert_eq!(calls, 3);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invalid_default_version_filters() {
+    let (_app, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/api/v1/crates?rust_version_max=1.x").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"rust_version_max value must be a Rust version like `1.70` or `1.70.0`"}]}"#);
+
+    let response = anon.get::<()>("/api/v1/crates?license=apache%202.0").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"license value must be a valid SPDX license expression"}]}"#);
+
+    let response = anon.get::<()>("/api/v1/crates?has_lib=maybe").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"has_lib value must be `yes` or `no`"}]}"#);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn yanked_versions_are_not_considered_for_max_version() -> anyhow::Result<()> {
     let (app, anon, user) = TestApp::init().with_user().await;
@@ -1036,6 +1459,80 @@ async fn pagination_links_included_if_applicable() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn relevance_includes_search_scores() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user = user.as_model();
+
+    let ten_years_ago = Utc::now() - chrono::Duration::days(3650);
+    CrateBuilder::new("abandoned", user.id)
+        .description("http http http client")
+        .version(VersionBuilder::new("0.1.0").created_at(ten_years_ago))
+        .expect_build(&mut conn)
+        .await;
+
+    let popular = CrateBuilder::new("popular", user.id)
+        .description("An HTTP client")
+        .recent_downloads(10_000_000)
+        .expect_build(&mut conn)
+        .await;
+
+    for name in ["dependent_a", "dependent_b", "dependent_c"] {
+        CrateBuilder::new(name, user.id)
+            .version(VersionBuilder::new("1.0.0").dependency(&popular, None))
+            .expect_build(&mut conn)
+            .await;
+    }
+
+    let names = |pages: &[crate::CrateList]| {
+        pages
+            .iter()
+            .flat_map(|page| page.crates.iter().map(|c| c.name.clone()))
+            .collect::<Vec<_>>()
+    };
+
+    // Without scores, the text rank decides
+    let (resp, calls) = page_with_seek(&anon, "q=http").await;
+    assert_eq!(names(&resp), ["abandoned", "popular"]);
+    assert_eq!(calls, 3);
+
+    jobs::UpdateSearchScores.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let scores: Vec<(String, i64, bool)> = crate_search_scores::table
+        .inner_join(crates::table)
+        .select((
+            crates::name,
+            crate_search_scores::reverse_dependencies,
+            crate_search_scores::last_release_at.is_not_null(),
+        ))
+        .order(crates::name)
+        .load(&mut conn)
+        .await?;
+
+    assert_eq!(
+        scores,
+        [
+            ("abandoned".to_string(), 0, true),
+            ("dependent_a".to_string(), 0, true),
+            ("dependent_b".to_string(), 0, true),
+            ("dependent_c".to_string(), 0, true),
+            ("popular".to_string(), 3, true),
+        ]
+    );
+
+    let (resp, calls) = page_with_seek(&anon, "q=http").await;
+    assert_eq!(names(&resp), ["popular", "abandoned"]);
+    assert_eq!(calls, 3);
+
+    for json in search_both(&anon, "q=http").await {
+        assert_eq!(names(&[json]), ["popular", "abandoned"]);
+    }
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn seek_based_pagination() -> anyhow::Result<()> {
     let (app, anon, user) = TestApp::init().with_user().await;
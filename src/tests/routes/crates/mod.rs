@@ -1,5 +1,7 @@
 mod admin;
+mod audit;
 mod delete;
+mod download_history;
 pub mod downloads;
 mod following;
 mod list;
@@ -7,5 +9,7 @@ mod new;
 pub mod owners;
 mod read;
 mod reverse_dependencies;
+mod suggest;
 mod update;
 pub mod versions;
+mod webhooks;
@@ -139,6 +139,7 @@ async fn test_remove_uppercase_team() {
         .returning(|_, _, _, _| {
             Ok(Some(GitHubTeamMembership {
                 state: "active".to_string(),
+                role: "member".to_string(),
             }))
         });
 
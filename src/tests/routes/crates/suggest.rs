@@ -0,0 +1,196 @@
+use crate::builders::CrateBuilder;
+use crate::util::{RequestHelper, TestApp};
+use http::{StatusCode, header};
+use insta::{assert_json_snapshot, assert_snapshot};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn suggest_crates() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user_id = user.as_model().id;
+
+    let crates = [
+        ("serde", 5000),
+        ("serde_json", 3000),
+        ("serde-yaml", 1000),
+        ("serdex", 0),
+        ("sered", 2000),
+        ("toml", 4000),
+    ];
+
+    for (name, recent_downloads) in crates {
+        let mut builder = CrateBuilder::new(name, user_id);
+        if recent_downloads > 0 {
+            builder = builder.recent_downloads(recent_downloads);
+        }
+        builder.expect_build(&mut conn).await;
+    }
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates/suggest", "q=SERDE")
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_snapshot!(response.headers()[header::CACHE_CONTROL].to_str()?, @"public,max-age=60");
+    assert_json_snapshot!(response.json(), @r#"
+    {
+      "crates": [
+        {
+          "name": "serde",
+          "recent_downloads": 5000
+        },
+        {
+          "name": "serde_json",
+          "recent_downloads": 3000
+        },
+        {
+          "name": "serde-yaml",
+          "recent_downloads": 1000
+        },
+        {
+          "name": "serdex",
+          "recent_downloads": null
+        },
+        {
+          "name": "sered",
+          "recent_downloads": 2000
+        }
+      ]
+    }
+    "#);
+
+    // Hyphens and underscores are equivalent, and prefix matches are ranked
+    // before similar names
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates/suggest", "q=serde-j")
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), @r#"
+    {
+      "crates": [
+        {
+          "name": "serde_json",
+          "recent_downloads": 3000
+        },
+        {
+          "name": "serde",
+          "recent_downloads": 5000
+        },
+        {
+          "name": "serde-yaml",
+          "recent_downloads": 1000
+        },
+        {
+          "name": "serdex",
+          "recent_downloads": null
+        }
+      ]
+    }
+    "#);
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates/suggest", "q=s&limit=2")
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), @r#"
+    {
+      "crates": [
+        {
+          "name": "serde",
+          "recent_downloads": 5000
+        },
+        {
+          "name": "serde_json",
+          "recent_downloads": 3000
+        }
+      ]
+    }
+    "#);
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates/suggest", "q=%20")
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_snapshot!(response.text(), @r#"{"crates":[]}"#);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn suggest_crates_is_cached() -> anyhow::Result<()> {
+    let (app, anon, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+    let user_id = user.as_model().id;
+
+    CrateBuilder::new("foo", user_id)
+        .expect_build(&mut conn)
+        .await;
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates/suggest", "q=fo")
+        .await;
+    assert_snapshot!(response.text(), @r#"{"crates":[{"name":"foo","recent_downloads":null}]}"#);
+
+    CrateBuilder::new("foo_bar", user_id)
+        .expect_build(&mut conn)
+        .await;
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates/suggest", "q=FO")
+        .await;
+    assert_snapshot!(response.text(), @r#"{"crates":[{"name":"foo","recent_downloads":null}]}"#);
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates/suggest", "q=foo_")
+        .await;
+    assert_snapshot!(response.text(), @r#"{"crates":[{"name":"foo_bar","recent_downloads":null},{"name":"foo","recent_downloads":null}]}"#);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn suggest_crates_invalid_params() {
+    let (_app, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/api/v1/crates/suggest").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates/suggest", "q=foo&limit=0")
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"limit must be between 1 and 25"}]}"#);
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates/suggest", "q=foo&limit=26")
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"limit must be between 1 and 25"}]}"#);
+
+    let query = format!("q={}", "a".repeat(65));
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates/suggest", &query)
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"q must not be longer than 64 characters"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn suggest_crates_rate_limit() {
+    let (_app, anon) = TestApp::init()
+        .with_config(|config| config.suggest_rate_limit = 2)
+        .empty()
+        .await;
+
+    for _ in 0..2 {
+        let response = anon
+            .get_with_query::<()>("/api/v1/crates/suggest", "q=foo")
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = anon
+        .get_with_query::<()>("/api/v1/crates/suggest", "q=foo")
+        .await;
+    assert_snapshot!(response.status(), @"429 Too Many Requests");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"You have requested too many crate suggestions in a short period of time"}]}"#);
+}
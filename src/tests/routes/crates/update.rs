@@ -159,6 +159,143 @@ async fn test_update_nonexistent_crate() {
     assert_eq!(app.emails().await.len(), 0);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_deprecate_crate() {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let owner_id = user.as_model().id;
+    CrateBuilder::new("foo", owner_id)
+        .expect_build(&mut conn)
+        .await;
+    CrateBuilder::new("bar", owner_id)
+        .expect_build(&mut conn)
+        .await;
+
+    let url = "/api/v1/crates/foo";
+
+    let deprecation = serde_json::json!({ "reason": "  Use bar instead.  ", "successor": "bar" });
+    let body = serde_json::json!({ "crate": { "deprecation": deprecation } });
+    let response = user.patch::<()>(url, body.to_string()).await;
+    app.run_pending_background_jobs().await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json()["crate"]["deprecation"], {
+        ".deprecated_at" => "[datetime]",
+    }, @r#"
+    {
+      "deprecated_at": "[datetime]",
+      "reason": "Use bar instead.",
+      "successor": "bar"
+    }
+    "#);
+
+    // The deprecation is visible on the crate page
+    let response = user.get::<()>(url).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let json = response.json();
+    assert_eq!(json["crate"]["deprecation"]["reason"], "Use bar instead.");
+    let deprecated_at = json["crate"]["deprecation"]["deprecated_at"].clone();
+
+    // The deprecation is announced in the crate feed
+    assert!(
+        app.stored_files()
+            .await
+            .contains(&"rss/crates/foo.xml".to_string())
+    );
+
+    // Updating the reason keeps the original deprecation date
+    let deprecation = serde_json::json!({ "reason": "Unmaintained" });
+    let body = serde_json::json!({ "crate": { "deprecation": deprecation } });
+    let response = user.patch::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    let json = response.json();
+    assert_eq!(json["crate"]["deprecation"]["reason"], "Unmaintained");
+    assert_eq!(
+        json["crate"]["deprecation"]["successor"],
+        serde_json::Value::Null
+    );
+    assert_eq!(json["crate"]["deprecation"]["deprecated_at"], deprecated_at);
+
+    // Omitting the field leaves the deprecation untouched
+    let body = serde_json::json!({ "crate": {} });
+    let response = user.patch::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(
+        response.json()["crate"]["deprecation"]["reason"],
+        "Unmaintained"
+    );
+
+    // Setting the field to `null` removes the deprecation
+    let body = serde_json::json!({ "crate": { "deprecation": null } });
+    let response = user.patch::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json()["crate"]["deprecation"], @"null");
+
+    assert_eq!(app.emails().await.len(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_deprecate_crate_validation() {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let owner_id = user.as_model().id;
+    CrateBuilder::new("foo", owner_id)
+        .expect_build(&mut conn)
+        .await;
+
+    let url = "/api/v1/crates/foo";
+
+    let deprecation = serde_json::json!({ "reason": "   " });
+    let body = serde_json::json!({ "crate": { "deprecation": deprecation } });
+    let response = user.patch::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"The deprecation reason must not be empty"}]}"#);
+
+    let deprecation = serde_json::json!({ "reason": "x".repeat(1001) });
+    let body = serde_json::json!({ "crate": { "deprecation": deprecation } });
+    let response = user.patch::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"The deprecation reason is too long. A maximum of 1000 characters are allowed."}]}"#);
+
+    let deprecation = serde_json::json!({ "reason": "Gone", "successor": "missing" });
+    let body = serde_json::json!({ "crate": { "deprecation": deprecation } });
+    let response = user.patch::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"The successor crate `missing` does not exist"}]}"#);
+
+    let deprecation = serde_json::json!({ "reason": "Gone", "successor": "FOO" });
+    let body = serde_json::json!({ "crate": { "deprecation": deprecation } });
+    let response = user.patch::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"A crate can not be its own successor"}]}"#);
+
+    let response = user.get::<()>(url).await;
+    assert_json_snapshot!(response.json()["crate"]["deprecation"], @"null");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_deprecate_crate_requires_ownership() {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let owner_id = user.as_model().id;
+    CrateBuilder::new("foo", owner_id)
+        .expect_build(&mut conn)
+        .await;
+
+    let another_user = app.db_new_user("another").await;
+
+    let url = "/api/v1/crates/foo";
+    let deprecation = serde_json::json!({ "reason": "Unmaintained" });
+    let body = serde_json::json!({ "crate": { "deprecation": deprecation } });
+    let response = another_user.patch::<()>(url, body.to_string()).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    let response = user.get::<()>(url).await;
+    assert_json_snapshot!(response.json()["crate"]["deprecation"], @"null");
+}
+
 mod auth {
     use super::*;
 
@@ -293,4 +430,57 @@ mod auth {
 
         assert!(!app.emails().await.is_empty());
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn token_user_with_manage_trustpub_scope() {
+        let (app, user) = prepare().await;
+        let token = user
+            .db_new_scoped_token(
+                "test-token",
+                None,
+                Some(vec![EndpointScope::ManageTrustpub]),
+                None,
+            )
+            .await;
+
+        let url = format!("/api/v1/crates/{}", CRATE_NAME);
+        let body = serde_json::json!({ "crate": { "trustpub_only": true } });
+        let response = token.patch::<()>(&url, body.to_string()).await;
+        assert_snapshot!(response.status(), @"200 OK");
+
+        assert!(!app.emails().await.is_empty());
+
+        // Deprecating a crate requires the `update-metadata` scope
+        let body = serde_json::json!({ "crate": { "deprecation": { "reason": "Unmaintained" } } });
+        let response = token.patch::<()>(&url, body.to_string()).await;
+        assert_snapshot!(response.status(), @"403 Forbidden");
+        assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this token does not have the required permissions to perform this action"}]}"#);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn token_user_with_update_metadata_scope() {
+        let (_app, user) = prepare().await;
+        let token = user
+            .db_new_scoped_token(
+                "test-token",
+                None,
+                Some(vec![EndpointScope::UpdateMetadata]),
+                None,
+            )
+            .await;
+
+        let url = format!("/api/v1/crates/{}", CRATE_NAME);
+        let body = serde_json::json!({ "crate": { "deprecation": { "reason": "Unmaintained" } } });
+        let response = token.patch::<()>(&url, body.to_string()).await;
+        assert_snapshot!(response.status(), @"200 OK");
+        assert_eq!(
+            response.json()["crate"]["deprecation"]["reason"],
+            "Unmaintained"
+        );
+
+        // Changing both settings at once requires both scopes
+        let body = serde_json::json!({ "crate": { "trustpub_only": true, "deprecation": null } });
+        let response = token.patch::<()>(&url, body.to_string()).await;
+        assert_snapshot!(response.status(), @"403 Forbidden");
+    }
 }
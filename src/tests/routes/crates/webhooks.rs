@@ -0,0 +1,250 @@
+use crate::builders::CrateBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::models::CrateAction;
+use crates_io::schema::crate_audit_actions;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+use serde_json::json;
+
+const URL: &str = "/api/v1/crates/foo/webhooks";
+
+fn new_webhook(url: &str) -> String {
+    let body = json!({
+        "webhook": {
+            "url": url,
+            "secret": "correct horse battery staple",
+            "events": ["publish", "yank", "publish"],
+        }
+    });
+    body.to_string()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_happy_path() -> anyhow::Result<()> {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let response = user.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_snapshot!(response.text(), @r#"{"webhooks":[]}"#);
+
+    let body = new_webhook("https://example.com/webhook");
+    let response = user.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), { ".webhook.created_at" => "[datetime]" }, @r#"
+    {
+      "webhook": {
+        "consecutive_failures": 0,
+        "created_at": "[datetime]",
+        "disabled_at": null,
+        "enabled": true,
+        "events": [
+          "publish",
+          "yank"
+        ],
+        "id": 1,
+        "url": "https://example.com/webhook"
+      }
+    }
+    "#);
+
+    let response = user.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(response.json()["webhooks"].as_array().unwrap().len(), 1);
+
+    let body = json!({ "webhook": { "enabled": false } });
+    let response = user
+        .patch::<()>(&format!("{URL}/1"), body.to_string())
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(response.json()["webhook"]["enabled"], false);
+    assert!(response.json()["webhook"]["disabled_at"].is_string());
+
+    let body = json!({ "webhook": { "enabled": true } });
+    let response = user
+        .patch::<()>(&format!("{URL}/1"), body.to_string())
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(response.json()["webhook"]["enabled"], true);
+
+    let response = user.get::<()>(&format!("{URL}/1/deliveries")).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_snapshot!(response.text(), @r#"{"deliveries":[],"meta":{"total":0,"next_page":null}}"#);
+
+    let response = user.delete::<()>(&format!("{URL}/1")).await;
+    assert_snapshot!(response.status(), @"204 No Content");
+
+    let response = user.get::<()>(URL).await;
+    assert_snapshot!(response.text(), @r#"{"webhooks":[]}"#);
+
+    let actions: Vec<CrateAction> = crate_audit_actions::table
+        .select(crate_audit_actions::action)
+        .order(crate_audit_actions::id)
+        .load(&mut conn)
+        .await?;
+    assert_eq!(
+        actions,
+        [
+            CrateAction::WebhookCreate,
+            CrateAction::WebhookUpdate,
+            CrateAction::WebhookUpdate,
+            CrateAction::WebhookDelete,
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_validation() {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let response = user.post::<()>(URL, new_webhook("not a url")).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"Invalid webhook URL"}]}"#);
+
+    let response = user
+        .post::<()>(URL, new_webhook("ftp://example.com/"))
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"Webhook URLs must use the `https` scheme"}]}"#);
+
+    let body = json!({
+        "webhook": {
+            "url": "https://example.com/webhook",
+            "secret": "too short",
+            "events": ["publish"],
+        }
+    });
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"The webhook secret must be between 16 and 256 characters long"}]}"#);
+
+    let body = json!({
+        "webhook": {
+            "url": "https://example.com/webhook",
+            "secret": "correct horse battery staple",
+            "events": [],
+        }
+    });
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"At least one webhook event must be selected"}]}"#);
+
+    let body = json!({
+        "webhook": {
+            "url": "https://example.com/webhook",
+            "secret": "correct horse battery staple",
+            "events": ["download"],
+        }
+    });
+    let response = user.post::<()>(URL, body.to_string()).await;
+    assert_snapshot!(response.status(), @"422 Unprocessable Entity");
+
+    for _ in 0..5 {
+        let body = new_webhook("https://example.com/webhook");
+        let response = user.post::<()>(URL, body).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = user
+        .post::<()>(URL, new_webhook("https://example.com/webhook"))
+        .await;
+    assert_snapshot!(response.status(), @"409 Conflict");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"This crate already has the maximum number of webhooks (5)"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_requires_ownership() {
+    let (app, anon, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let response = user
+        .post::<()>(URL, new_webhook("https://example.com/webhook"))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = anon.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this action requires authentication"}]}"#);
+
+    let other_user = app.db_new_user("other").await;
+    let response = other_user.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only owners have permission to manage webhooks"}]}"#);
+
+    let response = other_user.delete::<()>(&format!("{URL}/1")).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+
+    let response = other_user.get::<()>(&format!("{URL}/1/deliveries")).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_team_members() {
+    let (app, _) = TestApp::full().empty().await;
+    let mut conn = app.db_conn().await;
+
+    let user_on_both_teams = app.db_new_user("user-all-teams").await;
+    CrateBuilder::new("foo", user_on_both_teams.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    user_on_both_teams
+        .add_named_owner("foo", "github:test-org:all")
+        .await
+        .good();
+
+    // Members of owning teams can publish, but not manage webhooks
+    let user_on_one_team = app.db_new_user("user-one-team").await;
+    let response = user_on_one_team.get::<()>(URL).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"team members don't have permission to manage webhooks"}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unknown_webhook() {
+    let (app, _, user) = TestApp::full().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new("foo", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+    CrateBuilder::new("bar", user.as_model().id)
+        .expect_build(&mut conn)
+        .await;
+
+    let response = user
+        .post::<()>(
+            "/api/v1/crates/bar/webhooks",
+            new_webhook("https://example.com/webhook"),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Webhooks of other crates can not be accessed
+    let response = user.delete::<()>(&format!("{URL}/1")).await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+
+    let response = user.get::<()>(&format!("{URL}/1/deliveries")).await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+
+    let response = user.delete::<()>("/api/v1/crates/unknown/webhooks/1").await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"crate `unknown` does not exist"}]}"#);
+}
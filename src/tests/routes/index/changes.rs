@@ -0,0 +1,89 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::worker::jobs;
+use crates_io_worker::BackgroundJob;
+use http::StatusCode;
+use insta::{assert_json_snapshot, assert_snapshot};
+use object_store::ObjectStoreExt;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_changes() {
+    let (app, anon, _, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    for (name, version) in [("foo", "1.0.0"), ("bar", "1.0.0"), ("foo", "1.1.0")] {
+        let crate_to_publish = PublishBuilder::new(name, version);
+        token.publish_crate(crate_to_publish).await.good();
+        app.run_pending_background_jobs().await;
+    }
+
+    // Syncing a crate that does not exist deletes its index file
+    jobs::SyncToSparseIndex::new("baz")
+        .enqueue(&mut conn)
+        .await
+        .unwrap();
+    app.run_pending_background_jobs().await;
+
+    let response = anon.get::<()>("/api/v1/index/changes").await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), {
+        ".changes[].time" => "[datetime]",
+    });
+
+    let response = anon
+        .get::<()>("/api/v1/index/changes?since=1&limit=2")
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), {
+        ".changes[].time" => "[datetime]",
+    });
+
+    let response = anon.get::<()>("/api/v1/index/changes?since=4").await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_snapshot!(response.text(), @r#"{"epoch":1,"changes":[],"meta":{"next_since":4}}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_changes_with_invalid_limit() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/api/v1/index/changes?limit=0").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"limit must be between 1 and 1000"}]}"#);
+
+    let response = anon.get::<()>("/api/v1/index/changes?limit=1001").await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_changes_page() {
+    let (app, anon, _, token) = TestApp::full().with_token().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+    app.run_pending_background_jobs().await;
+
+    let response = anon.get::<()>("/index/changes/1/0.json").await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), {
+        ".changes[].time" => "[datetime]",
+    });
+
+    // The served page matches the page in the index storage
+    let store = app.as_inner().storage.as_inner();
+    let path = object_store::path::Path::parse("index/changes/1/0.json").unwrap();
+    let stored = store.get(&path).await.unwrap().bytes().await.unwrap();
+    assert_eq!(response.text().as_bytes(), &stored[..]);
+
+    let response = anon.get::<()>("/index/changes/1/1.json").await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_snapshot!(response.text(), @r#"{"epoch":1,"page":1,"complete":false,"changes":[]}"#);
+
+    let response = anon.get::<()>("/index/changes/2/0.json").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = anon.get::<()>("/index/changes/1/foo.json").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
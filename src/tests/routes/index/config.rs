@@ -0,0 +1,25 @@
+use crate::util::{MockRequestExt, RequestHelper, TestApp};
+use http::{StatusCode, header};
+use insta::assert_snapshot;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_config() {
+    let (_, anon) = TestApp::init().empty().await;
+
+    let response = anon.get::<()>("/index/config.json").await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_snapshot!(response.headers()[header::CONTENT_TYPE].to_str().unwrap(), @"application/json");
+    assert_snapshot!(response.text(), @r#"
+    {
+      "api": "https://crates.io",
+      "dl": "https://crates.io/api/v1/crates"
+    }
+    "#);
+
+    let etag = response.headers()[header::ETAG].clone();
+
+    let mut request = anon.get_request("/index/config.json");
+    request.header(header::IF_NONE_MATCH.as_str(), etag.to_str().unwrap());
+    let response = anon.run::<()>(request).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+}
@@ -0,0 +1,118 @@
+use crate::builders::PublishBuilder;
+use crate::util::{MockRequestExt, RequestHelper, TestApp};
+use http::{StatusCode, header};
+use insta::assert_snapshot;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_crate_file() {
+    let (app, anon, _, token) = TestApp::full().with_token().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+    app.run_pending_background_jobs().await;
+
+    let response = anon.get::<()>("/index/3/f/foo").await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_snapshot!(response.headers()[header::CONTENT_TYPE].to_str().unwrap(), @"text/plain");
+    assert!(response.headers().contains_key(header::LAST_MODIFIED));
+
+    // The served file matches the file in the git index
+    let expected = serde_json::to_value(app.crates_from_index_head("foo")).unwrap();
+    let served = response
+        .text()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect::<Vec<serde_json::Value>>();
+    assert_eq!(serde_json::Value::from(served), expected);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn conditional_requests() {
+    let (app, anon, _, token) = TestApp::full().with_token().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+    app.run_pending_background_jobs().await;
+
+    let response = anon.get::<()>("/index/3/f/foo").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag = response.headers()[header::ETAG]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let last_modified = response.headers()[header::LAST_MODIFIED].to_str().unwrap();
+    let last_modified = last_modified.to_string();
+
+    let mut request = anon.get_request("/index/3/f/foo");
+    request.header(header::IF_NONE_MATCH.as_str(), &etag);
+    let response = anon.run::<()>(request).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers()[header::ETAG], etag.as_str());
+    assert!(response.text().is_empty());
+
+    let mut request = anon.get_request("/index/3/f/foo");
+    request.header(header::IF_MODIFIED_SINCE.as_str(), &last_modified);
+    let response = anon.run::<()>(request).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    // `If-None-Match` takes precedence over `If-Modified-Since`
+    let mut request = anon.get_request("/index/3/f/foo");
+    request.header(header::IF_NONE_MATCH.as_str(), "\"outdated\"");
+    request.header(header::IF_MODIFIED_SINCE.as_str(), &last_modified);
+    let response = anon.run::<()>(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Yanking a version invalidates the cached file
+    let response = token.delete::<()>("/api/v1/crates/foo/1.0.0/yank").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    app.run_pending_background_jobs().await;
+
+    let mut request = anon.get_request("/index/3/f/foo");
+    request.header(header::IF_NONE_MATCH.as_str(), &etag);
+    let response = anon.run::<()>(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_ne!(response.headers()[header::ETAG], etag.as_str());
+    assert!(response.text().contains(r#""yanked":true"#));
+
+    // Publishing a new version invalidates the cached file
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.1.0"))
+        .await
+        .good();
+    app.run_pending_background_jobs().await;
+
+    let response = anon.get::<()>("/index/3/f/foo").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().lines().count(), 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_unknown_crate_file() {
+    let (app, anon, _, token) = TestApp::full().with_token().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo_bar", "1.0.0"))
+        .await
+        .good();
+    app.run_pending_background_jobs().await;
+
+    let response = anon.get::<()>("/index/fo/o_/foo_bar").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Crate names with `-` and `_` use separate index files
+    let response = anon.get::<()>("/index/fo/o-/foo-bar").await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+
+    // Only canonical paths are served
+    let response = anon.get::<()>("/index/FO/O_/FOO_BAR").await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+    let response = anon.get::<()>("/index/3/f/foo_bar").await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+
+    let response = anon.get::<()>("/index/3/b/bar").await;
+    assert_snapshot!(response.status(), @"404 Not Found");
+}
@@ -0,0 +1,3 @@
+mod changes;
+mod config;
+mod crate_file;
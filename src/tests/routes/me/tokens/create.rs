@@ -1,8 +1,8 @@
 use crate::util::insta::{self, assert_json_snapshot};
 use crate::util::{RequestHelper, TestApp};
 use claims::assert_ok;
-use crates_io::models::ApiToken;
-use crates_io::models::token::{CrateScope, EndpointScope, NewApiToken};
+use crates_io::models::token::{CrateScope, EndpointScope, IpRange, NewApiToken};
+use crates_io::models::{ApiToken, NewTeam};
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 use googletest::prelude::*;
@@ -253,6 +253,82 @@ async fn create_token_with_invalid_endpoint_scope() {
     assert!(app.emails().await.is_empty());
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn create_token_with_empty_endpoint_scopes() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let json = json!({
+        "api_token": {
+            "name": "bar",
+            "crate_scopes": ["tokio"],
+            "endpoint_scopes": [],
+        }
+    });
+
+    let response = user
+        .put::<()>("/api/v1/me/tokens", serde_json::to_vec(&json).unwrap())
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(response.json()["api_token"]["endpoint_scopes"], json!([]));
+
+    let tokens: Vec<ApiToken> = assert_ok!(
+        ApiToken::belonging_to(user.as_model())
+            .select(ApiToken::as_select())
+            .load(&mut conn)
+            .await
+    );
+
+    assert_that!(tokens, len(eq(1)));
+    assert_eq!(tokens[0].endpoint_scopes, Some(vec![]));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn create_token_with_fine_grained_scopes() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let json = json!({
+        "api_token": {
+            "name": "bar",
+            "crate_scopes": null,
+            "endpoint_scopes": ["read-private", "update-metadata", "manage-trustpub", "delete", "delete"],
+        }
+    });
+
+    let response = user
+        .put::<()>("/api/v1/me/tokens", serde_json::to_vec(&json).unwrap())
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(
+        response.json()["api_token"]["endpoint_scopes"],
+        json!([
+            "read-private",
+            "update-metadata",
+            "manage-trustpub",
+            "delete"
+        ])
+    );
+
+    let tokens: Vec<ApiToken> = assert_ok!(
+        ApiToken::belonging_to(user.as_model())
+            .select(ApiToken::as_select())
+            .load(&mut conn)
+            .await
+    );
+
+    assert_that!(tokens, len(eq(1)));
+    assert_eq!(
+        tokens[0].endpoint_scopes,
+        Some(vec![
+            EndpointScope::ReadPrivate,
+            EndpointScope::UpdateMetadata,
+            EndpointScope::ManageTrustpub,
+            EndpointScope::Delete,
+        ])
+    );
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn create_token_with_expiry_date() {
     let (app, _, user) = TestApp::init().with_user().await;
@@ -347,3 +423,173 @@ async fn create_token_with_trusted_publishing_scope() {
 
     assert_snapshot!(app.emails_snapshot().await);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn create_token_with_allowed_ips() {
+    let (app, _, user) = TestApp::init().with_user().await;
+    let mut conn = app.db_conn().await;
+
+    let json = json!({
+        "api_token": {
+            "name": "bar",
+            "endpoint_scopes": ["publish-update"],
+            "allowed_ips": ["192.0.2.0/24", "2001:db8::1"],
+        }
+    });
+
+    let response = user
+        .put::<()>("/api/v1/me/tokens", serde_json::to_vec(&json).unwrap())
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json()["api_token"]["allowed_ips"], @r#"
+    [
+      "192.0.2.0/24",
+      "2001:db8::1/128"
+    ]
+    "#);
+
+    let tokens: Vec<ApiToken> = assert_ok!(
+        ApiToken::belonging_to(user.as_model())
+            .select(ApiToken::as_select())
+            .load(&mut conn)
+            .await
+    );
+
+    assert_that!(tokens, len(eq(1)));
+    assert_eq!(
+        tokens[0].allowed_ips,
+        Some(vec![
+            IpRange::try_from("192.0.2.0/24").unwrap(),
+            IpRange::try_from("2001:db8::1").unwrap(),
+        ])
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn create_token_with_invalid_allowed_ips() {
+    let (app, _, user) = TestApp::init().with_user().await;
+
+    let json = json!({ "api_token": { "name": "bar", "allowed_ips": ["192.0.2.0/33"] } });
+    let response = user
+        .put::<()>("/api/v1/me/tokens", serde_json::to_vec(&json).unwrap())
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"invalid allowed IP range"}]}"#);
+
+    let json = json!({ "api_token": { "name": "bar", "allowed_ips": [] } });
+    let response = user
+        .put::<()>("/api/v1/me/tokens", serde_json::to_vec(&json).unwrap())
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"at least one allowed IP range is required"}]}"#);
+
+    assert!(app.emails().await.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn create_team_token() {
+    let (app, _) = TestApp::init().empty().await;
+    let mut conn = app.db_conn().await;
+    let user = app.db_new_user("user-all-teams").await;
+
+    let team = NewTeam::builder()
+        .login("github:test-org:core")
+        .org_id(1000)
+        .github_id(2001)
+        .build();
+    let team = assert_ok!(team.create_or_update(&mut conn).await);
+
+    let json = json!({
+        "api_token": {
+            "name": "release automation",
+            "endpoint_scopes": ["publish-update", "yank"],
+            "team": "github:test-org:core",
+        }
+    });
+
+    let response = user
+        .put::<()>("/api/v1/me/tokens", serde_json::to_vec(&json).unwrap())
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_eq!(response.json()["api_token"]["team_id"], team.id);
+
+    let tokens: Vec<ApiToken> = assert_ok!(
+        ApiToken::belonging_to(user.as_model())
+            .select(ApiToken::as_select())
+            .load(&mut conn)
+            .await
+    );
+
+    assert_that!(tokens, len(eq(1)));
+    assert_eq!(tokens[0].team_id, Some(team.id));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn create_team_token_as_non_maintainer() {
+    let (app, _) = TestApp::init().empty().await;
+    let mut conn = app.db_conn().await;
+    let user = app.db_new_user("user-one-team").await;
+
+    let team = NewTeam::builder()
+        .login("github:test-org:all")
+        .org_id(1000)
+        .github_id(2000)
+        .build();
+    assert_ok!(team.create_or_update(&mut conn).await);
+
+    let json = json!({
+        "api_token": {
+            "name": "release automation",
+            "endpoint_scopes": ["publish-update"],
+            "team": "github:test-org:all",
+        }
+    });
+
+    let response = user
+        .put::<()>("/api/v1/me/tokens", serde_json::to_vec(&json).unwrap())
+        .await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"only maintainers of a team can create API tokens for it"}]}"#);
+    assert!(app.emails().await.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn create_team_token_with_invalid_request() {
+    let (app, _) = TestApp::init().empty().await;
+    let user = app.db_new_user("user-all-teams").await;
+
+    let json = json!({ "api_token": { "name": "bar", "team": "github:test-org:core" } });
+    let response = user
+        .put::<()>("/api/v1/me/tokens", serde_json::to_vec(&json).unwrap())
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"team tokens require endpoint scopes and only support the `publish-update`, `publish-release` and `yank` scopes"}]}"#);
+
+    let json = json!({
+        "api_token": {
+            "name": "bar",
+            "endpoint_scopes": ["publish-update", "change-owners"],
+            "team": "github:test-org:core",
+        }
+    });
+    let response = user
+        .put::<()>("/api/v1/me/tokens", serde_json::to_vec(&json).unwrap())
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"team tokens require endpoint scopes and only support the `publish-update`, `publish-release` and `yank` scopes"}]}"#);
+
+    let json = json!({
+        "api_token": {
+            "name": "bar",
+            "endpoint_scopes": ["publish-update"],
+            "team": "github:test-org:core",
+        }
+    });
+    let response = user
+        .put::<()>("/api/v1/me/tokens", serde_json::to_vec(&json).unwrap())
+        .await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"could not find the team `github:test-org:core`. Only teams that own crates can own API tokens."}]}"#);
+
+    assert!(app.emails().await.is_empty());
+}
@@ -1,7 +1,8 @@
-use crate::util::{RequestHelper, TestApp};
+use crate::util::{MockRequestExt, RequestHelper, TestApp};
 use chrono::{Duration, Utc};
 use claims::assert_ok;
 use crates_io::models::token::{CrateScope, EndpointScope, NewApiToken};
+use http::StatusCode;
 use insta::{assert_json_snapshot, assert_snapshot};
 
 #[tokio::test(flavor = "multi_thread")]
@@ -77,3 +78,56 @@ async fn show_other_user_token() {
     let response = user1.get::<()>(&url).await;
     assert_snapshot!(response.status(), @"404 Not Found");
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn show_with_legacy_token() {
+    let (_, _, _, token) = TestApp::init().with_token().await;
+    let url = format!("/api/v1/me/tokens/{}", token.as_model().id);
+
+    let response = token.get::<()>(&url).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"This endpoint cannot be used with legacy API tokens. Use a scoped API token instead."}]}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn show_token_usages() {
+    let (_, _, user, token) = TestApp::init()
+        .with_scoped_token(None, Some(vec![EndpointScope::ReadPrivate]))
+        .await;
+    let url = format!("/api/v1/me/tokens/{}", token.as_model().id);
+
+    let response = token.get::<()>(&url).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    // Repeated requests from the same IP address are counted in a single entry
+    for _ in 0..2 {
+        let mut request = token.get_request(&url);
+        request.header("X-Forwarded-For", "192.0.2.42");
+        let response = token.run::<()>(request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    // Requests authenticated via cookie are not part of the usage log
+    let response = user.get::<()>(&url).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json()["usages"], {
+        "[].last_used_at" => "[datetime]",
+    }, @r#"
+    [
+      {
+        "endpoint": "GET /api/v1/me/tokens/1",
+        "ip_address": "192.0.2.42",
+        "last_used_at": "[datetime]",
+        "request_count": 2,
+        "user_agent": "conduit-test"
+      },
+      {
+        "endpoint": "GET /api/v1/me/tokens/1",
+        "ip_address": "127.0.0.1",
+        "last_used_at": "[datetime]",
+        "request_count": 1,
+        "user_agent": "conduit-test"
+      }
+    ]
+    "#);
+}
@@ -2,8 +2,9 @@ use crate::builders::CrateBuilder;
 use crate::util::{RequestHelper, Response, TestApp};
 use anyhow::anyhow;
 use bytes::Bytes;
+use crates_io_database::models::CrateAction;
 use crates_io_database::models::token::{CrateScope, EndpointScope};
-use crates_io_database::schema::{emails, trustpub_configs_github};
+use crates_io_database::schema::{crate_audit_actions, emails, trustpub_configs_github};
 use crates_io_github::{GitHubError, GitHubUser, MockGitHubClient};
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
@@ -75,6 +76,12 @@ async fn test_happy_path() -> anyhow::Result<()> {
     assert_eq!(config_ids.len(), 1);
     assert_eq!(config_ids[0], 1);
 
+    let actions = crate_audit_actions::table
+        .select(crate_audit_actions::action)
+        .load::<CrateAction>(&mut conn)
+        .await?;
+    assert_eq!(actions, [CrateAction::TrustpubConfigCreate]);
+
     Ok(())
 }
 
@@ -191,6 +198,67 @@ async fn test_invalid_environment() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_happy_path_with_ref_pattern_and_expiry() -> anyhow::Result<()> {
+    let body = serde_json::to_vec(&json!({
+        "github_config": {
+            "crate": CRATE_NAME,
+            "repository_owner": "rust-lang",
+            "repository_name": "foo-rs",
+            "workflow_filename": "publish.yml",
+            "environment": null,
+            "ref_pattern": "refs/tags/v*",
+            "expires_at": "2100-01-01T00:00:00Z",
+        }
+    }))?;
+
+    let (_app, response) = run_test(body).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), { ".github_config.created_at" => "[datetime]" });
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_invalid_ref_pattern() -> anyhow::Result<()> {
+    let body = serde_json::to_vec(&json!({
+        "github_config": {
+            "crate": CRATE_NAME,
+            "repository_owner": "rust-lang",
+            "repository_name": "foo-rs",
+            "workflow_filename": "publish.yml",
+            "environment": null,
+            "ref_pattern": "main",
+        }
+    }))?;
+
+    let (_app, response) = run_test(body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"Ref pattern must start with `refs/`"}]}"#);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_expiry_in_the_past() -> anyhow::Result<()> {
+    let body = serde_json::to_vec(&json!({
+        "github_config": {
+            "crate": CRATE_NAME,
+            "repository_owner": "rust-lang",
+            "repository_name": "foo-rs",
+            "workflow_filename": "publish.yml",
+            "environment": null,
+            "expires_at": "2000-01-01T00:00:00Z",
+        }
+    }))?;
+
+    let (_app, response) = run_test(body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"Expiry date must be in the future"}]}"#);
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_unauthenticated() -> anyhow::Result<()> {
     let (app, client, cookie_client) = TestApp::full()
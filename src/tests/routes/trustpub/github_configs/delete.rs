@@ -1,8 +1,9 @@
 use crate::builders::CrateBuilder;
 use crate::util::{RequestHelper, TestApp};
-use crates_io_database::models::Crate;
 use crates_io_database::models::token::{CrateScope, EndpointScope};
 use crates_io_database::models::trustpub::{GitHubConfig, NewGitHubConfig};
+use crates_io_database::models::{Crate, CrateAction};
+use crates_io_database::schema::crate_audit_actions;
 use diesel::prelude::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use insta::assert_snapshot;
@@ -27,6 +28,8 @@ async fn create_config(conn: &mut AsyncPgConnection, crate_id: i32) -> QueryResu
         repository_name: "foo-rs",
         workflow_filename: "publish.yml",
         environment: None,
+        ref_pattern: None,
+        expires_at: None,
     };
 
     config.insert(conn).await
@@ -53,6 +56,12 @@ async fn test_happy_path() -> anyhow::Result<()> {
     let configs = get_all_configs(&mut conn).await?;
     assert_eq!(configs.len(), 0);
 
+    let actions = crate_audit_actions::table
+        .select(crate_audit_actions::action)
+        .load::<CrateAction>(&mut conn)
+        .await?;
+    assert_eq!(actions, [CrateAction::TrustpubConfigDelete]);
+
     // Verify emails were sent to crate owners
     assert_snapshot!(app.emails_snapshot().await);
 
@@ -20,6 +20,8 @@ async fn create_config(
         repository_name,
         workflow_filename: "publish.yml",
         environment: None,
+        ref_pattern: None,
+        expires_at: None,
     };
 
     config.insert(conn).await
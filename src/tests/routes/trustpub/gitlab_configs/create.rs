@@ -78,6 +78,59 @@ async fn test_happy_path_with_environment() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_happy_path_with_self_hosted_issuer() -> anyhow::Result<()> {
+    const ISSUER: &str = "https://gitlab.example.com";
+
+    let (app, _client, cookie_client) = TestApp::full()
+        .with_config(|config| config.trustpub_gitlab_issuer_urls.push(ISSUER.into()))
+        .with_user()
+        .await;
+
+    let mut conn = app.db_conn().await;
+
+    CrateBuilder::new(CRATE_NAME, cookie_client.as_model().id)
+        .build(&mut conn)
+        .await?;
+
+    let body = serde_json::to_vec(&json!({
+        "gitlab_config": {
+            "crate": CRATE_NAME,
+            "namespace": "rust-lang",
+            "project": "foo-rs",
+            "workflow_filepath": ".gitlab-ci.yml",
+            "environment": null,
+            "issuer": ISSUER,
+        }
+    }))?;
+
+    let response = cookie_client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), { ".gitlab_config.created_at" => "[datetime]" });
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unsupported_issuer() -> anyhow::Result<()> {
+    let body = serde_json::to_vec(&json!({
+        "gitlab_config": {
+            "crate": CRATE_NAME,
+            "namespace": "rust-lang",
+            "project": "foo-rs",
+            "workflow_filepath": ".gitlab-ci.yml",
+            "environment": null,
+            "issuer": "https://gitlab.example.com",
+        }
+    }))?;
+
+    let (_app, response) = run_test(body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"Unsupported GitLab issuer: https://gitlab.example.com"}]}"#);
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_empty_body() -> anyhow::Result<()> {
     let (_app, response) = run_test("").await;
@@ -172,6 +225,67 @@ async fn test_invalid_environment() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_happy_path_with_ref_pattern_and_expiry() -> anyhow::Result<()> {
+    let body = serde_json::to_vec(&json!({
+        "gitlab_config": {
+            "crate": CRATE_NAME,
+            "namespace": "rust-lang",
+            "project": "foo-rs",
+            "workflow_filepath": ".gitlab-ci.yml",
+            "environment": null,
+            "ref_pattern": "refs/tags/v*",
+            "expires_at": "2100-01-01T00:00:00Z",
+        }
+    }))?;
+
+    let (_app, response) = run_test(body).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_json_snapshot!(response.json(), { ".gitlab_config.created_at" => "[datetime]" });
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_invalid_ref_pattern() -> anyhow::Result<()> {
+    let body = serde_json::to_vec(&json!({
+        "gitlab_config": {
+            "crate": CRATE_NAME,
+            "namespace": "rust-lang",
+            "project": "foo-rs",
+            "workflow_filepath": ".gitlab-ci.yml",
+            "environment": null,
+            "ref_pattern": "main",
+        }
+    }))?;
+
+    let (_app, response) = run_test(body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"Ref pattern must start with `refs/`"}]}"#);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_expiry_in_the_past() -> anyhow::Result<()> {
+    let body = serde_json::to_vec(&json!({
+        "gitlab_config": {
+            "crate": CRATE_NAME,
+            "namespace": "rust-lang",
+            "project": "foo-rs",
+            "workflow_filepath": ".gitlab-ci.yml",
+            "environment": null,
+            "expires_at": "2000-01-01T00:00:00Z",
+        }
+    }))?;
+
+    let (_app, response) = run_test(body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"Expiry date must be in the future"}]}"#);
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_unauthenticated() -> anyhow::Result<()> {
     let (app, client, cookie_client) = TestApp::full().with_user().await;
@@ -3,6 +3,7 @@ use crate::util::{RequestHelper, TestApp};
 use crates_io_database::models::Crate;
 use crates_io_database::models::token::{CrateScope, EndpointScope};
 use crates_io_database::models::trustpub::{GitLabConfig, NewGitLabConfig};
+use crates_io_trustpub::gitlab::GITLAB_ISSUER_URL;
 use diesel::prelude::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use insta::assert_snapshot;
@@ -26,6 +27,9 @@ async fn create_config(conn: &mut AsyncPgConnection, crate_id: i32) -> QueryResu
         project: "foo-rs",
         workflow_filepath: ".gitlab-ci.yml",
         environment: None,
+        issuer: GITLAB_ISSUER_URL,
+        ref_pattern: None,
+        expires_at: None,
     };
 
     config.insert(conn).await
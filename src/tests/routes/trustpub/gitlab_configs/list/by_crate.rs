@@ -3,6 +3,7 @@ use crate::builders::CrateBuilder;
 use crate::util::{RequestHelper, TestApp};
 use crates_io_database::models::token::{CrateScope, EndpointScope};
 use crates_io_database::models::trustpub::{GitLabConfig, NewGitLabConfig};
+use crates_io_trustpub::gitlab::GITLAB_ISSUER_URL;
 use diesel::prelude::*;
 use diesel_async::AsyncPgConnection;
 use insta::{assert_json_snapshot, assert_snapshot};
@@ -19,6 +20,9 @@ async fn create_config(
         project,
         workflow_filepath: ".gitlab-ci.yml",
         environment: None,
+        issuer: GITLAB_ISSUER_URL,
+        ref_pattern: None,
+        expires_at: None,
     };
 
     config.insert(conn).await
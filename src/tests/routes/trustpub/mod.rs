@@ -1,3 +1,4 @@
+mod forgejo_configs;
 mod github_configs;
 mod gitlab_configs;
 mod tokens;
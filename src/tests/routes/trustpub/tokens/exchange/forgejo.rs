@@ -0,0 +1,275 @@
+use crate::builders::CrateBuilder;
+use crate::util::{MockAnonymousUser, RequestHelper, TestApp};
+use claims::{assert_ok, assert_some_eq};
+use crates_io_database::models::trustpub::{ForgejoConfig, NewForgejoConfig};
+use crates_io_database::schema::{trustpub_configs_forgejo, trustpub_tokens};
+use crates_io_trustpub::access_token::AccessToken;
+use crates_io_trustpub::forgejo::FORGEJO_ISSUER_URL;
+use crates_io_trustpub::forgejo::test_helpers::FullForgejoClaims;
+use crates_io_trustpub::keystore::MockOidcKeyStore;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use insta::{assert_compact_debug_snapshot, assert_json_snapshot, assert_snapshot};
+
+const URL: &str = "/api/v1/trusted_publishing/tokens";
+
+const CRATE_NAME: &str = "foo";
+const OWNER_NAME: &str = "rust-lang";
+const OWNER_ID: i32 = 42;
+const REPOSITORY_NAME: &str = "foo-rs";
+const WORKFLOW_FILENAME: &str = "publish.yml";
+
+async fn prepare() -> anyhow::Result<MockAnonymousUser> {
+    let (app, client, cookie) = TestApp::full()
+        .with_oidc_keystore(FORGEJO_ISSUER_URL, MockOidcKeyStore::with_test_key())
+        .with_user()
+        .await;
+
+    let mut conn = app.db_conn().await;
+
+    let owner_id = cookie.as_model().id;
+    let krate = CrateBuilder::new(CRATE_NAME, owner_id)
+        .build(&mut conn)
+        .await?;
+
+    new_oidc_config(krate.id).insert(&mut conn).await?;
+
+    Ok(client)
+}
+
+fn new_oidc_config(crate_id: i32) -> NewForgejoConfig<'static> {
+    NewForgejoConfig {
+        crate_id,
+        repository_owner: OWNER_NAME,
+        repository_name: REPOSITORY_NAME,
+        workflow_filename: WORKFLOW_FILENAME,
+    }
+}
+
+fn default_claims() -> FullForgejoClaims {
+    FullForgejoClaims::builder()
+        .owner_id(OWNER_ID)
+        .owner_name(OWNER_NAME)
+        .repository_name(REPOSITORY_NAME)
+        .workflow_filename(WORKFLOW_FILENAME)
+        .build()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_happy_path() -> anyhow::Result<()> {
+    let client = prepare().await?;
+
+    let body = default_claims().as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let json = response.json();
+    assert_json_snapshot!(json, { ".token" => "[token]" }, @r#"
+    {
+      "token": "[token]"
+    }
+    "#);
+
+    let token = json["token"].as_str().unwrap();
+    let token = assert_ok!(token.parse::<AccessToken>());
+    let hashed_token = token.sha256();
+
+    let mut conn = client.app().db_conn().await;
+
+    let tokens = trustpub_tokens::table
+        .filter(trustpub_tokens::hashed_token.eq(hashed_token.as_slice()))
+        .select((trustpub_tokens::id, trustpub_tokens::crate_ids))
+        .get_results::<(i64, Vec<Option<i32>>)>(&mut conn)
+        .await?;
+
+    assert_eq!(tokens.len(), 1);
+    assert_compact_debug_snapshot!(tokens, @"[(1, [Some(1)])]");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_case_insensitive() -> anyhow::Result<()> {
+    let client = prepare().await?;
+
+    let claims = FullForgejoClaims::builder()
+        .owner_id(OWNER_ID)
+        .owner_name("RUST-lanG")
+        .repository_name("foo-RS")
+        .workflow_filename(WORKFLOW_FILENAME)
+        .build();
+
+    let body = claims.as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_custom_issuer_not_configured() -> anyhow::Result<()> {
+    let client = prepare().await?;
+
+    let claims = FullForgejoClaims::builder()
+        .owner_id(OWNER_ID)
+        .owner_name(OWNER_NAME)
+        .repository_name(REPOSITORY_NAME)
+        .workflow_filename(WORKFLOW_FILENAME)
+        .issuer("https://forgejo.example.com/api/actions")
+        .build();
+
+    let body = claims.as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.json(), @r#"{"errors":[{"detail":"Unsupported JWT issuer: https://forgejo.example.com/api/actions"}]}"#);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_invalid_audience() -> anyhow::Result<()> {
+    let client = prepare().await?;
+
+    let mut claims = default_claims();
+    claims.aud = "invalid-audience".into();
+
+    let body = claims.as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.json(), @r#"{"errors":[{"detail":"Failed to decode JWT"}]}"#);
+
+    Ok(())
+}
+
+/// Test that OIDC tokens can only be exchanged once
+#[tokio::test(flavor = "multi_thread")]
+async fn test_token_reuse() -> anyhow::Result<()> {
+    let client = prepare().await?;
+
+    let body = default_claims().as_exchange_body()?;
+
+    // The first exchange should succeed
+    let response = client.post::<()>(URL, body.clone()).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    // The second exchange should fail
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.json(), @r#"{"errors":[{"detail":"JWT has already been used"}]}"#);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unsupported_event() -> anyhow::Result<()> {
+    let client = prepare().await?;
+
+    let mut claims = default_claims();
+    claims.event_name = "pull_request_target".into();
+
+    let body = claims.as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.json(), @r#"{"errors":[{"detail":"Trusted Publishing does not support the `pull_request_target` event trigger due to security concerns. Please use a different trigger such as `push`, `release`, or `workflow_dispatch`."}]}"#);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_invalid_repository() -> anyhow::Result<()> {
+    let client = prepare().await?;
+
+    let mut claims = default_claims();
+    claims.repository = "what?".into();
+
+    let body = claims.as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.json(), @r#"{"errors":[{"detail":"Unexpected `repository` value"}]}"#);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_invalid_workflow() -> anyhow::Result<()> {
+    let client = prepare().await?;
+
+    let mut claims = default_claims();
+    claims.workflow_ref = "what?".into();
+
+    let body = claims.as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.json(), @r#"{"errors":[{"detail":"Unexpected `workflow_ref` value"}]}"#);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_missing_config() -> anyhow::Result<()> {
+    let (_app, client, _cookie) = TestApp::full()
+        .with_oidc_keystore(FORGEJO_ISSUER_URL, MockOidcKeyStore::with_test_key())
+        .with_user()
+        .await;
+
+    let body = default_claims().as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.json(), @r#"{"errors":[{"detail":"No Trusted Publishing config found for repository `rust-lang/foo-rs`."}]}"#);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_lazy_owner_id_population() -> anyhow::Result<()> {
+    let client = prepare().await?;
+    let mut conn = client.app().db_conn().await;
+
+    let body = default_claims().as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let config: ForgejoConfig = ForgejoConfig::query()
+        .filter(trustpub_configs_forgejo::repository_owner.eq(OWNER_NAME))
+        .filter(trustpub_configs_forgejo::repository_name.eq(REPOSITORY_NAME))
+        .first(&mut conn)
+        .await?;
+
+    assert_some_eq!(config.repository_owner_id, OWNER_ID.to_string());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_owner_id_mismatch_resurrection_attack() -> anyhow::Result<()> {
+    let client = prepare().await?;
+
+    let mut conn = client.app().db_conn().await;
+
+    diesel::update(trustpub_configs_forgejo::table)
+        .set(trustpub_configs_forgejo::repository_owner_id.eq("999"))
+        .execute(&mut conn)
+        .await?;
+
+    let body = default_claims().as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.json(), @r#"{"errors":[{"detail":"The Trusted Publishing config for repository `rust-lang/foo-rs` does not match the repository owner ID (42) in the JWT. Expected owner IDs: 999. Please recreate the Trusted Publishing config to update the repository owner ID."}]}"#);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_wrong_workflow_filename() -> anyhow::Result<()> {
+    let client = prepare().await?;
+
+    let mut claims = default_claims();
+    claims.workflow_ref = "rust-lang/foo-rs/.forgejo/workflows/other.yml@refs/heads/main".into();
+
+    let body = claims.as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.json(), @r#"{"errors":[{"detail":"The Trusted Publishing config for repository `rust-lang/foo-rs` does not match the workflow filename `other.yml` in the JWT. Expected workflow filenames: `publish.yml`"}]}"#);
+
+    Ok(())
+}
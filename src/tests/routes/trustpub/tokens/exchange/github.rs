@@ -1,5 +1,6 @@
 use crate::builders::CrateBuilder;
 use crate::util::{MockAnonymousUser, RequestHelper, TestApp};
+use chrono::{TimeDelta, Utc};
 use claims::assert_ok;
 use crates_io_database::models::trustpub::NewGitHubConfig;
 use crates_io_database::schema::trustpub_tokens;
@@ -56,6 +57,8 @@ fn new_oidc_config(crate_id: i32) -> NewGitHubConfig<'static> {
         repository_name: REPOSITORY_NAME,
         workflow_filename: WORKFLOW_FILENAME,
         environment: None,
+        ref_pattern: None,
+        expires_at: None,
     }
 }
 
@@ -479,3 +482,62 @@ async fn test_wrong_environment() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+// ============================================================================
+// Ref pattern matching tests
+// ============================================================================
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_happy_path_with_ref_pattern() -> anyhow::Result<()> {
+    let client = prepare_with_config(|c| c.ref_pattern = Some("refs/tags/v*")).await?;
+
+    let mut claims = default_claims();
+    claims.r#ref = "refs/tags/v1.2.3".into();
+
+    let body = claims.as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_wrong_ref() -> anyhow::Result<()> {
+    let client = prepare_with_config(|c| c.ref_pattern = Some("refs/tags/v*")).await?;
+
+    let body = default_claims().as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.json(), @r#"{"errors":[{"detail":"The Trusted Publishing config for repository `rust-lang/foo-rs` does not match the ref `refs/heads/main` in the JWT. Expected ref patterns: `refs/tags/v*`"}]}"#);
+
+    Ok(())
+}
+
+// ============================================================================
+// Expiry tests
+// ============================================================================
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_expired_config() -> anyhow::Result<()> {
+    let client =
+        prepare_with_config(|c| c.expires_at = Some(Utc::now() - TimeDelta::days(1))).await?;
+
+    let body = default_claims().as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.json(), @r#"{"errors":[{"detail":"The Trusted Publishing config for repository `rust-lang/foo-rs` has expired. Please create a new Trusted Publishing config."}]}"#);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unexpired_config() -> anyhow::Result<()> {
+    let client =
+        prepare_with_config(|c| c.expires_at = Some(Utc::now() + TimeDelta::days(1))).await?;
+
+    let body = default_claims().as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use crate::builders::CrateBuilder;
 use crate::util::{MockAnonymousUser, RequestHelper, TestApp};
+use chrono::{TimeDelta, Utc};
 use claims::{assert_ok, assert_some_eq};
 use crates_io_database::models::trustpub::{GitLabConfig, NewGitLabConfig};
 use crates_io_database::schema::{trustpub_configs_gitlab, trustpub_tokens};
@@ -21,6 +22,7 @@ const NAMESPACE: &str = "rust-lang";
 const NAMESPACE_ID: &str = "42";
 const PROJECT: &str = "foo-rs";
 const WORKFLOW_FILEPATH: &str = "some/subfolder/jobs.yaml";
+const SELF_HOSTED_ISSUER: &str = "https://gitlab.example.com";
 
 async fn prepare() -> anyhow::Result<MockAnonymousUser> {
     prepare_with_config(|_config| {}).await
@@ -55,6 +57,9 @@ fn new_oidc_config(crate_id: i32) -> NewGitLabConfig<'static> {
         project: PROJECT,
         workflow_filepath: WORKFLOW_FILEPATH,
         environment: None,
+        issuer: GITLAB_ISSUER_URL,
+        ref_pattern: None,
+        expires_at: None,
     }
 }
 
@@ -151,6 +156,59 @@ async fn test_case_insensitive() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_happy_path_with_self_hosted_issuer() -> anyhow::Result<()> {
+    let (app, client, cookie) = TestApp::full()
+        .with_config(|config| {
+            config
+                .trustpub_gitlab_issuer_urls
+                .push(SELF_HOSTED_ISSUER.into())
+        })
+        .with_oidc_keystore(SELF_HOSTED_ISSUER, MockOidcKeyStore::with_test_key())
+        .with_user()
+        .await;
+
+    let mut conn = app.db_conn().await;
+
+    let owner_id = cookie.as_model().id;
+    let krate = CrateBuilder::new(CRATE_NAME, owner_id)
+        .build(&mut conn)
+        .await?;
+
+    let mut new_oidc_config = new_oidc_config(krate.id);
+    new_oidc_config.issuer = SELF_HOSTED_ISSUER;
+    new_oidc_config.insert(&mut conn).await?;
+
+    let claims = FullGitLabClaims::builder()
+        .namespace_id(NAMESPACE_ID)
+        .namespace(NAMESPACE)
+        .project(PROJECT)
+        .workflow_filepath(WORKFLOW_FILEPATH)
+        .issuer(SELF_HOSTED_ISSUER)
+        .build();
+
+    let body = claims.as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let trustpub_data = trustpub_tokens::table
+        .select(trustpub_tokens::trustpub_data)
+        .get_result::<Option<serde_json::Value>>(&mut conn)
+        .await?;
+
+    assert_json_snapshot!(trustpub_data, @r#"
+    {
+      "issuer": "https://gitlab.example.com",
+      "job_id": "11530106120",
+      "project_path": "rust-lang/foo-rs",
+      "provider": "gitlab",
+      "sha": "76719c2658b5c4423810d655a4624af1b38b7091"
+    }
+    "#);
+
+    Ok(())
+}
+
 // ============================================================================
 // JWT decode and validation tests
 // ============================================================================
@@ -392,6 +450,67 @@ async fn test_missing_config() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_config_for_different_issuer() -> anyhow::Result<()> {
+    let (app, client, cookie) = TestApp::full()
+        .with_config(|config| {
+            config
+                .trustpub_gitlab_issuer_urls
+                .push(SELF_HOSTED_ISSUER.into())
+        })
+        .with_oidc_keystore(SELF_HOSTED_ISSUER, MockOidcKeyStore::with_test_key())
+        .with_user()
+        .await;
+
+    let mut conn = app.db_conn().await;
+
+    let owner_id = cookie.as_model().id;
+    let krate = CrateBuilder::new(CRATE_NAME, owner_id)
+        .build(&mut conn)
+        .await?;
+
+    // The config is for `gitlab.com`, but the JWT comes from the self-hosted instance
+    new_oidc_config(krate.id).insert(&mut conn).await?;
+
+    let claims = FullGitLabClaims::builder()
+        .namespace_id(NAMESPACE_ID)
+        .namespace(NAMESPACE)
+        .project(PROJECT)
+        .workflow_filepath(WORKFLOW_FILEPATH)
+        .issuer(SELF_HOSTED_ISSUER)
+        .build();
+
+    let body = claims.as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.json(), @r#"{"errors":[{"detail":"No Trusted Publishing config found for repository `rust-lang/foo-rs`."}]}"#);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_issuer_not_allowed() -> anyhow::Result<()> {
+    let (_app, client, _cookie) = TestApp::full()
+        .with_oidc_keystore(SELF_HOSTED_ISSUER, MockOidcKeyStore::with_test_key())
+        .with_user()
+        .await;
+
+    let claims = FullGitLabClaims::builder()
+        .namespace_id(NAMESPACE_ID)
+        .namespace(NAMESPACE)
+        .project(PROJECT)
+        .workflow_filepath(WORKFLOW_FILEPATH)
+        .issuer(SELF_HOSTED_ISSUER)
+        .build();
+
+    let body = claims.as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.json(), @r#"{"errors":[{"detail":"Unsupported JWT issuer: https://gitlab.example.com"}]}"#);
+
+    Ok(())
+}
+
 // ============================================================================
 // Namespace ID lazy population and resurrection protection tests
 // ============================================================================
@@ -494,3 +613,62 @@ async fn test_wrong_environment() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+// ============================================================================
+// Ref pattern matching tests
+// ============================================================================
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_happy_path_with_ref_pattern() -> anyhow::Result<()> {
+    let client = prepare_with_config(|c| c.ref_pattern = Some("refs/tags/v*")).await?;
+
+    let mut claims = default_claims();
+    claims.ref_path = "refs/tags/v1.2.3".into();
+
+    let body = claims.as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_wrong_ref() -> anyhow::Result<()> {
+    let client = prepare_with_config(|c| c.ref_pattern = Some("refs/tags/v*")).await?;
+
+    let body = default_claims().as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.json(), @r#"{"errors":[{"detail":"The Trusted Publishing config for repository `rust-lang/foo-rs` does not match the ref `refs/heads/main` in the JWT. Expected ref patterns: `refs/tags/v*`"}]}"#);
+
+    Ok(())
+}
+
+// ============================================================================
+// Expiry tests
+// ============================================================================
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_expired_config() -> anyhow::Result<()> {
+    let client =
+        prepare_with_config(|c| c.expires_at = Some(Utc::now() - TimeDelta::days(1))).await?;
+
+    let body = default_claims().as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"400 Bad Request");
+    assert_snapshot!(response.json(), @r#"{"errors":[{"detail":"The Trusted Publishing config for repository `rust-lang/foo-rs` has expired. Please create a new Trusted Publishing config."}]}"#);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unexpired_config() -> anyhow::Result<()> {
+    let client =
+        prepare_with_config(|c| c.expires_at = Some(Utc::now() + TimeDelta::days(1))).await?;
+
+    let body = default_claims().as_exchange_body()?;
+    let response = client.post::<()>(URL, body).await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    Ok(())
+}
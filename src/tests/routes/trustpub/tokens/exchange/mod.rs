@@ -1,2 +1,3 @@
+mod forgejo;
 mod github;
 mod gitlab;
@@ -1,10 +1,14 @@
 use crate::builders::{CrateBuilder, PublishBuilder};
+use crate::util::{MockCookieUser, MockTokenUser};
 use crate::{OwnerTeamsResponse, RequestHelper, TestApp, add_team_to_crate, new_team};
 use crates_io::models::{Crate, CrateOwner, NewTeam};
+use crates_io::schema::{teams, version_owner_actions, versions, versions_published_by};
 
 use diesel::*;
 use diesel_async::RunQueryDsl;
+use http::StatusCode;
 use insta::assert_snapshot;
+use serde_json::json;
 
 impl crate::util::MockAnonymousUser {
     /// List the team owners of the specified crate.
@@ -500,3 +504,121 @@ async fn crates_by_team_id_not_including_deleted_owners() -> anyhow::Result<()>
 
     Ok(())
 }
+
+/// Creates a token owned by the `github:test-org:core` team on behalf of the
+/// given team maintainer.
+async fn new_team_token(app: &TestApp, maintainer: &MockCookieUser) -> MockTokenUser {
+    let json = json!({
+        "api_token": {
+            "name": "release automation",
+            "endpoint_scopes": ["publish-update", "yank"],
+            "team": "github:test-org:core",
+        }
+    });
+
+    let response = maintainer
+        .put::<()>("/api/v1/me/tokens", serde_json::to_vec(&json).unwrap())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let plaintext = response.json()["api_token"]["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    MockTokenUser::with_auth_header(plaintext, app.clone())
+}
+
+/// Test publishing and yanking with a token that is owned by an owning team
+#[tokio::test(flavor = "multi_thread")]
+async fn publish_with_team_token() -> anyhow::Result<()> {
+    let (app, _) = TestApp::full().empty().await;
+    let mut conn = app.db_conn().await;
+    let maintainer = app.db_new_user("user-all-teams").await;
+    let token = maintainer.db_new_token("arbitrary token name").await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo_team_token", "1.0.0"))
+        .await
+        .good();
+
+    token
+        .add_named_owner("foo_team_token", "github:test-org:core")
+        .await
+        .good();
+
+    let team_token = new_team_token(&app, &maintainer).await;
+
+    team_token
+        .publish_crate(PublishBuilder::new("foo_team_token", "2.0.0"))
+        .await
+        .good();
+
+    let response = team_token
+        .delete::<()>("/api/v1/crates/foo_team_token/2.0.0/yank")
+        .await;
+    assert_snapshot!(response.status(), @"200 OK");
+
+    let team_id: i32 = teams::table
+        .filter(teams::login.eq("github:test-org:core"))
+        .select(teams::id)
+        .get_result(&mut conn)
+        .await?;
+
+    let version_id: i32 = versions::table
+        .filter(versions::num.eq("2.0.0"))
+        .select(versions::id)
+        .get_result(&mut conn)
+        .await?;
+
+    let published_by: Option<i32> = versions_published_by::table
+        .find(version_id)
+        .select(versions_published_by::team_id)
+        .get_result(&mut conn)
+        .await?;
+    assert_eq!(published_by, Some(team_id));
+
+    let action_team_ids: Vec<Option<i32>> = version_owner_actions::table
+        .filter(version_owner_actions::version_id.eq(version_id))
+        .select(version_owner_actions::team_id)
+        .order(version_owner_actions::id)
+        .load(&mut conn)
+        .await?;
+    assert_eq!(action_team_ids, vec![Some(team_id), Some(team_id)]);
+
+    Ok(())
+}
+
+/// Test that team tokens can't be used for crates that the team doesn't own
+#[tokio::test(flavor = "multi_thread")]
+async fn publish_not_owned_with_team_token() {
+    let (app, _) = TestApp::full().empty().await;
+    let maintainer = app.db_new_user("user-all-teams").await;
+    let token = maintainer.db_new_token("arbitrary token name").await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo_team_token", "1.0.0"))
+        .await
+        .good();
+
+    token
+        .add_named_owner("foo_team_token", "github:test-org:core")
+        .await
+        .good();
+
+    token
+        .publish_crate(PublishBuilder::new("foo_not_team_owned", "1.0.0"))
+        .await
+        .good();
+
+    let team_token = new_team_token(&app, &maintainer).await;
+
+    let crate_to_publish = PublishBuilder::new("foo_not_team_owned", "2.0.0");
+    let response = team_token.publish_crate(crate_to_publish).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"team tokens can only be used for crates that are owned by the team"}]}"#);
+
+    let crate_to_publish = PublishBuilder::new("foo_new_crate", "1.0.0");
+    let response = team_token.publish_crate(crate_to_publish).await;
+    assert_snapshot!(response.status(), @"403 Forbidden");
+    assert_snapshot!(response.text(), @r#"{"errors":[{"detail":"this token does not have the required permissions to perform this action"}]}"#);
+}
@@ -49,11 +49,13 @@ pub mod matchers;
 mod mock_request;
 mod response;
 mod test_app;
+mod webhook_receiver;
 
 use mock_request::MockRequest;
 pub use mock_request::MockRequestExt;
 pub use response::Response;
 pub use test_app::TestApp;
+pub use webhook_receiver::WebhookReceiver;
 
 /// This function can be used to create a `Cookie` header for mock requests that
 /// include cookie-based authentication.
@@ -16,11 +16,13 @@ pub(crate) const MOCK_GITHUB_DATA: MockData = MockData {
                 id: 2000,
                 name: "all",
                 members: &["user-all-teams", "user-one-team"],
+                maintainers: &[],
             },
             MockTeam {
                 id: 2001,
                 name: "core",
                 members: &["user-all-teams"],
+                maintainers: &["user-all-teams"],
             },
         ],
     }],
@@ -126,8 +128,14 @@ impl MockData {
             .find(|team| team.id == team_id)
             .ok_or_else(not_found)?;
         if team.members.contains(&username) {
+            let role = match team.maintainers.contains(&username) {
+                true => "maintainer",
+                false => "member",
+            };
+
             Ok(Some(GitHubTeamMembership {
                 state: "active".into(),
+                role: role.into(),
             }))
         } else {
             Ok(None)
@@ -191,4 +199,5 @@ struct MockTeam {
     id: i32,
     name: &'static str,
     members: &'static [&'static str],
+    maintainers: &'static [&'static str],
 }
@@ -20,7 +20,9 @@ use crates_io_index::{Credentials, RepositoryConfig};
 use crates_io_og_image::OgImageGenerator;
 use crates_io_team_repo::MockTeamRepo;
 use crates_io_test_db::TestDatabase;
+use crates_io_trustpub::forgejo::FORGEJO_ISSUER_URL;
 use crates_io_trustpub::github::test_helpers::AUDIENCE;
+use crates_io_trustpub::gitlab::GITLAB_ISSUER_URL;
 use crates_io_trustpub::keystore::{MockOidcKeyStore, OidcKeyStore};
 use crates_io_worker::Runner;
 use diesel_async::AsyncPgConnection;
@@ -46,6 +48,39 @@ struct TestAppInner {
     test_database: TestDatabase,
 }
 
+impl TestAppInner {
+    /// Runs the background job runner until no new jobs remain in the queue.
+    ///
+    /// Jobs can enqueue other jobs on a different queue, whose workers might
+    /// have already shut down, so the runner is restarted until only failed
+    /// jobs are left.
+    async fn run_pending_jobs(&self) {
+        use crates_io::schema::background_jobs;
+        use diesel::prelude::*;
+        use diesel_async::RunQueryDsl;
+
+        let Some(runner) = &self.runner else {
+            return;
+        };
+
+        loop {
+            let handle = runner.start();
+            handle.wait_for_shutdown().await;
+
+            let mut conn = self.test_database.async_connect().await;
+            let new_jobs = background_jobs::table.filter(background_jobs::retries.eq(0));
+            let has_new_jobs = diesel::select(diesel::dsl::exists(new_jobs))
+                .get_result::<bool>(&mut conn)
+                .await
+                .unwrap();
+
+            if !has_new_jobs {
+                break;
+            }
+        }
+    }
+}
+
 impl Drop for TestAppInner {
     fn drop(&mut self) {
         use crates_io::schema::background_jobs;
@@ -57,13 +92,8 @@ impl Drop for TestAppInner {
         }
 
         // Lazily run any remaining jobs
-        if let Some(runner) = &self.runner {
-            block_in_place(move || {
-                Handle::current().block_on(async {
-                    let handle = runner.start();
-                    handle.wait_for_shutdown().await;
-                })
-            });
+        if self.runner.is_some() {
+            block_in_place(|| Handle::current().block_on(self.run_pending_jobs()));
         }
 
         // Manually verify that all jobs have completed successfully
@@ -118,6 +148,14 @@ impl TestApp {
         Self::init().with_git_index().with_job_runner()
     }
 
+    /// Initialize a full application without a git index, like an
+    /// installation that only publishes the sparse index
+    pub fn sparse_only() -> TestAppBuilder {
+        Self::init()
+            .with_job_runner()
+            .with_config(|config| config.git_index_enabled = false)
+    }
+
     /// Obtain an async database connection from the primary database pool.
     pub async fn db_conn(&self) -> AsyncPgConnection {
         self.0.test_database.async_connect().await
@@ -216,14 +254,19 @@ impl TestApp {
     }
 
     pub async fn run_pending_background_jobs(&self) {
+        let result = self.try_run_pending_background_jobs().await;
+        result.expect("Could not determine if jobs failed");
+    }
+
+    /// Runs all pending background jobs and returns an error if any of them
+    /// failed and remain in the queue for a retry.
+    pub async fn try_run_pending_background_jobs(&self) -> anyhow::Result<()> {
         let runner = &self.0.runner;
         let runner = runner.as_ref().expect("Index has not been initialized");
 
-        let handle = runner.start();
-        handle.wait_for_shutdown().await;
+        self.0.run_pending_jobs().await;
 
-        let result = runner.check_for_failed_jobs().await;
-        result.expect("Could not determine if jobs failed");
+        runner.check_for_failed_jobs().await
     }
 
     /// Obtain a reference to the inner `App` value
@@ -302,22 +345,27 @@ impl TestAppBuilder {
         let (app, router) = build_app(self.config, self.github, self.oidc_key_stores);
 
         let runner = if self.build_job_runner {
-            let index = self
-                .index
-                .as_ref()
-                .expect("Index must be initialized to build a job runner");
-
-            let repository_config = RepositoryConfig {
-                index_location: index.url(),
-                credentials: Credentials::Missing,
-            };
+            let repository_config = app.config.git_index_enabled.then(|| {
+                let index = self
+                    .index
+                    .as_ref()
+                    .expect("Index must be initialized to build a job runner");
+
+                RepositoryConfig {
+                    index_location: index.url(),
+                    credentials: Credentials::Missing,
+                }
+            });
 
             let environment = Environment::builder()
                 .config(app.config.clone())
-                .repository_config(repository_config)
+                .maybe_repository_config(repository_config)
                 .storage(app.storage.clone())
                 .deadpool(app.primary_database.clone())
                 .emails(app.emails.clone())
+                // The mock client of the app can't be shared, so the job
+                // runner always uses the default mock data.
+                .github(Box::new(MOCK_GITHUB_DATA.as_mock_client()))
                 .maybe_docs_rs(self.docs_rs.map(|cl| Box::new(cl) as _))
                 .team_repo(Box::new(self.team_repo))
                 .maybe_og_image_generator(self.og_image_generator)
@@ -526,10 +574,23 @@ fn simple_config() -> config::Server {
         html_render_cache_max_capacity: 1024,
         content_security_policy: None,
         trustpub_audience: AUDIENCE.to_string(),
+        trustpub_gitlab_issuer_urls: vec![GITLAB_ISSUER_URL.to_string()],
+        trustpub_forgejo_issuer_url: FORGEJO_ISSUER_URL.to_string(),
         disable_token_creation: None,
         banner_message: None,
         index_include_pubtime: false,
+        git_index_enabled: true,
         sparse_index_fastly_enabled: true,
+        serve_sparse_index: true,
+        sparse_index_api_url: "https://crates.io".parse().unwrap(),
+        sparse_index_cache_size: 1000,
+        sparse_index_cache_ttl: Duration::from_secs(60),
+        suggest_cache_size: 1000,
+        suggest_cache_ttl: Duration::from_secs(60),
+        suggest_rate_limit: 1000,
+        index_signing: None,
+        // The webhook tests deliver events to a local receiver.
+        webhooks_allow_private_addresses: true,
     }
 }
 
@@ -553,6 +614,9 @@ fn build_app(
         .emails(emails)
         .storage_from_config(&config.storage)
         .rate_limiter_from_config(config.rate_limiter.clone())
+        .sparse_index_cache_from_config(&config)
+        .suggest_cache_from_config(&config)
+        .suggest_rate_limiter_from_config(&config)
         .config(Arc::new(config))
         .build();
 
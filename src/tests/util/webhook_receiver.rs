@@ -0,0 +1,76 @@
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::routing::post;
+use http::{HeaderMap, StatusCode};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+/// A request that was received by a [`WebhookReceiver`].
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl ReceivedRequest {
+    pub fn header(&self, name: &str) -> &str {
+        self.headers[name].to_str().unwrap()
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).unwrap()
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    status: Mutex<Option<StatusCode>>,
+    requests: Mutex<Vec<ReceivedRequest>>,
+}
+
+/// A local HTTP server that records all incoming webhook deliveries and
+/// responds with a configurable status code.
+pub struct WebhookReceiver {
+    url: String,
+    inner: Arc<Inner>,
+}
+
+impl WebhookReceiver {
+    pub async fn new() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/webhook", listener.local_addr().unwrap());
+
+        let inner = Arc::new(Inner::default());
+
+        let router = Router::new()
+            .route("/webhook", post(handle))
+            .with_state(inner.clone());
+
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        Self { url, inner }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Sets the status code of all subsequent responses.
+    pub fn respond_with(&self, status: StatusCode) {
+        *self.inner.status.lock() = Some(status);
+    }
+
+    pub fn requests(&self) -> Vec<ReceivedRequest> {
+        self.inner.requests.lock().clone()
+    }
+}
+
+async fn handle(State(inner): State<Arc<Inner>>, headers: HeaderMap, body: Bytes) -> StatusCode {
+    inner
+        .requests
+        .lock()
+        .push(ReceivedRequest { headers, body });
+    inner.status.lock().unwrap_or(StatusCode::NO_CONTENT)
+}
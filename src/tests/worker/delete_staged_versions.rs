@@ -0,0 +1,58 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use chrono::{TimeDelta, Utc};
+use crates_io::schema::staged_versions;
+use crates_io::worker::jobs;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::StatusCode;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn deletes_expired_staged_versions() -> anyhow::Result<()> {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+
+    // `bar` has not been published yet
+    for (name, version) in [("foo", "1.1.0"), ("foo", "1.2.0"), ("bar", "1.0.0")] {
+        let url = "/api/v1/crates/new?staged=true";
+        let response = token
+            .put::<()>(url, PublishBuilder::new(name, version))
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    // Move the first staged version of `foo` and the staged version of `bar`
+    // past their expiry date
+    let expired_at = Utc::now() - jobs::DeleteExpiredStagedVersions::LIFETIME - TimeDelta::hours(1);
+    diesel::update(staged_versions::table)
+        .filter(staged_versions::num.eq_any(["1.0.0", "1.1.0"]))
+        .set(staged_versions::created_at.eq(expired_at))
+        .execute(&mut conn)
+        .await?;
+
+    jobs::DeleteExpiredStagedVersions.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let remaining: Vec<String> = staged_versions::table
+        .select(staged_versions::num)
+        .load(&mut conn)
+        .await?;
+    assert_eq!(remaining, ["1.2.0"]);
+
+    let staged_files = app
+        .stored_files()
+        .await
+        .into_iter()
+        .filter(|path| path.starts_with("private/staged-crates/"))
+        .collect::<Vec<_>>();
+    assert_eq!(staged_files.len(), 1);
+    assert!(staged_files[0].starts_with("private/staged-crates/foo/foo-1.2.0-"));
+
+    Ok(())
+}
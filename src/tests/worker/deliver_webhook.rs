@@ -0,0 +1,350 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp, WebhookReceiver};
+use chrono::{TimeDelta, Utc};
+use crates_io::schema::{background_jobs, crate_webhooks};
+use crates_io::worker::jobs::DeliverWebhook;
+use crates_io::worker::jobs::webhooks::{
+    DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER, signature,
+};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use http::StatusCode;
+use insta::assert_json_snapshot;
+use serde_json::{Value, json};
+
+const SECRET: &str = "correct horse battery staple";
+
+async fn create_webhook(user: &impl RequestHelper, url: &str, events: &[&str]) -> Value {
+    let body = json!({ "webhook": { "url": url, "secret": SECRET, "events": events } });
+    let response = user
+        .post::<()>("/api/v1/crates/foo/webhooks", body.to_string())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    response.json()["webhook"].clone()
+}
+
+async fn deliveries(user: &impl RequestHelper, webhook_id: &Value) -> Vec<Value> {
+    let url = format!("/api/v1/crates/foo/webhooks/{webhook_id}/deliveries");
+    let response = user.get::<()>(&url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    response.json()["deliveries"].as_array().unwrap().clone()
+}
+
+/// Makes failed jobs eligible for an immediate retry.
+async fn expire_retry_backoff(conn: &mut AsyncPgConnection) -> QueryResult<usize> {
+    let last_retry = (Utc::now() - TimeDelta::days(1)).naive_utc();
+    diesel::update(background_jobs::table)
+        .set(background_jobs::last_retry.eq(last_retry))
+        .execute(conn)
+        .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn delivers_signed_payloads() {
+    let (app, _, user, token) = TestApp::full().with_token().await;
+    let receiver = WebhookReceiver::new().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+
+    let events = ["publish", "yank", "unyank", "owner-change"];
+    let webhook = create_webhook(&user, receiver.url(), &events).await;
+    let yank_only = create_webhook(&user, receiver.url(), &["yank"]).await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.1.0"))
+        .await
+        .good();
+
+    let response = token.delete::<()>("/api/v1/crates/foo/1.1.0/yank").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    app.run_pending_background_jobs().await;
+
+    let response = token.put::<()>("/api/v1/crates/foo/1.1.0/unyank", "").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    app.run_pending_background_jobs().await;
+
+    app.db_new_user("user2").await;
+    token.add_named_owner("foo", "user2").await.good();
+    app.run_pending_background_jobs().await;
+
+    let mut requests = receiver.requests();
+    requests.sort_by_key(|request| request.header(DELIVERY_HEADER).parse::<i64>().unwrap());
+
+    for request in &requests {
+        let expected_signature = signature(SECRET, &request.body);
+        assert_eq!(request.header(SIGNATURE_HEADER), expected_signature);
+        assert_eq!(request.header("content-type"), "application/json");
+        assert_eq!(request.header(EVENT_HEADER), request.json()["event"]);
+    }
+
+    let payloads = requests.iter().map(|r| r.json()).collect::<Vec<_>>();
+    assert_json_snapshot!(payloads, { "[].timestamp" => "[datetime]" }, @r#"
+    [
+      {
+        "crate": "foo",
+        "event": "publish",
+        "timestamp": "[datetime]",
+        "version": "1.1.0"
+      },
+      {
+        "crate": "foo",
+        "event": "yank",
+        "timestamp": "[datetime]",
+        "version": "1.1.0",
+        "yank_message": null
+      },
+      {
+        "crate": "foo",
+        "event": "yank",
+        "timestamp": "[datetime]",
+        "version": "1.1.0",
+        "yank_message": null
+      },
+      {
+        "crate": "foo",
+        "event": "unyank",
+        "timestamp": "[datetime]",
+        "version": "1.1.0",
+        "yank_message": null
+      },
+      {
+        "action": "invite",
+        "crate": "foo",
+        "event": "owner-change",
+        "owner": "user2",
+        "timestamp": "[datetime]"
+      }
+    ]
+    "#);
+
+    let webhook_deliveries = deliveries(&user, &webhook["id"]).await;
+    assert_eq!(webhook_deliveries.len(), 4);
+    assert_json_snapshot!(webhook_deliveries[0], {
+        ".created_at" => "[datetime]",
+        ".last_attempt_at" => "[datetime]",
+        ".delivered_at" => "[datetime]",
+        ".payload.timestamp" => "[datetime]",
+    }, @r#"
+    {
+      "attempts": 1,
+      "created_at": "[datetime]",
+      "delivered_at": "[datetime]",
+      "error": null,
+      "event": "owner-change",
+      "id": 5,
+      "last_attempt_at": "[datetime]",
+      "payload": {
+        "action": "invite",
+        "crate": "foo",
+        "event": "owner-change",
+        "owner": "user2",
+        "timestamp": "[datetime]"
+      },
+      "response_status": 204
+    }
+    "#);
+
+    assert_eq!(deliveries(&user, &yank_only["id"]).await.len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn retries_and_disables_failing_webhooks() -> anyhow::Result<()> {
+    let (app, _, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+    let receiver = WebhookReceiver::new().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+
+    let webhook = create_webhook(&user, receiver.url(), &["yank", "unyank"]).await;
+    let webhook_id = webhook["id"].as_i64().unwrap() as i32;
+
+    // The first attempt fails and is retried later
+    receiver.respond_with(StatusCode::INTERNAL_SERVER_ERROR);
+
+    let response = token.delete::<()>("/api/v1/crates/foo/1.0.0/yank").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(app.try_run_pending_background_jobs().await.is_err());
+
+    let delivery = &deliveries(&user, &webhook["id"]).await[0];
+    assert_eq!(delivery["attempts"], 1);
+    assert_eq!(delivery["response_status"], 500);
+    assert_eq!(
+        delivery["error"],
+        "unexpected response status: 500 Internal Server Error"
+    );
+    assert_eq!(delivery["delivered_at"], Value::Null);
+
+    let consecutive_failures: i32 = crate_webhooks::table
+        .find(webhook_id)
+        .select(crate_webhooks::consecutive_failures)
+        .get_result(&mut conn)
+        .await?;
+    assert_eq!(consecutive_failures, 1);
+
+    // The retry succeeds and resets the failure counter
+    receiver.respond_with(StatusCode::OK);
+    expire_retry_backoff(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let delivery = &deliveries(&user, &webhook["id"]).await[0];
+    assert_eq!(delivery["attempts"], 2);
+    assert_eq!(delivery["response_status"], 200);
+    assert_eq!(delivery["error"], Value::Null);
+    assert!(delivery["delivered_at"].is_string());
+
+    let consecutive_failures: i32 = crate_webhooks::table
+        .find(webhook_id)
+        .select(crate_webhooks::consecutive_failures)
+        .get_result(&mut conn)
+        .await?;
+    assert_eq!(consecutive_failures, 0);
+    assert_eq!(receiver.requests().len(), 2);
+
+    // Repeated failures disable the webhook
+    receiver.respond_with(StatusCode::INTERNAL_SERVER_ERROR);
+
+    diesel::update(crate_webhooks::table.find(webhook_id))
+        .set(crate_webhooks::consecutive_failures.eq(DeliverWebhook::MAX_CONSECUTIVE_FAILURES - 1))
+        .execute(&mut conn)
+        .await?;
+
+    let response = token.put::<()>("/api/v1/crates/foo/1.0.0/unyank", "").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    app.run_pending_background_jobs().await;
+    assert_eq!(receiver.requests().len(), 3);
+
+    let response = user.get::<()>("/api/v1/crates/foo/webhooks").await;
+    let webhook = &response.json()["webhooks"][0];
+    assert_eq!(webhook["enabled"], false);
+    assert_eq!(
+        webhook["consecutive_failures"],
+        DeliverWebhook::MAX_CONSECUTIVE_FAILURES
+    );
+
+    // Disabled webhooks don't receive any new events
+    let response = token.delete::<()>("/api/v1/crates/foo/1.0.0/yank").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    app.run_pending_background_jobs().await;
+    assert_eq!(receiver.requests().len(), 3);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn gives_up_after_max_attempts() -> anyhow::Result<()> {
+    let (app, _, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+    let receiver = WebhookReceiver::new().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+
+    let webhook = create_webhook(&user, receiver.url(), &["yank"]).await;
+
+    receiver.respond_with(StatusCode::NOT_FOUND);
+
+    let response = token.delete::<()>("/api/v1/crates/foo/1.0.0/yank").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    for _ in 1..DeliverWebhook::MAX_ATTEMPTS {
+        assert!(app.try_run_pending_background_jobs().await.is_err());
+        expire_retry_backoff(&mut conn).await?;
+    }
+    app.run_pending_background_jobs().await;
+
+    let max_attempts = DeliverWebhook::MAX_ATTEMPTS as usize;
+    assert_eq!(receiver.requests().len(), max_attempts);
+
+    let delivery = &deliveries(&user, &webhook["id"]).await[0];
+    assert_eq!(delivery["attempts"], DeliverWebhook::MAX_ATTEMPTS);
+    assert_eq!(delivery["response_status"], 404);
+    assert_eq!(delivery["delivered_at"], Value::Null);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn delivers_deletion_event() -> anyhow::Result<()> {
+    let (app, _, user, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+    let receiver = WebhookReceiver::new().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+
+    create_webhook(&user, receiver.url(), &["delete"]).await;
+    create_webhook(&user, receiver.url(), &["publish"]).await;
+
+    let response = user.delete::<()>("/api/v1/crates/foo?message=bye").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    app.run_pending_background_jobs().await;
+
+    let requests = receiver.requests();
+    assert_eq!(requests.len(), 1);
+    assert_json_snapshot!(requests[0].json(), { ".timestamp" => "[datetime]" }, @r#"
+    {
+      "crate": "foo",
+      "event": "delete",
+      "message": "bye",
+      "timestamp": "[datetime]"
+    }
+    "#);
+
+    // The webhooks are removed once the deletion event has been delivered
+    let remaining: i64 = crate_webhooks::table.count().get_result(&mut conn).await?;
+    assert_eq!(remaining, 0);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_internal_addresses() -> anyhow::Result<()> {
+    let (app, _, user, token) = TestApp::full()
+        .with_config(|config| config.webhooks_allow_private_addresses = false)
+        .with_token()
+        .await;
+    let mut conn = app.db_conn().await;
+    let receiver = WebhookReceiver::new().await;
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .await
+        .good();
+
+    // Both the IP address literal and the resolved host name point at the
+    // local receiver
+    let by_ip = create_webhook(&user, receiver.url(), &["yank"]).await;
+    let by_name_url = receiver.url().replace("127.0.0.1", "localhost");
+    let by_name = create_webhook(&user, &by_name_url, &["yank"]).await;
+
+    let response = token.delete::<()>("/api/v1/crates/foo/1.0.0/yank").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    for _ in 1..DeliverWebhook::MAX_ATTEMPTS {
+        assert!(app.try_run_pending_background_jobs().await.is_err());
+        expire_retry_backoff(&mut conn).await?;
+    }
+    app.run_pending_background_jobs().await;
+
+    assert_eq!(receiver.requests().len(), 0);
+
+    for webhook in [by_ip, by_name] {
+        let delivery = &deliveries(&user, &webhook["id"]).await[0];
+        assert_eq!(delivery["attempts"], DeliverWebhook::MAX_ATTEMPTS);
+        assert_eq!(delivery["response_status"], Value::Null);
+        assert_eq!(delivery["error"], "address not allowed");
+        assert_eq!(delivery["delivered_at"], Value::Null);
+    }
+
+    Ok(())
+}
@@ -1,8 +1,16 @@
+mod delete_staged_versions;
+mod deliver_webhook;
 mod generate_og_image;
 mod git;
+mod prune_api_token_usages;
+mod publish_index_changes;
 mod readmes;
 mod rss;
 mod send_publish_notifications;
+mod sign_index_metadata;
+mod sparse_only;
 mod sync_admins;
 mod trustpub;
 mod update_default_version;
+mod verify_index;
+mod verify_team_tokens;
@@ -0,0 +1,51 @@
+use crate::util::{MockRequestExt, RequestHelper, TestApp};
+use chrono::{TimeDelta, Utc};
+use crates_io::models::token::EndpointScope;
+use crates_io::schema::api_token_usages;
+use crates_io::worker::jobs;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use http::StatusCode;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn prunes_old_api_token_usages() -> anyhow::Result<()> {
+    let (app, _, _, token) = TestApp::full()
+        .with_scoped_token(None, Some(vec![EndpointScope::ReadPrivate]))
+        .await;
+    let mut conn = app.db_conn().await;
+
+    let url = format!("/api/v1/me/tokens/{}", token.as_model().id);
+    for ip in ["192.0.2.1", "192.0.2.2", "192.0.2.3"] {
+        let mut request = token.get_request(&url);
+        request.header("X-Forwarded-For", ip);
+        let response = token.run::<()>(request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let ids: Vec<i64> = api_token_usages::table
+        .select(api_token_usages::id)
+        .order(api_token_usages::id)
+        .load(&mut conn)
+        .await?;
+    assert_eq!(ids.len(), 3);
+
+    // Move the first usage past the retention period
+    let last_used_at = Utc::now() - jobs::PruneApiTokenUsages::RETENTION - TimeDelta::hours(1);
+    diesel::update(api_token_usages::table.find(ids[0]))
+        .set(api_token_usages::last_used_at.eq(last_used_at))
+        .execute(&mut conn)
+        .await?;
+
+    jobs::PruneApiTokenUsages.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let remaining: Vec<i64> = api_token_usages::table
+        .select(api_token_usages::id)
+        .order(api_token_usages::id)
+        .load(&mut conn)
+        .await?;
+    assert_eq!(remaining, ids[1..]);
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::models::{IndexChangeReason, NewIndexChange};
+use crates_io::schema::index_changes;
+use crates_io::worker::jobs::PublishIndexChanges;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use insta::assert_snapshot;
+use object_store::ObjectStoreExt;
+use serde_json::Value;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn publishes_changes_page() -> anyhow::Result<()> {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    for version in ["1.0.0", "1.1.0"] {
+        let crate_to_publish = PublishBuilder::new("foo", version);
+        token.publish_crate(crate_to_publish).await.good();
+        app.run_pending_background_jobs().await;
+    }
+
+    let unpublished: i64 = index_changes::table
+        .filter(index_changes::published_at.is_null())
+        .count()
+        .get_result(&mut conn)
+        .await?;
+    assert_eq!(unpublished, 0);
+
+    let store = app.as_inner().storage.as_inner();
+    let path = object_store::path::Path::parse("index/changes/1/0.json")?;
+    let stored = store.get(&path).await?.bytes().await?;
+    let page: serde_json::Value = serde_json::from_slice(&stored)?;
+
+    assert_snapshot!(page["complete"], @"false");
+    let changes = page["changes"].as_array().unwrap();
+    let changes = changes
+        .iter()
+        .map(|change| format!("{} {} {}", change["seq"], change["name"], change["reason"]))
+        .collect::<Vec<_>>();
+    assert_snapshot!(changes.join("\n"), @r#"
+    1 "foo" "update"
+    2 "foo" "update"
+    "#);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn out_of_order_commits_are_not_skipped() -> anyhow::Result<()> {
+    let (app, anon) = TestApp::full().empty().await;
+    let mut conn = app.db_conn().await;
+    let mut slow_conn = app.db_conn().await;
+
+    let change = |crate_name| NewIndexChange {
+        crate_name,
+        reason: IndexChangeReason::Update,
+    };
+
+    let list_changes = async |since: &Value| {
+        let url = format!("/api/v1/index/changes?since={since}");
+        let json = anon.get::<()>(&url).await.json();
+        let names = json["changes"].as_array().unwrap().iter();
+        let names = names.map(|change| change["name"].as_str().unwrap().to_string());
+        (
+            names.collect::<Vec<_>>(),
+            json["meta"]["next_since"].clone(),
+        )
+    };
+
+    // The first transaction allocates the lower id, but commits last
+    diesel::sql_query("BEGIN").execute(&mut slow_conn).await?;
+    change("foo").insert(&mut slow_conn).await?;
+
+    change("bar").insert(&mut conn).await?;
+    PublishIndexChanges.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let (names, next_since) = list_changes(&Value::from(0)).await;
+    assert_eq!(names, ["bar"]);
+
+    diesel::sql_query("COMMIT").execute(&mut slow_conn).await?;
+    PublishIndexChanges.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    // A mirror that already synced `bar` still receives `foo`
+    let (names, _) = list_changes(&next_since).await;
+    assert_eq!(names, ["foo"]);
+
+    Ok(())
+}
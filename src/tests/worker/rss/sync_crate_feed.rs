@@ -36,6 +36,39 @@ async fn test_sync_crate_feed() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sync_crate_feed_deprecated() -> anyhow::Result<()> {
+    let (app, _) = TestApp::full().empty().await;
+    let mut conn = app.db_conn().await;
+
+    create_version(&mut conn, "foo", "1.0.0", "2024-06-21T17:01:33Z").await?;
+    create_version(&mut conn, "foo", "1.1.0", "2024-06-22T08:30:01Z").await?;
+
+    let deprecated_at = DateTime::parse_from_rfc3339("2024-07-01T09:12:00Z")?.naive_utc();
+    diesel::update(crates::table)
+        .filter(crates::name.eq("foo"))
+        .set((
+            crates::deprecated_at.eq(deprecated_at),
+            crates::deprecation_reason.eq("No longer maintained"),
+            crates::deprecation_successor.eq("bar"),
+        ))
+        .execute(&mut conn)
+        .await?;
+
+    let job = jobs::rss::SyncCrateFeed::new("foo".to_string());
+    job.enqueue(&mut conn).await?;
+
+    app.run_pending_background_jobs().await;
+
+    let store = app.as_inner().storage.as_inner();
+    let result = store.get(&"rss/crates/foo.xml".into()).await?;
+    let bytes = result.bytes().await?;
+    let content = String::from_utf8(bytes.to_vec())?;
+    assert_snapshot!(content);
+
+    Ok(())
+}
+
 async fn create_version(
     conn: &mut AsyncPgConnection,
     name: &str,
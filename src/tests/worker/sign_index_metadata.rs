@@ -0,0 +1,270 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::config::IndexSigningConfig;
+use crates_io::schema::index_targets;
+use crates_io::worker::jobs;
+use crates_io_index::tuf::{
+    NUM_BINS, RoleType, Root, Signed, SigningKey, Snapshot, TargetFile, Targets, Timestamp,
+    bin_for_path, bin_role_name,
+};
+use crates_io_worker::BackgroundJob;
+use diesel_async::RunQueryDsl;
+use insta::assert_snapshot;
+use object_store::ObjectStoreExt;
+
+fn online_key() -> SigningKey {
+    SigningKey::from_secret_bytes(&[2; 32]).unwrap()
+}
+
+fn signing_config() -> IndexSigningConfig {
+    IndexSigningConfig {
+        online_key: online_key(),
+        targets_expiry: chrono::Duration::days(7),
+        timestamp_expiry: chrono::Duration::days(1),
+    }
+}
+
+/// Builds the `root` metadata that a mirror would have obtained out of band.
+fn trusted_root() -> Root {
+    let root_key = SigningKey::from_secret_bytes(&[1; 32]).unwrap();
+
+    let mut root = Root::new(1, chrono::Utc::now() + chrono::Duration::days(365));
+    root.add_key(RoleType::Root, root_key.public_key()).unwrap();
+    for role in [RoleType::Targets, RoleType::Snapshot, RoleType::Timestamp] {
+        root.add_key(role, online_key().public_key()).unwrap();
+    }
+    root
+}
+
+async fn download(app: &TestApp, path: &str) -> anyhow::Result<Vec<u8>> {
+    let store = app.as_inner().storage.as_inner();
+    let path = object_store::path::Path::parse(path)?;
+    Ok(store.get(&path).await?.bytes().await?.to_vec())
+}
+
+/// Downloads and verifies the `snapshot` metadata and the hash bin that is
+/// responsible for the given index file.
+async fn download_bin(
+    app: &TestApp,
+    targets: &Signed<Targets>,
+    path: &str,
+) -> anyhow::Result<Signed<Targets>> {
+    let name = bin_role_name(bin_for_path(path));
+    let bytes = download(app, &format!("index/tuf/{name}.json")).await?;
+    let bin = Signed::<Targets>::from_slice(&bytes)?;
+
+    let delegations = targets.signed.delegations.as_ref().unwrap();
+    delegations.verify(&name, &bin)?;
+
+    Ok(bin)
+}
+
+async fn download_snapshot(app: &TestApp) -> anyhow::Result<Snapshot> {
+    let bytes = download(app, "index/tuf/snapshot.json").await?;
+    let snapshot = Signed::<Snapshot>::from_slice(&bytes)?;
+    trusted_root().verify(&snapshot)?;
+    Ok(snapshot.signed)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn signs_index_metadata() -> anyhow::Result<()> {
+    let (app, _, _, token) = TestApp::full()
+        .with_config(|config| config.index_signing = Some(signing_config()))
+        .with_token()
+        .await;
+
+    for name in ["foo", "bar"] {
+        let crate_to_publish = PublishBuilder::new(name, "1.0.0");
+        token.publish_crate(crate_to_publish).await.good();
+        app.run_pending_background_jobs().await;
+    }
+
+    let root = trusted_root();
+
+    let bytes = download(&app, "index/tuf/timestamp.json").await?;
+    let timestamp = Signed::<Timestamp>::from_slice(&bytes)?;
+    root.verify(&timestamp)?;
+    assert_eq!(timestamp.signed.version, 2);
+
+    let bytes = download(&app, "index/tuf/snapshot.json").await?;
+    let snapshot = Signed::<Snapshot>::from_slice(&bytes)?;
+    root.verify(&snapshot)?;
+    assert!(timestamp.signed.meta["snapshot.json"].matches(&bytes));
+
+    let bytes = download(&app, "index/tuf/targets.json").await?;
+    let targets = Signed::<Targets>::from_slice(&bytes)?;
+    root.verify(&targets)?;
+    assert!(targets.signed.targets.is_empty());
+    assert_eq!(
+        snapshot.signed.meta["targets.json"].version,
+        targets.signed.version
+    );
+
+    // All index files are listed in the hash bins that the `targets`
+    // metadata delegates to
+    assert_eq!(snapshot.signed.meta.len(), NUM_BINS + 1);
+
+    let mut paths = Vec::new();
+    for path in ["3/b/bar", "3/f/foo"] {
+        let bin = download_bin(&app, &targets, path).await?;
+        let file_name = format!("{}.json", bin_role_name(bin_for_path(path)));
+        assert_eq!(snapshot.signed.meta[&file_name].version, bin.signed.version);
+
+        paths.extend(bin.signed.targets.keys().cloned());
+    }
+    assert_snapshot!(paths.join("\n"), @r"
+    3/b/bar
+    3/f/foo
+    ");
+
+    let bin = download_bin(&app, &targets, "3/f/foo").await?;
+    let index_file = download(&app, "index/3/f/foo").await?;
+    let expected = TargetFile::from_content(&index_file);
+    assert_eq!(bin.signed.targets["3/f/foo"], expected);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn publish_only_resigns_a_single_bin() -> anyhow::Result<()> {
+    let (app, _, _, token) = TestApp::full()
+        .with_config(|config| config.index_signing = Some(signing_config()))
+        .with_token()
+        .await;
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0");
+    token.publish_crate(crate_to_publish).await.good();
+    app.run_pending_background_jobs().await;
+
+    let before = download_snapshot(&app).await?;
+
+    let crate_to_publish = PublishBuilder::new("bar", "1.0.0");
+    token.publish_crate(crate_to_publish).await.good();
+    app.run_pending_background_jobs().await;
+
+    let after = download_snapshot(&app).await?;
+    assert_eq!(after.version, before.version + 1);
+
+    let changed = after
+        .meta
+        .iter()
+        .filter(|(file_name, meta)| before.meta[*file_name].version != meta.version)
+        .map(|(file_name, _)| file_name.clone())
+        .collect::<Vec<_>>();
+
+    let bin = bin_role_name(bin_for_path("3/b/bar"));
+    assert_eq!(changed, vec![format!("{bin}.json")]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn backfills_missing_targets() -> anyhow::Result<()> {
+    let (app, _, _, token) = TestApp::full()
+        .with_config(|config| config.index_signing = Some(signing_config()))
+        .with_token()
+        .await;
+    let mut conn = app.db_conn().await;
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0");
+    token.publish_crate(crate_to_publish).await.good();
+    app.run_pending_background_jobs().await;
+
+    // Simulate an index file that was uploaded before signing was enabled
+    diesel::delete(index_targets::table)
+        .execute(&mut conn)
+        .await?;
+    jobs::SignIndexMetadata.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let bytes = download(&app, "index/tuf/targets.json").await?;
+    let targets = Signed::<Targets>::from_slice(&bytes)?;
+    let bin = download_bin(&app, &targets, "3/f/foo").await?;
+    assert!(bin.signed.targets.is_empty());
+
+    jobs::BackfillIndexTargets.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let bin = download_bin(&app, &targets, "3/f/foo").await?;
+    let index_file = download(&app, "index/3/f/foo").await?;
+    let expected = TargetFile::from_content(&index_file);
+    assert_eq!(bin.signed.targets["3/f/foo"], expected);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_tampered_metadata() -> anyhow::Result<()> {
+    let (app, _, _, token) = TestApp::full()
+        .with_config(|config| config.index_signing = Some(signing_config()))
+        .with_token()
+        .await;
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0");
+    token.publish_crate(crate_to_publish).await.good();
+    app.run_pending_background_jobs().await;
+
+    let bytes = download(&app, "index/tuf/targets.json").await?;
+    let targets = Signed::<Targets>::from_slice(&bytes)?;
+    let delegations = targets.signed.delegations.as_ref().unwrap();
+
+    let name = bin_role_name(bin_for_path("3/f/foo"));
+    let mut bin = download_bin(&app, &targets, "3/f/foo").await?;
+    let target = bin.signed.targets.get_mut("3/f/foo").unwrap();
+    *target = TargetFile::from_content(b"tampered");
+
+    assert!(delegations.verify(&name, &bin).is_err());
+
+    // Metadata signed by an untrusted key is rejected as well
+    let untrusted_key = SigningKey::from_secret_bytes(&[3; 32])?;
+    bin.signatures.clear();
+    bin.sign(&untrusted_key)?;
+    assert!(delegations.verify(&name, &bin).is_err());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn increments_version_on_every_run() -> anyhow::Result<()> {
+    let (app, _) = TestApp::full()
+        .with_config(|config| config.index_signing = Some(signing_config()))
+        .empty()
+        .await;
+    let mut conn = app.db_conn().await;
+
+    for _ in 0..2 {
+        jobs::SignIndexMetadata.enqueue(&mut conn).await?;
+        app.run_pending_background_jobs().await;
+    }
+
+    let bytes = download(&app, "index/tuf/timestamp.json").await?;
+    let timestamp = Signed::<Timestamp>::from_slice(&bytes)?;
+    assert_eq!(timestamp.signed.version, 2);
+    assert_eq!(timestamp.signed.meta["snapshot.json"].version, 2);
+    assert!(timestamp.signed.meta["snapshot.json"].hashes.is_some());
+
+    // The `targets` metadata and the bins are only re-signed when they
+    // change or are about to expire
+    let snapshot = download_snapshot(&app).await?;
+    assert!(snapshot.meta.values().all(|meta| meta.version == 1));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn skips_signing_without_config() -> anyhow::Result<()> {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0");
+    token.publish_crate(crate_to_publish).await.good();
+    app.run_pending_background_jobs().await;
+
+    let stored_files = app.stored_files().await;
+    assert!(
+        !stored_files
+            .iter()
+            .any(|path| path.starts_with("index/tuf/"))
+    );
+
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::schema::background_jobs;
+use crates_io::worker::jobs;
+use crates_io_database::models::IndexVerification;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use insta::assert_snapshot;
+use object_store::ObjectStoreExt;
+
+/// Returns the `yanked` flags of all versions in the sparse index file of the
+/// given crate, or `None` if the file does not exist.
+async fn sparse_index_yanked(app: &TestApp, path: &str) -> anyhow::Result<Option<Vec<bool>>> {
+    let store = app.as_inner().storage.as_inner();
+    let path = object_store::path::Path::parse(path)?;
+    let bytes = match store.get(&path).await {
+        Ok(result) => result.bytes().await?,
+        Err(object_store::Error::NotFound { .. }) => return Ok(None),
+        Err(error) => return Err(error.into()),
+    };
+
+    let yanked = std::str::from_utf8(&bytes)?
+        .lines()
+        .map(|line| Ok(serde_json::from_str::<crates_io_index::Crate>(line)?.yanked == Some(true)))
+        .collect::<anyhow::Result<_>>()?;
+
+    Ok(Some(yanked))
+}
+
+/// Asserts that no git index jobs have been enqueued. These are not
+/// registered by the job runner, so they would stay in the queue forever.
+async fn assert_no_git_index_jobs(app: &TestApp) -> anyhow::Result<()> {
+    let mut conn = app.db_conn().await;
+
+    let job_types: Vec<String> = background_jobs::table
+        .select(background_jobs::job_type)
+        .load(&mut conn)
+        .await?;
+
+    assert!(
+        !job_types
+            .iter()
+            .any(|t| t == jobs::SyncToGitIndex::JOB_NAME)
+    );
+    assert!(!job_types.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn publish_yank_and_delete() -> anyhow::Result<()> {
+    let (app, _, user, token) = TestApp::sparse_only().with_token().await;
+
+    let body = PublishBuilder::new("foo", "1.0.0").body();
+    let response = token.put::<()>("/api/v1/crates/new", body).await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_no_git_index_jobs(&app).await?;
+    app.run_pending_background_jobs().await;
+    assert_eq!(
+        sparse_index_yanked(&app, "index/3/f/foo").await?,
+        Some(vec![false])
+    );
+
+    let response = token.delete::<()>("/api/v1/crates/foo/1.0.0/yank").await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_no_git_index_jobs(&app).await?;
+    app.run_pending_background_jobs().await;
+    assert_eq!(
+        sparse_index_yanked(&app, "index/3/f/foo").await?,
+        Some(vec![true])
+    );
+
+    let response = token.put::<()>("/api/v1/crates/foo/1.0.0/unyank", "").await;
+    assert_snapshot!(response.status(), @"200 OK");
+    assert_no_git_index_jobs(&app).await?;
+    app.run_pending_background_jobs().await;
+    assert_eq!(
+        sparse_index_yanked(&app, "index/3/f/foo").await?,
+        Some(vec![false])
+    );
+
+    let response = user.delete::<()>("/api/v1/crates/foo").await;
+    assert_snapshot!(response.status(), @"204 No Content");
+    assert_no_git_index_jobs(&app).await?;
+    app.run_pending_background_jobs().await;
+    assert_eq!(sparse_index_yanked(&app, "index/3/f/foo").await?, None);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn verify_index_skips_git_index() -> anyhow::Result<()> {
+    let (app, _, _, token) = TestApp::sparse_only().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0");
+    token.publish_crate(crate_to_publish).await.good();
+    app.run_pending_background_jobs().await;
+
+    jobs::VerifyIndex::new(false).enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let verification = IndexVerification::latest(&mut conn).await?.unwrap();
+    assert_eq!(verification.crates_checked, 1);
+    assert_eq!(verification.num_mismatches(), 0);
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::index::{IndexFiles, verify_index};
+use crates_io::schema::{crates, index_verifications, versions};
+use crates_io::worker::jobs;
+use crates_io_database::models::IndexVerification;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use insta::assert_json_snapshot;
+use object_store::ObjectStoreExt;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reports_no_mismatches_for_synced_index() -> anyhow::Result<()> {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    for name in ["foo", "bar"] {
+        let crate_to_publish = PublishBuilder::new(name, "1.0.0");
+        token.publish_crate(crate_to_publish).await.good();
+    }
+    app.run_pending_background_jobs().await;
+
+    jobs::VerifyIndex::new(false).enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let verification = IndexVerification::latest(&mut conn).await?.unwrap();
+    assert_eq!(verification.crates_checked, 2);
+    assert_eq!(verification.num_mismatches(), 0);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn detects_and_repairs_drift() -> anyhow::Result<()> {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    for name in ["foo", "bar"] {
+        let crate_to_publish = PublishBuilder::new(name, "1.0.0");
+        token.publish_crate(crate_to_publish).await.good();
+    }
+    app.run_pending_background_jobs().await;
+
+    // Change the database without syncing the index files
+    let foo_id: i32 = crates::table
+        .filter(crates::name.eq("foo"))
+        .select(crates::id)
+        .first(&mut conn)
+        .await?;
+
+    diesel::update(versions::table)
+        .filter(versions::crate_id.eq(foo_id))
+        .set(versions::yanked.eq(true))
+        .execute(&mut conn)
+        .await?;
+
+    // Remove a sparse index file, and add a git index file for an unknown crate
+    let store = app.as_inner().storage.as_inner();
+    store.delete(&"index/3/b/bar".into()).await?;
+    app.upstream_index().write_file("3/b/baz", "{}\n")?;
+
+    jobs::VerifyIndex::new(true).enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let verification = IndexVerification::query()
+        .order(index_verifications::id)
+        .first(&mut conn)
+        .await?;
+    assert_eq!(verification.crates_checked, 2);
+    assert_json_snapshot!(verification.mismatches);
+
+    // The repair jobs have been run too, so a second run should not find any
+    // more mismatches
+    jobs::VerifyIndex::new(false).enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let verification = IndexVerification::latest(&mut conn).await?.unwrap();
+    assert_eq!(verification.num_mismatches(), 0);
+    assert!(!app.upstream_index().crate_exists("baz")?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn skips_crates_with_queued_sync_jobs() -> anyhow::Result<()> {
+    let (app, _, _, token) = TestApp::full().with_token().await;
+    let mut conn = app.db_conn().await;
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0");
+    token.publish_crate(crate_to_publish).await.good();
+    app.run_pending_background_jobs().await;
+
+    // Change the database, and queue the sync job without running it yet
+    diesel::update(versions::table)
+        .set(versions::yanked.eq(true))
+        .execute(&mut conn)
+        .await?;
+
+    let storage = &app.as_inner().storage;
+    let backends = [IndexFiles::Sparse(storage)];
+    let report = verify_index(&mut conn, None, false, &backends).await?;
+    assert_eq!(report.mismatches.len(), 1);
+
+    jobs::SyncToSparseIndex::new("foo")
+        .enqueue(&mut conn)
+        .await?;
+
+    let report = verify_index(&mut conn, None, false, &backends).await?;
+    assert_eq!(report.crates_checked, 1);
+    assert!(report.mismatches.is_empty());
+
+    app.run_pending_background_jobs().await;
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use crate::util::TestApp;
+use crates_io::models::NewTeam;
+use crates_io::models::token::{EndpointScope, NewApiToken};
+use crates_io::schema::api_tokens;
+use crates_io::worker::jobs;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn revokes_tokens_of_former_team_members() -> anyhow::Result<()> {
+    let (app, _) = TestApp::full().empty().await;
+    let mut conn = app.db_conn().await;
+
+    let team = NewTeam::builder()
+        .login("github:test-org:core")
+        .org_id(1000)
+        .github_id(2001)
+        .build();
+    let team = team.create_or_update(&mut conn).await?;
+
+    let member = app.db_new_user("user-all-teams").await;
+    let non_member = app.db_new_user("user-one-team").await;
+
+    let mut token_ids = Vec::new();
+    for user in [&member, &non_member] {
+        let token = NewApiToken::builder()
+            .name("release automation")
+            .user_id(user.as_model().id)
+            .endpoint_scopes(vec![EndpointScope::PublishUpdate])
+            .team_id(team.id)
+            .build()
+            .insert(&mut conn)
+            .await?;
+
+        token_ids.push(token.id);
+    }
+
+    jobs::VerifyTeamTokens.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let tokens: Vec<(bool, bool)> = api_tokens::table
+        .filter(api_tokens::id.eq_any(&token_ids))
+        .select((
+            api_tokens::revoked,
+            api_tokens::team_membership_verified_at.is_not_null(),
+        ))
+        .order(api_tokens::id)
+        .load(&mut conn)
+        .await?;
+
+    // The token of the current member is marked as verified, while the token
+    // of the user that is no longer a member of the team is revoked.
+    assert_eq!(tokens, vec![(false, true), (true, false)]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn revokes_tokens_of_former_team_maintainers() -> anyhow::Result<()> {
+    let (app, _) = TestApp::full().empty().await;
+    let mut conn = app.db_conn().await;
+
+    // `user-all-teams` is a member of this team, but not a maintainer
+    let team = NewTeam::builder()
+        .login("github:test-org:all")
+        .org_id(1000)
+        .github_id(2000)
+        .build();
+    let team = team.create_or_update(&mut conn).await?;
+
+    let user = app.db_new_user("user-all-teams").await;
+    let token = NewApiToken::builder()
+        .name("release automation")
+        .user_id(user.as_model().id)
+        .endpoint_scopes(vec![EndpointScope::PublishUpdate])
+        .team_id(team.id)
+        .build()
+        .insert(&mut conn)
+        .await?;
+
+    jobs::VerifyTeamTokens.enqueue(&mut conn).await?;
+    app.run_pending_background_jobs().await;
+
+    let revoked: bool = api_tokens::table
+        .find(token.id)
+        .select(api_tokens::revoked)
+        .get_result(&mut conn)
+        .await?;
+
+    assert!(revoked);
+
+    Ok(())
+}
@@ -244,6 +244,18 @@ impl From<crates_io_trustpub::gitlab::validation::ValidationError> for BoxedAppE
     }
 }
 
+impl From<crates_io_trustpub::forgejo::validation::ValidationError> for BoxedAppError {
+    fn from(error: crates_io_trustpub::forgejo::validation::ValidationError) -> Self {
+        bad_request(error)
+    }
+}
+
+impl From<crates_io_trustpub::ref_pattern::ValidationError> for BoxedAppError {
+    fn from(error: crates_io_trustpub::ref_pattern::ValidationError) -> Self {
+        bad_request(error)
+    }
+}
+
 // =============================================================================
 // Internal error for use with `chain_error`
 
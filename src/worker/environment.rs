@@ -3,11 +3,12 @@ use crate::cloudfront::CloudFront;
 use crate::storage::Storage;
 use crate::typosquat;
 use crate::worker::jobs::ProcessCloudfrontInvalidationQueue;
-use anyhow::Context;
+use anyhow::{Context, anyhow};
 use bon::Builder;
 use crates_io_database::models::{CloudFrontDistribution, CloudFrontInvalidationQueueItem};
 use crates_io_docs_rs::DocsRsClient;
 use crates_io_fastly::Fastly;
+use crates_io_github::GitHubClient;
 use crates_io_index::{Repository, RepositoryConfig};
 use crates_io_og_image::OgImageGenerator;
 use crates_io_team_repo::TeamRepo;
@@ -26,7 +27,9 @@ use tracing::{info, instrument};
 pub struct Environment {
     pub config: Arc<crate::config::Server>,
 
-    repository_config: RepositoryConfig,
+    /// The configuration of the git index, or `None` if the git index is
+    /// disabled.
+    repository_config: Option<RepositoryConfig>,
     #[builder(skip)]
     repository: Mutex<Option<Repository>>,
     cloudfront: Option<CloudFront>,
@@ -35,6 +38,7 @@ pub struct Environment {
     pub downloads_archive_store: Option<Box<dyn ObjectStore>>,
     pub deadpool: Pool<AsyncPgConnection>,
     pub emails: Emails,
+    pub github: Box<dyn GitHubClient>,
     pub team_repo: Box<dyn TeamRepo + Send + Sync>,
     pub docs_rs: Option<Box<dyn DocsRsClient>>,
     pub og_image_generator: Option<OgImageGenerator>,
@@ -55,7 +59,7 @@ impl Environment {
             info!("Cloning index");
             let clone_start = Instant::now();
 
-            *repo = Some(Repository::open(&self.repository_config)?);
+            *repo = Some(Repository::open(self.repository_config()?)?);
 
             let clone_duration = clone_start.elapsed();
             info!(duration = clone_duration.as_nanos(), "Index cloned");
@@ -66,6 +70,19 @@ impl Environment {
         Ok(repo_lock)
     }
 
+    /// Clones a separate copy of the index repository, e.g. for long-running
+    /// read-only operations that should not hold the index lock.
+    #[instrument(skip_all)]
+    pub fn clone_index(&self) -> anyhow::Result<Repository> {
+        Repository::open(self.repository_config()?)
+    }
+
+    fn repository_config(&self) -> anyhow::Result<&RepositoryConfig> {
+        self.repository_config
+            .as_ref()
+            .ok_or_else(|| anyhow!("The git index is disabled"))
+    }
+
     pub(crate) fn cloudfront(&self) -> Option<&CloudFront> {
         self.cloudfront.as_ref()
     }
@@ -1,4 +1,5 @@
 use super::IndexVersionDownloadsArchive;
+use super::roll_up_version_downloads::roll_up;
 use crate::schema::version_downloads;
 use crate::tasks::spawn_blocking;
 use crate::worker::Environment;
@@ -27,7 +28,8 @@ const FILE_NAME: &str = "version_downloads.csv";
 /// This job first exports the data from the database to a CSV file using `psql`
 /// and a `COPY` command. The CSV file is then split into multiple files based
 /// on the date column and those are uploaded to the object store. Finally, the
-/// successfully uploaded dates are deleted from the database.
+/// successfully uploaded dates are rolled up into the long-range download
+/// history and deleted from the database.
 #[derive(Serialize, Deserialize)]
 pub struct ArchiveVersionDownloads {
     before: NaiveDate,
@@ -67,6 +69,13 @@ impl BackgroundJob for ArchiveVersionDownloads {
         let uploaded_dates = upload(downloads_archive_store, tempdir.path(), dates).await?;
 
         let mut conn = env.deadpool.get().await?;
+
+        // Keep the weekly and monthly download history of the crates before
+        // the daily rows disappear from the database.
+        roll_up(&mut conn, self.before)
+            .await
+            .context("Failed to roll up version downloads")?;
+
         delete(&mut conn, uploaded_dates).await?;
 
         // Queue up the job to regenerate the archive index.
@@ -251,7 +260,9 @@ async fn delete(conn: &mut AsyncPgConnection, dates: Vec<NaiveDate>) -> anyhow::
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::schema::{crates, version_downloads, versions};
+    use crate::worker::jobs::test_util::{
+        create_crate, create_version, insert_downloads, parse_date,
+    };
     use claims::assert_err;
     use crates_io_test_db::TestDatabase;
     use insta::assert_snapshot;
@@ -379,54 +390,11 @@ mod tests {
         let c1 = create_crate(conn, "foo").await;
         let v1 = create_version(conn, c1, "1.0.0").await;
         let v2 = create_version(conn, c1, "2.0.0").await;
-        insert_downloads(conn, v1, "2021-01-01", 100).await;
-        insert_downloads(conn, v1, "2021-01-02", 200).await;
-        insert_downloads(conn, v1, "2021-01-03", 300).await;
-        insert_downloads(conn, v2, "2021-01-01", 400).await;
-        insert_downloads(conn, v2, "2021-01-02", 500).await;
-        insert_downloads(conn, v2, "2021-01-03", 600).await;
-    }
-
-    async fn create_crate(conn: &mut AsyncPgConnection, name: &str) -> i32 {
-        diesel::insert_into(crates::table)
-            .values(crates::name.eq(name))
-            .returning(crates::id)
-            .get_result(conn)
-            .await
-            .unwrap()
-    }
-
-    async fn create_version(conn: &mut AsyncPgConnection, crate_id: i32, num: &str) -> i32 {
-        diesel::insert_into(versions::table)
-            .values((
-                versions::crate_id.eq(crate_id),
-                versions::num.eq(num),
-                versions::num_no_build.eq(num),
-                versions::checksum.eq(""),
-                versions::crate_size.eq(0),
-            ))
-            .returning(versions::id)
-            .get_result(conn)
-            .await
-            .unwrap()
-    }
-
-    async fn insert_downloads(
-        conn: &mut AsyncPgConnection,
-        version_id: i32,
-        date: &str,
-        downloads: i32,
-    ) {
-        let date = NaiveDate::parse_from_str(date, "%F").unwrap();
-
-        diesel::insert_into(version_downloads::table)
-            .values((
-                version_downloads::version_id.eq(version_id),
-                version_downloads::date.eq(date),
-                version_downloads::downloads.eq(downloads),
-            ))
-            .execute(conn)
-            .await
-            .unwrap();
+        insert_downloads(conn, v1, parse_date("2021-01-01"), 100).await;
+        insert_downloads(conn, v1, parse_date("2021-01-02"), 200).await;
+        insert_downloads(conn, v1, parse_date("2021-01-03"), 300).await;
+        insert_downloads(conn, v2, parse_date("2021-01-01"), 400).await;
+        insert_downloads(conn, v2, parse_date("2021-01-02"), 500).await;
+        insert_downloads(conn, v2, parse_date("2021-01-03"), 600).await;
     }
 }
@@ -0,0 +1,235 @@
+use crate::schema::rolled_up_download_dates;
+use crate::worker::Environment;
+use anyhow::Context;
+use chrono::NaiveDate;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{Array, BigInt, Date, Integer};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use futures_util::TryStreamExt;
+use object_store::{ObjectStore, ObjectStoreExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Roll up the per-date CSV files in the downloads archive into the weekly
+/// and monthly per-crate counts of the `crate_download_rollups` table.
+///
+/// The `version_downloads` rows of these dates were archived and deleted
+/// before the rollups were introduced, so the
+/// [`RollUpVersionDownloads`](super::RollUpVersionDownloads) job can't see
+/// them anymore. Dates that are already tracked in the
+/// `rolled_up_download_dates` table are skipped, so the job can safely be
+/// run more than once. Downloads of versions that have been deleted since
+/// they were archived are not included.
+#[derive(Serialize, Deserialize, Default)]
+pub struct BackfillDownloadRollups;
+
+impl BackgroundJob for BackfillDownloadRollups {
+    const JOB_NAME: &'static str = "backfill_download_rollups";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        info!("Rolling up archived version downloads…");
+
+        let Some(downloads_archive_store) = env.downloads_archive_store.as_ref() else {
+            warn!("No downloads archive store configured");
+            return Ok(());
+        };
+
+        let mut conn = env.deadpool.get().await?;
+        let count = backfill(downloads_archive_store, &mut conn).await?;
+        info!("Rolled up archived version downloads of {count} dates");
+
+        Ok(())
+    }
+}
+
+/// Roll up all archived dates that have not been rolled up yet.
+///
+/// Returns the number of rolled up dates.
+async fn backfill(store: &impl ObjectStore, conn: &mut AsyncPgConnection) -> anyhow::Result<usize> {
+    let rolled_up_dates: Vec<NaiveDate> = rolled_up_download_dates::table
+        .select(rolled_up_download_dates::date)
+        .load(conn)
+        .await?;
+    let rolled_up_dates = rolled_up_dates.into_iter().collect::<HashSet<_>>();
+
+    let dates = archived_dates(store).await?;
+    let dates = dates
+        .into_iter()
+        .filter(|date| !rolled_up_dates.contains(date))
+        .collect::<Vec<_>>();
+
+    info!(
+        "Found {} archived dates that were not rolled up",
+        dates.len()
+    );
+
+    let mut count = 0;
+    for date in dates {
+        let path = object_store::path::Path::from(format!("{date}.csv"));
+        let content = store.get(&path).await?.bytes().await?;
+        let (version_ids, downloads) =
+            parse(&content).with_context(|| format!("Failed to parse archive of {date}"))?;
+
+        if roll_up_date(conn, date, version_ids, downloads).await? {
+            info!("Rolled up archived version downloads of {date}");
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+/// Returns the dates of all per-date CSV files in the downloads archive.
+async fn archived_dates(store: &impl ObjectStore) -> anyhow::Result<BTreeSet<NaiveDate>> {
+    let mut dates = BTreeSet::new();
+
+    let mut contents = store.list(None);
+    while let Some(object) = contents.try_next().await? {
+        let date = object
+            .location
+            .filename()
+            .and_then(|filename| filename.strip_suffix(".csv"))
+            .and_then(|date| NaiveDate::parse_from_str(date, "%F").ok());
+
+        if let Some(date) = date {
+            dates.insert(date);
+        }
+    }
+
+    Ok(dates)
+}
+
+/// Parses the `version_id,downloads` rows of an archived CSV file.
+fn parse(content: &[u8]) -> anyhow::Result<(Vec<i32>, Vec<i64>)> {
+    #[derive(Deserialize)]
+    struct Row {
+        version_id: i32,
+        downloads: i64,
+    }
+
+    let mut version_ids = Vec::new();
+    let mut downloads = Vec::new();
+
+    let mut reader = csv::Reader::from_reader(content);
+    for row in reader.deserialize() {
+        let row: Row = row?;
+        version_ids.push(row.version_id);
+        downloads.push(row.downloads);
+    }
+
+    Ok((version_ids, downloads))
+}
+
+/// Adds the given downloads of a single date to the rollups, unless the date
+/// has been rolled up already.
+///
+/// Returns `true` if the date was rolled up.
+async fn roll_up_date(
+    conn: &mut AsyncPgConnection,
+    date: NaiveDate,
+    version_ids: Vec<i32>,
+    downloads: Vec<i64>,
+) -> QueryResult<bool> {
+    conn.transaction(|conn| {
+        async move {
+            // Claim the date first, so that it is never counted twice.
+            let claimed = diesel::insert_into(rolled_up_download_dates::table)
+                .values(rolled_up_download_dates::date.eq(date))
+                .on_conflict_do_nothing()
+                .execute(conn)
+                .await?;
+
+            if claimed == 0 {
+                return Ok(false);
+            }
+
+            sql_query(include_str!("backfill_download_rollups.sql"))
+                .bind::<Date, _>(date)
+                .bind::<Array<Integer>, _>(version_ids)
+                .bind::<Array<BigInt>, _>(downloads)
+                .execute(conn)
+                .await?;
+
+            Ok(true)
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::worker::jobs::test_util::{all_rollups, create_crate, create_version};
+    use crates_io_test_db::TestDatabase;
+    use insta::assert_debug_snapshot;
+    use object_store::memory::InMemory;
+
+    #[tokio::test]
+    async fn test_backfill() {
+        let test_db = TestDatabase::new();
+        let mut conn = test_db.async_connect().await;
+
+        let foo = create_crate(&mut conn, "foo").await;
+        let foo_v1 = create_version(&mut conn, foo, "1.0.0").await;
+        let foo_v2 = create_version(&mut conn, foo, "2.0.0").await;
+
+        // 2021-01-04 is a Monday. Version `9999` has been deleted since.
+        let store = InMemory::new();
+        let files = [
+            (
+                "2021-01-03.csv",
+                format!("version_id,downloads\n{foo_v1},1\n"),
+            ),
+            (
+                "2021-01-04.csv",
+                format!("version_id,downloads\n{foo_v1},10\n{foo_v2},20\n9999,5\n"),
+            ),
+            (
+                "2021-01-05.csv",
+                format!("version_id,downloads\n{foo_v2},100\n"),
+            ),
+            ("index.html", String::new()),
+        ];
+        for (path, content) in files {
+            store.put(&path.into(), content.into()).await.unwrap();
+        }
+
+        // 2021-01-05 was already rolled up, e.g. by `RollUpVersionDownloads`
+        let date = NaiveDate::from_ymd_opt(2021, 1, 5).unwrap();
+        diesel::insert_into(rolled_up_download_dates::table)
+            .values(rolled_up_download_dates::date.eq(date))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        assert_eq!(backfill(&store, &mut conn).await.unwrap(), 2);
+        assert_debug_snapshot!(all_rollups(&mut conn).await, @r#"
+        [
+            "foo | month | 2021-01-01 | 31",
+            "foo | week | 2020-12-28 | 1",
+            "foo | week | 2021-01-04 | 30",
+        ]
+        "#);
+
+        // Running the backfill again does not count any date twice
+        assert_eq!(backfill(&store, &mut conn).await.unwrap(), 0);
+        assert_eq!(all_rollups(&mut conn).await.len(), 3);
+
+        let dates: Vec<NaiveDate> = rolled_up_download_dates::table
+            .select(rolled_up_download_dates::date)
+            .order(rolled_up_download_dates::date)
+            .load(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(dates.len(), 3);
+    }
+}
@@ -0,0 +1,12 @@
+INSERT INTO crate_download_rollups (crate_id, granularity, period_start, downloads)
+SELECT
+    versions.crate_id,
+    granularities.granularity,
+    date_trunc(granularities.granularity, $1::timestamp)::date,
+    SUM(archived.downloads)
+FROM unnest($2::integer[], $3::bigint[]) AS archived (version_id, downloads)
+INNER JOIN versions ON versions.id = archived.version_id
+CROSS JOIN (VALUES ('week'), ('month')) AS granularities (granularity)
+GROUP BY 1, 2, 3
+ON CONFLICT (crate_id, granularity, period_start)
+DO UPDATE SET downloads = crate_download_rollups.downloads + EXCLUDED.downloads;
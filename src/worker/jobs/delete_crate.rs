@@ -30,12 +30,17 @@ impl BackgroundJob for DeleteCrateFromStorage {
         let name = &self.name;
         let feed_id = FeedId::Crate { name };
 
-        let (crate_file_paths, readme_paths, _, _) = try_join!(
+        let (crate_file_paths, staged_crate_file_paths, readme_paths, _, _) = try_join!(
             async {
                 info!("{name}: Deleting crate files from S3…");
                 let result = ctx.storage.delete_all_crate_files(name).await;
                 result.context("Failed to delete crate files from S3")
             },
+            async {
+                info!("{name}: Deleting staged crate files from S3…");
+                let result = ctx.storage.delete_all_staged_crate_files(name).await;
+                result.context("Failed to delete staged crate files from S3")
+            },
             async {
                 info!("{name}: Deleting readme files from S3…");
                 let result = ctx.storage.delete_all_readmes(name).await;
@@ -61,6 +66,7 @@ impl BackgroundJob for DeleteCrateFromStorage {
         InvalidateCdns::new(
             crate_file_paths
                 .into_iter()
+                .chain(staged_crate_file_paths.into_iter())
                 .chain(readme_paths.into_iter())
                 .chain(std::iter::once(format!("og-images/{name}.png").into()))
                 .chain(std::iter::once(object_store::path::Path::from(&feed_id))),
@@ -0,0 +1,77 @@
+use crate::schema::{crates, staged_versions};
+use crate::worker::Environment;
+use anyhow::Context;
+use chrono::{TimeDelta, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use object_store::Error as ObjectStoreError;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+/// A background job that deletes staged versions, including their crate
+/// files, if they have not been released within
+/// [`DeleteExpiredStagedVersions::LIFETIME`].
+#[derive(Deserialize, Serialize)]
+pub struct DeleteExpiredStagedVersions;
+
+impl DeleteExpiredStagedVersions {
+    /// The duration after which unreleased staged versions are deleted.
+    pub const LIFETIME: TimeDelta = TimeDelta::days(7);
+}
+
+impl BackgroundJob for DeleteExpiredStagedVersions {
+    const JOB_NAME: &'static str = "delete_expired_staged_versions";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, ctx: Self::Context) -> anyhow::Result<()> {
+        let mut conn = ctx.deadpool.get().await?;
+
+        // Staged versions of crates that have not been published yet don't
+        // have a `crates` row, but store the crate name themselves.
+        type Row = (i32, Option<String>, Option<String>, String, String);
+        let expired: Vec<Row> = staged_versions::table
+            .left_join(crates::table)
+            .filter(staged_versions::created_at.lt(Utc::now() - Self::LIFETIME))
+            .select((
+                staged_versions::id,
+                crates::name.nullable(),
+                staged_versions::crate_name,
+                staged_versions::num,
+                staged_versions::checksum,
+            ))
+            .load(&mut conn)
+            .await?;
+
+        for (id, crate_name, staged_crate_name, version, checksum) in expired {
+            let name = crate_name
+                .or(staged_crate_name)
+                .with_context(|| format!("Staged version {id} has no crate name"))?;
+
+            info!("Deleting expired staged version {name}@{version}…");
+
+            let result = ctx
+                .storage
+                .delete_staged_crate_file(&name, &version, &checksum)
+                .await;
+
+            match result {
+                Ok(()) | Err(ObjectStoreError::NotFound { .. }) => {}
+                Err(error) => {
+                    let context =
+                        format!("Failed to delete staged crate file for {name}@{version}");
+                    return Err(error).context(context);
+                }
+            }
+
+            diesel::delete(staged_versions::table.find(id))
+                .execute(&mut conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,376 @@
+use crate::schema::{download_anomalies, version_download_sources};
+use crate::worker::Environment;
+use chrono::{NaiveDate, Utc};
+use crates_io_database::models::DownloadAnomaly;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Date, Double, Integer};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// The minimum number of downloads of a version on a single day for the
+/// download count to be considered for the anomaly detection.
+const MIN_DOWNLOADS: i32 = 1_000;
+
+/// The number of days before the checked date that are used to calculate
+/// the expected number of downloads.
+const BASELINE_DAYS: i32 = 28;
+
+/// The number of standard deviations above the mean of the baseline period
+/// at which a download count is considered a spike.
+const SPIKE_THRESHOLD: f64 = 6.0;
+
+/// The share of the downloads from a single IP prefix at which a download
+/// count is considered to be concentrated on a single source.
+const SOURCE_SHARE_THRESHOLD: f64 = 0.5;
+
+/// The minimum number of downloads of a version from a single source on a
+/// single day for the `version_download_sources` row to be kept after the
+/// anomaly detection.
+///
+/// Regular clients only download a version a few times per day, so pruning
+/// them keeps the table small while still keeping the CI loops and bots that
+/// admins are interested in during the review.
+const MIN_SOURCE_DOWNLOADS: i64 = 10;
+
+/// The number of days that `version_download_sources` rows are kept for
+/// after the checked date, to allow admins to investigate anomalies.
+const SOURCE_RETENTION_DAYS: i64 = 7;
+
+/// Flags anomalous daily download counts of versions (e.g. from CI loops or
+/// bots) in the `download_anomalies` table for review by an admin.
+///
+/// A download count is flagged if it has at least [`MIN_DOWNLOADS`]
+/// downloads, and it is either a statistically significant spike compared to
+/// the previous [`BASELINE_DAYS`] days, or most of the downloads came from a
+/// single IP prefix according to the `version_download_sources` table.
+///
+/// If `exclude` is set, the excess downloads of newly flagged anomalies are
+/// subtracted from the download counts right away. Otherwise, this can be
+/// done by an admin during the review via `crates-admin download-anomalies`.
+///
+/// Afterwards, `version_download_sources` rows up to the checked date with
+/// less than [`MIN_SOURCE_DOWNLOADS`] downloads, and all rows that are older
+/// than [`SOURCE_RETENTION_DAYS`] days before the checked date are deleted.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DetectDownloadAnomalies {
+    date: NaiveDate,
+    exclude: bool,
+}
+
+impl DetectDownloadAnomalies {
+    pub fn new(date: NaiveDate, exclude: bool) -> Self {
+        Self { date, exclude }
+    }
+
+    /// Checks the download counts of yesterday, since the CDN logs of today
+    /// have not been fully processed yet.
+    pub fn yesterday(exclude: bool) -> Self {
+        Self::new(Utc::now().date_naive() - chrono::Duration::days(1), exclude)
+    }
+}
+
+impl BackgroundJob for DetectDownloadAnomalies {
+    const JOB_NAME: &'static str = "detect_download_anomalies";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        info!("Detecting download anomalies on {}…", self.date);
+        let anomalies = detect(&mut conn, self.date).await?;
+        if !anomalies.is_empty() {
+            warn!("Detected {} download anomalies", anomalies.len());
+        }
+
+        if self.exclude {
+            for anomaly in &anomalies {
+                anomaly.exclude(&mut conn).await?;
+            }
+
+            let excluded = anomalies.iter().map(|a| a.excess_downloads()).sum::<i64>();
+            info!("Excluded {excluded} downloads");
+        }
+
+        let deleted = prune_small_sources(&mut conn, self.date).await?;
+        info!("Deleted {deleted} version download sources with few downloads");
+
+        let before = self.date - chrono::Duration::days(SOURCE_RETENTION_DAYS);
+        let deleted = prune_sources(&mut conn, before).await?;
+        info!("Deleted {deleted} version download sources before {before}");
+
+        Ok(())
+    }
+}
+
+#[derive(QueryableByName)]
+struct AnomalyId {
+    #[diesel(sql_type = BigInt)]
+    id: i64,
+}
+
+/// Flags the anomalous download counts on the given date, and returns the
+/// newly flagged anomalies.
+async fn detect(
+    conn: &mut AsyncPgConnection,
+    date: NaiveDate,
+) -> QueryResult<Vec<DownloadAnomaly>> {
+    let ids = diesel::sql_query(include_str!("detect_anomalies.sql"))
+        .bind::<Date, _>(date)
+        .bind::<Integer, _>(MIN_DOWNLOADS)
+        .bind::<Integer, _>(BASELINE_DAYS)
+        .bind::<Double, _>(SPIKE_THRESHOLD)
+        .bind::<Double, _>(SOURCE_SHARE_THRESHOLD)
+        .load::<AnomalyId>(conn)
+        .await?;
+
+    let ids = ids.into_iter().map(|row| row.id).collect::<Vec<_>>();
+
+    DownloadAnomaly::query()
+        .filter(download_anomalies::id.eq_any(ids))
+        .order(download_anomalies::id)
+        .load(conn)
+        .await
+}
+
+/// Deletes the `version_download_sources` rows up to the given date with less
+/// than [`MIN_SOURCE_DOWNLOADS`] downloads over the whole day.
+async fn prune_small_sources(conn: &mut AsyncPgConnection, date: NaiveDate) -> QueryResult<usize> {
+    diesel::delete(version_download_sources::table)
+        .filter(version_download_sources::date.le(date))
+        .filter(version_download_sources::downloads.lt(MIN_SOURCE_DOWNLOADS))
+        .execute(conn)
+        .await
+}
+
+/// Deletes the `version_download_sources` rows before the given date.
+async fn prune_sources(conn: &mut AsyncPgConnection, before: NaiveDate) -> QueryResult<usize> {
+    diesel::delete(version_download_sources::table)
+        .filter(version_download_sources::date.lt(before))
+        .execute(conn)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::version_downloads;
+    use crate::worker::jobs::test_util::{create_crate, create_version_at, insert_downloads};
+    use chrono::Days;
+    use crates_io_test_db::TestDatabase;
+    use insta::assert_debug_snapshot;
+
+    #[tokio::test]
+    async fn test_detect() {
+        let test_db = TestDatabase::new();
+        let mut conn = test_db.async_connect().await;
+
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let old = "2023-01-01T00:00:00Z".parse().unwrap();
+        let new = "2024-02-29T00:00:00Z".parse().unwrap();
+
+        // A spike of an old version
+        let foo = create_crate(&mut conn, "foo").await;
+        let foo_v1 = create_version_at(&mut conn, foo, "1.0.0", old).await;
+        insert_daily_downloads(&mut conn, foo_v1, date, 100).await;
+        insert_downloads(&mut conn, foo_v1, date, 5000).await;
+
+        // Steady downloads of an old version
+        let foo_v2 = create_version_at(&mut conn, foo, "2.0.0", old).await;
+        insert_daily_downloads(&mut conn, foo_v2, date, 1500).await;
+        insert_downloads(&mut conn, foo_v2, date, 1500).await;
+
+        // Downloads of a new version that are concentrated on one IP prefix
+        let bar = create_crate(&mut conn, "bar").await;
+        let bar_v1 = create_version_at(&mut conn, bar, "1.0.0", new).await;
+        insert_downloads(&mut conn, bar_v1, date, 2000).await;
+        insert_source(&mut conn, bar_v1, date, "ip_prefix", "1.2.3.0/24", 1500).await;
+        insert_source(&mut conn, bar_v1, date, "user_agent", "cargo/1.74.0", 1800).await;
+
+        // Downloads of a new version from many sources
+        let baz = create_crate(&mut conn, "baz").await;
+        let baz_v1 = create_version_at(&mut conn, baz, "1.0.0", new).await;
+        insert_downloads(&mut conn, baz_v1, date, 2000).await;
+        insert_source(&mut conn, baz_v1, date, "ip_prefix", "1.2.3.0/24", 100).await;
+
+        let anomalies = detect(&mut conn, date).await.unwrap();
+        let anomalies = anomalies.iter().map(format_anomaly).collect::<Vec<_>>();
+        assert_debug_snapshot!(anomalies, @r#"
+        [
+            "1 | 2024-03-01 | spike | 5000 | 100 | None None | None None",
+            "3 | 2024-03-01 | concentrated_source | 2000 | 0 | Some(\"1.2.3.0/24\") Some(1500) | Some(\"cargo/1.74.0\") Some(1800)",
+        ]
+        "#);
+
+        // Anomalies are only flagged once
+        assert_eq!(detect(&mut conn, date).await.unwrap().len(), 0);
+
+        // Excluding an anomaly subtracts the excess downloads once
+        let anomaly = DownloadAnomaly::query()
+            .filter(download_anomalies::version_id.eq(foo_v1))
+            .first(&mut conn)
+            .await
+            .unwrap();
+
+        assert!(anomaly.exclude(&mut conn).await.unwrap());
+        assert!(!anomaly.exclude(&mut conn).await.unwrap());
+
+        let (downloads, processed): (i32, bool) = version_downloads::table
+            .find((foo_v1, date))
+            .select((version_downloads::downloads, version_downloads::processed))
+            .first(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(downloads, 100);
+        assert!(!processed);
+
+        let anomaly = DownloadAnomaly::find(&mut conn, anomaly.id).await.unwrap();
+        assert_eq!(anomaly.excluded_downloads, Some(4900));
+
+        // Only the downloads of the top IP prefix are excluded for
+        // concentrated sources
+        let anomaly = DownloadAnomaly::query()
+            .filter(download_anomalies::version_id.eq(bar_v1))
+            .first(&mut conn)
+            .await
+            .unwrap();
+
+        assert_eq!(anomaly.excess_downloads(), 1500);
+        assert!(anomaly.exclude(&mut conn).await.unwrap());
+
+        let downloads: i32 = version_downloads::table
+            .find((bar_v1, date))
+            .select(version_downloads::downloads)
+            .first(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(downloads, 500);
+
+        let anomaly = DownloadAnomaly::query()
+            .filter(download_anomalies::version_id.eq(foo_v1))
+            .first(&mut conn)
+            .await
+            .unwrap();
+
+        // Reviewed anomalies are no longer listed as unreviewed
+        anomaly.mark_reviewed(&mut conn).await.unwrap();
+        let unreviewed = DownloadAnomaly::unreviewed(&mut conn).await.unwrap();
+        assert_eq!(unreviewed.len(), 1);
+        assert_eq!(unreviewed[0].version_id, bar_v1);
+    }
+
+    #[tokio::test]
+    async fn test_prune_sources() {
+        let test_db = TestDatabase::new();
+        let mut conn = test_db.async_connect().await;
+
+        let old = "2023-01-01T00:00:00Z".parse().unwrap();
+        let foo = create_crate(&mut conn, "foo").await;
+        let foo_v1 = create_version_at(&mut conn, foo, "1.0.0", old).await;
+
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let previous_day = date - Days::new(1);
+        insert_source(&mut conn, foo_v1, previous_day, "ip_prefix", "::/48", 10).await;
+        insert_source(&mut conn, foo_v1, date, "ip_prefix", "::/48", 10).await;
+
+        assert_eq!(prune_sources(&mut conn, date).await.unwrap(), 1);
+
+        let dates: Vec<NaiveDate> = version_download_sources::table
+            .select(version_download_sources::date)
+            .load(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(dates, vec![date]);
+    }
+
+    #[tokio::test]
+    async fn test_prune_small_sources() {
+        let test_db = TestDatabase::new();
+        let mut conn = test_db.async_connect().await;
+
+        let old = "2023-01-01T00:00:00Z".parse().unwrap();
+        let foo = create_crate(&mut conn, "foo").await;
+        let foo_v1 = create_version_at(&mut conn, foo, "1.0.0", old).await;
+
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let next_day = date + Days::new(1);
+        insert_source(&mut conn, foo_v1, date, "ip_prefix", "1.2.3.0/24", 9).await;
+        insert_source(&mut conn, foo_v1, date, "ip_prefix", "3.4.5.0/24", 10).await;
+        insert_source(&mut conn, foo_v1, next_day, "ip_prefix", "1.2.3.0/24", 9).await;
+
+        assert_eq!(prune_small_sources(&mut conn, date).await.unwrap(), 1);
+
+        let sources: Vec<(NaiveDate, String)> = version_download_sources::table
+            .select((
+                version_download_sources::date,
+                version_download_sources::source,
+            ))
+            .order((
+                version_download_sources::date,
+                version_download_sources::source,
+            ))
+            .load(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(
+            sources,
+            vec![
+                (date, "3.4.5.0/24".to_string()),
+                (next_day, "1.2.3.0/24".to_string()),
+            ]
+        );
+    }
+
+    fn format_anomaly(anomaly: &DownloadAnomaly) -> String {
+        format!(
+            "{} | {} | {} | {} | {} | {:?} {:?} | {:?} {:?}",
+            anomaly.version_id,
+            anomaly.date,
+            anomaly.reason,
+            anomaly.downloads,
+            anomaly.expected_downloads,
+            anomaly.top_ip_prefix,
+            anomaly.top_ip_prefix_downloads,
+            anomaly.top_user_agent,
+            anomaly.top_user_agent_downloads,
+        )
+    }
+
+    /// Inserts the given number of downloads for each day of the baseline
+    /// period before the given date.
+    async fn insert_daily_downloads(
+        conn: &mut AsyncPgConnection,
+        version_id: i32,
+        date: NaiveDate,
+        downloads: i32,
+    ) {
+        for days in 1..=BASELINE_DAYS as u64 {
+            insert_downloads(conn, version_id, date - Days::new(days), downloads).await;
+        }
+    }
+
+    async fn insert_source(
+        conn: &mut AsyncPgConnection,
+        version_id: i32,
+        date: NaiveDate,
+        source_type: &str,
+        source: &str,
+        downloads: i64,
+    ) {
+        diesel::insert_into(version_download_sources::table)
+            .values((
+                version_download_sources::version_id.eq(version_id),
+                version_download_sources::date.eq(date),
+                version_download_sources::source_type.eq(source_type),
+                version_download_sources::source.eq(source),
+                version_download_sources::downloads.eq(downloads),
+            ))
+            .execute(conn)
+            .await
+            .unwrap();
+    }
+}
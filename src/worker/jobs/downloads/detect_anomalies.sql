@@ -0,0 +1,87 @@
+WITH candidates AS (
+    -- Select the versions with enough downloads on the given date to be
+    -- considered for the anomaly detection.
+    SELECT version_downloads.version_id, version_downloads.downloads, versions.created_at
+    FROM version_downloads
+    INNER JOIN versions ON versions.id = version_downloads.version_id
+    WHERE version_downloads.date = $1
+        AND version_downloads.downloads >= $2
+), baselines AS (
+    -- Calculate the mean and the mean of the squares of the daily downloads
+    -- in the baseline period before the given date. Days without a
+    -- `version_downloads` row are counted as zero downloads.
+    SELECT
+        candidates.version_id,
+        COALESCE(SUM(version_downloads.downloads), 0)::float8 / $3 AS mean,
+        COALESCE(SUM(version_downloads.downloads::float8 ^ 2), 0) / $3 AS mean_of_squares
+    FROM candidates
+    LEFT JOIN version_downloads
+        ON version_downloads.version_id = candidates.version_id
+        AND version_downloads.date >= $1 - $3
+        AND version_downloads.date < $1
+    GROUP BY candidates.version_id
+), top_sources AS (
+    -- Select the IP prefix and the user agent with the most downloads for
+    -- each of the candidates.
+    SELECT DISTINCT ON (version_id, source_type) version_id, source_type, source, downloads
+    FROM version_download_sources
+    WHERE date = $1
+        AND version_id IN (SELECT version_id FROM candidates)
+    ORDER BY version_id, source_type, downloads DESC, source
+), stats AS (
+    SELECT
+        candidates.version_id,
+        candidates.downloads,
+        candidates.created_at,
+        baselines.mean,
+        SQRT(GREATEST(baselines.mean_of_squares - baselines.mean ^ 2, 0)) AS stddev,
+        top_ip_prefixes.source AS top_ip_prefix,
+        top_ip_prefixes.downloads AS top_ip_prefix_downloads,
+        top_user_agents.source AS top_user_agent,
+        top_user_agents.downloads AS top_user_agent_downloads
+    FROM candidates
+    INNER JOIN baselines USING (version_id)
+    LEFT JOIN top_sources top_ip_prefixes
+        ON top_ip_prefixes.version_id = candidates.version_id
+        AND top_ip_prefixes.source_type = 'ip_prefix'
+    LEFT JOIN top_sources top_user_agents
+        ON top_user_agents.version_id = candidates.version_id
+        AND top_user_agents.source_type = 'user_agent'
+), anomalies AS (
+    -- A spike is only detected for versions that existed for the whole
+    -- baseline period, since new versions naturally start from zero.
+    SELECT stats.*, CASE
+        WHEN stats.created_at < $1 - $3
+            AND stats.downloads > stats.mean + $4 * GREATEST(stats.stddev, 1)
+            THEN 'spike'
+        WHEN stats.top_ip_prefix_downloads >= stats.downloads * $5
+            THEN 'concentrated_source'
+    END AS reason
+    FROM stats
+)
+INSERT INTO download_anomalies (
+    version_id,
+    date,
+    reason,
+    downloads,
+    expected_downloads,
+    top_ip_prefix,
+    top_ip_prefix_downloads,
+    top_user_agent,
+    top_user_agent_downloads
+)
+SELECT
+    version_id,
+    $1,
+    reason,
+    downloads,
+    ROUND(mean)::bigint,
+    top_ip_prefix,
+    top_ip_prefix_downloads,
+    top_user_agent,
+    top_user_agent_downloads
+FROM anomalies
+WHERE reason IS NOT NULL
+ORDER BY version_id
+ON CONFLICT (version_id, date) DO NOTHING
+RETURNING id
@@ -1,9 +1,13 @@
 mod clean_processed_log_files;
+mod detect_anomalies;
 mod process_log;
+mod prune_breakdowns;
 mod queue;
 mod update_metadata;
 
 pub use clean_processed_log_files::CleanProcessedLogFiles;
+pub use detect_anomalies::DetectDownloadAnomalies;
 pub use process_log::ProcessCdnLog;
+pub use prune_breakdowns::PruneDownloadBreakdowns;
 pub use queue::ProcessCdnLogQueue;
 pub use update_metadata::UpdateDownloads;
@@ -209,7 +209,8 @@ impl From<(String, Version, NaiveDate, u64)> for NewDownload {
 }
 
 /// Saves the downloads from the given [`DownloadsMap`] to the database into
-/// the `version_downloads` table.
+/// the `version_downloads`, `cargo_version_downloads`,
+/// `crate_platform_downloads` and `version_download_sources` tables.
 ///
 /// This function **should be run inside a transaction** to ensure that the
 /// temporary `temp_downloads` table is dropped after the inserts are
@@ -222,6 +223,21 @@ pub async fn save_downloads(
     downloads: DownloadsMap,
     conn: &mut AsyncPgConnection,
 ) -> anyhow::Result<()> {
+    debug!("Saving per-client downloads to cargo_version_downloads table");
+    save_to_cargo_version_downloads(&downloads, conn)
+        .await
+        .context("Failed to save downloads to cargo_version_downloads table")?;
+
+    debug!("Saving per-platform downloads to crate_platform_downloads table");
+    save_to_crate_platform_downloads(&downloads, conn)
+        .await
+        .context("Failed to save downloads to crate_platform_downloads table")?;
+
+    debug!("Saving per-source downloads to version_download_sources table");
+    save_to_version_download_sources(&downloads, conn)
+        .await
+        .context("Failed to save downloads to version_download_sources table")?;
+
     debug!("Creating temp_downloads table");
     create_temp_downloads_table(conn)
         .await
@@ -336,6 +352,312 @@ async fn save_to_version_downloads(
         .load(conn).await
 }
 
+table! {
+    /// Diesel table definition for the temporary `temp_cargo_version_downloads`
+    /// table that is created by the [`save_to_cargo_version_downloads`]
+    /// function.
+    ///
+    /// The primary key does not actually exist, but specifying one is
+    /// required by Diesel.
+    temp_cargo_version_downloads (name, date, cargo_version) {
+        name -> Text,
+        date -> Date,
+        cargo_version -> Text,
+        downloads -> BigInt,
+    }
+}
+
+/// Helper struct for inserting downloads into the
+/// `temp_cargo_version_downloads` table.
+#[derive(Insertable)]
+#[diesel(table_name = temp_cargo_version_downloads)]
+struct NewCargoVersionDownload<'a> {
+    name: &'a str,
+    date: NaiveDate,
+    cargo_version: String,
+    downloads: i64,
+}
+
+/// Saves the per-client downloads from the given [`DownloadsMap`] to the
+/// `cargo_version_downloads` table.
+///
+/// Similar to [`save_downloads`], the downloads are first inserted into a
+/// temporary table, so that the crate names can be resolved to crate IDs in
+/// a single query. Downloads of unknown crates are silently ignored, since
+/// they are already reported by [`save_to_version_downloads`].
+#[instrument(
+    "db.query",
+    skip_all,
+    fields(message = "INSERT INTO cargo_version_downloads ...")
+)]
+async fn save_to_cargo_version_downloads(
+    downloads: &DownloadsMap,
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<()> {
+    // See `fill_temp_downloads_table()`.
+    const MAX_BATCH_SIZE: usize = 5_000;
+
+    diesel::sql_query(
+        r#"
+            CREATE TEMPORARY TABLE temp_cargo_version_downloads (
+                name VARCHAR NOT NULL,
+                date DATE NOT NULL,
+                cargo_version VARCHAR NOT NULL,
+                downloads INTEGER NOT NULL
+            ) ON COMMIT DROP;
+        "#,
+    )
+    .execute(conn)
+    .await?;
+
+    let map = downloads
+        .client_downloads()
+        .into_iter()
+        .map(|(name, date, client, downloads)| NewCargoVersionDownload {
+            name,
+            date,
+            cargo_version: client.to_string(),
+            downloads: downloads as i64,
+        })
+        .collect::<Vec<_>>();
+
+    for chunk in map.chunks(MAX_BATCH_SIZE) {
+        diesel::insert_into(temp_cargo_version_downloads::table)
+            .values(chunk)
+            .execute(conn)
+            .await?;
+    }
+
+    diesel::sql_query(
+        r#"
+            INSERT INTO cargo_version_downloads (crate_id, date, cargo_version, downloads)
+            SELECT crates.id, temp_cargo_version_downloads.date, temp_cargo_version_downloads.cargo_version, temp_cargo_version_downloads.downloads
+            FROM temp_cargo_version_downloads
+            INNER JOIN crates ON crates.name = temp_cargo_version_downloads.name
+            ORDER BY crates.id, temp_cargo_version_downloads.date, temp_cargo_version_downloads.cargo_version
+            ON CONFLICT (crate_id, date, cargo_version)
+            DO UPDATE SET downloads = cargo_version_downloads.downloads + EXCLUDED.downloads
+        "#,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+table! {
+    /// Diesel table definition for the temporary `temp_crate_platform_downloads`
+    /// table that is created by the [`save_to_crate_platform_downloads`]
+    /// function.
+    ///
+    /// The primary key does not actually exist, but specifying one is
+    /// required by Diesel.
+    temp_crate_platform_downloads (name, date, host_os, host_arch, cdn_region) {
+        name -> Text,
+        date -> Date,
+        host_os -> Text,
+        host_arch -> Text,
+        cdn_region -> Text,
+        downloads -> BigInt,
+    }
+}
+
+/// Helper struct for inserting downloads into the
+/// `temp_crate_platform_downloads` table.
+#[derive(Insertable)]
+#[diesel(table_name = temp_crate_platform_downloads)]
+struct NewCratePlatformDownload<'a> {
+    name: &'a str,
+    date: NaiveDate,
+    host_os: &'static str,
+    host_arch: &'static str,
+    cdn_region: &'static str,
+    downloads: i64,
+}
+
+/// Saves the per-platform downloads from the given [`DownloadsMap`] to the
+/// `crate_platform_downloads` table.
+///
+/// Similar to [`save_to_cargo_version_downloads`], the downloads are first
+/// inserted into a temporary table, so that the crate names can be resolved
+/// to crate IDs in a single query.
+#[instrument(
+    "db.query",
+    skip_all,
+    fields(message = "INSERT INTO crate_platform_downloads ...")
+)]
+async fn save_to_crate_platform_downloads(
+    downloads: &DownloadsMap,
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<()> {
+    // See `fill_temp_downloads_table()`.
+    const MAX_BATCH_SIZE: usize = 5_000;
+
+    diesel::sql_query(
+        r#"
+            CREATE TEMPORARY TABLE temp_crate_platform_downloads (
+                name VARCHAR NOT NULL,
+                date DATE NOT NULL,
+                host_os VARCHAR NOT NULL,
+                host_arch VARCHAR NOT NULL,
+                cdn_region VARCHAR NOT NULL,
+                downloads BIGINT NOT NULL
+            ) ON COMMIT DROP;
+        "#,
+    )
+    .execute(conn)
+    .await?;
+
+    let map = downloads
+        .platform_downloads()
+        .into_iter()
+        .map(
+            |(name, date, platform, downloads)| NewCratePlatformDownload {
+                name,
+                date,
+                host_os: platform.os.as_str(),
+                host_arch: platform.arch.as_str(),
+                cdn_region: platform.region.as_str(),
+                downloads: downloads as i64,
+            },
+        )
+        .collect::<Vec<_>>();
+
+    for chunk in map.chunks(MAX_BATCH_SIZE) {
+        diesel::insert_into(temp_crate_platform_downloads::table)
+            .values(chunk)
+            .execute(conn)
+            .await?;
+    }
+
+    diesel::sql_query(
+        r#"
+            INSERT INTO crate_platform_downloads (crate_id, date, host_os, host_arch, cdn_region, downloads)
+            SELECT crates.id, temp.date, temp.host_os, temp.host_arch, temp.cdn_region, temp.downloads
+            FROM temp_crate_platform_downloads temp
+            INNER JOIN crates ON crates.name = temp.name
+            ORDER BY crates.id, temp.date, temp.host_os, temp.host_arch, temp.cdn_region
+            ON CONFLICT (crate_id, date, host_os, host_arch, cdn_region)
+            DO UPDATE SET downloads = crate_platform_downloads.downloads + EXCLUDED.downloads
+        "#,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+table! {
+    /// Diesel table definition for the temporary `temp_version_download_sources`
+    /// table that is created by the [`save_to_version_download_sources`]
+    /// function.
+    ///
+    /// The primary key does not actually exist, but specifying one is
+    /// required by Diesel.
+    temp_version_download_sources (name, version, date, source_type, source) {
+        name -> Text,
+        version -> Text,
+        date -> Date,
+        source_type -> Text,
+        source -> Text,
+        downloads -> BigInt,
+    }
+}
+
+/// Helper struct for inserting downloads into the
+/// `temp_version_download_sources` table.
+#[derive(Insertable)]
+#[diesel(table_name = temp_version_download_sources)]
+struct NewVersionDownloadSource<'a> {
+    name: &'a str,
+    version: String,
+    date: NaiveDate,
+    source_type: &'static str,
+    source: &'a str,
+    downloads: i64,
+}
+
+/// Saves the per-source downloads from the given [`DownloadsMap`] to the
+/// `version_download_sources` table.
+///
+/// All sources are saved, since a single log file only covers a fraction of
+/// a day. Sources with too few downloads over the whole day are pruned by the
+/// `detect_download_anomalies` background job instead.
+///
+/// Similar to [`save_to_cargo_version_downloads`], the downloads are first
+/// inserted into a temporary table, so that the crate names and versions can
+/// be resolved to version IDs in a single query.
+#[instrument(
+    "db.query",
+    skip_all,
+    fields(message = "INSERT INTO version_download_sources ...")
+)]
+async fn save_to_version_download_sources(
+    downloads: &DownloadsMap,
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<()> {
+    // See `fill_temp_downloads_table()`.
+    const MAX_BATCH_SIZE: usize = 5_000;
+
+    let map = downloads
+        .source_downloads(1)
+        .into_iter()
+        .map(
+            |(name, version, date, source, downloads)| NewVersionDownloadSource {
+                name,
+                version: version.to_string(),
+                date,
+                source_type: source.kind(),
+                source: source.value(),
+                downloads: downloads as i64,
+            },
+        )
+        .collect::<Vec<_>>();
+
+    if map.is_empty() {
+        return Ok(());
+    }
+
+    diesel::sql_query(
+        r#"
+            CREATE TEMPORARY TABLE temp_version_download_sources (
+                name VARCHAR NOT NULL,
+                version VARCHAR NOT NULL,
+                date DATE NOT NULL,
+                source_type VARCHAR NOT NULL,
+                source VARCHAR NOT NULL,
+                downloads BIGINT NOT NULL
+            ) ON COMMIT DROP;
+        "#,
+    )
+    .execute(conn)
+    .await?;
+
+    for chunk in map.chunks(MAX_BATCH_SIZE) {
+        diesel::insert_into(temp_version_download_sources::table)
+            .values(chunk)
+            .execute(conn)
+            .await?;
+    }
+
+    diesel::sql_query(
+        r#"
+            INSERT INTO version_download_sources (version_id, date, source_type, source, downloads)
+            SELECT versions.id, temp.date, temp.source_type, temp.source, temp.downloads
+            FROM temp_version_download_sources temp
+            INNER JOIN crates ON crates.name = temp.name
+            INNER JOIN versions ON versions.crate_id = crates.id AND versions.num = temp.version
+            ORDER BY versions.id, temp.date, temp.source_type, temp.source
+            ON CONFLICT (version_id, date, source_type, source)
+            DO UPDATE SET downloads = version_download_sources.downloads + EXCLUDED.downloads
+        "#,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
 table! {
     /// Imaginary table to make Diesel happy when using the `sql_query` macro in
     /// the [`save_to_version_downloads()`] function.
@@ -413,7 +735,10 @@ async fn save_as_processed(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::schema::{crates, version_downloads, versions};
+    use crate::schema::{
+        cargo_version_downloads, crate_platform_downloads, crates, version_download_sources,
+        version_downloads, versions,
+    };
     use claims::assert_ok;
     use crates_io_test_db::TestDatabase;
     use diesel_async::pooled_connection::AsyncDieselConnectionManager;
@@ -444,11 +769,27 @@ mod tests {
             "tracing-core | 0.1.32 | 1 | 0 | 2024-01-16 | false",
         ]
         "#);
+        assert_debug_snapshot!(all_cargo_version_downloads(db_pool.clone()).await, @r#"
+        [
+            "bindgen | 2024-01-16 | 1.74 | 1",
+            "quick-error | 2024-01-16 | 1.74 | 2",
+            "quick-error | 2024-01-17 | 1.74 | 1",
+            "tracing-core | 2024-01-16 | 1.74 | 1",
+        ]
+        "#);
+        assert_debug_snapshot!(all_crate_platform_downloads(db_pool.clone()).await, @r#"
+        [
+            "bindgen | 2024-01-16 | unknown | unknown | north_america | 1",
+            "quick-error | 2024-01-16 | unknown | unknown | north_america | 2",
+            "quick-error | 2024-01-17 | unknown | unknown | north_america | 1",
+            "tracing-core | 2024-01-16 | unknown | unknown | north_america | 1",
+        ]
+        "#);
 
         // Check that processing the same log file again does not insert
         // duplicate data.
         assert_ok!(run(store, CLOUDFRONT_PATH, db_pool.clone()).await);
-        assert_debug_snapshot!(all_version_downloads(db_pool).await, @r#"
+        assert_debug_snapshot!(all_version_downloads(db_pool.clone()).await, @r#"
         [
             "bindgen | 0.65.1 | 1 | 0 | 2024-01-16 | false",
             "quick-error | 1.2.3 | 2 | 0 | 2024-01-16 | false",
@@ -456,6 +797,89 @@ mod tests {
             "tracing-core | 0.1.32 | 1 | 0 | 2024-01-16 | false",
         ]
         "#);
+        assert_debug_snapshot!(all_cargo_version_downloads(db_pool.clone()).await, @r#"
+        [
+            "bindgen | 2024-01-16 | 1.74 | 1",
+            "quick-error | 2024-01-16 | 1.74 | 2",
+            "quick-error | 2024-01-17 | 1.74 | 1",
+            "tracing-core | 2024-01-16 | 1.74 | 1",
+        ]
+        "#);
+        assert_debug_snapshot!(all_crate_platform_downloads(db_pool).await, @r#"
+        [
+            "bindgen | 2024-01-16 | unknown | unknown | north_america | 1",
+            "quick-error | 2024-01-16 | unknown | unknown | north_america | 2",
+            "quick-error | 2024-01-17 | unknown | unknown | north_america | 1",
+            "tracing-core | 2024-01-16 | unknown | unknown | north_america | 1",
+        ]
+        "#);
+    }
+
+    #[tokio::test]
+    async fn test_save_to_version_download_sources() {
+        crate::util::tracing::init_for_test();
+
+        let test_database = TestDatabase::new();
+        let db_pool = build_connection_pool(test_database.url());
+        create_dummy_crates_and_versions(db_pool.clone()).await;
+
+        let store = build_dummy_store().await;
+        let downloads = assert_ok!(load_and_count(&CLOUDFRONT_PATH.into(), store).await);
+
+        // The temporary table is dropped at the end of each transaction.
+        let mut conn = db_pool.get().await.unwrap();
+        for _ in 0..2 {
+            let downloads = &downloads;
+            assert_ok!(
+                conn.transaction(|conn| {
+                    async move { save_to_version_download_sources(downloads, conn).await }
+                        .scope_boxed()
+                })
+                .await
+            );
+        }
+
+        let sources: Vec<(String, String, NaiveDate, String, String, i64)> =
+            version_download_sources::table
+                .inner_join(versions::table)
+                .inner_join(crates::table.on(versions::crate_id.eq(crates::id)))
+                .select((
+                    crates::name,
+                    versions::num,
+                    version_download_sources::date,
+                    version_download_sources::source_type,
+                    version_download_sources::source,
+                    version_download_sources::downloads,
+                ))
+                .order((
+                    crates::name,
+                    version_download_sources::date,
+                    version_download_sources::source_type,
+                ))
+                .load(&mut conn)
+                .await
+                .unwrap();
+
+        let sources = sources
+            .into_iter()
+            .map(|(name, version, date, source_type, source, downloads)| {
+                format!("{name} | {version} | {date} | {source_type} | {source} | {downloads}")
+            })
+            .collect::<Vec<_>>();
+
+        // The downloads of the same source on the same day are added up.
+        assert_debug_snapshot!(sources, @r#"
+        [
+            "bindgen | 0.65.1 | 2024-01-16 | ip_prefix | 1.2.3.0/24 | 2",
+            "bindgen | 0.65.1 | 2024-01-16 | user_agent | cargo 1.74.0 (ecb9851af 2023-10-18) | 2",
+            "quick-error | 1.2.3 | 2024-01-16 | ip_prefix | 1.2.3.0/24 | 4",
+            "quick-error | 1.2.3 | 2024-01-16 | user_agent | cargo 1.74.0 (ecb9851af 2023-10-18) | 4",
+            "quick-error | 1.2.3 | 2024-01-17 | ip_prefix | 3.4.5.0/24 | 2",
+            "quick-error | 1.2.3 | 2024-01-17 | user_agent | cargo 1.74.0 (ecb9851af 2023-10-18) | 2",
+            "tracing-core | 0.1.32 | 2024-01-16 | ip_prefix | 1.2.3.0/24 | 2",
+            "tracing-core | 0.1.32 | 2024-01-16 | user_agent | cargo 1.74.0 (ecb9851af 2023-10-18) | 2",
+        ]
+        "#);
     }
 
     #[test]
@@ -566,4 +990,67 @@ mod tests {
             .await
             .unwrap()
     }
+
+    /// Queries all cargo version downloads from the database and returns them
+    /// as a [`Vec`] of strings for use with [`assert_debug_snapshot!()`].
+    async fn all_crate_platform_downloads(db_pool: Pool<AsyncPgConnection>) -> Vec<String> {
+        let mut conn = db_pool.get().await.unwrap();
+
+        let downloads: Vec<(String, NaiveDate, String, String, String, i64)> =
+            crate_platform_downloads::table
+                .inner_join(crates::table)
+                .select((
+                    crates::name,
+                    crate_platform_downloads::date,
+                    crate_platform_downloads::host_os,
+                    crate_platform_downloads::host_arch,
+                    crate_platform_downloads::cdn_region,
+                    crate_platform_downloads::downloads,
+                ))
+                .order((
+                    crates::name,
+                    crate_platform_downloads::date,
+                    crate_platform_downloads::host_os,
+                    crate_platform_downloads::host_arch,
+                    crate_platform_downloads::cdn_region,
+                ))
+                .load(&mut conn)
+                .await
+                .unwrap();
+
+        downloads
+            .into_iter()
+            .map(|(name, date, host_os, host_arch, cdn_region, downloads)| {
+                format!("{name} | {date} | {host_os} | {host_arch} | {cdn_region} | {downloads}")
+            })
+            .collect()
+    }
+
+    async fn all_cargo_version_downloads(db_pool: Pool<AsyncPgConnection>) -> Vec<String> {
+        let mut conn = db_pool.get().await.unwrap();
+
+        let downloads: Vec<(String, NaiveDate, String, i64)> = cargo_version_downloads::table
+            .inner_join(crates::table)
+            .select((
+                crates::name,
+                cargo_version_downloads::date,
+                cargo_version_downloads::cargo_version,
+                cargo_version_downloads::downloads,
+            ))
+            .order((
+                crates::name,
+                cargo_version_downloads::date,
+                cargo_version_downloads::cargo_version,
+            ))
+            .load(&mut conn)
+            .await
+            .unwrap();
+
+        downloads
+            .into_iter()
+            .map(|(name, date, cargo_version, downloads)| {
+                format!("{name} | {date} | {cargo_version} | {downloads}")
+            })
+            .collect()
+    }
 }
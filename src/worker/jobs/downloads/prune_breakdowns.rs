@@ -0,0 +1,154 @@
+use crate::schema::{cargo_version_downloads, crate_platform_downloads};
+use crate::worker::Environment;
+use chrono::{NaiveDate, TimeDelta, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+/// This job is responsible for deleting the download breakdowns in the
+/// `cargo_version_downloads` and `crate_platform_downloads` tables that are
+/// older than [`PruneDownloadBreakdowns::RETENTION`], since the API only
+/// exposes the last 90 days anyway.
+///
+/// The job is meant to be enqueued once a day via the
+/// `crates-admin enqueue-job prune_download_breakdowns` command.
+#[derive(Serialize, Deserialize)]
+pub struct PruneDownloadBreakdowns;
+
+impl PruneDownloadBreakdowns {
+    /// The duration for which the download breakdowns are kept.
+    pub const RETENTION: TimeDelta = TimeDelta::days(90);
+}
+
+impl BackgroundJob for PruneDownloadBreakdowns {
+    const JOB_NAME: &'static str = "prune_download_breakdowns";
+    const DEDUPLICATED: bool = true;
+    const QUEUE: &'static str = "downloads";
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+        Ok(run(&mut conn).await?)
+    }
+}
+
+async fn run(conn: &mut AsyncPgConnection) -> QueryResult<()> {
+    let cut_off_date = cut_off_date();
+
+    let deleted = diesel::delete(cargo_version_downloads::table)
+        .filter(cargo_version_downloads::date.lt(cut_off_date))
+        .execute(conn)
+        .await?;
+
+    info!("Deleted {deleted} cargo version downloads from before {cut_off_date}");
+
+    let deleted = diesel::delete(crate_platform_downloads::table)
+        .filter(crate_platform_downloads::date.lt(cut_off_date))
+        .execute(conn)
+        .await?;
+
+    info!("Deleted {deleted} crate platform downloads from before {cut_off_date}");
+
+    Ok(())
+}
+
+fn cut_off_date() -> NaiveDate {
+    (Utc::now() - PruneDownloadBreakdowns::RETENTION).date_naive()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::crates;
+    use chrono::Days;
+    use crates_io_test_db::TestDatabase;
+    use insta::assert_debug_snapshot;
+
+    #[tokio::test]
+    async fn test_prune() {
+        let test_db = TestDatabase::new();
+        let mut conn = test_db.async_connect().await;
+
+        let crate_id: i32 = diesel::insert_into(crates::table)
+            .values(crates::name.eq("foo"))
+            .returning(crates::id)
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+
+        let cut_off_date = cut_off_date();
+        let dates = [
+            ("1.70", cut_off_date - Days::new(30)),
+            ("1.71", cut_off_date - Days::new(1)),
+            ("1.72", cut_off_date),
+            ("1.73", Utc::now().date_naive()),
+        ];
+
+        let inserts = dates
+            .iter()
+            .map(|(cargo_version, date)| {
+                (
+                    cargo_version_downloads::crate_id.eq(crate_id),
+                    cargo_version_downloads::date.eq(date),
+                    cargo_version_downloads::cargo_version.eq(cargo_version),
+                    cargo_version_downloads::downloads.eq(1),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(cargo_version_downloads::table)
+            .values(&inserts)
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let inserts = dates
+            .iter()
+            .map(|(_, date)| {
+                (
+                    crate_platform_downloads::crate_id.eq(crate_id),
+                    crate_platform_downloads::date.eq(date),
+                    crate_platform_downloads::host_os.eq("linux"),
+                    crate_platform_downloads::host_arch.eq("x86_64"),
+                    crate_platform_downloads::cdn_region.eq("europe"),
+                    crate_platform_downloads::downloads.eq(1),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(crate_platform_downloads::table)
+            .values(&inserts)
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        run(&mut conn).await.unwrap();
+
+        let remaining: Vec<String> = cargo_version_downloads::table
+            .select(cargo_version_downloads::cargo_version)
+            .order(cargo_version_downloads::cargo_version)
+            .load(&mut conn)
+            .await
+            .unwrap();
+
+        assert_debug_snapshot!(remaining, @r#"
+        [
+            "1.72",
+            "1.73",
+        ]
+        "#);
+
+        let remaining: Vec<NaiveDate> = crate_platform_downloads::table
+            .select(crate_platform_downloads::date)
+            .order(crate_platform_downloads::date)
+            .load(&mut conn)
+            .await
+            .unwrap();
+
+        assert_eq!(remaining, vec![dates[2].1, dates[3].1]);
+    }
+}
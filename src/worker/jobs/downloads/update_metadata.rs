@@ -107,8 +107,9 @@ async fn batch_update(batch_size: i64, conn: &mut AsyncPgConnection) -> QueryRes
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{Crate, NewCrate, NewUser, NewVersion, User, Version};
-    use crate::schema::{crate_downloads, crates, versions};
+    use crate::models::{Crate, DownloadAnomaly, NewCrate, NewUser, NewVersion, User, Version};
+    use crate::schema::{crate_downloads, crates, download_anomalies, metadata, versions};
+    use chrono::{TimeDelta, Utc};
     use crates_io_test_db::TestDatabase;
     use diesel::sql_types::Timestamptz;
     use diesel_async::AsyncConnection;
@@ -386,4 +387,78 @@ mod tests {
         assert_eq!(versions_changed, Ok(false));
         assert_eq!(crates_changed, Ok(false));
     }
+
+    #[tokio::test]
+    async fn decrement_after_exclusion() {
+        use diesel::dsl::*;
+
+        let test_db = TestDatabase::new();
+        let mut conn = test_db.async_connect().await;
+
+        let user = user(&mut conn).await;
+        let (krate, version) = crate_and_version(&mut conn, user.id).await;
+        let yesterday = (Utc::now() - TimeDelta::days(1)).date_naive();
+        insert_into(version_downloads::table)
+            .values((
+                version_downloads::version_id.eq(version.id),
+                version_downloads::downloads.eq(1000),
+                version_downloads::date.eq(yesterday),
+            ))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let total_before: i64 = metadata::table
+            .select(metadata::total_downloads)
+            .first(&mut conn)
+            .await
+            .unwrap();
+
+        super::update(&mut conn).await.unwrap();
+
+        let anomaly_id: i64 = insert_into(download_anomalies::table)
+            .values((
+                download_anomalies::version_id.eq(version.id),
+                download_anomalies::date.eq(yesterday),
+                download_anomalies::reason.eq("spike"),
+                download_anomalies::downloads.eq(1000),
+                download_anomalies::expected_downloads.eq(100),
+            ))
+            .returning(download_anomalies::id)
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+
+        let anomaly = DownloadAnomaly::find(&mut conn, anomaly_id).await.unwrap();
+        assert!(anomaly.exclude(&mut conn).await.unwrap());
+
+        super::update(&mut conn).await.unwrap();
+
+        let version_downloads = versions::table
+            .find(version.id)
+            .select(versions::downloads)
+            .first(&mut conn)
+            .await;
+        assert_eq!(version_downloads, Ok(100));
+
+        let crate_downloads = crate_downloads::table
+            .find(krate.id)
+            .select(crate_downloads::downloads)
+            .first(&mut conn)
+            .await;
+        assert_eq!(crate_downloads, Ok(100));
+
+        let total_downloads = metadata::table
+            .select(metadata::total_downloads)
+            .first(&mut conn)
+            .await;
+        assert_eq!(total_downloads, Ok(total_before + 100));
+
+        let processed = version_downloads::table
+            .find((version.id, yesterday))
+            .select(version_downloads::processed)
+            .first(&mut conn)
+            .await;
+        assert_eq!(processed, Ok(true));
+    }
 }
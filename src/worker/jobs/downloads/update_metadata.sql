@@ -38,7 +38,7 @@ WITH downloads_batch AS (
         SELECT COALESCE(SUM(downloads), 0) as downloads
         FROM downloads_batch
     ) sum
-    WHERE sum.downloads > 0
+    WHERE sum.downloads != 0
 ), sorted_downloads_batch AS (
     -- Sort the `downloads_batch` CTE by `version_id` and `date` to
     -- ensure that the `version_downloads` table is updated in a
@@ -0,0 +1,78 @@
+use crate::index::IndexChangesPage;
+use crate::models::IndexChange;
+use crate::schema::index_changes;
+use crate::worker::Environment;
+use anyhow::Context;
+use chrono::Utc;
+use crates_io_worker::BackgroundJob;
+use diesel::dsl::{max, min};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{info, instrument};
+
+/// Assigns sequence numbers to the recorded index changes and publishes the
+/// pages of the sparse index changes feed that contain unpublished changes to
+/// the index storage.
+#[derive(Default, Serialize, Deserialize)]
+pub struct PublishIndexChanges;
+
+impl BackgroundJob for PublishIndexChanges {
+    const JOB_NAME: &'static str = "publish_index_changes";
+    const PRIORITY: i16 = 50;
+    const DEDUPLICATED: bool = true;
+    /// Uses a separate queue with a single worker to ensure that an older
+    /// version of a page never overwrites a newer one.
+    const QUEUE: &'static str = "index_changes";
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        let assigned = IndexChange::assign_sequence_numbers(&mut conn).await?;
+        info!("Assigned sequence numbers to {assigned} index changes");
+
+        let first_unpublished: Option<i64> = index_changes::table
+            .filter(index_changes::published_at.is_null())
+            .select(min(index_changes::seq))
+            .get_result(&mut conn)
+            .await?;
+
+        let Some(first_unpublished) = first_unpublished else {
+            info!("No unpublished index changes found");
+            return Ok(());
+        };
+
+        let last_seq: Option<i64> = index_changes::table
+            .select(max(index_changes::seq))
+            .get_result(&mut conn)
+            .await?;
+
+        let last_seq = last_seq.unwrap_or(first_unpublished);
+
+        let first_page = IndexChangesPage::number_for(first_unpublished);
+        let last_page = IndexChangesPage::number_for(last_seq);
+        for page in first_page..=last_page {
+            let content = IndexChangesPage::load(&mut conn, page).await?;
+            let content = serde_json::to_string(&content)?;
+
+            let path = IndexChangesPage::path(page);
+            info!(%path, "Uploading index changes page");
+
+            let future = env.storage.upload_index_json(&path, content);
+            future.await.context("Failed to upload index changes")?;
+        }
+
+        diesel::update(index_changes::table)
+            .filter(index_changes::published_at.is_null())
+            .filter(index_changes::seq.le(last_seq))
+            .set(index_changes::published_at.eq(Utc::now()))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+}
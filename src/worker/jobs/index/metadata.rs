@@ -0,0 +1,182 @@
+use crate::index::{tuf_bin_metadata_path, tuf_metadata_path};
+use crate::worker::Environment;
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use crates_io_database::models::IndexTargetBin;
+use crates_io_index::tuf::{
+    Delegations, MetaFile, Metadata, RoleType, Signed, SigningKey, Snapshot, TargetFile, Targets,
+    Timestamp, bin_role_name,
+};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing::{info, instrument, warn};
+
+/// Regenerates and signs the `targets`, `snapshot` and `timestamp` TUF
+/// metadata of the sparse index, and uploads them to the index storage.
+///
+/// Only the hash bins of the `targets` metadata whose index files have
+/// changed since they were last signed are regenerated, so publishing a
+/// crate only requires re-signing a single bin.
+///
+/// This job is enqueued whenever an index file changes, but it also needs to
+/// run periodically to refresh the expiry date of the `timestamp` metadata.
+#[derive(Default, Serialize, Deserialize)]
+pub struct SignIndexMetadata;
+
+impl BackgroundJob for SignIndexMetadata {
+    const JOB_NAME: &'static str = "sign_index_metadata";
+    const PRIORITY: i16 = 50;
+    const DEDUPLICATED: bool = true;
+    /// Uses a separate queue with a single worker to ensure that the version
+    /// numbers of the metadata are strictly increasing.
+    const QUEUE: &'static str = "index_metadata";
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let Some(config) = &env.config.index_signing else {
+            warn!("Index signing is not configured, skipping");
+            return Ok(());
+        };
+
+        // The `snapshot` and `timestamp` metadata share the same version
+        // number, which is incremented whenever they are regenerated.
+        let timestamp_path = tuf_metadata_path(RoleType::Timestamp);
+        let previous = env.storage.download_index_file(&timestamp_path).await?;
+        let version = match previous {
+            Some(bytes) => {
+                let timestamp = Signed::<Timestamp>::from_slice(&bytes)
+                    .context("Failed to parse previous timestamp metadata")?;
+                timestamp.signed.version + 1
+            }
+            None => 1,
+        };
+
+        info!(version, "Signing index metadata");
+
+        let key = &config.online_key;
+        let now = Utc::now();
+        let targets_expires = now + config.targets_expiry;
+        let timestamp_expires = now + config.timestamp_expiry;
+
+        // The `targets` metadata and the bins are re-signed once half of
+        // their validity period has passed, even if they haven't changed.
+        let refresh_interval = config.targets_expiry / 2;
+
+        let mut conn = env.deadpool.get().await?;
+        let bins = IndexTargetBin::needs_signing(&mut conn, now - refresh_interval).await?;
+        for bin in bins {
+            let bin_version = bin.version + 1;
+
+            let targets = bin.targets(&mut conn).await?;
+            let targets = targets
+                .into_iter()
+                .map(|target| {
+                    let file = TargetFile::new(target.length as u64, &target.sha256);
+                    (target.path, file)
+                })
+                .collect();
+
+            let targets = Targets::new(bin_version as u64, targets_expires, targets);
+            let content = sign(targets, key)?;
+
+            let path = tuf_bin_metadata_path(u8::try_from(bin.bin)?);
+            info!(%path, version = bin_version, "Uploading index metadata");
+
+            let future = env.storage.upload_index_json(&path, content);
+            future.await.context("Failed to upload index metadata")?;
+
+            bin.mark_signed(&mut conn, bin_version).await?;
+        }
+
+        let refresh_before = now + refresh_interval;
+        let future = sign_targets(&env, key, targets_expires, refresh_before);
+        let targets_version = future.await?;
+
+        let mut meta = BTreeMap::from([(
+            RoleType::Targets.file_name().to_string(),
+            MetaFile::new(targets_version),
+        )]);
+
+        let bins: Vec<IndexTargetBin> = IndexTargetBin::query().load(&mut conn).await?;
+        for bin in bins {
+            let file_name = format!("{}.json", bin_role_name(u8::try_from(bin.bin)?));
+            meta.insert(file_name, MetaFile::new(bin.version as u64));
+        }
+
+        let snapshot = Snapshot::new(version, targets_expires, meta);
+        let snapshot = sign(snapshot, key)?;
+
+        let snapshot_meta = MetaFile::with_content(version, snapshot.as_bytes());
+        let timestamp = Timestamp::new(version, timestamp_expires, snapshot_meta);
+        let timestamp = sign(timestamp, key)?;
+
+        // The files are uploaded in this order so that clients never see a
+        // `timestamp` that references a `snapshot` that does not exist yet.
+        let files = [
+            (RoleType::Snapshot, snapshot),
+            (RoleType::Timestamp, timestamp),
+        ];
+
+        for (role, content) in files {
+            let path = tuf_metadata_path(role);
+            info!(%path, "Uploading index metadata");
+
+            let future = env.storage.upload_index_json(&path, content);
+            future.await.context("Failed to upload index metadata")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Signs and uploads the top-level `targets` metadata, which delegates all
+/// index files to the hash bins, and returns its version.
+///
+/// The metadata only changes when the online key is rotated, so it is only
+/// re-signed with the given expiry date if the delegations have changed or
+/// if the previous metadata expires before `refresh_before`.
+async fn sign_targets(
+    env: &Environment,
+    key: &SigningKey,
+    expires: DateTime<Utc>,
+    refresh_before: DateTime<Utc>,
+) -> anyhow::Result<u64> {
+    let delegations = Delegations::hash_bins(key.public_key())?;
+
+    let path = tuf_metadata_path(RoleType::Targets);
+    let previous = env.storage.download_index_file(&path).await?;
+    let previous = previous
+        .map(|bytes| Signed::<Targets>::from_slice(&bytes))
+        .transpose()
+        .context("Failed to parse previous targets metadata")?
+        .map(|targets| targets.signed);
+
+    if let Some(previous) = &previous
+        && previous.delegations.as_ref() == Some(&delegations)
+        && previous.expires > refresh_before
+    {
+        return Ok(previous.version);
+    }
+
+    let version = previous.map_or(1, |previous| previous.version + 1);
+    let targets = Targets::with_delegations(version, expires, delegations);
+    let content = sign(targets, key)?;
+
+    info!(%path, version, "Uploading index metadata");
+    let future = env.storage.upload_index_json(&path, content);
+    future.await.context("Failed to upload index metadata")?;
+
+    Ok(version)
+}
+
+fn sign<T: Metadata>(metadata: T, key: &SigningKey) -> anyhow::Result<String> {
+    let mut signed = Signed::new(metadata);
+    signed.sign(key)?;
+    signed.to_json()
+}
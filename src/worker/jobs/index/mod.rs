@@ -1,10 +1,18 @@
 //! This module contains all background jobs related to the git and
 //! sparse indexes.
 
+mod changes;
+mod metadata;
 mod normalize;
 mod squash;
 mod sync;
+mod targets;
+mod verify;
 
+pub use changes::PublishIndexChanges;
+pub use metadata::SignIndexMetadata;
 pub use normalize::NormalizeIndex;
 pub use squash::SquashIndex;
-pub use sync::{BulkSyncToGitIndex, SyncToGitIndex, SyncToSparseIndex};
+pub use sync::{BulkSyncToGitIndex, SyncToGitIndex, SyncToSparseIndex, enqueue_sync_to_index};
+pub use targets::BackfillIndexTargets;
+pub use verify::VerifyIndex;
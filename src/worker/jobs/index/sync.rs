@@ -1,12 +1,20 @@
 use crate::index::get_index_data;
 use crate::tasks::spawn_blocking;
 use crate::worker::Environment;
-use crate::worker::jobs::ProcessCloudfrontInvalidationQueue;
+use crate::worker::jobs::{
+    ProcessCloudfrontInvalidationQueue, PublishIndexChanges, SignIndexMetadata,
+};
 use anyhow::Context;
-use crates_io_database::models::{CloudFrontDistribution, CloudFrontInvalidationQueueItem};
+use chrono::Utc;
+use crates_io_database::models::{
+    CloudFrontDistribution, CloudFrontInvalidationQueueItem, IndexChangeReason, IndexTarget,
+    NewIndexChange, NewIndexTarget,
+};
 use crates_io_index::Repository;
-use crates_io_worker::BackgroundJob;
+use crates_io_worker::{BackgroundJob, EnqueueError};
+use diesel_async::AsyncPgConnection;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::fs;
 use std::fs::File;
 use std::io::{ErrorKind, Write};
@@ -16,6 +24,24 @@ use std::time::Instant;
 use tokio::runtime::Handle;
 use tracing::{debug, info, instrument, warn};
 
+/// Enqueues the jobs that regenerate the index files of the given crate from
+/// the database.
+///
+/// The git index is skipped if it is disabled in the configuration.
+pub async fn enqueue_sync_to_index(
+    krate: &str,
+    git_index_enabled: bool,
+    conn: &mut AsyncPgConnection,
+) -> Result<(), EnqueueError> {
+    if git_index_enabled {
+        SyncToGitIndex::new(krate).enqueue(conn).await?;
+    }
+
+    SyncToSparseIndex::new(krate).enqueue(conn).await?;
+
+    Ok(())
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct SyncToGitIndex {
     krate: String,
@@ -208,10 +234,48 @@ impl BackgroundJob for SyncToSparseIndex {
             .await
             .context("Failed to get index data")?;
 
+        let reason = match content {
+            Some(_) => IndexChangeReason::Update,
+            None => IndexChangeReason::Delete,
+        };
+
+        let path = Repository::relative_index_file_for_url(&self.krate);
+        let target = content.as_ref().map(|content| {
+            let sha256 = Sha256::digest(content.as_bytes());
+            (content.len() as i64, sha256)
+        });
+
         let future = env.storage.sync_index(&self.krate, content);
         future.await.context("Failed to sync index data")?;
 
-        let path = Repository::relative_index_file_for_url(&self.krate);
+        let result = match &target {
+            Some((length, sha256)) => {
+                let target = NewIndexTarget {
+                    path: &path,
+                    length: *length,
+                    sha256: sha256.as_slice(),
+                    updated_at: Utc::now(),
+                };
+                target.create_or_update(&mut conn).await
+            }
+            None => IndexTarget::delete(&mut conn, &path).await,
+        };
+        result.context("Failed to record index target")?;
+
+        let change = NewIndexChange {
+            crate_name: &self.krate,
+            reason,
+        };
+        let result = change.insert(&mut conn).await;
+        result.context("Failed to record index change")?;
+
+        let result = PublishIndexChanges.enqueue(&mut conn).await;
+        result.context("Failed to enqueue index changes publishing job")?;
+
+        if env.config.index_signing.is_some() {
+            let result = SignIndexMetadata.enqueue(&mut conn).await;
+            result.context("Failed to enqueue index metadata signing job")?;
+        }
 
         if let Some(fastly) = env.fastly()
             && env.config.sparse_index_fastly_enabled
@@ -0,0 +1,74 @@
+use crate::worker::Environment;
+use crate::worker::jobs::SignIndexMetadata;
+use anyhow::Context;
+use chrono::Utc;
+use crates_io_database::models::NewIndexTarget;
+use crates_io_worker::BackgroundJob;
+use futures_util::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tracing::{info, instrument, warn};
+
+/// Records the length and hash of all sparse index files in the index
+/// storage that don't have an entry in the `index_targets` table yet.
+///
+/// Targets are only recorded when an index file is uploaded, so this job
+/// needs to run once after index signing is enabled, to include the index
+/// files of crates that haven't been published since.
+#[derive(Default, Serialize, Deserialize)]
+pub struct BackfillIndexTargets;
+
+impl BackgroundJob for BackfillIndexTargets {
+    const JOB_NAME: &'static str = "backfill_index_targets";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        // Download at most 10 index files concurrently.
+        const MAX_CONCURRENCY: usize = 10;
+
+        let paths = env.storage.list_index_files().await?;
+        info!("Recording targets for {} index files", paths.len());
+
+        let mut files = futures_util::stream::iter(paths)
+            .map(async |path| {
+                let content = env.storage.download_index_file(&path).await?;
+                Ok::<_, anyhow::Error>((path, content))
+            })
+            .buffer_unordered(MAX_CONCURRENCY);
+
+        let mut conn = env.deadpool.get().await?;
+        let mut num_recorded = 0;
+        while let Some((path, content)) = files.try_next().await? {
+            // The file might have been deleted since the files were listed.
+            let Some(content) = content else {
+                warn!(%path, "Skipping deleted index file");
+                continue;
+            };
+
+            let target = NewIndexTarget {
+                path: &path,
+                length: content.len() as i64,
+                sha256: &Sha256::digest(&content),
+                updated_at: Utc::now(),
+            };
+
+            let result = target.create_if_missing(&mut conn).await;
+            if result.context("Failed to record index target")? {
+                num_recorded += 1;
+            }
+        }
+
+        info!("Recorded {num_recorded} missing index targets");
+
+        if env.config.index_signing.is_some() {
+            let result = SignIndexMetadata.enqueue(&mut conn).await;
+            result.context("Failed to enqueue index metadata signing job")?;
+        }
+
+        Ok(())
+    }
+}
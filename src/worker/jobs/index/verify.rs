@@ -0,0 +1,74 @@
+use crate::index::{IndexFiles, verify_index};
+use crate::tasks::spawn_blocking;
+use crate::worker::Environment;
+use anyhow::Context;
+use crates_io_database::models::NewIndexVerification;
+use crates_io_worker::BackgroundJob;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{info, instrument, warn};
+
+/// Compares the git and sparse index files of all crates with the content
+/// generated from the database, and stores the result in the
+/// `index_verifications` table, where it is picked up by the `monitor`.
+#[derive(Serialize, Deserialize)]
+pub struct VerifyIndex {
+    repair: bool,
+}
+
+impl VerifyIndex {
+    /// If `repair` is `true`, sync jobs are enqueued for all mismatched index
+    /// files.
+    pub fn new(repair: bool) -> Self {
+        Self { repair }
+    }
+}
+
+impl BackgroundJob for VerifyIndex {
+    const JOB_NAME: &'static str = "verify_index";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(repair = self.repair))]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        info!("Verifying the index");
+
+        // Reading all files takes a while, so we use a separate clone of the
+        // index instead of blocking the `repository` queue.
+        let repo = if env.config.git_index_enabled {
+            let env = env.clone();
+            Some(spawn_blocking(move || env.clone_index()).await??)
+        } else {
+            None
+        };
+
+        let mut backends = Vec::new();
+        if let Some(repo) = &repo {
+            backends.push(IndexFiles::Git(repo.checkout_path()));
+        }
+        backends.push(IndexFiles::Sparse(&env.storage));
+
+        let mut conn = env.deadpool.get().await?;
+        let include_pubtime = env.config.index_include_pubtime;
+        let report = verify_index(&mut conn, None, include_pubtime, &backends).await?;
+
+        let verification = NewIndexVerification {
+            crates_checked: report.crates_checked.try_into()?,
+            mismatches: serde_json::to_value(&report.mismatches)?,
+        };
+        let result = verification.insert(&mut conn).await;
+        result.context("Failed to store index verification result")?;
+
+        if !report.mismatches.is_empty() {
+            warn!("Found {} mismatched index files", report.mismatches.len());
+
+            if self.repair {
+                let num_jobs = report.enqueue_repairs(&mut conn).await?;
+                info!("Enqueued {num_jobs} index sync jobs");
+            }
+        }
+
+        Ok(())
+    }
+}
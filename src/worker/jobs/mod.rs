@@ -1,7 +1,9 @@
 mod analyze_crate_file;
 mod archive_version_downloads;
+mod backfill_download_rollups;
 mod daily_db_maintenance;
 mod delete_crate;
+mod delete_staged_versions;
 mod docs_rs_queue_rebuild;
 mod downloads;
 pub mod dump_db;
@@ -11,33 +13,50 @@ mod index;
 mod index_version_downloads_archive;
 mod invalidate_cdns;
 mod process_cloudfront_invalidation_queue;
+mod prune_api_token_usages;
 mod readmes;
+mod roll_up_version_downloads;
 pub mod rss;
 mod send_publish_notifications;
 mod sync_admins;
+#[cfg(test)]
+mod test_util;
 pub mod trustpub;
 mod typosquat;
 mod update_default_version;
+mod update_search_scores;
+mod verify_team_tokens;
+pub mod webhooks;
 
 pub use self::analyze_crate_file::AnalyzeCrateFile;
 pub use self::archive_version_downloads::ArchiveVersionDownloads;
+pub use self::backfill_download_rollups::BackfillDownloadRollups;
 pub use self::daily_db_maintenance::DailyDbMaintenance;
 pub use self::delete_crate::DeleteCrateFromStorage;
+pub use self::delete_staged_versions::DeleteExpiredStagedVersions;
 pub use self::docs_rs_queue_rebuild::DocsRsQueueRebuild;
 pub use self::downloads::{
-    CleanProcessedLogFiles, ProcessCdnLog, ProcessCdnLogQueue, UpdateDownloads,
+    CleanProcessedLogFiles, DetectDownloadAnomalies, ProcessCdnLog, ProcessCdnLogQueue,
+    PruneDownloadBreakdowns, UpdateDownloads,
 };
 pub use self::dump_db::DumpDb;
 pub use self::expiry_notification::SendTokenExpiryNotifications;
 pub use self::generate_og_image::GenerateOgImage;
 pub use self::index::{
-    BulkSyncToGitIndex, NormalizeIndex, SquashIndex, SyncToGitIndex, SyncToSparseIndex,
+    BackfillIndexTargets, BulkSyncToGitIndex, NormalizeIndex, PublishIndexChanges,
+    SignIndexMetadata, SquashIndex, SyncToGitIndex, SyncToSparseIndex, VerifyIndex,
+    enqueue_sync_to_index,
 };
 pub use self::index_version_downloads_archive::IndexVersionDownloadsArchive;
 pub use self::invalidate_cdns::InvalidateCdns;
 pub use self::process_cloudfront_invalidation_queue::ProcessCloudfrontInvalidationQueue;
+pub use self::prune_api_token_usages::PruneApiTokenUsages;
 pub use self::readmes::RenderAndUploadReadme;
+pub use self::roll_up_version_downloads::RollUpVersionDownloads;
 pub use self::send_publish_notifications::SendPublishNotificationsJob;
 pub use self::sync_admins::SyncAdmins;
 pub use self::typosquat::CheckTyposquat;
 pub use self::update_default_version::UpdateDefaultVersion;
+pub use self::update_search_scores::UpdateSearchScores;
+pub use self::verify_team_tokens::VerifyTeamTokens;
+pub use self::webhooks::DeliverWebhook;
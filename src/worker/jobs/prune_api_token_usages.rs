@@ -0,0 +1,40 @@
+use crate::schema::api_token_usages;
+use crate::worker::Environment;
+use chrono::{TimeDelta, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+/// A background job that deletes API token usage log entries that are older
+/// than [`PruneApiTokenUsages::RETENTION`].
+#[derive(Deserialize, Serialize)]
+pub struct PruneApiTokenUsages;
+
+impl PruneApiTokenUsages {
+    /// The duration for which API token usages are kept.
+    pub const RETENTION: TimeDelta = TimeDelta::days(90);
+}
+
+impl BackgroundJob for PruneApiTokenUsages {
+    const JOB_NAME: &'static str = "prune_api_token_usages";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, ctx: Self::Context) -> anyhow::Result<()> {
+        let mut conn = ctx.deadpool.get().await?;
+
+        let cutoff = Utc::now() - Self::RETENTION;
+        let deleted = diesel::delete(api_token_usages::table)
+            .filter(api_token_usages::last_used_at.lt(cutoff))
+            .execute(&mut conn)
+            .await?;
+
+        info!("Deleted {deleted} API token usages older than {cutoff}");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+use crate::worker::Environment;
+use chrono::{NaiveDate, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::sql_query;
+use diesel::sql_types::Date;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+/// Roll up the `version_downloads` rows older than the given date into the
+/// weekly and monthly per-crate counts of the `crate_download_rollups` table.
+///
+/// Every date is only rolled up once, which is tracked in the
+/// `rolled_up_download_dates` table. Downloads that are added to an already
+/// rolled up date afterwards are not reflected in the rollups, so the cutoff
+/// date should be far enough in the past for the CDN logs of that date to
+/// have been processed.
+///
+/// The [`ArchiveVersionDownloads`](super::ArchiveVersionDownloads) job runs
+/// the rollup itself before deleting archived rows from the database, so
+/// this job is mostly useful to roll up more recent dates.
+#[derive(Serialize, Deserialize)]
+pub struct RollUpVersionDownloads {
+    before: NaiveDate,
+}
+
+impl RollUpVersionDownloads {
+    pub fn before(before: NaiveDate) -> Self {
+        Self { before }
+    }
+}
+
+impl Default for RollUpVersionDownloads {
+    fn default() -> Self {
+        Self::before(Utc::now().date_naive() - chrono::Duration::days(90))
+    }
+}
+
+impl BackgroundJob for RollUpVersionDownloads {
+    const JOB_NAME: &'static str = "roll_up_version_downloads";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        info!("Rolling up version downloads before {}…", self.before);
+        let count = roll_up(&mut conn, self.before).await?;
+        info!("Updated {count} download rollups");
+
+        Ok(())
+    }
+}
+
+/// Roll up all `version_downloads` rows older than the given date that have
+/// not been rolled up yet.
+///
+/// Returns the number of inserted or updated `crate_download_rollups` rows.
+pub(super) async fn roll_up(
+    conn: &mut AsyncPgConnection,
+    before: NaiveDate,
+) -> diesel::QueryResult<usize> {
+    sql_query(include_str!("roll_up_version_downloads.sql"))
+        .bind::<Date, _>(before)
+        .execute(conn)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::worker::jobs::test_util::{
+        all_rollups, create_crate, create_version, insert_downloads, parse_date,
+    };
+    use crates_io_test_db::TestDatabase;
+    use insta::assert_debug_snapshot;
+
+    #[tokio::test]
+    async fn test_roll_up() {
+        let test_db = TestDatabase::new();
+        let mut conn = test_db.async_connect().await;
+
+        let foo = create_crate(&mut conn, "foo").await;
+        let foo_v1 = create_version(&mut conn, foo, "1.0.0").await;
+        let foo_v2 = create_version(&mut conn, foo, "2.0.0").await;
+        let bar = create_crate(&mut conn, "bar").await;
+        let bar_v1 = create_version(&mut conn, bar, "1.0.0").await;
+
+        // 2024-01-29 is a Monday, 2024-02-04 the Sunday of the same week.
+        insert_downloads(&mut conn, foo_v1, parse_date("2024-01-28"), 1).await;
+        insert_downloads(&mut conn, foo_v1, parse_date("2024-01-29"), 10).await;
+        insert_downloads(&mut conn, foo_v2, parse_date("2024-01-29"), 20).await;
+        insert_downloads(&mut conn, foo_v2, parse_date("2024-02-04"), 100).await;
+        insert_downloads(&mut conn, bar_v1, parse_date("2024-02-05"), 1000).await;
+
+        let before = NaiveDate::from_ymd_opt(2024, 2, 5).unwrap();
+        roll_up(&mut conn, before).await.unwrap();
+        assert_debug_snapshot!(all_rollups(&mut conn).await, @r#"
+        [
+            "foo | month | 2024-01-01 | 31",
+            "foo | month | 2024-02-01 | 100",
+            "foo | week | 2024-01-22 | 1",
+            "foo | week | 2024-01-29 | 130",
+        ]
+        "#);
+
+        // Rolling up the same dates again does not count them twice, but
+        // newer dates are added to the existing periods.
+        insert_downloads(&mut conn, foo_v1, parse_date("2024-02-05"), 5).await;
+
+        let before = NaiveDate::from_ymd_opt(2024, 2, 6).unwrap();
+        roll_up(&mut conn, before).await.unwrap();
+        assert_debug_snapshot!(all_rollups(&mut conn).await, @r#"
+        [
+            "bar | month | 2024-02-01 | 1000",
+            "bar | week | 2024-02-05 | 1000",
+            "foo | month | 2024-01-01 | 31",
+            "foo | month | 2024-02-01 | 105",
+            "foo | week | 2024-01-22 | 1",
+            "foo | week | 2024-01-29 | 130",
+            "foo | week | 2024-02-05 | 5",
+        ]
+        "#);
+    }
+}
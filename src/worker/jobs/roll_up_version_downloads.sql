@@ -0,0 +1,23 @@
+WITH new_dates AS (
+    -- Claim all dates before the cutoff that have not been rolled up yet, so
+    -- that running the job again does not count them twice.
+    INSERT INTO rolled_up_download_dates (date)
+    SELECT DISTINCT date
+    FROM version_downloads
+    WHERE date < $1
+    ON CONFLICT DO NOTHING
+    RETURNING date
+)
+INSERT INTO crate_download_rollups (crate_id, granularity, period_start, downloads)
+SELECT
+    versions.crate_id,
+    granularities.granularity,
+    date_trunc(granularities.granularity, version_downloads.date::timestamp)::date,
+    SUM(version_downloads.downloads)
+FROM version_downloads
+INNER JOIN new_dates ON new_dates.date = version_downloads.date
+INNER JOIN versions ON versions.id = version_downloads.version_id
+CROSS JOIN (VALUES ('week'), ('month')) AS granularities (granularity)
+GROUP BY 1, 2, 3
+ON CONFLICT (crate_id, granularity, period_start)
+DO UPDATE SET downloads = crate_download_rollups.downloads + EXCLUDED.downloads;
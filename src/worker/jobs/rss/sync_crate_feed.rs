@@ -46,6 +46,7 @@ impl BackgroundJob for SyncCrateFeed {
         let mut conn = ctx.deadpool.get().await?;
 
         let version_updates = load_version_updates(name, &mut conn).await?;
+        let deprecation = load_deprecation(name, &mut conn).await?;
 
         let feed_id = FeedId::Crate { name };
 
@@ -56,9 +57,14 @@ impl BackgroundJob for SyncCrateFeed {
             ..Default::default()
         };
 
-        let items = version_updates
+        let deprecation_item = deprecation.map(|d| d.into_rss_item(name, domain));
+        let items = deprecation_item
             .into_iter()
-            .map(|u| u.into_rss_item(name, domain))
+            .chain(
+                version_updates
+                    .into_iter()
+                    .map(|u| u.into_rss_item(name, domain)),
+            )
             .collect();
 
         let namespaces = vec![("crates".to_string(), "https://crates.io/".to_string())];
@@ -123,6 +129,78 @@ async fn load_version_updates(
         .await
 }
 
+/// Load the deprecation state of the crate, if it is deprecated.
+async fn load_deprecation(
+    name: &str,
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<Option<Deprecation>> {
+    crates::table
+        .filter(crates::name.eq(name))
+        .filter(crates::deprecated_at.is_not_null())
+        .select(Deprecation::as_select())
+        .first(conn)
+        .await
+        .optional()
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = crates, check_for_backend(diesel::pg::Pg))]
+struct Deprecation {
+    #[diesel(select_expression = crates::columns::deprecated_at.assume_not_null())]
+    time: chrono::DateTime<Utc>,
+    #[diesel(select_expression = crates::columns::deprecation_reason)]
+    reason: Option<String>,
+    #[diesel(select_expression = crates::columns::deprecation_successor)]
+    successor: Option<String>,
+}
+
+impl Deprecation {
+    fn into_rss_item(self, name: &str, domain: &str) -> rss::Item {
+        let title = format!("Crate deprecated: {name}");
+        let link = format!("https://{domain}/crates/{name}");
+        let pub_date = self.time.to_rfc2822();
+
+        let guid = rss::Guid {
+            value: format!("{link}#deprecated-{}", self.time.timestamp()),
+            permalink: false,
+        };
+
+        let mut description = self.reason.unwrap_or_default();
+        if let Some(successor) = &self.successor {
+            description.push_str(&format!("\n\nUse the `{successor}` crate instead."));
+        }
+
+        let name_extension = rss::extension::Extension {
+            name: "crates:name".into(),
+            value: Some(name.to_string()),
+            ..Default::default()
+        };
+
+        let mut extensions = vec![("name".to_string(), vec![name_extension])];
+        if let Some(successor) = self.successor {
+            let successor_extension = rss::extension::Extension {
+                name: "crates:successor".into(),
+                value: Some(successor),
+                ..Default::default()
+            };
+            extensions.push(("successor".to_string(), vec![successor_extension]));
+        }
+        let extensions = extensions.into_iter().collect();
+        let extensions = vec![("crates".to_string(), extensions)];
+        let extensions = extensions.into_iter().collect();
+
+        rss::Item {
+            guid: Some(guid),
+            title: Some(title),
+            link: Some(link),
+            description: Some(description),
+            pub_date: Some(pub_date),
+            extensions,
+            ..Default::default()
+        }
+    }
+}
+
 #[derive(Debug, HasQuery)]
 #[diesel(base_query = versions::table.inner_join(crates::table))]
 struct VersionUpdate {
@@ -4,6 +4,7 @@ use crate::schema::{crate_owners, crates, emails, users, versions};
 use crate::worker::Environment;
 use anyhow::anyhow;
 use chrono::{DateTime, SecondsFormat, Utc};
+use crates_io_trustpub::gitlab::GITLAB_ISSUER_URL;
 use crates_io_worker::BackgroundJob;
 use diesel::prelude::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
@@ -102,11 +103,19 @@ impl BackgroundJob for SendPublishNotificationsJob {
                     Some(TrustpubData::GitLab {
                         project_path,
                         job_id,
+                        issuer,
                         ..
                     }),
-                ) => {
-                    &format!(" by GitLab CI/CD (https://gitlab.com/{project_path}/-/jobs/{job_id})")
-                }
+                ) => &format!(
+                    " by GitLab CI/CD ({}/{project_path}/-/jobs/{job_id})",
+                    issuer.as_deref().unwrap_or(GITLAB_ISSUER_URL)
+                ),
+                (
+                    _,
+                    Some(TrustpubData::Forgejo {
+                        repository, run_id, ..
+                    }),
+                ) => &format!(" by Forgejo Actions ({repository}, run {run_id})"),
                 _ => "",
             };
 
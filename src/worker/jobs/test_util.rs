@@ -0,0 +1,98 @@
+//! Database fixtures for the download related background job tests.
+
+use crate::schema::{crate_download_rollups, crates, version_downloads, versions};
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+pub fn parse_date(date: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(date, "%F").unwrap()
+}
+
+pub async fn create_crate(conn: &mut AsyncPgConnection, name: &str) -> i32 {
+    diesel::insert_into(crates::table)
+        .values(crates::name.eq(name))
+        .returning(crates::id)
+        .get_result(conn)
+        .await
+        .unwrap()
+}
+
+pub async fn create_version(conn: &mut AsyncPgConnection, crate_id: i32, num: &str) -> i32 {
+    diesel::insert_into(versions::table)
+        .values((
+            versions::crate_id.eq(crate_id),
+            versions::num.eq(num),
+            versions::num_no_build.eq(num),
+            versions::checksum.eq(""),
+            versions::crate_size.eq(0),
+        ))
+        .returning(versions::id)
+        .get_result(conn)
+        .await
+        .unwrap()
+}
+
+/// Creates a version like [`create_version`], but with the given
+/// `created_at` timestamp.
+pub async fn create_version_at(
+    conn: &mut AsyncPgConnection,
+    crate_id: i32,
+    num: &str,
+    created_at: DateTime<Utc>,
+) -> i32 {
+    let version_id = create_version(conn, crate_id, num).await;
+
+    diesel::update(versions::table.find(version_id))
+        .set(versions::created_at.eq(created_at))
+        .execute(conn)
+        .await
+        .unwrap();
+
+    version_id
+}
+
+pub async fn insert_downloads(
+    conn: &mut AsyncPgConnection,
+    version_id: i32,
+    date: NaiveDate,
+    downloads: i32,
+) {
+    diesel::insert_into(version_downloads::table)
+        .values((
+            version_downloads::version_id.eq(version_id),
+            version_downloads::date.eq(date),
+            version_downloads::downloads.eq(downloads),
+        ))
+        .execute(conn)
+        .await
+        .unwrap();
+}
+
+/// Loads all rows of the `crate_download_rollups` table, formatted as
+/// `name | granularity | period_start | downloads`.
+pub async fn all_rollups(conn: &mut AsyncPgConnection) -> Vec<String> {
+    let rollups: Vec<(String, String, NaiveDate, i64)> = crate_download_rollups::table
+        .inner_join(crates::table)
+        .select((
+            crates::name,
+            crate_download_rollups::granularity,
+            crate_download_rollups::period_start,
+            crate_download_rollups::downloads,
+        ))
+        .order((
+            crates::name,
+            crate_download_rollups::granularity,
+            crate_download_rollups::period_start,
+        ))
+        .load(conn)
+        .await
+        .unwrap();
+
+    rollups
+        .into_iter()
+        .map(|(name, granularity, period_start, downloads)| {
+            format!("{name} | {granularity} | {period_start} | {downloads}")
+        })
+        .collect()
+}
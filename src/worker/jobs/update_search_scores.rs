@@ -0,0 +1,53 @@
+use crate::worker::Environment;
+use crates_io_worker::BackgroundJob;
+use diesel::sql_query;
+use diesel::sql_types::Double;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+/// Weight of `log10(1 + recent downloads)` in the search score.
+const RECENT_DOWNLOADS_WEIGHT: f64 = 0.25;
+
+/// Weight of `log10(1 + reverse dependencies)` in the search score.
+const REVERSE_DEPENDENCIES_WEIGHT: f64 = 0.25;
+
+/// Weight of `1 / (1 + years since the last release)` in the search score.
+const RECENCY_WEIGHT: f64 = 1.0;
+
+/// Refreshes the `crate_search_scores` table, which is used to weight the
+/// text rank of the crates when sorting search results by relevance.
+///
+/// The score of a crate is `1` plus the weighted popularity, usage and
+/// recency terms above, so a crate that is popular, widely used and actively
+/// maintained ranks up to about five times higher than an abandoned crate
+/// with the same text rank.
+#[derive(Serialize, Deserialize)]
+pub struct UpdateSearchScores;
+
+impl BackgroundJob for UpdateSearchScores {
+    const JOB_NAME: &'static str = "update_search_scores";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        info!("Updating search scores…");
+        let count = update(&mut conn).await?;
+        info!("Updated search scores of {count} crates");
+
+        Ok(())
+    }
+}
+
+async fn update(conn: &mut AsyncPgConnection) -> diesel::QueryResult<usize> {
+    sql_query(include_str!("update_search_scores.sql"))
+        .bind::<Double, _>(RECENT_DOWNLOADS_WEIGHT)
+        .bind::<Double, _>(REVERSE_DEPENDENCIES_WEIGHT)
+        .bind::<Double, _>(RECENCY_WEIGHT)
+        .execute(conn)
+        .await
+}
@@ -0,0 +1,43 @@
+WITH reverse_dependencies AS (
+    -- Count the crates whose default version depends on each crate.
+    -- (if the default version is yanked, then the whole crate is yanked)
+    SELECT dependencies.crate_id, COUNT(DISTINCT default_versions.crate_id) AS count
+    FROM dependencies
+    INNER JOIN default_versions ON default_versions.version_id = dependencies.version_id
+    INNER JOIN versions ON versions.id = default_versions.version_id
+    WHERE NOT versions.yanked
+    GROUP BY dependencies.crate_id
+), last_releases AS (
+    SELECT crate_id, MAX(created_at) AS last_release_at
+    FROM versions
+    WHERE NOT yanked
+    GROUP BY crate_id
+), scores AS (
+    SELECT
+        crates.id AS crate_id,
+        COALESCE(reverse_dependencies.count, 0) AS reverse_dependencies,
+        COALESCE(recent_crate_downloads.downloads, 0) AS recent_downloads,
+        last_releases.last_release_at,
+        -- Age of the last release in years, or `NULL` if all versions are yanked
+        GREATEST(EXTRACT(EPOCH FROM NOW() - last_releases.last_release_at) / 31557600, 0) AS age
+    FROM crates
+    LEFT JOIN reverse_dependencies ON reverse_dependencies.crate_id = crates.id
+    LEFT JOIN recent_crate_downloads ON recent_crate_downloads.crate_id = crates.id
+    LEFT JOIN last_releases ON last_releases.crate_id = crates.id
+)
+INSERT INTO crate_search_scores (crate_id, reverse_dependencies, last_release_at, score)
+SELECT
+    crate_id,
+    reverse_dependencies,
+    last_release_at,
+    (
+        1
+        + $1 * LOG(1 + recent_downloads::double precision)
+        + $2 * LOG(1 + reverse_dependencies::double precision)
+        + $3 * COALESCE(1 / (1 + age::double precision), 0)
+    )::real
+FROM scores
+ON CONFLICT (crate_id) DO UPDATE
+SET reverse_dependencies = excluded.reverse_dependencies,
+    last_release_at = excluded.last_release_at,
+    score = excluded.score
@@ -0,0 +1,125 @@
+use crate::models::{ApiToken, Team, User};
+use crate::schema::{api_tokens, teams, users};
+use crate::worker::Environment;
+use chrono::{TimeDelta, Utc};
+use crates_io_worker::BackgroundJob;
+use diesel::dsl::now;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{info, instrument, warn};
+
+/// The maximum number of tokens to check per run.
+const MAX_ROWS: i64 = 1000;
+
+/// A background job that re-verifies the GitHub team membership of the
+/// creators of team tokens, and revokes the tokens of creators that are no
+/// longer maintainers of the team.
+#[derive(Default, Serialize, Deserialize, Debug)]
+pub struct VerifyTeamTokens;
+
+impl VerifyTeamTokens {
+    /// The duration after which the team membership of a token creator is
+    /// verified again.
+    pub const VERIFICATION_INTERVAL: TimeDelta = TimeDelta::hours(24);
+}
+
+impl BackgroundJob for VerifyTeamTokens {
+    const JOB_NAME: &'static str = "verify_team_tokens";
+    const DEDUPLICATED: bool = true;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip(env), err)]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        let mut conn = env.deadpool.get().await?;
+
+        let verified_before = Utc::now() - Self::VERIFICATION_INTERVAL;
+        let tokens = find_unverified_team_tokens(&mut conn, verified_before).await?;
+        info!(
+            "Verifying team membership for {} team tokens…",
+            tokens.len()
+        );
+
+        for (token, user, team) in tokens {
+            let encryption = &env.config.gh_token_encryption;
+            let gh_token = match encryption.decrypt(&user.gh_encrypted_token) {
+                Ok(gh_token) => gh_token,
+                Err(error) => {
+                    warn!(
+                        token.id,
+                        "Failed to decrypt GitHub token of team token creator: {error}"
+                    );
+                    continue;
+                }
+            };
+
+            let membership = env
+                .github
+                .team_membership(team.org_id, team.github_id, &user.gh_login, &gh_token)
+                .await;
+
+            // Errors are most likely temporary (e.g. rate limits), so the
+            // token is verified again on the next run instead of being revoked.
+            // Only maintainers can create team tokens, so the same check is
+            // applied when the membership is verified again.
+            let is_maintainer = match membership {
+                Ok(membership) => membership.is_some_and(|m| m.is_active_maintainer()),
+                Err(error) => {
+                    warn!(token.id, "Failed to verify team membership: {error}");
+                    continue;
+                }
+            };
+
+            let query = diesel::update(api_tokens::table.find(token.id));
+            if is_maintainer {
+                query
+                    .set(api_tokens::team_membership_verified_at.eq(now))
+                    .execute(&mut conn)
+                    .await?;
+            } else {
+                info!(
+                    token.id,
+                    "Revoking team token of `{}`, who is no longer a maintainer of `{}`",
+                    user.gh_login,
+                    team.login
+                );
+
+                query
+                    .set(api_tokens::revoked.eq(true))
+                    .execute(&mut conn)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds active team tokens whose team membership was not verified since
+/// `verified_before`, together with their creators and teams.
+async fn find_unverified_team_tokens(
+    conn: &mut AsyncPgConnection,
+    verified_before: chrono::DateTime<Utc>,
+) -> QueryResult<Vec<(ApiToken, User, Team)>> {
+    api_tokens::table
+        .inner_join(users::table)
+        .inner_join(teams::table)
+        .filter(api_tokens::revoked.eq(false))
+        .filter(
+            api_tokens::expired_at
+                .is_null()
+                .or(api_tokens::expired_at.gt(now)),
+        )
+        .filter(
+            api_tokens::team_membership_verified_at
+                .is_null()
+                .or(api_tokens::team_membership_verified_at.lt(verified_before)),
+        )
+        .select((ApiToken::as_select(), User::as_select(), Team::as_select()))
+        .order(api_tokens::team_membership_verified_at.asc().nulls_first())
+        .limit(MAX_ROWS)
+        .load(conn)
+        .await
+}
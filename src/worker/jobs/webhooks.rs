@@ -0,0 +1,441 @@
+use crate::models::{CrateWebhook, CrateWebhookDelivery, NewCrateWebhookDelivery, WebhookEvent};
+use crate::schema::{crate_webhook_deliveries, crate_webhooks};
+use crate::worker::Environment;
+use anyhow::anyhow;
+use chrono::Utc;
+use crates_io_worker::{BackgroundJob, EnqueueError};
+use diesel::dsl::{exists, not};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::Sha256;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+use url::{Host, Url};
+
+/// The header that contains the event type of the delivery.
+pub const EVENT_HEADER: &str = "X-Crates-Io-Event";
+
+/// The header that contains the ID of the delivery.
+pub const DELIVERY_HEADER: &str = "X-Crates-Io-Delivery";
+
+/// The header that contains the hex-encoded HMAC-SHA256 signature of the
+/// request body, prefixed with `sha256=`.
+pub const SIGNATURE_HEADER: &str = "X-Crates-Io-Signature-256";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Records a delivery of the given event for all enabled webhooks of the
+/// crate that are subscribed to it and enqueues the corresponding
+/// [`DeliverWebhook`] jobs.
+///
+/// The `data` object is merged into the payload, next to the `event`,
+/// `crate` and `timestamp` fields.
+pub async fn enqueue_deliveries(
+    conn: &mut AsyncPgConnection,
+    crate_id: i32,
+    crate_name: &str,
+    event: WebhookEvent,
+    data: Value,
+) -> Result<(), EnqueueError> {
+    let webhooks = CrateWebhook::query()
+        .filter(crate_webhooks::crate_id.eq(crate_id))
+        .filter(crate_webhooks::disabled_at.is_null())
+        .load(conn)
+        .await?;
+
+    let webhooks = webhooks
+        .into_iter()
+        .filter(|webhook| webhook.events.contains(&event));
+
+    let mut payload = json!({
+        "event": event,
+        "crate": crate_name,
+        "timestamp": Utc::now(),
+    });
+    if let (Some(payload), Value::Object(data)) = (payload.as_object_mut(), data) {
+        payload.extend(data);
+    }
+
+    for webhook in webhooks {
+        let delivery = NewCrateWebhookDelivery {
+            webhook_id: webhook.id,
+            event,
+            payload: &payload,
+        };
+
+        let delivery = delivery.insert(conn).await?;
+        DeliverWebhook::new(delivery.id).enqueue(conn).await?;
+    }
+
+    Ok(())
+}
+
+/// Records and enqueues the deliveries of the [`WebhookEvent::Delete`] event
+/// for a crate that is about to be deleted.
+///
+/// Webhooks that will not receive the event are deleted right away. The
+/// remaining webhooks are kept (with `crate_id` set to `NULL` by the
+/// database) until the event has been delivered.
+pub async fn enqueue_deletion_deliveries(
+    conn: &mut AsyncPgConnection,
+    crate_id: i32,
+    crate_name: &str,
+    data: Value,
+) -> Result<(), EnqueueError> {
+    enqueue_deliveries(conn, crate_id, crate_name, WebhookEvent::Delete, data).await?;
+
+    let pending_deletion_event = crate_webhook_deliveries::table
+        .filter(crate_webhook_deliveries::webhook_id.eq(crate_webhooks::id))
+        .filter(crate_webhook_deliveries::event.eq(WebhookEvent::Delete));
+
+    diesel::delete(crate_webhooks::table)
+        .filter(crate_webhooks::crate_id.eq(crate_id))
+        .filter(not(exists(pending_deletion_event)))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Computes the value of the [`SIGNATURE_HEADER`] for the given request body.
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take keys of any size");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// A background job that sends a signed event payload to a webhook.
+///
+/// Failed deliveries are retried by the background worker until they
+/// succeed or [`DeliverWebhook::MAX_ATTEMPTS`] is reached. Webhooks that
+/// failed [`DeliverWebhook::MAX_CONSECUTIVE_FAILURES`] times in a row are
+/// disabled.
+#[derive(Serialize, Deserialize)]
+pub struct DeliverWebhook {
+    delivery_id: i64,
+}
+
+impl DeliverWebhook {
+    /// The maximum number of delivery attempts for a single event.
+    pub const MAX_ATTEMPTS: i32 = 5;
+
+    /// The number of failed delivery attempts in a row after which the
+    /// webhook is disabled.
+    pub const MAX_CONSECUTIVE_FAILURES: i32 = 20;
+
+    pub fn new(delivery_id: i64) -> Self {
+        Self { delivery_id }
+    }
+}
+
+impl BackgroundJob for DeliverWebhook {
+    const JOB_NAME: &'static str = "deliver_webhook";
+
+    type Context = Arc<Environment>;
+
+    async fn run(&self, ctx: Self::Context) -> anyhow::Result<()> {
+        let delivery_id = self.delivery_id;
+
+        let mut conn = ctx.deadpool.get().await?;
+
+        let Some((delivery, webhook)) = crate_webhook_deliveries::table
+            .inner_join(crate_webhooks::table)
+            .filter(crate_webhook_deliveries::id.eq(delivery_id))
+            .select((CrateWebhookDelivery::as_select(), CrateWebhook::as_select()))
+            .first(&mut conn)
+            .await
+            .optional()?
+        else {
+            warn!("Skipping webhook delivery {delivery_id}: delivery not found");
+            return Ok(());
+        };
+
+        if delivery.delivered_at.is_some() {
+            info!("Skipping webhook delivery {delivery_id}: already delivered");
+            return Ok(());
+        }
+
+        if webhook.disabled_at.is_some() {
+            info!("Skipping webhook delivery {delivery_id}: webhook is disabled");
+            finish(&mut conn, &webhook, &delivery).await?;
+            return Ok(());
+        }
+
+        info!(
+            "Delivering `{}` event to webhook {}…",
+            delivery.event.as_str(),
+            webhook.id
+        );
+
+        let allow_private_addresses = ctx.config.webhooks_allow_private_addresses;
+        let result = send(&webhook, &delivery, allow_private_addresses).await;
+
+        let now = Utc::now();
+        let attempts = delivery.attempts + 1;
+        let (response_status, error) = match &result {
+            Ok(status) => (Some(*status), None),
+            Err(Failure { status, error }) => (*status, Some(error.clone())),
+        };
+
+        diesel::update(crate_webhook_deliveries::table.find(delivery_id))
+            .set((
+                crate_webhook_deliveries::attempts.eq(attempts),
+                crate_webhook_deliveries::last_attempt_at.eq(now),
+                crate_webhook_deliveries::response_status.eq(response_status),
+                crate_webhook_deliveries::error.eq(&error),
+                crate_webhook_deliveries::delivered_at.eq(result.is_ok().then_some(now)),
+            ))
+            .execute(&mut conn)
+            .await?;
+
+        let Err(Failure { error, .. }) = result else {
+            diesel::update(crate_webhooks::table.find(webhook.id))
+                .set(crate_webhooks::consecutive_failures.eq(0))
+                .execute(&mut conn)
+                .await?;
+
+            finish(&mut conn, &webhook, &delivery).await?;
+            return Ok(());
+        };
+
+        let consecutive_failures = diesel::update(crate_webhooks::table.find(webhook.id))
+            .set(crate_webhooks::consecutive_failures.eq(crate_webhooks::consecutive_failures + 1))
+            .returning(crate_webhooks::consecutive_failures)
+            .get_result::<i32>(&mut conn)
+            .await?;
+
+        if consecutive_failures >= Self::MAX_CONSECUTIVE_FAILURES {
+            warn!(
+                "Disabling webhook {} after {consecutive_failures} consecutive failures",
+                webhook.id
+            );
+
+            diesel::update(crate_webhooks::table.find(webhook.id))
+                .set(crate_webhooks::disabled_at.eq(now))
+                .execute(&mut conn)
+                .await?;
+
+            finish(&mut conn, &webhook, &delivery).await?;
+            return Ok(());
+        }
+
+        if attempts >= Self::MAX_ATTEMPTS {
+            warn!("Giving up on webhook delivery {delivery_id} after {attempts} attempts: {error}");
+
+            finish(&mut conn, &webhook, &delivery).await?;
+            return Ok(());
+        }
+
+        Err(anyhow!(
+            "Failed to deliver webhook delivery {delivery_id}: {error}"
+        ))
+    }
+}
+
+struct Failure {
+    status: Option<i32>,
+    /// A coarse description of the failure that is shown to the owners of
+    /// the crate. The details are only logged, since they might leak
+    /// information about the network of the delivering server.
+    error: String,
+}
+
+impl Failure {
+    fn new(error: &str) -> Self {
+        let error = error.to_string();
+        Self {
+            status: None,
+            error,
+        }
+    }
+
+    fn from_reqwest(error: reqwest::Error) -> Self {
+        info!("Webhook request failed: {error}");
+
+        let category = if error.is_timeout() {
+            "request timed out"
+        } else if error.is_connect() {
+            "connection failed"
+        } else {
+            "request failed"
+        };
+
+        Self::new(category)
+    }
+}
+
+async fn send(
+    webhook: &CrateWebhook,
+    delivery: &CrateWebhookDelivery,
+    allow_private_addresses: bool,
+) -> Result<i32, Failure> {
+    let url = Url::parse(&webhook.url).map_err(|_| Failure::new("invalid URL"))?;
+
+    let body = serde_json::to_vec(&delivery.payload).map_err(|error| {
+        warn!("Failed to serialize webhook payload: {error}");
+        Failure::new("request failed")
+    })?;
+
+    let mut client = reqwest::Client::builder()
+        .user_agent(crates_io_version::user_agent())
+        .redirect(reqwest::redirect::Policy::none())
+        .no_proxy()
+        .timeout(REQUEST_TIMEOUT);
+
+    // The addresses are resolved and checked only once, and the client is
+    // pinned to them, so that a DNS record that changes between the check and
+    // the request can't be used to reach internal services.
+    let addrs = resolve(&url).await?;
+    if !allow_private_addresses && !addrs.iter().all(|addr| is_public_address(addr.ip())) {
+        info!("Rejecting webhook delivery to non-public address: {addrs:?}");
+        return Err(Failure::new("address not allowed"));
+    }
+
+    if let Some(Host::Domain(domain)) = url.host() {
+        client = client.resolve_to_addrs(domain, &addrs);
+    }
+
+    let client = client.build().map_err(Failure::from_reqwest)?;
+
+    let response = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, delivery.event.as_str())
+        .header(DELIVERY_HEADER, delivery.id.to_string())
+        .header(SIGNATURE_HEADER, signature(&webhook.secret, &body))
+        .body(body)
+        .send()
+        .await
+        .map_err(Failure::from_reqwest)?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Failure {
+            status: Some(status.as_u16().into()),
+            error: format!("unexpected response status: {status}"),
+        });
+    }
+
+    Ok(status.as_u16().into())
+}
+
+/// Resolves the socket addresses of the host of the webhook URL.
+async fn resolve(url: &Url) -> Result<Vec<SocketAddr>, Failure> {
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| Failure::new("invalid URL"))?;
+
+    let addrs = match url.host() {
+        Some(Host::Ipv4(ip)) => vec![SocketAddr::new(ip.into(), port)],
+        Some(Host::Ipv6(ip)) => vec![SocketAddr::new(ip.into(), port)],
+        Some(Host::Domain(domain)) => match tokio::net::lookup_host((domain, port)).await {
+            Ok(addrs) => addrs.collect(),
+            Err(error) => {
+                info!("Failed to resolve webhook host `{domain}`: {error}");
+                return Err(Failure::new("DNS resolution failed"));
+            }
+        },
+        None => return Err(Failure::new("invalid URL")),
+    };
+
+    if addrs.is_empty() {
+        return Err(Failure::new("DNS resolution failed"));
+    }
+
+    Ok(addrs)
+}
+
+/// Checks whether the IP address is publicly routable, as opposed to e.g.
+/// loopback, private, link-local or shared (CGNAT) addresses.
+fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ipv4(ip),
+            None => is_public_ipv6(ip),
+        },
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+
+    // `Ipv4Addr::is_shared()` is not stable yet
+    let is_shared = a == 100 && (b & 0b1100_0000) == 64;
+    // `0.0.0.0/8` is reserved for "this network"
+    let is_this_network = a == 0;
+
+    !(ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || is_shared
+        || is_this_network)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        || ip.is_unique_local()
+        || ip.is_unicast_link_local())
+}
+
+/// Cleans up after the final delivery attempt of an event.
+///
+/// Webhooks of deleted crates are only kept around until the deletion event
+/// has been delivered.
+async fn finish(
+    conn: &mut AsyncPgConnection,
+    webhook: &CrateWebhook,
+    delivery: &CrateWebhookDelivery,
+) -> QueryResult<()> {
+    if webhook.crate_id.is_none() && delivery.event == WebhookEvent::Delete {
+        diesel::delete(crate_webhooks::table.find(webhook.id))
+            .execute(conn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_public_address() {
+        let is_public = |ip: &str| is_public_address(ip.parse().unwrap());
+
+        assert!(is_public("1.1.1.1"));
+        assert!(is_public("140.82.112.3"));
+        assert!(is_public("100.128.0.1"));
+        assert!(is_public("2606:4700:4700::1111"));
+
+        assert!(!is_public("127.0.0.1"));
+        assert!(!is_public("10.1.2.3"));
+        assert!(!is_public("172.16.0.1"));
+        assert!(!is_public("192.168.1.1"));
+        assert!(!is_public("169.254.169.254"));
+        assert!(!is_public("100.64.0.1"));
+        assert!(!is_public("100.127.255.255"));
+        assert!(!is_public("0.0.0.0"));
+        assert!(!is_public("255.255.255.255"));
+        assert!(!is_public("::1"));
+        assert!(!is_public("::"));
+        assert!(!is_public("fc00::1"));
+        assert!(!is_public("fd12:3456::1"));
+        assert!(!is_public("fe80::1"));
+        assert!(!is_public("::ffff:127.0.0.1"));
+        assert!(!is_public("::ffff:10.0.0.1"));
+    }
+}
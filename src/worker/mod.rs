@@ -19,35 +19,59 @@ pub trait RunnerExt {
 
 impl RunnerExt for Runner<Arc<Environment>> {
     fn register_crates_io_job_types(self) -> Self {
-        self.register_job_type::<jobs::AnalyzeCrateFile>()
+        let git_index_enabled = self.context().config.git_index_enabled;
+
+        let runner = self
+            .register_job_type::<jobs::AnalyzeCrateFile>()
             .register_job_type::<jobs::ArchiveVersionDownloads>()
-            .register_job_type::<jobs::BulkSyncToGitIndex>()
+            .register_job_type::<jobs::BackfillDownloadRollups>()
+            .register_job_type::<jobs::BackfillIndexTargets>()
             .register_job_type::<jobs::CheckTyposquat>()
             .register_job_type::<jobs::CleanProcessedLogFiles>()
             .register_job_type::<jobs::DailyDbMaintenance>()
+            .register_job_type::<jobs::DeliverWebhook>()
             .register_job_type::<jobs::DeleteCrateFromStorage>()
+            .register_job_type::<jobs::DeleteExpiredStagedVersions>()
+            .register_job_type::<jobs::DetectDownloadAnomalies>()
             .register_job_type::<jobs::DocsRsQueueRebuild>()
             .register_job_type::<jobs::DumpDb>()
             .register_job_type::<jobs::GenerateOgImage>()
             .register_job_type::<jobs::IndexVersionDownloadsArchive>()
             .register_job_type::<jobs::InvalidateCdns>()
-            .register_job_type::<jobs::NormalizeIndex>()
             .register_job_type::<jobs::ProcessCdnLog>()
             .register_job_type::<jobs::ProcessCdnLogQueue>()
             .register_job_type::<jobs::ProcessCloudfrontInvalidationQueue>()
+            .register_job_type::<jobs::PruneApiTokenUsages>()
+            .register_job_type::<jobs::PruneDownloadBreakdowns>()
+            .register_job_type::<jobs::PublishIndexChanges>()
             .register_job_type::<jobs::RenderAndUploadReadme>()
-            .register_job_type::<jobs::SquashIndex>()
+            .register_job_type::<jobs::RollUpVersionDownloads>()
+            .register_job_type::<jobs::SignIndexMetadata>()
             .register_job_type::<jobs::SyncAdmins>()
-            .register_job_type::<jobs::SyncToGitIndex>()
             .register_job_type::<jobs::SyncToSparseIndex>()
             .register_job_type::<jobs::UpdateDownloads>()
             .register_job_type::<jobs::UpdateDefaultVersion>()
+            .register_job_type::<jobs::UpdateSearchScores>()
+            .register_job_type::<jobs::VerifyIndex>()
+            .register_job_type::<jobs::VerifyTeamTokens>()
             .register_job_type::<jobs::SendTokenExpiryNotifications>()
             .register_job_type::<jobs::SendPublishNotificationsJob>()
             .register_job_type::<jobs::rss::SyncCrateFeed>()
             .register_job_type::<jobs::rss::SyncCratesFeed>()
             .register_job_type::<jobs::rss::SyncUpdatesFeed>()
             .register_job_type::<jobs::trustpub::DeleteExpiredJtis>()
-            .register_job_type::<jobs::trustpub::DeleteExpiredTokens>()
+            .register_job_type::<jobs::trustpub::DeleteExpiredTokens>();
+
+        // Installations without a git index don't register the git index
+        // jobs, so that they can't accidentally try to clone the repository.
+        if git_index_enabled {
+            runner
+                .register_job_type::<jobs::BulkSyncToGitIndex>()
+                .register_job_type::<jobs::NormalizeIndex>()
+                .register_job_type::<jobs::SquashIndex>()
+                .register_job_type::<jobs::SyncToGitIndex>()
+        } else {
+            runner
+        }
     }
 }
@@ -347,28 +347,28 @@ module('/settings/tokens/new', function (hooks) {
     assert.dom('[data-test-title]').hasText('Token not found');
   });
 
-  test('trusted-publishing scope', async function (assert) {
+  test('manage-trustpub scope', async function (assert) {
     await prepare(this);
 
     await visit('/settings/tokens/new');
     assert.strictEqual(currentURL(), '/settings/tokens/new');
 
-    await fillIn('[data-test-name]', 'trusted-publishing-token');
+    await fillIn('[data-test-name]', 'manage-trustpub-token');
     await select('[data-test-expiry]', 'none');
-    await click('[data-test-scope="trusted-publishing"]');
+    await click('[data-test-scope="manage-trustpub"]');
     await click('[data-test-generate]');
 
-    let token = this.db.apiToken.findFirst(q => q.where({ name: 'trusted-publishing-token' }));
+    let token = this.db.apiToken.findFirst(q => q.where({ name: 'manage-trustpub-token' }));
     assert.ok(Boolean(token), 'API token has been created in the backend database');
-    assert.strictEqual(token.name, 'trusted-publishing-token');
+    assert.strictEqual(token.name, 'manage-trustpub-token');
     assert.strictEqual(token.expiredAt, null);
     assert.strictEqual(token.crateScopes, null);
-    assert.deepEqual(token.endpointScopes, ['trusted-publishing']);
+    assert.deepEqual(token.endpointScopes, ['manage-trustpub']);
 
     assert.strictEqual(currentURL(), '/settings/tokens');
-    assert.dom('[data-test-api-token="1"] [data-test-name]').hasText('trusted-publishing-token');
+    assert.dom('[data-test-api-token="1"] [data-test-name]').hasText('manage-trustpub-token');
     assert.dom('[data-test-api-token="1"] [data-test-token]').hasText(token.token);
-    assert.dom('[data-test-api-token="1"] [data-test-endpoint-scopes]').hasText('Scopes: trusted-publishing');
+    assert.dom('[data-test-api-token="1"] [data-test-endpoint-scopes]').hasText('Scopes: manage-trustpub');
     assert.dom('[data-test-api-token="1"] [data-test-crate-scopes]').doesNotExist();
     assert.dom('[data-test-api-token="1"] [data-test-expired-at]').doesNotExist();
   });